@@ -0,0 +1,240 @@
+//! `roc deps`: the packages an app depends on (transitively), for license audits and
+//! reproducibility reviews.
+use clap::ArgMatches;
+use roc_build::program::{handle_loading_problem, DEFAULT_ROC_FILENAME};
+use roc_load::{ExecutionMode, LoadConfig, LoadedModule, PackageDependency, Threading};
+use roc_module::symbol::ModuleId;
+use roc_packaging::cache::{self, RocCacheDir};
+use roc_packaging::https::PackageMetadata;
+use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+use roc_target::TargetInfo;
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{FLAG_JSON, FLAG_OUTPUT, ROC_FILE};
+
+/// A package along with the packages its own header lists.
+struct Node<'a> {
+    dependency: &'a PackageDependency,
+    /// The BLAKE3 hash embedded in the URL, if the package came from one
+    content_hash: Option<&'a str>,
+    /// Where the package's files are on disk - in the cache dir, if the package came from a URL
+    location: PathBuf,
+    imported_by: Vec<&'a Path>,
+    children: Vec<Node<'a>>,
+}
+
+pub fn deps(matches: &ArgMatches) -> io::Result<i32> {
+    let arena = bumpalo::Bump::new();
+    let path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
+
+    if !path.exists() {
+        eprintln!(
+            "\nThis file was not found: {}\n\n(`roc deps` uses {} in the current directory if no .roc file is given.)\n",
+            path.display(),
+            DEFAULT_ROC_FILENAME
+        );
+
+        return Ok(1);
+    }
+
+    let cache_dir = cache::roc_cache_dir();
+    let load_config = LoadConfig {
+        // Only used for generating errors; we don't do code generation.
+        target_info: TargetInfo::default_x86_64(),
+        render: RenderTarget::ColorTerminal,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::AllAvailable,
+        exec_mode: ExecutionMode::Check,
+    };
+
+    let loaded = match roc_load::load_and_typecheck(
+        &arena,
+        path.to_path_buf(),
+        RocCacheDir::Persistent(cache_dir.as_path()),
+        load_config,
+    ) {
+        Ok(loaded) => loaded,
+        Err(problem) => return handle_loading_problem(problem, RenderTarget::ColorTerminal),
+    };
+
+    let mut visited = Vec::new();
+    let roots = children_of(&loaded, loaded.module_id, &cache_dir, &mut visited);
+    let rendered = if matches.is_present(FLAG_JSON) {
+        render_json(&roots, &loaded)
+    } else {
+        render_tree(&roots, &loaded)
+    };
+
+    match matches.value_of_os(FLAG_OUTPUT) {
+        Some(output_path) => {
+            if let Err(err) = std::fs::write(output_path, rendered) {
+                eprintln!(
+                    "\nCould not write the dependencies to {}: {}\n",
+                    Path::new(output_path).display(),
+                    err
+                );
+
+                return Ok(1);
+            }
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(0)
+}
+
+/// The packages listed in the given module's header, each with the packages listed in its
+/// own root module's header, and so on.
+fn children_of<'a>(
+    loaded: &'a LoadedModule,
+    module_id: ModuleId,
+    cache_dir: &Path,
+    visited: &mut Vec<&'a Path>,
+) -> Vec<Node<'a>> {
+    loaded
+        .package_dependencies
+        .iter()
+        .filter(|dependency| dependency.declared_in == module_id)
+        .map(|dependency| {
+            let opt_metadata = if dependency.source.starts_with("https://") {
+                PackageMetadata::try_from(dependency.source.as_str()).ok()
+            } else {
+                None
+            };
+            let location = match &opt_metadata {
+                Some(metadata) => cache_dir
+                    .join(metadata.cache_subdir)
+                    .join(metadata.content_hash),
+                None => dependency
+                    .root_module
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+            };
+            let imported_by = dependency
+                .imported_by
+                .iter()
+                .filter_map(|id| Some(loaded.sources.get(id)?.0.as_path()))
+                .collect();
+
+            // Several modules can list the same package, but we only need to show what
+            // that package depends on the first time it comes up.
+            let root_module = dependency.root_module.as_path();
+            let children = if visited.contains(&root_module) {
+                Vec::new()
+            } else {
+                visited.push(root_module);
+
+                match module_id_for_path(loaded, root_module) {
+                    Some(package_module_id) => {
+                        children_of(loaded, package_module_id, cache_dir, visited)
+                    }
+                    None => Vec::new(),
+                }
+            };
+
+            Node {
+                dependency,
+                content_hash: opt_metadata.map(|metadata| metadata.content_hash),
+                location,
+                imported_by,
+                children,
+            }
+        })
+        .collect()
+}
+
+fn module_id_for_path(loaded: &LoadedModule, path: &Path) -> Option<ModuleId> {
+    loaded
+        .sources
+        .iter()
+        .find(|(_, (module_path, _))| module_path == path)
+        .map(|(module_id, _)| *module_id)
+}
+
+fn module_path(loaded: &LoadedModule, module_id: ModuleId) -> String {
+    match loaded.sources.get(&module_id) {
+        Some((path, _)) => path.display().to_string(),
+        None => loaded.interns.module_name(module_id).to_string(),
+    }
+}
+
+fn render_tree(roots: &[Node], loaded: &LoadedModule) -> String {
+    let mut buf = String::new();
+
+    let root_module = module_path(loaded, loaded.module_id);
+
+    if roots.is_empty() {
+        writeln!(buf, "{root_module} does not depend on any packages.").unwrap();
+    } else {
+        writeln!(buf, "{root_module}").unwrap();
+        render_nodes(&mut buf, roots, 1);
+    }
+
+    buf
+}
+
+fn render_nodes(buf: &mut String, nodes: &[Node], depth: usize) {
+    let indent = "    ".repeat(depth);
+
+    for node in nodes {
+        let dependency = node.dependency;
+
+        writeln!(
+            buf,
+            "{indent}{}: {}",
+            dependency.shorthand, dependency.source
+        )
+        .unwrap();
+
+        if let Some(content_hash) = node.content_hash {
+            writeln!(buf, "{indent}    hash: {content_hash}").unwrap();
+        }
+
+        writeln!(buf, "{indent}    location: {}", node.location.display()).unwrap();
+
+        if !node.imported_by.is_empty() {
+            let imported_by: Vec<_> = node
+                .imported_by
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+
+            writeln!(buf, "{indent}    imported by: {}", imported_by.join(", ")).unwrap();
+        }
+
+        render_nodes(buf, &node.children, depth + 1);
+    }
+}
+
+fn render_json(roots: &[Node], loaded: &LoadedModule) -> String {
+    fn node_json(node: &Node) -> serde_json::Value {
+        let dependency = node.dependency;
+
+        serde_json::json!({
+            "shorthand": dependency.shorthand,
+            "source": dependency.source,
+            "hash": node.content_hash,
+            "location": node.location.to_string_lossy(),
+            "root_module": dependency.root_module.to_string_lossy(),
+            "imported_by": node
+                .imported_by
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>(),
+            "packages": node.children.iter().map(node_json).collect::<Vec<_>>(),
+        })
+    }
+
+    let json = serde_json::json!({
+        "module": module_path(loaded, loaded.module_id),
+        "packages": roots.iter().map(node_json).collect::<Vec<_>>(),
+    });
+
+    let mut rendered = serde_json::to_string_pretty(&json).unwrap();
+    rendered.push('\n');
+
+    rendered
+}
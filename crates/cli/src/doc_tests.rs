@@ -0,0 +1,148 @@
+//! `roc test --doc`: checking the ```roc code blocks in `##` doc comments.
+use bumpalo::Bump;
+use roc_build::program::{
+    handle_loading_problem, report_problems_monomorphized, report_problems_typechecked,
+};
+use roc_docs::doc_tests::DocTest;
+use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError, Threading};
+use roc_mono::ir::OptLevel;
+use roc_packaging::cache::{self, RocCacheDir};
+use roc_repl_expect::run::PropertyConfig;
+use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+use roc_target::TargetInfo;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+use target_lexicon::Triple;
+
+use crate::run_expects;
+use crate::toplevel_expects::{self, ExpectFilter};
+
+/// Each code block becomes its own module with this name, next to the root module.
+const DOC_TEST_MODULE_NAME: &str = "DocTest";
+
+pub fn test_docs(
+    path: &Path,
+    target: &Triple,
+    opt_level: OptLevel,
+    threading: Threading,
+    verbose: bool,
+) -> io::Result<i32> {
+    let start_time = Instant::now();
+    let loaded = roc_docs::load_module_for_docs(path.to_path_buf());
+    let doc_tests = roc_docs::doc_tests::doc_tests(&loaded);
+
+    if doc_tests.is_empty() {
+        println!("No ```roc code blocks were found in the docs.");
+
+        // Like `roc test` without any `expect`s, this is probably a mistake.
+        return Ok(2);
+    }
+
+    // Code blocks can import the package's modules, which are relative to the root module.
+    let src_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut failed = 0;
+
+    for doc_test in doc_tests.iter() {
+        let test_start = Instant::now();
+        let passed = run_doc_test(doc_test, src_dir, target, opt_level, threading);
+
+        if !passed {
+            failed += 1;
+
+            println!(
+                "\x1B[31mThe ```roc code block in the docs for {} failed.\x1B[39m\n",
+                doc_test.name()
+            );
+        }
+
+        if verbose {
+            let (color, status) = match passed {
+                true => (32, "PASS"),  // green
+                false => (31, "FAIL"), // red
+            };
+
+            println!(
+                "\x1B[{color}m{status}\x1B[39m {:>6} ms  {}",
+                test_start.elapsed().as_millis(),
+                doc_test.name()
+            );
+        }
+    }
+
+    let passed = doc_tests.len() - failed;
+    let failed_color = if failed == 0 {
+        32 // green
+    } else {
+        31 // red
+    };
+
+    println!(
+        "\n\x1B[{failed_color}m{failed}\x1B[39m failed and \x1B[32m{passed}\x1B[39m passed in {} ms.\n",
+        start_time.elapsed().as_millis(),
+    );
+
+    Ok((failed > 0) as i32)
+}
+
+/// A code block passes if it compiles without errors and all of its `expect`s pass.
+/// Any problems get reported along the way.
+fn run_doc_test(
+    doc_test: &DocTest,
+    src_dir: &Path,
+    target: &Triple,
+    opt_level: OptLevel,
+    threading: Threading,
+) -> bool {
+    let arena = Bump::new();
+    let arena = &arena;
+    let src = arena.alloc_str(&doc_test.module_source(DOC_TEST_MODULE_NAME));
+    let filename = src_dir.join(format!("{DOC_TEST_MODULE_NAME}.roc"));
+    let load_config = LoadConfig {
+        target_info: TargetInfo::from(target),
+        render: RenderTarget::ColorTerminal,
+        palette: DEFAULT_PALETTE,
+        threading,
+        exec_mode: ExecutionMode::Test,
+    };
+
+    let load_result = roc_load::load_and_monomorphize_from_str(
+        arena,
+        filename,
+        src,
+        src_dir.to_path_buf(),
+        RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+        load_config,
+    );
+
+    let mut loaded = match load_result {
+        Ok(loaded) => loaded,
+        Err(LoadMonomorphizedError::LoadingProblem(problem)) => {
+            let _ = handle_loading_problem(problem, RenderTarget::ColorTerminal);
+
+            return false;
+        }
+        Err(LoadMonomorphizedError::ErrorModule(mut module)) => {
+            report_problems_typechecked(&mut module, RenderTarget::ColorTerminal);
+
+            return false;
+        }
+    };
+
+    if report_problems_monomorphized(&mut loaded, RenderTarget::ColorTerminal).errors > 0 {
+        return false;
+    }
+
+    // Only the code block's own `expect`s, not the ones in the modules it imports.
+    let filter = ExpectFilter::only_module(DOC_TEST_MODULE_NAME);
+
+    if toplevel_expects::select_expects(&mut loaded, &filter).is_empty() {
+        // It compiled, and that's all there is to check.
+        return true;
+    }
+
+    // The examples in the docs always use the default property settings.
+    run_expects(arena, target, loaded, opt_level, PropertyConfig::default())
+        .iter()
+        .all(|result| result.passed)
+}
@@ -1,4 +1,5 @@
 use std::ffi::OsStr;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use crate::FormatMode;
@@ -8,11 +9,13 @@ use roc_fmt::def::fmt_defs;
 use roc_fmt::module::fmt_module;
 use roc_fmt::spaces::RemoveSpaces;
 use roc_fmt::{Ast, Buf};
+use roc_parse::ast::Defs;
 use roc_parse::{
     module::{self, module_defs},
     parser::{Parser, SyntaxError},
     state::State,
 };
+use roc_region::all::Region;
 
 fn flatten_directories(files: std::vec::Vec<PathBuf>) -> std::vec::Vec<PathBuf> {
     let mut to_flatten = files;
@@ -58,72 +61,250 @@ fn is_roc_file(path: &Path) -> bool {
     matches!(path.extension().and_then(OsStr::to_str), Some("roc"))
 }
 
-pub fn format(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), String> {
+/// Which part of a file to format, e.g. the selection in an editor. Only the top-level defs
+/// which overlap it get formatted; the rest of the file is left as it was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatRange {
+    /// 1-based and inclusive, like the line numbers editors show
+    Lines { start: usize, end: usize },
+    /// 0-based byte offsets; the end is exclusive
+    Bytes { start: usize, end: usize },
+}
+
+impl FormatRange {
+    /// Parses a range like `3:10`
+    pub fn parse_lines(arg: &str) -> Result<Self, String> {
+        let (start, end) = parse_range(arg)?;
+
+        if start == 0 {
+            return Err(format!(
+                "Line numbers start at 1, so this line range is invalid: {}",
+                arg
+            ));
+        }
+
+        Ok(FormatRange::Lines { start, end })
+    }
+
+    /// Parses a range like `120:480`
+    pub fn parse_bytes(arg: &str) -> Result<Self, String> {
+        let (start, end) = parse_range(arg)?;
+
+        Ok(FormatRange::Bytes { start, end })
+    }
+
+    fn to_byte_range(self, src: &str) -> std::ops::Range<usize> {
+        match self {
+            FormatRange::Lines { start, end } => {
+                let line_starts: std::vec::Vec<usize> = std::iter::once(0)
+                    .chain(src.match_indices('\n').map(|(index, _)| index + 1))
+                    .collect();
+                let byte_start = line_starts.get(start - 1).copied().unwrap_or(src.len());
+                let byte_end = line_starts.get(end).copied().unwrap_or(src.len());
+
+                byte_start..byte_end
+            }
+            FormatRange::Bytes { start, end } => start.min(src.len())..end.min(src.len()),
+        }
+    }
+}
+
+fn parse_range(arg: &str) -> Result<(usize, usize), String> {
+    let invalid = || {
+        format!(
+            "I was expecting a range like 3:10 (start:end), but got this instead: {}",
+            arg
+        )
+    };
+    let (start, end) = arg.split_once(':').ok_or_else(invalid)?;
+    let start: usize = start.trim().parse().map_err(|_| invalid())?;
+    let end: usize = end.trim().parse().map_err(|_| invalid())?;
+
+    if start > end {
+        return Err(invalid());
+    }
+
+    Ok((start, end))
+}
+
+pub fn format(
+    files: std::vec::Vec<PathBuf>,
+    mode: FormatMode,
+    opt_range: Option<FormatRange>,
+    opt_max_width: Option<usize>,
+) -> Result<(), String> {
     let files = flatten_directories(files);
 
+    if opt_range.is_some() && files.len() != 1 {
+        return Err("A range can only be formatted in a single file.".to_string());
+    }
+
+    let mut needs_formatting = false;
+
     for file in files {
         let arena = Bump::new();
 
         let src = std::fs::read_to_string(&file).unwrap();
+        let formatted = format_src(&arena, &src, opt_range, opt_max_width, &file);
 
-        let ast = arena.alloc(parse_all(&arena, &src).unwrap_or_else(|e| {
-            user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{:?}\n\n", src, e)
-        }));
-        let mut buf = Buf::new_in(&arena);
-        fmt_all(&mut buf, ast);
+        match mode {
+            FormatMode::CheckOnly => {
+                // Keep going, so that the diffs for all the files get printed.
+                if formatted != src {
+                    let path = file.display().to_string();
 
-        let reparsed_ast = arena.alloc(parse_all(&arena, buf.as_str()).unwrap_or_else(|e| {
-            let mut fail_file = file.clone();
-            fail_file.set_extension("roc-format-failed");
-            std::fs::write(&fail_file, buf.as_str()).unwrap();
-            internal_error!(
-                "Formatting bug; formatted code isn't valid\n\n\
-                I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
-                Parse error was: {:?}\n\n",
-                fail_file.display(),
-                e
-            );
-        }));
-
-        let ast_normalized = ast.remove_spaces(&arena);
-        let reparsed_ast_normalized = reparsed_ast.remove_spaces(&arena);
-
-        // HACK!
-        // We compare the debug format strings of the ASTs, because I'm finding in practice that _somewhere_ deep inside the ast,
-        // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
-        // I don't have the patience to debug this right now, so let's leave it for another day...
-        // TODO: fix PartialEq impl on ast types
-        if format!("{:?}", ast_normalized) != format!("{:?}", reparsed_ast_normalized) {
-            let mut fail_file = file.clone();
-            fail_file.set_extension("roc-format-failed");
-            std::fs::write(&fail_file, buf.as_str()).unwrap();
-
-            let mut before_file = file.clone();
-            before_file.set_extension("roc-format-failed-ast-before");
-            std::fs::write(&before_file, &format!("{:#?}\n", ast_normalized)).unwrap();
-
-            let mut after_file = file.clone();
-            after_file.set_extension("roc-format-failed-ast-after");
-            std::fs::write(&after_file, &format!("{:#?}\n", reparsed_ast_normalized)).unwrap();
+                    print!("{}", unified_diff(&src, &formatted, &path));
+                    needs_formatting = true;
+                }
+            }
 
-            internal_error!(
-                "Formatting bug; formatting didn't reparse as the same tree\n\n\
-                I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
-                I wrote the tree before and after formatting to these files for debugging purposes:\n{}\n{}\n\n",
-                fail_file.display(),
-                before_file.display(),
-                after_file.display());
+            FormatMode::Format => {
+                // If all the checks passed, actually write out the new file.
+                if formatted != src {
+                    std::fs::write(&file, formatted).unwrap();
+                }
+            }
         }
+    }
+
+    if needs_formatting {
+        return Err("One or more files need to be reformatted.".to_string());
+    }
+
+    Ok(())
+}
+
+/// Formats the code given on stdin, and prints it to stdout (or, when checking, prints
+/// a diff of what would change) - e.g. for editors that pipe the current buffer through `roc format`.
+pub fn format_stdin(
+    mode: FormatMode,
+    opt_range: Option<FormatRange>,
+    opt_max_width: Option<usize>,
+) -> Result<(), String> {
+    let mut src = String::new();
+
+    std::io::stdin()
+        .read_to_string(&mut src)
+        .map_err(|err| format!("Could not read the code to format from stdin: {}", err))?;
+
+    let arena = Bump::new();
 
-        // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
-        let mut reformatted_buf = Buf::new_in(&arena);
+    // There's no file to write debugging info next to, so use the current directory.
+    let formatted = format_src(
+        &arena,
+        &src,
+        opt_range,
+        opt_max_width,
+        Path::new("stdin.roc"),
+    );
+
+    match mode {
+        FormatMode::CheckOnly => {
+            if formatted != src {
+                print!("{}", unified_diff(&src, &formatted, "<stdin>"));
+
+                return Err("The code from stdin needs to be reformatted.".to_string());
+            }
+        }
+        FormatMode::Format => {
+            std::io::stdout()
+                .write_all(formatted.as_bytes())
+                .map_err(|err| format!("Could not write the formatted code to stdout: {}", err))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn unified_diff(src: &str, formatted: &str, path: &str) -> String {
+    similar::TextDiff::from_lines(src, formatted)
+        .unified_diff()
+        .header(path, &format!("{} (formatted)", path))
+        .to_string()
+}
+
+/// Formats the given source, and checks that the result is still the same program. If it's
+/// not, that's a formatter bug, so the results get written next to `file` for debugging.
+fn format_src<'a>(
+    arena: &'a Bump,
+    src: &'a str,
+    opt_range: Option<FormatRange>,
+    opt_max_width: Option<usize>,
+    file: &Path,
+) -> String {
+    let ast = arena.alloc(parse_all(arena, src).unwrap_or_else(|e| {
+        user_error!("Unexpected parse failure when parsing this formatting:\n\n{:?}\n\nParse error was:\n\n{:?}\n\n", src, e)
+    }));
+    let formatted: &str = match opt_range {
+        Some(range) => arena.alloc_str(&fmt_defs_in_range(
+            arena,
+            src,
+            ast,
+            range.to_byte_range(src),
+            opt_max_width,
+        )),
+        None => {
+            let mut buf = new_buf(arena, opt_max_width);
+            fmt_all(&mut buf, ast);
+
+            arena.alloc_str(buf.as_str())
+        }
+    };
+
+    let reparsed_ast = arena.alloc(parse_all(arena, formatted).unwrap_or_else(|e| {
+        let mut fail_file = file.to_path_buf();
+        fail_file.set_extension("roc-format-failed");
+        std::fs::write(&fail_file, formatted).unwrap();
+        internal_error!(
+            "Formatting bug; formatted code isn't valid\n\n\
+            I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
+            Parse error was: {:?}\n\n",
+            fail_file.display(),
+            e
+        );
+    }));
+
+    let ast_normalized = ast.remove_spaces(arena);
+    let reparsed_ast_normalized = reparsed_ast.remove_spaces(arena);
+
+    // HACK!
+    // We compare the debug format strings of the ASTs, because I'm finding in practice that _somewhere_ deep inside the ast,
+    // the PartialEq implementation is returning `false` even when the Debug-formatted impl is exactly the same.
+    // I don't have the patience to debug this right now, so let's leave it for another day...
+    // TODO: fix PartialEq impl on ast types
+    if format!("{:?}", ast_normalized) != format!("{:?}", reparsed_ast_normalized) {
+        let mut fail_file = file.to_path_buf();
+        fail_file.set_extension("roc-format-failed");
+        std::fs::write(&fail_file, formatted).unwrap();
+
+        let mut before_file = file.to_path_buf();
+        before_file.set_extension("roc-format-failed-ast-before");
+        std::fs::write(&before_file, &format!("{:#?}\n", ast_normalized)).unwrap();
+
+        let mut after_file = file.to_path_buf();
+        after_file.set_extension("roc-format-failed-ast-after");
+        std::fs::write(&after_file, &format!("{:#?}\n", reparsed_ast_normalized)).unwrap();
+
+        internal_error!(
+            "Formatting bug; formatting didn't reparse as the same tree\n\n\
+            I wrote the incorrect result to this file for debugging purposes:\n{}\n\n\
+            I wrote the tree before and after formatting to these files for debugging purposes:\n{}\n{}\n\n",
+            fail_file.display(),
+            before_file.display(),
+            after_file.display());
+    }
+
+    // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted.
+    // (Formatting part of a file moves the rest of it around, so there's nothing to compare to.)
+    if opt_range.is_none() {
+        let mut reformatted_buf = new_buf(arena, opt_max_width);
         fmt_all(&mut reformatted_buf, reparsed_ast);
-        if buf.as_str() != reformatted_buf.as_str() {
-            let mut unstable_1_file = file.clone();
+        if formatted != reformatted_buf.as_str() {
+            let mut unstable_1_file = file.to_path_buf();
             unstable_1_file.set_extension("roc-format-unstable-1");
-            std::fs::write(&unstable_1_file, buf.as_str()).unwrap();
+            std::fs::write(&unstable_1_file, formatted).unwrap();
 
-            let mut unstable_2_file = file.clone();
+            let mut unstable_2_file = file.to_path_buf();
             unstable_2_file.set_extension("roc-format-unstable-2");
             std::fs::write(&unstable_2_file, reformatted_buf.as_str()).unwrap();
 
@@ -134,23 +315,83 @@ pub fn format(files: std::vec::Vec<PathBuf>, mode: FormatMode) -> Result<(), Str
                 unstable_1_file.display(),
                 unstable_2_file.display());
         }
+    }
 
-        match mode {
-            FormatMode::CheckOnly => {
-                // If we notice that this file needs to be formatted, return early
-                if buf.as_str() != src {
-                    return Err("One or more files need to be reformatted.".to_string());
-                }
-            }
+    formatted.to_string()
+}
 
-            FormatMode::Format => {
-                // If all the checks above passed, actually write out the new file.
-                std::fs::write(&file, buf.as_str()).unwrap();
-            }
+/// Formats the top-level defs which overlap the given byte range, and leaves everything
+/// else - the module header, the other defs, and the spaces around them - as it was.
+fn fmt_defs_in_range<'a>(
+    arena: &'a Bump,
+    src: &str,
+    ast: &'a Ast,
+    range: std::ops::Range<usize>,
+    opt_max_width: Option<usize>,
+) -> String {
+    let overlaps = |region: &Region| {
+        let start = region.start().offset as usize;
+        let end = region.end().offset as usize;
+
+        // An empty range (e.g. just a cursor position) still picks the def it's in.
+        start < range.end.max(range.start + 1) && range.start < end
+    };
+    let mut indices = ast
+        .defs
+        .regions
+        .iter()
+        .enumerate()
+        .filter(|(_, region)| overlaps(region))
+        .map(|(index, _)| index);
+
+    let first = match indices.next() {
+        Some(index) => index,
+        None => return src.to_string(),
+    };
+    let last = indices.next_back().unwrap_or(first);
+
+    // The spaces before the first def and after the last one are outside the range,
+    // but the ones between the defs get formatted along with them.
+    let mut defs = Defs::default();
+
+    for (index, def) in ast.defs.defs().enumerate().take(last + 1).skip(first) {
+        let region = ast.defs.regions[index];
+        let spaces_before: &[_] = if index == first {
+            &[]
+        } else {
+            &ast.defs.spaces[ast.defs.space_before[index].indices()]
+        };
+        let spaces_after: &[_] = if index == last {
+            &[]
+        } else {
+            &ast.defs.spaces[ast.defs.space_after[index].indices()]
+        };
+
+        match def {
+            Ok(type_def) => defs.push_type_def(*type_def, region, spaces_before, spaces_after),
+            Err(value_def) => defs.push_value_def(*value_def, region, spaces_before, spaces_after),
         }
     }
 
-    Ok(())
+    let mut buf = new_buf(arena, opt_max_width);
+    fmt_defs(&mut buf, &defs, 0);
+
+    let start = ast.defs.regions[first].start().offset as usize;
+    let end = ast.defs.regions[last].end().offset as usize;
+
+    format!(
+        "{}{}{}",
+        &src[..start],
+        buf.as_str().trim_start_matches('\n').trim_end(),
+        &src[end..]
+    )
+}
+
+fn new_buf(arena: &Bump, opt_max_width: Option<usize>) -> Buf<'_> {
+    match opt_max_width {
+        Some(max_width) => Buf::with_max_width_in(arena, max_width),
+        None => Buf::new_in(arena),
+    }
 }
 
 fn parse_all<'a>(arena: &'a Bump, src: &'a str) -> Result<Ast<'a>, SyntaxError<'a>> {
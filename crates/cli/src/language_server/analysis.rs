@@ -0,0 +1,225 @@
+//! Typechecks open documents and answers editor queries against the results.
+use bumpalo::Bump;
+use roc_can::traverse::{find_closest_symbol_at, find_closest_type_at, find_symbol_introduction};
+use roc_load::{LoadedModule, LoadingProblem};
+use roc_packaging::cache::RocCacheDir;
+use roc_problem::Severity;
+use roc_region::all::{LineColumn, LineColumnRegion, LineInfo, Region};
+use roc_reporting::report::{
+    can_problem, type_problem, RenderTarget, Report, RocDocAllocator, DEFAULT_PALETTE,
+};
+use roc_target::TargetInfo;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use std::path::{Path, PathBuf};
+
+/// A problem found while checking a document, positioned within some module's source.
+pub struct Diagnostic {
+    pub region: LineColumnRegion,
+    pub severity: Severity,
+    pub title: String,
+    pub message: String,
+}
+
+/// The diagnostics for one module, along with the source their regions refer to.
+pub struct FileDiagnostics {
+    pub path: PathBuf,
+    pub source: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// The diagnostics for every module that was loaded while checking a document.
+/// Modules without problems are included with an empty list, so that the editor
+/// clears any diagnostics it was previously showing for them.
+pub type DiagnosticsByFile = Vec<FileDiagnostics>;
+
+/// An open document, along with the result of typechecking its most recent contents.
+pub struct Document {
+    source: String,
+    line_info: LineInfo,
+    /// `None` if loading failed outright, e.g. because the module header did not parse.
+    module: Option<LoadedModule>,
+}
+
+impl Document {
+    pub fn check(path: PathBuf, source: &str, roc_cache_dir: &Path) -> (Self, DiagnosticsByFile) {
+        let arena = Bump::new();
+        let src_dir = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        let load_result = roc_load::load_and_typecheck_str(
+            &arena,
+            path.clone(),
+            arena.alloc_str(source),
+            src_dir,
+            // Only used for generating errors; we don't do any code generation here.
+            TargetInfo::default_x86_64(),
+            RenderTarget::Generic,
+            RocCacheDir::Persistent(roc_cache_dir),
+            DEFAULT_PALETTE,
+        );
+
+        let (module, diagnostics) = match load_result {
+            Ok(mut module) => {
+                let diagnostics = module_diagnostics(&mut module);
+
+                (Some(module), diagnostics)
+            }
+            Err(problem) => (
+                None,
+                vec![FileDiagnostics {
+                    path,
+                    source: source.to_string(),
+                    diagnostics: vec![loading_diagnostic(problem)],
+                }],
+            ),
+        };
+
+        let document = Document {
+            source: source.to_string(),
+            line_info: LineInfo::new(source),
+            module,
+        };
+
+        (document, diagnostics)
+    }
+
+    /// The contents this document was last checked with.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The solved type of the innermost expression or pattern under the cursor, plus its region.
+    pub fn hover(&mut self, line_column: LineColumn) -> Option<(LineColumnRegion, String)> {
+        let module = self.module.as_mut()?;
+        let home = module.module_id;
+        let decls = module.declarations_by_id.get(&home)?;
+        let pos = self.line_info.convert_line_column(line_column);
+        let (region, var) = find_closest_type_at(pos, decls)?;
+
+        let subs = module.solved.inner_mut();
+        let snapshot = subs.snapshot();
+        let type_str = name_and_print_var(var, subs, home, &module.interns, DebugPrint::NOTHING);
+        subs.rollback_to(snapshot);
+
+        let contents = match find_closest_symbol_at(pos, decls) {
+            Some(loc_symbol) if loc_symbol.region == region => {
+                format!(
+                    "{} : {}",
+                    loc_symbol.value.as_str(&module.interns),
+                    type_str
+                )
+            }
+            _ => type_str,
+        };
+
+        Some((self.line_info.convert_region(region), contents))
+    }
+
+    /// Where the symbol under the cursor was defined, along with the source of the module
+    /// it was defined in.
+    pub fn definition(&self, line_column: LineColumn) -> Option<(&Path, &str, LineColumnRegion)> {
+        let module = self.module.as_ref()?;
+        let home = module.module_id;
+        let decls = module.declarations_by_id.get(&home)?;
+        let pos = self.line_info.convert_line_column(line_column);
+        let symbol = find_closest_symbol_at(pos, decls)?.value;
+        let symbol_home = symbol.module_id();
+
+        // Builtins have no entry in `sources`, so there is nowhere to jump to.
+        let (path, src) = module.sources.get(&symbol_home)?;
+
+        let opt_region = module
+            .declarations_by_id
+            .get(&symbol_home)
+            .and_then(|symbol_decls| find_symbol_introduction(symbol, symbol_decls));
+
+        let region = match opt_region {
+            Some(region) if symbol_home == home => self.line_info.convert_region(region),
+            Some(region) => LineInfo::new(src).convert_region(region),
+            // The loader only keeps declarations around for the module being checked,
+            // so for definitions in other modules the best we can do is open the right file.
+            None => LineColumnRegion::zero(),
+        };
+
+        let src: &str = if symbol_home == home {
+            self.source.as_str()
+        } else {
+            src
+        };
+
+        Some((path.as_path(), src, region))
+    }
+}
+
+fn module_diagnostics(loaded: &mut LoadedModule) -> DiagnosticsByFile {
+    let mut diagnostics_by_file = Vec::with_capacity(loaded.sources.len());
+
+    for (home, (module_path, src)) in loaded.sources.iter() {
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let alloc = RocDocAllocator::new(&src_lines, *home, &loaded.interns);
+        let mut diagnostics = Vec::new();
+
+        for problem in loaded.can_problems.remove(home).unwrap_or_default() {
+            let region = problem.region();
+            let report = can_problem(&alloc, &lines, module_path.clone(), problem);
+
+            diagnostics.push(report_to_diagnostic(report, region, &alloc, &lines));
+        }
+
+        for problem in loaded.type_problems.remove(home).unwrap_or_default() {
+            let region = problem.region();
+
+            if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
+                diagnostics.push(report_to_diagnostic(report, region, &alloc, &lines));
+            }
+        }
+
+        diagnostics_by_file.push(FileDiagnostics {
+            path: module_path.clone(),
+            source: src.to_string(),
+            diagnostics,
+        });
+    }
+
+    diagnostics_by_file
+}
+
+fn report_to_diagnostic<'b>(
+    mut report: Report<'b>,
+    region: Option<Region>,
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+) -> Diagnostic {
+    // The editor shows the title separately, so leave it out of the rendered message.
+    let title = std::mem::take(&mut report.title);
+    let severity = report.severity;
+    let mut message = String::new();
+
+    report.render_ci(&mut message, alloc);
+
+    Diagnostic {
+        region: region
+            .map(|region| lines.convert_region(region))
+            .unwrap_or_else(LineColumnRegion::zero),
+        severity,
+        title,
+        message,
+    }
+}
+
+fn loading_diagnostic(problem: LoadingProblem) -> Diagnostic {
+    let message = match problem {
+        LoadingProblem::FormattedReport(report) => report,
+        other => format!("{:?}", other),
+    };
+
+    Diagnostic {
+        region: LineColumnRegion::zero(),
+        severity: Severity::Fatal,
+        title: String::new(),
+        message,
+    }
+}
@@ -0,0 +1,329 @@
+//! A [Language Server Protocol](https://microsoft.github.io/language-server-protocol/) server for
+//! Roc, which editors talk to over stdio.
+//!
+//! Every open document is typechecked from its in-memory contents whenever it changes, and the
+//! resulting problems are published as diagnostics. The most recent check of each document is
+//! kept around so that hover (the solved type under the cursor) and go-to-definition can be
+//! answered without checking again.
+mod analysis;
+mod position;
+mod transport;
+
+use analysis::{Diagnostic, Document};
+use position::PositionEncoding;
+use roc_collections::all::MutMap;
+use roc_packaging::cache;
+use roc_problem::Severity;
+use serde_json::{json, Value};
+use std::io;
+use std::path::{Path, PathBuf};
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#errorCodes
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocumentSyncKind
+const TEXT_DOCUMENT_SYNC_FULL: u8 = 1;
+
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl ResponseError {
+    fn invalid_params(method: &str) -> Self {
+        ResponseError {
+            code: INVALID_PARAMS,
+            message: format!("invalid params for {method}"),
+        }
+    }
+}
+
+struct Server {
+    documents: MutMap<PathBuf, Document>,
+    roc_cache_dir: PathBuf,
+    /// How the `character` of positions is counted, as agreed on in `initialize`.
+    position_encoding: PositionEncoding,
+    shutdown_requested: bool,
+}
+
+/// Runs the language server until the client sends `exit`, returning the exit code
+/// the protocol prescribes: 0 if `shutdown` was requested first, and 1 otherwise.
+pub fn run_language_server() -> io::Result<i32> {
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+    let stdout = io::stdout();
+    let mut writer = stdout.lock();
+
+    let mut server = Server {
+        documents: MutMap::default(),
+        roc_cache_dir: cache::roc_cache_dir(),
+        position_encoding: PositionEncoding::Utf16,
+        shutdown_requested: false,
+    };
+
+    while let Some(message) = transport::read_message(&mut reader)? {
+        let method = message.get("method").and_then(Value::as_str);
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        match (method, message.get("id")) {
+            (Some("exit"), _) => {
+                return Ok(if server.shutdown_requested { 0 } else { 1 });
+            }
+            (Some(method), Some(id)) => {
+                let response = match server.handle_request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(ResponseError { code, message }) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": message },
+                    }),
+                };
+
+                transport::write_message(&mut writer, &response)?;
+            }
+            (Some(method), None) => {
+                for notification in server.handle_notification(method, params) {
+                    transport::write_message(&mut writer, &notification)?;
+                }
+            }
+            (None, _) => {
+                // A response to a request from us, but we never send any.
+            }
+        }
+    }
+
+    // The client went away without telling us to exit.
+    Ok(1)
+}
+
+impl Server {
+    fn handle_request(&mut self, method: &str, params: Value) -> Result<Value, ResponseError> {
+        match method {
+            "initialize" => {
+                self.position_encoding = PositionEncoding::negotiate(&params);
+
+                Ok(json!({
+                    "capabilities": {
+                        "positionEncoding": self.position_encoding.as_str(),
+                        "textDocumentSync": TEXT_DOCUMENT_SYNC_FULL,
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                    },
+                    "serverInfo": {
+                        "name": "roc",
+                        "version": crate::VERSION.trim(),
+                    },
+                }))
+            }
+            "shutdown" => {
+                self.shutdown_requested = true;
+
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => {
+                let (path, line, character) = text_document_position(&params)
+                    .ok_or_else(|| ResponseError::invalid_params(method))?;
+
+                let encoding = self.position_encoding;
+                let document = match self.documents.get_mut(&path) {
+                    Some(document) => document,
+                    None => return Ok(Value::Null),
+                };
+                let line_column = encoding.to_line_column(document.source(), line, character);
+
+                Ok(match document.hover(line_column) {
+                    Some((region, type_str)) => json!({
+                        "contents": {
+                            "kind": "markdown",
+                            "value": format!("```roc\n{type_str}\n```"),
+                        },
+                        "range": encoding.range_to_json(document.source(), region),
+                    }),
+                    None => Value::Null,
+                })
+            }
+            "textDocument/definition" => {
+                let (path, line, character) = text_document_position(&params)
+                    .ok_or_else(|| ResponseError::invalid_params(method))?;
+
+                let encoding = self.position_encoding;
+                let document = match self.documents.get(&path) {
+                    Some(document) => document,
+                    None => return Ok(Value::Null),
+                };
+                let line_column = encoding.to_line_column(document.source(), line, character);
+
+                Ok(match document.definition(line_column) {
+                    Some((def_path, def_src, region)) => json!({
+                        "uri": path_to_uri(def_path),
+                        "range": encoding.range_to_json(def_src, region),
+                    }),
+                    None => Value::Null,
+                })
+            }
+            _ => Err(ResponseError {
+                code: METHOD_NOT_FOUND,
+                message: format!("the Roc language server does not support {method}"),
+            }),
+        }
+    }
+
+    /// Handles a notification from the client, returning notifications to send back.
+    fn handle_notification(&mut self, method: &str, params: Value) -> Vec<Value> {
+        let opt_path = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .and_then(uri_to_path);
+
+        let path = match opt_path {
+            Some(path) => path,
+            // Notifications like `initialized` carry no document, and we have nothing to do for them.
+            None => return Vec::new(),
+        };
+
+        match method {
+            "textDocument/didOpen" => match params.pointer("/textDocument/text") {
+                Some(Value::String(text)) => self.check(path, text),
+                _ => Vec::new(),
+            },
+            "textDocument/didChange" => {
+                // We ask for full document sync, so the last change holds the entire new text.
+                let opt_text = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+
+                match opt_text {
+                    Some(text) => self.check(path, text),
+                    None => Vec::new(),
+                }
+            }
+            "textDocument/didSave" => {
+                // Other modules the document imports may have changed on disk, so check again.
+                match std::fs::read_to_string(&path) {
+                    Ok(text) => self.check(path, &text),
+                    Err(_) => Vec::new(),
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&path);
+
+                vec![publish_diagnostics(self.position_encoding, &path, "", &[])]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn check(&mut self, path: PathBuf, text: &str) -> Vec<Value> {
+        let (document, diagnostics_by_file) =
+            Document::check(path.clone(), text, &self.roc_cache_dir);
+
+        self.documents.insert(path, document);
+
+        diagnostics_by_file
+            .iter()
+            .map(|file| {
+                publish_diagnostics(
+                    self.position_encoding,
+                    &file.path,
+                    &file.source,
+                    &file.diagnostics,
+                )
+            })
+            .collect()
+    }
+}
+
+fn publish_diagnostics(
+    encoding: PositionEncoding,
+    path: &Path,
+    src: &str,
+    diagnostics: &[Diagnostic],
+) -> Value {
+    let diagnostics: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#diagnosticSeverity
+            let severity = match diagnostic.severity {
+                Severity::Fatal | Severity::RuntimeError => 1,
+                Severity::Warning => 2,
+            };
+
+            let message = if diagnostic.title.is_empty() {
+                diagnostic.message.clone()
+            } else {
+                format!("{}\n\n{}", diagnostic.title, diagnostic.message)
+            };
+
+            json!({
+                "range": encoding.range_to_json(src, diagnostic.region),
+                "severity": severity,
+                "source": "roc",
+                "message": message,
+            })
+        })
+        .collect();
+
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {
+            "uri": path_to_uri(path),
+            "diagnostics": diagnostics,
+        },
+    })
+}
+
+/// The document, line and `character` of a position. The `character` is still in the
+/// negotiated [PositionEncoding], since converting it needs the document's source.
+fn text_document_position(params: &Value) -> Option<(PathBuf, u32, u32)> {
+    let path = uri_to_path(params.pointer("/textDocument/uri")?.as_str()?)?;
+    let line = params.pointer("/position/line")?.as_u64()?;
+    let character = params.pointer("/position/character")?.as_u64()?;
+
+    Some((path, line as u32, character as u32))
+}
+
+/// Converts a `file://` URI to a path, decoding any percent-escapes.
+/// Other schemes (e.g. `untitled:`) are not backed by files, so we can't check them.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).ok()?;
+
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 3;
+            }
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
@@ -0,0 +1,194 @@
+//! Converts between the positions editors send and Roc's regions.
+//!
+//! Roc counts columns in bytes, but the `character` of an LSP position counts UTF-16 code
+//! units unless the client agreed to something else during `initialize`. The two only agree
+//! on lines that are entirely ASCII up to the position.
+use roc_region::all::{LineColumn, LineColumnRegion};
+use serde_json::{json, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8,
+    Utf16,
+}
+
+impl PositionEncoding {
+    /// Uses UTF-8 if the client says it supports it, since that is what Roc uses, and
+    /// otherwise falls back to UTF-16, which every client has to support.
+    pub fn negotiate(initialize_params: &Value) -> Self {
+        let supports_utf8 = initialize_params
+            .pointer("/capabilities/general/positionEncodings")
+            .and_then(Value::as_array)
+            .map_or(false, |encodings| {
+                encodings.iter().any(|encoding| encoding == "utf-8")
+            });
+
+        if supports_utf8 {
+            PositionEncoding::Utf8
+        } else {
+            PositionEncoding::Utf16
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PositionEncoding::Utf8 => "utf-8",
+            PositionEncoding::Utf16 => "utf-16",
+        }
+    }
+
+    /// Converts an LSP position in `src` to a Roc line and column. Positions past the end
+    /// of a line or of the source are moved back to the end of it.
+    pub fn to_line_column(self, src: &str, line: u32, character: u32) -> LineColumn {
+        let line_count = src.split('\n').count() as u32;
+        let line = line.min(line_count.saturating_sub(1));
+        let text = line_text(src, line);
+
+        let column = match self {
+            PositionEncoding::Utf8 => (character as usize).min(text.len()),
+            PositionEncoding::Utf16 => {
+                let mut units = 0;
+                let mut column = text.len();
+
+                for (byte_index, ch) in text.char_indices() {
+                    if units >= character as usize {
+                        column = byte_index;
+                        break;
+                    }
+
+                    units += ch.len_utf16();
+                }
+
+                column
+            }
+        };
+
+        LineColumn {
+            line,
+            column: column as u32,
+        }
+    }
+
+    /// Converts a Roc line and column in `src` to the `character` of an LSP position.
+    pub fn to_character(self, src: &str, line_column: LineColumn) -> u32 {
+        match self {
+            PositionEncoding::Utf8 => line_column.column,
+            PositionEncoding::Utf16 => {
+                let text = line_text(src, line_column.line);
+                let column = (line_column.column as usize).min(text.len());
+
+                text.get(..column)
+                    .unwrap_or(text)
+                    .chars()
+                    .map(char::len_utf16)
+                    .sum::<usize>() as u32
+            }
+        }
+    }
+
+    /// The LSP range of a Roc region in `src`.
+    pub fn range_to_json(self, src: &str, region: LineColumnRegion) -> Value {
+        json!({
+            "start": {
+                "line": region.start.line,
+                "character": self.to_character(src, region.start),
+            },
+            "end": {
+                "line": region.end.line,
+                "character": self.to_character(src, region.end),
+            },
+        })
+    }
+}
+
+fn line_text(src: &str, line: u32) -> &str {
+    let text = src.split('\n').nth(line as usize).unwrap_or("");
+
+    text.strip_suffix('\r').unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PositionEncoding::{self, *};
+    use roc_region::all::{LineColumn, LineColumnRegion};
+    use serde_json::json;
+
+    const SRC: &str = "x = 1\ngreeting = \"héllo 🐦\" |> Str.concat name\n";
+
+    fn lc(line: u32, column: u32) -> LineColumn {
+        LineColumn { line, column }
+    }
+
+    #[test]
+    fn negotiate_prefers_utf8() {
+        let params =
+            json!({ "capabilities": { "general": { "positionEncodings": ["utf-16", "utf-8"] } } });
+
+        assert_eq!(PositionEncoding::negotiate(&params), Utf8);
+    }
+
+    #[test]
+    fn negotiate_defaults_to_utf16() {
+        let params = json!({ "capabilities": { "general": { "positionEncodings": ["utf-32"] } } });
+
+        assert_eq!(PositionEncoding::negotiate(&params), Utf16);
+        assert_eq!(PositionEncoding::negotiate(&json!({})), Utf16);
+    }
+
+    #[test]
+    fn ascii_columns_agree() {
+        assert_eq!(Utf16.to_line_column(SRC, 0, 4), lc(0, 4));
+        assert_eq!(Utf16.to_character(SRC, lc(0, 4)), 4);
+        assert_eq!(Utf8.to_line_column(SRC, 0, 4), lc(0, 4));
+    }
+
+    #[test]
+    fn utf16_after_non_ascii() {
+        // `é` is 2 bytes but 1 UTF-16 unit; `🐦` is 4 bytes but 2 UTF-16 units.
+        // `|>` starts at byte 25, but at UTF-16 unit 22.
+        assert_eq!(Utf16.to_line_column(SRC, 1, 22), lc(1, 25));
+        assert_eq!(Utf16.to_character(SRC, lc(1, 25)), 22);
+
+        // UTF-8 clients count bytes, like Roc does.
+        assert_eq!(Utf8.to_line_column(SRC, 1, 25), lc(1, 25));
+        assert_eq!(Utf8.to_character(SRC, lc(1, 25)), 25);
+    }
+
+    #[test]
+    fn round_trip_every_char() {
+        let line = SRC.split('\n').nth(1).unwrap();
+
+        for (byte_index, _) in line.char_indices() {
+            let character = Utf16.to_character(SRC, lc(1, byte_index as u32));
+
+            assert_eq!(
+                Utf16.to_line_column(SRC, 1, character),
+                lc(1, byte_index as u32)
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_positions_are_clamped() {
+        assert_eq!(Utf16.to_line_column(SRC, 0, 100), lc(0, 5));
+        assert_eq!(Utf8.to_line_column(SRC, 0, 100), lc(0, 5));
+        assert_eq!(Utf16.to_line_column(SRC, 100, 0), lc(2, 0));
+    }
+
+    #[test]
+    fn range_json() {
+        // The string literal on the second line.
+        let region = LineColumnRegion {
+            start: lc(1, 11),
+            end: lc(1, 24),
+        };
+
+        assert_eq!(
+            Utf16.range_to_json(SRC, region),
+            json!({
+                "start": { "line": 1, "character": 11 },
+                "end": { "line": 1, "character": 21 },
+            })
+        );
+    }
+}
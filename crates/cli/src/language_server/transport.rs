@@ -0,0 +1,126 @@
+//! Reads and writes [JSON-RPC](https://www.jsonrpc.org/specification) messages, framed with the
+//! `Content-Length` headers the Language Server Protocol uses when talking over stdio.
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// Reads the next message, or returns `None` once the client has closed the stream.
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            break;
+        }
+
+        // Other headers (e.g. Content-Type) are allowed, but we have no use for them.
+        if let Some(len) = line.strip_prefix("Content-Length:") {
+            content_length = Some(len.trim().parse::<usize>().map_err(invalid_data)?);
+        }
+    }
+
+    let len = content_length.ok_or_else(|| invalid_data("missing Content-Length header"))?;
+    let mut body = vec![0; len];
+
+    reader.read_exact(&mut body)?;
+
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(invalid_data)
+}
+
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+
+    writer.flush()
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_message, write_message};
+    use serde_json::json;
+    use std::io::{self, Cursor};
+
+    #[test]
+    fn reads_consecutive_messages() {
+        // There is no separator between messages, so the body must end at the advertised length.
+        let input =
+            "Content-Length: 14\r\n\r\n{\"id\":1,\"a\":2}Content-Length: 8\r\n\r\n{\"id\":2}";
+        let mut reader = Cursor::new(input);
+
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(json!({ "id": 1, "a": 2 }))
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), Some(json!({ "id": 2 })));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn ignores_other_headers() {
+        let input = "Content-Type: application/vscode-jsonrpc; charset=utf-8\r\nContent-Length: 2\r\n\r\n{}";
+
+        assert_eq!(
+            read_message(&mut Cursor::new(input)).unwrap(),
+            Some(json!({}))
+        );
+    }
+
+    #[test]
+    fn content_length_counts_bytes() {
+        let body = r#"{"text":"héllo 🐦"}"#;
+        let input = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+
+        assert_eq!(
+            read_message(&mut Cursor::new(input)).unwrap(),
+            Some(json!({ "text": "héllo 🐦" }))
+        );
+    }
+
+    #[test]
+    fn missing_content_length() {
+        let err = read_message(&mut Cursor::new("Content-Type: x\r\n\r\n{}")).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn truncated_body() {
+        let err = read_message(&mut Cursor::new("Content-Length: 10\r\n\r\n{}")).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn writes_framed_messages() {
+        let mut output = Vec::new();
+
+        write_message(&mut output, &json!({ "text": "é" })).unwrap();
+
+        // `é` is 2 bytes, so the body is 13 bytes even though it is 12 chars.
+        assert_eq!(
+            String::from_utf8(output.clone()).unwrap(),
+            "Content-Length: 13\r\n\r\n{\"text\":\"é\"}"
+        );
+        assert_eq!(
+            read_message(&mut Cursor::new(output)).unwrap(),
+            Some(json!({ "text": "é" }))
+        );
+    }
+}
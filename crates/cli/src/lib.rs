@@ -8,7 +8,7 @@ use clap::{Arg, ArgMatches, Command, ValueSource};
 use roc_build::link::{LinkType, LinkingStrategy};
 use roc_build::program::{
     handle_error_module, handle_loading_problem, standard_load_config, BuildFileError,
-    BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions, DEFAULT_ROC_FILENAME,
+    BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions, EmitOptions, DEFAULT_ROC_FILENAME,
 };
 use roc_error_macros::{internal_error, user_error};
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
@@ -16,10 +16,14 @@ use roc_load::{ExpectMetadata, Threading};
 use roc_mono::ir::OptLevel;
 use roc_packaging::cache::RocCacheDir;
 use roc_packaging::tarball::Compression;
+#[cfg(not(windows))]
+use roc_repl_expect::run::PropertyConfig;
+use roc_reporting::report::RenderTarget;
 use std::env;
 use std::ffi::{CString, OsStr};
 use std::io;
 use std::mem::ManuallyDrop;
+use std::num::NonZeroU64;
 use std::os::raw::{c_char, c_int};
 use std::path::{Path, PathBuf};
 use std::process;
@@ -32,8 +36,22 @@ use target_lexicon::{
 #[cfg(not(target_os = "linux"))]
 use tempfile::TempDir;
 
+#[cfg(not(windows))]
+mod doc_tests;
+mod test_report;
+mod toplevel_expects;
+
+mod deps;
+pub use deps::deps;
+
 mod format;
-pub use format::format;
+pub use format::{format, format_stdin, FormatRange};
+
+mod language_server;
+pub use language_server::run_language_server;
+
+mod watch;
+pub use watch::watch;
 
 pub const CMD_BUILD: &str = "build";
 pub const CMD_RUN: &str = "run";
@@ -46,7 +64,10 @@ pub const CMD_VERSION: &str = "version";
 pub const CMD_FORMAT: &str = "format";
 pub const CMD_TEST: &str = "test";
 pub const CMD_GLUE: &str = "glue";
+pub const CMD_VENDOR: &str = "vendor";
+pub const CMD_DEPS: &str = "deps";
 pub const CMD_GEN_STUB_LIB: &str = "gen-stub-lib";
+pub const CMD_LANGUAGE_SERVER: &str = "language-server";
 
 pub const FLAG_DEBUG: &str = "debug";
 pub const FLAG_BUNDLE: &str = "bundle";
@@ -62,10 +83,30 @@ pub const FLAG_LINKER: &str = "linker";
 pub const FLAG_PREBUILT: &str = "prebuilt-platform";
 pub const FLAG_CHECK: &str = "check";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
+pub const FLAG_ERROR_FORMAT: &str = "error-format";
+pub const FLAG_MODULE: &str = "module";
+pub const FLAG_FILTER: &str = "filter";
+pub const FLAG_LIST: &str = "list";
+pub const FLAG_VERBOSE: &str = "verbose";
+pub const FLAG_REPORT: &str = "report";
+pub const FLAG_REPORT_PATH: &str = "report-path";
+pub const FLAG_JSON: &str = "json";
+pub const FLAG_OUTPUT: &str = "output";
+pub const FLAG_DOC: &str = "doc";
+pub const FLAG_PROPERTY_RUNS: &str = "property-runs";
+pub const FLAG_PROPERTY_SEED: &str = "property-seed";
+pub const FLAG_STDIN: &str = "stdin";
+pub const FLAG_LINES: &str = "lines";
+pub const FLAG_BYTES: &str = "bytes";
+pub const FLAG_MAX_WIDTH: &str = "max-width";
+pub const FLAG_EMIT: &str = "emit";
+pub const FLAG_WATCH: &str = "watch";
+pub const FLAG_HOT_RELOAD: &str = "hot-reload";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const ROC_DIR: &str = "ROC_DIR";
 pub const GLUE_DIR: &str = "GLUE_DIR";
 pub const GLUE_SPEC: &str = "GLUE_SPEC";
+pub const VENDOR_DIR: &str = "VENDOR_DIR";
 pub const DIRECTORY_OR_FILES: &str = "DIRECTORY_OR_FILES";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
 
@@ -116,6 +157,11 @@ pub fn build_app<'a>() -> Command<'a> {
         .possible_values(["true", "false"])
         .required(false);
 
+    let flag_watch = Arg::new(FLAG_WATCH)
+        .long(FLAG_WATCH)
+        .help("Run again whenever any of the loaded .roc files change, including those of the platform and packages")
+        .required(false);
+
     let flag_wasm_stack_size_kb = Arg::new(FLAG_WASM_STACK_SIZE_KB)
         .long(FLAG_WASM_STACK_SIZE_KB)
         .help("Stack size in kilobytes for wasm32 target\n(This only applies when --dev also provided.)")
@@ -123,6 +169,14 @@ pub fn build_app<'a>() -> Command<'a> {
         .validator(|s| s.parse::<u32>())
         .required(false);
 
+    let flag_error_format = Arg::new(FLAG_ERROR_FORMAT)
+        .long(FLAG_ERROR_FORMAT)
+        .help("How to print errors and warnings\n(`json` prints one JSON object per problem, for use by other tools.)")
+        .takes_value(true)
+        .possible_values(["human", "json"])
+        .default_value("human")
+        .required(false);
+
     let roc_file_to_run = Arg::new(ROC_FILE)
         .help("The .roc file of an app to run")
         .allow_invalid_utf8(true)
@@ -151,6 +205,7 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
             .arg(flag_wasm_stack_size_kb.clone())
+            .arg(flag_error_format.clone())
             .arg(
                 Arg::new(FLAG_TARGET)
                     .long(FLAG_TARGET)
@@ -181,6 +236,17 @@ pub fn build_app<'a>() -> Command<'a> {
                     .help("Do not link\n(Instead, just output the `.o` file.)")
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_EMIT)
+                    .long(FLAG_EMIT)
+                    .help("Also write intermediate representations next to the output\n(Takes a comma-separated list, e.g. `--emit=mono-ir,llvm-ir`.)")
+                    .takes_value(true)
+                    .use_value_delimiter(true)
+                    .require_value_delimiter(true)
+                    .multiple_occurrences(true)
+                    .possible_values(EmitOptions::KINDS)
+                    .required(false),
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to build")
@@ -199,6 +265,78 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_watch.clone())
+            .arg(
+                Arg::new(FLAG_MODULE)
+                    .long(FLAG_MODULE)
+                    .help("Only run the `expect`s in the module with this name\n(Can be given multiple times.)")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_FILTER)
+                    .long(FLAG_FILTER)
+                    .help("Only run the `expect`s whose name contains this text\n(An `expect` is named by the `##` doc comment above it, or else by its module and line number. Can be given multiple times.)")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_LIST)
+                    .long(FLAG_LIST)
+                    .help("List the `expect`s that would run, without running them")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_VERBOSE)
+                    .long(FLAG_VERBOSE)
+                    .help("Print whether each `expect` passed or failed, and how long it took")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_REPORT)
+                    .long(FLAG_REPORT)
+                    .help("Also write the results to a file, for use by CI systems\n(Requires --report-path.)")
+                    .takes_value(true)
+                    .possible_values(["junit", "json"])
+                    .requires(FLAG_REPORT_PATH)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_REPORT_PATH)
+                    .long(FLAG_REPORT_PATH)
+                    .help("Where to write the report requested with --report")
+                    .takes_value(true)
+                    .allow_invalid_utf8(true)
+                    .requires(FLAG_REPORT)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_PROPERTY_RUNS)
+                    .long(FLAG_PROPERTY_RUNS)
+                    .help("How many generated inputs each `Arbitrary.check` tries\n(Without it, that's `Arbitrary.defaultConfig.runs`. `Arbitrary.checkWith` isn't affected.)")
+                    .takes_value(true)
+                    .value_name("RUNS")
+                    .validator(|s| s.parse::<NonZeroU64>())
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_PROPERTY_SEED)
+                    .long(FLAG_PROPERTY_SEED)
+                    .help("The seed each `Arbitrary.check` generates its inputs from\n(Without it, that's `Arbitrary.defaultConfig.seed`. `Arbitrary.checkWith` isn't affected.)")
+                    .takes_value(true)
+                    .value_name("SEED")
+                    .validator(|s| s.parse::<NonZeroU64>())
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_DOC)
+                    .long(FLAG_DOC)
+                    .help("Instead of running the `expect`s in the code, check the ```roc code blocks in the `##` doc comments of the modules a package or platform exposes\n(Each block must compile, and any `expect`s in it must pass.)")
+                    .conflicts_with_all(&[FLAG_MODULE, FLAG_FILTER, FLAG_LIST, FLAG_REPORT, FLAG_WATCH, FLAG_PROPERTY_RUNS, FLAG_PROPERTY_SEED])
+                    .required(false),
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file for the main module")
@@ -234,6 +372,13 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_watch)
+            .arg(
+                Arg::new(FLAG_HOT_RELOAD)
+                    .long(FLAG_HOT_RELOAD)
+                    .help("Like --watch, but while the app is running, rebuild it as a shared library for the platform to load instead of restarting it\n(The platform's host needs to support this using roc_std's hot_reload module.)")
+                    .required(false),
+            )
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone())
         )
@@ -248,7 +393,40 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(
                 Arg::new(FLAG_CHECK)
                     .long(FLAG_CHECK)
-                    .help("Checks that specified files are formatted\n(If formatting is needed, return a non-zero exit code.)")
+                    .help("Checks that specified files are formatted\n(If formatting is needed, print a diff of the changes and return a non-zero exit code.)")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_STDIN)
+                    .long(FLAG_STDIN)
+                    .help("Format the code from stdin, and print the result to stdout")
+                    .conflicts_with(DIRECTORY_OR_FILES)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_LINES)
+                    .long(FLAG_LINES)
+                    .help("Only format the top-level definitions on these lines, e.g. --lines 3:10\n(Line numbers start at 1, and the range includes both ends.)")
+                    .takes_value(true)
+                    .value_name("START:END")
+                    .conflicts_with(FLAG_BYTES)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_BYTES)
+                    .long(FLAG_BYTES)
+                    .help("Only format the top-level definitions in this byte range, e.g. --bytes 120:480\n(Offsets start at 0, and the range excludes the end.)")
+                    .takes_value(true)
+                    .value_name("START:END")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_MAX_WIDTH)
+                    .long(FLAG_MAX_WIDTH)
+                    .help("Break lists, records, function calls, pipelines and function types that would make a line wider than this\n(Without it, lines are only broken where they already were.)")
+                    .takes_value(true)
+                    .value_name("COLUMNS")
+                    .validator(|s| s.parse::<usize>())
                     .required(false),
             )
         )
@@ -258,6 +436,7 @@ pub fn build_app<'a>() -> Command<'a> {
             .about("Check the code for problems, but don’t build or run it")
             .arg(flag_time.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_error_format)
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file of an app to check")
@@ -299,6 +478,49 @@ pub fn build_app<'a>() -> Command<'a> {
                     .default_value(DEFAULT_ROC_FILENAME)
             )
         )
+        .subcommand(Command::new(CMD_VENDOR)
+            .about("Copy every package an app depends on via a URL into a local directory\n(Set the ROC_PACKAGE_MIRRORS environment variable to that directory to build without network access.)")
+            .arg(
+                Arg::new(VENDOR_DIR)
+                    .help("The directory to copy the packages into")
+                    .allow_invalid_utf8(true)
+                    .required(true)
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file whose dependencies should be vendored")
+                    .allow_invalid_utf8(true)
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+        )
+        .subcommand(Command::new(CMD_DEPS)
+            .about("List the packages an app depends on, including the packages those depend on")
+            .arg(
+                Arg::new(FLAG_JSON)
+                    .long(FLAG_JSON)
+                    .help("Print the dependencies as JSON, for use by other tools")
+                    .required(false),
+            )
+            .arg(
+                Arg::new(FLAG_OUTPUT)
+                    .long(FLAG_OUTPUT)
+                    .help("Write the dependencies to this file instead of printing them")
+                    .takes_value(true)
+                    .allow_invalid_utf8(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file whose dependencies should be listed")
+                    .allow_invalid_utf8(true)
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+        )
+        .subcommand(Command::new(CMD_LANGUAGE_SERVER)
+            .about("Start a language server, which editors can use to show problems, types and definitions\n(It communicates using the Language Server Protocol over stdin and stdout.)")
+        )
         .subcommand(Command::new(CMD_GEN_STUB_LIB)
             .about("Generate a stubbed shared library that can be used for linking a platform binary.\nThe stubbed library has prototypes, but no function bodies.\n\nNote: This command will be removed in favor of just using `roc build` once all platforms support the surgical linker")
             .arg(
@@ -347,6 +569,44 @@ pub fn build_app<'a>() -> Command<'a> {
     }
 }
 
+/// Problems are rendered for humans unless `--error-format json` was given.
+/// Subcommands that don't accept the flag always render for humans.
+pub fn render_target(matches: &ArgMatches) -> RenderTarget {
+    match matches.try_get_one::<String>(FLAG_ERROR_FORMAT) {
+        Ok(Some(format)) if format == "json" => RenderTarget::Json,
+        _ => RenderTarget::ColorTerminal,
+    }
+}
+
+/// The optimization level asked for by `--optimize` or `--opt-size`, for subcommands where
+/// `--dev` picks the backend rather than the optimization level.
+fn build_opt_level(matches: &ArgMatches) -> OptLevel {
+    match (
+        matches.is_present(FLAG_OPTIMIZE),
+        matches.is_present(FLAG_OPT_SIZE),
+    ) {
+        (true, false) => OptLevel::Optimize,
+        (false, true) => OptLevel::Size,
+        (false, false) => OptLevel::Normal,
+        (true, true) => {
+            user_error!("build can be only one of `--optimize` and `--opt-size`")
+        }
+    }
+}
+
+/// How many threads `--max-threads` allows us to build with.
+fn threading(matches: &ArgMatches) -> Threading {
+    match matches
+        .value_of(FLAG_MAX_THREADS)
+        .and_then(|s| s.parse::<usize>().ok())
+    {
+        None => Threading::AllAvailable,
+        Some(0) => user_error!("cannot build with at most 0 threads"),
+        Some(1) => Threading::Single,
+        Some(n) => Threading::AtMost(n),
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum BuildConfig {
     BuildOnly,
@@ -386,15 +646,7 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         _ => user_error!("build can be only one of `--dev`, `--optimize`, or `--opt-size`"),
     };
 
-    let threading = match matches
-        .value_of(FLAG_MAX_THREADS)
-        .and_then(|s| s.parse::<usize>().ok())
-    {
-        None => Threading::AllAvailable,
-        Some(0) => user_error!("cannot build with at most 0 threads"),
-        Some(1) => Threading::Single,
-        Some(n) => Threading::AtMost(n),
-    };
+    let threading = threading(matches);
 
     let path = Path::new(filename);
 
@@ -421,6 +673,16 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     let opt_level = opt_level;
     let target_info = TargetInfo::from(target);
 
+    if matches.is_present(FLAG_DOC) {
+        return doc_tests::test_docs(
+            path,
+            target,
+            opt_level,
+            threading,
+            matches.is_present(FLAG_VERBOSE),
+        );
+    }
+
     // Step 1: compile the app and generate the .o file
     let load_config = LoadConfig {
         target_info,
@@ -438,28 +700,47 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     );
 
     let mut loaded = match load_result {
-        Ok(loaded) => loaded,
+        Ok(loaded) => {
+            watch::report_sources(loaded.sources.values().map(|(path, _)| path));
+
+            loaded
+        }
         Err(LoadMonomorphizedError::LoadingProblem(problem)) => {
-            return handle_loading_problem(problem);
+            return handle_loading_problem(problem, RenderTarget::ColorTerminal);
         }
         Err(LoadMonomorphizedError::ErrorModule(module)) => {
-            return handle_error_module(module, start_time.elapsed(), filename, false);
+            watch::report_sources(module.sources.values().map(|(path, _)| path));
+
+            return handle_error_module(
+                module,
+                start_time.elapsed(),
+                filename,
+                false,
+                RenderTarget::ColorTerminal,
+            );
         }
     };
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems = report_problems_monomorphized(&mut loaded, RenderTarget::ColorTerminal);
 
-    let mut expectations = std::mem::take(&mut loaded.expectations);
+    let filter = toplevel_expects::ExpectFilter::from_matches(matches);
+    let descriptions = toplevel_expects::select_expects(&mut loaded, &filter);
 
-    let interns = loaded.interns.clone();
+    if descriptions.is_empty() {
+        if problems.warnings > 0 {
+            problems.print_to_stdout(start_time.elapsed());
+            println!(".\n");
+        }
 
-    let (lib, expects, layout_interner) = roc_repl_expect::run::expect_mono_module_to_dylib(
-        arena,
-        target.clone(),
-        loaded,
-        opt_level,
-        LlvmBackendMode::CliTest,
-    )
-    .unwrap();
+        println!("No expectations were found.");
+
+        return Ok(2);
+    }
+
+    if matches.is_present(FLAG_LIST) {
+        toplevel_expects::print_list(&descriptions);
+
+        return Ok(0);
+    }
 
     // Print warnings before running tests.
     {
@@ -473,26 +754,46 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         }
     }
 
-    // Run the tests.
-    let arena = &bumpalo::Bump::new();
-    let interns = arena.alloc(interns);
-
-    let mut writer = std::io::stdout();
+    let property_config = PropertyConfig {
+        runs: matches
+            .value_of(FLAG_PROPERTY_RUNS)
+            .map(|runs| runs.parse().unwrap()),
+        seed: matches
+            .value_of(FLAG_PROPERTY_SEED)
+            .map(|seed| seed.parse().unwrap()),
+    };
 
-    let (failed, passed) = roc_repl_expect::run::run_toplevel_expects(
-        &mut writer,
-        roc_reporting::report::RenderTarget::ColorTerminal,
-        arena,
-        interns,
-        &layout_interner.into_global(),
-        &lib,
-        &mut expectations,
-        expects,
-    )
-    .unwrap();
+    let results = run_expects(arena, target, loaded, opt_level, property_config);
 
     let total_time = start_time.elapsed();
 
+    if matches.is_present(FLAG_VERBOSE) {
+        toplevel_expects::print_results(&descriptions, &results);
+    }
+
+    let mut report_failed = false;
+
+    if let (Some(format), Some(report_path)) = (
+        matches.value_of(FLAG_REPORT),
+        matches.value_of_os(FLAG_REPORT_PATH),
+    ) {
+        let format = test_report::ReportFormat::from_flag(format);
+        let report = test_report::render(format, &descriptions, &results, total_time);
+
+        if let Err(err) = std::fs::write(report_path, report) {
+            eprintln!(
+                "\nCould not write the test report to {}: {}\n",
+                Path::new(report_path).display(),
+                err
+            );
+
+            report_failed = true;
+        }
+    }
+
+    let passed = results.iter().filter(|result| result.passed).count();
+    let failed = results.len() - passed;
+
     if failed == 0 && passed == 0 {
         // TODO print this in a more nicely formatted way!
         println!("No expectations were found.");
@@ -515,10 +816,51 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
             total_time.as_millis(),
         );
 
-        Ok((failed > 0) as i32)
+        Ok((failed > 0 || report_failed) as i32)
     }
 }
 
+/// Compile the top-level `expect`s which are left in the module, and run them.
+#[cfg(not(windows))]
+fn run_expects<'a>(
+    arena: &'a Bump,
+    target: &Triple,
+    mut loaded: roc_load::MonomorphizedModule<'a>,
+    opt_level: OptLevel,
+    property_config: PropertyConfig,
+) -> Vec<roc_repl_expect::run::ToplevelExpectResult<'a>> {
+    let mut expectations = std::mem::take(&mut loaded.expectations);
+
+    let interns = loaded.interns.clone();
+
+    let (lib, expects, layout_interner) = roc_repl_expect::run::expect_mono_module_to_dylib(
+        arena,
+        target.clone(),
+        loaded,
+        opt_level,
+        LlvmBackendMode::CliTest,
+    )
+    .unwrap();
+
+    property_config.set_in(&lib);
+
+    let interns = arena.alloc(interns);
+
+    let mut writer = std::io::stdout();
+
+    roc_repl_expect::run::run_toplevel_expects(
+        &mut writer,
+        roc_reporting::report::RenderTarget::ColorTerminal,
+        arena,
+        interns,
+        &layout_interner.into_global(),
+        &lib,
+        &mut expectations,
+        expects,
+    )
+    .unwrap()
+}
+
 pub fn build(
     matches: &ArgMatches,
     config: BuildConfig,
@@ -603,17 +945,7 @@ pub fn build(
     let opt_level = if let BuildConfig::BuildAndRunIfNoErrors = config {
         OptLevel::Development
     } else {
-        match (
-            matches.is_present(FLAG_OPTIMIZE),
-            matches.is_present(FLAG_OPT_SIZE),
-        ) {
-            (true, false) => OptLevel::Optimize,
-            (false, true) => OptLevel::Size,
-            (false, false) => OptLevel::Normal,
-            (true, true) => {
-                user_error!("build can be only one of `--optimize` and `--opt-size`")
-            }
-        }
+        build_opt_level(matches)
     };
 
     let code_gen_backend = if matches!(triple.architecture, Architecture::Wasm32) {
@@ -637,15 +969,29 @@ pub fn build(
     let emit_debug_info = matches.is_present(FLAG_DEBUG);
     let emit_timings = matches.is_present(FLAG_TIME);
 
-    let threading = match matches
-        .value_of(FLAG_MAX_THREADS)
-        .and_then(|s| s.parse::<usize>().ok())
-    {
-        None => Threading::AllAvailable,
-        Some(0) => user_error!("cannot build with at most 0 threads"),
-        Some(1) => Threading::Single,
-        Some(n) => Threading::AtMost(n),
-    };
+    let mut emit = EmitOptions::default();
+
+    if config == BuildConfig::BuildOnly {
+        for kind in matches.values_of(FLAG_EMIT).unwrap_or_default() {
+            if let Err(problem) = emit.enable(kind) {
+                user_error!("{}", problem);
+            }
+        }
+
+        if (emit.llvm_ir || emit.asm) && matches!(code_gen_backend, CodeGenBackend::Assembly) {
+            user_error!("`--{FLAG_EMIT}=llvm-ir` and `--{FLAG_EMIT}=asm` need the LLVM backend, so they cannot be combined with `--{FLAG_DEV}`");
+        }
+
+        if emit.wasm_wat && !matches!(triple.architecture, Architecture::Wasm32) {
+            user_error!("`--{FLAG_EMIT}=wasm-wat` is only available when building for `--{FLAG_TARGET}=wasm32`");
+        }
+
+        if emit.wasm_wat && link_type == LinkType::None {
+            user_error!("`--{FLAG_EMIT}=wasm-wat` needs a linked binary, so it cannot be combined with `--{FLAG_NO_LINK}`");
+        }
+    }
+
+    let threading = threading(matches);
 
     let wasm_dev_backend = matches!(opt_level, OptLevel::Development)
         && matches!(code_gen_backend, CodeGenBackend::Wasm);
@@ -685,9 +1031,11 @@ pub fn build(
         backend: code_gen_backend,
         opt_level,
         emit_debug_info,
+        emit,
     };
 
-    let load_config = standard_load_config(&triple, build_ordering, threading);
+    let render = render_target(matches);
+    let load_config = standard_load_config(&triple, build_ordering, threading, render);
 
     let res_binary_path = build_file(
         &arena,
@@ -709,22 +1057,43 @@ pub fn build(
             problems,
             total_time,
             expect_metadata,
+            emitted_paths,
+            source_paths,
         }) => {
+            watch::report_sources(&source_paths);
+
             match config {
                 BuildOnly => {
                     // If possible, report the generated executable name relative to the current dir.
-                    let generated_filename = binary_path
-                        .strip_prefix(env::current_dir().unwrap())
-                        .unwrap_or(&binary_path)
-                        .to_str()
-                        .unwrap();
+                    let cwd = env::current_dir().unwrap();
+                    let relative_to_cwd =
+                        |path: &PathBuf| path.strip_prefix(&cwd).unwrap_or(path).to_path_buf();
+                    let generated_filename = relative_to_cwd(&binary_path);
+                    let generated_filename = generated_filename.to_str().unwrap();
 
                     // No need to waste time freeing this memory,
                     // since the process is about to exit anyway.
                     // std::mem::forget(arena);
 
-                    problems.print_to_stdout(total_time);
-                    println!(" while successfully building:\n\n    {generated_filename}");
+                    if render == RenderTarget::Json {
+                        // Keep stdout to just the JSON reports.
+                        eprintln!("Successfully built {generated_filename}");
+
+                        for path in emitted_paths.iter() {
+                            eprintln!("Wrote {}", relative_to_cwd(path).display());
+                        }
+                    } else {
+                        problems.print_to_stdout(total_time);
+                        println!(" while successfully building:\n\n    {generated_filename}");
+
+                        if !emitted_paths.is_empty() {
+                            println!("\nand writing:\n");
+
+                            for path in emitted_paths.iter() {
+                                println!("    {}", relative_to_cwd(path).display());
+                            }
+                        }
+                    }
 
                     // Return a nonzero exit code if there were problems
                     Ok(problems.exit_code())
@@ -791,9 +1160,132 @@ pub fn build(
             }
         }
         Err(BuildFileError::ErrorModule { module, total_time }) => {
-            handle_error_module(module, total_time, filename, true)
+            watch::report_sources(module.sources.values().map(|(path, _)| path));
+
+            handle_error_module(module, total_time, filename, true, render)
+        }
+        Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem, render),
+    }
+}
+
+/// `roc dev --hot-reload`: runs the app like `roc dev --watch`, but while it is running,
+/// code changes are sent to it by rebuilding it as a shared library.
+pub fn dev_hot_reload(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
+    let roc_file = Path::new(matches.value_of_os(ROC_FILE).unwrap());
+
+    watch::hot_reload(roc_file, |library_path| {
+        build_hot_reload_library(matches, &triple, roc_file, library_path)
+    })
+}
+
+/// Builds the app as a shared library, and moves it to `library_path` once it is complete,
+/// so the host never loads a half-written library.
+/// Returns the paths of the modules that were loaded, if it could be built.
+fn build_hot_reload_library(
+    matches: &ArgMatches,
+    triple: &Triple,
+    roc_file: &Path,
+    library_path: &Path,
+) -> Option<Vec<PathBuf>> {
+    use roc_build::program::build_file;
+    use roc_packaging::cache;
+
+    let arena = Bump::new();
+
+    let opt_level = build_opt_level(matches);
+
+    let backend = match matches.is_present(FLAG_DEV) {
+        true => CodeGenBackend::Assembly,
+        false => CodeGenBackend::Llvm(LlvmBackendMode::Binary),
+    };
+
+    let code_gen_options = CodeGenOptions {
+        backend,
+        opt_level,
+        emit_debug_info: matches.is_present(FLAG_DEBUG),
+        emit: EmitOptions::default(),
+    };
+
+    let threading = threading(matches);
+
+    let render = RenderTarget::ColorTerminal;
+    let load_config = standard_load_config(triple, BuildOrdering::BuildIfChecks, threading, render);
+
+    let res_binary_path = build_file(
+        &arena,
+        triple,
+        roc_file.to_path_buf(),
+        code_gen_options,
+        matches.is_present(FLAG_TIME),
+        LinkType::Dylib,
+        // The surgical linker only produces executables.
+        LinkingStrategy::Legacy,
+        // Only the app is rebuilt; the running host stays as it is.
+        true,
+        None,
+        RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+        load_config,
+    );
+
+    match res_binary_path {
+        Ok(BuiltFile {
+            binary_path,
+            problems,
+            total_time,
+            source_paths,
+            ..
+        }) => {
+            if problems.fatally_errored {
+                problems.print_to_stdout(total_time);
+                println!(".");
+
+                return None;
+            }
+
+            if problems.warnings > 0 {
+                problems.print_to_stdout(total_time);
+                println!(".");
+            }
+
+            let built_path = match triple.operating_system {
+                OperatingSystem::Windows => binary_path.with_extension("dll"),
+                OperatingSystem::Darwin | OperatingSystem::MacOSX { .. } => {
+                    binary_path.with_extension("dylib")
+                }
+                _ => binary_path.with_extension("so.1.0"),
+            };
+
+            // Copy it next to its destination first, because renaming is only atomic
+            // within the same file system.
+            let tmp_path = library_path.with_extension("tmp");
+            let moved = std::fs::copy(&built_path, &tmp_path)
+                .and_then(|_| std::fs::rename(&tmp_path, library_path))
+                .and_then(|()| std::fs::remove_file(&built_path));
+
+            match moved {
+                Ok(()) => Some(source_paths),
+                Err(err) => {
+                    eprintln!(
+                        "Could not move {} to {}: {}",
+                        built_path.display(),
+                        library_path.display(),
+                        err
+                    );
+
+                    None
+                }
+            }
+        }
+        Err(BuildFileError::ErrorModule { module, total_time }) => {
+            let _ = handle_error_module(module, total_time, roc_file.as_os_str(), false, render);
+
+            None
+        }
+        Err(BuildFileError::LoadingProblem(problem)) => {
+            let _ = handle_loading_problem(problem, render);
+
+            None
         }
-        Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
     }
 }
 
@@ -1044,11 +1536,16 @@ fn roc_dev_native(
 
             std::process::exit(1)
         }
-        1.. => {
+        child_pid @ 1.. => {
             let sigchld = Arc::new(AtomicBool::new(false));
             signal_hook::flag::register(signal_hook::consts::SIGCHLD, Arc::clone(&sigchld))
                 .unwrap();
 
+            // Stop waiting for the app when asked to exit (e.g. by `roc dev --watch`),
+            // and pass that request on to the app below.
+            signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&sigchld))
+                .unwrap();
+
             loop {
                 match memory.wait_for_child(sigchld.clone()) {
                     ChildProcessMsg::Terminate => break,
@@ -1081,6 +1578,14 @@ fn roc_dev_native(
                 }
             }
 
+            // If the app is still running, we were asked to exit; make sure the app does too.
+            unsafe {
+                if libc::waitpid(child_pid, std::ptr::null_mut(), libc::WNOHANG) == 0 {
+                    libc::kill(child_pid, libc::SIGTERM);
+                    libc::waitpid(child_pid, std::ptr::null_mut(), 0);
+                }
+            }
+
             std::process::exit(0)
         }
         _ => unreachable!(),
@@ -2,15 +2,18 @@
 use roc_build::link::LinkType;
 use roc_build::program::check_file;
 use roc_cli::{
-    build_app, format, test, BuildConfig, FormatMode, Target, CMD_BUILD, CMD_CHECK, CMD_DEV,
-    CMD_DOCS, CMD_EDIT, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_REPL, CMD_RUN, CMD_TEST,
-    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_LIB, FLAG_NO_LINK, FLAG_TARGET, FLAG_TIME,
-    GLUE_DIR, GLUE_SPEC, ROC_FILE,
+    build_app, deps, dev_hot_reload, format, format_stdin, render_target, run_language_server,
+    test, watch, BuildConfig, FormatMode, FormatRange, Target, CMD_BUILD, CMD_CHECK, CMD_DEPS,
+    CMD_DEV, CMD_DOCS, CMD_EDIT, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_LANGUAGE_SERVER,
+    CMD_REPL, CMD_RUN, CMD_TEST, CMD_VENDOR, CMD_VERSION, DIRECTORY_OR_FILES, FLAG_BYTES,
+    FLAG_CHECK, FLAG_HOT_RELOAD, FLAG_LIB, FLAG_LINES, FLAG_MAX_WIDTH, FLAG_NO_LINK, FLAG_STDIN,
+    FLAG_TARGET, FLAG_TIME, FLAG_WATCH, GLUE_DIR, GLUE_SPEC, ROC_FILE, VENDOR_DIR,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::user_error;
 use roc_load::{LoadingProblem, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_reporting::report::RenderTarget;
 use std::fs::{self, FileType};
 use std::io;
 use std::path::{Path, PathBuf};
@@ -63,7 +66,9 @@ fn main() -> io::Result<()> {
             }
         }
         Some((CMD_TEST, matches)) => {
-            if matches.is_present(ROC_FILE) {
+            if matches.is_present(FLAG_WATCH) {
+                watch(Path::new(matches.value_of_os(ROC_FILE).unwrap()))
+            } else if matches.is_present(ROC_FILE) {
                 test(matches, Triple::host())
             } else {
                 eprintln!("What .roc file do you want to test? Specify it at the end of the `roc test` command.");
@@ -72,7 +77,11 @@ fn main() -> io::Result<()> {
             }
         }
         Some((CMD_DEV, matches)) => {
-            if matches.is_present(ROC_FILE) {
+            if matches.is_present(FLAG_HOT_RELOAD) {
+                dev_hot_reload(matches, Triple::host())
+            } else if matches.is_present(FLAG_WATCH) {
+                watch(Path::new(matches.value_of_os(ROC_FILE).unwrap()))
+            } else if matches.is_present(ROC_FILE) {
                 build(
                     matches,
                     BuildConfig::BuildAndRunIfNoErrors,
@@ -99,6 +108,38 @@ fn main() -> io::Result<()> {
                 Ok(1)
             }
         }
+        Some((CMD_VENDOR, matches)) => {
+            let input_path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
+            let vendor_dir = Path::new(matches.value_of_os(VENDOR_DIR).unwrap());
+
+            match roc_packaging::vendor::vendor(
+                input_path,
+                vendor_dir,
+                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+            ) {
+                Ok(vendored) => {
+                    println!(
+                        "Vendored {} package(s) into {}\n\nTo build using them instead of the network, set:\n\n    {}={}\n",
+                        vendored.len(),
+                        vendor_dir.display(),
+                        cache::ROC_PACKAGE_MIRRORS,
+                        vendor_dir.display()
+                    );
+
+                    Ok(0)
+                }
+                Err(problem) => {
+                    eprintln!(
+                        "Unable to vendor the packages {} depends on: {:?}",
+                        input_path.display(),
+                        problem
+                    );
+
+                    Ok(1)
+                }
+            }
+        }
+        Some((CMD_DEPS, matches)) => deps(matches),
         Some((CMD_GEN_STUB_LIB, matches)) => {
             let input_path = Path::new(matches.value_of_os(ROC_FILE).unwrap());
             let target: Target = matches.value_of_t(FLAG_TARGET).unwrap_or_default();
@@ -144,13 +185,17 @@ fn main() -> io::Result<()> {
                 Some(n) => Threading::AtMost(n),
             };
 
+            let render = render_target(matches);
+
             match check_file(
                 &arena,
                 roc_file_path,
                 emit_timings,
                 RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
                 threading,
+                render,
             ) {
+                Ok((problems, _)) if render == RenderTarget::Json => Ok(problems.exit_code()),
                 Ok((problems, total_time)) => {
                     println!(
                         "\x1B[{}m{}\x1B[39m {} and \x1B[{}m{}\x1B[39m {} found in {} ms.",
@@ -182,6 +227,11 @@ fn main() -> io::Result<()> {
                     Ok(problems.exit_code())
                 }
 
+                Err(LoadingProblem::FormattedReport(report)) if render == RenderTarget::Json => {
+                    println!("{}", report);
+
+                    Ok(1)
+                }
                 Err(LoadingProblem::FormattedReport(report)) => {
                     print!("{}", report);
 
@@ -193,6 +243,7 @@ fn main() -> io::Result<()> {
             }
         }
         Some((CMD_REPL, _)) => Ok(roc_repl_cli::main()),
+        Some((CMD_LANGUAGE_SERVER, _)) => run_language_server(),
         Some((CMD_EDIT, matches)) => {
             match matches
                 .values_of_os(DIRECTORY_OR_FILES)
@@ -217,42 +268,61 @@ fn main() -> io::Result<()> {
             Ok(0)
         }
         Some((CMD_FORMAT, matches)) => {
-            let maybe_values = matches.values_of_os(DIRECTORY_OR_FILES);
-
-            let mut values: Vec<OsString> = Vec::new();
-
-            match maybe_values {
-                None => {
-                    let mut os_string_values: Vec<OsString> = Vec::new();
-                    read_all_roc_files(
-                        &std::env::current_dir()?.as_os_str().to_os_string(),
-                        &mut os_string_values,
-                    )?;
-                    for os_string in os_string_values {
-                        values.push(os_string);
+            let format_mode = match matches.is_present(FLAG_CHECK) {
+                true => FormatMode::CheckOnly,
+                false => FormatMode::Format,
+            };
+
+            let opt_range = match (matches.value_of(FLAG_LINES), matches.value_of(FLAG_BYTES)) {
+                (Some(lines), _) => Some(FormatRange::parse_lines(lines)),
+                (None, Some(bytes)) => Some(FormatRange::parse_bytes(bytes)),
+                (None, None) => None,
+            }
+            .transpose();
+
+            // This was already validated by clap
+            let opt_max_width = matches
+                .value_of(FLAG_MAX_WIDTH)
+                .map(|width| width.parse::<usize>().unwrap());
+
+            let format_result = if matches.is_present(FLAG_STDIN) {
+                opt_range.and_then(|opt_range| format_stdin(format_mode, opt_range, opt_max_width))
+            } else {
+                let maybe_values = matches.values_of_os(DIRECTORY_OR_FILES);
+
+                let mut values: Vec<OsString> = Vec::new();
+
+                match maybe_values {
+                    None => {
+                        let mut os_string_values: Vec<OsString> = Vec::new();
+                        read_all_roc_files(
+                            &std::env::current_dir()?.as_os_str().to_os_string(),
+                            &mut os_string_values,
+                        )?;
+                        for os_string in os_string_values {
+                            values.push(os_string);
+                        }
                     }
-                }
-                Some(os_values) => {
-                    for os_str in os_values {
-                        values.push(os_str.to_os_string());
+                    Some(os_values) => {
+                        for os_str in os_values {
+                            values.push(os_str.to_os_string());
+                        }
                     }
                 }
-            }
 
-            let mut roc_files = Vec::new();
+                let mut roc_files = Vec::new();
 
-            // Populate roc_files
-            for os_str in values {
-                let metadata = fs::metadata(os_str.clone())?;
-                roc_files_recursive(os_str.as_os_str(), metadata.file_type(), &mut roc_files)?;
-            }
+                // Populate roc_files
+                for os_str in values {
+                    let metadata = fs::metadata(os_str.clone())?;
+                    roc_files_recursive(os_str.as_os_str(), metadata.file_type(), &mut roc_files)?;
+                }
 
-            let format_mode = match matches.is_present(FLAG_CHECK) {
-                true => FormatMode::CheckOnly,
-                false => FormatMode::Format,
+                opt_range
+                    .and_then(|opt_range| format(roc_files, format_mode, opt_range, opt_max_width))
             };
 
-            let format_exit_code = match format(roc_files, format_mode) {
+            let format_exit_code = match format_result {
                 Ok(_) => 0,
                 Err(message) => {
                     eprintln!("{}", message);
@@ -0,0 +1,211 @@
+//! Machine-readable reports of a `roc test` run, for CI dashboards.
+use roc_collections::MutMap;
+use roc_module::symbol::Symbol;
+use roc_region::all::LineColumnRegion;
+use roc_repl_expect::run::ToplevelExpectResult;
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::toplevel_expects::{sorted_descriptions, ExpectDescription};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    JUnit,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn from_flag(value: &str) -> Self {
+        match value {
+            "junit" => ReportFormat::JUnit,
+            "json" => ReportFormat::Json,
+            other => unreachable!("clap only accepts known report formats, not {other:?}"),
+        }
+    }
+}
+
+struct Entry<'a> {
+    description: &'a ExpectDescription,
+    passed: bool,
+    duration: Duration,
+    /// The failure as it was printed to the terminal, without colors
+    failure: Option<String>,
+}
+
+pub fn render(
+    format: ReportFormat,
+    descriptions: &MutMap<Symbol, ExpectDescription>,
+    results: &[ToplevelExpectResult],
+    total_time: Duration,
+) -> String {
+    let results: MutMap<Symbol, &ToplevelExpectResult> = results
+        .iter()
+        .map(|result| (result.expect.symbol, result))
+        .collect();
+
+    // Report the expects in source order, rather than in the order they happened to run
+    let entries: Vec<Entry> = sorted_descriptions(descriptions)
+        .into_iter()
+        .filter_map(|(symbol, description)| {
+            let result = results.get(symbol)?;
+
+            Some(Entry {
+                description,
+                passed: result.passed,
+                duration: result.duration,
+                failure: (!result.passed).then(|| strip_colors(&result.rendered)),
+            })
+        })
+        .collect();
+
+    match format {
+        ReportFormat::JUnit => render_junit(&entries, total_time),
+        ReportFormat::Json => render_json(&entries, total_time),
+    }
+}
+
+fn strip_colors(text: &str) -> String {
+    let bytes = strip_ansi_escapes::strip(text).unwrap_or_else(|_| text.as_bytes().to_vec());
+
+    String::from_utf8_lossy(&bytes).trim().to_string()
+}
+
+/// The format understood by most CI systems, see https://github.com/testmoapp/junitxml
+/// Each module becomes a `<testsuite>` and each `expect` a `<testcase>`.
+fn render_junit(entries: &[Entry], total_time: Duration) -> String {
+    let mut xml = String::new();
+    let failures = entries.iter().filter(|entry| !entry.passed).count();
+
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        xml,
+        r#"<testsuites name="roc test" tests="{}" failures="{}" time="{}">"#,
+        entries.len(),
+        failures,
+        total_time.as_secs_f64()
+    )
+    .unwrap();
+
+    // entries are sorted by module, so each module's entries are contiguous
+    let mut remaining = entries;
+
+    while let Some(first) = remaining.first() {
+        let module_name = &first.description.module_name;
+        let len = remaining
+            .iter()
+            .take_while(|entry| &entry.description.module_name == module_name)
+            .count();
+        let (suite, rest) = remaining.split_at(len);
+        remaining = rest;
+
+        writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" time="{}">"#,
+            escape_xml(module_name),
+            suite.len(),
+            suite.iter().filter(|entry| !entry.passed).count(),
+            suite
+                .iter()
+                .map(|entry| entry.duration)
+                .sum::<Duration>()
+                .as_secs_f64(),
+        )
+        .unwrap();
+
+        for entry in suite {
+            let description = entry.description;
+
+            write!(
+                xml,
+                r#"    <testcase name="{}" classname="{}" file="{}" line="{}" time="{}""#,
+                escape_xml(&description.name()),
+                escape_xml(module_name),
+                escape_xml(&description.path.to_string_lossy()),
+                description.line(),
+                entry.duration.as_secs_f64(),
+            )
+            .unwrap();
+
+            match &entry.failure {
+                None => xml.push_str("/>\n"),
+                Some(failure) => {
+                    writeln!(
+                        xml,
+                        ">\n      <failure message=\"{} failed\">{}</failure>\n    </testcase>",
+                        escape_xml(&description.location()),
+                        escape_xml(failure)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+
+    xml
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(ch),
+            // Other control characters are not allowed in XML 1.0 documents at all
+            ch if ch.is_control() => {}
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// Regions are 1-based and end-exclusive, like in `--error-format=json`.
+fn render_json(entries: &[Entry], total_time: Duration) -> String {
+    let region_json = |region: LineColumnRegion| {
+        serde_json::json!({
+            "start": { "line": region.start.line + 1, "column": region.start.column + 1 },
+            "end": { "line": region.end.line + 1, "column": region.end.column + 1 },
+        })
+    };
+
+    let expects: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            let description = entry.description;
+
+            serde_json::json!({
+                "module": description.module_name,
+                "name": description.name(),
+                "doc_comment": description.doc_comment,
+                "path": description.path.to_string_lossy(),
+                "region": region_json(description.region),
+                "result": if entry.passed { "passed" } else { "failed" },
+                "duration_ms": entry.duration.as_secs_f64() * 1000.0,
+                "failure": entry.failure,
+            })
+        })
+        .collect();
+
+    let passed = entries.iter().filter(|entry| entry.passed).count();
+
+    let json = serde_json::json!({
+        "passed": passed,
+        "failed": entries.len() - passed,
+        "duration_ms": total_time.as_secs_f64() * 1000.0,
+        "expects": expects,
+    });
+
+    let mut rendered = serde_json::to_string_pretty(&json).unwrap();
+    rendered.push('\n');
+
+    rendered
+}
@@ -0,0 +1,213 @@
+//! Choosing which top-level `expect`s `roc test` runs, and describing them to the user.
+use clap::ArgMatches;
+use roc_collections::MutMap;
+use roc_load::MonomorphizedModule;
+use roc_module::symbol::Symbol;
+use roc_region::all::{LineColumnRegion, LineInfo, Region};
+use roc_repl_expect::run::ToplevelExpectResult;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::{FLAG_FILTER, FLAG_MODULE};
+
+/// Which `expect`s to run, as given by `--module` and `--filter`
+#[derive(Debug, Default)]
+pub struct ExpectFilter {
+    modules: Vec<String>,
+    patterns: Vec<String>,
+}
+
+impl ExpectFilter {
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let values = |flag| -> Vec<String> {
+            matches
+                .values_of(flag)
+                .map(|values| values.map(String::from).collect())
+                .unwrap_or_default()
+        };
+
+        Self {
+            modules: values(FLAG_MODULE),
+            patterns: values(FLAG_FILTER),
+        }
+    }
+
+    /// Every `expect` in the module with the given name
+    pub fn only_module(module_name: &str) -> Self {
+        Self {
+            modules: vec![module_name.to_string()],
+            patterns: Vec::new(),
+        }
+    }
+
+    fn matches(&self, description: &ExpectDescription) -> bool {
+        let module_matches =
+            self.modules.is_empty() || self.modules.contains(&description.module_name);
+
+        let pattern_matches = self.patterns.is_empty()
+            || self.patterns.iter().any(|pattern| {
+                description.location().contains(pattern.as_str())
+                    || description
+                        .doc_comment
+                        .iter()
+                        .any(|doc| doc.contains(pattern.as_str()))
+            });
+
+        module_matches && pattern_matches
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpectDescription {
+    pub module_name: String,
+    pub path: PathBuf,
+    pub region: LineColumnRegion,
+    /// The `##` comment directly above the `expect`, without the leading `##`
+    pub doc_comment: Option<String>,
+}
+
+impl ExpectDescription {
+    /// 1-based, like the line numbers editors show
+    pub fn line(&self) -> u32 {
+        self.region.start.line + 1
+    }
+
+    /// e.g. `Parser:42`
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.module_name, self.line())
+    }
+
+    /// The first line of the doc comment, or else the location
+    pub fn name(&self) -> String {
+        match self
+            .doc_comment
+            .as_deref()
+            .and_then(|doc| doc.lines().next())
+        {
+            Some(first_line) if !first_line.trim().is_empty() => first_line.trim().to_string(),
+            _ => self.location(),
+        }
+    }
+}
+
+/// Drops the top-level `expect`s that the filter rejects, so they are never compiled or run.
+/// Returns a description of every remaining `expect`.
+pub fn select_expects(
+    loaded: &mut MonomorphizedModule,
+    filter: &ExpectFilter,
+) -> MutMap<Symbol, ExpectDescription> {
+    let mut line_infos = MutMap::default();
+
+    let mut describe = |symbol: Symbol, region: Region| {
+        let module_id = symbol.module_id();
+        let (path, source) = &loaded.sources[&module_id];
+        let line_info = line_infos
+            .entry(module_id)
+            .or_insert_with(|| LineInfo::new(source));
+        let region = line_info.convert_region(region);
+
+        ExpectDescription {
+            module_name: loaded.interns.module_name(module_id).as_str().to_string(),
+            path: path.clone(),
+            region,
+            doc_comment: doc_comment_above(source, region.start.line as usize),
+        }
+    };
+
+    let mut descriptions = MutMap::default();
+    let toplevel_expects = &mut loaded.toplevel_expects;
+
+    for expects in [&mut toplevel_expects.pure, &mut toplevel_expects.fx] {
+        let mut kept = Vec::with_capacity(expects.len());
+
+        for (symbol, region) in std::mem::take(expects) {
+            let description = describe(symbol, region);
+
+            if filter.matches(&description) {
+                descriptions.insert(symbol, description);
+                kept.push((symbol, region));
+            }
+        }
+
+        expects.extend(kept);
+    }
+
+    descriptions
+}
+
+/// Sorted by module name, then by line, which is the order they appear in the source.
+pub fn sorted_descriptions(
+    descriptions: &MutMap<Symbol, ExpectDescription>,
+) -> Vec<(&Symbol, &ExpectDescription)> {
+    let mut sorted: Vec<_> = descriptions.iter().collect();
+    sorted.sort_by(|(_, a), (_, b)| (&a.module_name, a.line()).cmp(&(&b.module_name, b.line())));
+
+    sorted
+}
+
+pub fn print_list(descriptions: &MutMap<Symbol, ExpectDescription>) {
+    for (_, description) in sorted_descriptions(descriptions) {
+        println!(
+            "{}:{}  {}",
+            description.path.display(),
+            description.line(),
+            description.name()
+        );
+    }
+}
+
+/// One line per `expect`, e.g. `PASS    3 ms  Parser:42  parses an empty list`
+pub fn print_results(
+    descriptions: &MutMap<Symbol, ExpectDescription>,
+    results: &[ToplevelExpectResult],
+) {
+    println!();
+
+    for result in results {
+        let description = &descriptions[&result.expect.symbol];
+        let (color, status) = match result.passed {
+            true => (32, "PASS"),  // green
+            false => (31, "FAIL"), // red
+        };
+        let name = description.name();
+
+        if name == description.location() {
+            println!(
+                "\x1B[{color}m{status}\x1B[39m {}  {name}",
+                format_duration(result.duration),
+            );
+        } else {
+            println!(
+                "\x1B[{color}m{status}\x1B[39m {}  {}  {name}",
+                format_duration(result.duration),
+                description.location(),
+            );
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:>6} ms", duration.as_millis())
+}
+
+/// Collects the consecutive `##` lines right above the given (0-based) line.
+fn doc_comment_above(source: &str, line: usize) -> Option<String> {
+    let lines: Vec<&str> = source.lines().take(line).collect();
+
+    let doc_lines: Vec<&str> = lines
+        .iter()
+        .rev()
+        .map(|line| line.trim_start())
+        .take_while(|line| line.starts_with("##"))
+        .map(|line| {
+            let text = line.trim_start_matches('#');
+            text.strip_prefix(' ').unwrap_or(text)
+        })
+        .collect();
+
+    if doc_lines.is_empty() {
+        None
+    } else {
+        Some(doc_lines.into_iter().rev().collect::<Vec<_>>().join("\n"))
+    }
+}
@@ -0,0 +1,484 @@
+//! Provides `--watch` for `roc dev` and `roc test`: the command is run in a
+//! child process, and run again whenever one of the modules it loaded changes.
+//! With `roc dev --hot-reload`, the app is instead rebuilt as a shared library
+//! which the running platform can load.
+use roc_collections::MutMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The watched child writes the paths of the modules it loaded to the file named by this
+/// environment variable, one per line.
+const SOURCES_VAR: &str = "ROC_WATCH_SOURCES";
+
+/// Where the host finds the library built by `--hot-reload`.
+/// This must match `roc_std::hot_reload::LIBRARY_VAR`.
+const LIBRARY_VAR: &str = "ROC_HOT_RELOAD_LIBRARY";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Editors often save a file in several steps, and a save can touch several files.
+/// Wait until nothing has changed for this long before starting over.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How long the child gets to exit after being asked to, before it is killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Tells the `--watch` process which files to watch, if this process is being watched.
+/// Should be called once all modules have been loaded, whether or not they had errors.
+pub fn report_sources<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) {
+    let sources_file = match env::var_os(SOURCES_VAR) {
+        Some(sources_file) => PathBuf::from(sources_file),
+        None => return,
+    };
+
+    let mut contents = String::new();
+
+    for path in paths {
+        contents.push_str(&path.to_string_lossy());
+        contents.push('\n');
+    }
+
+    // Write to a temporary file first, so the watcher never reads half a list.
+    let tmp_file = sources_file.with_extension("tmp");
+
+    if let Err(err) =
+        fs::write(&tmp_file, contents).and_then(|()| fs::rename(&tmp_file, &sources_file))
+    {
+        eprintln!("Could not tell `--watch` which files to watch: {}", err);
+    }
+}
+
+/// Runs this same `roc` command without `--watch` in a child process, and restarts it
+/// whenever any of the modules it loaded change, until interrupted.
+pub fn watch(roc_file: &Path) -> io::Result<i32> {
+    run(roc_file, None)
+}
+
+/// Like [watch], but while the app is running, changes are sent to it by rebuilding it
+/// as a shared library instead of restarting it. The host finds that library through
+/// `roc_std::hot_reload`.
+///
+/// `build_library` should build the app as a shared library at the given path, and
+/// return the paths of the modules it loaded, or `None` if it could not be built.
+pub fn hot_reload(
+    roc_file: &Path,
+    mut build_library: impl FnMut(&Path) -> Option<Vec<PathBuf>>,
+) -> io::Result<i32> {
+    run(roc_file, Some(&mut build_library))
+}
+
+type BuildLibrary<'a> = &'a mut dyn FnMut(&Path) -> Option<Vec<PathBuf>>;
+
+fn run(roc_file: &Path, mut build_library: Option<BuildLibrary>) -> io::Result<i32> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&interrupted))?;
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&interrupted))?;
+
+    let roc_exe = env::current_exe()?;
+    let args = args_without_watch(env::args_os().skip(1));
+    let sources_file = env::temp_dir().join(format!("roc_watch_{}", process::id()));
+    let library_file = env::temp_dir().join(format!("roc_hot_reload_{}", process::id()));
+
+    let mut watched = WatchedFiles::new(roc_file);
+
+    loop {
+        let _ = fs::remove_file(&sources_file);
+        let _ = fs::remove_file(&library_file);
+
+        let mut command = Command::new(&roc_exe);
+        command.args(&args).env(SOURCES_VAR, &sources_file);
+
+        if build_library.is_some() {
+            command.env(LIBRARY_VAR, &library_file);
+        }
+
+        let mut child = command.spawn()?;
+        let mut sources_modified = None;
+        let mut running = true;
+
+        loop {
+            let changed = loop {
+                if interrupted.load(Ordering::Relaxed) {
+                    stop(&mut child)?;
+                    let _ = fs::remove_file(&sources_file);
+                    let _ = fs::remove_file(&library_file);
+
+                    return Ok(0);
+                }
+
+                if running {
+                    if let Some(status) = child.try_wait()? {
+                        running = false;
+
+                        match status.code() {
+                            Some(code) => println!(
+                                "\n\x1B[36mExited with code {code}. Waiting for changes…\x1B[39m"
+                            ),
+                            None => println!("\n\x1B[36mExited. Waiting for changes…\x1B[39m"),
+                        }
+                    }
+                }
+
+                let modified = modified_time(&sources_file);
+
+                if modified.is_some() && modified != sources_modified {
+                    sources_modified = modified;
+
+                    if let Ok(contents) = fs::read_to_string(&sources_file) {
+                        watched.replace(contents.lines().map(PathBuf::from));
+                    }
+                }
+
+                if let Some(path) = watched.changed() {
+                    break path;
+                }
+
+                thread::sleep(POLL_INTERVAL);
+            };
+
+            // Wait for a quiet period, so that rapid saves only restart once.
+            debounce(DEBOUNCE, POLL_INTERVAL, || watched.changed().is_some());
+
+            let build_library = match build_library.as_mut() {
+                Some(build_library) if child.try_wait()?.is_none() => build_library,
+                _ => {
+                    stop(&mut child)?;
+
+                    println!(
+                        "\n\x1B[36m{} changed. Restarting…\n{}\x1B[39m\n",
+                        changed.display(),
+                        "─".repeat(80)
+                    );
+
+                    break;
+                }
+            };
+
+            println!(
+                "\n\x1B[36m{} changed. Rebuilding the app…\x1B[39m\n",
+                changed.display()
+            );
+
+            match build_library(&library_file) {
+                Some(source_paths) => {
+                    watched.replace(source_paths.into_iter());
+
+                    println!("\n\x1B[36mThe app was rebuilt, and will be reloaded by the platform.\x1B[39m\n");
+                }
+                None => {
+                    println!("\n\x1B[36mThe app could not be rebuilt, so the previous version keeps running.\x1B[39m\n");
+                }
+            }
+        }
+    }
+}
+
+/// Polls `changed` until it has not reported a change for `quiet`.
+fn debounce(quiet: Duration, poll_interval: Duration, mut changed: impl FnMut() -> bool) {
+    let mut last_change = Instant::now();
+
+    while last_change.elapsed() < quiet {
+        thread::sleep(poll_interval);
+
+        if changed() {
+            last_change = Instant::now();
+        }
+    }
+}
+
+/// The arguments this process was started with (without the executable), minus the
+/// `--watch` and `--hot-reload` flags. Everything after `--` is meant for the app, so
+/// flags there are kept.
+fn args_without_watch(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut args: Vec<OsString> = args.into_iter().collect();
+
+    for flag in [crate::FLAG_WATCH, crate::FLAG_HOT_RELOAD] {
+        let flag = format!("--{}", flag);
+
+        if let Some(index) = args
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .position(|arg| *arg == *flag)
+        {
+            args.remove(index);
+        }
+    }
+
+    args
+}
+
+/// Asks the child to exit, so `roc dev` gets the chance to stop the app it started,
+/// and kills it if it does not exit in time.
+fn stop(child: &mut Child) -> io::Result<()> {
+    if child.try_wait()?.is_some() {
+        return Ok(());
+    }
+
+    #[cfg(target_family = "unix")]
+    {
+        unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) };
+
+        let start = Instant::now();
+
+        while start.elapsed() < STOP_TIMEOUT {
+            if child.try_wait()?.is_some() {
+                return Ok(());
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    child.kill()?;
+    child.wait()?;
+
+    Ok(())
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// The files being watched, with the modification time each had when last checked.
+struct WatchedFiles {
+    root: PathBuf,
+    files: MutMap<PathBuf, Option<SystemTime>>,
+}
+
+impl WatchedFiles {
+    fn new(root: &Path) -> Self {
+        let mut watched = Self {
+            root: root.to_path_buf(),
+            files: MutMap::default(),
+        };
+
+        watched.replace(std::iter::empty());
+
+        watched
+    }
+
+    /// Watches these files (and the root module) instead of the ones watched so far.
+    /// Files which do not exist, like those of builtin modules, are not watched.
+    fn replace(&mut self, paths: impl Iterator<Item = PathBuf>) {
+        let mut files = MutMap::default();
+
+        for path in std::iter::once(self.root.clone()).chain(paths) {
+            // Keep the time from before the file was loaded, so that a change made
+            // while the child was still loading is not missed.
+            let modified = match self.files.remove(&path) {
+                Some(modified) => modified,
+                None => modified_time(&path),
+            };
+
+            if modified.is_some() || path == self.root {
+                files.insert(path, modified);
+            }
+        }
+
+        self.files = files;
+    }
+
+    /// Returns a file which changed since the last check, if any did.
+    fn changed(&mut self) -> Option<PathBuf> {
+        let mut changed = None;
+
+        for (path, modified) in self.files.iter_mut() {
+            let now = modified_time(path);
+
+            if now != *modified {
+                *modified = now;
+                changed = Some(path.clone());
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os_strings(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    /// Changes a file, making sure its modification time is different from before even on
+    /// file systems with a coarse timestamp resolution.
+    fn touch(path: &Path) {
+        let before = modified_time(path);
+
+        loop {
+            fs::write(path, "# changed").unwrap();
+
+            if modified_time(path) != before {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn args_without_watch_removes_flags() {
+        assert_eq!(
+            args_without_watch(os_strings(&["dev", "--watch", "main.roc"])),
+            os_strings(&["dev", "main.roc"])
+        );
+        assert_eq!(
+            args_without_watch(os_strings(&["dev", "--hot-reload", "main.roc", "--watch"])),
+            os_strings(&["dev", "main.roc"])
+        );
+    }
+
+    #[test]
+    fn args_without_watch_keeps_app_args() {
+        assert_eq!(
+            args_without_watch(os_strings(&["dev", "--watch", "main.roc", "--", "--watch"])),
+            os_strings(&["dev", "main.roc", "--", "--watch"])
+        );
+        assert_eq!(
+            args_without_watch(os_strings(&["test", "main.roc", "--watched"])),
+            os_strings(&["test", "main.roc", "--watched"])
+        );
+    }
+
+    #[test]
+    fn args_without_watch_keeps_flags_after_separator() {
+        assert_eq!(
+            args_without_watch(os_strings(&[
+                "dev",
+                "--watch",
+                "main.roc",
+                "--",
+                "--hot-reload"
+            ])),
+            os_strings(&["dev", "main.roc", "--", "--hot-reload"])
+        );
+        assert_eq!(
+            args_without_watch(os_strings(&["dev", "main.roc", "--", "--watch"])),
+            os_strings(&["dev", "main.roc", "--", "--watch"])
+        );
+    }
+
+    #[test]
+    fn args_without_watch_keeps_app_watch_flag_with_hot_reload() {
+        assert_eq!(
+            args_without_watch(os_strings(&[
+                "dev",
+                "--hot-reload",
+                "main.roc",
+                "--",
+                "--watch"
+            ])),
+            os_strings(&["dev", "main.roc", "--", "--watch"])
+        );
+        assert_eq!(
+            args_without_watch(os_strings(&["dev", "main.roc", "--", "--hot-reload"])),
+            os_strings(&["dev", "main.roc", "--", "--hot-reload"])
+        );
+    }
+
+    #[test]
+    fn watched_files_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("main.roc");
+        let dep = dir.path().join("Dep.roc");
+
+        fs::write(&root, "").unwrap();
+        fs::write(&dep, "").unwrap();
+
+        let mut watched = WatchedFiles::new(&root);
+        assert_eq!(watched.changed(), None);
+
+        // Not watched until the child reports it.
+        touch(&dep);
+        assert_eq!(watched.changed(), None);
+
+        watched.replace([dep.clone()].into_iter());
+        touch(&dep);
+        assert_eq!(watched.changed(), Some(dep.clone()));
+        assert_eq!(watched.changed(), None);
+
+        touch(&root);
+        assert_eq!(watched.changed(), Some(root.clone()));
+
+        fs::remove_file(&dep).unwrap();
+        assert_eq!(watched.changed(), Some(dep));
+        assert_eq!(watched.changed(), None);
+    }
+
+    #[test]
+    fn watched_files_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("main.roc");
+        let dep = dir.path().join("Dep.roc");
+        let builtin = dir.path().join("Str.roc");
+
+        fs::write(&root, "").unwrap();
+        fs::write(&dep, "").unwrap();
+
+        let mut watched = WatchedFiles::new(&root);
+        watched.replace([dep.clone(), builtin].into_iter());
+
+        // Files which do not exist are not watched, but the root module always is.
+        let mut files: Vec<_> = watched.files.keys().cloned().collect();
+        files.sort();
+        assert_eq!(files, vec![dep.clone(), root.clone()]);
+
+        // A change made before the new list came in is still noticed.
+        touch(&dep);
+        watched.replace([dep.clone()].into_iter());
+        assert_eq!(watched.changed(), Some(dep.clone()));
+
+        // Files missing from the new list are no longer watched.
+        watched.replace(std::iter::empty());
+        touch(&dep);
+        assert_eq!(watched.changed(), None);
+        assert_eq!(watched.files.keys().collect::<Vec<_>>(), vec![&root]);
+    }
+
+    #[test]
+    fn debounce_waits_for_quiet() {
+        let quiet = Duration::from_millis(50);
+        let mut polls = 0;
+        let mut last_change = Instant::now();
+
+        // Keeps changing for the first few polls, like an editor saving in several steps.
+        debounce(quiet, Duration::from_millis(5), || {
+            polls += 1;
+
+            if polls <= 3 {
+                last_change = Instant::now();
+                true
+            } else {
+                false
+            }
+        });
+
+        assert!(polls > 3);
+        assert!(last_change.elapsed() >= quiet);
+    }
+
+    #[test]
+    fn debounce_returns_when_quiet() {
+        let quiet = Duration::from_millis(50);
+        let start = Instant::now();
+
+        debounce(quiet, Duration::from_millis(5), || false);
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= quiet);
+        assert!(elapsed < Duration::from_secs(5));
+    }
+}
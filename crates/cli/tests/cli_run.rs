@@ -51,7 +51,17 @@ mod cli_run {
     const OPTIMIZE_FLAG: &str = concatcp!("--", roc_cli::FLAG_OPTIMIZE);
     const LINKER_FLAG: &str = concatcp!("--", roc_cli::FLAG_LINKER);
     const CHECK_FLAG: &str = concatcp!("--", roc_cli::FLAG_CHECK);
+    const STDIN_FLAG: &str = concatcp!("--", roc_cli::FLAG_STDIN);
+    const LINES_FLAG: &str = concatcp!("--", roc_cli::FLAG_LINES);
+    const MAX_WIDTH_FLAG: &str = concatcp!("--", roc_cli::FLAG_MAX_WIDTH);
+    const EMIT_FLAG: &str = concatcp!("--", roc_cli::FLAG_EMIT);
     const PREBUILT_PLATFORM: &str = concatcp!("--", roc_cli::FLAG_PREBUILT, "=true");
+    const MODULE_FLAG: &str = concatcp!("--", roc_cli::FLAG_MODULE);
+    const FILTER_FLAG: &str = concatcp!("--", roc_cli::FLAG_FILTER);
+    const LIST_FLAG: &str = concatcp!("--", roc_cli::FLAG_LIST);
+    const VERBOSE_FLAG: &str = concatcp!("--", roc_cli::FLAG_VERBOSE);
+    const REPORT_FLAG: &str = concatcp!("--", roc_cli::FLAG_REPORT);
+    const REPORT_PATH_FLAG: &str = concatcp!("--", roc_cli::FLAG_REPORT_PATH);
     #[allow(dead_code)]
     const TARGET_FLAG: &str = concatcp!("--", roc_cli::FLAG_TARGET);
 
@@ -1303,6 +1313,46 @@ mod cli_run {
         );
     }
 
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn build_emits_intermediate_representations() {
+        let path = fixture_file("multi-dep-str", "Main.roc");
+        let out = run_roc(
+            [
+                CMD_BUILD,
+                OPTIMIZE_FLAG,
+                concatcp!(EMIT_FLAG, "=mono-ir,llvm-ir,asm,obj"),
+                path.to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(out.status.success(), "{}{}", out.stdout, out.stderr);
+
+        let emitted = |extension: &str| {
+            path.with_file_name("multi-dep-str")
+                .with_extension(extension)
+        };
+
+        let mono_ir = std::fs::read_to_string(emitted("mono")).unwrap();
+        assert!(mono_ir.contains("procedure "), "{}", mono_ir);
+
+        let llvm_ir = std::fs::read_to_string(emitted("ll")).unwrap();
+        assert!(llvm_ir.contains("define "), "{}", llvm_ir);
+
+        assert!(emitted("s").exists());
+        assert!(emitted("o").exists());
+
+        let stdout = strip_colors(&out.stdout);
+        assert!(stdout.contains("multi-dep-str.mono"), "{}", stdout);
+
+        for extension in ["mono", "ll", "s", "o"] {
+            std::fs::remove_file(emitted(extension)).unwrap();
+        }
+    }
+
     #[test]
     #[serial(multi_dep_str)]
     #[cfg_attr(windows, ignore)]
@@ -1492,6 +1542,152 @@ mod cli_run {
         );
     }
 
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn test_list_filtered_expects() {
+        let path = fixture_file("expects", "Arith.roc");
+        let out = run_roc(
+            [
+                CMD_TEST,
+                LIST_FLAG,
+                FILTER_FLAG,
+                "zero",
+                path.to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        assert!(out.status.success());
+
+        let stdout = strip_colors(&out.stdout);
+        assert!(stdout.contains("Arith.roc:13  adds zero"), "{}", stdout);
+        assert!(!stdout.contains("small"), "{}", stdout);
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn test_filter_skips_failing_expect() {
+        let path = fixture_file("expects", "Arith.roc");
+
+        let out = run_roc([CMD_TEST, path.to_str().unwrap()], &[], &[]);
+        assert_eq!(out.status.code(), Some(1), "{}", out.stdout);
+
+        let out = run_roc(
+            [
+                CMD_TEST,
+                VERBOSE_FLAG,
+                FILTER_FLAG,
+                "adds",
+                path.to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+        assert!(out.status.success(), "{}", out.stdout);
+
+        let stdout = strip_colors(&out.stdout);
+        assert!(stdout.contains("Arith:9  adds small numbers"), "{}", stdout);
+        assert!(stdout.contains("Arith:13  adds zero"), "{}", stdout);
+        assert!(stdout.contains("0 failed and 2 passed"), "{}", stdout);
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn test_json_report() {
+        let path = fixture_file("expects", "Arith.roc");
+        let dir = tempfile::tempdir().unwrap();
+        let report_path = dir.path().join("report.json");
+
+        let out = run_roc(
+            [
+                CMD_TEST,
+                REPORT_FLAG,
+                "json",
+                REPORT_PATH_FLAG,
+                report_path.to_str().unwrap(),
+                path.to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+        assert_eq!(out.status.code(), Some(1), "{}", out.stdout);
+
+        let report: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+        let expects = report["expects"].as_array().unwrap();
+
+        assert_eq!(report["passed"], 2);
+        assert_eq!(report["failed"], 1);
+        assert_eq!(expects.len(), 3);
+
+        assert_eq!(expects[0]["module"], "Arith");
+        assert_eq!(expects[0]["name"], "adds small numbers");
+        assert_eq!(expects[0]["region"]["start"]["line"], 9);
+        assert_eq!(expects[0]["result"], "passed");
+        assert_eq!(expects[0]["failure"], serde_json::Value::Null);
+
+        assert_eq!(expects[2]["name"], "Arith:15");
+        assert_eq!(expects[2]["result"], "failed");
+        assert!(expects[2]["failure"]
+            .as_str()
+            .unwrap()
+            .contains("This expectation failed"));
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn test_junit_report() {
+        let path = fixture_file("expects", "Arith.roc");
+        let dir = tempfile::tempdir().unwrap();
+        let report_path = dir.path().join("report.xml");
+
+        run_roc(
+            [
+                CMD_TEST,
+                REPORT_FLAG,
+                "junit",
+                REPORT_PATH_FLAG,
+                report_path.to_str().unwrap(),
+                path.to_str().unwrap(),
+            ],
+            &[],
+            &[],
+        );
+
+        let report = std::fs::read_to_string(&report_path).unwrap();
+
+        assert!(
+            report.contains(r#"<testsuite name="Arith" tests="3" failures="1""#),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains(r#"<testcase name="adds zero" classname="Arith""#),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains(r#"<failure message="Arith:15 failed">"#),
+            "{}",
+            report
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn test_unknown_module_finds_no_expects() {
+        let path = fixture_file("expects", "Arith.roc");
+        let out = run_roc(
+            [CMD_TEST, MODULE_FLAG, "Geometry", path.to_str().unwrap()],
+            &[],
+            &[],
+        );
+
+        assert_eq!(out.status.code(), Some(2));
+        assert!(out.stdout.contains("No expectations were found."));
+    }
+
     #[test]
     fn format_check_good() {
         check_format_check_as_expected(&fixture_file("format", "Formatted.roc"), true);
@@ -1510,6 +1706,112 @@ mod cli_run {
         // This doesn't fail, since only "Formatted.roc" and non-roc files are present in this folder
         check_format_check_as_expected(&fixtures_dir("format/formatted_directory"), true);
     }
+
+    #[test]
+    fn format_check_prints_diff() {
+        let file = fixture_file("format", "NotFormatted.roc");
+        let out = run_roc([CMD_FORMAT, file.to_str().unwrap(), CHECK_FLAG], &[], &[]);
+
+        assert!(!out.status.success());
+        assert!(
+            out.stdout
+                .contains("-  provides [main] to pf\n+    provides [main] to pf\n"),
+            "Expected a diff of the formatting changes, but got:\n\n{}",
+            out.stdout
+        );
+    }
+
+    const UNFORMATTED_SRC: &str = indoc!(
+        r#"
+        interface Foo
+            exposes [x, y]
+            imports []
+
+        x   =    1
+
+        y =   [1,2,
+          3]
+        "#
+    );
+
+    #[test]
+    fn format_stdin() {
+        let out = run_roc([CMD_FORMAT, STDIN_FLAG], &[UNFORMATTED_SRC], &[]);
+
+        assert!(out.status.success());
+        assert_multiline_str_eq!(
+            out.stdout.as_str(),
+            indoc!(
+                r#"
+                interface Foo
+                    exposes [x, y]
+                    imports []
+
+                x = 1
+
+                y = [
+                    1,
+                    2,
+                    3,
+                ]
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn format_stdin_lines() {
+        // Only `x` is on line 5, so `y` stays the way it was.
+        let out = run_roc(
+            [CMD_FORMAT, STDIN_FLAG, LINES_FLAG, "5:5"],
+            &[UNFORMATTED_SRC],
+            &[],
+        );
+
+        assert!(out.status.success());
+        assert_multiline_str_eq!(
+            out.stdout.as_str(),
+            indoc!(
+                r#"
+                interface Foo
+                    exposes [x, y]
+                    imports []
+
+                x = 1
+
+                y =   [1,2,
+                  3]
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn format_stdin_max_width() {
+        let out = run_roc(
+            [CMD_FORMAT, STDIN_FLAG, MAX_WIDTH_FLAG, "20"],
+            &["interface Foo\n    exposes [x]\n    imports []\n\nx = List.sum [100, 200, 300]\n"],
+            &[],
+        );
+
+        assert!(out.status.success());
+        assert_multiline_str_eq!(
+            out.stdout.as_str(),
+            indoc!(
+                r#"
+                interface Foo
+                    exposes [x]
+                    imports []
+
+                x = List.sum [
+                    100,
+                    200,
+                    300,
+                ]
+                "#
+            )
+        );
+    }
 }
 
 #[cfg(feature = "wasm32-cli-run")]
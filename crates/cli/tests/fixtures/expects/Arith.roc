@@ -0,0 +1,15 @@
+interface Arith
+    exposes [add]
+    imports []
+
+add : I64, I64 -> I64
+add = \a, b -> a + b
+
+## adds small numbers
+expect add 1 2 == 3
+
+## adds zero
+## on either side
+expect add 0 5 == add 5 0
+
+expect add 2 2 == 5
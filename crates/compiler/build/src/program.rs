@@ -12,11 +12,13 @@ use roc_load::{
 };
 use roc_mono::ir::{OptLevel, SingleEntryPoint};
 use roc_packaging::cache::RocCacheDir;
+use roc_problem::Severity;
 use roc_reporting::{
     cli::{report_problems, Problems},
-    report::{RenderTarget, DEFAULT_PALETTE},
+    report::{render_json_without_report, RenderTarget, DEFAULT_PALETTE},
 };
 use roc_target::TargetInfo;
+use roc_wasm_module::WasmModule;
 use std::ffi::OsStr;
 use std::ops::Deref;
 use std::{
@@ -36,23 +38,28 @@ pub struct CodeGenTiming {
     pub code_gen: Duration,
 }
 
-pub fn report_problems_monomorphized(loaded: &mut MonomorphizedModule) -> Problems {
+pub fn report_problems_monomorphized(
+    loaded: &mut MonomorphizedModule,
+    render: RenderTarget,
+) -> Problems {
     report_problems(
         loaded.total_problems(),
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        render,
     )
 }
 
-pub fn report_problems_typechecked(loaded: &mut LoadedModule) -> Problems {
+pub fn report_problems_typechecked(loaded: &mut LoadedModule, render: RenderTarget) -> Problems {
     report_problems(
         loaded.total_problems(),
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        render,
     )
 }
 
@@ -84,6 +91,52 @@ pub struct CodeGenOptions {
     pub backend: CodeGenBackend,
     pub opt_level: OptLevel,
     pub emit_debug_info: bool,
+    pub emit: EmitOptions,
+}
+
+/// Intermediate artifacts to write next to the build output, as requested with `roc build --emit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmitOptions {
+    /// The mono IR after specialization, refcounting and reset/reuse, as `<output>.mono`.
+    pub mono_ir: bool,
+    /// The optimized LLVM module, as `<output>.ll`.
+    pub llvm_ir: bool,
+    /// Target assembly generated from the LLVM module, as `<output>.s`.
+    pub asm: bool,
+    /// The text format of the final WebAssembly binary, as `<output>.wat`.
+    pub wasm_wat: bool,
+    /// The app's object file before linking, as `<output>.o` (or the target's equivalent).
+    pub obj: bool,
+}
+
+impl EmitOptions {
+    pub const KINDS: [&'static str; 5] = ["mono-ir", "llvm-ir", "asm", "wasm-wat", "obj"];
+
+    /// Enables the artifact with the given name (one of [EmitOptions::KINDS]).
+    pub fn enable(&mut self, kind: &str) -> Result<(), String> {
+        match kind {
+            "mono-ir" => self.mono_ir = true,
+            "llvm-ir" => self.llvm_ir = true,
+            "asm" => self.asm = true,
+            "wasm-wat" => self.wasm_wat = true,
+            "obj" => self.obj = true,
+            other => {
+                return Err(format!(
+                    "`{other}` is not something I can emit. Try one of: {}",
+                    Self::KINDS.join(", ")
+                ))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn emit_path(emit_base: &Path, extension: &str) -> PathBuf {
+    let mut path = emit_base.to_path_buf();
+    path.set_extension(extension);
+
+    path
 }
 
 type GenFromMono<'a> = (CodeObject, CodeGenTiming, ExpectMetadata<'a>);
@@ -97,10 +150,12 @@ pub fn gen_from_mono_module<'a>(
     code_gen_options: CodeGenOptions,
     preprocessed_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
+    emit_base: &Path,
 ) -> GenFromMono<'a> {
     let path = roc_file_path;
     let debug = code_gen_options.emit_debug_info;
     let opt = code_gen_options.opt_level;
+    let emit = EmitLlvm::new(code_gen_options.emit, emit_base);
 
     match code_gen_options.backend {
         CodeGenBackend::Assembly => gen_from_mono_module_dev(
@@ -111,7 +166,7 @@ pub fn gen_from_mono_module<'a>(
             wasm_dev_stack_bytes,
         ),
         CodeGenBackend::Llvm(backend_mode) => {
-            gen_from_mono_module_llvm(arena, loaded, path, target, opt, backend_mode, debug)
+            gen_from_mono_module_llvm(arena, loaded, path, target, opt, backend_mode, debug, emit)
         }
         CodeGenBackend::Wasm => {
             // emit wasm via the llvm backend
@@ -121,7 +176,22 @@ pub fn gen_from_mono_module<'a>(
                 OptLevel::Normal | OptLevel::Size | OptLevel::Optimize => LlvmBackendMode::Binary,
             };
 
-            gen_from_mono_module_llvm(arena, loaded, path, target, opt, backend_mode, debug)
+            gen_from_mono_module_llvm(arena, loaded, path, target, opt, backend_mode, debug, emit)
+        }
+    }
+}
+
+/// Where to write the LLVM artifacts requested with `--emit`, if any.
+struct EmitLlvm {
+    llvm_ir: Option<PathBuf>,
+    asm: Option<PathBuf>,
+}
+
+impl EmitLlvm {
+    fn new(emit: EmitOptions, emit_base: &Path) -> Self {
+        Self {
+            llvm_ir: emit.llvm_ir.then(|| emit_path(emit_base, "ll")),
+            asm: emit.asm.then(|| emit_path(emit_base, "s")),
         }
     }
 }
@@ -129,6 +199,7 @@ pub fn gen_from_mono_module<'a>(
 // TODO how should imported modules factor into this? What if those use builtins too?
 // TODO this should probably use more helper functions
 // TODO make this polymorphic in the llvm functions so it can be reused for another backend.
+#[allow(clippy::too_many_arguments)]
 fn gen_from_mono_module_llvm<'a>(
     arena: &'a bumpalo::Bump,
     mut loaded: MonomorphizedModule<'a>,
@@ -137,6 +208,7 @@ fn gen_from_mono_module_llvm<'a>(
     opt_level: OptLevel,
     backend_mode: LlvmBackendMode,
     emit_debug_info: bool,
+    emit: EmitLlvm,
 ) -> GenFromMono<'a> {
     use crate::target::{self, convert_opt_level};
     use inkwell::attributes::{Attribute, AttributeLoc};
@@ -263,6 +335,32 @@ fn gen_from_mono_module_llvm<'a>(
     // Uncomment this to see the module's optimized LLVM instruction output:
     // env.module.print_to_stderr();
 
+    if let Some(llvm_ir_path) = &emit.llvm_ir {
+        if let Err(err) = env.module.print_to_file(llvm_ir_path) {
+            internal_error!(
+                "I could not write the LLVM IR to {}: {}",
+                llvm_ir_path.display(),
+                err.to_string()
+            );
+        }
+    }
+
+    if let Some(asm_path) = &emit.asm {
+        let target_machine =
+            target::target_machine(target, convert_opt_level(opt_level), RelocMode::PIC)
+                .unwrap_or_else(|| {
+                    internal_error!("I could not create an LLVM target machine for {}", target)
+                });
+
+        if let Err(err) = target_machine.write_to_file(env.module, FileType::Assembly, asm_path) {
+            internal_error!(
+                "I could not write the assembly to {}: {}",
+                asm_path.display(),
+                err.to_string()
+            );
+        }
+    }
+
     // annotate the LLVM IR output with debug info
     // so errors are reported with the line number of the LLVM source
     let memory_buffer = if cfg!(feature = "sanitizers") && std::env::var("ROC_SANITIZERS").is_ok() {
@@ -625,6 +723,11 @@ pub struct BuiltFile<'a> {
     pub problems: Problems,
     pub total_time: Duration,
     pub expect_metadata: ExpectMetadata<'a>,
+    /// The intermediate artifacts written because of [EmitOptions].
+    pub emitted_paths: Vec<PathBuf>,
+    /// The paths of all the modules that were loaded, including those of the
+    /// platform and any packages.
+    pub source_paths: Vec<PathBuf>,
 }
 
 pub enum BuildOrdering {
@@ -658,15 +761,31 @@ impl<'a> BuildFileError<'a> {
     }
 }
 
+/// Prints a timing message from `--time`. With [RenderTarget::Json], stdout only has the
+/// reports, so these go to stderr instead.
+fn print_timing(render: RenderTarget, message: std::fmt::Arguments) {
+    if render == RenderTarget::Json {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
 pub fn handle_error_module(
     mut module: roc_load::LoadedModule,
     total_time: std::time::Duration,
     filename: &OsStr,
     print_run_anyway_hint: bool,
+    render: RenderTarget,
 ) -> std::io::Result<i32> {
     debug_assert!(module.total_problems() > 0);
 
-    let problems = report_problems_typechecked(&mut module);
+    let problems = report_problems_typechecked(&mut module, render);
+
+    if render == RenderTarget::Json {
+        // Only the reports themselves go to stdout, so that it remains valid JSON Lines.
+        return Ok(problems.exit_code());
+    }
 
     problems.print_to_stdout(total_time);
 
@@ -685,16 +804,36 @@ pub fn handle_error_module(
     Ok(problems.exit_code())
 }
 
-pub fn handle_loading_problem(problem: LoadingProblem) -> std::io::Result<i32> {
+pub fn handle_loading_problem(
+    problem: LoadingProblem,
+    render: RenderTarget,
+) -> std::io::Result<i32> {
     match problem {
+        LoadingProblem::FormattedReport(report) if render == RenderTarget::Json => {
+            println!("{}", report);
+            Ok(1)
+        }
         LoadingProblem::FormattedReport(report) => {
             print!("{}", report);
             Ok(1)
         }
+        // TODO: tighten up the types here, we should always end up with a
+        // formatted report from load.
+        _ if render == RenderTarget::Json => {
+            let filename = match &problem {
+                LoadingProblem::FileProblem { filename, .. } => filename.clone(),
+                _ => PathBuf::new(),
+            };
+            let message = format!("Failed with error: {:?}", problem);
+
+            println!(
+                "{}",
+                render_json_without_report("LOADING PROBLEM", Severity::Fatal, &filename, &message)
+            );
+            Ok(1)
+        }
         _ => {
-            // TODO: tighten up the types here, we should always end up with a
-            // formatted report from load.
-            print!("Failed with error: {:?}", problem);
+            println!("Failed with error: {:?}", problem);
             Ok(1)
         }
     }
@@ -704,6 +843,7 @@ pub fn standard_load_config(
     target: &Triple,
     order: BuildOrdering,
     threading: Threading,
+    render: RenderTarget,
 ) -> LoadConfig {
     let target_info = TargetInfo::from(target);
 
@@ -714,7 +854,7 @@ pub fn standard_load_config(
 
     LoadConfig {
         target_info,
-        render: RenderTarget::ColorTerminal,
+        render,
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode,
@@ -736,6 +876,7 @@ pub fn build_file<'a>(
     load_config: LoadConfig,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
     let compilation_start = Instant::now();
+    let render = load_config.render;
 
     // Step 1: compile the app and generate the .o file
     let loaded =
@@ -752,6 +893,7 @@ pub fn build_file<'a>(
         linking_strategy,
         prebuilt_requested,
         wasm_dev_stack_bytes,
+        render,
         loaded,
         compilation_start,
     )
@@ -768,6 +910,7 @@ fn build_loaded_file<'a>(
     linking_strategy: LinkingStrategy,
     prebuilt_requested: bool,
     wasm_dev_stack_bytes: Option<u32>,
+    render: RenderTarget,
     loaded: roc_load::MonomorphizedModule<'a>,
     compilation_start: Instant,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
@@ -795,8 +938,17 @@ fn build_loaded_file<'a>(
     // even if the --prebuilt-platform=true CLI flag wasn't set.
     let is_platform_prebuilt = prebuilt_requested || loaded.uses_prebuilt_platform;
 
+    let source_paths = loaded
+        .sources
+        .values()
+        .map(|(path, _)| path.clone())
+        .collect();
+
     let cwd = app_module_path.parent().unwrap();
     let mut output_exe_path = cwd.join(&*loaded.output_path);
+    let emit_base = output_exe_path.clone();
+    let emit = code_gen_options.emit;
+    let mut emitted_paths = Vec::new();
 
     if let Some(extension) = operating_system.executable_file_ext() {
         output_exe_path.set_extension(extension);
@@ -871,7 +1023,7 @@ fn build_loaded_file<'a>(
     // This only needs to be mutable for report_problems. This can't be done
     // inside a nested scope without causing a borrow error!
     let mut loaded = loaded;
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems = report_problems_monomorphized(&mut loaded, render);
     let loaded = loaded;
 
     enum HostRebuildTiming {
@@ -886,9 +1038,12 @@ fn build_loaded_file<'a>(
                 .expect("Failed to (re)build platform.");
 
             if emit_timings && !is_platform_prebuilt {
-                println!(
-                    "Finished rebuilding the platform in {} ms\n",
-                    rebuild_duration
+                print_timing(
+                    render,
+                    format_args!(
+                        "Finished rebuilding the platform in {} ms\n",
+                        rebuild_duration
+                    ),
                 );
             }
 
@@ -900,6 +1055,12 @@ fn build_loaded_file<'a>(
         None
     };
 
+    if emit.mono_ir {
+        let mono_ir_path = emit_path(&emit_base, "mono");
+        write_mono_ir(&loaded, &mono_ir_path);
+        emitted_paths.push(mono_ir_path);
+    }
+
     let (roc_app_bytes, code_gen_timing, expect_metadata) = gen_from_mono_module(
         arena,
         loaded,
@@ -908,8 +1069,25 @@ fn build_loaded_file<'a>(
         code_gen_options,
         &preprocessed_host_path,
         wasm_dev_stack_bytes,
+        &emit_base,
     );
 
+    if emit.llvm_ir {
+        emitted_paths.push(emit_path(&emit_base, "ll"));
+    }
+
+    if emit.asm {
+        emitted_paths.push(emit_path(&emit_base, "s"));
+    }
+
+    if emit.obj {
+        let obj_path = emit_path(&emit_base, operating_system.object_file_ext());
+        std::fs::write(&obj_path, &*roc_app_bytes).unwrap_or_else(|err| {
+            internal_error!("I could not write {}: {}", obj_path.display(), err)
+        });
+        emitted_paths.push(obj_path);
+    }
+
     buf.push('\n');
     buf.push_str("    ");
     buf.push_str("Code Generation");
@@ -925,15 +1103,21 @@ fn build_loaded_file<'a>(
     let size = roc_app_bytes.len();
 
     if emit_timings {
-        println!(
-            "\n\nCompilation finished!\n\nHere's how long each module took to compile:\n\n{}",
-            buf
+        print_timing(
+            render,
+            format_args!(
+                "\n\nCompilation finished!\n\nHere's how long each module took to compile:\n\n{}",
+                buf
+            ),
         );
 
-        println!(
-            "Finished compilation and code gen in {} ms\n\nProduced a app.o file of size {:?}\n",
-            compilation_end.as_millis(),
-            size,
+        print_timing(
+            render,
+            format_args!(
+                "Finished compilation and code gen in {} ms\n\nProduced a app.o file of size {:?}\n",
+                compilation_end.as_millis(),
+                size,
+            ),
         );
     }
 
@@ -941,9 +1125,12 @@ fn build_loaded_file<'a>(
         let rebuild_duration = thread.join().expect("Failed to (re)build platform.");
 
         if emit_timings && !is_platform_prebuilt {
-            println!(
-                "Finished rebuilding the platform in {} ms\n",
-                rebuild_duration
+            print_timing(
+                render,
+                format_args!(
+                    "Finished rebuilding the platform in {} ms\n",
+                    rebuild_duration
+                ),
             );
         }
     }
@@ -1012,7 +1199,20 @@ fn build_loaded_file<'a>(
     let linking_time = link_start.elapsed();
 
     if emit_timings {
-        println!("Finished linking in {} ms\n", linking_time.as_millis());
+        print_timing(
+            render,
+            format_args!("Finished linking in {} ms\n", linking_time.as_millis()),
+        );
+    }
+
+    if emit.wasm_wat {
+        let wat_path = emit_path(&emit_base, "wat");
+
+        match write_wasm_wat(&output_exe_path, &wat_path) {
+            Ok(()) => emitted_paths.push(wat_path),
+            // The build itself worked, so carry on without the disassembly.
+            Err(message) => eprintln!("{}", message),
+        }
     }
 
     let total_time = compilation_start.elapsed();
@@ -1022,9 +1222,66 @@ fn build_loaded_file<'a>(
         problems,
         total_time,
         expect_metadata,
+        emitted_paths,
+        source_paths,
     })
 }
 
+/// Writes the final mono IR of every procedure, sorted by name so builds can be diffed.
+fn write_mono_ir(loaded: &MonomorphizedModule, path: &Path) {
+    let mut procs: Vec<_> = loaded
+        .procedures
+        .values()
+        .map(|proc| {
+            let symbol = proc.name.name();
+            let name = format!(
+                "{}.{}",
+                symbol.module_string(&loaded.interns),
+                symbol.as_str(&loaded.interns)
+            );
+
+            (name, proc.to_pretty(&loaded.layout_interner, 200, true))
+        })
+        .collect();
+
+    procs.sort();
+
+    let mut buf = String::new();
+
+    for (name, proc) in procs {
+        buf.push_str("# ");
+        buf.push_str(&name);
+        buf.push('\n');
+        buf.push_str(&proc);
+        buf.push('\n');
+    }
+
+    std::fs::write(path, buf)
+        .unwrap_or_else(|err| internal_error!("I could not write {}: {}", path.display(), err));
+}
+
+/// Writes the text format of a linked WebAssembly binary. Our disassembler only understands
+/// what Roc's own toolchain produces, so the error explains what went wrong for the user.
+fn write_wasm_wat(wasm_path: &Path, wat_path: &Path) -> Result<(), String> {
+    let bytes = std::fs::read(wasm_path)
+        .map_err(|err| format!("I could not read {}: {}", wasm_path.display(), err))?;
+
+    let arena = Bump::new();
+    let wat = WasmModule::preload(&arena, &bytes, false)
+        .and_then(|module| module.to_wat())
+        .map_err(|err| {
+            format!(
+                "I could not disassemble {} for --emit=wasm-wat. There's something I don't understand at byte offset {:#x}:\n{}",
+                wasm_path.display(),
+                err.offset,
+                err.message
+            )
+        })?;
+
+    std::fs::write(wat_path, wat)
+        .map_err(|err| format!("I could not write {}: {}", wat_path.display(), err))
+}
+
 fn invalid_prebuilt_platform(prebuilt_requested: bool, preprocessed_host_path: PathBuf) {
     let prefix = match prebuilt_requested {
         true => "Because I was run with --prebuilt-platform=true, ",
@@ -1161,6 +1418,7 @@ pub fn check_file<'a>(
     emit_timings: bool,
     roc_cache_dir: RocCacheDir<'_>,
     threading: Threading,
+    render: RenderTarget,
 ) -> Result<(Problems, Duration), LoadingProblem<'a>> {
     let compilation_start = Instant::now();
 
@@ -1172,8 +1430,7 @@ pub fn check_file<'a>(
 
     let load_config = LoadConfig {
         target_info,
-        // TODO: expose this from CLI?
-        render: RenderTarget::ColorTerminal,
+        render,
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
@@ -1216,15 +1473,24 @@ pub fn check_file<'a>(
     let compilation_end = compilation_start.elapsed();
 
     if emit_timings {
-        println!(
-            "\n\nCompilation finished!\n\nHere's how long each module took to compile:\n\n{}",
-            buf
+        print_timing(
+            render,
+            format_args!(
+                "\n\nCompilation finished!\n\nHere's how long each module took to compile:\n\n{}",
+                buf
+            ),
         );
 
-        println!("Finished checking in {} ms\n", compilation_end.as_millis(),);
+        print_timing(
+            render,
+            format_args!("Finished checking in {} ms\n", compilation_end.as_millis()),
+        );
     }
 
-    Ok((report_problems_typechecked(&mut loaded), compilation_end))
+    Ok((
+        report_problems_typechecked(&mut loaded, render),
+        compilation_end,
+    ))
 }
 
 pub fn build_str_test<'a>(
@@ -1239,6 +1505,7 @@ pub fn build_str_test<'a>(
         backend: CodeGenBackend::Llvm(LlvmBackendMode::Binary),
         opt_level: OptLevel::Normal,
         emit_debug_info: false,
+        emit: EmitOptions::default(),
     };
 
     let emit_timings = false;
@@ -1250,7 +1517,8 @@ pub fn build_str_test<'a>(
     let build_ordering = BuildOrdering::AlwaysBuild;
     let threading = Threading::AtMost(2);
 
-    let load_config = standard_load_config(&triple, build_ordering, threading);
+    let render = RenderTarget::ColorTerminal;
+    let load_config = standard_load_config(&triple, build_ordering, threading, render);
 
     let compilation_start = std::time::Instant::now();
 
@@ -1275,6 +1543,7 @@ pub fn build_str_test<'a>(
         linking_strategy,
         assume_prebuild,
         wasm_dev_stack_bytes,
+        render,
         loaded,
         compilation_start,
     )
@@ -0,0 +1,443 @@
+interface Arbitrary
+    exposes [
+        Arbitrary,
+        arbitrary,
+        Source,
+        Generated,
+        draw,
+        map,
+        bool,
+        u8,
+        u16,
+        u32,
+        u64,
+        u128,
+        i8,
+        i16,
+        i32,
+        i64,
+        i128,
+        nat,
+        f32,
+        f64,
+        dec,
+        str,
+        list,
+        Config,
+        CheckResult,
+        defaultConfig,
+        check,
+        checkWith,
+    ] imports [
+        Bool.{ Bool },
+        Result.{ Result },
+        List,
+        Str,
+        Num.{ U8, U16, U32, U64, U128, I8, I16, I32, I64, I128, Nat, F32, F64, Dec, Frac },
+    ]
+
+## A value that can be generated from a [Source] of random choices, for use in
+## property-based tests.
+##
+## Records, tuples, and tag unions of types that have [Arbitrary] can derive it.
+Arbitrary has
+    ## Generates a value by drawing choices from a [Source].
+    ##
+    ## Values generated from smaller choices should themselves be "smaller", so
+    ## that [check] can shrink a failing input down to a minimal counterexample.
+    arbitrary : Source -> Generated a | a has Arbitrary
+
+## The choices a generator draws from.
+##
+## While looking for a failing input the choices are random. While shrinking,
+## a previously recorded (and simplified) list of choices is replayed instead.
+Source := {
+    mode : [Random U64, Replay (List U64)],
+    drawn : List U64,
+}
+
+## A generated value, together with the [Source] to draw the next value from.
+Generated a : { value : a, source : Source }
+
+## At most this many choices are drawn for a single input. Once it is reached,
+## every further draw is `0`, so that recursive generators stop growing.
+maxDraws : Nat
+maxDraws = 1000
+
+## Lists and strings are never generated with more elements than this.
+maxLen : Nat
+maxLen = 100
+
+## Draws a choice between `0` and `max`, both inclusive.
+##
+## This is the primitive all other generators are built on.
+draw : Source, U64 -> Generated U64
+draw = \@Source src, max ->
+    if List.len src.drawn >= maxDraws then
+        { value: 0, source: @Source src }
+    else
+        when src.mode is
+            Random state ->
+                next = Num.addWrap state 0x9E3779B97F4A7C15
+                choice = clampChoice (mix next) max
+
+                { value: choice, source: @Source { mode: Random next, drawn: List.append src.drawn choice } }
+
+            Replay choices ->
+                choice =
+                    when List.get choices (List.len src.drawn) is
+                        Ok replayed -> clampChoice replayed max
+                        Err OutOfBounds -> 0
+
+                { value: choice, source: @Source { src & drawn: List.append src.drawn choice } }
+
+clampChoice : U64, U64 -> U64
+clampChoice = \choice, max ->
+    if choice <= max then
+        choice
+    else
+        # max < choice, so max + 1 cannot overflow
+        Num.rem choice (max + 1)
+
+## The splitmix64 finalizer.
+mix : U64 -> U64
+mix = \state ->
+    a = Num.mulWrap (Num.bitwiseXor state (Num.shiftRightZfBy state 30)) 0xBF58476D1CE4E5B9
+    b = Num.mulWrap (Num.bitwiseXor a (Num.shiftRightZfBy a 27)) 0x94D049BB133111EB
+
+    Num.bitwiseXor b (Num.shiftRightZfBy b 31)
+
+## Transforms a generated value, keeping its source.
+map : Generated a, (a -> b) -> Generated b
+map = \generated, transform -> { value: transform generated.value, source: generated.source }
+
+## Draws a number that is usually small: first one of `classes + 1` sizes (8,
+## 16, 32, or 64 bits), then a number of that size.
+drawSized : Source, U64 -> Generated U64
+drawSized = \src, classes ->
+    size = draw src classes
+    max =
+        when size.value is
+            0 -> 0xFF
+            1 -> 0xFFFF
+            2 -> 0xFFFFFFFF
+            _ -> Num.maxU64
+
+    draw size.source max
+
+drawU128 : Source -> Generated U128
+drawU128 = \src ->
+    low = drawSized src 3
+    wide = draw low.source 1
+
+    if wide.value == 0 then
+        { value: Num.toU128 low.value, source: wide.source }
+    else
+        high = drawSized wide.source 3
+        value = Num.bitwiseOr (Num.shiftLeftBy (Num.toU128 high.value) 64) (Num.toU128 low.value)
+
+        { value, source: high.source }
+
+## Maps 0, 1, 2, 3, ... to 0, -1, 1, -2, ..., so that signed numbers shrink
+## toward zero.
+zigzag : U128 -> I128
+zigzag = \n ->
+    half = Num.toI128 (Num.shiftRightZfBy n 1)
+
+    if Num.bitwiseAnd n 1 == 0 then
+        half
+    else
+        -half - 1
+
+bool : Source -> Generated Bool
+bool = \src -> map (draw src 1) \choice -> choice == 1
+
+u8 : Source -> Generated U8
+u8 = \src -> map (draw src 0xFF) Num.toU8
+
+u16 : Source -> Generated U16
+u16 = \src -> map (drawSized src 1) Num.toU16
+
+u32 : Source -> Generated U32
+u32 = \src -> map (drawSized src 2) Num.toU32
+
+u64 : Source -> Generated U64
+u64 = \src -> drawSized src 3
+
+u128 : Source -> Generated U128
+u128 = \src -> drawU128 src
+
+i8 : Source -> Generated I8
+i8 = \src -> map (draw src 0xFF) \choice -> Num.toI8 (zigzag (Num.toU128 choice))
+
+i16 : Source -> Generated I16
+i16 = \src -> map (drawSized src 1) \choice -> Num.toI16 (zigzag (Num.toU128 choice))
+
+i32 : Source -> Generated I32
+i32 = \src -> map (drawSized src 2) \choice -> Num.toI32 (zigzag (Num.toU128 choice))
+
+i64 : Source -> Generated I64
+i64 = \src -> map (drawSized src 3) \choice -> Num.toI64 (zigzag (Num.toU128 choice))
+
+i128 : Source -> Generated I128
+i128 = \src -> map (drawU128 src) zigzag
+
+nat : Source -> Generated Nat
+nat = \src -> map (drawSized src 3) Num.toNat
+
+## Generates a fraction with a (usually small) whole part and three decimal
+## places, which shrinks toward `0`.
+frac : Source -> Generated (Frac a)
+frac = \src ->
+    whole = map (drawSized src 2) \choice -> zigzag (Num.toU128 choice)
+    thousandths = draw whole.source 999
+    magnitude = fracFromU64 (Num.toU64 (Num.abs whole.value)) + fracFromU64 thousandths.value / 1000
+    value = if whole.value < 0 then -magnitude else magnitude
+
+    { value, source: thousandths.source }
+
+## Converts with plain arithmetic, which (unlike `Num.toFrac`) also works for
+## [Dec].
+fracFromU64 : U64 -> Frac a
+fracFromU64 = \n ->
+    if n == 0 then
+        0
+    else
+        half = fracFromU64 (Num.shiftRightZfBy n 1)
+
+        if Num.bitwiseAnd n 1 == 0 then
+            2 * half
+        else
+            2 * half + 1
+
+f32 : Source -> Generated F32
+f32 = \src -> frac src
+
+f64 : Source -> Generated F64
+f64 = \src -> frac src
+
+dec : Source -> Generated Dec
+dec = \src -> frac src
+
+## Generates printable ASCII strings, which shrink toward fewer characters, and
+## characters toward `a`.
+str : Source -> Generated Str
+str = \src ->
+    bytes = strHelp src []
+
+    when Str.fromUtf8 bytes.value is
+        Ok string -> { value: string, source: bytes.source }
+        Err _ -> { value: "", source: bytes.source }
+
+strHelp : Source, List U8 -> Generated (List U8)
+strHelp = \src, bytes ->
+    if List.len bytes >= maxLen then
+        { value: bytes, source: src }
+    else
+        more = draw src 7
+
+        if more.value == 0 then
+            { value: bytes, source: more.source }
+        else
+            char = draw more.source 94
+            # 0 is 'a', and the other choices wrap around the printable range ' '..'~'
+            byte = Num.toU8 (Num.rem (char.value + 65) 95 + 32)
+
+            strHelp char.source (List.append bytes byte)
+
+## Generates lists, which shrink toward fewer and smaller elements.
+##
+## Before each element a "continue" choice is drawn, so deleting an element
+## while shrinking is the same as deleting its choices.
+list : Source -> Generated (List a) | a has Arbitrary
+list = \src -> listHelp src []
+
+listHelp : Source, List a -> Generated (List a) | a has Arbitrary
+listHelp = \src, items ->
+    if List.len items >= maxLen then
+        { value: items, source: src }
+    else
+        more = draw src 7
+
+        if more.value == 0 then
+            { value: items, source: more.source }
+        else
+            item = arbitrary more.source
+
+            listHelp item.source (List.append items item.value)
+
+## How [checkWith] looks for and shrinks counterexamples.
+##
+## - `runs` is how many random inputs to try.
+## - `seed` seeds the random choices; the same seed always tries the same inputs.
+## - `maxShrinks` bounds how many times a counterexample is simplified.
+Config : { runs : U64, seed : U64, maxShrinks : U64 }
+
+defaultConfig : Config
+defaultConfig = { runs: 100, seed: 0x2545F4914F6CDD1D, maxShrinks: 1000 }
+
+## The outcome of checking a property. `run` is the (1-based) run that found
+## the counterexample, and `shrinks` how many times it was simplified since.
+CheckResult a : [Passed, Falsified { counterexample : a, run : U64, shrinks : U64 }]
+
+## Checks that a property holds for [defaultConfig]`.runs` generated inputs.
+##
+## If it does not, the failing input is shrunk to a minimal counterexample:
+## ```
+## expect
+##     result = Arbitrary.check \list -> List.len (List.reverse list) == List.len list
+##
+##     result == Passed
+## ```
+## When such an `expect` fails, `roc test` shows the `Falsified` counterexample.
+##
+## `roc test --property-runs <N>` tries `N` inputs instead, and
+## `roc test --property-seed <SEED>` generates them from another seed, for
+## every `check` in the tested modules. For a single property, use [checkWith].
+check : (a -> Bool) -> CheckResult a | a has Arbitrary
+check = \property -> checkWith (runnerConfig {}) property
+
+## [defaultConfig], with the `runs` and `seed` given to `roc test`, if any.
+runnerConfig : {} -> Config
+runnerConfig = \{} ->
+    runs = propertyRunsLowlevel {}
+    seed = propertySeedLowlevel {}
+
+    {
+        runs: if runs == 0 then defaultConfig.runs else runs,
+        seed: if seed == 0 then defaultConfig.seed else seed,
+        maxShrinks: defaultConfig.maxShrinks,
+    }
+
+# These are 0 unless given to `roc test`, which doesn't accept 0 for either of them.
+propertyRunsLowlevel : {} -> U64
+propertySeedLowlevel : {} -> U64
+
+## Like [check], with a custom [Config].
+checkWith : Config, (a -> Bool) -> CheckResult a | a has Arbitrary
+checkWith = \config, property -> checkHelp config property 0 config.seed
+
+checkHelp : Config, (a -> Bool), U64, U64 -> CheckResult a | a has Arbitrary
+checkHelp = \config, property, run, seed ->
+    if run >= config.runs then
+        Passed
+    else
+        generated = arbitrary (@Source { mode: Random seed, drawn: [] })
+
+        if property generated.value then
+            checkHelp config property (run + 1) (mix (Num.addWrap seed 1))
+        else
+            shrunk = shrink config property (drawnOf generated.source) generated.value 0
+
+            Falsified { counterexample: shrunk.value, run: run + 1, shrinks: shrunk.shrinks }
+
+drawnOf : Source -> List U64
+drawnOf = \@Source src -> src.drawn
+
+## Shrinks by simplifying the recorded choices rather than the value itself, in
+## the style of Hypothesis: a simpler list of choices that still makes the
+## property fail replaces the current one, until none can be found.
+shrink : Config, (a -> Bool), List U64, a, U64 -> { value : a, shrinks : U64 } | a has Arbitrary
+shrink = \config, property, choices, value, shrinks ->
+    if shrinks >= config.maxShrinks then
+        { value, shrinks }
+    else
+        when shrinkOnce property choices is
+            Ok simpler -> shrink config property simpler.choices simpler.value (shrinks + 1)
+            Err NoSimpler -> { value, shrinks }
+
+shrinkOnce : (a -> Bool), List U64 -> Result { choices : List U64, value : a } [NoSimpler] | a has Arbitrary
+shrinkOnce = \property, choices ->
+    List.walkUntil (shrinkCandidates choices) (Err NoSimpler) \state, candidate ->
+        replayed = arbitrary (@Source { mode: Replay (applyCandidate choices candidate), drawn: [] })
+        drawn = drawnOf replayed.source
+
+        # Compare what was actually drawn, which may be shorter than the candidate
+        if isSimpler drawn choices && Bool.not (property replayed.value) then
+            Break (Ok { choices: drawn, value: replayed.value })
+        else
+            Continue state
+
+Candidate : [Delete Nat Nat, Replace Nat U64]
+
+## Deleting choices first removes whole elements or branches; after that the
+## individual choices are made smaller.
+shrinkCandidates : List U64 -> List Candidate
+shrinkCandidates = \choices ->
+    len = List.len choices
+    deletions =
+        List.walk [8, 4, 2, 1] [] \candidates, size ->
+            if size > len then
+                candidates
+            else
+                List.walk (List.range { start: At 0, end: Length (len - size + 1) }) candidates \acc, start ->
+                    List.append acc (Delete start size)
+
+    List.walk (List.range { start: At 0, end: Length len }) deletions \candidates, index ->
+        when List.get choices index is
+            Ok choice ->
+                List.concat candidates (List.map (reductions choice) \smaller -> Replace index smaller)
+
+            Err OutOfBounds -> candidates
+
+## Smaller values to try for a choice, smallest first: `0`, then halving the
+## distance to `choice` each time, which amounts to a binary search.
+reductions : U64 -> List U64
+reductions = \choice ->
+    if choice == 0 then
+        []
+    else
+        reductionsHelp choice (Num.shiftRightZfBy choice 1) [0]
+
+reductionsHelp : U64, U64, List U64 -> List U64
+reductionsHelp = \choice, delta, smaller ->
+    if delta == 0 then
+        smaller
+    else
+        reductionsHelp choice (Num.shiftRightZfBy delta 1) (List.append smaller (choice - delta))
+
+applyCandidate : List U64, Candidate -> List U64
+applyCandidate = \choices, candidate ->
+    when candidate is
+        Delete start size ->
+            before = List.sublist choices { start: 0, len: start }
+            after = List.sublist choices { start: start + size, len: List.len choices }
+
+            List.concat before after
+
+        Replace index choice -> List.set choices index choice
+
+## Shorter choice lists are simpler; among those of equal length, the
+## lexicographically smaller one is.
+isSimpler : List U64, List U64 -> Bool
+isSimpler = \left, right ->
+    if List.len left != List.len right then
+        List.len left < List.len right
+    else
+        List.walkUntil (List.map2 left right \a, b -> Num.compare a b) Bool.false \_, order ->
+            when order is
+                LT -> Break Bool.true
+                GT -> Break Bool.false
+                EQ -> Continue Bool.false
+
+expect
+    generated = draw (@Source { mode: Replay [3, 700], drawn: [] }) 10
+    next = draw generated.source 255
+
+    generated.value == 3 && next.value == Num.rem 700 256 && drawnOf next.source == [3, Num.rem 700 256]
+
+expect
+    exhausted = draw (@Source { mode: Replay [], drawn: [] }) 10
+
+    exhausted.value == 0
+
+expect
+    zigzag 0 == 0 && zigzag 1 == -1 && zigzag 2 == 1 && zigzag 255 == -128 && zigzag 254 == 127
+
+expect
+    reductions 0 == [] && reductions 1 == [0] && reductions 10 == [0, 5, 8, 9]
+
+expect
+    when check \n -> n < 1000u64 is
+        Falsified { counterexample } -> counterexample == 1000
+        Passed -> Bool.false
@@ -1,5 +1,5 @@
 interface Bool
-    exposes [Bool, Eq, true, false, and, or, not, isEq, isNotEq]
+    exposes [Bool, Eq, true, false, and, or, not, isEq, isNotEq, compare]
     imports []
 
 ## Defines a type that can be compared for total equality.
@@ -126,3 +126,19 @@ structuralEq : a, a -> Bool
 # INTERNAL COMPILER USE ONLY: used to lower calls to `isNotEq` to structural
 # inequality via the `NotEq` low-level for derived types.
 structuralNotEq : a, a -> Bool
+
+## Orders two booleans, with `Bool.false` ordered before `Bool.true`.
+##
+## This is how `Bool` implements the [Sort](../Sort#Sort) ability.
+## ```
+## expect Bool.compare Bool.false Bool.true == LT
+## expect Bool.compare Bool.true Bool.true == EQ
+## ```
+compare : Bool, Bool -> [LT, EQ, GT]
+compare = \a, b ->
+    if a then
+        if b then EQ else GT
+    else if b then
+        LT
+    else
+        EQ
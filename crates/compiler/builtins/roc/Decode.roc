@@ -24,11 +24,18 @@ interface Decode
         list,
         record,
         tuple,
+        tag,
         custom,
         decodeWith,
         fromBytesPartial,
         fromBytes,
         mapResult,
+        set,
+        dict,
+        OptionalField,
+        optionalFieldFromResult,
+        optionalFieldToResult,
+        decodeMissingField,
     ]
     imports [
         List,
@@ -49,7 +56,10 @@ interface Decode
             F64,
             Dec,
         },
-        Bool.{ Bool },
+        Bool.{ Bool, Eq },
+        Dict.{ Dict },
+        Set.{ Set },
+        Hash.{ Hash },
     ]
 
 ## Error types when decoding a `List U8` of utf-8 bytes using a [Decoder]
@@ -101,9 +111,16 @@ DecoderFormatting has
     ## `record state stepField finalizer` decodes a record field-by-field.
     ##
     ## `stepField` returns a decoder for the given field in the record, or
-    ## `Skip` if the field is not a part of the decoded record.
+    ## `Skip` if the field is not a part of the decoded record. Field names
+    ## are those of the Roc record, so a format that renames fields, like
+    ## `Json` with a `FieldNameMapping`, should map them back first.
     ##
     ## `finalizer` should produce the record value from the decoded `state`.
+    ## Derived decoders let [OptionalField]s be missing from the input, and
+    ## fail if a field of any other type is missing.
+    ##
+    ## Formats decide what to do with fields that `stepField` skips; `Json`
+    ## ignores them unless configured otherwise.
     record : state, (state, Str -> [Keep (Decoder state fmt), Skip]), (state -> Result val DecodeError) -> Decoder val fmt | fmt has DecoderFormatting
 
     ## `tuple state stepElem finalizer` decodes a tuple element-by-element.
@@ -115,6 +132,15 @@ DecoderFormatting has
     ## `finalizer` should produce the tuple value from the decoded `state`.
     tuple : state, (state, Nat -> [Next (Decoder state fmt), TooLong]), (state -> Result val DecodeError) -> Decoder val fmt | fmt has DecoderFormatting
 
+    ## `tag stepTag` decodes a tag union value.
+    ##
+    ## `stepTag` is given the name of the tag that was found, and returns a
+    ## decoder for that tag's payload, or `UnknownTag` if the tag is not a part
+    ## of the decoded tag union. Derived decoders read the payload like a
+    ## [tuple] of the tag's arguments, so a tag without arguments has an empty
+    ## payload.
+    tag : (Str -> [Payload (Decoder val fmt), UnknownTag]) -> Decoder val fmt | fmt has DecoderFormatting
+
 ## Build a custom [Decoder] function. For example the implementation of
 ## `decodeBool` could be defined as follows;
 ##
@@ -169,3 +195,174 @@ fromBytes = \bytes, fmt ->
 ## Transform the `val` of a [DecodeResult]
 mapResult : DecodeResult a, (a -> b) -> DecodeResult b
 mapResult = \{ result, rest }, mapper -> { result: Result.map result mapper, rest }
+
+## Decode a [Set] from a [list] of its elements. Duplicate elements are
+## dropped.
+set : Decoder elem fmt -> Decoder (Set elem) fmt | elem has Hash & Eq, fmt has DecoderFormatting
+set = \decodeElem ->
+    custom \bytes, fmt ->
+        decodeWith bytes (list decodeElem) fmt
+        |> mapResult Set.fromList
+
+## Decode a [Dict] from a [list] of its entries, where each entry is a
+## two-element [tuple] of a key and its value. If a key appears more than once,
+## its last value is kept.
+dict : Decoder k fmt, Decoder v fmt -> Decoder (Dict k v) fmt | k has Hash & Eq, fmt has DecoderFormatting
+dict = \decodeKey, decodeValue ->
+    custom \bytes, fmt ->
+        decodeWith bytes (list (entry decodeKey decodeValue)) fmt
+        |> mapResult Dict.fromList
+
+entry : Decoder k fmt, Decoder v fmt -> Decoder [T k v] fmt | fmt has DecoderFormatting
+entry = \decodeKey, decodeValue ->
+    custom \bytes, fmt ->
+        stepElem = \state, index ->
+            when index is
+                0 ->
+                    Next
+                        (
+                            custom \keyBytes, keyFmt ->
+                                decodeWith keyBytes decodeKey keyFmt
+                                |> mapResult \key -> { state & key: Ok key }
+                        )
+
+                1 ->
+                    Next
+                        (
+                            custom \valueBytes, valueFmt ->
+                                decodeWith valueBytes decodeValue valueFmt
+                                |> mapResult \value -> { state & value: Ok value }
+                        )
+
+                _ -> TooLong
+
+        finalizer = \state ->
+            when state.key is
+                Ok key ->
+                    when state.value is
+                        Ok value -> Ok (T key value)
+                        Err NoElem -> Err TooShort
+
+                Err NoElem -> Err TooShort
+
+        decodeWith bytes (tuple { key: Err NoElem, value: Err NoElem } stepElem finalizer) fmt
+
+## A record field which may be left out of the input.
+##
+## When a derived record decoder does not find a field of this type in the
+## input, the field is missing, where a missing field of any other type makes
+## decoding fail. A field which is there is decoded like any other.
+## ```
+## expect
+##     input = Str.toUtf8 "{\"name\":\"Röc Lang\"}"
+##     actual : Result { name : Str, year : OptionalField U16 } _
+##     actual = Decode.fromBytes input Json.fromUtf8
+##
+##     Result.map actual \{ year } -> optionalFieldToResult year == Ok (Err Missing)
+## ```
+OptionalField val := Result val [Missing] has [Decoding { decoder: decodeOptionalField }, Eq]
+
+## Makes an [OptionalField], which is missing for `Err Missing`.
+optionalFieldFromResult : Result val [Missing] -> OptionalField val
+optionalFieldFromResult = \result -> @OptionalField result
+
+## The value of an [OptionalField], or `Err Missing` if it was missing.
+optionalFieldToResult : OptionalField val -> Result val [Missing]
+optionalFieldToResult = \@OptionalField result -> result
+
+# Not annotated, since the solver could not find the ambient lambda set of the
+# specialization through the opaque Decoder.
+decodeOptionalField = custom \bytes, fmt ->
+    if isMissingField fmt then
+        { result: Ok (@OptionalField (Err Missing)), rest: bytes }
+    else
+        decodeWith bytes decoder fmt
+        |> mapResult \val -> @OptionalField (Ok val)
+
+## Decodes a record field which never appeared in the input. This only
+## succeeds for an [OptionalField], and is how derived record decoders let
+## those be missing.
+##
+## Any other type fails to decode, unless its [Decoding] implementation makes
+## up a value without looking at the input or the format.
+decodeMissingField : {} -> Result val DecodeError | val has Decoding
+decodeMissingField = \{} ->
+    (decodeWith [] decoder (@MissingField {})).result
+
+# Whether `fmt` is the format decodeMissingField uses. Only its tag decoder
+# steps with missingFieldTag, which is not a valid tag name, so no derived tag
+# union decoder can accept it.
+isMissingField = \fmt ->
+    probe = tag \name ->
+        if name == missingFieldTag then
+            Payload (custom \rest, _ -> { result: Ok {}, rest })
+        else
+            UnknownTag
+
+    Result.isOk (decodeWith [] probe fmt).result
+
+missingFieldTag : Str
+missingFieldTag = "#MissingField"
+
+# The format missing record fields are decoded with. There is never anything
+# to decode, so every value fails, except for the tag isMissingField probes
+# with.
+MissingField := {} has [
+         DecoderFormatting {
+             u8: missingU8,
+             u16: missingU16,
+             u32: missingU32,
+             u64: missingU64,
+             u128: missingU128,
+             i8: missingI8,
+             i16: missingI16,
+             i32: missingI32,
+             i64: missingI64,
+             i128: missingI128,
+             f32: missingF32,
+             f64: missingF64,
+             dec: missingDec,
+             bool: missingBool,
+             string: missingString,
+             list: missingList,
+             record: missingRecord,
+             tuple: missingTuple,
+             tag: missingTag,
+         },
+     ]
+
+missingValue : List U8 -> DecodeResult val
+missingValue = \bytes -> { result: Err TooShort, rest: bytes }
+
+missingU8 = custom \bytes, @MissingField {} -> missingValue bytes
+missingU16 = custom \bytes, @MissingField {} -> missingValue bytes
+missingU32 = custom \bytes, @MissingField {} -> missingValue bytes
+missingU64 = custom \bytes, @MissingField {} -> missingValue bytes
+missingU128 = custom \bytes, @MissingField {} -> missingValue bytes
+missingI8 = custom \bytes, @MissingField {} -> missingValue bytes
+missingI16 = custom \bytes, @MissingField {} -> missingValue bytes
+missingI32 = custom \bytes, @MissingField {} -> missingValue bytes
+missingI64 = custom \bytes, @MissingField {} -> missingValue bytes
+missingI128 = custom \bytes, @MissingField {} -> missingValue bytes
+missingF32 = custom \bytes, @MissingField {} -> missingValue bytes
+missingF64 = custom \bytes, @MissingField {} -> missingValue bytes
+missingDec = custom \bytes, @MissingField {} -> missingValue bytes
+missingBool = custom \bytes, @MissingField {} -> missingValue bytes
+missingString = custom \bytes, @MissingField {} -> missingValue bytes
+
+# The arrows are annotated so that their lambda sets are exercised, and the
+# solver can find an ambient lambda set for the specialization.
+missingList : Decoder _ _ -> Decoder _ _
+missingList = \_decodeElem -> custom \bytes, @MissingField {} -> missingValue bytes
+
+missingRecord : _, (_, _ -> [Keep (Decoder _ _), Skip]), (_ -> _) -> Decoder _ _
+missingRecord = \_initialState, _stepField, _finalizer -> custom \bytes, @MissingField {} -> missingValue bytes
+
+missingTuple : _, (_, _ -> [Next (Decoder _ _), TooLong]), (_ -> _) -> Decoder _ _
+missingTuple = \_initialState, _stepElem, _finalizer -> custom \bytes, @MissingField {} -> missingValue bytes
+
+missingTag : (_ -> [Payload (Decoder _ _), UnknownTag]) -> Decoder _ _
+missingTag = \stepTag -> custom \bytes, @MissingField {} ->
+        when stepTag missingFieldTag is
+            Payload decodePayload -> decodeWith bytes decodePayload (@MissingField {})
+            UnknownTag -> missingValue bytes
@@ -21,6 +21,7 @@ interface Dict
         insertAll,
         keepShared,
         removeAll,
+        toSortedList,
     ]
     imports [
         Bool.{ Bool, Eq },
@@ -29,6 +30,7 @@ interface Dict
         Str,
         Num.{ Nat, U64, U8, I8 },
         Hash.{ Hasher, Hash },
+        Sort.{ Sort },
     ]
 
 ## A [dictionary](https://en.wikipedia.org/wiki/Associative_array) that lets you
@@ -429,6 +431,19 @@ toList : Dict k v -> List (T k v) | k has Hash & Eq
 toList = \@Dict { data } ->
     data
 
+## Returns the keys and values of a dictionary as a [List], ordered by key.
+## ```
+## expect
+##     Dict.single 3 "Three"
+##     |> Dict.insert 1 "One"
+##     |> Dict.insert 2 "Two"
+##     |> Dict.toSortedList
+##     |> Bool.isEq [T 1 "One", T 2 "Two", T 3 "Three"]
+## ```
+toSortedList : Dict k v -> List (T k v) | k has Hash & Eq & Sort
+toSortedList = \@Dict { data } ->
+    List.sortWith data (\T a _, T b _ -> Sort.compare a b)
+
 ## Returns the keys of a dictionary as a [List].
 ## This requires allocating a temporary [List], prefer using [Dict.toList] or [Dict.walk] instead.
 ## ```
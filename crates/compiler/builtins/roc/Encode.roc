@@ -69,7 +69,12 @@ EncoderFormatting has
     bool : Bool -> Encoder fmt | fmt has EncoderFormatting
     string : Str -> Encoder fmt | fmt has EncoderFormatting
     list : List elem, (elem -> Encoder fmt) -> Encoder fmt | fmt has EncoderFormatting
+
+    ## `record fields` encodes a record from its fields, where each `key` is
+    ## the name of the field in the Roc record. Formats may write the keys
+    ## differently, like `Json` with a `FieldNameMapping`.
     record : List { key : Str, value : Encoder fmt } -> Encoder fmt | fmt has EncoderFormatting
+
     tuple : List (Encoder fmt) -> Encoder fmt | fmt has EncoderFormatting
     tag : Str, List (Encoder fmt) -> Encoder fmt | fmt has EncoderFormatting
 
@@ -0,0 +1,289 @@
+## Turn any value into a human-readable description of it, for example to log
+## structured values from an application.
+##
+## ```
+## expect Inspect.toStr { name: "Röc", tags: [Fast, Friendly] } == "{name: \"Röc\", tags: [Fast, Friendly]}"
+## ```
+##
+## Records, tuples, tag unions, lists, [Dict]s, [Set]s and all builtin
+## primitives can be inspected without writing any code. Opaque types are shown
+## as `<opaque>` unless they list `Inspect` in their `has` clause or provide a
+## custom implementation of [toInspector].
+##
+## How the description is written out is controlled by an [InspectFormatter].
+## [toStr] uses the builtin debug formatter, which produces output close to
+## the syntax of Roc source code.
+interface Inspect
+    exposes [
+        Inspect,
+        Inspector,
+        InspectFormatter,
+        toInspector,
+        bool,
+        str,
+        u8,
+        u16,
+        u32,
+        u64,
+        u128,
+        i8,
+        i16,
+        i32,
+        i64,
+        i128,
+        nat,
+        f32,
+        f64,
+        dec,
+        list,
+        set,
+        dict,
+        record,
+        tuple,
+        tag,
+        opaque,
+        custom,
+        apply,
+        DbgFormatter,
+        toStr,
+    ]
+    imports [
+        Bool.{ Bool },
+        Num.{
+            U8,
+            U16,
+            U32,
+            U64,
+            U128,
+            I8,
+            I16,
+            I32,
+            I64,
+            I128,
+            Nat,
+            F32,
+            F64,
+            Dec,
+        },
+        List,
+        Str,
+        Result,
+        Dict.{ Dict },
+        Set.{ Set },
+        Hash.{ Hash },
+        Bool.{ Eq },
+    ]
+
+## Describes how to write a value out with the formatter `f`.
+Inspector f := f -> f | f has InspectFormatter
+
+## Values that can be turned into an [Inspector].
+Inspect has
+    toInspector : val -> Inspector f | val has Inspect, f has InspectFormatter
+
+## A way of writing out inspected values.
+InspectFormatter has
+    bool : Bool -> Inspector f | f has InspectFormatter
+    str : Str -> Inspector f | f has InspectFormatter
+    u8 : U8 -> Inspector f | f has InspectFormatter
+    u16 : U16 -> Inspector f | f has InspectFormatter
+    u32 : U32 -> Inspector f | f has InspectFormatter
+    u64 : U64 -> Inspector f | f has InspectFormatter
+    u128 : U128 -> Inspector f | f has InspectFormatter
+    i8 : I8 -> Inspector f | f has InspectFormatter
+    i16 : I16 -> Inspector f | f has InspectFormatter
+    i32 : I32 -> Inspector f | f has InspectFormatter
+    i64 : I64 -> Inspector f | f has InspectFormatter
+    i128 : I128 -> Inspector f | f has InspectFormatter
+    nat : Nat -> Inspector f | f has InspectFormatter
+    f32 : F32 -> Inspector f | f has InspectFormatter
+    f64 : F64 -> Inspector f | f has InspectFormatter
+    dec : Dec -> Inspector f | f has InspectFormatter
+    list : List elem, (elem -> Inspector f) -> Inspector f | f has InspectFormatter
+    set : Set elem, (elem -> Inspector f) -> Inspector f | elem has Hash & Eq, f has InspectFormatter
+    dict : Dict k v, (k -> Inspector f), (v -> Inspector f) -> Inspector f | k has Hash & Eq, f has InspectFormatter
+    record : List { key : Str, value : Inspector f } -> Inspector f | f has InspectFormatter
+    tuple : List (Inspector f) -> Inspector f | f has InspectFormatter
+    tag : Str, List (Inspector f) -> Inspector f | f has InspectFormatter
+    opaque : * -> Inspector f | f has InspectFormatter
+
+## Build an [Inspector] from a function that writes to a formatter.
+custom : (f -> f) -> Inspector f | f has InspectFormatter
+custom = \inspector -> @Inspector inspector
+
+## Write a value out to a formatter using its [Inspector].
+apply : Inspector f, f -> f | f has InspectFormatter
+apply = \@Inspector inspector, fmt -> inspector fmt
+
+## Describe a value using the builtin debug formatter.
+## ```
+## expect Inspect.toStr (Ok [1, 2]) == "Ok [1, 2]"
+## ```
+toStr : val -> Str | val has Inspect
+toStr = \val ->
+    when apply (toInspector val) (@DbgFormatter { data: "" }) is
+        @DbgFormatter { data } -> data
+
+## The formatter used by [toStr].
+DbgFormatter := { data : Str }
+     has [
+         InspectFormatter {
+             bool: dbgBool,
+             str: dbgStr,
+             u8: dbgU8,
+             u16: dbgU16,
+             u32: dbgU32,
+             u64: dbgU64,
+             u128: dbgU128,
+             i8: dbgI8,
+             i16: dbgI16,
+             i32: dbgI32,
+             i64: dbgI64,
+             i128: dbgI128,
+             nat: dbgNat,
+             f32: dbgF32,
+             f64: dbgF64,
+             dec: dbgDec,
+             list: dbgList,
+             set: dbgSet,
+             dict: dbgDict,
+             record: dbgRecord,
+             tuple: dbgTuple,
+             tag: dbgTag,
+             opaque: dbgOpaque,
+         },
+     ]
+
+write : DbgFormatter, Str -> DbgFormatter
+write = \@DbgFormatter { data }, added ->
+    @DbgFormatter { data: Str.concat data added }
+
+dbgBool = \b -> custom \fmt ->
+        if b then
+            write fmt "Bool.true"
+        else
+            write fmt "Bool.false"
+
+dbgStr = \s -> custom \fmt ->
+        escaped =
+            s
+            |> replaceAll "\\" "\\\\"
+            |> replaceAll "\"" "\\\""
+            |> replaceAll "\n" "\\n"
+
+        fmt
+        |> write "\""
+        |> write escaped
+        |> write "\""
+
+replaceAll : Str, Str, Str -> Str
+replaceAll = \haystack, needle, flower ->
+    Str.replaceEach haystack needle flower
+    |> Result.withDefault haystack
+
+dbgU8 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgU16 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgU32 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgU64 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgU128 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgI8 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgI16 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgI32 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgI64 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgI128 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgNat = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgF32 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgF64 = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+dbgDec = \n -> custom \fmt -> write fmt (Num.toStr n)
+
+## Writes `elems` separated by commas, between `open` and `close`.
+writeSequence : DbgFormatter, List a, (DbgFormatter, a -> DbgFormatter), Str, Str -> DbgFormatter
+writeSequence = \fmt, elems, writeElem, open, close ->
+    withOpen = write fmt open
+    { state: withElems } =
+        List.walk elems { state: withOpen, first: Bool.true } \{ state, first }, elem ->
+            withSep = if first then state else write state ", "
+
+            { state: writeElem withSep elem, first: Bool.false }
+
+    write withElems close
+
+dbgList = \elems, toElem -> custom \fmt ->
+        writeSequence fmt elems (\acc, elem -> apply (toElem elem) acc) "[" "]"
+
+dbgSet = \elems, toElem -> custom \fmt ->
+        writeSequence fmt (Set.toList elems) (\acc, elem -> apply (toElem elem) acc) "{" "}"
+
+dbgDict = \entries, toKey, toValue -> custom \fmt ->
+        withOpen = write fmt "{"
+        { state: withEntries } =
+            Dict.walk entries { state: withOpen, first: Bool.true } \{ state, first }, key, value ->
+                withSep = if first then state else write state ", "
+                withEntry =
+                    apply (toKey key) withSep
+                    |> write ": "
+                    |> \acc -> apply (toValue value) acc
+
+                { state: withEntry, first: Bool.false }
+
+        write withEntries "}"
+
+dbgRecord = \fields -> custom \fmt ->
+        writeField = \acc, { key, value } ->
+            write acc key
+            |> write ": "
+            |> \withKey -> apply value withKey
+
+        writeSequence fmt fields writeField "{" "}"
+
+dbgTuple = \elems -> custom \fmt ->
+        writeSequence fmt elems (\acc, elem -> apply elem acc) "(" ")"
+
+dbgTag = \name, payloads -> custom \fmt ->
+        List.walk payloads (write fmt name) \acc, payload ->
+            write acc " "
+            |> write (parenthesize (render payload))
+
+dbgOpaque = \_ -> custom \fmt -> write fmt "<opaque>"
+
+render : Inspector DbgFormatter -> Str
+render = \inspector ->
+    when apply inspector (@DbgFormatter { data: "" }) is
+        @DbgFormatter { data } -> data
+
+## Wraps a tag payload in parentheses if it would otherwise be read as several
+## payloads, as with a nested tag that has payloads of its own.
+parenthesize : Str -> Str
+parenthesize = \payload ->
+    bytes = Str.toUtf8 payload
+    delimited =
+        when List.first bytes is
+            Ok '[' | Ok '{' | Ok '(' | Ok '"' -> Bool.true
+            _ -> Bool.false
+
+    if !delimited && List.contains bytes ' ' then
+        "(\(payload))"
+    else
+        payload
+
+expect toStr "hi" == "\"hi\""
+expect toStr "say \"hi\"\n" == "\"say \\\"hi\\\"\\n\""
+expect toStr Bool.true == "Bool.true"
+expect toStr [1u8, 2, 3] == "[1, 2, 3]"
+expect toStr { a: 1u8, b: "x" } == "{a: 1, b: \"x\"}"
+expect toStr ("x", -2i64) == "(\"x\", -2)"
+expect toStr (Set.fromList [1u16]) == "{1}"
+expect toStr (Dict.single "k" 1u8) == "{\"k\": 1}"
@@ -11,7 +11,7 @@
 ## type named `Language` which includes a `name` field. The JSON string is
 ## decoded and then the field is encoded back into a UTF-8 string.
 ##
-## ```
+## ```roc
 ## Language : {
 ##     name : Str,
 ## }
@@ -40,6 +40,9 @@ interface Json
         Json,
         toUtf8,
         fromUtf8,
+        toUtf8With,
+        fromUtf8With,
+        FieldNameMapping,
     ]
     imports [
         List,
@@ -70,14 +73,20 @@ interface Json
             F32,
             F64,
             Dec,
+            Nat,
         },
         Bool.{ Bool, Eq },
         Result,
+        Dict.{ Dict },
+        Set.{ Set },
     ]
 
 ## An opaque type with the `EncoderFormatting` and
 ## `DecoderFormatting` abilities.
-Json := {} has [
+Json := {
+    unknownFields : [Ignore, Reject],
+    fieldNameMapping : FieldNameMapping,
+} has [
          EncoderFormatting {
              u8: encodeU8,
              u16: encodeU16,
@@ -118,14 +127,135 @@ Json := {} has [
              list: decodeList,
              record: decodeRecord,
              tuple: decodeTuple,
+             tag: decodeTag,
          },
      ]
 
+## How the camelCase field names of Roc records are written as the names of
+## JSON object fields.
+##
+## ```
+## Default     # firstName
+## SnakeCase   # first_name
+## KebabCase   # first-name
+## PascalCase  # FirstName
+## ```
+##
+## `Custom` uses `toObjectName` to name object fields when encoding, and
+## `fromObjectName` to turn them back into record field names when decoding.
+FieldNameMapping : [
+    Default,
+    SnakeCase,
+    KebabCase,
+    PascalCase,
+    Custom { toObjectName : Str -> Str, fromObjectName : Str -> Str },
+]
+
 ## Returns a JSON `Decoder`
-toUtf8 = @Json {}
+toUtf8 = @Json { unknownFields: Ignore, fieldNameMapping: Default }
 
 ## Returns a JSON `Encoder`
-fromUtf8 = @Json {}
+fromUtf8 = @Json { unknownFields: Ignore, fieldNameMapping: Default }
+
+## Returns a JSON `Encoder` which names object fields using the given
+## [FieldNameMapping].
+## ```
+## expect
+##     actual = Encode.toBytes { firstName: "Röc" } (Json.toUtf8With { fieldNameMapping: SnakeCase })
+##     expected = Str.toUtf8 "{\"first_name\":\"Röc\"}"
+##
+##     actual == expected
+## ```
+toUtf8With : { fieldNameMapping ? FieldNameMapping } -> Json
+toUtf8With = \{ fieldNameMapping ? Default } -> @Json { unknownFields: Ignore, fieldNameMapping }
+
+## Returns a JSON `Decoder` with the given options.
+##
+## `unknownFields` decides what happens to object fields that are not a part
+## of the decoded record: `Ignore` skips over them, like [fromUtf8] does, and
+## `Reject` makes decoding fail. The error is `TooShort`, the same as for any
+## other object which does not match the record.
+##
+## `fieldNameMapping` decides how object field names are matched to record
+## field names, see [FieldNameMapping].
+## ```
+## expect
+##     input = Str.toUtf8 "{\"name\":\"Röc Lang\",\"year\":2023}"
+##     actual : Result { name : Str } _
+##     actual = Decode.fromBytes input (Json.fromUtf8With { unknownFields: Reject })
+##
+##     Result.isErr actual
+## ```
+fromUtf8With : { unknownFields ? [Ignore, Reject], fieldNameMapping ? FieldNameMapping } -> Json
+fromUtf8With = \{ unknownFields ? Ignore, fieldNameMapping ? Default } -> @Json { unknownFields, fieldNameMapping }
+
+# The name of the JSON object field for a record field.
+toObjectName : Str, FieldNameMapping -> Str
+toObjectName = \fieldName, mapping ->
+    when mapping is
+        Default -> fieldName
+        SnakeCase -> separateWords fieldName '_'
+        KebabCase -> separateWords fieldName '-'
+        PascalCase -> mapFirstByte fieldName toUpper
+        Custom { toObjectName: transform } -> transform fieldName
+
+# The name of the record field for a JSON object field.
+fromObjectName : Str, FieldNameMapping -> Str
+fromObjectName = \objectName, mapping ->
+    when mapping is
+        Default -> objectName
+        SnakeCase -> joinWords objectName '_'
+        KebabCase -> joinWords objectName '-'
+        PascalCase -> mapFirstByte objectName toLower
+        Custom { fromObjectName: transform } -> transform objectName
+
+# Splits a camelCase name into lowercase words, e.g. `firstName` becomes `first_name`
+separateWords : Str, U8 -> Str
+separateWords = \name, separator ->
+    Str.toUtf8 name
+    |> List.walk [] \bytes, b ->
+        if isUpper b then
+            bytes |> List.append separator |> List.append (toLower b)
+        else
+            List.append bytes b
+    |> asciiToStr name
+
+# Joins separated words into a camelCase name, e.g. `first_name` becomes `firstName`
+joinWords : Str, U8 -> Str
+joinWords = \name, separator ->
+    { bytes } =
+        Str.toUtf8 name
+        |> List.walk { bytes: [], capitalize: Bool.false } \state, b ->
+            if b == separator then
+                { bytes: state.bytes, capitalize: Bool.true }
+            else if state.capitalize then
+                { bytes: List.append state.bytes (toUpper b), capitalize: Bool.false }
+            else
+                { bytes: List.append state.bytes b, capitalize: Bool.false }
+
+    asciiToStr bytes name
+
+mapFirstByte : Str, (U8 -> U8) -> Str
+mapFirstByte = \name, mapper ->
+    bytes = Str.toUtf8 name
+
+    when List.first bytes is
+        Ok first -> asciiToStr (List.set bytes 0 (mapper first)) name
+        Err ListWasEmpty -> name
+
+# Only ASCII letters are ever changed, so the bytes are still valid UTF-8.
+asciiToStr : List U8, Str -> Str
+asciiToStr = \bytes, original ->
+    Str.fromUtf8 bytes |> Result.withDefault original
+
+isUpper : U8 -> Bool
+isUpper = \b -> b >= 'A' && b <= 'Z'
+
+toUpper : U8 -> U8
+toUpper = \b -> if b >= 'a' && b <= 'z' then b - 32 else b
+
+toLower : U8 -> U8
+toLower = \b -> if isUpper b then b + 32 else b
 
 numToBytes = \n ->
     n |> Num.toStr |> Str.toUtf8
@@ -170,9 +300,9 @@ encodeString = \s -> Encode.custom \bytes, @Json {} ->
         |> List.append (Num.toU8 '"')
 
 encodeList = \lst, encodeElem ->
-    Encode.custom \bytes, @Json {} ->
+    Encode.custom \bytes, @Json config ->
         writeList = \{ buffer, elemsLeft }, elem ->
-            bufferWithElem = appendWith buffer (encodeElem elem) (@Json {})
+            bufferWithElem = appendWith buffer (encodeElem elem) (@Json config)
             bufferWithSuffix =
                 if elemsLeft > 1 then
                     List.append bufferWithElem (Num.toU8 ',')
@@ -187,14 +317,14 @@ encodeList = \lst, encodeElem ->
         List.append withList (Num.toU8 ']')
 
 encodeRecord = \fields ->
-    Encode.custom \bytes, @Json {} ->
+    Encode.custom \bytes, @Json config ->
         writeRecord = \{ buffer, fieldsLeft }, { key, value } ->
             bufferWithKeyValue =
                 List.append buffer (Num.toU8 '"')
-                |> List.concat (Str.toUtf8 key)
+                |> List.concat (Str.toUtf8 (toObjectName key config.fieldNameMapping))
                 |> List.append (Num.toU8 '"')
                 |> List.append (Num.toU8 ':')
-                |> appendWith value (@Json {})
+                |> appendWith value (@Json config)
 
             bufferWithSuffix =
                 if fieldsLeft > 1 then
@@ -210,10 +340,10 @@ encodeRecord = \fields ->
         List.append bytesWithRecord (Num.toU8 '}')
 
 encodeTuple = \elems ->
-    Encode.custom \bytes, @Json {} ->
+    Encode.custom \bytes, @Json config ->
         writeTuple = \{ buffer, elemsLeft }, elemEncoder ->
             bufferWithElem =
-                appendWith buffer elemEncoder (@Json {})
+                appendWith buffer elemEncoder (@Json config)
 
             bufferWithSuffix =
                 if elemsLeft > 1 then
@@ -229,10 +359,10 @@ encodeTuple = \elems ->
         List.append bytesWithRecord (Num.toU8 ']')
 
 encodeTag = \name, payload ->
-    Encode.custom \bytes, @Json {} ->
+    Encode.custom \bytes, @Json config ->
         # Idea: encode `A v1 v2` as `{"A": [v1, v2]}`
         writePayload = \{ buffer, itemsLeft }, encoder ->
-            bufferWithValue = appendWith buffer encoder (@Json {})
+            bufferWithValue = appendWith buffer encoder (@Json config)
             bufferWithSuffix =
                 if itemsLeft > 1 then
                     List.append bufferWithValue (Num.toU8 ',')
@@ -445,10 +575,10 @@ jsonString = \bytes ->
 decodeString = Decode.custom \bytes, @Json {} ->
     jsonString bytes
 
-decodeList = \decodeElem -> Decode.custom \bytes, @Json {} ->
+decodeList = \decodeElem -> Decode.custom \bytes, @Json config ->
 
         decodeElems = \chunk, accum ->
-            when Decode.decodeWith chunk decodeElem (@Json {}) is
+            when Decode.decodeWith chunk decodeElem (@Json config) is
                 { result, rest } ->
                     when result is
                         Err e -> Errored e rest
@@ -510,6 +640,61 @@ recordKey = \bytes -> jsonString bytes
 anything : List U8 -> DecodeResult {}
 anything = \bytes -> { result: Err TooShort, rest: bytes }
 
+# Skips over a single Json value of any kind, which is how fields that are not
+# a part of the decoded record are ignored.
+skipValue : List U8 -> DecodeResult {}
+skipValue = \bytes ->
+    input = eatWhitespace bytes
+
+    when input is
+        ['"', ..] -> skipString (List.dropFirst input)
+        ['{', ..] | ['[', ..] -> skipNested (List.dropFirst input) 1
+        _ -> skipScalar input
+
+# Skips the rest of a string whose opening quote has already been read.
+skipString : List U8 -> DecodeResult {}
+skipString = \bytes ->
+    when bytes is
+        ['\\', _, ..] -> skipString (List.drop bytes 2)
+        ['"', ..] -> { result: Ok {}, rest: List.dropFirst bytes }
+        [_, ..] -> skipString (List.dropFirst bytes)
+        [] -> { result: Err TooShort, rest: bytes }
+
+# Skips the rest of an object or array whose opening bracket has already been
+# read, along with everything nested in it.
+skipNested : List U8, Nat -> DecodeResult {}
+skipNested = \bytes, depth ->
+    when bytes is
+        ['"', ..] ->
+            { rest: afterString } <- skipString (List.dropFirst bytes) |> tryDecode
+            skipNested afterString depth
+
+        ['{', ..] | ['[', ..] -> skipNested (List.dropFirst bytes) (depth + 1)
+        ['}', ..] | [']', ..] ->
+            if depth == 1 then
+                { result: Ok {}, rest: List.dropFirst bytes }
+            else
+                skipNested (List.dropFirst bytes) (depth - 1)
+
+        [_, ..] -> skipNested (List.dropFirst bytes) depth
+        [] -> { result: Err TooShort, rest: bytes }
+
+# Skips a number, `true`, `false` or `null`.
+skipScalar : List U8 -> DecodeResult {}
+skipScalar = \bytes ->
+    { taken, rest } = takeWhile bytes \b -> !(isScalarEnd b)
+
+    if List.isEmpty taken then
+        { result: Err TooShort, rest: bytes }
+    else
+        { result: Ok {}, rest }
+
+isScalarEnd : U8 -> Bool
+isScalarEnd = \b ->
+    when b is
+        ',' | '}' | ']' | ' ' | '\n' | '\r' | '\t' -> Bool.true
+        _ -> Bool.false
+
 colon : List U8 -> DecodeResult {}
 colon = \bytes -> parseExactChar bytes ':'
 
@@ -522,20 +707,27 @@ tryDecode = \{ result, rest }, mapper ->
         Ok val -> mapper { val, rest }
         Err e -> { result: Err e, rest }
 
-decodeRecord = \initialState, stepField, finalizer -> Decode.custom \bytes, @Json {} ->
+decodeRecord = \initialState, stepField, finalizer -> Decode.custom \bytes, @Json config ->
         # NB: the stepper function must be passed explicitly until #2894 is resolved.
         decodeFields = \stepper, state, kvBytes ->
             { val: key, rest } <- recordKey kvBytes |> tryDecode
             { rest: afterColonBytes } <- colon rest |> tryDecode
             { val: newState, rest: beforeCommaOrBreak } <- tryDecode
                     (
-                        when stepper state key is
+                        when stepper state (fromObjectName key config.fieldNameMapping) is
                             Skip ->
-                                { rest: beforeCommaOrBreak } <- afterColonBytes |> anything |> tryDecode
-                                { result: Ok state, rest: beforeCommaOrBreak }
+                                when config.unknownFields is
+                                    Ignore ->
+                                        { rest: beforeCommaOrBreak } <- afterColonBytes |> skipValue |> tryDecode
+                                        { result: Ok state, rest: beforeCommaOrBreak }
+
+                                    Reject ->
+                                        # DecodeError has no way to say which field it was, so this
+                                        # fails like any other object which does not match the record.
+                                        { result: Err TooShort, rest: afterColonBytes }
 
                             Keep decoder ->
-                                Decode.decodeWith afterColonBytes decoder (@Json {})
+                                Decode.decodeWith afterColonBytes decoder (@Json config)
                     )
 
             { result: commaResult, rest: nextBytes } = comma beforeCommaOrBreak
@@ -546,7 +738,14 @@ decodeRecord = \initialState, stepField, finalizer -> Decode.custom \bytes, @Jso
 
         { rest: afterBraceBytes } <- bytes |> openBrace |> tryDecode
 
-        { val: endStateResult, rest: beforeClosingBraceBytes } <- decodeFields stepField initialState afterBraceBytes |> tryDecode
+        { val: endStateResult, rest: beforeClosingBraceBytes } <- tryDecode
+                (
+                    # An empty object has no fields to step through, which is fine when every
+                    # field of the record is an OptionalField.
+                    when eatWhitespace afterBraceBytes is
+                        ['}', ..] -> { result: Ok initialState, rest: eatWhitespace afterBraceBytes }
+                        _ -> decodeFields stepField initialState afterBraceBytes
+                )
 
         { rest: afterRecordBytes } <- beforeClosingBraceBytes |> closingBrace |> tryDecode
 
@@ -554,7 +753,7 @@ decodeRecord = \initialState, stepField, finalizer -> Decode.custom \bytes, @Jso
             Ok val -> { result: Ok val, rest: afterRecordBytes }
             Err e -> { result: Err e, rest: afterRecordBytes }
 
-decodeTuple = \initialState, stepElem, finalizer -> Decode.custom \initialBytes, @Json {} ->
+decodeTuple = \initialState, stepElem, finalizer -> Decode.custom \initialBytes, @Json config ->
         # NB: the stepper function must be passed explicitly until #2894 is resolved.
         decodeElems = \stepper, state, index, bytes ->
             { val: newState, rest: beforeCommaOrBreak } <- tryDecode
@@ -565,7 +764,7 @@ decodeTuple = \initialState, stepElem, finalizer -> Decode.custom \initialBytes,
                                 { result: Ok state, rest: beforeCommaOrBreak }
 
                             Next decoder ->
-                                Decode.decodeWith bytes decoder (@Json {})
+                                Decode.decodeWith bytes decoder (@Json config)
                     )
 
             { result: commaResult, rest: nextBytes } = comma beforeCommaOrBreak
@@ -576,7 +775,14 @@ decodeTuple = \initialState, stepElem, finalizer -> Decode.custom \initialBytes,
 
         { rest: afterBracketBytes } <- initialBytes |> openBracket |> tryDecode
 
-        { val: endStateResult, rest: beforeClosingBracketBytes } <- decodeElems stepElem initialState 0 afterBracketBytes |> tryDecode
+        { val: endStateResult, rest: beforeClosingBracketBytes } <- tryDecode
+                (
+                    # An empty tuple has no elements to step through, e.g. the payload of a tag
+                    # without arguments.
+                    when eatWhitespace afterBracketBytes is
+                        [']', ..] -> { result: Ok initialState, rest: eatWhitespace afterBracketBytes }
+                        _ -> decodeElems stepElem initialState 0 afterBracketBytes
+                )
 
         { rest: afterTupleBytes } <- beforeClosingBracketBytes |> closingBracket |> tryDecode
 
@@ -584,6 +790,28 @@ decodeTuple = \initialState, stepElem, finalizer -> Decode.custom \initialBytes,
             Ok val -> { result: Ok val, rest: afterTupleBytes }
             Err e -> { result: Err e, rest: afterTupleBytes }
 
+decodeTag = \stepTag -> Decode.custom \bytes, @Json config ->
+        # Idea: decode `{"A": [v1, v2]}` as `A v1 v2`, the inverse of `encodeTag`
+        { rest: afterBraceBytes } <- bytes |> openBrace |> tryDecode
+
+        { val: name, rest: afterNameBytes } <- afterBraceBytes |> eatWhitespace |> recordKey |> tryDecode
+
+        { rest: afterColonBytes } <- afterNameBytes |> eatWhitespace |> colon |> tryDecode
+
+        { val, rest: beforeClosingBraceBytes } <- tryDecode
+                (
+                    when stepTag name is
+                        Payload decoder ->
+                            Decode.decodeWith (eatWhitespace afterColonBytes) decoder (@Json config)
+
+                        UnknownTag ->
+                            { result: Err TooShort, rest: afterColonBytes }
+                )
+
+        { rest: afterTagBytes } <- beforeClosingBraceBytes |> eatWhitespace |> closingBrace |> tryDecode
+
+        { result: Ok val, rest: afterTagBytes }
+
 # Helper to eat leading Json whitespace characters
 eatWhitespace = \input ->
     when input is
@@ -642,3 +870,145 @@ expect
     expected = Ok ["one", "two", "3"]
 
     actual == expected
+
+# Test json tag decoding
+expect
+    input = Str.toUtf8 "{\"Pair\": [1, \"two\"]}"
+    actual : Result [Pair U8 Str, Empty] _
+    actual = Decode.fromBytes input fromUtf8
+    expected = Ok (Pair 1 "two")
+
+    actual == expected
+
+# Test json tag decoding of a tag without a payload
+expect
+    input = Str.toUtf8 "{\"Empty\":[]}"
+    actual : Result [Pair U8 Str, Empty] _
+    actual = Decode.fromBytes input fromUtf8
+    expected = Ok Empty
+
+    actual == expected
+
+# Test json tag decoding fails on a tag that is not in the union
+expect
+    input = Str.toUtf8 "{\"Other\":[]}"
+    actual : Result [Pair U8 Str, Empty] _
+    actual = Decode.fromBytes input fromUtf8
+
+    Result.isErr actual
+
+# Test json tag encoding round-trips through decoding
+expect
+    value : [Leaf, Node U8 (List Str)]
+    value = Node 7 ["a", "b"]
+    actual = Decode.fromBytes (Encode.toBytes value toUtf8) fromUtf8
+
+    actual == Ok value
+
+# Test json set decoding drops duplicates
+expect
+    input = Str.toUtf8 "[1, 2, 2, 3]"
+    actual : Result (Set U8) _
+    actual = Decode.fromBytes input fromUtf8
+    expected = Ok (Set.fromList [1, 2, 3])
+
+    actual == expected
+
+# Test json dict decoding from a list of key-value pairs
+expect
+    input = Str.toUtf8 "[[\"a\", 1], [\"b\", 2]]"
+    actual : Result (Dict Str U8) _
+    actual = Decode.fromBytes input fromUtf8
+    expected = Ok [T "a" 1, T "b" 2]
+
+    Result.map actual Dict.toList == expected
+
+# Test json record decoding ignores unknown fields of any kind
+expect
+    input = Str.toUtf8 "{\"extra\":{\"a\":[1,\"]}\\\"\"]},\"name\":\"Röc\",\"more\":true,\"year\":2023,\"last\":null}"
+    actual : Result { name : Str, year : U16 } _
+    actual = Decode.fromBytes input fromUtf8
+    expected = Ok { name: "Röc", year: 2023 }
+
+    actual == expected
+
+# Test json record decoding rejects unknown fields when configured to
+expect
+    input = Str.toUtf8 "{\"name\":\"Röc\",\"year\":2023}"
+    actual : Result { name : Str } _
+    actual = Decode.fromBytes input (fromUtf8With { unknownFields: Reject })
+
+    Result.isErr actual
+
+# Test json record decoding of optional fields
+expect
+    decodeYear = \input ->
+        actual : Result { name : Str, year : Decode.OptionalField U16 } _
+        actual = Decode.fromBytes (Str.toUtf8 input) fromUtf8
+
+        Result.map actual \{ year } -> Decode.optionalFieldToResult year
+
+    present = decodeYear "{\"name\":\"Röc\",\"year\":2023}"
+    missing = decodeYear "{\"name\":\"Röc\"}"
+    invalid = decodeYear "{\"name\":\"Röc\",\"year\":\"soon\"}"
+
+    present == Ok (Ok 2023) && missing == Ok (Err Missing) && Result.isErr invalid
+
+# Test json decoding of an optional field with no bytes left fails, rather than being missing
+expect
+    actual : DecodeResult (Decode.OptionalField U16)
+    actual = Decode.decodeWith [] Decode.decoder fromUtf8
+
+    Result.isErr actual.result
+
+# Test json record decoding fails on a missing field that is not optional
+expect
+    input = Str.toUtf8 "{\"name\":\"Röc\"}"
+    actual : Result { name : Str, year : U16 } _
+    actual = Decode.fromBytes input fromUtf8
+
+    Result.isErr actual
+
+# Test json record encoding with field name mappings
+expect
+    value = { firstName: "a", lastName: "b" }
+    actual =
+        [SnakeCase, KebabCase, PascalCase]
+        |> List.map \fieldNameMapping -> Encode.toBytes value (toUtf8With { fieldNameMapping })
+    expected = [
+        Str.toUtf8 "{\"first_name\":\"a\",\"last_name\":\"b\"}",
+        Str.toUtf8 "{\"first-name\":\"a\",\"last-name\":\"b\"}",
+        Str.toUtf8 "{\"FirstName\":\"a\",\"LastName\":\"b\"}",
+    ]
+
+    actual == expected
+
+# Test json record decoding with field name mappings
+expect
+    input = Str.toUtf8 "{\"first_name\":\"a\",\"last_name\":\"b\"}"
+    actual : Result { firstName : Str, lastName : Str } _
+    actual = Decode.fromBytes input (fromUtf8With { fieldNameMapping: SnakeCase })
+    expected = Ok { firstName: "a", lastName: "b" }
+
+    actual == expected
+
+# Test json record field name mappings round-trip
+expect
+    value = { firstName: "a", userId: 1u8 }
+    mappings = [SnakeCase, KebabCase, PascalCase]
+
+    List.all mappings \fieldNameMapping ->
+        bytes = Encode.toBytes value (toUtf8With { fieldNameMapping })
+        Decode.fromBytes bytes (fromUtf8With { fieldNameMapping }) == Ok value
+
+# Test json record decoding with a custom field name mapping
+expect
+    input = Str.toUtf8 "{\"$name\":\"a\"}"
+    fieldNameMapping = Custom {
+        toObjectName: \name -> Str.concat "$" name,
+        fromObjectName: \name -> Str.replaceFirst name "$" "" |> Result.withDefault name,
+    }
+    actual : Result { name : Str } _
+    actual = Decode.fromBytes input (fromUtf8With { fieldNameMapping })
+
+    actual == Ok { name: "a" }
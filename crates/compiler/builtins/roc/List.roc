@@ -61,6 +61,7 @@ interface List
         dropIf,
         sortAsc,
         sortDesc,
+        compare,
         reserve,
         releaseExcessCapacity,
         walkBackwardsUntil,
@@ -70,6 +71,7 @@ interface List
         Bool.{ Bool, Eq },
         Result.{ Result },
         Num.{ Nat, Num, Int },
+        Sort.{ Sort },
     ]
 
 ## Types
@@ -798,19 +800,46 @@ expect
 ## Sort with a custom comparison function
 sortWith : List a, (a, a -> [LT, EQ, GT]) -> List a
 
-## Sorts a list in ascending order (lowest to highest), using a function which
-## specifies a way to represent each element as a number.
+## Sorts a list in ascending order (lowest to highest), using the element
+## type's [Sort](../Sort#Sort) ability to compare elements.
 ##
 ## To sort in descending order (highest to lowest), use [List.sortDesc] instead.
-sortAsc : List (Num a) -> List (Num a)
-sortAsc = \list -> List.sortWith list Num.compare
+## ```
+## expect List.sortAsc [3, 1, 2] == [1, 2, 3]
+## expect List.sortAsc [{ x: 2, y: 1 }, { x: 1, y: 5 }] == [{ x: 1, y: 5 }, { x: 2, y: 1 }]
+## ```
+sortAsc : List a -> List a | a has Sort
+sortAsc = \list -> List.sortWith list Sort.compare
 
-## Sorts a list in descending order (highest to lowest), using a function which
-## specifies a way to represent each element as a number.
+## Sorts a list in descending order (highest to lowest), using the element
+## type's [Sort](../Sort#Sort) ability to compare elements.
 ##
 ## To sort in ascending order (lowest to highest), use [List.sortAsc] instead.
-sortDesc : List (Num a) -> List (Num a)
-sortDesc = \list -> List.sortWith list (\a, b -> Num.compare b a)
+sortDesc : List a -> List a | a has Sort
+sortDesc = \list -> List.sortWith list (\a, b -> Sort.compare b a)
+
+## Compares two lists lexicographically: element by element from the front,
+## with a list that is a prefix of the other ordered first.
+##
+## This is how `List` implements the [Sort](../Sort#Sort) ability.
+## ```
+## expect List.compare [1, 2] [1, 3] == LT
+## expect List.compare [1, 2] [1] == GT
+## ```
+compare : List a, List a -> [LT, EQ, GT] | a has Sort
+compare = \xs, ys ->
+    shared = if List.len xs < List.len ys then List.len xs else List.len ys
+
+    compareHelp xs ys 0 shared
+
+compareHelp : List a, List a, Nat, Nat -> [LT, EQ, GT] | a has Sort
+compareHelp = \xs, ys, index, shared ->
+    if index < shared then
+        when Sort.compare (List.getUnsafe xs index) (List.getUnsafe ys index) is
+            EQ -> compareHelp xs ys (index + 1) shared
+            order -> order
+    else
+        Num.compare (List.len xs) (List.len ys)
 
 swap : List a, Nat, Nat -> List a
 
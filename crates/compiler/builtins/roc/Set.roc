@@ -14,6 +14,7 @@ interface Set
         union,
         intersection,
         difference,
+        toSortedList,
     ]
     imports [
         List,
@@ -21,6 +22,7 @@ interface Set
         Dict.{ Dict },
         Num.{ Nat },
         Hash.{ Hash },
+        Sort.{ Sort },
     ]
 
 # We should have this line above the next has.
@@ -178,6 +180,14 @@ toList : Set k -> List k | k has Hash & Eq
 toList = \@Set dict ->
     Dict.keys dict
 
+## Retrieve the values in a `Set` as a `List`, from smallest to largest.
+## ```
+## expect Set.toSortedList (Set.fromList [3, 1, 2]) == [1, 2, 3]
+## ```
+toSortedList : Set k -> List k | k has Hash & Eq & Sort
+toSortedList = \@Set dict ->
+    List.sortAsc (Dict.keys dict)
+
 ## Create a `Set` from a `List` of values.
 ## ```
 ## values =
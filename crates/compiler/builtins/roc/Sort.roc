@@ -0,0 +1,28 @@
+interface Sort
+    exposes [Sort, compare]
+    imports []
+
+## Defines a total ordering on the values of a type.
+##
+## Numbers, [Str]s, [Bool]s and [List]s have [Sort]. The one exception to the
+## ordering being total is NaN: [F32] and [F64] are compared with [Num.compare],
+## which does not give NaN a consistent place, so sorting floats that include
+## NaN produces an unspecified order.
+##
+## Records, tuples, and tag unions of types that have [Sort] can derive it, in
+## which case values are compared lexicographically:
+##
+## 1. Records are compared field by field, in alphabetical order of the field
+## names.
+## 2. Tuples are compared element by element, from first to last.
+## 3. Tags are ordered by their name; two values with the same tag are
+## compared by their payloads, from first to last.
+##
+## Functions, [Dict]s and [Set]s cannot be ordered, so Roc cannot derive
+## [compare] for types that contain them.
+Sort has
+    ## Returns `LT` if the first value is smaller than the second, `GT` if it is
+    ## larger, and `EQ` if the two values are equal.
+    ##
+    ## [List.sortAsc] and [List.sortDesc] use this to sort a list.
+    compare : a, a -> [LT, EQ, GT] | a has Sort
@@ -135,6 +135,7 @@ interface Str
         withCapacity,
         withPrefix,
         graphemes,
+        compare,
     ]
     imports [
         Bool.{ Bool, Eq },
@@ -990,3 +991,15 @@ strToNumHelp = \string ->
 ## ```
 withPrefix : Str, Str -> Str
 withPrefix = \str, prefix -> Str.concat prefix str
+
+## Compares two strings by their UTF-8 bytes.
+##
+## This is how `Str` implements the [Sort](../Sort#Sort) ability. Note that
+## this is not a locale-aware ordering; for example, all uppercase ASCII
+## letters are ordered before all lowercase ones.
+## ```
+## expect Str.compare "apple" "banana" == LT
+## expect Str.compare "Zebra" "apple" == LT
+## ```
+compare : Str, Str -> [LT, EQ, GT]
+compare = \a, b -> List.compare (Str.toUtf8 a) (Str.toUtf8 b)
@@ -210,6 +210,8 @@ map_symbol_to_lowlevel_and_arity! {
     BoxExpr; BOX_BOX_FUNCTION; 1,
     UnboxExpr; BOX_UNBOX; 1,
     Unreachable; LIST_UNREACHABLE; 1,
+    PropertyRuns; ARBITRARY_PROPERTY_RUNS_LOWLEVEL; 1,
+    PropertySeed; ARBITRARY_PROPERTY_SEED_LOWLEVEL; 1,
 }
 
 /// Some builtins cannot be constructed in code gen alone, and need to be defined
@@ -162,6 +162,20 @@ fn hash<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
 }
 
 fn is_eq<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
+    call_with_both_payloads(env, at_opaque, "Bool", "isEq")
+}
+
+fn compare<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
+    call_with_both_payloads(env, at_opaque, "Sort", "compare")
+}
+
+/// Builds `\@Opaq payload1, @Opaq payload2 -> Module.member payload1 payload2`.
+fn call_with_both_payloads<'a>(
+    env: &mut Env<'a>,
+    at_opaque: &'a str,
+    module_name: &'a str,
+    member: &'a str,
+) -> ast::Expr<'a> {
     let alloc_pat = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
     let alloc_expr = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
 
@@ -184,11 +198,11 @@ fn is_eq<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
             .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Identifier(payload2))]),
     );
 
-    // Bool.isEq payload1 payload2
+    // Module.member payload1 payload2
     let call_member = alloc_expr(ast::Expr::Apply(
         alloc_expr(ast::Expr::Var {
-            module_name: "Bool",
-            ident: "isEq",
+            module_name,
+            ident: member,
         }),
         &*env.arena.alloc([
             &*alloc_expr(ast::Expr::Var {
@@ -203,7 +217,7 @@ fn is_eq<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
         roc_module::called_via::CalledVia::Space,
     ));
 
-    // \@Opaq payload1, @Opaq payload2 -> Bool.isEq payload1 payload2
+    // \@Opaq payload1, @Opaq payload2 -> Module.member payload1 payload2
     ast::Expr::Closure(
         env.arena.alloc([
             Loc::at(DERIVED_REGION, opaque1),
@@ -213,6 +227,81 @@ fn is_eq<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
     )
 }
 
+fn arbitrary<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
+    let alloc_expr = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+
+    let source = "#source";
+
+    // Arbitrary.arbitrary source
+    let call_member = ast::Expr::Apply(
+        alloc_expr(ast::Expr::Var {
+            module_name: "Arbitrary",
+            ident: "arbitrary",
+        }),
+        &*env.arena.alloc([&*alloc_expr(ast::Expr::Var {
+            module_name: "",
+            ident: source,
+        })]),
+        CalledVia::Space,
+    );
+
+    // Arbitrary.map (Arbitrary.arbitrary source) @Opaq
+    let call_map = alloc_expr(ast::Expr::Apply(
+        alloc_expr(ast::Expr::Var {
+            module_name: "Arbitrary",
+            ident: "map",
+        }),
+        env.arena.alloc([
+            &*alloc_expr(call_member),
+            alloc_expr(ast::Expr::OpaqueRef(at_opaque)),
+        ]),
+        CalledVia::Space,
+    ));
+
+    // \source -> Arbitrary.map (Arbitrary.arbitrary source) @Opaq
+    ast::Expr::Closure(
+        env.arena
+            .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Identifier(source))]),
+        call_map,
+    )
+}
+
+fn to_inspector<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
+    let alloc_pat = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+    let alloc_expr = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+
+    let payload = "#payload";
+
+    // \@Opaq payload
+    let opaque_ref = alloc_pat(ast::Pattern::OpaqueRef(at_opaque));
+    let opaque_apply_pattern = ast::Pattern::Apply(
+        opaque_ref,
+        &*env
+            .arena
+            .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Identifier(payload))]),
+    );
+
+    // Inspect.toInspector payload
+    let call_member = alloc_expr(ast::Expr::Apply(
+        alloc_expr(ast::Expr::Var {
+            module_name: "Inspect",
+            ident: "toInspector",
+        }),
+        &*env.arena.alloc([&*alloc_expr(ast::Expr::Var {
+            module_name: "",
+            ident: payload,
+        })]),
+        CalledVia::Space,
+    ));
+
+    // \@Opaq payload -> Inspect.toInspector payload
+    ast::Expr::Closure(
+        env.arena
+            .alloc([Loc::at(DERIVED_REGION, opaque_apply_pattern)]),
+        call_member,
+    )
+}
+
 pub const DERIVED_REGION: Region = Region::zero();
 
 pub(crate) fn synthesize_member_impl<'a>(
@@ -232,6 +321,15 @@ pub(crate) fn synthesize_member_impl<'a>(
         Symbol::DECODE_DECODER => (format!("#{}_decoder", opaque_name), decoder(env, at_opaque)),
         Symbol::HASH_HASH => (format!("#{}_hash", opaque_name), hash(env, at_opaque)),
         Symbol::BOOL_IS_EQ => (format!("#{}_isEq", opaque_name), is_eq(env, at_opaque)),
+        Symbol::ARBITRARY_ARBITRARY => (
+            format!("#{}_arbitrary", opaque_name),
+            arbitrary(env, at_opaque),
+        ),
+        Symbol::INSPECT_TO_INSPECTOR => (
+            format!("#{}_toInspector", opaque_name),
+            to_inspector(env, at_opaque),
+        ),
+        Symbol::SORT_COMPARE => (format!("#{}_compare", opaque_name), compare(env, at_opaque)),
         other => internal_error!("{:?} is not a derivable ability member!", other),
     };
 
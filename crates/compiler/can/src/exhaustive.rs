@@ -52,7 +52,7 @@ pub fn check(
     let exhaustive = match roc_exhaustive::check(overall_region, context, non_redundant_rows) {
         Ok(()) => true,
         Err(errors) => {
-            all_errors.extend(errors);
+            all_errors.extend(errors.into_iter().map(prefer_concrete_witnesses));
             false
         }
     };
@@ -64,6 +64,44 @@ pub fn check(
     })
 }
 
+/// Missing patterns that only exist because a nested tag union is open are rendered with a `_` in
+/// place of the unknown tag, which reads as "anything" and overlaps with the concrete patterns
+/// reported next to them. Only show those when there is nothing more specific to suggest; a
+/// missing open tag at the top level (plain `_`) is always worth mentioning.
+fn prefer_concrete_witnesses(error: Error) -> Error {
+    match error {
+        Error::Incomplete(region, context, patterns)
+            if patterns.iter().any(|p| !mentions_open_ctor(p)) =>
+        {
+            let patterns = patterns
+                .into_iter()
+                .filter(|p| is_open_ctor(p) || !mentions_open_ctor(p))
+                .collect();
+
+            Error::Incomplete(region, context, patterns)
+        }
+        error => error,
+    }
+}
+
+fn is_open_ctor(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Ctor(union, tag_id, _) => matches!(
+            &union.alternatives[tag_id.0 as usize].name,
+            CtorName::Tag(TagName(name)) if name.as_str() == NONEXHAUSIVE_CTOR
+        ),
+        _ => false,
+    }
+}
+
+fn mentions_open_ctor(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Anything | Pattern::Literal(_) => false,
+        Pattern::Ctor(_, _, args) => is_open_ctor(pattern) || args.iter().any(mentions_open_ctor),
+        Pattern::List(_, elems) => elems.iter().any(mentions_open_ctor),
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum SketchedPattern {
     Anything,
@@ -254,12 +292,72 @@ fn index_var(
     }
 }
 
+/// Record patterns only mention the fields they destructure, so two patterns on the same record
+/// may disagree about its shape. Line a record pattern up against every field of the record type,
+/// treating the fields it does not mention as `_`.
+fn complete_record_pattern(
+    subs: &Subs,
+    var: Variable,
+    union: Union,
+    patterns: Vec<SketchedPattern>,
+) -> (Union, Vec<SketchedPattern>) {
+    let mentioned = match &union.render_as {
+        RenderAs::Record(fields) => fields,
+        _ => return (union, patterns),
+    };
+
+    let all_fields = match record_field_names(subs, var) {
+        Some(fields) if mentioned.iter().all(|field| fields.contains(field)) => fields,
+        _ => return (union, patterns),
+    };
+
+    let mut by_field: VecMap<Lowercase, SketchedPattern> =
+        mentioned.iter().cloned().zip(patterns).collect();
+    let patterns = all_fields
+        .iter()
+        .map(|field| match by_field.remove(field) {
+            Some((_, pattern)) => pattern,
+            None => SketchedPattern::Anything,
+        })
+        .collect();
+
+    let union = Union {
+        alternatives: vec![Ctor {
+            name: CtorName::Tag(TagName("#Record".into())),
+            tag_id: TagId(0),
+            arity: all_fields.len(),
+        }],
+        render_as: RenderAs::Record(all_fields),
+    };
+
+    (union, patterns)
+}
+
+fn record_field_names(subs: &Subs, mut var: Variable) -> Option<Vec<Lowercase>> {
+    loop {
+        match subs.get_content_without_compacting(var) {
+            Content::Structure(FlatType::Record(fields, ext)) => {
+                let names = fields
+                    .sorted_iterator(subs, *ext)
+                    .map(|(name, _)| name)
+                    .collect();
+
+                return Some(names);
+            }
+            Content::Alias(_, _, inner, AliasKind::Structural) => var = *inner,
+            Content::RecursionVar { structure, .. } => var = *structure,
+            _ => return None,
+        }
+    }
+}
+
 impl SketchedPattern {
     fn reify(self, subs: &Subs, real_var: Variable) -> Result<Pattern, TypeError> {
         match self {
             Self::Anything => Ok(Pattern::Anything),
             Self::Literal(lit) => Ok(Pattern::Literal(lit)),
             Self::KnownCtor(union, tag_id, patterns) => {
+                let (union, patterns) = complete_record_pattern(subs, real_var, union, patterns);
                 let index_ctor = IndexCtor::of_union(&union, tag_id);
                 let arg_vars = index_var(subs, real_var, index_ctor, &union.render_as)?;
 
@@ -568,6 +666,7 @@ fn to_nonredundant_rows(
         overall_region,
     } = rows;
     let mut checked_rows = Vec::with_capacity(rows.len());
+    let mut checked_row_numbers = Vec::with_capacity(rows.len());
 
     let mut redundancies = vec![];
     let mut errors = vec![];
@@ -596,10 +695,16 @@ fn to_nonredundant_rows(
         } else if !(matches!(guard, Guard::HasGuard)
             || is_useful(checked_rows.clone(), next_row.clone()))
         {
+            let covered_by = covering_rows(&checked_rows, &next_row)
+                .into_iter()
+                .map(|i| HumanIndex::zero_based(checked_row_numbers[i]))
+                .collect();
+
             Some(Error::Redundant {
                 overall_region,
                 branch_region: region,
                 index: HumanIndex::zero_based(row_number),
+                covered_by,
             })
         } else {
             None
@@ -608,6 +713,7 @@ fn to_nonredundant_rows(
         match redundant_err {
             None => {
                 checked_rows.push(next_row);
+                checked_row_numbers.push(row_number);
             }
             Some(err) => {
                 redundancies.push(redundant_mark);
@@ -623,6 +729,27 @@ fn to_nonredundant_rows(
     })
 }
 
+/// Given a row that is not useful with respect to `checked_rows`, find a small set of those rows
+/// that together already cover it, preferring earlier rows. Returns indices into `checked_rows`.
+fn covering_rows(checked_rows: &[Vec<Pattern>], redundant_row: &[Pattern]) -> Vec<usize> {
+    let mut covering: Vec<usize> = (0..checked_rows.len()).collect();
+
+    // Drop rows from the back for as long as the remaining ones still cover the redundant row.
+    for candidate in (0..checked_rows.len()).rev() {
+        let remaining: Vec<Vec<Pattern>> = covering
+            .iter()
+            .filter(|&&i| i != candidate)
+            .map(|&i| checked_rows[i].clone())
+            .collect();
+
+        if !is_useful(remaining, redundant_row.to_vec()) {
+            covering.retain(|&i| i != candidate);
+        }
+    }
+
+    covering
+}
+
 fn is_inhabited_row(patterns: &[Pattern]) -> bool {
     patterns.iter().any(is_inhabited_pattern)
 }
@@ -1255,6 +1255,10 @@ pub struct TypeState {
 }
 
 impl TypeState {
+    /// Each section is serialized as if it started at offset 0, so every section has to start at
+    /// an offset that is aligned for anything it contains.
+    const SECTION_ALIGNMENT: usize = std::mem::align_of::<u64>();
+
     pub fn serialize(&self, writer: &mut impl std::io::Write) -> std::io::Result<usize> {
         let Self {
             subs,
@@ -1264,18 +1268,36 @@ impl TypeState {
         } = self;
 
         let written_subs = subs.serialize(exposed_vars_by_symbol, writer)?;
+        let padding_subs = Self::write_section_padding(written_subs, writer)?;
         let written_ab = abilities.serialize(writer)?;
+        let padding_ab = Self::write_section_padding(written_ab, writer)?;
         let written_solved_impls =
             crate::abilities::serialize_solved_implementations(solved_implementations, writer)?;
 
-        Ok(written_subs + written_ab + written_solved_impls)
+        Ok(written_subs + padding_subs + written_ab + padding_ab + written_solved_impls)
+    }
+
+    fn write_section_padding(
+        written: usize,
+        writer: &mut impl std::io::Write,
+    ) -> std::io::Result<usize> {
+        let padding = Self::section_padding(written);
+        writer.write_all(&[0; Self::SECTION_ALIGNMENT][..padding])?;
+
+        Ok(padding)
+    }
+
+    fn section_padding(length: usize) -> usize {
+        roc_serialize::bytes::next_multiple_of(length, Self::SECTION_ALIGNMENT) - length
     }
 
     pub fn deserialize(bytes: &[u8]) -> (Self, usize) {
         let ((subs, exposed_vars_by_symbol), len_subs) = Subs::deserialize(bytes);
+        let len_subs = len_subs + Self::section_padding(len_subs);
         let bytes = &bytes[len_subs..];
 
         let (abilities, len_abilities) = AbilitiesStore::deserialize(bytes);
+        let len_abilities = len_abilities + Self::section_padding(len_abilities);
         let bytes = &bytes[len_abilities..];
 
         let (solved_implementations, len_solved_impls) =
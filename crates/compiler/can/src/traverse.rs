@@ -1,7 +1,7 @@
 //! Traversals over the can ast.
 
 use roc_module::{ident::Lowercase, symbol::Symbol};
-use roc_region::all::{Loc, Region};
+use roc_region::all::{Loc, Position, Region};
 use roc_types::{subs::Variable, types::MemberImpl};
 
 use crate::{
@@ -664,6 +664,160 @@ pub fn find_type_at(region: Region, decls: &Declarations) -> Option<Variable> {
     visitor.typ
 }
 
+struct ClosestTypeVisitor {
+    region: Region,
+    found: Option<(Region, Variable)>,
+}
+
+impl Visitor for ClosestTypeVisitor {
+    fn should_visit(&mut self, region: Region) -> bool {
+        region.contains(&self.region)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        if self.should_visit(region) {
+            // Nodes are visited outside-in, so the last match is the innermost one.
+            self.found = Some((region, var));
+            walk_expr(self, expr, var);
+        }
+    }
+
+    fn visit_pattern(&mut self, pat: &Pattern, region: Region, opt_var: Option<Variable>) {
+        if self.should_visit(region) {
+            if let Some(var) = opt_var {
+                self.found = Some((region, var));
+            }
+            walk_pattern(self, pat);
+        }
+    }
+}
+
+/// Finds the innermost expression or pattern whose region contains `pos`, along with its type.
+/// Unlike [find_type_at], the position need not line up exactly with the start of a node, which
+/// makes this suitable for editor queries like "what is the type under the cursor?".
+pub fn find_closest_type_at(pos: Position, decls: &Declarations) -> Option<(Region, Variable)> {
+    let mut visitor = ClosestTypeVisitor {
+        region: Region::from_pos(pos),
+        found: None,
+    };
+    visitor.visit_decls(decls);
+    visitor.found
+}
+
+/// Finds the innermost symbol, either referenced or introduced, whose region contains `pos`.
+pub fn find_closest_symbol_at(pos: Position, decls: &Declarations) -> Option<Loc<Symbol>> {
+    let mut visitor = Finder {
+        region: Region::from_pos(pos),
+        found: None,
+    };
+    visitor.visit_decls(decls);
+    return visitor.found;
+
+    struct Finder {
+        region: Region,
+        found: Option<Loc<Symbol>>,
+    }
+
+    impl Visitor for Finder {
+        fn should_visit(&mut self, region: Region) -> bool {
+            region.contains(&self.region)
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            if !self.should_visit(region) {
+                return;
+            }
+
+            match pattern {
+                Pattern::Identifier(symbol)
+                | Pattern::Shadowed(_, _, symbol)
+                | Pattern::AbilityMemberSpecialization { ident: symbol, .. } => {
+                    self.found = Some(Loc::at(region, *symbol));
+                }
+                _ => walk_pattern(self, pattern),
+            }
+        }
+
+        fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+            if !self.should_visit(region) {
+                return;
+            }
+
+            match &destruct.typ {
+                DestructType::Guard(_, subpattern) => {
+                    self.visit_pattern(&subpattern.value, subpattern.region, None)
+                }
+                _ => self.found = Some(Loc::at(region, destruct.symbol)),
+            }
+        }
+
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            if !self.should_visit(region) {
+                return;
+            }
+
+            match expr {
+                Expr::Var(symbol, _) | Expr::AbilityMember(symbol, _, _) => {
+                    self.found = Some(Loc::at(region, *symbol));
+                }
+                _ => walk_expr(self, expr, var),
+            }
+        }
+    }
+}
+
+/// Finds the region of the pattern that introduces `symbol` into scope, if it is bound anywhere
+/// in `decls`. This covers top-level definitions as well as local defs, closure arguments and
+/// `when` branch patterns.
+pub fn find_symbol_introduction(symbol: Symbol, decls: &Declarations) -> Option<Region> {
+    let mut visitor = Finder {
+        symbol,
+        found: None,
+    };
+    visitor.visit_decls(decls);
+    return visitor.found;
+
+    struct Finder {
+        symbol: Symbol,
+        found: Option<Region>,
+    }
+
+    impl Visitor for Finder {
+        fn should_visit(&mut self, _region: Region) -> bool {
+            self.found.is_none()
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            if !self.should_visit(region) {
+                return;
+            }
+
+            match pattern {
+                Pattern::Identifier(symbol)
+                | Pattern::Shadowed(_, _, symbol)
+                | Pattern::AbilityMemberSpecialization { ident: symbol, .. }
+                    if *symbol == self.symbol =>
+                {
+                    self.found = Some(region);
+                }
+                _ => walk_pattern(self, pattern),
+            }
+        }
+
+        fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+            if !self.should_visit(region) {
+                return;
+            }
+
+            match &destruct.typ {
+                DestructType::Guard(..) => walk_record_destruct(self, destruct),
+                _ if destruct.symbol == self.symbol => self.found = Some(region),
+                _ => walk_record_destruct(self, destruct),
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum FoundSymbol {
     /// Specialization(T, foo1) is the specialization of foo for T.
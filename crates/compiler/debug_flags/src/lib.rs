@@ -163,4 +163,7 @@ flags! {
 
     /// Don't build and use the subs cache (speeds up compilation of load and previous crates)
     ROC_SKIP_SUBS_CACHE
+
+    /// Don't read or write the on-disk cache of typechecked modules
+    ROC_SKIP_MODULE_CACHE
 }
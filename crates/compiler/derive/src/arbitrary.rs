@@ -0,0 +1,578 @@
+//! Derivers for the `Arbitrary` ability.
+
+use std::iter::once;
+
+use roc_can::{
+    def::Def,
+    expr::{
+        AnnotatedMark, ClosureData, Expr, Field, IntValue, Recursive, WhenBranch, WhenBranchPattern,
+    },
+    num::{IntBound, IntLitWidth},
+    pattern::Pattern,
+};
+use roc_collections::SendMap;
+use roc_derive_key::arbitrary::FlatArbitraryKey;
+use roc_module::{
+    called_via::CalledVia,
+    ident::{Lowercase, TagName},
+    symbol::Symbol,
+};
+use roc_region::all::{Loc, Region};
+use roc_types::{
+    num::int_lit_width_to_variable,
+    subs::{
+        Content, ExhaustiveMark, FlatType, LambdaSet, OptVariable, RecordFields, RedundantMark,
+        SubsSlice, TagExt, TupleElems, UnionLambdas, UnionTags, Variable, VariableSubsSlice,
+    },
+    types::RecordField,
+};
+
+use crate::{synth_var, util::Env, DerivedBody};
+
+pub(crate) fn derive_arbitrary(
+    env: &mut Env<'_>,
+    key: FlatArbitraryKey,
+    def_symbol: Symbol,
+) -> DerivedBody {
+    let (body_type, body) = match key {
+        FlatArbitraryKey::Record(fields) => arbitrary_record(env, def_symbol, fields),
+        FlatArbitraryKey::Tuple(arity) => arbitrary_tuple(env, def_symbol, arity),
+        FlatArbitraryKey::TagUnion(tags) => arbitrary_tag_union(env, def_symbol, tags),
+    };
+
+    let specialization_lambda_sets =
+        env.get_specialization_lambda_sets(body_type, Symbol::ARBITRARY_ARBITRARY);
+
+    DerivedBody {
+        body,
+        body_type,
+        specialization_lambda_sets,
+    }
+}
+
+fn arbitrary_record(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    fields: Vec<Lowercase>,
+) -> (Variable, Expr) {
+    // Suppose rcd = { f1, ..., fn }.
+    // Build a generalized type t_rcd = { f1: t1, ..., fn: tn }, with fresh t1, ..., tn,
+    // so that we can re-use the derived impl for many records of the same fields.
+    let field_vars: Vec<_> = fields
+        .iter()
+        .map(|_| env.subs.fresh_unnamed_flex_var())
+        .collect();
+    let record_var = {
+        let flex_fields = (fields.iter().cloned())
+            .zip(field_vars.iter().map(|var| RecordField::Required(*var)))
+            .collect::<Vec<(Lowercase, _)>>();
+        let fields = RecordFields::insert_into_subs(env.subs, flex_fields);
+
+        synth_var(
+            env.subs,
+            Content::Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+        )
+    };
+
+    // Now, a generator for this record is
+    //
+    // arbitrary_rcd : Source -> Generated { f1: t1, ..., fn: tn } | t1 has Arbitrary, ...
+    // arbitrary_rcd = \source ->
+    //   generated = Arbitrary.arbitrary source
+    //   ...
+    //   generatedN = Arbitrary.arbitrary generatedN-1.source
+    //   { value: { f1: generated.value, ..., fn: generatedN.value }, source: generatedN.source }
+    let source_sym = env.new_symbol("source");
+    let source_var = env.subs.fresh_unnamed_flex_var();
+
+    let mut draws = Draws::new(source_var, Expr::Var(source_sym, source_var));
+    let mut fields_map = SendMap::default();
+
+    for (field_name, field_var) in fields.into_iter().zip(field_vars) {
+        let value = draws.arbitrary(env, field_var);
+
+        let field = Field {
+            var: field_var,
+            region: Region::zero(),
+            loc_expr: Box::new(Loc::at_zero(value)),
+        };
+
+        fields_map.insert(field_name, field);
+    }
+
+    let value = if fields_map.is_empty() {
+        Expr::EmptyRecord
+    } else {
+        Expr::Record {
+            record_var,
+            fields: fields_map,
+        }
+    };
+
+    let (body_var, body) = draws.finish(env, (record_var, value));
+
+    build_outer_derived_closure(env, fn_name, (source_var, source_sym), (body_var, body))
+}
+
+fn arbitrary_tuple(env: &mut Env<'_>, fn_name: Symbol, arity: u32) -> (Variable, Expr) {
+    // Suppose tup = (v1, ..., vn).
+    // Build a generalized type t_tup = (t1, ..., tn), with fresh t1, ..., tn,
+    // so that we can re-use the derived impl for many tuples of the same arity.
+    let elem_vars: Vec<_> = (0..arity)
+        .map(|_| env.subs.fresh_unnamed_flex_var())
+        .collect();
+    let tuple_var = {
+        let elems = TupleElems::insert_into_subs(env.subs, elem_vars.iter().copied().enumerate());
+
+        synth_var(
+            env.subs,
+            Content::Structure(FlatType::Tuple(elems, Variable::EMPTY_TUPLE)),
+        )
+    };
+
+    // Now, a generator for this tuple is
+    //
+    // arbitrary_tup : Source -> Generated (t1, ..., tn) | t1 has Arbitrary, ...
+    // arbitrary_tup = \source ->
+    //   generated = Arbitrary.arbitrary source
+    //   ...
+    //   generatedN = Arbitrary.arbitrary generatedN-1.source
+    //   { value: (generated.value, ..., generatedN.value), source: generatedN.source }
+    let source_sym = env.new_symbol("source");
+    let source_var = env.subs.fresh_unnamed_flex_var();
+
+    let mut draws = Draws::new(source_var, Expr::Var(source_sym, source_var));
+
+    let elems = elem_vars
+        .into_iter()
+        .map(|elem_var| {
+            let value = draws.arbitrary(env, elem_var);
+
+            (elem_var, Box::new(Loc::at_zero(value)))
+        })
+        .collect();
+
+    let (body_var, body) = draws.finish(env, (tuple_var, Expr::Tuple { tuple_var, elems }));
+
+    build_outer_derived_closure(env, fn_name, (source_var, source_sym), (body_var, body))
+}
+
+fn arbitrary_tag_union(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    tags: Vec<(TagName, u16)>,
+) -> (Variable, Expr) {
+    // Suppose tags = [ A p11 .. p1n, ..., Q pq1 .. pqm ]
+    // Build a generalized type t_tags = [ A t11 .. t1n, ..., Q tq1 .. tqm ],
+    // with fresh t1, ..., tqm, so that we can re-use the derived impl for many
+    // unions of the same tags and payloads.
+    //
+    // The tags are kept in the order of the key, fewest payloads first.
+    let tags: Vec<(TagName, Vec<Variable>)> = tags
+        .into_iter()
+        .map(|(label, arity)| {
+            let payload_vars = (0..arity)
+                .map(|_| env.subs.fresh_unnamed_flex_var())
+                .collect();
+
+            (label, payload_vars)
+        })
+        .collect();
+    let mut flex_tag_labels: Vec<_> = (tags.iter())
+        .map(|(label, payload_vars)| {
+            let variables_slice =
+                VariableSubsSlice::insert_into_subs(env.subs, payload_vars.iter().copied());
+
+            (label.clone(), variables_slice)
+        })
+        .collect();
+    flex_tag_labels.sort_by(|(t1, _), (t2, _)| t1.cmp(t2));
+
+    // [ A t11 .. t1n, ..., Q tq1 .. tqm ], minus the tag `except` if there is one
+    let mut union_without = |except: Option<&TagName>| {
+        let union_tags = UnionTags::insert_slices_into_subs(
+            env.subs,
+            (flex_tag_labels.iter())
+                .filter(|(label, _)| Some(label) != except)
+                .cloned(),
+        );
+
+        synth_var(
+            env.subs,
+            Content::Structure(FlatType::TagUnion(
+                union_tags,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        )
+    };
+
+    let union_var = union_without(None);
+    // The extension of each tag in the union is all the other tags.
+    let tag_ext_vars: Vec<_> = (tags.iter())
+        .map(|(label, _)| union_without(Some(label)))
+        .collect();
+
+    // Now, a generator for this tag union is
+    //
+    // arbitrary_union : Source -> Generated [ A t11 .. t1n, ..., Q tq1 .. tqm ] | t11 has Arbitrary, ...
+    // arbitrary_union = \source ->
+    //   generated = Arbitrary.draw source (q - 1)
+    //   when generated.value is
+    //     0 ->
+    //       generated2 = Arbitrary.arbitrary generated.source
+    //       ...
+    //       { value: A generated2.value ..., source: generatedN.source }
+    //     ...
+    //     _ -> ... Q ...
+    //
+    // A union with a single tag does not draw which tag to generate.
+    let source_sym = env.new_symbol("source");
+    let source_var = env.subs.fresh_unnamed_flex_var();
+    let source = Expr::Var(source_sym, source_var);
+
+    let (body_var, body) = if tags.len() == 1 {
+        let (tag_name, payload_vars) = tags.into_iter().next().unwrap();
+        let tag = (tag_name, tag_ext_vars[0], payload_vars);
+
+        generate_tag(env, (source_var, source), union_var, tag)
+    } else {
+        let mut draws = Draws::new(source_var, source);
+        let chosen = draws.draw(env, tags.len() as u64 - 1);
+        let chosen_source = draws.source.clone();
+
+        let last = tags.len() - 1;
+        let whole_generated_var = env.subs.fresh_unnamed_flex_var();
+        let branches = (tags.into_iter().zip(tag_ext_vars).enumerate())
+            .map(|(index, ((tag_name, payload_vars), tag_ext_var))| {
+                let pattern = if index == last {
+                    Pattern::Underscore
+                } else {
+                    Pattern::IntLiteral(
+                        Variable::U64,
+                        Variable::UNSIGNED64,
+                        index.to_string().into_boxed_str(),
+                        IntValue::I128((index as i128).to_ne_bytes()),
+                        IntBound::Exact(IntLitWidth::U64),
+                    )
+                };
+
+                let (generated_var, generated) = generate_tag(
+                    env,
+                    (source_var, chosen_source.clone()),
+                    union_var,
+                    (tag_name, tag_ext_var, payload_vars),
+                );
+
+                env.unify(whole_generated_var, generated_var);
+
+                WhenBranch {
+                    patterns: vec![WhenBranchPattern {
+                        pattern: Loc::at_zero(pattern),
+                        degenerate: false,
+                    }],
+                    value: Loc::at_zero(generated),
+                    guard: None,
+                    redundant: RedundantMark::known_non_redundant(),
+                }
+            })
+            .collect();
+
+        // when generated.value is
+        //   ...
+        let when_expr = Expr::When {
+            loc_cond: Box::new(Loc::at_zero(chosen)),
+            cond_var: Variable::U64,
+            expr_var: whole_generated_var,
+            region: Region::zero(),
+            branches,
+            branches_cond_var: Variable::U64,
+            exhaustive: ExhaustiveMark::known_exhaustive(),
+        };
+
+        (whole_generated_var, draws.wrap(when_expr))
+    };
+
+    build_outer_derived_closure(env, fn_name, (source_var, source_sym), (body_var, body))
+}
+
+/// Generates `Tag p1 .. pn` by generating each of its payloads in turn.
+fn generate_tag(
+    env: &mut Env<'_>,
+    source: (Variable, Expr),
+    union_var: Variable,
+    tag: (TagName, Variable, Vec<Variable>),
+) -> (Variable, Expr) {
+    let (source_var, source_expr) = source;
+    let (tag_name, ext_var, payload_vars) = tag;
+    let mut draws = Draws::new(source_var, source_expr);
+
+    let arguments = payload_vars
+        .into_iter()
+        .map(|payload_var| {
+            let value = draws.arbitrary(env, payload_var);
+
+            (payload_var, Loc::at_zero(value))
+        })
+        .collect();
+
+    let tag = Expr::Tag {
+        tag_union_var: union_var,
+        ext_var,
+        name: tag_name,
+        arguments,
+    };
+
+    draws.finish(env, (union_var, tag))
+}
+
+/// A sequence of draws from a source, each of which is bound to a name so that both its value
+/// and the source left after it can be used.
+///
+/// Every source in the sequence has the same (`Source`) type, so they share one variable.
+struct Draws {
+    source_var: Variable,
+    /// The source to draw from next.
+    source: Expr,
+    defs: Vec<Def>,
+}
+
+impl Draws {
+    fn new(source_var: Variable, source: Expr) -> Self {
+        Self {
+            source_var,
+            source,
+            defs: vec![],
+        }
+    }
+
+    /// Binds `Arbitrary.arbitrary source`, returning the generated value.
+    fn arbitrary(&mut self, env: &mut Env<'_>, value_var: Variable) -> Expr {
+        let call_vars = self.call_vars(env, Symbol::ARBITRARY_ARBITRARY, vec![], value_var);
+
+        // Arbitrary.arbitrary : Source -[clos]-> Generated t | t has Arbitrary
+        let head = Expr::AbilityMember(Symbol::ARBITRARY_ARBITRARY, None, call_vars.0);
+
+        self.bind(env, call_vars, head, vec![], value_var)
+    }
+
+    /// Binds `Arbitrary.draw source max`, returning the drawn choice.
+    fn draw(&mut self, env: &mut Env<'_>, max: u64) -> Expr {
+        let max_var = int_lit_width_to_variable(IntLitWidth::U64);
+        let max_expr = Expr::Int(
+            max_var,
+            Variable::UNSIGNED64,
+            max.to_string().into_boxed_str(),
+            IntValue::I128((max as i128).to_ne_bytes()),
+            IntBound::Exact(IntLitWidth::U64),
+        );
+        let call_vars = self.call_vars(env, Symbol::ARBITRARY_DRAW, vec![max_var], Variable::U64);
+
+        // Arbitrary.draw : Source, U64 -[clos]-> Generated U64
+        let head = Expr::Var(Symbol::ARBITRARY_DRAW, call_vars.0);
+
+        self.bind(
+            env,
+            call_vars,
+            head,
+            vec![(max_var, max_expr)],
+            Variable::U64,
+        )
+    }
+
+    /// Builds the type `Source, ..rest_arg_vars -[clos]-> Generated t` of a call to `member`, and
+    /// unifies it with the member's own type. Returns the function, closure and return variables.
+    fn call_vars(
+        &self,
+        env: &mut Env<'_>,
+        member: Symbol,
+        rest_arg_vars: Vec<Variable>,
+        value_var: Variable,
+    ) -> (Variable, Variable, Variable) {
+        let exposed_fn_var = env.import_builtin_symbol_var(member);
+
+        let generated_var = generated_var(env, value_var, self.source_var);
+        let arguments_slice = VariableSubsSlice::insert_into_subs(
+            env.subs,
+            once(self.source_var).chain(rest_arg_vars),
+        );
+        let clos_var = env.subs.fresh_unnamed_flex_var();
+        let fn_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Func(arguments_slice, clos_var, generated_var)),
+        );
+
+        env.unify(exposed_fn_var, fn_var);
+
+        (fn_var, clos_var, generated_var)
+    }
+
+    /// Binds `generated = head source ..rest_args`, moves on to `generated.source`, and returns
+    /// `generated.value`.
+    fn bind(
+        &mut self,
+        env: &mut Env<'_>,
+        call_vars: (Variable, Variable, Variable),
+        head: Expr,
+        rest_args: Vec<(Variable, Expr)>,
+        value_var: Variable,
+    ) -> Expr {
+        let (fn_var, clos_var, generated_var) = call_vars;
+
+        let source = std::mem::replace(&mut self.source, Expr::EmptyRecord);
+        let arguments = once((self.source_var, source))
+            .chain(rest_args)
+            .map(|(var, expr)| (var, Loc::at_zero(expr)))
+            .collect();
+        let call = Expr::Call(
+            Box::new((fn_var, Loc::at_zero(head), clos_var, generated_var)),
+            arguments,
+            CalledVia::Space,
+        );
+
+        let generated_sym = env.new_symbol("generated");
+        self.defs.push(Def {
+            loc_pattern: Loc::at_zero(Pattern::Identifier(generated_sym)),
+            loc_expr: Loc::at_zero(call),
+            expr_var: generated_var,
+            pattern_vars: once((generated_sym, generated_var)).collect(),
+            annotation: None,
+        });
+
+        self.source = access(
+            env,
+            (generated_sym, generated_var),
+            "source",
+            self.source_var,
+        );
+
+        access(env, (generated_sym, generated_var), "value", value_var)
+    }
+
+    /// Finishes the sequence with `{ value, source }`, where `source` is the one left after the
+    /// last draw.
+    fn finish(mut self, env: &mut Env<'_>, value: (Variable, Expr)) -> (Variable, Expr) {
+        let (value_var, value_expr) = value;
+        let generated_var = generated_var(env, value_var, self.source_var);
+        let source = std::mem::replace(&mut self.source, Expr::EmptyRecord);
+
+        let mut fields = SendMap::default();
+        fields.insert(
+            "value".into(),
+            Field {
+                var: value_var,
+                region: Region::zero(),
+                loc_expr: Box::new(Loc::at_zero(value_expr)),
+            },
+        );
+        fields.insert(
+            "source".into(),
+            Field {
+                var: self.source_var,
+                region: Region::zero(),
+                loc_expr: Box::new(Loc::at_zero(source)),
+            },
+        );
+
+        let generated = Expr::Record {
+            record_var: generated_var,
+            fields,
+        };
+
+        (generated_var, self.wrap(generated))
+    }
+
+    /// Wraps `body` in the bindings of all draws.
+    fn wrap(self, body: Expr) -> Expr {
+        self.defs.into_iter().rev().fold(body, |body, def| {
+            Expr::LetNonRec(Box::new(def), Box::new(Loc::at_zero(body)))
+        })
+    }
+}
+
+/// `{ value : t, source : Source }`, which is what `Generated t` unfolds to.
+fn generated_var(env: &mut Env<'_>, value_var: Variable, source_var: Variable) -> Variable {
+    let fields = RecordFields::insert_into_subs(
+        env.subs,
+        [
+            ("source".into(), RecordField::Required(source_var)),
+            ("value".into(), RecordField::Required(value_var)),
+        ],
+    );
+
+    synth_var(
+        env.subs,
+        Content::Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+    )
+}
+
+/// `generated.field`
+fn access(
+    env: &mut Env<'_>,
+    generated: (Symbol, Variable),
+    field: &str,
+    field_var: Variable,
+) -> Expr {
+    let (generated_sym, generated_var) = generated;
+
+    Expr::RecordAccess {
+        record_var: generated_var,
+        ext_var: env.subs.fresh_unnamed_flex_var(),
+        field_var,
+        loc_expr: Box::new(Loc::at_zero(Expr::Var(generated_sym, generated_var))),
+        field: field.into(),
+    }
+}
+
+fn build_outer_derived_closure(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    source: (Variable, Symbol),
+    body: (Variable, Expr),
+) -> (Variable, Expr) {
+    let (source_var, source_sym) = source;
+    let (body_var, body_expr) = body;
+
+    let (fn_var, fn_clos_var) = {
+        // Create fn_var for ambient capture; we fix it up below.
+        let fn_var = synth_var(env.subs, Content::Error);
+
+        // -[fn_name]->
+        let fn_captures = vec![];
+        let fn_name_labels = UnionLambdas::insert_into_subs(env.subs, once((fn_name, fn_captures)));
+        let fn_clos_var = synth_var(
+            env.subs,
+            Content::LambdaSet(LambdaSet {
+                solved: fn_name_labels,
+                recursion_var: OptVariable::NONE,
+                unspecialized: SubsSlice::default(),
+                ambient_function: fn_var,
+            }),
+        );
+
+        // source -[fn_name]-> (generated = body_var)
+        let args_slice = SubsSlice::insert_into_subs(env.subs, [source_var]);
+        env.subs.set_content(
+            fn_var,
+            Content::Structure(FlatType::Func(args_slice, fn_clos_var, body_var)),
+        );
+
+        (fn_var, fn_clos_var)
+    };
+
+    let clos_expr = Expr::Closure(ClosureData {
+        function_type: fn_var,
+        closure_type: fn_clos_var,
+        return_type: body_var,
+        name: fn_name,
+        captured_symbols: vec![],
+        recursive: Recursive::NotRecursive,
+        arguments: vec![(
+            source_var,
+            AnnotatedMark::known_exhaustive(),
+            Loc::at_zero(Pattern::Identifier(source_sym)),
+        )],
+        loc_body: Box::new(Loc::at_zero(body_expr)),
+    });
+
+    (fn_var, clos_expr)
+}
@@ -16,6 +16,7 @@ use crate::{synth_var, DerivedBody};
 
 mod list;
 mod record;
+mod tag_union;
 mod tuple;
 
 pub(crate) fn derive_decoder(
@@ -25,8 +26,11 @@ pub(crate) fn derive_decoder(
 ) -> DerivedBody {
     let (body, body_type) = match key {
         FlatDecodableKey::List() => list::decoder(env, def_symbol),
+        FlatDecodableKey::Set() => list::collection_decoder(env, Symbol::DECODE_SET, 1),
+        FlatDecodableKey::Dict() => list::collection_decoder(env, Symbol::DECODE_DICT, 2),
         FlatDecodableKey::Record(fields) => record::decoder(env, def_symbol, fields),
         FlatDecodableKey::Tuple(arity) => tuple::decoder(env, def_symbol, arity),
+        FlatDecodableKey::TagUnion(tags) => tag_union::decoder(env, def_symbol, tags),
     };
 
     let specialization_lambda_sets =
@@ -19,74 +19,97 @@ pub(crate) fn decoder(env: &mut Env<'_>, _def_symbol: Symbol) -> (Expr, Variable
     //   def_symbol = Decode.custom \bytes, fmt -> Decode.decodeWith bytes (Decode.list Decode.decoder) fmt
     //
     // NB: reduction to `Decode.list Decode.decoder` is not possible to the HRR.
+    collection_decoder(env, Symbol::DECODE_LIST, 1)
+}
 
+/// Builds a decoder for a builtin collection from the `Decode` function that builds it out of the
+/// decoders of the collection's type parameters; that is, `Decode.list`, `Decode.set` or
+/// `Decode.dict`. For example, for dicts this is
+///
+/// ```roc
+/// Decode.custom \bytes, fmt -> Decode.decodeWith bytes (Decode.dict Decode.decoder Decode.decoder) fmt
+/// ```
+pub(super) fn collection_decoder(
+    env: &mut Env<'_>,
+    decode_collection: Symbol,
+    num_type_params: usize,
+) -> (Expr, Variable) {
     use Expr::*;
 
-    // Decode.list Decode.decoder : Decoder (List elem) fmt
-    let (decode_list_call, this_decode_list_ret_var) = {
-        // List elem
-        let elem_var = env.subs.fresh_unnamed_flex_var();
-
-        // Decode.decoder : Decoder elem fmt | elem has Decoding, fmt has EncoderFormatting
-        let (elem_decoder, elem_decoder_var) = {
-            // build `Decode.decoder : Decoder elem fmt` type
-            // Decoder val fmt | val has Decoding, fmt has EncoderFormatting
-            let elem_decoder_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODER);
-
-            // set val ~ elem
-            let val_var = match env.subs.get_content_without_compacting(elem_decoder_var) {
-                Content::Alias(Symbol::DECODE_DECODER_OPAQUE, vars, _, AliasKind::Opaque)
-                    if vars.type_variables_len == 2 =>
-                {
-                    env.subs.get_subs_slice(vars.type_variables())[0]
-                }
-                _ => internal_error!("Decode.decode not an opaque type"),
-            };
-
-            env.unify(val_var, elem_var);
-
-            (
-                AbilityMember(Symbol::DECODE_DECODER, None, elem_decoder_var),
-                elem_decoder_var,
-            )
-        };
-
-        // Build `Decode.list Decode.decoder` type
+    // e.g. Decode.list Decode.decoder : Decoder (List elem) fmt
+    let (decode_collection_call, this_decode_collection_ret_var) = {
+        // Decode.decoder : Decoder elem fmt | elem has Decoding, fmt has EncoderFormatting, for each
+        // type parameter `elem` of the collection
+        let elem_decoders = (0..num_type_params)
+            .map(|_| {
+                let elem_var = env.subs.fresh_unnamed_flex_var();
+
+                // build `Decode.decoder : Decoder elem fmt` type
+                // Decoder val fmt | val has Decoding, fmt has EncoderFormatting
+                let elem_decoder_var = env.import_builtin_symbol_var(Symbol::DECODE_DECODER);
+
+                // set val ~ elem
+                let val_var = match env.subs.get_content_without_compacting(elem_decoder_var) {
+                    Content::Alias(Symbol::DECODE_DECODER_OPAQUE, vars, _, AliasKind::Opaque)
+                        if vars.type_variables_len == 2 =>
+                    {
+                        env.subs.get_subs_slice(vars.type_variables())[0]
+                    }
+                    _ => internal_error!("Decode.decode not an opaque type"),
+                };
+
+                env.unify(val_var, elem_var);
+
+                (
+                    elem_decoder_var,
+                    Loc::at_zero(AbilityMember(
+                        Symbol::DECODE_DECODER,
+                        None,
+                        elem_decoder_var,
+                    )),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Build `Decode.list Decode.decoder` type, e.g.
         // Decoder val fmt -[uls]-> Decoder (List val) fmt | fmt has DecoderFormatting
-        let decode_list_fn_var = env.import_builtin_symbol_var(Symbol::DECODE_LIST);
+        let decode_collection_fn_var = env.import_builtin_symbol_var(decode_collection);
 
         // Decoder elem fmt -a-> b
-        let elem_decoder_var_slice = SubsSlice::insert_into_subs(env.subs, [elem_decoder_var]);
-        let this_decode_list_clos_var = env.subs.fresh_unnamed_flex_var();
-        let this_decode_list_ret_var = env.subs.fresh_unnamed_flex_var();
-        let this_decode_list_fn_var = synth_var(
+        let elem_decoder_var_slice =
+            SubsSlice::insert_into_subs(env.subs, elem_decoders.iter().map(|(var, _)| *var));
+        let this_decode_collection_clos_var = env.subs.fresh_unnamed_flex_var();
+        let this_decode_collection_ret_var = env.subs.fresh_unnamed_flex_var();
+        let this_decode_collection_fn_var = synth_var(
             env.subs,
             Content::Structure(FlatType::Func(
                 elem_decoder_var_slice,
-                this_decode_list_clos_var,
-                this_decode_list_ret_var,
+                this_decode_collection_clos_var,
+                this_decode_collection_ret_var,
             )),
         );
 
         //   Decoder val  fmt -[uls]-> Decoder (List val) fmt | fmt has DecoderFormatting
         // ~ Decoder elem fmt -a    -> b
-        env.unify(decode_list_fn_var, this_decode_list_fn_var);
-
-        let decode_list_member = AbilityMember(Symbol::DECODE_LIST, None, this_decode_list_fn_var);
-        let decode_list_fn = Box::new((
-            decode_list_fn_var,
-            Loc::at_zero(decode_list_member),
-            this_decode_list_clos_var,
-            this_decode_list_ret_var,
+        env.unify(decode_collection_fn_var, this_decode_collection_fn_var);
+
+        // `Decode.list` is a member of `DecoderFormatting`, whereas `Decode.set` and `Decode.dict`
+        // are plain functions built on top of it.
+        let decode_collection_fn = if decode_collection == Symbol::DECODE_LIST {
+            AbilityMember(decode_collection, None, this_decode_collection_fn_var)
+        } else {
+            Var(decode_collection, this_decode_collection_fn_var)
+        };
+        let decode_collection_fn = Box::new((
+            decode_collection_fn_var,
+            Loc::at_zero(decode_collection_fn),
+            this_decode_collection_clos_var,
+            this_decode_collection_ret_var,
         ));
 
-        let decode_list_call = Call(
-            decode_list_fn,
-            vec![(elem_decoder_var, Loc::at_zero(elem_decoder))],
-            CalledVia::Space,
-        );
+        let decode_collection_call = Call(decode_collection_fn, elem_decoders, CalledVia::Space);
 
-        (decode_list_call, this_decode_list_ret_var)
+        (decode_collection_call, this_decode_collection_ret_var)
     };
 
     let bytes_sym = env.new_symbol("bytes");
@@ -99,6 +122,6 @@ pub(crate) fn decoder(env: &mut Env<'_>, _def_symbol: Symbol) -> (Expr, Variable
         bytes_sym,
         (fmt_sym, fmt_var),
         captures,
-        (decode_list_call, this_decode_list_ret_var),
+        (decode_collection_call, this_decode_collection_ret_var),
     )
 }
@@ -29,8 +29,8 @@ use super::wrap_in_decode_custom_decode_with;
 /// ```roc
 /// decoder : Decoder {first: a, second: b} fmt | a has Decoding, b has Decoding, fmt has DecoderFormatting
 /// decoder =
-///     initialState : {f0: Result a [NoField], f1: Result b [NoField]}
-///     initialState = {f0: Err NoField, f1: Err NoField}
+///     initialState : {first: Result a [NoField], second: Result b [NoField]}
+///     initialState = {first: Err NoField, second: Err NoField}
 ///
 ///     stepField = \state, field ->
 ///         when field is
@@ -38,21 +38,29 @@ use super::wrap_in_decode_custom_decode_with;
 ///                 Keep (Decode.custom \bytes, fmt ->
 ///                     when Decode.decodeWith bytes Decode.decoder fmt is
 ///                         {result, rest} ->
-///                             {result: Result.map result \val -> {state & f0: Ok val}, rest})
+///                             {result: Result.map result \val -> {state & first: Ok val}, rest})
 ///             "second" ->
 ///                 Keep (Decode.custom \bytes, fmt ->
 ///                     when Decode.decodeWith bytes Decode.decoder fmt is
 ///                         {result, rest} ->
-///                             {result: Result.map result \val -> {state & f1: Ok val}, rest})
+///                             {result: Result.map result \val -> {state & second: Ok val}, rest})
 ///             _ -> Skip
 ///
-///     finalizer = \{f0, f1} ->
-///         when f0 is
+///     finalizer = \rec ->
+///         when
+///             when rec.first is
+///                 Ok first -> Ok first
+///                 _ -> Decode.decodeMissingField {}
+///         is
 ///             Ok first ->
-///                 when f1 is
+///                 when
+///                     when rec.second is
+///                         Ok second -> Ok second
+///                         _ -> Decode.decodeMissingField {}
+///                 is
 ///                     Ok second -> Ok {first, second}
-///                     Err NoField -> Err TooShort
-///             Err NoField -> Err TooShort
+///                     _ -> Err TooShort
+///             _ -> Err TooShort
 ///
 ///     Decode.custom \bytes, fmt -> Decode.decodeWith bytes (Decode.record initialState stepField finalizer) fmt
 /// ```
@@ -726,12 +734,20 @@ fn step_field(
 
 // Example:
 // finalizer = \rec ->
-//     when rec.first is
+//     when
+//         when rec.first is
+//             Ok first -> Ok first
+//             _ -> Decode.decodeMissingField {}
+//     is
 //         Ok first ->
-//             when rec.second is
+//             when
+//                 when rec.second is
+//                     Ok second -> Ok second
+//                     _ -> Decode.decodeMissingField {}
+//             is
 //                 Ok second -> Ok {first, second}
-//                 Err NoField -> Err TooShort
-//         Err NoField -> Err TooShort
+//                 _ -> Err TooShort
+//         _ -> Err TooShort
 fn finalizer(
     env: &mut Env,
     state_record_var: Variable,
@@ -802,11 +818,16 @@ fn finalizer(
         }
     };
 
-    // Unwrap each result in the decoded state
+    // Unwrap each result in the decoded state. A field that never appeared in the input is
+    // decoded as missing, which only succeeds for an OptionalField.
     //
-    // when rec.first is
+    // when
+    //     when rec.first is
+    //         Ok first -> Ok first
+    //         _ -> Decode.decodeMissingField {}
+    // is
     //     Ok first -> ...happy path...
-    //     Err NoField -> Err TooShort
+    //     _ -> Err TooShort
     for (((symbol, field_name), &field_var), &result_field_var) in pattern_symbols
         .iter()
         .rev()
@@ -814,20 +835,83 @@ fn finalizer(
         .zip(field_vars.iter().rev())
         .zip(result_field_vars.iter().rev())
     {
+        // The field's value, or the error from decoding it as missing: [Ok a, Err DecodeError]
+        let decoded_field_var = {
+            let flat_type = FlatType::TagUnion(
+                UnionTags::for_result(env.subs, field_var, decode_err_var),
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            );
+
+            synth_var(env.subs, Content::Structure(flat_type))
+        };
+
         // when rec.first is
-        let cond_expr = Expr::RecordAccess {
-            record_var: state_record_var,
-            ext_var: env.new_ext_var(ExtensionKind::Record),
-            field_var: result_field_var,
-            loc_expr: Box::new(Loc::at_zero(Expr::Var(state_arg_symbol, state_record_var))),
-            field: field_name.clone(),
+        //     Ok first -> Ok first
+        //     _ -> Decode.decodeMissingField {}
+        let decoded_field = {
+            let present_symbol = env.new_symbol(field_name.as_str());
+
+            // Example: `Ok first -> Ok first`
+            let present_branch = WhenBranch {
+                patterns: vec![WhenBranchPattern {
+                    pattern: Loc::at_zero(Pattern::AppliedTag {
+                        whole_var: result_field_var,
+                        ext_var: Variable::EMPTY_TAG_UNION,
+                        tag_name: "Ok".into(),
+                        arguments: vec![(
+                            field_var,
+                            Loc::at_zero(Pattern::Identifier(present_symbol)),
+                        )],
+                    }),
+                    degenerate: false,
+                }],
+                value: Loc::at_zero(Expr::Tag {
+                    tag_union_var: decoded_field_var,
+                    ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                    name: "Ok".into(),
+                    arguments: vec![(
+                        field_var,
+                        Loc::at_zero(Expr::Var(present_symbol, field_var)),
+                    )],
+                }),
+                guard: None,
+                redundant: RedundantMark::known_non_redundant(),
+            };
+
+            // Example: `_ -> Decode.decodeMissingField {}`
+            let missing_branch = WhenBranch {
+                patterns: vec![WhenBranchPattern {
+                    pattern: Loc::at_zero(Pattern::Underscore),
+                    degenerate: false,
+                }],
+                value: Loc::at_zero(decode_missing_field(env, decoded_field_var)),
+                guard: None,
+                redundant: RedundantMark::known_non_redundant(),
+            };
+
+            // when rec.first is
+            Expr::When {
+                loc_cond: Box::new(Loc::at_zero(Expr::RecordAccess {
+                    record_var: state_record_var,
+                    ext_var: env.new_ext_var(ExtensionKind::Record),
+                    field_var: result_field_var,
+                    loc_expr: Box::new(Loc::at_zero(Expr::Var(state_arg_symbol, state_record_var))),
+                    field: field_name.clone(),
+                })),
+                cond_var: result_field_var,
+                expr_var: decoded_field_var,
+                region: Region::zero(),
+                branches: vec![present_branch, missing_branch],
+                branches_cond_var: result_field_var,
+                exhaustive: ExhaustiveMark::known_exhaustive(),
+            }
         };
 
         // Example: `Ok x -> expr`
         let ok_branch = WhenBranch {
             patterns: vec![WhenBranchPattern {
                 pattern: Loc::at_zero(Pattern::AppliedTag {
-                    whole_var: result_field_var,
+                    whole_var: decoded_field_var,
                     ext_var: Variable::EMPTY_TAG_UNION,
                     tag_name: "Ok".into(),
                     arguments: vec![(field_var, Loc::at_zero(Pattern::Identifier(*symbol)))],
@@ -864,12 +948,12 @@ fn finalizer(
         };
 
         body = Expr::When {
-            loc_cond: Box::new(Loc::at_zero(cond_expr)),
-            cond_var: result_field_var,
+            loc_cond: Box::new(Loc::at_zero(decoded_field)),
+            cond_var: decoded_field_var,
             expr_var: return_type_var,
             region: Region::zero(),
             branches: vec![ok_branch, err_branch],
-            branches_cond_var: result_field_var,
+            branches_cond_var: decoded_field_var,
             exhaustive: ExhaustiveMark::known_exhaustive(),
         };
     }
@@ -911,6 +995,46 @@ fn finalizer(
     (finalizer, function_var, decode_err_var)
 }
 
+// Decodes a field that never appeared in the input, which only succeeds if it is an
+// OptionalField.
+//
+// Example:
+// Decode.decodeMissingField {}
+fn decode_missing_field(env: &mut Env, decoded_field_var: Variable) -> Expr {
+    let decode_missing_field_var =
+        env.import_builtin_symbol_var(Symbol::DECODE_DECODE_MISSING_FIELD);
+    let lambda_set_var = env.subs.fresh_unnamed_flex_var();
+    let this_decode_missing_field_var = {
+        let subs_slice = SubsSlice::insert_into_subs(env.subs, [Variable::EMPTY_RECORD]);
+        let this_decode_missing_field_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Func(
+                subs_slice,
+                lambda_set_var,
+                decoded_field_var,
+            )),
+        );
+
+        env.unify(decode_missing_field_var, this_decode_missing_field_var);
+
+        this_decode_missing_field_var
+    };
+
+    Expr::Call(
+        Box::new((
+            this_decode_missing_field_var,
+            Loc::at_zero(Expr::Var(
+                Symbol::DECODE_DECODE_MISSING_FIELD,
+                this_decode_missing_field_var,
+            )),
+            lambda_set_var,
+            decoded_field_var,
+        )),
+        vec![(Variable::EMPTY_RECORD, Loc::at_zero(Expr::EmptyRecord))],
+        CalledVia::Space,
+    )
+}
+
 // Example:
 // initialState : {first: Result a [NoField], second: Result b [NoField]}
 // initialState = {first: Err NoField, second: Err NoField}
@@ -0,0 +1,261 @@
+use roc_can::expr::{AnnotatedMark, ClosureData, Expr, Recursive, WhenBranch, WhenBranchPattern};
+use roc_can::pattern::Pattern;
+use roc_module::called_via::CalledVia;
+use roc_module::ident::TagName;
+use roc_module::symbol::Symbol;
+use roc_region::all::{Loc, Region};
+use roc_types::subs::{
+    Content, ExhaustiveMark, FlatType, LambdaSet, OptVariable, RedundantMark, SubsSlice, TagExt,
+    UnionLambdas, UnionTags, Variable,
+};
+
+use crate::synth_var;
+use crate::util::{Env, ExtensionKind};
+
+use super::tuple::call_decode_tuple;
+use super::wrap_in_decode_custom_decode_with;
+
+/// Implements decoding of a tag union. For example, for
+///
+/// ```text
+///   [A a b, B]
+/// ```
+///
+/// we'd like to generate an impl like
+///
+/// ```roc
+/// decoder : Decoder [A a b, B] fmt | a has Decoding, b has Decoding, fmt has DecoderFormatting
+/// decoder =
+///     stepTag = \tagName ->
+///         when tagName is
+///             "A" -> Payload (Decode.tuple initialStateA stepElemA finalizerA)
+///             "B" -> Payload (Decode.tuple {} stepElemB finalizerB)
+///             _ -> UnknownTag
+///
+///     Decode.custom \bytes, fmt -> Decode.decodeWith bytes (Decode.tag stepTag) fmt
+/// ```
+///
+/// The payload of each tag is decoded like a tuple of the tag's arguments, as in
+/// [`super::tuple::decoder`], except that the finalizer of `A` produces `Ok (A e0 e1)`.
+pub(crate) fn decoder(
+    env: &mut Env,
+    _def_symbol: Symbol,
+    tags: Vec<(TagName, u16)>,
+) -> (Expr, Variable) {
+    // Generalized tag union var so we can reuse this impl between many unions:
+    // if tags = [ A arity=2, B arity=0 ], this is [ A t1 t2, B ] for fresh t1, t2
+    let tags = tags
+        .into_iter()
+        .map(|(tag_name, arity)| {
+            let payload_vars = (0..arity)
+                .map(|_| env.subs.fresh_unnamed_flex_var())
+                .collect::<Vec<_>>();
+            (tag_name, payload_vars)
+        })
+        .collect::<Vec<_>>();
+    let tag_union_var = {
+        let union_tags = UnionTags::insert_into_subs(env.subs, tags.iter().cloned());
+
+        synth_var(
+            env.subs,
+            Content::Structure(FlatType::TagUnion(
+                union_tags,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        )
+    };
+
+    // stepTag = ...
+    let (step_tag, step_tag_var) = step_tag(env, tag_union_var, tags);
+
+    // Build up the type of `Decode.tag` we expect
+    let tag_decoder_var = env.subs.fresh_unnamed_flex_var();
+    let decode_tag_lambda_set = env.subs.fresh_unnamed_flex_var();
+    let decode_tag_var = env.import_builtin_symbol_var(Symbol::DECODE_TAG);
+    let this_decode_tag_var = {
+        let flat_type = FlatType::Func(
+            SubsSlice::insert_into_subs(env.subs, [step_tag_var]),
+            decode_tag_lambda_set,
+            tag_decoder_var,
+        );
+
+        synth_var(env.subs, Content::Structure(flat_type))
+    };
+
+    env.unify(decode_tag_var, this_decode_tag_var);
+
+    // Decode.tag stepTag
+    let call_decode_tag = Expr::Call(
+        Box::new((
+            this_decode_tag_var,
+            Loc::at_zero(Expr::AbilityMember(
+                Symbol::DECODE_TAG,
+                None,
+                this_decode_tag_var,
+            )),
+            decode_tag_lambda_set,
+            tag_decoder_var,
+        )),
+        vec![(step_tag_var, Loc::at_zero(step_tag))],
+        CalledVia::Space,
+    );
+
+    let bytes_sym = env.new_symbol("bytes");
+    let fmt_sym = env.new_symbol("fmt");
+    let fmt_var = env.subs.fresh_unnamed_flex_var();
+
+    wrap_in_decode_custom_decode_with(
+        env,
+        bytes_sym,
+        (fmt_sym, fmt_var),
+        vec![],
+        (call_decode_tag, tag_decoder_var),
+    )
+}
+
+// Example:
+// stepTag = \tagName ->
+//     when tagName is
+//         "A" -> Payload (Decode.tuple initialStateA stepElemA finalizerA)
+//         "B" -> Payload (Decode.tuple {} stepElemB finalizerB)
+//         _ -> UnknownTag
+fn step_tag(
+    env: &mut Env,
+    tag_union_var: Variable,
+    tags: Vec<(TagName, Vec<Variable>)>,
+) -> (Expr, Variable) {
+    let tag_name_arg_symbol = env.new_symbol("tagName");
+
+    // +1 because of the default branch.
+    let mut branches = Vec::with_capacity(tags.len() + 1);
+
+    // The decoder of every tag's payload produces the whole tag union.
+    let payload_decoder_var = env.subs.fresh_unnamed_flex_var();
+    let payload_or_unknown_var = {
+        let payload_subs_slice = SubsSlice::insert_into_subs(env.subs, [payload_decoder_var]);
+        let flat_type = FlatType::TagUnion(
+            UnionTags::insert_slices_into_subs(
+                env.subs,
+                [
+                    ("Payload".into(), payload_subs_slice),
+                    ("UnknownTag".into(), Default::default()),
+                ],
+            ),
+            TagExt::Any(Variable::EMPTY_TAG_UNION),
+        );
+
+        synth_var(env.subs, Content::Structure(flat_type))
+    };
+
+    for (tag_name, payload_vars) in tags {
+        // Decode.tuple initialStateA stepElemA finalizerA
+        let pattern = Pattern::StrLiteral(tag_name.0.as_str().into());
+        let (decode_payload, decode_payload_var) =
+            call_decode_tuple(env, &payload_vars, |env, elems| {
+                // A e0 e1
+                let tag = Expr::Tag {
+                    tag_union_var,
+                    ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                    name: tag_name,
+                    arguments: elems
+                        .into_iter()
+                        .map(|(var, elem)| (var, Loc::at_zero(elem)))
+                        .collect(),
+                };
+
+                (tag, tag_union_var)
+            });
+
+        env.unify(payload_decoder_var, decode_payload_var);
+
+        // "A" -> Payload (Decode.tuple initialStateA stepElemA finalizerA)
+        let branch = WhenBranch {
+            patterns: vec![WhenBranchPattern {
+                pattern: Loc::at_zero(pattern),
+                degenerate: false,
+            }],
+            value: Loc::at_zero(Expr::Tag {
+                tag_union_var: payload_or_unknown_var,
+                ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+                name: "Payload".into(),
+                arguments: vec![(payload_decoder_var, Loc::at_zero(decode_payload))],
+            }),
+            guard: None,
+            redundant: RedundantMark::known_non_redundant(),
+        };
+
+        branches.push(branch);
+    }
+
+    // Example: `_ -> UnknownTag`
+    let default_branch = WhenBranch {
+        patterns: vec![WhenBranchPattern {
+            pattern: Loc::at_zero(Pattern::Underscore),
+            degenerate: false,
+        }],
+        value: Loc::at_zero(Expr::Tag {
+            tag_union_var: payload_or_unknown_var,
+            ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+            name: "UnknownTag".into(),
+            arguments: Vec::new(),
+        }),
+        guard: None,
+        redundant: RedundantMark::known_non_redundant(),
+    };
+
+    branches.push(default_branch);
+
+    // when tagName is
+    let body = Expr::When {
+        loc_cond: Box::new(Loc::at_zero(Expr::Var(tag_name_arg_symbol, Variable::STR))),
+        cond_var: Variable::STR,
+        expr_var: payload_or_unknown_var,
+        region: Region::zero(),
+        branches,
+        branches_cond_var: Variable::STR,
+        exhaustive: ExhaustiveMark::known_exhaustive(),
+    };
+
+    let step_tag_closure = env.new_symbol("stepTag");
+    let function_type = env.subs.fresh_unnamed_flex_var();
+    let closure_type = {
+        let lambda_set = LambdaSet {
+            solved: UnionLambdas::tag_without_arguments(env.subs, step_tag_closure),
+            recursion_var: OptVariable::NONE,
+            unspecialized: Default::default(),
+            ambient_function: function_type,
+        };
+
+        synth_var(env.subs, Content::LambdaSet(lambda_set))
+    };
+
+    {
+        let args_slice = SubsSlice::insert_into_subs(env.subs, [Variable::STR]);
+
+        env.subs.set_content(
+            function_type,
+            Content::Structure(FlatType::Func(
+                args_slice,
+                closure_type,
+                payload_or_unknown_var,
+            )),
+        )
+    };
+
+    let expr = Expr::Closure(ClosureData {
+        function_type,
+        closure_type,
+        return_type: payload_or_unknown_var,
+        name: step_tag_closure,
+        captured_symbols: Vec::new(),
+        recursive: Recursive::NotRecursive,
+        arguments: vec![(
+            Variable::STR,
+            AnnotatedMark::known_exhaustive(),
+            Loc::at_zero(Pattern::Identifier(tag_name_arg_symbol)),
+        )],
+        loc_body: Box::new(Loc::at_zero(body)),
+    });
+
+    (expr, function_type)
+}
@@ -59,33 +59,81 @@ use super::wrap_in_decode_custom_decode_with;
 /// ```
 pub(crate) fn decoder(env: &mut Env, _def_symbol: Symbol, arity: u32) -> (Expr, Variable) {
     // The decoded type of each index in the tuple, e.g. (a, b).
-    let mut index_vars = Vec::with_capacity(arity as _);
+    let index_vars = (0..arity)
+        .map(|_| env.subs.fresh_unnamed_flex_var())
+        .collect::<Vec<_>>();
+
+    // Decode.tuple initialState stepElem finalizer
+    let (call_decode_tuple, tuple_decoder_var) =
+        call_decode_tuple(env, &index_vars, |env, elems| {
+            // (e0, e1)
+            let tuple_elems_iter = index_vars.iter().copied().enumerate();
+            let flat_type = FlatType::Tuple(
+                TupleElems::insert_into_subs(env.subs, tuple_elems_iter),
+                Variable::EMPTY_TUPLE,
+            );
+            let done_tuple_var = synth_var(env.subs, Content::Structure(flat_type));
+            let done_tuple = Expr::Tuple {
+                tuple_var: done_tuple_var,
+                elems: elems
+                    .into_iter()
+                    .map(|(var, elem)| (var, Box::new(Loc::at_zero(elem))))
+                    .collect(),
+            };
+
+            (done_tuple, done_tuple_var)
+        });
+
+    let (call_decode_custom, decode_custom_ret_var) = {
+        let bytes_sym = env.new_symbol("bytes");
+        let fmt_sym = env.new_symbol("fmt");
+        let fmt_var = env.subs.fresh_unnamed_flex_var();
+
+        let (decode_custom, decode_custom_var) = wrap_in_decode_custom_decode_with(
+            env,
+            bytes_sym,
+            (fmt_sym, fmt_var),
+            vec![],
+            (call_decode_tuple, tuple_decoder_var),
+        );
+
+        (decode_custom, decode_custom_var)
+    };
+
+    (call_decode_custom, decode_custom_ret_var)
+}
+
+/// Builds `Decode.tuple initialState stepElem finalizer`, which decodes one element for each of
+/// `index_vars` and then produces `Ok (done elems)` from the decoded elements.
+///
+/// Besides tuples themselves, this is used to decode the payloads of tags.
+pub(super) fn call_decode_tuple(
+    env: &mut Env,
+    index_vars: &[Variable],
+    done: impl FnOnce(&mut Env, Vec<(Variable, Expr)>) -> (Expr, Variable),
+) -> (Expr, Variable) {
     // The type of each index in the decoding state, e.g. {e0: Result a [NoElem], e1: Result b [NoElem]}
-    let mut state_fields = Vec::with_capacity(arity as _);
-    let mut state_field_vars = Vec::with_capacity(arity as _);
+    let mut state_fields = Vec::with_capacity(index_vars.len());
+    let mut state_field_vars = Vec::with_capacity(index_vars.len());
 
     // initialState = ...
-    let (state_var, initial_state) = initial_state(
-        env,
-        arity,
-        &mut index_vars,
-        &mut state_fields,
-        &mut state_field_vars,
-    );
+    let (state_var, initial_state) =
+        initial_state(env, index_vars, &mut state_fields, &mut state_field_vars);
 
     // finalizer = ...
     let (finalizer, finalizer_var, decode_err_var) = finalizer(
         env,
-        &index_vars,
+        index_vars,
         state_var,
         &state_fields,
         &state_field_vars,
+        done,
     );
 
     // stepElem = ...
     let (step_elem, step_var) = step_elem(
         env,
-        &index_vars,
+        index_vars,
         state_var,
         &state_fields,
         &state_field_vars,
@@ -94,30 +142,30 @@ pub(crate) fn decoder(env: &mut Env, _def_symbol: Symbol, arity: u32) -> (Expr,
 
     // Build up the type of `Decode.tuple` we expect
     let tuple_decoder_var = env.subs.fresh_unnamed_flex_var();
-    let decode_record_lambda_set = env.subs.fresh_unnamed_flex_var();
-    let decode_record_var = env.import_builtin_symbol_var(Symbol::DECODE_TUPLE);
-    let this_decode_record_var = {
+    let decode_tuple_lambda_set = env.subs.fresh_unnamed_flex_var();
+    let decode_tuple_var = env.import_builtin_symbol_var(Symbol::DECODE_TUPLE);
+    let this_decode_tuple_var = {
         let flat_type = FlatType::Func(
             SubsSlice::insert_into_subs(env.subs, [state_var, step_var, finalizer_var]),
-            decode_record_lambda_set,
+            decode_tuple_lambda_set,
             tuple_decoder_var,
         );
 
         synth_var(env.subs, Content::Structure(flat_type))
     };
 
-    env.unify(decode_record_var, this_decode_record_var);
+    env.unify(decode_tuple_var, this_decode_tuple_var);
 
     // Decode.tuple initialState stepElem finalizer
-    let call_decode_record = Expr::Call(
+    let call_decode_tuple = Expr::Call(
         Box::new((
-            this_decode_record_var,
+            this_decode_tuple_var,
             Loc::at_zero(Expr::AbilityMember(
                 Symbol::DECODE_TUPLE,
                 None,
-                this_decode_record_var,
+                this_decode_tuple_var,
             )),
-            decode_record_lambda_set,
+            decode_tuple_lambda_set,
             tuple_decoder_var,
         )),
         vec![
@@ -128,23 +176,7 @@ pub(crate) fn decoder(env: &mut Env, _def_symbol: Symbol, arity: u32) -> (Expr,
         CalledVia::Space,
     );
 
-    let (call_decode_custom, decode_custom_ret_var) = {
-        let bytes_sym = env.new_symbol("bytes");
-        let fmt_sym = env.new_symbol("fmt");
-        let fmt_var = env.subs.fresh_unnamed_flex_var();
-
-        let (decode_custom, decode_custom_var) = wrap_in_decode_custom_decode_with(
-            env,
-            bytes_sym,
-            (fmt_sym, fmt_var),
-            vec![],
-            (call_decode_record, tuple_decoder_var),
-        );
-
-        (decode_custom, decode_custom_var)
-    };
-
-    (call_decode_custom, decode_custom_ret_var)
+    (call_decode_tuple, tuple_decoder_var)
 }
 
 // Example:
@@ -746,9 +778,10 @@ fn finalizer(
     state_record_var: Variable,
     state_fields: &[Lowercase],
     state_field_vars: &[Variable],
+    done: impl FnOnce(&mut Env, Vec<(Variable, Expr)>) -> (Expr, Variable),
 ) -> (Expr, Variable, Variable) {
     let state_arg_symbol = env.new_symbol("stateRecord");
-    let mut tuple_elems = Vec::with_capacity(index_vars.len());
+    let mut elems = Vec::with_capacity(index_vars.len());
     let mut pattern_symbols = Vec::with_capacity(index_vars.len());
     let decode_err_var = {
         let flat_type = FlatType::TagUnion(
@@ -764,41 +797,29 @@ fn finalizer(
 
         pattern_symbols.push(symbol);
 
-        let index_expr = Expr::Var(symbol, index_var);
-
-        tuple_elems.push((index_var, Box::new(Loc::at_zero(index_expr))));
+        elems.push((index_var, Expr::Var(symbol, index_var)));
     }
 
-    // The bottom of the happy path - return the decoded tuple (a, b) wrapped with
-    // "Ok".
+    // The bottom of the happy path - return the decoded value, e.g. the tuple (a, b), wrapped
+    // with "Ok".
     let return_type_var;
     let mut body = {
-        let subs = &mut env.subs;
-        let tuple_indices_iter = index_vars.iter().copied().enumerate();
-        let flat_type = FlatType::Tuple(
-            TupleElems::insert_into_subs(subs, tuple_indices_iter),
-            Variable::EMPTY_TUPLE,
-        );
-        let done_tuple_var = synth_var(subs, Content::Structure(flat_type));
-        let done_record = Expr::Tuple {
-            tuple_var: done_tuple_var,
-            elems: tuple_elems,
-        };
+        let (done_value, done_var) = done(env, elems);
 
         return_type_var = {
             let flat_type = FlatType::TagUnion(
-                UnionTags::for_result(subs, done_tuple_var, decode_err_var),
+                UnionTags::for_result(env.subs, done_var, decode_err_var),
                 TagExt::Any(Variable::EMPTY_TAG_UNION),
             );
 
-            synth_var(subs, Content::Structure(flat_type))
+            synth_var(env.subs, Content::Structure(flat_type))
         };
 
         Expr::Tag {
             tag_union_var: return_type_var,
             ext_var: env.new_ext_var(ExtensionKind::TagUnion),
             name: "Ok".into(),
-            arguments: vec![(done_tuple_var, Loc::at_zero(done_record))],
+            arguments: vec![(done_var, Loc::at_zero(done_value))],
         }
     };
 
@@ -916,19 +937,19 @@ fn finalizer(
 // initialState = {e0: Err NoElem, e1: Err NoElem}
 fn initial_state(
     env: &mut Env<'_>,
-    arity: u32,
-    index_vars: &mut Vec<Variable>,
+    index_vars: &[Variable],
     state_fields: &mut Vec<Lowercase>,
     state_field_vars: &mut Vec<Variable>,
 ) -> (Variable, Expr) {
+    if index_vars.is_empty() {
+        // Nothing to decode, e.g. for the payload of a tag without arguments.
+        return (Variable::EMPTY_RECORD, Expr::EmptyRecord);
+    }
+
     let mut initial_state_fields = SendMap::default();
 
-    for i in 0..arity {
+    for (i, &index_var) in index_vars.iter().enumerate() {
         let subs = &mut env.subs;
-        let index_var = subs.fresh_unnamed_flex_var();
-
-        index_vars.push(index_var);
-
         let state_field = Lowercase::from(format!("e{i}"));
         state_fields.push(state_field.clone());
 
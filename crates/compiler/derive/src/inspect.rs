@@ -0,0 +1,777 @@
+//! Derivers for the `Inspect` ability.
+
+use std::iter::once;
+
+use roc_can::expr::{
+    AnnotatedMark, ClosureData, Expr, Field, Recursive, WhenBranch, WhenBranchPattern,
+};
+use roc_can::pattern::Pattern;
+use roc_collections::SendMap;
+use roc_derive_key::inspect::FlatInspectableKey;
+use roc_module::called_via::CalledVia;
+use roc_module::ident::Lowercase;
+use roc_module::symbol::Symbol;
+use roc_region::all::{Loc, Region};
+use roc_types::subs::{
+    Content, ExhaustiveMark, FlatType, GetSubsSlice, LambdaSet, OptVariable, RecordFields,
+    RedundantMark, SubsSlice, TagExt, TupleElems, UnionLambdas, UnionTags, Variable,
+    VariableSubsSlice,
+};
+use roc_types::types::RecordField;
+
+use crate::util::Env;
+use crate::{synth_var, DerivedBody};
+
+pub(crate) fn derive_to_inspector(
+    env: &mut Env<'_>,
+    key: FlatInspectableKey,
+    def_symbol: Symbol,
+) -> DerivedBody {
+    let (body, body_type) = match key {
+        FlatInspectableKey::List() => to_inspector_list(env, def_symbol),
+        FlatInspectableKey::Set() => to_inspector_set(env, def_symbol),
+        FlatInspectableKey::Dict() => to_inspector_dict(env, def_symbol),
+        FlatInspectableKey::Record(fields) => {
+            // Generalized record var so we can reuse this impl between many records:
+            // if fields = { a, b }, this is { a: t1, b: t2 } for fresh t1, t2.
+            let flex_fields = fields
+                .into_iter()
+                .map(|name| {
+                    (
+                        name,
+                        RecordField::Required(env.subs.fresh_unnamed_flex_var()),
+                    )
+                })
+                .collect::<Vec<(Lowercase, _)>>();
+            let fields = RecordFields::insert_into_subs(env.subs, flex_fields);
+            let record_var = synth_var(
+                env.subs,
+                Content::Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+            );
+
+            to_inspector_record(env, record_var, fields, def_symbol)
+        }
+        FlatInspectableKey::Tuple(arity) => {
+            // Generalized tuple var so we can reuse this impl between many tuples:
+            // if arity = n, this is (t1, ..., tn) for fresh t1, ..., tn.
+            let flex_elems = (0..arity)
+                .map(|idx| (idx as usize, env.subs.fresh_unnamed_flex_var()))
+                .collect::<Vec<_>>();
+            let elems = TupleElems::insert_into_subs(env.subs, flex_elems);
+            let tuple_var = synth_var(
+                env.subs,
+                Content::Structure(FlatType::Tuple(elems, Variable::EMPTY_TUPLE)),
+            );
+
+            to_inspector_tuple(env, tuple_var, elems, def_symbol)
+        }
+        FlatInspectableKey::TagUnion(tags) => {
+            // Generalized tag union var so we can reuse this impl between many unions:
+            // if tags = [ A arity=2, B arity=1 ], this is [ A t1 t2, B t3 ] for fresh t1, t2, t3
+            let flex_tag_labels = tags
+                .into_iter()
+                .map(|(label, arity)| {
+                    let variables_slice =
+                        VariableSubsSlice::reserve_into_subs(env.subs, arity.into());
+                    for var_index in variables_slice {
+                        env.subs[var_index] = env.subs.fresh_unnamed_flex_var();
+                    }
+                    (label, variables_slice)
+                })
+                .collect::<Vec<_>>();
+            let union_tags = UnionTags::insert_slices_into_subs(env.subs, flex_tag_labels);
+            let tag_union_var = synth_var(
+                env.subs,
+                Content::Structure(FlatType::TagUnion(
+                    union_tags,
+                    TagExt::Any(Variable::EMPTY_TAG_UNION),
+                )),
+            );
+
+            to_inspector_tag_union(env, tag_union_var, union_tags, def_symbol)
+        }
+    };
+
+    let specialization_lambda_sets =
+        env.get_specialization_lambda_sets(body_type, Symbol::INSPECT_TO_INSPECTOR);
+
+    DerivedBody {
+        body,
+        body_type,
+        specialization_lambda_sets,
+    }
+}
+
+fn to_inspector_list(env: &mut Env<'_>, fn_name: Symbol) -> (Expr, Variable) {
+    // Build \lst -> Inspect.list lst (\elem -> Inspect.toInspector elem)
+    let lst_sym = env.new_symbol("lst");
+
+    // List elem
+    let elem_var = env.subs.fresh_unnamed_flex_var();
+    let elem_var_slice = SubsSlice::insert_into_subs(env.subs, [elem_var]);
+    let list_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::Apply(Symbol::LIST_LIST, elem_var_slice)),
+    );
+
+    // \elem -> Inspect.toInspector elem
+    let (to_elem_inspector, to_elem_inspector_fn_var) =
+        to_inspector_closure(env, "to_elem_inspector", elem_var);
+
+    // Inspect.list lst (\elem -> Inspect.toInspector elem)
+    let (inspect_list_call, list_inspector_var) = call_formatter_member(
+        env,
+        Symbol::INSPECT_LIST,
+        vec![
+            (list_var, Expr::Var(lst_sym, list_var)),
+            (to_elem_inspector_fn_var, to_elem_inspector),
+        ],
+    );
+
+    // Inspect.custom \fmt -> Inspect.apply (Inspect.list ..) fmt
+    let (body, this_inspector_var) = wrap_in_inspect_custom(
+        env,
+        inspect_list_call,
+        list_inspector_var,
+        lst_sym,
+        list_var,
+    );
+
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        (list_var, lst_sym),
+        (this_inspector_var, body),
+    )
+}
+
+fn to_inspector_set(env: &mut Env<'_>, fn_name: Symbol) -> (Expr, Variable) {
+    // Build \set -> Inspect.set set (\elem -> Inspect.toInspector elem)
+    //
+    // The type of `set` is left for `Inspect.set` to fill in as `Set elem`.
+    let set_sym = env.new_symbol("set");
+    let set_var = env.subs.fresh_unnamed_flex_var();
+    let elem_var = env.subs.fresh_unnamed_flex_var();
+
+    // \elem -> Inspect.toInspector elem
+    let (to_elem_inspector, to_elem_inspector_fn_var) =
+        to_inspector_closure(env, "to_elem_inspector", elem_var);
+
+    // Inspect.set set (\elem -> Inspect.toInspector elem)
+    let (inspect_set_call, set_inspector_var) = call_formatter_member(
+        env,
+        Symbol::INSPECT_SET,
+        vec![
+            (set_var, Expr::Var(set_sym, set_var)),
+            (to_elem_inspector_fn_var, to_elem_inspector),
+        ],
+    );
+
+    // Inspect.custom \fmt -> Inspect.apply (Inspect.set ..) fmt
+    let (body, this_inspector_var) =
+        wrap_in_inspect_custom(env, inspect_set_call, set_inspector_var, set_sym, set_var);
+
+    build_outer_derived_closure(env, fn_name, (set_var, set_sym), (this_inspector_var, body))
+}
+
+fn to_inspector_dict(env: &mut Env<'_>, fn_name: Symbol) -> (Expr, Variable) {
+    // Build
+    //
+    // \dict -> Inspect.dict dict
+    //     (\key -> Inspect.toInspector key)
+    //     (\value -> Inspect.toInspector value)
+    //
+    // The type of `dict` is left for `Inspect.dict` to fill in as `Dict k v`.
+    let dict_sym = env.new_symbol("dict");
+    let dict_var = env.subs.fresh_unnamed_flex_var();
+    let key_var = env.subs.fresh_unnamed_flex_var();
+    let value_var = env.subs.fresh_unnamed_flex_var();
+
+    // \key -> Inspect.toInspector key
+    let (to_key_inspector, to_key_inspector_fn_var) =
+        to_inspector_closure(env, "to_key_inspector", key_var);
+    // \value -> Inspect.toInspector value
+    let (to_value_inspector, to_value_inspector_fn_var) =
+        to_inspector_closure(env, "to_value_inspector", value_var);
+
+    // Inspect.dict dict (\key -> ..) (\value -> ..)
+    let (inspect_dict_call, dict_inspector_var) = call_formatter_member(
+        env,
+        Symbol::INSPECT_DICT,
+        vec![
+            (dict_var, Expr::Var(dict_sym, dict_var)),
+            (to_key_inspector_fn_var, to_key_inspector),
+            (to_value_inspector_fn_var, to_value_inspector),
+        ],
+    );
+
+    // Inspect.custom \fmt -> Inspect.apply (Inspect.dict ..) fmt
+    let (body, this_inspector_var) = wrap_in_inspect_custom(
+        env,
+        inspect_dict_call,
+        dict_inspector_var,
+        dict_sym,
+        dict_var,
+    );
+
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        (dict_var, dict_sym),
+        (this_inspector_var, body),
+    )
+}
+
+fn to_inspector_record(
+    env: &mut Env<'_>,
+    record_var: Variable,
+    fields: RecordFields,
+    fn_name: Symbol,
+) -> (Expr, Variable) {
+    // Suppose rcd = { a: t1, b: t2 }. Build
+    //
+    // \rcd -> Inspect.record [
+    //      { key: "a", value: Inspect.toInspector rcd.a },
+    //      { key: "b", value: Inspect.toInspector rcd.b },
+    //   ]
+
+    let rcd_sym = env.new_symbol("rcd");
+    let whole_rcd_var = env.subs.fresh_unnamed_flex_var(); // type of the { key, value } records in the list
+
+    use Expr::*;
+
+    let fields_list = fields
+        .iter_all()
+        .map(|(field_name_index, field_var_index, _)| {
+            let field_name = env.subs[field_name_index].clone();
+            let field_var = env.subs[field_var_index];
+
+            // key: "a"
+            let key_field = Field {
+                var: Variable::STR,
+                region: Region::zero(),
+                loc_expr: Box::new(Loc::at_zero(Str(field_name.as_str().into()))),
+            };
+
+            // rcd.a
+            let field_access = RecordAccess {
+                record_var,
+                ext_var: env.subs.fresh_unnamed_flex_var(),
+                field_var,
+                loc_expr: Box::new(Loc::at_zero(Var(
+                    rcd_sym,
+                    env.subs.fresh_unnamed_flex_var(),
+                ))),
+                field: field_name,
+            };
+
+            // Inspect.toInspector rcd.a
+            let (to_inspector_call, inspector_var) =
+                call_to_inspector(env, field_var, field_access);
+
+            // value: Inspect.toInspector rcd.a
+            let value_field = Field {
+                var: inspector_var,
+                region: Region::zero(),
+                loc_expr: Box::new(Loc::at_zero(to_inspector_call)),
+            };
+
+            // { key: "a", value: Inspect.toInspector rcd.a }
+            let mut kv = SendMap::default();
+            kv.insert("key".into(), key_field);
+            kv.insert("value".into(), value_field);
+
+            let this_record_fields = RecordFields::insert_into_subs(
+                env.subs,
+                (once(("key".into(), RecordField::Required(Variable::STR))))
+                    .chain(once(("value".into(), RecordField::Required(inspector_var)))),
+            );
+            let this_record_var = synth_var(
+                env.subs,
+                Content::Structure(FlatType::Record(this_record_fields, Variable::EMPTY_RECORD)),
+            );
+            // NOTE: must be done to unify the lambda sets under `inspector_var`
+            env.unify(this_record_var, whole_rcd_var);
+
+            Loc::at_zero(Record {
+                record_var: whole_rcd_var,
+                fields: kv,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // typeof [ { key: .., value: .. }, { key: .., value: .. } ]
+    let fields_rcd_var_slice = VariableSubsSlice::insert_into_subs(env.subs, once(whole_rcd_var));
+    let fields_list_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::Apply(Symbol::LIST_LIST, fields_rcd_var_slice)),
+    );
+
+    // [ { key: .., value: ..}, .. ]
+    let fields_list = List {
+        elem_var: whole_rcd_var,
+        loc_elems: fields_list,
+    };
+
+    // Inspect.record [ { key: .., value: .. }, .. ]
+    let (inspect_record_call, inspector_var) = call_formatter_member(
+        env,
+        Symbol::INSPECT_RECORD,
+        vec![(fields_list_var, fields_list)],
+    );
+
+    // Inspect.custom \fmt -> Inspect.apply (Inspect.record ..) fmt
+    let (body, this_inspector_var) =
+        wrap_in_inspect_custom(env, inspect_record_call, inspector_var, rcd_sym, record_var);
+
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        (record_var, rcd_sym),
+        (this_inspector_var, body),
+    )
+}
+
+fn to_inspector_tuple(
+    env: &mut Env<'_>,
+    tuple_var: Variable,
+    elems: TupleElems,
+    fn_name: Symbol,
+) -> (Expr, Variable) {
+    // Suppose tup = (t1, t2). Build
+    //
+    // \tup -> Inspect.tuple [
+    //      Inspect.toInspector tup.0,
+    //      Inspect.toInspector tup.1,
+    //   ]
+
+    let tup_sym = env.new_symbol("tup");
+    let whole_inspector_in_list_var = env.subs.fresh_unnamed_flex_var(); // type of the inspector in the list
+
+    use Expr::*;
+
+    let elem_inspectors_list = elems
+        .iter_all()
+        .map(|(elem_index, elem_var_index)| {
+            let index = env.subs[elem_index];
+            let elem_var = env.subs[elem_var_index];
+
+            // tup.0
+            let tuple_access = TupleAccess {
+                tuple_var,
+                ext_var: env.subs.fresh_unnamed_flex_var(),
+                elem_var,
+                loc_expr: Box::new(Loc::at_zero(Var(
+                    tup_sym,
+                    env.subs.fresh_unnamed_flex_var(),
+                ))),
+                index,
+            };
+
+            // Inspect.toInspector tup.0
+            let (to_inspector_call, inspector_var) = call_to_inspector(env, elem_var, tuple_access);
+
+            // NOTE: must be done to unify the lambda sets under `inspector_var`
+            env.unify(inspector_var, whole_inspector_in_list_var);
+
+            Loc::at_zero(to_inspector_call)
+        })
+        .collect::<Vec<_>>();
+
+    // typeof [ Inspect.toInspector tup.0, Inspect.toInspector tup.1 ]
+    let whole_inspector_in_list_var_slice =
+        VariableSubsSlice::insert_into_subs(env.subs, once(whole_inspector_in_list_var));
+    let elem_inspectors_list_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::Apply(
+            Symbol::LIST_LIST,
+            whole_inspector_in_list_var_slice,
+        )),
+    );
+
+    // [ Inspect.toInspector tup.0, Inspect.toInspector tup.1 ]
+    let elem_inspectors_list = List {
+        elem_var: whole_inspector_in_list_var,
+        loc_elems: elem_inspectors_list,
+    };
+
+    // Inspect.tuple [ Inspect.toInspector tup.0, .. ]
+    let (inspect_tuple_call, inspector_var) = call_formatter_member(
+        env,
+        Symbol::INSPECT_TUPLE,
+        vec![(elem_inspectors_list_var, elem_inspectors_list)],
+    );
+
+    // Inspect.custom \fmt -> Inspect.apply (Inspect.tuple ..) fmt
+    let (body, this_inspector_var) =
+        wrap_in_inspect_custom(env, inspect_tuple_call, inspector_var, tup_sym, tuple_var);
+
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        (tuple_var, tup_sym),
+        (this_inspector_var, body),
+    )
+}
+
+fn to_inspector_tag_union(
+    env: &mut Env<'_>,
+    tag_union_var: Variable,
+    tags: UnionTags,
+    fn_name: Symbol,
+) -> (Expr, Variable) {
+    // Suppose tag = [ A t1 t2, B t3 ]. Build
+    //
+    // \tag -> when tag is
+    //     A v1 v2 -> Inspect.tag "A" [ Inspect.toInspector v1, Inspect.toInspector v2 ]
+    //     B v3 -> Inspect.tag "B" [ Inspect.toInspector v3 ]
+
+    let tag_sym = env.new_symbol("tag");
+    let whole_tag_inspectors_var = env.subs.fresh_unnamed_flex_var(); // type of the Inspect.tag ... calls in the branch bodies
+
+    use Expr::*;
+
+    let branches = tags
+        .iter_all()
+        .map(|(tag_name_index, tag_vars_slice_index)| {
+            // A
+            let tag_name = &env.subs[tag_name_index].clone();
+            let vars_slice = env.subs[tag_vars_slice_index];
+            // t1 t2
+            let payload_vars = env.subs.get_subs_slice(vars_slice).to_vec();
+            // v1 v2
+            let payload_syms: Vec<_> = std::iter::repeat_with(|| env.unique_symbol())
+                .take(payload_vars.len())
+                .collect();
+
+            // `A v1 v2` pattern
+            let pattern = Pattern::AppliedTag {
+                whole_var: tag_union_var,
+                tag_name: tag_name.clone(),
+                ext_var: Variable::EMPTY_TAG_UNION,
+                // (t1, v1) (t2, v2)
+                arguments: (payload_vars.iter())
+                    .zip(payload_syms.iter())
+                    .map(|(var, sym)| (*var, Loc::at_zero(Pattern::Identifier(*sym))))
+                    .collect(),
+            };
+            let branch_pattern = WhenBranchPattern {
+                pattern: Loc::at_zero(pattern),
+                degenerate: false,
+            };
+
+            // whole type of the elements in [ Inspect.toInspector v1, Inspect.toInspector v2 ]
+            let whole_payload_inspectors_var = env.subs.fresh_unnamed_flex_var();
+            // [ Inspect.toInspector v1, Inspect.toInspector v2 ]
+            let payload_to_inspectors = (payload_syms.iter())
+                .zip(payload_vars.iter())
+                .map(|(&sym, &sym_var)| {
+                    // Inspect.toInspector v1
+                    let (to_inspector_call, inspector_var) =
+                        call_to_inspector(env, sym_var, Var(sym, sym_var));
+
+                    // NOTE: must be done to unify the lambda sets under `inspector_var`
+                    env.unify(inspector_var, whole_payload_inspectors_var);
+
+                    Loc::at_zero(to_inspector_call)
+                })
+                .collect();
+
+            // typeof [ Inspect.toInspector v1, Inspect.toInspector v2 ]
+            let whole_inspectors_var_slice =
+                VariableSubsSlice::insert_into_subs(env.subs, [whole_payload_inspectors_var]);
+            let payload_inspectors_list_var = synth_var(
+                env.subs,
+                Content::Structure(FlatType::Apply(
+                    Symbol::LIST_LIST,
+                    whole_inspectors_var_slice,
+                )),
+            );
+
+            // [ Inspect.toInspector v1, Inspect.toInspector v2 ]
+            let payload_inspectors_list = List {
+                elem_var: whole_payload_inspectors_var,
+                loc_elems: payload_to_inspectors,
+            };
+
+            // Inspect.tag "A" [ Inspect.toInspector v1, Inspect.toInspector v2 ]
+            let (inspect_tag_call, this_inspector_var) = call_formatter_member(
+                env,
+                Symbol::INSPECT_TAG,
+                vec![
+                    (Variable::STR, Str(tag_name.0.as_str().into())),
+                    (payload_inspectors_list_var, payload_inspectors_list),
+                ],
+            );
+
+            // NOTE: must be done to unify the lambda sets under `inspector_var`
+            // Inspect.tag "A" [ .. ] ~ whole_inspectors
+            env.unify(this_inspector_var, whole_tag_inspectors_var);
+
+            WhenBranch {
+                patterns: vec![branch_pattern],
+                value: Loc::at_zero(inspect_tag_call),
+                guard: None,
+                redundant: RedundantMark::known_non_redundant(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // when tag is
+    //     A v1 v2 -> Inspect.tag "A" [ Inspect.toInspector v1, Inspect.toInspector v2 ]
+    //     B v3 -> Inspect.tag "B" [ Inspect.toInspector v3 ]
+    let when_branches = When {
+        loc_cond: Box::new(Loc::at_zero(Var(tag_sym, tag_union_var))),
+        cond_var: tag_union_var,
+        expr_var: whole_tag_inspectors_var,
+        region: Region::zero(),
+        branches,
+        branches_cond_var: tag_union_var,
+        exhaustive: ExhaustiveMark::known_exhaustive(),
+    };
+
+    // Inspect.custom \fmt -> Inspect.apply (when ..) fmt
+    let (body, this_inspector_var) = wrap_in_inspect_custom(
+        env,
+        when_branches,
+        whole_tag_inspectors_var,
+        tag_sym,
+        tag_union_var,
+    );
+
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        (tag_union_var, tag_sym),
+        (this_inspector_var, body),
+    )
+}
+
+/// Builds `Inspect.toInspector value`, returning the call and the type of the inspector it makes.
+fn call_to_inspector(env: &mut Env<'_>, value_var: Variable, value: Expr) -> (Expr, Variable) {
+    call_builtin(
+        env,
+        Symbol::INSPECT_TO_INSPECTOR,
+        vec![(value_var, value)],
+        |fn_var| Expr::AbilityMember(Symbol::INSPECT_TO_INSPECTOR, None, fn_var),
+    )
+}
+
+/// Builds a call to a member of `InspectFormatter`, like `Inspect.list`.
+fn call_formatter_member(
+    env: &mut Env<'_>,
+    member: Symbol,
+    args: Vec<(Variable, Expr)>,
+) -> (Expr, Variable) {
+    call_builtin(env, member, args, |fn_var| {
+        Expr::AbilityMember(member, None, fn_var)
+    })
+}
+
+/// Builds `function ..args`, where `function` is a builtin, unifying the type of the call with the
+/// type of `function`. Returns the call and its return type.
+fn call_builtin(
+    env: &mut Env<'_>,
+    function: Symbol,
+    args: Vec<(Variable, Expr)>,
+    head: impl FnOnce(Variable) -> Expr,
+) -> (Expr, Variable) {
+    // expected: the type `function` is declared with
+    let exposed_fn_var = env.import_builtin_symbol_var(function);
+
+    // wanted: typeof arg1, .., typeof argN -[clos]-> t'
+    let args_slice =
+        VariableSubsSlice::insert_into_subs(env.subs, args.iter().map(|(var, _)| *var));
+    let clos_var = env.subs.fresh_unnamed_flex_var(); // clos
+    let ret_var = env.subs.fresh_unnamed_flex_var(); // t'
+    let fn_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::Func(args_slice, clos_var, ret_var)),
+    );
+
+    env.unify(exposed_fn_var, fn_var);
+
+    let call = Expr::Call(
+        Box::new((fn_var, Loc::at_zero(head(fn_var)), clos_var, ret_var)),
+        args.into_iter()
+            .map(|(var, expr)| (var, Loc::at_zero(expr)))
+            .collect(),
+        CalledVia::Space,
+    );
+
+    (call, ret_var)
+}
+
+/// Builds `\elem -> Inspect.toInspector elem`, returning the closure and its function type.
+fn to_inspector_closure(env: &mut Env<'_>, name: &str, elem_var: Variable) -> (Expr, Variable) {
+    let elem_sym = env.new_symbol("elem");
+    let fn_name = env.new_symbol(name);
+
+    // Inspect.toInspector elem
+    let (to_inspector_call, inspector_var) =
+        call_to_inspector(env, elem_var, Expr::Var(elem_sym, elem_var));
+
+    // Create fn_var for ambient capture; we fix it up below.
+    let fn_var = synth_var(env.subs, Content::Error);
+
+    // -[fn_name]->
+    let fn_name_labels = UnionLambdas::insert_into_subs(env.subs, once((fn_name, vec![])));
+    let fn_clos_var = synth_var(
+        env.subs,
+        Content::LambdaSet(LambdaSet {
+            solved: fn_name_labels,
+            recursion_var: OptVariable::NONE,
+            unspecialized: SubsSlice::default(),
+            ambient_function: fn_var,
+        }),
+    );
+    // elem -[fn_name]-> Inspect.toInspector elem
+    let elem_var_slice = SubsSlice::insert_into_subs(env.subs, [elem_var]);
+    env.subs.set_content(
+        fn_var,
+        Content::Structure(FlatType::Func(elem_var_slice, fn_clos_var, inspector_var)),
+    );
+
+    // \elem -[fn_name]-> Inspect.toInspector elem
+    let clos = Expr::Closure(ClosureData {
+        function_type: fn_var,
+        closure_type: fn_clos_var,
+        return_type: inspector_var,
+        name: fn_name,
+        captured_symbols: vec![],
+        recursive: Recursive::NotRecursive,
+        arguments: vec![(
+            elem_var,
+            AnnotatedMark::known_exhaustive(),
+            Loc::at_zero(Pattern::Identifier(elem_sym)),
+        )],
+        loc_body: Box::new(Loc::at_zero(to_inspector_call)),
+    });
+
+    (clos, fn_var)
+}
+
+/// Lift `inspector` to `Inspect.custom \fmt -> Inspect.apply inspector fmt`.
+///
+/// This mirrors `wrap_in_encode_custom` in the encoding deriver, which has more on why the lift
+/// is needed.
+fn wrap_in_inspect_custom(
+    env: &mut Env,
+    inspector: Expr,
+    inspector_var: Variable,
+    captured_symbol: Symbol,
+    captured_var: Variable,
+) -> (Expr, Variable) {
+    use Expr::*;
+
+    let fn_name = env.new_symbol("custom");
+
+    // fmt: fmt | fmt has InspectFormatter
+    let fmt_sym = env.new_symbol("fmt");
+    let fmt_var = env.subs.fresh_unnamed_flex_var();
+
+    // Inspect.apply inspector fmt
+    let (apply_call, applied_var) = call_builtin(
+        env,
+        Symbol::INSPECT_APPLY,
+        vec![(inspector_var, inspector), (fmt_var, Var(fmt_sym, fmt_var))],
+        |fn_var| Var(Symbol::INSPECT_APPLY, fn_var),
+    );
+
+    // Create fn_var for ambient capture; we fix it up below.
+    let fn_var = synth_var(env.subs, Content::Error);
+
+    // -[[fn_name captured_var]]->
+    let fn_name_labels =
+        UnionLambdas::insert_into_subs(env.subs, once((fn_name, vec![captured_var])));
+    let fn_clos_var = synth_var(
+        env.subs,
+        Content::LambdaSet(LambdaSet {
+            solved: fn_name_labels,
+            recursion_var: OptVariable::NONE,
+            unspecialized: SubsSlice::default(),
+            ambient_function: fn_var,
+        }),
+    );
+
+    // fmt -[[fn_name captured_var]]-> Inspect.apply inspector fmt
+    let args_slice = SubsSlice::insert_into_subs(env.subs, [fmt_var]);
+    env.subs.set_content(
+        fn_var,
+        Content::Structure(FlatType::Func(args_slice, fn_clos_var, applied_var)),
+    );
+
+    // \fmt -[[fn_name captured_var]]-> Inspect.apply inspector fmt
+    let clos = Closure(ClosureData {
+        function_type: fn_var,
+        closure_type: fn_clos_var,
+        return_type: applied_var,
+        name: fn_name,
+        captured_symbols: vec![(captured_symbol, captured_var)],
+        recursive: Recursive::NotRecursive,
+        arguments: vec![(
+            fmt_var,
+            AnnotatedMark::known_exhaustive(),
+            Loc::at_zero(Pattern::Identifier(fmt_sym)),
+        )],
+        loc_body: Box::new(Loc::at_zero(apply_call)),
+    });
+
+    // Inspect.custom \fmt -> Inspect.apply inspector fmt
+    call_builtin(
+        env,
+        Symbol::INSPECT_CUSTOM,
+        vec![(fn_var, clos)],
+        |fn_var| Var(Symbol::INSPECT_CUSTOM, fn_var),
+    )
+}
+
+/// Builds the derived `\arg -[fn_name]-> body`.
+fn build_outer_derived_closure(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    arg: (Variable, Symbol),
+    body: (Variable, Expr),
+) -> (Expr, Variable) {
+    let (arg_var, arg_sym) = arg;
+    let (body_var, body_expr) = body;
+
+    // Create fn_var for ambient capture; we fix it up below.
+    let fn_var = synth_var(env.subs, Content::Error);
+
+    // -[fn_name]->
+    let fn_name_labels = UnionLambdas::insert_into_subs(env.subs, once((fn_name, vec![])));
+    let fn_clos_var = synth_var(
+        env.subs,
+        Content::LambdaSet(LambdaSet {
+            solved: fn_name_labels,
+            recursion_var: OptVariable::NONE,
+            unspecialized: SubsSlice::default(),
+            ambient_function: fn_var,
+        }),
+    );
+    // typeof arg -[fn_name]-> Inspector fmt
+    let arg_var_slice = SubsSlice::insert_into_subs(env.subs, once(arg_var));
+    env.subs.set_content(
+        fn_var,
+        Content::Structure(FlatType::Func(arg_var_slice, fn_clos_var, body_var)),
+    );
+
+    // \arg -[fn_name]-> body
+    let clos = Expr::Closure(ClosureData {
+        function_type: fn_var,
+        closure_type: fn_clos_var,
+        return_type: body_var,
+        name: fn_name,
+        captured_symbols: vec![],
+        recursive: Recursive::NotRecursive,
+        arguments: vec![(
+            arg_var,
+            AnnotatedMark::known_exhaustive(),
+            Loc::at_zero(Pattern::Identifier(arg_sym)),
+        )],
+        loc_body: Box::new(Loc::at_zero(body_expr)),
+    });
+
+    (clos, fn_var)
+}
@@ -16,9 +16,12 @@ use roc_types::subs::{
 };
 use util::Env;
 
+mod arbitrary;
 mod decoding;
 mod encoding;
 mod hash;
+mod inspect;
+mod sort;
 
 mod util;
 
@@ -79,6 +82,15 @@ fn build_derived_body(
             decoding::derive_decoder(&mut env, decoder_key, derived_symbol)
         }
         DeriveKey::Hash(hash_key) => hash::derive_hash(&mut env, hash_key, derived_symbol),
+        DeriveKey::Arbitrary(arbitrary_key) => {
+            arbitrary::derive_arbitrary(&mut env, arbitrary_key, derived_symbol)
+        }
+        DeriveKey::ToInspector(to_inspector_key) => {
+            inspect::derive_to_inspector(&mut env, to_inspector_key, derived_symbol)
+        }
+        DeriveKey::Compare(compare_key) => {
+            sort::derive_compare(&mut env, compare_key, derived_symbol)
+        }
     };
 
     let def = Def {
@@ -0,0 +1,578 @@
+//! Derivers for the `Sort` ability.
+
+use std::iter::once;
+
+use roc_can::{
+    expr::{AnnotatedMark, ClosureData, Expr, Recursive, WhenBranch, WhenBranchPattern},
+    pattern::Pattern,
+};
+use roc_derive_key::sort::FlatSortKey;
+use roc_module::{
+    called_via::CalledVia,
+    ident::{Lowercase, TagName},
+    symbol::Symbol,
+};
+use roc_region::all::{Loc, Region};
+use roc_types::{
+    subs::{
+        Content, ExhaustiveMark, FlatType, GetSubsSlice, LambdaSet, OptVariable, RecordFields,
+        RedundantMark, SubsSlice, TagExt, TupleElems, UnionLambdas, UnionTags, Variable,
+        VariableSubsSlice,
+    },
+    types::RecordField,
+};
+
+use crate::{synth_var, util::Env, DerivedBody};
+
+pub(crate) fn derive_compare(
+    env: &mut Env<'_>,
+    key: FlatSortKey,
+    def_symbol: Symbol,
+) -> DerivedBody {
+    let (body_type, body) = match key {
+        FlatSortKey::Record(fields) => compare_record(env, def_symbol, fields),
+        FlatSortKey::Tuple(arity) => compare_tuple(env, def_symbol, arity),
+        FlatSortKey::TagUnion(tags) => compare_tag_union(env, def_symbol, tags),
+    };
+
+    let specialization_lambda_sets =
+        env.get_specialization_lambda_sets(body_type, Symbol::SORT_COMPARE);
+
+    DerivedBody {
+        body,
+        body_type,
+        specialization_lambda_sets,
+    }
+}
+
+/// A pair of values of the same type to compare with `Sort.compare`.
+type Comparison = (Variable, Expr, Expr);
+
+fn compare_record(env: &mut Env<'_>, fn_name: Symbol, fields: Vec<Lowercase>) -> (Variable, Expr) {
+    // Suppose rcd = { f1, ..., fn }.
+    // Build a generalized type t_rcd = { f1: t1, ..., fn: tn }, with fresh t1, ..., tn,
+    // so that we can re-use the derived impl for many records of the same fields.
+    let (record_var, record_fields) = {
+        let flex_fields = fields
+            .into_iter()
+            .map(|name| {
+                (
+                    name,
+                    RecordField::Required(env.subs.fresh_unnamed_flex_var()),
+                )
+            })
+            .collect::<Vec<(Lowercase, _)>>();
+        let fields = RecordFields::insert_into_subs(env.subs, flex_fields);
+        let record_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+        );
+
+        (record_var, fields)
+    };
+
+    // Now, a comparison for this record is
+    //
+    // compare_rcd : { f1: t1, ..., fn: tn }, { f1: t1, ..., fn: tn } -> [LT, EQ, GT]
+    // compare_rcd = \lhs, rhs ->
+    //   when Sort.compare lhs.f1 rhs.f1 is
+    //     EQ ->
+    //       ...
+    //         Sort.compare lhs.fn rhs.fn
+    //     order -> order
+    //
+    // The fields are sorted by name, so they are always compared in the same order.
+    let lhs_sym = env.new_symbol("lhs");
+    let rhs_sym = env.new_symbol("rhs");
+
+    let comparisons = record_fields
+        .iter_all()
+        .map(|(field_name, field_var, _)| {
+            let field_name = env.subs[field_name].clone();
+            let field_var = env.subs[field_var];
+
+            let mut access = |sym| Expr::RecordAccess {
+                record_var,
+                field_var,
+                ext_var: env.subs.fresh_unnamed_flex_var(),
+                loc_expr: Box::new(Loc::at_zero(Expr::Var(
+                    sym,
+                    env.subs.fresh_unnamed_flex_var(),
+                ))),
+                field: field_name.clone(),
+            };
+
+            (field_var, access(lhs_sym), access(rhs_sym))
+        })
+        .collect();
+
+    let (body_var, body) = compare_lexicographically(env, comparisons);
+
+    // Finally, build the closure
+    // \lhs, rhs -> body
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        (record_var, Pattern::Identifier(lhs_sym)),
+        (record_var, Pattern::Identifier(rhs_sym)),
+        (body_var, body),
+    )
+}
+
+fn compare_tuple(env: &mut Env<'_>, fn_name: Symbol, arity: u32) -> (Variable, Expr) {
+    // Suppose tup = (v1, ..., vn).
+    // Build a generalized type t_tup = (t1, ..., tn), with fresh t1, ..., tn,
+    // so that we can re-use the derived impl for many tuples of the same arity.
+    let (tuple_var, tuple_elems) = {
+        let flex_elems: Vec<_> = (0..arity)
+            .map(|i| (i as usize, env.subs.fresh_unnamed_flex_var()))
+            .collect();
+        let elems = TupleElems::insert_into_subs(env.subs, flex_elems);
+        let tuple_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Tuple(elems, Variable::EMPTY_TUPLE)),
+        );
+
+        (tuple_var, elems)
+    };
+
+    // Now, a comparison for this tuple is
+    //
+    // compare_tup : (t1, ..., tn), (t1, ..., tn) -> [LT, EQ, GT]
+    // compare_tup = \lhs, rhs ->
+    //   when Sort.compare lhs.0 rhs.0 is
+    //     EQ ->
+    //       ...
+    //         Sort.compare lhs.n rhs.n
+    //     order -> order
+    let lhs_sym = env.new_symbol("lhs");
+    let rhs_sym = env.new_symbol("rhs");
+
+    let comparisons = tuple_elems
+        .iter_all()
+        .map(|(elem_idx, elem_var)| {
+            let index = env.subs[elem_idx];
+            let elem_var = env.subs[elem_var];
+
+            let mut access = |sym| Expr::TupleAccess {
+                tuple_var,
+                elem_var,
+                ext_var: env.subs.fresh_unnamed_flex_var(),
+                loc_expr: Box::new(Loc::at_zero(Expr::Var(
+                    sym,
+                    env.subs.fresh_unnamed_flex_var(),
+                ))),
+                index,
+            };
+
+            (elem_var, access(lhs_sym), access(rhs_sym))
+        })
+        .collect();
+
+    let (body_var, body) = compare_lexicographically(env, comparisons);
+
+    // Finally, build the closure
+    // \lhs, rhs -> body
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        (tuple_var, Pattern::Identifier(lhs_sym)),
+        (tuple_var, Pattern::Identifier(rhs_sym)),
+        (body_var, body),
+    )
+}
+
+fn compare_tag_union(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    tags: Vec<(TagName, u16)>,
+) -> (Variable, Expr) {
+    // Suppose tags = [ A p11 .. p1n, ..., Q pq1 .. pqm ]
+    // Build a generalized type t_tags = [ A t11 .. t1n, ..., Q tq1 .. tqm ],
+    // with fresh t1, ..., tqm, so that we can re-use the derived impl for many
+    // unions of the same tags and payloads.
+    let (union_var, union_tags) = {
+        let flex_tag_labels = tags
+            .into_iter()
+            .map(|(label, arity)| {
+                let variables_slice = VariableSubsSlice::reserve_into_subs(env.subs, arity.into());
+                for var_index in variables_slice {
+                    env.subs[var_index] = env.subs.fresh_unnamed_flex_var();
+                }
+                (label, variables_slice)
+            })
+            .collect::<Vec<_>>();
+        let union_tags = UnionTags::insert_slices_into_subs(env.subs, flex_tag_labels);
+        let tag_union_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::TagUnion(
+                union_tags,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        );
+
+        (tag_union_var, union_tags)
+    };
+
+    // Now, a comparison for this tag union is
+    //
+    // compare_union : [ A t11 .. t1n, ..., Q tq1 .. tqm ], [ A t11 .. t1n, ..., Q tq1 .. tqm ] -> [LT, EQ, GT]
+    // compare_union = \lhs, rhs ->
+    //   when lhs is
+    //     A x11 .. x1n ->
+    //       when rhs is
+    //         A y11 .. y1n -> <compare x11 .. x1n against y11 .. y1n>
+    //         B .. | ... | Q .. -> LT
+    //     ...
+    //     Q xq1 .. xqm ->
+    //       when rhs is
+    //         Q yq1 .. yqm -> <compare xq1 .. xqm against yq1 .. yqm>
+    //         A .. | ... | P .. -> GT
+    //
+    // The tags are sorted by name, so a tag is smaller than all tags after it.
+    let lhs_sym = env.new_symbol("lhs");
+    let rhs_sym = env.new_symbol("rhs");
+
+    let tags: Vec<(TagName, Vec<Variable>)> = union_tags
+        .iter_all()
+        .map(|(tag, payloads)| {
+            (
+                env.subs[tag].clone(),
+                env.subs.get_subs_slice(env.subs[payloads]).to_vec(),
+            )
+        })
+        .collect();
+
+    let whole_order_var = order_var(env);
+    let branches = tags
+        .iter()
+        .enumerate()
+        .map(|(i, (tag_name, payload_vars))| {
+            // x11 .. x1n
+            let lhs_payload_syms: Vec<_> = std::iter::repeat_with(|| env.unique_symbol())
+                .take(payload_vars.len())
+                .collect();
+            // y11 .. y1n
+            let rhs_payload_syms: Vec<_> = std::iter::repeat_with(|| env.unique_symbol())
+                .take(payload_vars.len())
+                .collect();
+
+            // `A y11 .. y1n -> <compare payloads>`
+            let comparisons = payload_vars
+                .iter()
+                .zip(lhs_payload_syms.iter().zip(rhs_payload_syms.iter()))
+                .map(|(&var, (&lhs, &rhs))| (var, Expr::Var(lhs, var), Expr::Var(rhs, var)))
+                .collect();
+            let (same_tag_var, same_tag_expr) = compare_lexicographically(env, comparisons);
+            env.unify(whole_order_var, same_tag_var);
+
+            let mut inner_branches = vec![when_branch(
+                vec![tag_pattern(
+                    union_var,
+                    tag_name.clone(),
+                    payload_vars,
+                    Some(&rhs_payload_syms),
+                )],
+                same_tag_expr,
+            )];
+
+            // Tags before this one are smaller, so `lhs` is greater than them.
+            if i > 0 {
+                let patterns = tags[..i]
+                    .iter()
+                    .map(|(other, vars)| tag_pattern(union_var, other.clone(), vars, None))
+                    .collect();
+                inner_branches.push(when_branch(patterns, order_tag(env, whole_order_var, "GT")));
+            }
+
+            // Tags after this one are larger, so `lhs` is less than them.
+            if i + 1 < tags.len() {
+                let patterns = tags[i + 1..]
+                    .iter()
+                    .map(|(other, vars)| tag_pattern(union_var, other.clone(), vars, None))
+                    .collect();
+                inner_branches.push(when_branch(patterns, order_tag(env, whole_order_var, "LT")));
+            }
+
+            // when rhs is
+            //   ...
+            let inner_when = Expr::When {
+                loc_cond: Box::new(Loc::at_zero(Expr::Var(rhs_sym, union_var))),
+                cond_var: union_var,
+                expr_var: whole_order_var,
+                region: Region::zero(),
+                branches: inner_branches,
+                branches_cond_var: union_var,
+                exhaustive: ExhaustiveMark::known_exhaustive(),
+            };
+
+            when_branch(
+                vec![tag_pattern(
+                    union_var,
+                    tag_name.clone(),
+                    payload_vars,
+                    Some(&lhs_payload_syms),
+                )],
+                inner_when,
+            )
+        })
+        .collect();
+
+    // when lhs is
+    //   ...
+    let when_expr = Expr::When {
+        loc_cond: Box::new(Loc::at_zero(Expr::Var(lhs_sym, union_var))),
+        cond_var: union_var,
+        expr_var: whole_order_var,
+        region: Region::zero(),
+        branches,
+        branches_cond_var: union_var,
+        exhaustive: ExhaustiveMark::known_exhaustive(),
+    };
+
+    // Finally, build the closure
+    // \lhs, rhs -> body
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        (union_var, Pattern::Identifier(lhs_sym)),
+        (union_var, Pattern::Identifier(rhs_sym)),
+        (whole_order_var, when_expr),
+    )
+}
+
+/// Builds
+///
+/// ```text
+/// when Sort.compare l1 r1 is
+///   EQ ->
+///     when Sort.compare l2 r2 is
+///       EQ -> ... Sort.compare ln rn
+///       order -> order
+///   order -> order
+/// ```
+///
+/// or just `EQ` if there is nothing to compare.
+fn compare_lexicographically(env: &mut Env<'_>, comparisons: Vec<Comparison>) -> (Variable, Expr) {
+    let whole_order_var = order_var(env);
+
+    let mut comparisons = comparisons.into_iter().rev();
+    let last = match comparisons.next() {
+        Some((var, lhs, rhs)) => {
+            let (last_var, last) = call_sort_compare(env, var, lhs, rhs);
+            env.unify(whole_order_var, last_var);
+            last
+        }
+        None => return (whole_order_var, order_tag(env, whole_order_var, "EQ")),
+    };
+
+    let body = comparisons.fold(last, |rest, (var, lhs, rhs)| {
+        let (compared_var, compared) = call_sort_compare(env, var, lhs, rhs);
+        env.unify(whole_order_var, compared_var);
+
+        let order_sym = env.new_symbol("order");
+
+        // EQ -> rest
+        let eq_branch = when_branch(
+            vec![Pattern::AppliedTag {
+                whole_var: whole_order_var,
+                tag_name: TagName("EQ".into()),
+                ext_var: Variable::EMPTY_TAG_UNION,
+                arguments: vec![],
+            }],
+            rest,
+        );
+        // order -> order
+        let other_branch = when_branch(
+            vec![Pattern::Identifier(order_sym)],
+            Expr::Var(order_sym, whole_order_var),
+        );
+
+        Expr::When {
+            loc_cond: Box::new(Loc::at_zero(compared)),
+            cond_var: whole_order_var,
+            expr_var: whole_order_var,
+            region: Region::zero(),
+            branches: vec![eq_branch, other_branch],
+            branches_cond_var: whole_order_var,
+            exhaustive: ExhaustiveMark::known_exhaustive(),
+        }
+    });
+
+    (whole_order_var, body)
+}
+
+/// A fresh `[EQ, GT, LT]`, the result of `Sort.compare`.
+fn order_var(env: &mut Env<'_>) -> Variable {
+    let order_tags = UnionTags::insert_into_subs(
+        env.subs,
+        ["EQ", "GT", "LT"]
+            .into_iter()
+            .map(|name| (TagName(name.into()), vec![])),
+    );
+
+    synth_var(
+        env.subs,
+        Content::Structure(FlatType::TagUnion(
+            order_tags,
+            TagExt::Any(Variable::EMPTY_TAG_UNION),
+        )),
+    )
+}
+
+fn order_tag(env: &mut Env<'_>, order_var: Variable, name: &str) -> Expr {
+    Expr::Tag {
+        tag_union_var: order_var,
+        ext_var: env.subs.fresh_unnamed_flex_var(),
+        name: TagName(name.into()),
+        arguments: vec![],
+    }
+}
+
+/// `A x1 .. xn`, or `A _ .. _` if no payload symbols are given.
+fn tag_pattern(
+    union_var: Variable,
+    tag_name: TagName,
+    payload_vars: &[Variable],
+    payload_syms: Option<&[Symbol]>,
+) -> Pattern {
+    let arguments = payload_vars
+        .iter()
+        .enumerate()
+        .map(|(i, var)| {
+            let pattern = match payload_syms {
+                Some(syms) => Pattern::Identifier(syms[i]),
+                None => Pattern::Underscore,
+            };
+            (*var, Loc::at_zero(pattern))
+        })
+        .collect();
+
+    Pattern::AppliedTag {
+        whole_var: union_var,
+        tag_name,
+        ext_var: Variable::EMPTY_TAG_UNION,
+        arguments,
+    }
+}
+
+fn when_branch(patterns: Vec<Pattern>, value: Expr) -> WhenBranch {
+    WhenBranch {
+        patterns: patterns
+            .into_iter()
+            .map(|pattern| WhenBranchPattern {
+                pattern: Loc::at_zero(pattern),
+                degenerate: false,
+            })
+            .collect(),
+        value: Loc::at_zero(value),
+        guard: None,
+        redundant: RedundantMark::known_non_redundant(),
+    }
+}
+
+fn call_sort_compare(
+    env: &mut Env<'_>,
+    val_var: Variable,
+    lhs: Expr,
+    rhs: Expr,
+) -> (Variable, Expr) {
+    // build `Sort.compare` function type.
+    //
+    // val, val -[uls]-> [LT, EQ, GT] | val has Sort
+    let exposed_compare_fn_var = env.import_builtin_symbol_var(Symbol::SORT_COMPARE);
+
+    // (typeof field), (typeof field) -[clos]-> order
+    let this_arguments_slice = VariableSubsSlice::insert_into_subs(env.subs, [val_var, val_var]);
+    let this_compare_clos_var = env.subs.fresh_unnamed_flex_var();
+    let this_order_var = env.subs.fresh_unnamed_flex_var();
+    let this_compare_fn_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::Func(
+            this_arguments_slice,
+            this_compare_clos_var,
+            this_order_var,
+        )),
+    );
+
+    //   val,            val            -[uls]->  [LT, EQ, GT] | val has Sort
+    // ~ (typeof field), (typeof field) -[clos]-> order
+    env.unify(exposed_compare_fn_var, this_compare_fn_var);
+
+    // Sort.compare : (typeof field), (typeof field) -[clos]-> [LT, EQ, GT] | (typeof field) has Sort
+    let compare_fn_head = Expr::AbilityMember(Symbol::SORT_COMPARE, None, this_compare_fn_var);
+    let compare_fn_data = Box::new((
+        this_compare_fn_var,
+        Loc::at_zero(compare_fn_head),
+        this_compare_clos_var,
+        this_order_var,
+    ));
+
+    let compare_arguments = vec![(val_var, Loc::at_zero(lhs)), (val_var, Loc::at_zero(rhs))];
+    let call_compare = Expr::Call(compare_fn_data, compare_arguments, CalledVia::Space);
+
+    (this_order_var, call_compare)
+}
+
+fn build_outer_derived_closure(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    lhs: (Variable, Pattern),
+    rhs: (Variable, Pattern),
+    body: (Variable, Expr),
+) -> (Variable, Expr) {
+    let (lhs_var, lhs_pattern) = lhs;
+    let (rhs_var, rhs_pattern) = rhs;
+    let (body_var, body_expr) = body;
+
+    let (fn_var, fn_clos_var) = {
+        // Create fn_var for ambient capture; we fix it up below.
+        let fn_var = synth_var(env.subs, Content::Error);
+
+        // -[fn_name]->
+        let fn_captures = vec![];
+        let fn_name_labels = UnionLambdas::insert_into_subs(env.subs, once((fn_name, fn_captures)));
+        let fn_clos_var = synth_var(
+            env.subs,
+            Content::LambdaSet(LambdaSet {
+                solved: fn_name_labels,
+                recursion_var: OptVariable::NONE,
+                unspecialized: SubsSlice::default(),
+                ambient_function: fn_var,
+            }),
+        );
+
+        // lhs_var, rhs_var -[fn_name]-> body_var
+        let args_slice = SubsSlice::insert_into_subs(env.subs, [lhs_var, rhs_var]);
+        env.subs.set_content(
+            fn_var,
+            Content::Structure(FlatType::Func(args_slice, fn_clos_var, body_var)),
+        );
+
+        (fn_var, fn_clos_var)
+    };
+
+    let clos_expr = Expr::Closure(ClosureData {
+        function_type: fn_var,
+        closure_type: fn_clos_var,
+        return_type: body_var,
+        name: fn_name,
+        captured_symbols: vec![],
+        recursive: Recursive::NotRecursive,
+        arguments: vec![
+            (
+                lhs_var,
+                AnnotatedMark::known_exhaustive(),
+                Loc::at_zero(lhs_pattern),
+            ),
+            (
+                rhs_var,
+                AnnotatedMark::known_exhaustive(),
+                Loc::at_zero(rhs_pattern),
+            ),
+        ],
+        loc_body: Box::new(Loc::at_zero(body_expr)),
+    });
+
+    (fn_var, clos_expr)
+}
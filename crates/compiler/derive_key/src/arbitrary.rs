@@ -0,0 +1,217 @@
+use roc_module::{
+    ident::{Lowercase, TagName},
+    symbol::Symbol,
+};
+use roc_types::subs::{Content, FlatType, GetSubsSlice, Subs, Variable};
+
+use crate::{
+    util::{check_derivable_ext_var, debug_name_record, debug_name_tag, debug_name_tuple},
+    DeriveError,
+};
+
+#[derive(Hash)]
+pub enum FlatArbitrary {
+    // `arbitrary` is always of form `Source -> Generated a`, where `Source` is opaque and
+    // `Generated a` holds no functions, so all immediates must have exactly one lambda set!
+    SingleLambdaSetImmediate(Symbol),
+    Key(FlatArbitraryKey),
+}
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub enum FlatArbitraryKey {
+    // Unfortunate that we must allocate here, c'est la vie
+    Record(Vec<Lowercase>),
+    Tuple(u32),
+    TagUnion(Vec<(TagName, u16)>),
+}
+
+impl FlatArbitraryKey {
+    pub(crate) fn debug_name(&self) -> String {
+        match self {
+            FlatArbitraryKey::Record(fields) => debug_name_record(fields),
+            FlatArbitraryKey::Tuple(arity) => debug_name_tuple(*arity),
+            FlatArbitraryKey::TagUnion(tags) => debug_name_tag(tags),
+        }
+    }
+}
+
+impl FlatArbitrary {
+    pub(crate) fn from_var(subs: &Subs, var: Variable) -> Result<FlatArbitrary, DeriveError> {
+        use DeriveError::*;
+        use FlatArbitrary::*;
+        match *subs.get_content_without_compacting(var) {
+            Content::Structure(flat_type) => match flat_type {
+                FlatType::Apply(sym, _) => match sym {
+                    Symbol::LIST_LIST => Ok(SingleLambdaSetImmediate(Symbol::ARBITRARY_LIST)),
+                    Symbol::STR_STR => Ok(SingleLambdaSetImmediate(Symbol::ARBITRARY_STR)),
+                    _ => Err(Underivable),
+                },
+                FlatType::Record(fields, ext) => {
+                    let (fields_iter, ext) = fields.unsorted_iterator_and_ext(subs, ext);
+
+                    check_derivable_ext_var(subs, ext, |ext| {
+                        matches!(ext, Content::Structure(FlatType::EmptyRecord))
+                    })?;
+
+                    let mut field_names = Vec::with_capacity(fields.len());
+                    for (field_name, record_field) in fields_iter {
+                        if record_field.is_optional() {
+                            // Can't derive a concrete generator for optional fields, since those are
+                            // compile-time-polymorphic
+                            return Err(Underivable);
+                        }
+                        field_names.push(field_name.clone());
+                    }
+
+                    field_names.sort();
+
+                    Ok(Key(FlatArbitraryKey::Record(field_names)))
+                }
+                FlatType::Tuple(elems, ext) => {
+                    let (elems_iter, ext) = elems.sorted_iterator_and_ext(subs, ext);
+
+                    check_derivable_ext_var(subs, ext, |ext| {
+                        matches!(ext, Content::Structure(FlatType::EmptyTuple))
+                    })?;
+
+                    Ok(Key(FlatArbitraryKey::Tuple(elems_iter.count() as _)))
+                }
+                FlatType::TagUnion(tags, ext) | FlatType::RecursiveTagUnion(_, tags, ext) => {
+                    // The recursion var doesn't matter, because the derived implementation will only
+                    // look on the surface of the tag union type, and more over the payloads of the
+                    // arguments will be left generic for the monomorphizer to fill in with the
+                    // appropriate type. That is,
+                    //   [ A t1, B t1 t2 ]
+                    // and
+                    //   [ A t1, B t1 t2 ] as R
+                    // look the same on the surface, because `R` is only somewhere inside of the
+                    // `t`-prefixed payload types.
+                    let (tags_iter, ext) = tags.unsorted_tags_and_ext(subs, ext);
+
+                    check_derivable_ext_var(subs, ext.var(), |ext| {
+                        matches!(ext, Content::Structure(FlatType::EmptyTagUnion))
+                    })?;
+
+                    let mut tag_names_and_payload_sizes: Vec<(TagName, u16)> = tags_iter
+                        .tags
+                        .into_iter()
+                        .map(|(name, payload_slice)| {
+                            let payload_size = payload_slice.len();
+                            (name.clone(), payload_size as _)
+                        })
+                        .collect();
+
+                    // Tags with fewer payloads come first, so that the choice `0` (which is what
+                    // every choice shrinks toward, and what is drawn once a source runs out)
+                    // picks a tag that ends recursion as soon as possible.
+                    tag_names_and_payload_sizes
+                        .sort_by(|(t1, a1), (t2, a2)| a1.cmp(a2).then_with(|| t1.cmp(t2)));
+
+                    Ok(Key(FlatArbitraryKey::TagUnion(tag_names_and_payload_sizes)))
+                }
+                FlatType::FunctionOrTagUnion(names_index, _, _) => {
+                    let mut tag_names: Vec<_> = subs
+                        .get_subs_slice(names_index)
+                        .iter()
+                        .map(|t| (t.clone(), 0))
+                        .collect();
+
+                    tag_names.sort();
+
+                    Ok(Key(FlatArbitraryKey::TagUnion(tag_names)))
+                }
+                FlatType::EmptyRecord => Ok(Key(FlatArbitraryKey::Record(vec![]))),
+                FlatType::EmptyTuple => Ok(Key(FlatArbitraryKey::Tuple(0))),
+                FlatType::EmptyTagUnion => Ok(Key(FlatArbitraryKey::TagUnion(vec![]))),
+                //
+                FlatType::Func(..) => Err(Underivable),
+            },
+            Content::Alias(sym, _, real_var, _) => match builtin_symbol_to_arbitrary_lambda(sym) {
+                Some(lambda) => Ok(lambda),
+                // NB: I believe it is okay to unwrap opaques here because derivers are only used
+                // by the backend, and the backend treats opaques like structural aliases.
+                None => Self::from_var(subs, real_var),
+            },
+            Content::RangedNumber(range) => {
+                // Find the integer we're going to compile to, that'll tell us what lambda we
+                // should resolve to.
+                //
+                // Note that at this point, we don't need to update the underlying type variable.
+                // That's because
+                //
+                //   - If the type variable always had a ground constructor after solving, we would
+                //     have already refined the ranged number during obligation checking.
+                //
+                //   - If the type variable was generalized, then this branch is only reached
+                //     during monomorphization, at which point we always choose a default layout
+                //     for ranged numbers, without concern for reification to a ground type.
+                let chosen_width = range.default_compilation_width();
+                let lambda = builtin_symbol_to_arbitrary_lambda(chosen_width.symbol()).unwrap();
+                Ok(lambda)
+            }
+            //
+            Content::RecursionVar { structure, .. } => Self::from_var(subs, structure),
+            //
+            Content::Error => Err(Underivable),
+            Content::FlexVar(_)
+            | Content::RigidVar(_)
+            | Content::FlexAbleVar(_, _)
+            | Content::RigidAbleVar(_, _) => Err(UnboundVar),
+            Content::LambdaSet(_) => Err(Underivable),
+        }
+    }
+
+    pub fn from_builtin_symbol(symbol: Symbol) -> Result<FlatArbitrary, DeriveError> {
+        builtin_symbol_to_arbitrary_lambda(symbol).ok_or(DeriveError::Underivable)
+    }
+}
+
+const fn builtin_symbol_to_arbitrary_lambda(symbol: Symbol) -> Option<FlatArbitrary> {
+    use FlatArbitrary::*;
+    match symbol {
+        Symbol::BOOL_BOOL => Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_BOOL)),
+        Symbol::NUM_U8 | Symbol::NUM_UNSIGNED8 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_U8))
+        }
+        Symbol::NUM_U16 | Symbol::NUM_UNSIGNED16 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_U16))
+        }
+        Symbol::NUM_U32 | Symbol::NUM_UNSIGNED32 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_U32))
+        }
+        Symbol::NUM_U64 | Symbol::NUM_UNSIGNED64 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_U64))
+        }
+        Symbol::NUM_U128 | Symbol::NUM_UNSIGNED128 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_U128))
+        }
+        Symbol::NUM_I8 | Symbol::NUM_SIGNED8 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_I8))
+        }
+        Symbol::NUM_I16 | Symbol::NUM_SIGNED16 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_I16))
+        }
+        Symbol::NUM_I32 | Symbol::NUM_SIGNED32 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_I32))
+        }
+        Symbol::NUM_I64 | Symbol::NUM_SIGNED64 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_I64))
+        }
+        Symbol::NUM_I128 | Symbol::NUM_SIGNED128 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_I128))
+        }
+        Symbol::NUM_NAT | Symbol::NUM_NATURAL => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_NAT))
+        }
+        Symbol::NUM_F32 | Symbol::NUM_BINARY32 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_F32))
+        }
+        Symbol::NUM_F64 | Symbol::NUM_BINARY64 => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_F64))
+        }
+        Symbol::NUM_DEC | Symbol::NUM_DECIMAL => {
+            Some(SingleLambdaSetImmediate(Symbol::ARBITRARY_DEC))
+        }
+        _ => None,
+    }
+}
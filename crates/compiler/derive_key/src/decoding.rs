@@ -1,8 +1,11 @@
-use roc_module::{ident::Lowercase, symbol::Symbol};
-use roc_types::subs::{Content, FlatType, Subs, Variable};
+use roc_module::{
+    ident::{Lowercase, TagName},
+    symbol::Symbol,
+};
+use roc_types::subs::{Content, FlatType, GetSubsSlice, Subs, Variable};
 
 use crate::{
-    util::{check_derivable_ext_var, debug_name_record, debug_name_tuple},
+    util::{check_derivable_ext_var, debug_name_record, debug_name_tag, debug_name_tuple},
     DeriveError,
 };
 
@@ -15,18 +18,24 @@ pub enum FlatDecodable {
 #[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub enum FlatDecodableKey {
     List(/* takes one variable */),
+    Set(/* takes one variable */),
+    Dict(/* takes two variables */),
 
     // Unfortunate that we must allocate here, c'est la vie
     Record(Vec<Lowercase>),
     Tuple(u32),
+    TagUnion(Vec<(TagName, u16)>),
 }
 
 impl FlatDecodableKey {
     pub(crate) fn debug_name(&self) -> String {
         match self {
             FlatDecodableKey::List() => "list".to_string(),
+            FlatDecodableKey::Set() => "set".to_string(),
+            FlatDecodableKey::Dict() => "dict".to_string(),
             FlatDecodableKey::Record(fields) => debug_name_record(fields),
             FlatDecodableKey::Tuple(arity) => debug_name_tuple(*arity),
+            FlatDecodableKey::TagUnion(tags) => debug_name_tag(tags),
         }
     }
 }
@@ -39,6 +48,8 @@ impl FlatDecodable {
             Content::Structure(flat_type) => match flat_type {
                 FlatType::Apply(sym, _) => match sym {
                     Symbol::LIST_LIST => Ok(Key(FlatDecodableKey::List())),
+                    Symbol::SET_SET => Ok(Key(FlatDecodableKey::Set())),
+                    Symbol::DICT_DICT => Ok(Key(FlatDecodableKey::Dict())),
                     Symbol::STR_STR => Ok(Immediate(Symbol::DECODE_STRING)),
                     _ => Err(Underivable),
                 },
@@ -72,17 +83,40 @@ impl FlatDecodable {
 
                     Ok(Key(FlatDecodableKey::Tuple(elems_iter.count() as _)))
                 }
-                FlatType::TagUnion(_tags, _ext) | FlatType::RecursiveTagUnion(_, _tags, _ext) => {
-                    Err(Underivable) // yet
+                FlatType::TagUnion(tags, ext) | FlatType::RecursiveTagUnion(_, tags, ext) => {
+                    // As with encoding, the derived implementation only looks at the surface of
+                    // the tag union; the payloads are decoded with whatever decoders the
+                    // monomorphizer picks for their types, so the recursion var does not matter.
+                    let (tags_iter, ext) = tags.unsorted_tags_and_ext(subs, ext);
+
+                    check_derivable_ext_var(subs, ext.var(), |ext| {
+                        matches!(ext, Content::Structure(FlatType::EmptyTagUnion))
+                    })?;
+
+                    let mut tag_names_and_payload_sizes: Vec<_> = tags_iter
+                        .tags
+                        .into_iter()
+                        .map(|(name, payload_slice)| {
+                            let payload_size = payload_slice.len();
+                            (name.clone(), payload_size as _)
+                        })
+                        .collect();
+
+                    tag_names_and_payload_sizes.sort_by(|(t1, _), (t2, _)| t1.cmp(t2));
+
+                    Ok(Key(FlatDecodableKey::TagUnion(tag_names_and_payload_sizes)))
                 }
-                FlatType::FunctionOrTagUnion(_name_index, _, _) => {
-                    Err(Underivable) // yet
+                FlatType::FunctionOrTagUnion(names_index, _, _) => {
+                    Ok(Key(FlatDecodableKey::TagUnion(
+                        subs.get_subs_slice(names_index)
+                            .iter()
+                            .map(|t| (t.clone(), 0))
+                            .collect(),
+                    )))
                 }
                 FlatType::EmptyRecord => Ok(Key(FlatDecodableKey::Record(vec![]))),
                 FlatType::EmptyTuple => todo!(),
-                FlatType::EmptyTagUnion => {
-                    Err(Underivable) // yet
-                }
+                FlatType::EmptyTagUnion => Ok(Key(FlatDecodableKey::TagUnion(vec![]))),
                 //
                 FlatType::Func(..) => Err(Underivable),
             },
@@ -130,6 +164,8 @@ const fn from_builtin_symbol(symbol: Symbol) -> Option<Result<FlatDecodable, Der
         Symbol::NUM_F32 | Symbol::NUM_BINARY32 => Some(Ok(Immediate(Symbol::DECODE_F32))),
         Symbol::NUM_F64 | Symbol::NUM_BINARY64 => Some(Ok(Immediate(Symbol::DECODE_F64))),
         Symbol::NUM_NAT | Symbol::NUM_NATURAL => Some(Err(DeriveError::Underivable)),
+        Symbol::DICT_DICT => Some(Ok(Key(FlatDecodableKey::Dict()))),
+        Symbol::SET_SET => Some(Ok(Key(FlatDecodableKey::Set()))),
         _ => None,
     }
 }
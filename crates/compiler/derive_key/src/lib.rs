@@ -9,18 +9,30 @@
 //!   between e.g. required and optional record fields.
 //! - `Decoding` is like encoding, but has some differences. For one, it *does* need to distinguish
 //!   between required and optional record fields.
+//! - `Inspect` is like encoding, except that it never looks inside of opaque types that do not
+//!   implement it themselves.
+//! - `Arbitrary` keys tag unions like `Hash` does, but orders the tags by payload count rather than
+//!   by name, so that the first tag is the one least likely to recurse.
+//! - `Sort` keys like `Hash`; the order of record fields and tags in the key is also the order in
+//!   which they are compared.
 //!
 //! For these reasons the content keying is based on a strategy as well, which are the variants of
 //! [`DeriveKey`].
 
+pub mod arbitrary;
 pub mod decoding;
 pub mod encoding;
 pub mod hash;
+pub mod inspect;
+pub mod sort;
 mod util;
 
+use arbitrary::{FlatArbitrary, FlatArbitraryKey};
 use decoding::{FlatDecodable, FlatDecodableKey};
 use encoding::{FlatEncodable, FlatEncodableKey};
 use hash::{FlatHash, FlatHashKey};
+use inspect::{FlatInspectable, FlatInspectableKey};
+use sort::{FlatSort, FlatSortKey};
 
 use roc_module::symbol::Symbol;
 use roc_types::subs::{Subs, Variable};
@@ -40,6 +52,9 @@ pub enum DeriveKey {
     ToEncoder(FlatEncodableKey),
     Decoder(FlatDecodableKey),
     Hash(FlatHashKey),
+    Arbitrary(FlatArbitraryKey),
+    ToInspector(FlatInspectableKey),
+    Compare(FlatSortKey),
 }
 
 impl DeriveKey {
@@ -48,6 +63,9 @@ impl DeriveKey {
             DeriveKey::ToEncoder(key) => format!("toEncoder_{}", key.debug_name()),
             DeriveKey::Decoder(key) => format!("decoder_{}", key.debug_name()),
             DeriveKey::Hash(key) => format!("hash_{}", key.debug_name()),
+            DeriveKey::Arbitrary(key) => format!("arbitrary_{}", key.debug_name()),
+            DeriveKey::ToInspector(key) => format!("toInspector_{}", key.debug_name()),
+            DeriveKey::Compare(key) => format!("compare_{}", key.debug_name()),
         }
     }
 }
@@ -77,6 +95,9 @@ pub enum DeriveBuiltin {
     Decoder,
     Hash,
     IsEq,
+    Arbitrary,
+    ToInspector,
+    Compare,
 }
 
 impl TryFrom<Symbol> for DeriveBuiltin {
@@ -88,6 +109,9 @@ impl TryFrom<Symbol> for DeriveBuiltin {
             Symbol::DECODE_DECODER => Ok(DeriveBuiltin::Decoder),
             Symbol::HASH_HASH => Ok(DeriveBuiltin::Hash),
             Symbol::BOOL_IS_EQ => Ok(DeriveBuiltin::IsEq),
+            Symbol::ARBITRARY_ARBITRARY => Ok(DeriveBuiltin::Arbitrary),
+            Symbol::INSPECT_TO_INSPECTOR => Ok(DeriveBuiltin::ToInspector),
+            Symbol::SORT_COMPARE => Ok(DeriveBuiltin::Compare),
             _ => Err(value),
         }
     }
@@ -121,6 +145,22 @@ impl Derived {
                     Symbol::BOOL_STRUCTURAL_EQ,
                 ))
             }
+            DeriveBuiltin::Arbitrary => match arbitrary::FlatArbitrary::from_var(subs, var)? {
+                FlatArbitrary::SingleLambdaSetImmediate(imm) => {
+                    Ok(Derived::SingleLambdaSetImmediate(imm))
+                }
+                FlatArbitrary::Key(repr) => Ok(Derived::Key(DeriveKey::Arbitrary(repr))),
+            },
+            DeriveBuiltin::ToInspector => match inspect::FlatInspectable::from_var(subs, var)? {
+                FlatInspectable::Immediate(imm) => Ok(Derived::Immediate(imm)),
+                FlatInspectable::Key(repr) => Ok(Derived::Key(DeriveKey::ToInspector(repr))),
+            },
+            DeriveBuiltin::Compare => match sort::FlatSort::from_var(subs, var)? {
+                FlatSort::SingleLambdaSetImmediate(imm) => {
+                    Ok(Derived::SingleLambdaSetImmediate(imm))
+                }
+                FlatSort::Key(repr) => Ok(Derived::Key(DeriveKey::Compare(repr))),
+            },
         }
     }
 
@@ -151,6 +191,26 @@ impl Derived {
                     Symbol::BOOL_STRUCTURAL_EQ,
                 ))
             }
+            DeriveBuiltin::Arbitrary => {
+                match arbitrary::FlatArbitrary::from_builtin_symbol(symbol)? {
+                    FlatArbitrary::SingleLambdaSetImmediate(imm) => {
+                        Ok(Derived::SingleLambdaSetImmediate(imm))
+                    }
+                    FlatArbitrary::Key(repr) => Ok(Derived::Key(DeriveKey::Arbitrary(repr))),
+                }
+            }
+            DeriveBuiltin::ToInspector => {
+                match inspect::FlatInspectable::from_builtin_symbol(symbol)? {
+                    FlatInspectable::Immediate(imm) => Ok(Derived::Immediate(imm)),
+                    FlatInspectable::Key(repr) => Ok(Derived::Key(DeriveKey::ToInspector(repr))),
+                }
+            }
+            DeriveBuiltin::Compare => match sort::FlatSort::from_builtin_symbol(symbol)? {
+                FlatSort::SingleLambdaSetImmediate(imm) => {
+                    Ok(Derived::SingleLambdaSetImmediate(imm))
+                }
+                FlatSort::Key(repr) => Ok(Derived::Key(DeriveKey::Compare(repr))),
+            },
         }
     }
 }
@@ -143,6 +143,8 @@ pub enum Error {
         overall_region: Region,
         branch_region: Region,
         index: HumanIndex,
+        /// The earlier branches that, together, already handle everything this branch matches.
+        covered_by: Vec<HumanIndex>,
     },
     Unmatchable {
         overall_region: Region,
@@ -175,6 +177,11 @@ pub enum Guard {
     NoGuard,
 }
 
+/// The most missing patterns we will ever report for a single `when`. Nested unions and lists can
+/// be missing a combinatorial number of concrete patterns; past this point more examples stop
+/// being helpful.
+const MAX_MISSING_PATTERNS: usize = 16;
+
 /// Check
 
 pub fn check(
@@ -183,12 +190,18 @@ pub fn check(
     matrix: Vec<Vec<Pattern>>,
 ) -> Result<(), Vec<Error>> {
     let mut errors = Vec::new();
-    let bad_patterns = is_exhaustive(&matrix, 1);
+    let bad_patterns = is_exhaustive(&matrix, 1, MAX_MISSING_PATTERNS);
     if !bad_patterns.is_empty() {
         // TODO i suspect this is like a concat in in practice? code below can panic
         // if this debug_assert! ever fails, the theory is disproven
         debug_assert!(bad_patterns.iter().map(|v| v.len()).sum::<usize>() == bad_patterns.len());
-        let heads = bad_patterns.into_iter().map(|mut v| v.remove(0)).collect();
+        let mut heads: Vec<Pattern> = Vec::with_capacity(bad_patterns.len());
+        for mut row in bad_patterns {
+            let head = row.remove(0);
+            if !heads.contains(&head) {
+                heads.push(head);
+            }
+        }
         errors.push(Error::Incomplete(region, context, heads));
         return Err(errors);
     }
@@ -202,7 +215,13 @@ pub fn check(
 ///   The initial rows "matrix" are all of length 1
 ///   The initial count of items per row "n" is also 1
 ///   The resulting rows are examples of missing patterns
-fn is_exhaustive(matrix: &RefPatternMatrix, n: usize) -> PatternMatrix {
+///   At most "budget" rows are returned, and "budget" is never zero
+///
+/// The budget is passed down so that we stop exploring alternatives once we have enough
+/// examples, rather than building every missing pattern and throwing most of them away.
+fn is_exhaustive(matrix: &RefPatternMatrix, n: usize, budget: usize) -> PatternMatrix {
+    debug_assert!(budget > 0);
+
     let ctors = if matrix.is_empty() {
         return vec![std::iter::repeat(Anything).take(n).collect()];
     } else if n == 0 {
@@ -217,7 +236,7 @@ fn is_exhaustive(matrix: &RefPatternMatrix, n: usize) -> PatternMatrix {
                 .iter()
                 .filter_map(|row| specialize_row_by_anything(row))
                 .collect();
-            let mut rest = is_exhaustive(&new_matrix, n - 1);
+            let mut rest = is_exhaustive(&new_matrix, n - 1, budget);
 
             for row in rest.iter_mut() {
                 row.push(Anything);
@@ -234,65 +253,80 @@ fn is_exhaustive(matrix: &RefPatternMatrix, n: usize) -> PatternMatrix {
             let alt_list = &alts.alternatives;
             let num_alts = alt_list.len();
 
-            if num_seen < num_alts {
+            let is_alt_exhaustive = |Ctor { arity, tag_id, .. }, budget| {
                 let new_matrix: Vec<_> = matrix
                     .iter()
-                    .filter_map(|row| specialize_row_by_anything(row))
+                    .filter_map(|r| specialize_row_by_ctor(tag_id, arity, r.to_owned()))
                     .collect();
-                let rest: Vec<Vec<Pattern>> = is_exhaustive(&new_matrix, n - 1);
+                let rest: Vec<Vec<Pattern>> = is_exhaustive(&new_matrix, arity + n - 1, budget);
+
+                let mut result = Vec::with_capacity(rest.len());
+                for row in rest {
+                    result.push(recover_ctor(alts.clone(), tag_id, arity, row));
+                }
 
-                let last: _ = alt_list
+                result
+            };
+
+            // Constructors that are never matched are missing entirely (with whatever the
+            // wildcard rows leave uncovered), but the ones that are matched may still be missing
+            // some of their arguments, so we look inside those too to find concrete examples.
+            let rest_of_missing: Vec<Vec<Pattern>> = if num_seen < num_alts {
+                let new_matrix: Vec<_> = matrix
                     .iter()
-                    .filter_map(|r| is_missing(alts.clone(), &ctors, r));
+                    .filter_map(|row| specialize_row_by_anything(row))
+                    .collect();
 
-                let mut result = Vec::new();
+                is_exhaustive(&new_matrix, n - 1, budget)
+            } else {
+                Vec::new()
+            };
 
-                for last_option in last {
-                    for mut row in rest.clone() {
-                        row.push(last_option.clone());
+            let mut result = Vec::new();
 
-                        result.push(row);
-                    }
+            for ctor in alt_list {
+                let remaining = budget - result.len();
+                if remaining == 0 {
+                    break;
                 }
 
-                result
-            } else {
-                let is_alt_exhaustive = |Ctor { arity, tag_id, .. }| {
-                    let new_matrix: Vec<_> = matrix
-                        .iter()
-                        .filter_map(|r| specialize_row_by_ctor(tag_id, arity, r.to_owned()))
-                        .collect();
-                    let rest: Vec<Vec<Pattern>> = is_exhaustive(&new_matrix, arity + n - 1);
-
-                    let mut result = Vec::with_capacity(rest.len());
-                    for row in rest {
-                        result.push(recover_ctor(alts.clone(), tag_id, arity, row));
-                    }
-
-                    result
-                };
+                match is_missing(alts.clone(), &ctors, ctor) {
+                    Some(missing) => {
+                        for mut row in rest_of_missing.iter().take(remaining).cloned() {
+                            row.push(missing.clone());
 
-                alt_list
-                    .iter()
-                    .cloned()
-                    .flat_map(is_alt_exhaustive)
-                    .collect()
+                            result.push(row);
+                        }
+                    }
+                    None => result.extend(is_alt_exhaustive(ctor.clone(), remaining)),
+                }
             }
+
+            result
         }
         CollectedCtors::NonExhaustiveList(alt_lists) => {
-            let is_alt_exhaustive = |arity: ListArity| {
+            let mut result = Vec::new();
+
+            for arity in alt_lists {
+                let remaining = budget - result.len();
+                if remaining == 0 {
+                    break;
+                }
+
                 let new_matrix: Vec<_> = matrix
                     .iter()
                     .filter_map(|row| specialize_row_by_list(arity, row.to_owned()))
                     .collect();
 
-                let rest = is_exhaustive(&new_matrix, arity.min_len() + n - 1);
+                let rest = is_exhaustive(&new_matrix, arity.min_len() + n - 1, remaining);
 
-                rest.into_iter()
-                    .map(move |row_not_covered| recover_list(arity, row_not_covered))
-            };
+                result.extend(
+                    rest.into_iter()
+                        .map(|row_not_covered| recover_list(arity, row_not_covered)),
+                );
+            }
 
-            alt_lists.into_iter().flat_map(is_alt_exhaustive).collect()
+            result
         }
     }
 }
@@ -652,12 +686,16 @@ fn collect_ctors(matrix: &RefPatternMatrix) -> CollectedCtors {
         return CollectedCtors::NonExhaustiveAny;
     }
 
-    let first_row = &matrix[0];
+    // The kind of constructor is decided by the first row that actually inspects the head column;
+    // a leading wildcard must not hide the constructors matched by the rows below it.
+    let first_head = matrix
+        .iter()
+        .filter_map(|row| row.last())
+        .find(|pattern| !matches!(pattern, Anything));
 
-    if let Some(ctor) = first_row.last() {
+    if let Some(ctor) = first_head {
         match ctor {
-            Anything => CollectedCtors::NonExhaustiveAny,
-            Pattern::Literal(_) => CollectedCtors::NonExhaustiveAny,
+            Anything | Pattern::Literal(_) => CollectedCtors::NonExhaustiveAny,
             List(_, _) => {
                 let list_ctors = build_list_ctors_covering_patterns(
                     ListArity::ANY,
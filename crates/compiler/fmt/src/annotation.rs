@@ -217,10 +217,24 @@ impl<'a> Formattable for TypeAnnotation<'a> {
 
         let self_is_multiline = self.is_multiline();
 
+        // Records, tag unions and tuples check this themselves, in fmt_collection
+        let may_break = !matches!(self, Record { .. } | TagUnion { .. } | Tuple { .. })
+            && buf.take_break_permission();
+
         match self {
             Function(args, ret) => {
                 let needs_parens = parens != Parens::NotNeeded;
 
+                // Only a function type that starts its own line can put each argument on
+                // its own line.
+                let self_is_multiline = self_is_multiline
+                    || (may_break
+                        && !needs_parens
+                        && buf.is_at_start_of_line()
+                        && !buf.fits_on_line(|buf| {
+                            self.format_with_options(buf, parens, newlines, indent)
+                        }));
+
                 buf.indent(indent);
 
                 if needs_parens {
@@ -392,9 +406,15 @@ impl<'a> Formattable for TypeAnnotation<'a> {
             SpaceBefore(ann, spaces) => {
                 buf.ensure_ends_with_newline();
                 fmt_comments_only(buf, spaces.iter(), NewlineAt::Bottom, indent);
+                if may_break {
+                    buf.allow_break();
+                }
                 ann.format_with_options(buf, parens, newlines, indent)
             }
             SpaceAfter(ann, spaces) => {
+                if may_break {
+                    buf.allow_break();
+                }
                 ann.format_with_options(buf, parens, newlines, indent);
                 fmt_comments_only(buf, spaces.iter(), NewlineAt::Bottom, indent);
             }
@@ -13,6 +13,24 @@ pub enum Braces {
     Curly,
 }
 
+impl Braces {
+    fn start(self) -> char {
+        match self {
+            Braces::Round => '(',
+            Braces::Curly => '{',
+            Braces::Square => '[',
+        }
+    }
+
+    fn end(self) -> char {
+        match self {
+            Braces::Round => ')',
+            Braces::Curly => '}',
+            Braces::Square => ']',
+        }
+    }
+}
+
 pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
     buf: &mut Buf<'buf>,
     indent: u16,
@@ -22,26 +40,18 @@ pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
 ) where
     <T as ExtractSpaces<'a>>::Item: Formattable,
 {
-    let start = match braces {
-        Braces::Round => '(',
-        Braces::Curly => '{',
-        Braces::Square => '[',
-    };
-
-    let end = match braces {
-        Braces::Round => ')',
-        Braces::Curly => '}',
-        Braces::Square => ']',
-    };
-
-    if is_collection_multiline(&items) {
+    let may_break = buf.take_break_permission();
+    let is_multiline = is_collection_multiline(&items)
+        || (may_break && !buf.fits_on_line(|buf| fmt_collection_flat(buf, indent, braces, items)));
+
+    if is_multiline {
         let braces_indent = indent;
         let item_indent = braces_indent + INDENT;
         if newline == Newlines::Yes {
             buf.ensure_ends_with_newline();
         }
         buf.indent(braces_indent);
-        buf.push(start);
+        buf.push(braces.start());
 
         for (index, item) in items.iter().enumerate() {
             let is_first_item = index == 0;
@@ -86,6 +96,8 @@ pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
             }
 
             buf.indent(item_indent);
+            // Each item is on its own line, so it can break too.
+            buf.allow_break();
             item.item.format(buf, item_indent);
 
             buf.push(',');
@@ -119,27 +131,37 @@ pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
 
         buf.ensure_ends_with_newline();
         buf.indent(braces_indent);
+        buf.push(braces.end());
     } else {
         // is_multiline == false
         // there is no comment to add
-        buf.indent(indent);
-        buf.push(start);
-        let mut iter = items.iter().enumerate().peekable();
-        while let Some((index, item)) = iter.next() {
-            if braces == Braces::Curly || index != 0 {
-                buf.spaces(1);
-            }
+        fmt_collection_flat(buf, indent, braces, items);
+    }
+}
 
-            item.format(buf, indent);
-            if iter.peek().is_some() {
-                buf.push(',');
-            }
+fn fmt_collection_flat<'a, 'buf, T: ExtractSpaces<'a> + Formattable>(
+    buf: &mut Buf<'buf>,
+    indent: u16,
+    braces: Braces,
+    items: Collection<'a, T>,
+) {
+    buf.indent(indent);
+    buf.push(braces.start());
+    let mut iter = items.iter().enumerate().peekable();
+    while let Some((index, item)) = iter.next() {
+        if braces == Braces::Curly || index != 0 {
+            buf.spaces(1);
         }
 
-        if !items.is_empty() && braces == Braces::Curly {
-            buf.spaces(1);
+        item.format(buf, indent);
+        if iter.peek().is_some() {
+            buf.push(',');
         }
     }
 
-    buf.push(end);
+    if !items.is_empty() && braces == Braces::Curly {
+        buf.spaces(1);
+    }
+
+    buf.push(braces.end());
 }
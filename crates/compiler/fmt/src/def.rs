@@ -94,6 +94,7 @@ impl<'a> Formattable for TypeDef<'a> {
                 buf.push_str(" :");
                 buf.spaces(1);
 
+                buf.allow_break();
                 ann.format(buf, indent)
             }
             Opaque {
@@ -112,10 +113,15 @@ impl<'a> Formattable for TypeDef<'a> {
                     false
                 };
 
-                let make_multiline = ann.is_multiline() || has_abilities_multiline;
+                let def_start = buf.as_str().len();
 
                 fmt_general_def(header, buf, indent, ":=", &ann.value, newlines);
 
+                // The annotation can also have been broken up for being too wide.
+                let make_multiline = ann.is_multiline()
+                    || has_abilities_multiline
+                    || buf.as_str()[def_start..].trim_start().contains('\n');
+
                 if let Some(has_abilities) = has_abilities {
                     buf.spaces(1);
 
@@ -282,12 +288,38 @@ fn fmt_general_def<L: Formattable>(
                 }
             }
         } else {
+            // Records and tag unions would move up after the separator if they got broken up.
+            if !matches!(
+                rhs.extract_spaces().item,
+                TypeAnnotation::Record { .. } | TypeAnnotation::TagUnion { .. }
+            ) {
+                buf.allow_break();
+            }
             rhs.format_with_options(buf, Parens::NotNeeded, newlines, indent + INDENT);
         }
+    } else if matches!(rhs, TypeAnnotation::Function(..))
+        && !buf.fits_on_line(|buf| {
+            buf.spaces(1);
+            buf.push_str(sep);
+            buf.spaces(1);
+            rhs.format_with_options(buf, Parens::NotNeeded, Newlines::No, indent);
+        })
+    {
+        // A function type that's too wide goes on its own line, like a multiline one,
+        // where it can be broken up further if it's still too wide.
+        buf.spaces(1);
+        buf.push_str(sep);
+        buf.newline();
+        buf.indent(indent + INDENT);
+        buf.allow_break();
+        rhs.format_with_options(buf, Parens::NotNeeded, newlines, indent + INDENT);
     } else {
         buf.spaces(1);
         buf.push_str(sep);
         buf.spaces(1);
+        if should_outdent(rhs) {
+            buf.allow_break();
+        }
         rhs.format_with_options(buf, Parens::NotNeeded, Newlines::No, indent);
     }
 }
@@ -418,6 +450,11 @@ pub fn fmt_body<'a, 'buf>(
                     buf.spaces(1);
                     sub_def.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
                 } else {
+                    // The body is on its own line, so it can break too - except for lists and
+                    // records, which would move up after the `=` if they did.
+                    if !matches!(sub_def, Expr::Record { .. } | Expr::List { .. }) {
+                        buf.allow_break();
+                    }
                     body.format_with_options(
                         buf,
                         Parens::NotNeeded,
@@ -440,6 +477,7 @@ pub fn fmt_body<'a, 'buf>(
                 //
                 // This makes it clear what the binop is applying to!
                 buf.newline();
+                buf.allow_break();
                 body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent + INDENT);
             }
             Expr::When(..) | Expr::Str(StrLiteral::Block(_)) => {
@@ -451,8 +489,19 @@ pub fn fmt_body<'a, 'buf>(
                 body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
             }
         }
+    } else if matches!(body, Expr::BinOps(..))
+        && !buf.fits_on_line(|buf| {
+            buf.spaces(1);
+            body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+        })
+    {
+        // Like a multiline binop chain, a binop chain that's too wide goes on its own line.
+        buf.newline();
+        buf.allow_break();
+        body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent + INDENT);
     } else {
         buf.spaces(1);
+        buf.allow_break();
         body.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
     }
 }
@@ -114,12 +114,21 @@ impl<'a> Formattable for Expr<'a> {
 
         let apply_needs_parens = parens == Parens::InApply;
 
+        // Lists and tuples check this themselves, in fmt_collection
+        let may_break = !matches!(self, List(_) | Tuple(_)) && buf.take_break_permission();
+
         match self {
             SpaceBefore(sub_expr, spaces) => {
                 format_spaces(buf, spaces, newlines, indent);
+                if may_break {
+                    buf.allow_break();
+                }
                 sub_expr.format_with_options(buf, parens, newlines, indent);
             }
             SpaceAfter(sub_expr, spaces) => {
+                if may_break {
+                    buf.allow_break();
+                }
                 sub_expr.format_with_options(buf, parens, newlines, indent);
                 format_spaces(buf, spaces, newlines, indent);
             }
@@ -236,8 +245,55 @@ impl<'a> Formattable for Expr<'a> {
                         })
                         .unwrap_or_default();
 
+                let expr_needs_parens =
+                    matches!(loc_expr.value.extract_spaces().item, Expr::Closure(..))
+                        && !loc_args.is_empty();
+
+                // If it's too wide, either only the last argument breaks (if it's a collection
+                // or a closure, and everything before it fits on the line), or each argument
+                // goes on its own line.
+                let is_too_wide = may_break
+                    && !loc_args.is_empty()
+                    && !self.is_multiline()
+                    && !buf.fits_on_line(|buf| {
+                        self.format_with_options(buf, parens, newlines, indent)
+                    });
+
+                // Collections which start on their own line would get moved back up to
+                // this one the next time this is formatted, so they always stay on this line.
+                let last_arg_breaks = is_too_wide
+                    && match loc_args.last().map(|a| a.value.extract_spaces().item) {
+                        Some(Expr::Tuple(_) | Expr::List(_) | Expr::Record(_)) => true,
+                        Some(Expr::Closure(..)) => buf.fits_on_line(|buf| {
+                            buf.indent(indent);
+                            if apply_needs_parens || expr_needs_parens {
+                                buf.push('(');
+                            }
+                            loc_expr.format_with_options(
+                                buf,
+                                Parens::InApply,
+                                Newlines::Yes,
+                                indent,
+                            );
+                            for loc_arg in except_last(loc_args) {
+                                buf.spaces(1);
+                                loc_arg.format_with_options(
+                                    buf,
+                                    Parens::InApply,
+                                    Newlines::Yes,
+                                    indent,
+                                );
+                            }
+                            // The space before the closure, and its backslash
+                            buf.spaces(1);
+                            buf.push('\\');
+                        }),
+                        _ => false,
+                    };
+
                 let needs_indent = !should_reflow_outdentable
-                    && (!loc_expr.extract_spaces().after.is_empty()
+                    && ((is_too_wide && !last_arg_breaks)
+                        || !loc_expr.extract_spaces().after.is_empty()
                         || except_last(loc_args).any(|a| a.is_multiline())
                         || loc_args
                             .last()
@@ -254,10 +310,6 @@ impl<'a> Formattable for Expr<'a> {
                     indent
                 };
 
-                let expr_needs_parens =
-                    matches!(loc_expr.value.extract_spaces().item, Expr::Closure(..))
-                        && !loc_args.is_empty();
-
                 if expr_needs_parens {
                     buf.push('(');
                 }
@@ -269,7 +321,7 @@ impl<'a> Formattable for Expr<'a> {
                     buf.push(')');
                 }
 
-                for loc_arg in loc_args.iter() {
+                for (index, loc_arg) in loc_args.iter().enumerate() {
                     if should_reflow_outdentable {
                         buf.spaces(1);
 
@@ -288,6 +340,15 @@ impl<'a> Formattable for Expr<'a> {
                         let arg = loc_arg.extract_spaces();
                         fmt_spaces(buf, arg.before.iter(), arg_indent);
                         buf.ensure_ends_with_newline();
+                        buf.indent(arg_indent);
+                        // Each argument is on its own line, so it can break too - except for
+                        // a collection at the end, which would move up to the first line if it did.
+                        let is_last = index == loc_args.len() - 1;
+                        if !(is_last
+                            && matches!(arg.item, Expr::Tuple(_) | Expr::List(_) | Expr::Record(_)))
+                        {
+                            buf.allow_break();
+                        }
                         arg.item.format_with_options(
                             buf,
                             Parens::InApply,
@@ -297,6 +358,9 @@ impl<'a> Formattable for Expr<'a> {
                         fmt_spaces(buf, arg.after.iter(), arg_indent);
                     } else {
                         buf.spaces(1);
+                        if last_arg_breaks && index == loc_args.len() - 1 {
+                            buf.allow_break();
+                        }
                         loc_arg.format_with_options(
                             buf,
                             Parens::InApply,
@@ -346,13 +410,13 @@ impl<'a> Formattable for Expr<'a> {
                 buf.push_str(string);
             }
             Record(fields) => {
-                fmt_record(buf, None, *fields, indent);
+                fmt_record(buf, None, *fields, may_break, indent);
             }
             RecordUpdate { update, fields } => {
-                fmt_record(buf, Some(*update), *fields, indent);
+                fmt_record(buf, Some(*update), *fields, may_break, indent);
             }
             Closure(loc_patterns, loc_ret) => {
-                fmt_closure(buf, loc_patterns, loc_ret, indent);
+                fmt_closure(buf, loc_patterns, loc_ret, may_break, indent);
             }
             Backpassing(loc_patterns, loc_body, loc_ret) => {
                 fmt_backpassing(buf, loc_patterns, loc_body, loc_ret, indent);
@@ -380,6 +444,7 @@ impl<'a> Formattable for Expr<'a> {
                             fmt_spaces(buf, spaces.iter(), indent);
 
                             buf.indent(indent);
+                            buf.allow_break();
 
                             sub_expr.format_with_options(
                                 buf,
@@ -391,6 +456,7 @@ impl<'a> Formattable for Expr<'a> {
                         _ => {
                             buf.ensure_ends_with_newline();
                             buf.indent(indent);
+                            buf.allow_break();
                             // Even if there were no defs, which theoretically should never happen,
                             // still print the return value.
                             ret.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
@@ -416,7 +482,7 @@ impl<'a> Formattable for Expr<'a> {
             When(loc_condition, branches) => fmt_when(buf, loc_condition, branches, indent),
             Tuple(items) => fmt_collection(buf, indent, Braces::Round, *items, Newlines::No),
             List(items) => fmt_collection(buf, indent, Braces::Square, *items, Newlines::No),
-            BinOps(lefts, right) => fmt_binops(buf, lefts, right, false, indent),
+            BinOps(lefts, right) => fmt_binops(buf, lefts, right, false, may_break, indent),
             UnaryOp(sub_expr, unary_op) => {
                 buf.indent(indent);
                 match &unary_op.value {
@@ -678,15 +744,29 @@ fn fmt_binops<'a, 'buf>(
     lefts: &'a [(Loc<Expr<'a>>, Loc<BinOp>)],
     loc_right_side: &'a Loc<Expr<'a>>,
     part_of_multi_line_binops: bool,
+    may_break: bool,
     indent: u16,
 ) {
     let is_multiline = part_of_multi_line_binops
         || loc_right_side.value.is_multiline()
         || lefts.iter().any(|(expr, _)| expr.value.is_multiline());
 
+    // Operators only go on their own lines when the first operand starts a line, because
+    // they line up with it.
+    let is_too_wide = may_break
+        && !is_multiline
+        && buf.is_at_start_of_line()
+        && !buf.fits_on_line(|buf| fmt_binops(buf, lefts, loc_right_side, false, false, indent));
+
+    let is_multiline = is_multiline || is_too_wide;
+
     for (loc_left_side, loc_binop) in lefts {
         let binop = loc_binop.value;
 
+        // Each operand is on its own line, so it can break too.
+        if is_multiline {
+            buf.allow_break();
+        }
         loc_left_side.format_with_options(buf, Parens::InOperator, Newlines::No, indent);
 
         if is_multiline {
@@ -701,6 +781,9 @@ fn fmt_binops<'a, 'buf>(
         buf.spaces(1);
     }
 
+    if is_multiline {
+        buf.allow_break();
+    }
     loc_right_side.format_with_options(buf, Parens::InOperator, Newlines::Yes, indent);
 }
 
@@ -1127,6 +1210,7 @@ fn fmt_closure<'a, 'buf>(
     buf: &mut Buf<'buf>,
     loc_patterns: &'a [Loc<Pattern<'a>>],
     loc_ret: &'a Loc<Expr<'a>>,
+    may_break: bool,
     indent: u16,
 ) {
     use self::Expr::*;
@@ -1173,6 +1257,39 @@ fn fmt_closure<'a, 'buf>(
 
     let is_multiline = loc_ret.value.is_multiline();
 
+    // A list or record body which has spaces after it would go somewhere else the next
+    // time this is formatted, so that gets left as it is.
+    let is_too_wide = may_break
+        && !is_multiline
+        && (matches!(loc_ret.value, Record { .. } | List { .. })
+            || !matches!(
+                loc_ret.value.extract_spaces().item,
+                Record { .. } | List { .. }
+            ))
+        && !buf.fits_on_line(|buf| {
+            buf.spaces(1);
+            loc_ret.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+        });
+
+    if is_too_wide {
+        match &loc_ret.value {
+            // These can break after the `->`
+            Record { .. } | List { .. } => {
+                buf.spaces(1);
+                buf.allow_break();
+                loc_ret.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
+            }
+            _ => {
+                buf.newline();
+                buf.indent(indent + INDENT);
+                buf.allow_break();
+                loc_ret.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent + INDENT);
+            }
+        }
+
+        return;
+    }
+
     // If the body is multiline, go down a line and indent.
     let body_indent = if is_multiline {
         indent + INDENT
@@ -1209,6 +1326,11 @@ fn fmt_closure<'a, 'buf>(
                     buf.spaces(1);
                     sub_expr.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, indent);
                 } else {
+                    // The body is on its own line, so it can break too - except for lists and
+                    // records, which would move up after the `->` if they did.
+                    if !matches!(sub_expr, Record { .. } | List { .. }) {
+                        buf.allow_break();
+                    }
                     loc_ret.format_with_options(buf, Parens::NotNeeded, Newlines::Yes, body_indent);
                 }
             }
@@ -1316,10 +1438,13 @@ fn fmt_record<'a, 'buf>(
     buf: &mut Buf<'buf>,
     update: Option<&'a Loc<Expr<'a>>>,
     fields: Collection<'a, Loc<AssignedField<'a, Expr<'a>>>>,
+    may_break: bool,
     indent: u16,
 ) {
     let loc_fields = fields.items;
     let final_comments = fields.final_comments();
+    let is_too_wide =
+        may_break && !buf.fits_on_line(|buf| fmt_record(buf, update, fields, false, indent));
     buf.indent(indent);
     if loc_fields.is_empty() && final_comments.iter().all(|c| c.is_newline()) && update.is_none() {
         buf.push_str("{}");
@@ -1340,7 +1465,8 @@ fn fmt_record<'a, 'buf>(
         }
 
         let is_multiline = loc_fields.iter().any(|loc_field| loc_field.is_multiline())
-            || !final_comments.is_empty();
+            || !final_comments.is_empty()
+            || is_too_wide;
 
         if is_multiline {
             let field_indent = indent + INDENT;
@@ -1427,6 +1553,8 @@ fn format_field_multiline<'a, 'buf, T>(
             buf.push_str(separator_prefix);
             buf.push_str(":");
             buf.spaces(1);
+            // Each field is on its own line, so its value can break too.
+            buf.allow_break();
             ann.value.format(buf, indent);
             buf.push(',');
         }
@@ -1443,6 +1571,8 @@ fn format_field_multiline<'a, 'buf, T>(
             buf.push_str(separator_prefix);
             buf.push_str("?");
             buf.spaces(1);
+            // Each field is on its own line, so its value can break too.
+            buf.allow_break();
             ann.value.format(buf, indent);
             buf.push(',');
         }
@@ -25,6 +25,13 @@ pub struct Buf<'a> {
     spaces_to_flush: usize,
     newlines_to_flush: usize,
     beginning_of_line: bool,
+    /// Collections, function applications, pipelines and function types which would make
+    /// a line longer than this get broken over multiple lines. Without a max width, the only
+    /// line breaks are the ones which were already in the code.
+    max_width: Option<usize>,
+    /// Where in `text` the node that's allowed to break over multiple lines starts, if any.
+    /// See `allow_break`.
+    break_allowed_at: Option<usize>,
 }
 
 impl<'a> Buf<'a> {
@@ -34,6 +41,68 @@ impl<'a> Buf<'a> {
             spaces_to_flush: 0,
             newlines_to_flush: 0,
             beginning_of_line: true,
+            max_width: None,
+            break_allowed_at: None,
+        }
+    }
+
+    pub fn with_max_width_in(arena: &'a Bump, max_width: usize) -> Buf<'a> {
+        Buf {
+            max_width: Some(max_width),
+            ..Buf::new_in(arena)
+        }
+    }
+
+    /// Lets the expression, type annotation or collection formatted next break over
+    /// multiple lines if it doesn't fit within the max width. Once anything else has been
+    /// written, the permission no longer applies.
+    ///
+    /// This is only safe where the surrounding code gets laid out the same way whether
+    /// that node is on one line or several - otherwise formatting the result again would
+    /// change it again - so it's up to each parent to decide which of its children may break.
+    pub fn allow_break(&mut self) {
+        if self.max_width.is_some() {
+            self.break_allowed_at = Some(self.text.len());
+        }
+    }
+
+    /// Whether the node that's about to be formatted was allowed to break. This resets the
+    /// permission, so that it doesn't carry over to that node's children.
+    pub fn take_break_permission(&mut self) -> bool {
+        self.break_allowed_at.take() == Some(self.text.len())
+    }
+
+    /// Whether what `fmt` writes would fit on the current line without going over the max
+    /// width, if it were all on one line. Always true if there's no max width.
+    pub fn fits_on_line(&self, fmt: impl FnOnce(&mut Buf<'a>)) -> bool {
+        let max_width = match self.max_width {
+            Some(max_width) => max_width,
+            None => return true,
+        };
+
+        // Nothing written to this buffer can break, because it has no max width.
+        let mut flat = Buf {
+            beginning_of_line: self.beginning_of_line,
+            ..Buf::new_in(self.text.bump())
+        };
+
+        fmt(&mut flat);
+
+        !flat.text.contains('\n')
+            && flat.newlines_to_flush == 0
+            && self.column() + flat.text.chars().count() <= max_width
+    }
+
+    /// How wide the current line is, including any spaces that haven't been written yet
+    fn column(&self) -> usize {
+        if self.beginning_of_line {
+            0
+        } else if self.newlines_to_flush > 0 {
+            self.spaces_to_flush
+        } else {
+            let line_start = self.text.rfind('\n').map_or(0, |index| index + 1);
+
+            self.text[line_start..].chars().count() + self.spaces_to_flush
         }
     }
 
@@ -154,6 +223,14 @@ impl<'a> Buf<'a> {
         self.flush_spaces();
     }
 
+    /// Whether nothing but indentation has been written to the current line
+    pub fn is_at_start_of_line(&self) -> bool {
+        self.beginning_of_line
+            || self.newlines_to_flush > 0
+            || self.text.is_empty()
+            || self.text.ends_with('\n')
+    }
+
     pub fn ends_with_space(&self) -> bool {
         self.spaces_to_flush > 0 || self.text.ends_with(' ')
     }
@@ -4317,6 +4317,27 @@ pub fn get_panic_tag_ptr<'a, 'ctx, 'env>(env: &Env<'a, 'ctx, 'env>) -> PointerVa
     global.as_pointer_value()
 }
 
+/// The globals that hold the settings `roc test --property-runs` and `--property-seed` give to
+/// `Arbitrary.check`. They are 0 unless the test runner writes to them.
+pub const PROPERTY_RUNS_GLOBAL: &str = "roc_property_runs";
+pub const PROPERTY_SEED_GLOBAL: &str = "roc_property_seed";
+
+/// Pointer to one of the property settings, e.g. [PROPERTY_RUNS_GLOBAL].
+pub fn get_property_setting_ptr<'a, 'ctx, 'env>(
+    env: &Env<'a, 'ctx, 'env>,
+    global_name: &str,
+) -> PointerValue<'ctx> {
+    let i64_typ = env.context.i64_type();
+
+    let global = env.module.get_global(global_name).unwrap_or_else(|| {
+        let global = env.module.add_global(i64_typ, None, global_name);
+        global.set_initializer(&i64_typ.const_zero());
+        global
+    });
+
+    global.as_pointer_value()
+}
+
 fn set_jump_and_catch_long_jump<'a, 'ctx, 'env>(
     env: &Env<'a, 'ctx, 'env>,
     layout_interner: &mut STLayoutInterner<'a>,
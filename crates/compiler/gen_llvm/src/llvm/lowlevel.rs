@@ -25,7 +25,8 @@ use crate::llvm::{
     },
     build::{
         complex_bitcast_check_size, create_entry_block_alloca, function_value_by_func_spec,
-        load_roc_value, roc_function_call, BuilderExt, RocReturn,
+        get_property_setting_ptr, load_roc_value, roc_function_call, BuilderExt, RocReturn,
+        PROPERTY_RUNS_GLOBAL, PROPERTY_SEED_GLOBAL,
     },
     build_list::{
         list_append_unsafe, list_concat, list_drop_at, list_get_unsafe, list_len, list_map,
@@ -1251,6 +1252,18 @@ pub(crate) fn run_low_level<'a, 'ctx, 'env>(
             unreachable!("Not used in LLVM backend: {:?}", op);
         }
 
+        PropertyRuns | PropertySeed => {
+            let global_name = match op {
+                PropertyRuns => PROPERTY_RUNS_GLOBAL,
+                _ => PROPERTY_SEED_GLOBAL,
+            };
+
+            let ptr = get_property_setting_ptr(env, global_name);
+
+            env.builder
+                .new_build_load(env.context.i64_type(), ptr, global_name)
+        }
+
         Unreachable => match RocReturn::from_layout(env, layout_interner, layout) {
             RocReturn::Return => {
                 let basic_type = basic_type_from_layout(env, layout_interner, layout);
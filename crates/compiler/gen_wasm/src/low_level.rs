@@ -1957,6 +1957,9 @@ impl<'a> LowLevelCall<'a> {
 
             Hash => todo!("{:?}", self.lowlevel),
 
+            // Only `roc test` sets these, and it doesn't use this backend
+            PropertyRuns | PropertySeed => backend.code_builder.i64_const(0),
+
             Eq | NotEq => self.eq_or_neq(backend),
 
             BoxExpr | UnboxExpr => {
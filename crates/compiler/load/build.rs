@@ -25,6 +25,9 @@ const MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode.roc"),
     (ModuleId::HASH, "Hash.roc"),
     (ModuleId::JSON, "Json.roc"),
+    (ModuleId::ARBITRARY, "Arbitrary.roc"),
+    (ModuleId::INSPECT, "Inspect.roc"),
+    (ModuleId::SORT, "Sort.roc"),
 ];
 
 fn main() {
@@ -106,6 +109,7 @@ fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path:
         &module.interns,
         &mut module.can_problems,
         &mut module.type_problems,
+        roc_reporting::report::RenderTarget::ColorTerminal,
     );
 
     if problems.errors + problems.warnings > 0 {
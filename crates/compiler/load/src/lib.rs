@@ -19,7 +19,8 @@ const SKIP_SUBS_CACHE: bool = {
 pub use roc_load_internal::docs;
 pub use roc_load_internal::file::{
     EntryPoint, ExecutionMode, ExpectMetadata, Expectations, ExposedToHost, LoadConfig, LoadResult,
-    LoadStart, LoadedModule, LoadingProblem, MonomorphizedModule, Phase, Threading,
+    LoadStart, LoadedModule, LoadingProblem, MonomorphizedModule, PackageDependency, Phase,
+    Threading,
 };
 
 #[allow(clippy::too_many_arguments)]
@@ -204,6 +205,9 @@ const BOX: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Box.dat")) as &[_];
 const ENCODE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Encode.dat")) as &[_];
 const DECODE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Decode.dat")) as &[_];
 const HASH: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Hash.dat")) as &[_];
+const ARBITRARY: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Arbitrary.dat")) as &[_];
+const INSPECT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Inspect.dat")) as &[_];
+const SORT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/Sort.dat")) as &[_];
 
 fn deserialize_help(bytes: &[u8]) -> TypeState {
     let (state, _offset) = TypeState::deserialize(bytes);
@@ -234,6 +238,10 @@ fn read_cached_types() -> MutMap<ModuleId, TypeState> {
         output.insert(ModuleId::DECODE, deserialize_help(DECODE));
 
         output.insert(ModuleId::HASH, deserialize_help(HASH));
+
+        output.insert(ModuleId::ARBITRARY, deserialize_help(ARBITRARY));
+        output.insert(ModuleId::INSPECT, deserialize_help(INSPECT));
+        output.insert(ModuleId::SORT, deserialize_help(SORT));
     }
 
     output
@@ -73,6 +73,7 @@ use {
     roc_packaging::https::PackageMetadata,
 };
 
+use crate::typecheck_cache::{self, CacheKey, TypeCheckCache};
 pub use crate::work::Phase;
 use crate::work::{DepCycle, Dependencies};
 
@@ -203,6 +204,9 @@ impl Default for ModuleCache<'_> {
             DECODE,
             HASH,
             JSON,
+            ARBITRARY,
+            INSPECT,
+            SORT,
         }
 
         Self {
@@ -376,6 +380,24 @@ fn start_phase<'a>(
                     }
                 }
 
+                if let Some(cache) = &state.typecheck_cache {
+                    let opt_key = typecheck_cache_key(
+                        cache,
+                        &state.typecheck_cache_keys,
+                        &parsed,
+                        &dep_idents,
+                        &module_ids,
+                    );
+
+                    if let Some(key) = opt_key {
+                        if let Some(type_state) = cache.read(&key) {
+                            state.cached_types.lock().insert(module_id, type_state);
+                        }
+
+                        state.typecheck_cache_keys.insert(module_id, key);
+                    }
+                }
+
                 let skip_constraint_gen = {
                     // Give this its own scope to make sure that the Guard from the lock() is dropped
                     // immediately after contains_key returns
@@ -414,6 +436,11 @@ fn start_phase<'a>(
 
                 let derived_module = SharedDerivedModule::clone(&state.derived_module);
 
+                let cache_entry = state.typecheck_cache.as_ref().and_then(|cache| {
+                    let key = state.typecheck_cache_keys.get(&module_id)?;
+                    Some(cache.entry_path(key))
+                });
+
                 BuildTask::solve_module(
                     module,
                     ident_ids,
@@ -428,6 +455,7 @@ fn start_phase<'a>(
                     dep_idents,
                     declarations,
                     state.cached_types.clone(),
+                    cache_entry,
                     derived_module,
                 )
             }
@@ -638,6 +666,22 @@ pub struct LoadedModule {
     pub timings: MutMap<ModuleId, ModuleTiming>,
     pub docs_by_module: VecMap<ModuleId, ModuleDocumentation>,
     pub abilities_store: AbilitiesStore,
+    pub package_dependencies: Vec<PackageDependency>,
+}
+
+/// A package listed in the `packages` section of some module's header.
+#[derive(Debug, Clone)]
+pub struct PackageDependency {
+    /// The module whose header lists this package
+    pub declared_in: ModuleId,
+    /// e.g. the "pf" in `pf: "https://example.com/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar.br"`
+    pub shorthand: String,
+    /// The URL or relative path, exactly as written in the header
+    pub source: String,
+    /// e.g. "/home/rtfeldman/.cache/roc/0.1.0/oUkxSOI9zFGtSoIaMB40QPdrXphr1p1780eiui2iO9Mz/main.roc"
+    pub root_module: PathBuf,
+    /// The modules which import something from this package via its shorthand, e.g. `pf.Stdout`
+    pub imported_by: Vec<ModuleId>,
 }
 
 impl LoadedModule {
@@ -980,6 +1024,8 @@ struct State<'a> {
     /// Note: only packages and platforms actually expose any modules;
     /// for all others, this will be empty.
     pub exposed_modules: &'a [ModuleId],
+    /// The modules exposed by the packages and platforms that the root module depends on
+    pub dependency_exposed_modules: Vec<ModuleId>,
 
     pub module_cache: ModuleCache<'a>,
     pub dependencies: Dependencies<'a>,
@@ -1020,10 +1066,19 @@ struct State<'a> {
 
     make_specializations_pass: MakeSpecializationsPass,
 
-    // cached types (used for builtin modules, could include packages in the future too)
+    // cached types (used for builtin modules, and user modules found in the typecheck cache)
     cached_types: CachedTypeState,
 
+    /// The on-disk cache of solved user modules; None if caching is disabled
+    typecheck_cache: Option<TypeCheckCache>,
+    typecheck_cache_keys: MutMap<ModuleId, CacheKey>,
+
     layout_interner: GlobalLayoutInterner<'a>,
+
+    /// Every package listed in a header, along with every package-qualified import
+    /// (e.g. `pf.Stdout`) by shorthand; these get combined once loading is done.
+    package_dependencies: Vec<PackageDependency>,
+    package_imports: Vec<(&'a str, ModuleId)>,
 }
 
 type CachedTypeState = Arc<Mutex<MutMap<ModuleId, TypeState>>>;
@@ -1045,6 +1100,7 @@ impl<'a> State<'a> {
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        roc_cache_dir: RocCacheDir<'_>,
     ) -> Self {
         let arc_shorthands = Arc::new(Mutex::new(MutMap::default()));
         let cache_dir = roc_packaging::cache::roc_cache_dir();
@@ -1065,6 +1121,7 @@ impl<'a> State<'a> {
             toplevel_expects: ToplevelExpects::default(),
             exposed_to_host: ExposedToHost::default(),
             exposed_modules: &[],
+            dependency_exposed_modules: Vec::new(),
             exposed_types,
             arc_modules,
             arc_shorthands,
@@ -1076,12 +1133,16 @@ impl<'a> State<'a> {
             timings: MutMap::default(),
             layout_caches: std::vec::Vec::with_capacity(number_of_workers),
             cached_types: Arc::new(Mutex::new(cached_types)),
+            typecheck_cache: TypeCheckCache::new(roc_cache_dir),
+            typecheck_cache_keys: MutMap::default(),
             render,
             palette,
             exec_mode,
             make_specializations_pass: MakeSpecializationsPass::Pass(1),
             world_abilities: Default::default(),
             layout_interner: GlobalLayoutInterner::with_capacity(128, target_info),
+            package_dependencies: Vec::new(),
+            package_imports: Vec::new(),
         }
     }
 }
@@ -1237,6 +1298,7 @@ enum BuildTask<'a> {
         declarations: Declarations,
         dep_idents: IdentIdsByModule,
         cached_subs: CachedTypeState,
+        cache_entry: Option<PathBuf>,
         derived_module: SharedDerivedModule,
     },
     BuildPendingSpecializations {
@@ -1672,6 +1734,7 @@ pub fn load_single_threaded<'a>(
         palette,
         number_of_workers,
         exec_mode,
+        roc_cache_dir,
     );
 
     // We'll add tasks to this, and then worker threads will take tasks from it.
@@ -1791,7 +1854,7 @@ fn state_thread_step<'a>(
                     Ok(ControlFlow::Break(LoadResult::Monomorphized(monomorphized)))
                 }
                 Msg::FailedToReadFile { filename, error } => {
-                    let buf = to_file_problem_report_string(&filename, error);
+                    let buf = to_file_problem_report_string(&filename, error, state.render);
                     Err(LoadingProblem::FormattedReport(buf))
                 }
 
@@ -1940,7 +2003,7 @@ pub fn report_loading_problem(
         }
         LoadingProblem::FormattedReport(report) => report,
         LoadingProblem::FileProblem { filename, error } => {
-            to_file_problem_report_string(&filename, error)
+            to_file_problem_report_string(&filename, error, render)
         }
         err => todo!("Loading error: {:?}", err),
     }
@@ -2002,6 +2065,7 @@ fn load_multi_threaded<'a>(
         palette,
         num_workers,
         exec_mode,
+        roc_cache_dir,
     );
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
@@ -2493,9 +2557,23 @@ fn update<'a>(
                         shorthand_path
                     );
 
+                    state.package_dependencies.push(PackageDependency {
+                        declared_in: home,
+                        shorthand: shorthand.to_string(),
+                        source: package_str.to_string(),
+                        root_module: shorthand_path.root_module().to_path_buf(),
+                        imported_by: Vec::new(),
+                    });
+
                     shorthands.insert(shorthand, shorthand_path);
                 }
 
+                for imported in header.package_qualified_imported_modules.iter() {
+                    if let PackageQualified::Qualified(shorthand, _) = imported {
+                        state.package_imports.push((*shorthand, home));
+                    }
+                }
+
                 match header.header_type {
                     App { to_platform, .. } => {
                         debug_assert!(matches!(state.platform_path, PlatformPath::NotSpecified));
@@ -2508,6 +2586,10 @@ fn update<'a>(
                     } => {
                         if header.is_root_module {
                             state.exposed_modules = exposes_ids;
+                        } else {
+                            state
+                                .dependency_exposed_modules
+                                .extend_from_slice(exposes_ids);
                         }
 
                         work.extend(state.dependencies.notify_package(config_shorthand));
@@ -2552,6 +2634,10 @@ fn update<'a>(
 
                         if header.is_root_module {
                             state.exposed_modules = exposes_ids;
+                        } else {
+                            state
+                                .dependency_exposed_modules
+                                .extend_from_slice(exposes_ids);
                         }
                     }
                     Builtin { .. } | Interface { .. } => {
@@ -2613,6 +2699,9 @@ fn update<'a>(
                 extend_header_with_builtin(header, ModuleId::ENCODE);
                 extend_header_with_builtin(header, ModuleId::DECODE);
                 extend_header_with_builtin(header, ModuleId::HASH);
+                extend_header_with_builtin(header, ModuleId::ARBITRARY);
+                extend_header_with_builtin(header, ModuleId::INSPECT);
+                extend_header_with_builtin(header, ModuleId::SORT);
             }
 
             state
@@ -3327,7 +3416,7 @@ fn finish_specialization<'a>(
                     }
                     Valid(To::NewPackage(p_or_p)) => PathBuf::from(p_or_p.as_str()),
                     other => {
-                        let buf = to_missing_platform_report(state.root_id, other);
+                        let buf = to_missing_platform_report(state.root_id, other, state.render);
                         return Err(LoadingProblem::FormattedReport(buf));
                     }
                 };
@@ -3511,6 +3600,25 @@ fn finish(
     documentation: VecMap<ModuleId, ModuleDocumentation>,
     abilities_store: AbilitiesStore,
 ) -> LoadedModule {
+    let mut package_dependencies = state.package_dependencies;
+
+    {
+        // Different packages can use the same shorthand for their own dependencies, so an import
+        // only counts for a dependency that was declared in the importing module's package.
+        let module_ids = (*state.arc_modules).lock();
+
+        for dependency in package_dependencies.iter_mut() {
+            for (shorthand, module_id) in state.package_imports.iter() {
+                if *shorthand == dependency.shorthand
+                    && module_ids.package_eq(dependency.declared_in, *module_id) == Some(true)
+                    && !dependency.imported_by.contains(module_id)
+                {
+                    dependency.imported_by.push(*module_id);
+                }
+            }
+        }
+    }
+
     let module_ids = Arc::try_unwrap(state.arc_modules)
         .unwrap_or_else(|_| panic!("There were still outstanding Arc references to module_ids"))
         .into_inner()
@@ -3541,6 +3649,16 @@ fn finish(
 
     let exposed_values = exposed_vars_by_symbol.iter().map(|x| x.0).collect();
 
+    // When the root is a package or platform, every module got documented (so that its docs can
+    // link to types from its dependencies), but only exposed modules belong in the docs.
+    let documentation = documentation
+        .into_iter()
+        .filter(|(module_id, _)| {
+            state.exposed_modules.contains(module_id)
+                || state.dependency_exposed_modules.contains(module_id)
+        })
+        .collect();
+
     LoadedModule {
         module_id: state.root_id,
         interns,
@@ -3558,6 +3676,7 @@ fn finish(
         timings: state.timings,
         docs_by_module: documentation,
         abilities_store,
+        package_dependencies,
     }
 }
 
@@ -3651,6 +3770,7 @@ fn load_package_from_disk<'a>(
                 )) => {
                     let exposes_ids = get_exposes_ids(
                         header.exposes.item.items,
+                        Some(shorthand),
                         arena,
                         &module_ids,
                         &ident_ids_by_module,
@@ -3687,8 +3807,11 @@ fn load_package_from_disk<'a>(
     }
 }
 
+/// The IDs of the modules a package or platform exposes. Those are qualified with the package's
+/// shorthand if it has one, i.e. if it isn't the root module.
 fn get_exposes_ids<'a>(
     entries: &'a [Loc<Spaced<'a, roc_parse::header::ModuleName<'a>>>],
+    opt_shorthand: Option<&'a str>,
     arena: &'a Bump,
     module_ids: &Arc<Mutex<PackageModuleIds<'a>>>,
     ident_ids_by_module: &Arc<Mutex<IdentIdsByModule>>,
@@ -3701,8 +3824,11 @@ fn get_exposes_ids<'a>(
 
     // TODO can we "iterate unspaced" instead of calling unspace here?
     for entry in unspace(arena, entries) {
-        let module_id =
-            module_ids.get_or_insert(&PQModuleName::Unqualified(entry.value.as_str().into()));
+        let module_name = entry.value.as_str().into();
+        let module_id = module_ids.get_or_insert(&match opt_shorthand {
+            Some(shorthand) => PQModuleName::Qualified(shorthand, module_name),
+            None => PQModuleName::Unqualified(module_name),
+        });
 
         // Ensure this module has an entry in the ident_ids_by_module map.
         ident_ids_by_module.get_or_insert(module_id);
@@ -3836,6 +3962,9 @@ fn load_module<'a>(
         "Decode", ModuleId::DECODE
         "Hash", ModuleId::HASH
         "Json", ModuleId::JSON
+        "Arbitrary", ModuleId::ARBITRARY
+        "Inspect", ModuleId::INSPECT
+        "Sort", ModuleId::SORT
     }
 
     let (filename, opt_shorthand) = module_name_to_path(src_dir, &module_name, arc_shorthands);
@@ -4223,22 +4352,46 @@ fn parse_header<'a>(
             },
             parse_state,
         )) => {
-            let (module_id, _, header) = build_package_header(
+            let mut package_dir = filename.clone();
+            package_dir.pop();
+
+            let (module_id, _, resolved_header) = build_package_header(
                 arena,
                 None,
                 is_root_module,
                 filename,
                 parse_state,
-                module_ids,
-                ident_ids_by_module,
+                module_ids.clone(),
+                ident_ids_by_module.clone(),
                 &header,
                 comments,
                 module_timing,
             )?;
 
+            let packages = unspace(arena, header.packages.item.items);
+            let mut messages = Vec::with_capacity(packages.len() + 1);
+
+            // The package header must come before the headers of the packages it depends on.
+            messages.push(Msg::Header(resolved_header));
+
+            // When building docs for a package, its modules can import from the packages
+            // it depends on, so those have to be loaded too.
+            if is_root_module {
+                load_packages(
+                    packages,
+                    &mut messages,
+                    roc_cache_dir,
+                    package_dir,
+                    arena,
+                    module_id,
+                    module_ids,
+                    ident_ids_by_module,
+                );
+            }
+
             Ok(HeaderOutput {
                 module_id,
-                msg: Msg::Header(header),
+                msg: Msg::Many(messages),
                 opt_platform_shorthand: None,
             })
         }
@@ -4252,6 +4405,7 @@ fn parse_header<'a>(
         )) => {
             let exposes_ids = get_exposes_ids(
                 header.exposes.item.items,
+                None,
                 arena,
                 &module_ids,
                 &ident_ids_by_module,
@@ -4774,6 +4928,7 @@ impl<'a> BuildTask<'a> {
         dep_idents: IdentIdsByModule,
         declarations: Declarations,
         cached_subs: CachedTypeState,
+        cache_entry: Option<PathBuf>,
         derived_module: SharedDerivedModule,
     ) -> Self {
         let exposed_by_module = exposed_types.retain_modules(imported_modules.keys());
@@ -4795,6 +4950,7 @@ impl<'a> BuildTask<'a> {
             dep_idents,
             module_timing,
             cached_subs,
+            cache_entry,
             derived_module,
         }
     }
@@ -5158,6 +5314,49 @@ fn run_solve_solve(
     )
 }
 
+/// The key of this module in the typecheck cache, or None if the module should not be cached.
+fn typecheck_cache_key(
+    cache: &TypeCheckCache,
+    cache_keys: &MutMap<ModuleId, CacheKey>,
+    parsed: &ParsedModule,
+    dep_idents: &IdentIdsByModule,
+    module_ids: &ModuleIds,
+) -> Option<CacheKey> {
+    match parsed.header_type {
+        HeaderType::Interface { .. } | HeaderType::Hosted { .. } => {}
+        // Apps and platforms are typechecked against each other (through `requires` and
+        // `provides`), so their types do not only follow from their own source and imports.
+        _ => return None,
+    }
+
+    let mut key = cache.key_builder();
+
+    key.module(
+        parsed.module_id,
+        module_ids.get_name(parsed.module_id)?.as_str(),
+    );
+    key.text(parsed.src);
+    key.ident_ids(&parsed.exposed_ident_ids);
+
+    // Builtins are covered by the compiler version; everything else must be in the cache too.
+    let mut deps: Vec<_> = parsed
+        .deps_by_name
+        .values()
+        .filter(|dep_id| !dep_id.is_builtin())
+        .map(|dep_id| Some((module_ids.get_name(*dep_id)?.as_str(), *dep_id)))
+        .collect::<Option<_>>()?;
+
+    deps.sort_unstable_by_key(|(name, _)| *name);
+
+    for (name, dep_id) in deps {
+        key.module(dep_id, name);
+        key.dependency(cache_keys.get(&dep_id)?);
+        key.ident_ids(dep_idents.get(&dep_id)?);
+    }
+
+    Some(key.finish())
+}
+
 fn run_solve<'a>(
     module: Module,
     ident_ids: IdentIds,
@@ -5171,6 +5370,7 @@ fn run_solve<'a>(
     decls: Declarations,
     dep_idents: IdentIdsByModule,
     cached_types: CachedTypeState,
+    cache_entry: Option<PathBuf>,
     derived_module: SharedDerivedModule,
 ) -> Msg<'a> {
     let solve_start = Instant::now();
@@ -5185,10 +5385,19 @@ fn run_solve<'a>(
     let loc_dbgs = std::mem::take(&mut module.loc_dbgs);
     let module = module;
 
-    let (solved_subs, solved_implementations, exposed_vars_by_symbol, problems, abilities_store) = {
-        if module_id.is_builtin() {
-            match cached_types.lock().remove(&module_id) {
-                None => run_solve_solve(
+    // Don't hold the lock while solving
+    let opt_cached = cached_types.lock().remove(&module_id);
+
+    let (solved_subs, solved_implementations, exposed_vars_by_symbol, problems, abilities_store) =
+        match opt_cached {
+            None => {
+                let (
+                    solved_subs,
+                    solved_implementations,
+                    exposed_vars_by_symbol,
+                    problems,
+                    abilities_store,
+                ) = run_solve_solve(
                     exposed_for_module,
                     types,
                     constraints,
@@ -5197,33 +5406,51 @@ fn run_solve<'a>(
                     var_store,
                     module,
                     derived_module,
-                ),
-                Some(TypeState {
-                    subs,
-                    exposed_vars_by_symbol,
-                    abilities,
-                    solved_implementations,
-                }) => (
-                    Solved(subs),
-                    solved_implementations,
-                    exposed_vars_by_symbol,
-                    vec![],
-                    abilities,
-                ),
+                );
+
+                match cache_entry {
+                    // Only cache modules without type errors, so their errors get reported
+                    // again next time.
+                    Some(path) if problems.is_empty() => {
+                        let type_state = TypeState {
+                            subs: solved_subs.into_inner(),
+                            exposed_vars_by_symbol,
+                            abilities: abilities_store,
+                            solved_implementations,
+                        };
+
+                        typecheck_cache::write_entry(&path, &type_state);
+
+                        (
+                            Solved(type_state.subs),
+                            type_state.solved_implementations,
+                            type_state.exposed_vars_by_symbol,
+                            problems,
+                            type_state.abilities,
+                        )
+                    }
+                    _ => (
+                        solved_subs,
+                        solved_implementations,
+                        exposed_vars_by_symbol,
+                        problems,
+                        abilities_store,
+                    ),
+                }
             }
-        } else {
-            run_solve_solve(
-                exposed_for_module,
-                types,
-                constraints,
-                constraint,
-                pending_derives,
-                var_store,
-                module,
-                derived_module,
-            )
-        }
-    };
+            Some(TypeState {
+                subs,
+                exposed_vars_by_symbol,
+                abilities,
+                solved_implementations,
+            }) => (
+                Solved(subs),
+                solved_implementations,
+                exposed_vars_by_symbol,
+                vec![],
+                abilities,
+            ),
+        };
 
     let mut solved_subs = solved_subs;
     let exposed_types = roc_solve::module::exposed_types_storage_subs(
@@ -5290,6 +5517,7 @@ fn build_package_header<'a>(
     let packages = unspace(arena, header.packages.item.items);
     let exposes_ids = get_exposes_ids(
         header.exposes.item.items,
+        opt_shorthand,
         arena,
         &module_ids,
         &ident_ids_by_module,
@@ -5460,10 +5688,13 @@ fn canonicalize_and_constrain<'a>(
             // TODO: actually generate docs for platform and package modules.
             None
         }
+        // We're generating docs for a package or platform. Which modules its dependencies expose
+        // isn't known until all of their headers have been loaded, so document every module here;
+        // the ones nobody exposes get dropped once loading is done.
         HeaderType::Interface { name, .. }
         | HeaderType::Builtin { name, .. }
         | HeaderType::Hosted { name, .. }
-            if exposed_module_ids.contains(&parsed.module_id) =>
+            if !exposed_module_ids.is_empty() =>
         {
             let mut scope = module_output.scope.clone();
             scope.add_docs_imports();
@@ -5481,7 +5712,7 @@ fn canonicalize_and_constrain<'a>(
             Some(docs)
         }
         HeaderType::Interface { .. } | HeaderType::Builtin { .. } | HeaderType::Hosted { .. } => {
-            // This module isn't exposed by the platform, so don't generate docs for it!
+            // The root module isn't a package or platform, so there are no docs to generate.
             None
         }
     };
@@ -5536,6 +5767,9 @@ fn canonicalize_and_constrain<'a>(
                         | ModuleId::DICT
                         | ModuleId::SET
                         | ModuleId::HASH
+                        | ModuleId::ARBITRARY
+                        | ModuleId::INSPECT
+                        | ModuleId::SORT
                 );
 
                 if !name.is_builtin() || should_include_builtin {
@@ -6474,6 +6708,7 @@ fn run_task<'a>(
             declarations,
             dep_idents,
             cached_subs,
+            cache_entry,
             derived_module,
         } => Ok(run_solve(
             module,
@@ -6488,6 +6723,7 @@ fn run_task<'a>(
             declarations,
             dep_idents,
             cached_subs,
+            cache_entry,
             derived_module,
         )),
         BuildPendingSpecializations {
@@ -6709,7 +6945,11 @@ fn to_parse_problem_report<'a>(
     buf
 }
 
-fn to_missing_platform_report(module_id: ModuleId, other: &PlatformPath) -> String {
+fn to_missing_platform_report(
+    module_id: ModuleId,
+    other: &PlatformPath,
+    render: RenderTarget,
+) -> String {
     use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
     use ven_pretty::DocAllocator;
     use PlatformPath::*;
@@ -6788,7 +7028,7 @@ fn to_missing_platform_report(module_id: ModuleId, other: &PlatformPath) -> Stri
 
     let palette = DEFAULT_PALETTE;
     let mut buf = String::new();
-    report.render_color_terminal(&mut buf, &alloc, &palette);
+    report.render(render, &mut buf, &alloc, &palette);
 
     buf
 }
@@ -6,6 +6,7 @@
 use roc_module::symbol::ModuleId;
 pub mod docs;
 pub mod file;
+mod typecheck_cache;
 mod work;
 
 #[cfg(target_family = "wasm")]
@@ -24,4 +25,7 @@ pub const BUILTIN_MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode"),
     (ModuleId::HASH, "Hash"),
     (ModuleId::JSON, "Json"),
+    (ModuleId::ARBITRARY, "Arbitrary"),
+    (ModuleId::INSPECT, "Inspect"),
+    (ModuleId::SORT, "Sort"),
 ];
@@ -0,0 +1,190 @@
+//! A persistent, on-disk cache of solved module types.
+//!
+//! Builtins ship with their solved types baked into the compiler binary. This extends the same
+//! idea to user modules: after a module is solved without problems, its `TypeState` is written to
+//! `<roc cache dir>/typechecked/<key>.dat`, where the key is a hash of everything that can
+//! influence the solved `Subs`. On the next `roc` invocation a module whose key is unchanged skips
+//! constraint generation and solving entirely.
+//!
+//! We still parse and canonicalize cached modules, because later phases need their declarations.
+//! Canonicalization is deterministic, so the variables in those declarations line up with the
+//! cached `Subs`, just like they do for the builtins.
+use roc_can::module::TypeState;
+use roc_debug_flags::{dbg_set, ROC_SKIP_MODULE_CACHE};
+use roc_module::symbol::{IdentIds, ModuleId};
+use roc_packaging::cache::RocCacheDir;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Identifies one version of a module's solved types.
+pub(crate) type CacheKey = blake3::Hash;
+
+const MAGIC: &[u8; 8] = b"roctypes";
+const CHECKSUM_LEN: usize = blake3::OUT_LEN;
+const DIR_NAME: &str = "typechecked";
+const VERSION: &str = include_str!("../../../../version.txt");
+
+#[derive(Debug)]
+pub(crate) struct TypeCheckCache {
+    dir: PathBuf,
+    compiler_fingerprint: CacheKey,
+}
+
+impl TypeCheckCache {
+    /// Returns None when caching should not happen, e.g. in tests and build scripts (which use
+    /// [RocCacheDir::Disallowed]), or when the `ROC_SKIP_MODULE_CACHE` debug flag is set.
+    pub fn new(roc_cache_dir: RocCacheDir<'_>) -> Option<Self> {
+        // The builtins cache is disabled on these targets too, see `roc_load::read_cached_types`
+        if cfg!(target_family = "wasm") || cfg!(windows) {
+            return None;
+        }
+
+        if dbg_set!(ROC_SKIP_MODULE_CACHE) {
+            return None;
+        }
+
+        match roc_cache_dir {
+            RocCacheDir::Persistent(path) => Some(Self {
+                dir: path.join(DIR_NAME),
+                compiler_fingerprint: compiler_fingerprint()?,
+            }),
+            RocCacheDir::Disallowed => None,
+        }
+    }
+
+    pub fn key_builder(&self) -> CacheKeyBuilder {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.compiler_fingerprint.as_bytes());
+
+        CacheKeyBuilder(hasher)
+    }
+
+    pub fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{}.dat", key.to_hex()))
+    }
+
+    /// Any problem reading the entry (it's missing, truncated, corrupted) is a cache miss.
+    pub fn read(&self, key: &CacheKey) -> Option<TypeState> {
+        let contents = fs::read(self.entry_path(key)).ok()?;
+
+        if contents.len() < MAGIC.len() + CHECKSUM_LEN || !contents.starts_with(MAGIC) {
+            return None;
+        }
+
+        let (payload, checksum) =
+            contents[MAGIC.len()..].split_at(contents.len() - MAGIC.len() - CHECKSUM_LEN);
+
+        if blake3::hash(payload).as_bytes() != checksum {
+            return None;
+        }
+
+        // Deserialization reinterprets the bytes in place, so they need the same alignment that
+        // serialization assumed.
+        let mut aligned = vec![0u64; (payload.len() + 7) / 8];
+        let bytes = unsafe {
+            let bytes =
+                std::slice::from_raw_parts_mut(aligned.as_mut_ptr() as *mut u8, payload.len());
+            bytes.copy_from_slice(payload);
+            &*bytes
+        };
+
+        let (type_state, length) = TypeState::deserialize(bytes);
+
+        if length != payload.len() {
+            return None;
+        }
+
+        Some(type_state)
+    }
+}
+
+/// Write a cache entry. This is best-effort: if the cache dir is not writable we just don't cache.
+pub(crate) fn write_entry(path: &Path, type_state: &TypeState) {
+    let _ = try_write_entry(path, type_state);
+}
+
+fn try_write_entry(path: &Path, type_state: &TypeState) -> io::Result<()> {
+    let mut payload = Vec::new();
+    type_state.serialize(&mut payload)?;
+
+    let dir = path.parent().unwrap();
+    fs::create_dir_all(dir)?;
+
+    // Write to a temporary file first and then rename it, so that concurrent `roc` invocations
+    // never observe a partially written entry.
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(MAGIC)?;
+    file.write_all(&payload)?;
+    file.write_all(blake3::hash(&payload).as_bytes())?;
+    file.persist(path)?;
+
+    Ok(())
+}
+
+/// Hashes everything that determines the solved types of a module.
+pub(crate) struct CacheKeyBuilder(blake3::Hasher);
+
+impl CacheKeyBuilder {
+    /// ModuleIds are handed out in the order modules are discovered, which differs between runs
+    /// when loading on multiple threads. Symbols in `Subs` embed the ModuleId, so it is part of
+    /// the key.
+    pub fn module(&mut self, module_id: ModuleId, name: &str) {
+        module_id.hash(&mut HashWriter(&mut self.0));
+        self.text(name);
+    }
+
+    pub fn text(&mut self, text: &str) {
+        self.0.update(&(text.len() as u64).to_le_bytes());
+        self.0.update(text.as_bytes());
+    }
+
+    /// Like ModuleIds, IdentIds depend on the order in which modules are processed.
+    pub fn ident_ids(&mut self, ident_ids: &IdentIds) {
+        self.0.update(&(ident_ids.len() as u64).to_le_bytes());
+
+        for (_, ident) in ident_ids.ident_strs() {
+            self.text(ident);
+        }
+    }
+
+    pub fn dependency(&mut self, key: &CacheKey) {
+        self.0.update(key.as_bytes());
+    }
+
+    pub fn finish(self) -> CacheKey {
+        self.0.finalize()
+    }
+}
+
+/// Adapter to feed `std::hash::Hash` implementations into blake3
+struct HashWriter<'a>(&'a mut blake3::Hasher);
+
+impl Hasher for HashWriter<'_> {
+    fn finish(&self) -> u64 {
+        unreachable!("only used to write into the blake3 hasher")
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
+
+/// Released compilers are identified by their version. Compilers built from source all share the
+/// same version string, so also take the executable itself into account.
+fn compiler_fingerprint() -> Option<CacheKey> {
+    let exe = fs::metadata(std::env::current_exe().ok()?).ok()?;
+    let modified = exe
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(VERSION.as_bytes());
+    hasher.update(&exe.len().to_le_bytes());
+    hasher.update(&modified.as_nanos().to_le_bytes());
+
+    Some(hasher.finalize())
+}
@@ -39,6 +39,22 @@ fn load_and_typecheck(
     exposed_types: ExposedByModule,
     target_info: TargetInfo,
 ) -> Result<LoadedModule, LoadingProblem> {
+    load_and_typecheck_with_cache_dir(
+        arena,
+        filename,
+        exposed_types,
+        target_info,
+        RocCacheDir::Disallowed,
+    )
+}
+
+fn load_and_typecheck_with_cache_dir<'a>(
+    arena: &'a Bump,
+    filename: PathBuf,
+    exposed_types: ExposedByModule,
+    target_info: TargetInfo,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<LoadedModule, LoadingProblem<'a>> {
     use LoadResult::*;
 
     let load_start = LoadStart::from_path(
@@ -61,7 +77,7 @@ fn load_and_typecheck(
         load_start,
         exposed_types,
         Default::default(), // these tests will re-compile the builtins
-        RocCacheDir::Disallowed,
+        roc_cache_dir,
         load_config,
     )? {
         Monomorphized(_) => unreachable!(""),
@@ -537,6 +553,68 @@ fn iface_dep_types() {
     );
 }
 
+#[test]
+fn iface_dep_types_from_typecheck_cache() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let typechecked_dir = cache_dir.path().join("typechecked");
+    let filename = fixtures_dir()
+        .join("interface_with_deps")
+        .join("Primary.roc");
+
+    let load = || {
+        let arena = Bump::new();
+        load_and_typecheck_with_cache_dir(
+            &arena,
+            filename.clone(),
+            Default::default(),
+            TARGET_INFO,
+            RocCacheDir::Persistent(cache_dir.path()),
+        )
+        .unwrap()
+    };
+
+    let cache_entries = || {
+        let mut entries: Vec<_> = std::fs::read_dir(&typechecked_dir)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (
+                    entry.file_name(),
+                    entry.metadata().unwrap().modified().unwrap(),
+                )
+            })
+            .collect();
+        entries.sort();
+        entries
+    };
+
+    load();
+    let entries_after_first_load = cache_entries();
+
+    // Primary and the interface modules it (transitively) imports
+    assert!(entries_after_first_load.len() >= 4);
+
+    // Nothing changed, so every module comes out of the cache and nothing gets written
+    let loaded_module = load();
+    assert_eq!(cache_entries(), entries_after_first_load);
+
+    expect_types(
+        loaded_module,
+        hashmap! {
+            "blah2" => "Float *",
+            "blah3" => "Str",
+            "str" => "Str",
+            "alwaysThree" => "* -> Float *",
+            "identity" => "a -> a",
+            "z" => "Float *",
+            "w" => "Dep1.Identity {}",
+            "succeed" => "a -> Dep1.Identity a",
+            "yay" => "Res.Res {} err",
+            "withDefault" => "Res.Res a err, a -> a",
+        },
+    );
+}
+
 #[test]
 fn app_dep_types() {
     let subs_by_module = Default::default();
@@ -918,8 +996,8 @@ fn issue_2863_module_type_does_not_exist() {
                         Did you mean one of these?
 
                             Decoding
-                            Result
                             Dict
+                            Result
                             DecodeError
                         "
                       )
@@ -966,6 +1044,385 @@ fn import_builtin_in_platform_and_check_app() {
     assert!(result.is_ok(), "should check");
 }
 
+#[test]
+fn package_dependencies_and_their_importers() {
+    let modules = vec![
+        (
+            "platform/main.roc",
+            indoc!(
+                r#"
+                    platform "testplatform"
+                        requires {} { main : Str }
+                        exposes [Greeting]
+                        packages {}
+                        imports []
+                        provides [mainForHost]
+
+                    mainForHost : Str
+                    mainForHost = main
+                    "#
+            ),
+        ),
+        (
+            "platform/Greeting",
+            indoc!(
+                r#"
+                    interface Greeting
+                        exposes [hello]
+                        imports []
+
+                    hello = "Hello"
+                    "#
+            ),
+        ),
+        (
+            "Main",
+            indoc!(
+                r#"
+                    app "test"
+                        packages { pf: "platform/main.roc" }
+                        imports [pf.Greeting]
+                        provides [main] to pf
+
+                    main = Greeting.hello
+                    "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("package_dependencies_and_their_importers", modules)
+        .expect("should check");
+
+    match loaded_module.package_dependencies.as_slice() {
+        [dependency] => {
+            assert_eq!(dependency.declared_in, loaded_module.module_id);
+            assert_eq!(dependency.shorthand, "pf");
+            assert_eq!(dependency.source, "platform/main.roc");
+            assert!(dependency.root_module.ends_with("platform/main.roc"));
+            assert_eq!(dependency.imported_by, vec![loaded_module.module_id]);
+        }
+        other => panic!(
+            "Expected exactly one package dependency, but got {:?}",
+            other
+        ),
+    }
+}
+
+#[test]
+fn package_dependencies_with_the_same_shorthand() {
+    let modules = vec![
+        (
+            "platform/main.roc",
+            indoc!(
+                r#"
+                    platform "testplatform"
+                        requires {} { main : Str }
+                        exposes []
+                        packages {}
+                        imports []
+                        provides [mainForHost]
+
+                    mainForHost : Str
+                    mainForHost = main
+                    "#
+            ),
+        ),
+        (
+            "json/main.roc",
+            indoc!(
+                r#"
+                    package "json"
+                        exposes [Quote]
+                        packages {}
+                    "#
+            ),
+        ),
+        (
+            "json/Quote",
+            indoc!(
+                r#"
+                    interface Quote
+                        exposes [quote]
+                        imports []
+
+                    quote = \str -> Str.concat str "\""
+                    "#
+            ),
+        ),
+        (
+            "csv/main.roc",
+            indoc!(
+                r#"
+                    package "csv"
+                        exposes [Csv]
+                        packages { json: "json/main.roc" }
+                    "#
+            ),
+        ),
+        (
+            "csv/Csv",
+            indoc!(
+                r#"
+                    interface Csv
+                        exposes [cell]
+                        imports [json.Quote]
+
+                    cell = Quote.quote "Hello"
+                    "#
+            ),
+        ),
+        (
+            "Main",
+            indoc!(
+                r#"
+                    app "test"
+                        packages { pf: "platform/main.roc", json: "json/main.roc", csv: "csv/main.roc" }
+                        imports [csv.Csv]
+                        provides [main] to pf
+
+                    main = Csv.cell
+                    "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("package_dependencies_with_the_same_shorthand", modules)
+        .expect("should check");
+
+    let app_id = loaded_module.module_id;
+    let imported_by = |declared_by_app: bool, shorthand: &str| {
+        let dependency = loaded_module
+            .package_dependencies
+            .iter()
+            .find(|dependency| {
+                (dependency.declared_in == app_id) == declared_by_app
+                    && dependency.shorthand == shorthand
+            })
+            .unwrap_or_else(|| panic!("Expected a package dependency called {}", shorthand));
+
+        dependency
+            .imported_by
+            .iter()
+            .map(|module_id| {
+                if *module_id == app_id {
+                    "app".to_string()
+                } else {
+                    loaded_module.interns.module_name(*module_id).to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(loaded_module.package_dependencies.len(), 4);
+    assert_eq!(imported_by(true, "pf"), Vec::<String>::new());
+    assert_eq!(imported_by(true, "csv"), vec!["app"]);
+
+    // The app and the csv package both call their dependency `json`, but only csv imports from it.
+    assert_eq!(imported_by(true, "json"), Vec::<String>::new());
+    assert_eq!(imported_by(false, "json"), vec!["Csv"]);
+}
+
+#[test]
+fn docs_include_modules_exposed_by_dependency_packages() {
+    let modules = vec![
+        (
+            "dep/main.roc",
+            indoc!(
+                r#"
+                    package "dep"
+                        exposes [Dep]
+                        packages {}
+                    "#
+            ),
+        ),
+        (
+            "dep/Dep",
+            indoc!(
+                r#"
+                    interface Dep
+                        exposes [Thing, hello]
+                        imports [Internal]
+
+                    ## A thing.
+                    Thing : Str
+
+                    hello : Thing
+                    hello = Internal.greeting
+                    "#
+            ),
+        ),
+        (
+            "dep/Internal",
+            indoc!(
+                r#"
+                    interface Internal
+                        exposes [greeting]
+                        imports []
+
+                    greeting = "Hello"
+                    "#
+            ),
+        ),
+        (
+            "Greeting",
+            indoc!(
+                r#"
+                    interface Greeting
+                        exposes [greet]
+                        imports [dep.Dep]
+
+                    greet : Dep.Thing
+                    greet = Dep.hello
+                    "#
+            ),
+        ),
+        (
+            "Main",
+            indoc!(
+                r#"
+                    package "test"
+                        exposes [Greeting]
+                        packages { dep: "dep/main.roc" }
+                    "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules(
+        "docs_include_modules_exposed_by_dependency_packages",
+        modules,
+    )
+    .expect("should check");
+
+    let mut documented: Vec<_> = loaded_module
+        .docs_by_module
+        .values()
+        .map(|docs| docs.name.as_str())
+        .collect();
+    documented.sort_unstable();
+
+    // Internal isn't exposed by the dep package, so it doesn't get any docs.
+    assert_eq!(documented, vec!["Dep", "Greeting"]);
+}
+
+#[test]
+fn packages_exposing_modules_with_the_same_name() {
+    let modules = vec![
+        (
+            "platform/main.roc",
+            indoc!(
+                r#"
+                    platform "testplatform"
+                        requires {} { main : Str }
+                        exposes []
+                        packages {}
+                        imports []
+                        provides [mainForHost]
+
+                    mainForHost : Str
+                    mainForHost = main
+                    "#
+            ),
+        ),
+        (
+            "a/main.roc",
+            indoc!(
+                r#"
+                    package "a"
+                        exposes [Util, Hello]
+                        packages {}
+                    "#
+            ),
+        ),
+        (
+            "a/Util",
+            indoc!(
+                r#"
+                    interface Util
+                        exposes [value]
+                        imports []
+
+                    value = "Hello"
+                    "#
+            ),
+        ),
+        (
+            "a/Hello",
+            indoc!(
+                r#"
+                    interface Hello
+                        exposes [text]
+                        imports [Util]
+
+                    text = Util.value
+                    "#
+            ),
+        ),
+        (
+            "b/main.roc",
+            indoc!(
+                r#"
+                    package "b"
+                        exposes [Util, World]
+                        packages {}
+                    "#
+            ),
+        ),
+        (
+            "b/Util",
+            indoc!(
+                r#"
+                    interface Util
+                        exposes [value]
+                        imports []
+
+                    value = 42
+                    "#
+            ),
+        ),
+        (
+            "b/World",
+            indoc!(
+                r#"
+                    interface World
+                        exposes [text]
+                        imports [Util]
+
+                    text = Num.toStr Util.value
+                    "#
+            ),
+        ),
+        (
+            "Main",
+            indoc!(
+                r#"
+                    app "test"
+                        packages { pf: "platform/main.roc", a: "a/main.roc", b: "b/main.roc" }
+                        imports [a.Hello, b.World]
+                        provides [main] to pf
+
+                    main = Str.concat Hello.text World.text
+                    "#
+            ),
+        ),
+    ];
+
+    let loaded_module = multiple_modules("packages_exposing_modules_with_the_same_name", modules)
+        .expect("should check");
+
+    // Each package's Util is its own module, loaded from that package's directory.
+    let util_paths: Vec<_> = loaded_module
+        .sources
+        .iter()
+        .filter(|(module_id, _)| loaded_module.interns.module_name(**module_id).as_str() == "Util")
+        .map(|(_, (path, _))| path)
+        .collect();
+
+    assert_eq!(util_paths.len(), 2);
+    assert!(util_paths.iter().any(|path| path.ends_with("a/Util.roc")));
+    assert!(util_paths.iter().any(|path| path.ends_with("b/Util.roc")));
+}
+
 #[test]
 fn module_doesnt_match_file_path() {
     let modules = vec![(
@@ -120,6 +120,8 @@ pub enum LowLevel {
     BoxExpr,
     UnboxExpr,
     Unreachable,
+    PropertyRuns,
+    PropertySeed,
 }
 
 macro_rules! higher_order {
@@ -332,4 +334,6 @@ map_symbol_to_lowlevel! {
     Or <= BOOL_OR,
     Not <= BOOL_NOT,
     Unreachable <= LIST_UNREACHABLE,
+    PropertyRuns <= ARBITRARY_PROPERTY_RUNS_LOWLEVEL,
+    PropertySeed <= ARBITRARY_PROPERTY_SEED_LOWLEVEL,
 }
@@ -52,6 +52,15 @@ pub const DERIVABLE_ABILITIES: &[(Symbol, &[Symbol])] = &[
     (Symbol::DECODE_DECODING, &[Symbol::DECODE_DECODER]),
     (Symbol::HASH_HASH_ABILITY, &[Symbol::HASH_HASH]),
     (Symbol::BOOL_EQ, &[Symbol::BOOL_IS_EQ]),
+    (
+        Symbol::ARBITRARY_ARBITRARY_ABILITY,
+        &[Symbol::ARBITRARY_ARBITRARY],
+    ),
+    (
+        Symbol::INSPECT_INSPECT_ABILITY,
+        &[Symbol::INSPECT_TO_INSPECTOR],
+    ),
+    (Symbol::SORT_SORT_ABILITY, &[Symbol::SORT_COMPARE]),
 ];
 
 /// In Debug builds only, Symbol has a name() method that lets
@@ -1266,8 +1275,9 @@ define_builtins! {
         8 BOOL_EQ: "Eq" exposed_type=true
         9 BOOL_IS_EQ: "isEq"
         10 BOOL_IS_EQ_IMPL: "boolIsEq"
-        unexposed 11 BOOL_STRUCTURAL_EQ: "structuralEq"
-        unexposed 12 BOOL_STRUCTURAL_NOT_EQ: "structuralNotEq"
+        11 BOOL_COMPARE: "compare"
+        unexposed 12 BOOL_STRUCTURAL_EQ: "structuralEq"
+        unexposed 13 BOOL_STRUCTURAL_NOT_EQ: "structuralNotEq"
     }
     5 STR: "Str" => {
         0 STR_STR: "Str" exposed_apply_type=true // the Str.Str type alias
@@ -1329,6 +1339,7 @@ define_builtins! {
         56 STR_IS_VALID_SCALAR: "isValidScalar"
         57 STR_RELEASE_EXCESS_CAPACITY: "releaseExcessCapacity"
         58 STR_WALK_UTF8: "walkUtf8"
+        59 STR_COMPARE: "compare"
     }
     6 LIST: "List" => {
         0 LIST_LIST: "List" exposed_apply_type=true // the List.List type alias
@@ -1413,6 +1424,7 @@ define_builtins! {
         79 LIST_WALK_FROM_UNTIL: "walkFromUntil"
         80 LIST_ITER_HELP: "iterHelp"
         81 LIST_RELEASE_EXCESS_CAPACITY: "releaseExcessCapacity"
+        82 LIST_COMPARE: "compare"
     }
     7 RESULT: "Result" => {
         0 RESULT_RESULT: "Result" exposed_type=true // the Result.Result type alias
@@ -1454,6 +1466,7 @@ define_builtins! {
         21 DICT_UPDATE: "update"
 
         22 DICT_LIST_GET_UNSAFE: "listGetUnsafe"
+        23 DICT_TO_SORTED_LIST: "toSortedList"
     }
     9 SET: "Set" => {
         0 SET_SET: "Set" exposed_type=true // the Set.Set type alias
@@ -1473,6 +1486,7 @@ define_builtins! {
         14 SET_CONTAINS: "contains"
         15 SET_TO_DICT: "toDict"
         16 SET_CAPACITY: "capacity"
+        17 SET_TO_SORTED_LIST: "toSortedList"
     }
     10 BOX: "Box" => {
         0 BOX_BOX_TYPE: "Box" exposed_apply_type=true // the Box.Box opaque type
@@ -1538,6 +1552,13 @@ define_builtins! {
         26 DECODE_FROM_BYTES_PARTIAL: "fromBytesPartial"
         27 DECODE_FROM_BYTES: "fromBytes"
         28 DECODE_MAP_RESULT: "mapResult"
+        29 DECODE_TAG: "tag"
+        30 DECODE_SET: "set"
+        31 DECODE_DICT: "dict"
+        32 DECODE_OPTIONAL_FIELD: "OptionalField" exposed_type=true
+        33 DECODE_OPTIONAL_FIELD_FROM_RESULT: "optionalFieldFromResult"
+        34 DECODE_OPTIONAL_FIELD_TO_RESULT: "optionalFieldToResult"
+        35 DECODE_DECODE_MISSING_FIELD: "decodeMissingField"
     }
     13 HASH: "Hash" => {
         0 HASH_HASH_ABILITY: "Hash" exposed_type=true
@@ -1564,6 +1585,75 @@ define_builtins! {
     14 JSON: "Json" => {
         0 JSON_JSON: "Json"
     }
-
-    num_modules: 15 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
+    15 ARBITRARY: "Arbitrary" => {
+        0 ARBITRARY_ARBITRARY_ABILITY: "Arbitrary" exposed_type=true
+        1 ARBITRARY_ARBITRARY: "arbitrary"
+        2 ARBITRARY_SOURCE: "Source"
+        3 ARBITRARY_GENERATED: "Generated"
+        4 ARBITRARY_DRAW: "draw"
+        5 ARBITRARY_MAP: "map"
+        6 ARBITRARY_BOOL: "bool"
+        7 ARBITRARY_U8: "u8"
+        8 ARBITRARY_U16: "u16"
+        9 ARBITRARY_U32: "u32"
+        10 ARBITRARY_U64: "u64"
+        11 ARBITRARY_U128: "u128"
+        12 ARBITRARY_I8: "i8"
+        13 ARBITRARY_I16: "i16"
+        14 ARBITRARY_I32: "i32"
+        15 ARBITRARY_I64: "i64"
+        16 ARBITRARY_I128: "i128"
+        17 ARBITRARY_NAT: "nat"
+        18 ARBITRARY_F32: "f32"
+        19 ARBITRARY_F64: "f64"
+        20 ARBITRARY_DEC: "dec"
+        21 ARBITRARY_STR: "str"
+        22 ARBITRARY_LIST: "list"
+        23 ARBITRARY_CONFIG: "Config"
+        24 ARBITRARY_CHECK_RESULT: "CheckResult"
+        25 ARBITRARY_DEFAULT_CONFIG: "defaultConfig"
+        26 ARBITRARY_CHECK: "check"
+        27 ARBITRARY_CHECK_WITH: "checkWith"
+        28 ARBITRARY_PROPERTY_RUNS_LOWLEVEL: "propertyRunsLowlevel"
+        29 ARBITRARY_PROPERTY_SEED_LOWLEVEL: "propertySeedLowlevel"
+    }
+    16 INSPECT: "Inspect" => {
+        0 INSPECT_INSPECT_ABILITY: "Inspect" exposed_type=true
+        1 INSPECT_INSPECTOR: "Inspector" exposed_type=true
+        2 INSPECT_INSPECT_FORMATTER: "InspectFormatter" exposed_type=true
+        3 INSPECT_TO_INSPECTOR: "toInspector"
+        4 INSPECT_BOOL: "bool"
+        5 INSPECT_STR: "str"
+        6 INSPECT_U8: "u8"
+        7 INSPECT_U16: "u16"
+        8 INSPECT_U32: "u32"
+        9 INSPECT_U64: "u64"
+        10 INSPECT_U128: "u128"
+        11 INSPECT_I8: "i8"
+        12 INSPECT_I16: "i16"
+        13 INSPECT_I32: "i32"
+        14 INSPECT_I64: "i64"
+        15 INSPECT_I128: "i128"
+        16 INSPECT_NAT: "nat"
+        17 INSPECT_F32: "f32"
+        18 INSPECT_F64: "f64"
+        19 INSPECT_DEC: "dec"
+        20 INSPECT_LIST: "list"
+        21 INSPECT_SET: "set"
+        22 INSPECT_DICT: "dict"
+        23 INSPECT_RECORD: "record"
+        24 INSPECT_TUPLE: "tuple"
+        25 INSPECT_TAG: "tag"
+        26 INSPECT_OPAQUE: "opaque"
+        27 INSPECT_CUSTOM: "custom"
+        28 INSPECT_APPLY: "apply"
+        29 INSPECT_DBG_FORMATTER: "DbgFormatter"
+        30 INSPECT_TO_STR: "toStr"
+    }
+    17 SORT: "Sort" => {
+        0 SORT_SORT_ABILITY: "Sort" exposed_type=true
+        1 SORT_COMPARE: "compare"
+    }
+
+    num_modules: 18 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
 }
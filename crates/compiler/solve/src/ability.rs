@@ -302,6 +302,24 @@ impl ObligationCache {
 
             Symbol::BOOL_EQ => Some(DeriveEq::is_derivable(self, abilities_store, subs, var)),
 
+            Symbol::ARBITRARY_ARBITRARY_ABILITY => Some(DeriveArbitrary::is_derivable(
+                self,
+                abilities_store,
+                subs,
+                var,
+            )),
+
+            Symbol::INSPECT_INSPECT_ABILITY => Some(DeriveInspect::is_derivable(
+                self,
+                abilities_store,
+                subs,
+                var,
+            )),
+
+            Symbol::SORT_SORT_ABILITY => {
+                Some(DeriveSort::is_derivable(self, abilities_store, subs, var))
+            }
+
             _ => None,
         };
 
@@ -380,6 +398,9 @@ impl ObligationCache {
             DeriveDecoding::ABILITY => DeriveDecoding::is_derivable_builtin_opaque(opaque),
             DeriveEq::ABILITY => DeriveEq::is_derivable_builtin_opaque(opaque),
             DeriveHash::ABILITY => DeriveHash::is_derivable_builtin_opaque(opaque),
+            DeriveArbitrary::ABILITY => DeriveArbitrary::is_derivable_builtin_opaque(opaque),
+            DeriveInspect::ABILITY => DeriveInspect::is_derivable_builtin_opaque(opaque),
+            DeriveSort::ABILITY => DeriveSort::is_derivable_builtin_opaque(opaque),
             _ => false,
         };
 
@@ -528,6 +549,13 @@ trait DerivableVisitor {
         false
     }
 
+    /// Whether the type arguments of a builtin opaque without a declared implementation must
+    /// themselves be derivable.
+    #[inline(always)]
+    fn descend_into_builtin_opaque(_symbol: Symbol) -> bool {
+        false
+    }
+
     #[inline(always)]
     fn visit_recursion(var: Variable) -> Result<Descend, NotDerivable> {
         Err(NotDerivable {
@@ -802,7 +830,7 @@ trait DerivableVisitor {
                         stack.push(real_var)
                     }
                 }
-                Alias(opaque, _alias_variables, _real_var, AliasKind::Opaque) => {
+                Alias(opaque, alias_variables, _real_var, AliasKind::Opaque) => {
                     if obligation_cache
                         .check_opaque_and_read(abilities_store, opaque, Self::ABILITY)
                         .is_err()
@@ -813,6 +841,12 @@ trait DerivableVisitor {
                             context: NotDerivableContext::Opaque(opaque),
                         });
                     }
+
+                    if Self::descend_into_builtin_opaque(opaque)
+                        && !abilities_store.has_declared_implementation(opaque, Self::ABILITY)
+                    {
+                        push_var_slice!(alias_variables.type_variables())
+                    }
                 }
                 Alias(symbol, _alias_variables, real_var, AliasKind::Structural) => {
                     let descend = Self::visit_alias(var, symbol)?;
@@ -955,6 +989,13 @@ impl DerivableVisitor for DeriveDecoding {
     fn is_derivable_builtin_opaque(symbol: Symbol) -> bool {
         (is_builtin_number_alias(symbol) && !is_builtin_nat_alias(symbol))
             || is_builtin_bool_alias(symbol)
+            || matches!(symbol, Symbol::DICT_DICT | Symbol::SET_SET)
+    }
+
+    /// `Dict`s and `Set`s are decoded from their entries, which must be decodable themselves.
+    #[inline(always)]
+    fn descend_into_builtin_opaque(symbol: Symbol) -> bool {
+        matches!(symbol, Symbol::DICT_DICT | Symbol::SET_SET)
     }
 
     #[inline(always)]
@@ -1170,6 +1211,319 @@ impl DerivableVisitor for DeriveHash {
     }
 }
 
+struct DeriveArbitrary;
+impl DerivableVisitor for DeriveArbitrary {
+    const ABILITY: Symbol = Symbol::ARBITRARY_ARBITRARY_ABILITY;
+    const ABILITY_SLICE: SubsSlice<Symbol> = Subs::AB_ARBITRARY;
+
+    #[inline(always)]
+    fn is_derivable_builtin_opaque(symbol: Symbol) -> bool {
+        is_builtin_number_alias(symbol) || is_builtin_bool_alias(symbol)
+    }
+
+    #[inline(always)]
+    fn visit_recursion(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_apply(var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        if matches!(symbol, Symbol::LIST_LIST | Symbol::STR_STR) {
+            Ok(Descend(true))
+        } else {
+            Err(NotDerivable {
+                var,
+                context: NotDerivableContext::NoContext,
+            })
+        }
+    }
+
+    #[inline(always)]
+    fn visit_record(
+        subs: &Subs,
+        var: Variable,
+        fields: RecordFields,
+    ) -> Result<Descend, NotDerivable> {
+        for (field_name, _, field) in fields.iter_all() {
+            if subs[field].is_optional() {
+                return Err(NotDerivable {
+                    var,
+                    context: NotDerivableContext::Decode(NotDerivableDecode::OptionalRecordField(
+                        subs[field_name].clone(),
+                    )),
+                });
+            }
+        }
+
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tuple(
+        _subs: &Subs,
+        _var: Variable,
+        _elems: TupleElems,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_recursive_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_function_or_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_empty_record(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_empty_tag_union(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_alias(_var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        if is_builtin_number_alias(symbol) {
+            Ok(Descend(false))
+        } else {
+            Ok(Descend(true))
+        }
+    }
+
+    #[inline(always)]
+    fn visit_ranged_number(_var: Variable, _range: NumericRange) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_floating_point_content(
+        _var: Variable,
+        _subs: &mut Subs,
+        _content_var: Variable,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(false))
+    }
+}
+
+struct DeriveInspect;
+impl DerivableVisitor for DeriveInspect {
+    const ABILITY: Symbol = Symbol::INSPECT_INSPECT_ABILITY;
+    const ABILITY_SLICE: SubsSlice<Symbol> = Subs::AB_INSPECT;
+
+    /// Every opaque type can be inspected; those that do not implement `Inspect` themselves are
+    /// shown as `<opaque>`, and `Dict`s and `Set`s show their entries.
+    #[inline(always)]
+    fn is_derivable_builtin_opaque(_symbol: Symbol) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn descend_into_builtin_opaque(symbol: Symbol) -> bool {
+        matches!(symbol, Symbol::DICT_DICT | Symbol::SET_SET)
+    }
+
+    #[inline(always)]
+    fn visit_recursion(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_apply(var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        if matches!(symbol, Symbol::LIST_LIST | Symbol::STR_STR) {
+            Ok(Descend(true))
+        } else {
+            Err(NotDerivable {
+                var,
+                context: NotDerivableContext::NoContext,
+            })
+        }
+    }
+
+    #[inline(always)]
+    fn visit_record(
+        _subs: &Subs,
+        _var: Variable,
+        _fields: RecordFields,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tuple(
+        _subs: &Subs,
+        _var: Variable,
+        _elems: TupleElems,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_recursive_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_function_or_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_empty_record(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_empty_tag_union(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_alias(_var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        if is_builtin_number_alias(symbol) {
+            Ok(Descend(false))
+        } else {
+            Ok(Descend(true))
+        }
+    }
+
+    #[inline(always)]
+    fn visit_ranged_number(_var: Variable, _range: NumericRange) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_floating_point_content(
+        _var: Variable,
+        _subs: &mut Subs,
+        _content_var: Variable,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(false))
+    }
+}
+
+struct DeriveSort;
+impl DerivableVisitor for DeriveSort {
+    const ABILITY: Symbol = Symbol::SORT_SORT_ABILITY;
+    const ABILITY_SLICE: SubsSlice<Symbol> = Subs::AB_SORT;
+
+    #[inline(always)]
+    fn is_derivable_builtin_opaque(symbol: Symbol) -> bool {
+        is_builtin_number_alias(symbol) || is_builtin_bool_alias(symbol)
+    }
+
+    #[inline(always)]
+    fn visit_recursion(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_apply(var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        if matches!(symbol, Symbol::LIST_LIST | Symbol::STR_STR) {
+            Ok(Descend(true))
+        } else {
+            Err(NotDerivable {
+                var,
+                context: NotDerivableContext::NoContext,
+            })
+        }
+    }
+
+    #[inline(always)]
+    fn visit_record(
+        subs: &Subs,
+        var: Variable,
+        fields: RecordFields,
+    ) -> Result<Descend, NotDerivable> {
+        for (field_name, _, field) in fields.iter_all() {
+            if subs[field].is_optional() {
+                return Err(NotDerivable {
+                    var,
+                    context: NotDerivableContext::Decode(NotDerivableDecode::OptionalRecordField(
+                        subs[field_name].clone(),
+                    )),
+                });
+            }
+        }
+
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tuple(
+        _subs: &Subs,
+        _var: Variable,
+        _elems: TupleElems,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_recursive_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_function_or_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_empty_record(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_empty_tag_union(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_alias(_var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        if is_builtin_number_alias(symbol) {
+            Ok(Descend(false))
+        } else {
+            Ok(Descend(true))
+        }
+    }
+
+    #[inline(always)]
+    fn visit_ranged_number(_var: Variable, _range: NumericRange) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    /// Floats are ordered by `Num.compare` like all other numbers, so that `List.sortAsc` keeps
+    /// working on them.
+    #[inline(always)]
+    fn visit_floating_point_content(
+        _var: Variable,
+        _subs: &mut Subs,
+        _content_var: Variable,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(false))
+    }
+}
+
 struct DeriveEq;
 impl DerivableVisitor for DeriveEq {
     const ABILITY: Symbol = Symbol::BOOL_EQ;
@@ -1389,6 +1743,21 @@ pub(crate) fn builtin_module_with_unlisted_ability_impl(module_id: ModuleId) ->
     matches!(module_id, ModuleId::NUM | ModuleId::BOOL)
 }
 
+/// Ability members that every opaque type implements, even if it does not declare an
+/// implementation. Such opaques get a derived implementation that does not look inside them.
+pub(crate) fn member_implemented_by_all_opaques(ability_member: Symbol) -> bool {
+    matches!(ability_member, Symbol::INSPECT_TO_INSPECTOR)
+}
+
+/// Builtin opaque types whose implementation of `ability_member` is derived from the types they
+/// are parameterized over, rather than declared by the opaque.
+pub(crate) fn builtin_opaque_with_derived_impl(opaque: Symbol, ability_member: Symbol) -> bool {
+    matches!(
+        (opaque, ability_member),
+        (Symbol::DICT_DICT | Symbol::SET_SET, Symbol::DECODE_DECODER)
+    )
+}
+
 #[derive(Debug)]
 pub enum ResolveError {
     NonDerivableAbility(Symbol),
@@ -1438,7 +1807,9 @@ pub fn resolve_ability_specialization<R: AbilityResolver>(
 
     let resolved = match obligated {
         Obligated::Opaque(symbol) => {
-            if builtin_module_with_unlisted_ability_impl(symbol.module_id()) {
+            if builtin_module_with_unlisted_ability_impl(symbol.module_id())
+                || builtin_opaque_with_derived_impl(symbol, ability_member)
+            {
                 let derive_key = roc_derive_key::Derived::builtin_with_builtin_symbol(
                     ability_member.try_into().map_err(NonDerivableAbility)?,
                     symbol,
@@ -1451,18 +1822,24 @@ pub fn resolve_ability_specialization<R: AbilityResolver>(
                     ability_member,
                 };
 
-                match resolver
-                    .get_implementation(impl_key)
-                    .ok_or(NoTypeImplementingSpecialization)?
-                {
-                    roc_types::types::MemberImpl::Impl(spec_symbol) => {
+                match resolver.get_implementation(impl_key) {
+                    Some(roc_types::types::MemberImpl::Impl(spec_symbol)) => {
                         Resolved::Specialization(spec_symbol)
                     }
                     // TODO this is not correct. We can replace `Resolved` with `MemberImpl` entirely,
                     // which will make this simpler.
-                    roc_types::types::MemberImpl::Error => {
+                    Some(roc_types::types::MemberImpl::Error) => {
                         Resolved::Specialization(Symbol::UNDERSCORE)
                     }
+                    None if member_implemented_by_all_opaques(ability_member) => {
+                        let derive_key = roc_derive_key::Derived::builtin_with_builtin_symbol(
+                            ability_member.try_into().map_err(NonDerivableAbility)?,
+                            symbol,
+                        )?;
+
+                        Resolved::Derive(derive_key)
+                    }
+                    None => return Err(NoTypeImplementingSpecialization),
                 }
             }
         }
@@ -25,7 +25,10 @@ use roc_types::{
 use roc_unify::unify::{unify, Env as UEnv, Mode, MustImplementConstraints};
 
 use crate::{
-    ability::builtin_module_with_unlisted_ability_impl,
+    ability::{
+        builtin_module_with_unlisted_ability_impl, builtin_opaque_with_derived_impl,
+        member_implemented_by_all_opaques,
+    },
     solve::{deep_copy_var_in, introduce, Pools},
 };
 
@@ -629,7 +632,9 @@ fn make_specialization_decision<P: Phase>(
     use SpecializationTypeKey::*;
     match subs.get_content_without_compacting(var) {
         Alias(opaque, _, _, AliasKind::Opaque)
-            if !builtin_module_with_unlisted_ability_impl(opaque.module_id()) =>
+            if !builtin_module_with_unlisted_ability_impl(opaque.module_id())
+                && !builtin_opaque_with_derived_impl(*opaque, ability_member)
+                && !uses_implicit_opaque_impl(phase, *opaque, ability_member) =>
         {
             if P::IS_LATE {
                 SpecializeDecision::Specialize(Opaque(*opaque))
@@ -705,6 +710,19 @@ fn make_specialization_decision<P: Phase>(
     }
 }
 
+/// Whether `opaque` gets the implementation of `ability_member` every opaque has, because it
+/// does not declare one of its own.
+fn uses_implicit_opaque_impl<P: Phase>(phase: &P, opaque: Symbol, ability_member: Symbol) -> bool {
+    member_implemented_by_all_opaques(ability_member)
+        && phase.with_module_abilities_store(opaque.module_id(), |abilities_store| {
+            let impl_key = ImplKey {
+                opaque,
+                ability_member,
+            };
+            abilities_store.get_implementation(impl_key).is_none()
+        })
+}
+
 #[allow(clippy::too_many_arguments)]
 fn get_specialization_lambda_set_ambient_function<P: Phase>(
     subs: &mut Subs,
@@ -56,6 +56,39 @@ impl TypeError {
             TypeError::IngestedFileUnsupportedType(..) => Fatal,
         }
     }
+
+    /// Returns the region most closely associated with this problem, if there is one.
+    /// Used by tooling (like editors) that needs to attach a problem to a location in the source.
+    pub fn region(&self) -> Option<Region> {
+        match self {
+            TypeError::BadExpr(region, ..)
+            | TypeError::BadPattern(region, ..)
+            | TypeError::CircularType(region, ..)
+            | TypeError::BadExprMissingAbility(region, ..)
+            | TypeError::BadPatternMissingAbility(region, ..)
+            | TypeError::StructuralSpecialization { region, .. }
+            | TypeError::WrongSpecialization { region, .. } => Some(*region),
+            TypeError::CircularDef(cycle_entries) => {
+                cycle_entries.first().map(|entry| entry.expr_region)
+            }
+            TypeError::UnfulfilledAbility(Unfulfilled::OpaqueUnderivable {
+                derive_region, ..
+            }) => Some(*derive_region),
+            TypeError::Exhaustive(roc_exhaustive::Error::Incomplete(region, ..))
+            | TypeError::Exhaustive(roc_exhaustive::Error::Redundant {
+                branch_region: region,
+                ..
+            })
+            | TypeError::Exhaustive(roc_exhaustive::Error::Unmatchable {
+                branch_region: region,
+                ..
+            }) => Some(*region),
+            TypeError::UnfulfilledAbility(_)
+            | TypeError::UnexposedLookup(_)
+            | TypeError::IngestedFileBadUtf8(..)
+            | TypeError::IngestedFileUnsupportedType(..) => None,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -0,0 +1,274 @@
+#![cfg(test)]
+// Even with #[allow(non_snake_case)] on individual idents, rust-analyzer issues diagnostics.
+// See https://github.com/rust-lang/rust-analyzer/issues/6541.
+// For the `v!` macro we use uppercase variables when constructing tag unions.
+#![allow(non_snake_case)]
+
+use crate::{
+    test_key_eq, test_key_neq,
+    util::{check_derivable, check_single_lset_immediate, check_underivable, derive_test},
+    v,
+};
+use insta::assert_snapshot;
+use roc_derive::synth_var;
+use roc_module::symbol::Symbol;
+use roc_types::subs::{Content, FlatType, Subs, Variable};
+
+use roc_derive_key::{
+    arbitrary::FlatArbitraryKey, DeriveBuiltin::Arbitrary, DeriveError, DeriveKey,
+};
+
+test_key_eq! {
+    Arbitrary,
+
+    same_record:
+        v!({ a: v!(U8), }), v!({ a: v!(U8), })
+    same_record_fields_diff_types:
+        v!({ a: v!(U8), }), v!({ a: v!(STR), })
+    same_record_fields_any_order:
+        v!({ a: v!(U8), b: v!(U8), c: v!(U8), }),
+        v!({ c: v!(U8), a: v!(U8), b: v!(U8), })
+    explicit_empty_record_and_implicit_empty_record:
+        v!(EMPTY_RECORD), v!({})
+
+    same_tuple:
+        v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16),))
+    same_tuple_fields_diff_types:
+        v!((v!(U8), v!(U16),)), v!((v!(U32), v!(U64),))
+
+    same_tag_union:
+        v!([ A v!(U8) v!(STR), B v!(STR) ]), v!([ A v!(U8) v!(STR), B v!(STR) ])
+    same_tag_union_tags_diff_types:
+        v!([ A v!(U8) v!(U8), B v!(U8) ]), v!([ A v!(STR) v!(STR), B v!(STR) ])
+    same_tag_union_tags_any_order:
+        v!([ A v!(U8) v!(U8), B v!(U8), C ]), v!([ C, B v!(STR), A v!(STR) v!(STR) ])
+    explicit_empty_tag_union_and_implicit_empty_tag_union:
+        v!(EMPTY_TAG_UNION), v!([])
+
+    same_recursive_tag_union:
+        v!([ Nil, Cons v!(^lst)] as lst), v!([ Nil, Cons v!(^lst)] as lst)
+    same_tag_union_and_recursive_tag_union_fields:
+        v!([ Nil, Cons v!(STR)]), v!([ Nil, Cons v!(^lst)] as lst)
+}
+
+test_key_neq! {
+    Arbitrary,
+
+    different_record_fields:
+        v!({ a: v!(U8), }), v!({ b: v!(U8), })
+    record_empty_vs_nonempty:
+        v!(EMPTY_RECORD), v!({ a: v!(U8), })
+
+    different_tuple_arities:
+        v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16), v!(U32),))
+
+    different_tag_union_tags:
+        v!([ A v!(U8) ]), v!([ B v!(U8) ])
+    tag_union_empty_vs_nonempty:
+        v!(EMPTY_TAG_UNION), v!([ B v!(U8) ])
+    different_recursive_tag_union_tags:
+        v!([ Nil, Cons v!(^lst) ] as lst), v!([ Nil, Next v!(^lst) ] as lst)
+}
+
+#[test]
+fn immediates() {
+    check_single_lset_immediate(Arbitrary, v!(U8), Symbol::ARBITRARY_U8);
+    check_single_lset_immediate(Arbitrary, v!(U16), Symbol::ARBITRARY_U16);
+    check_single_lset_immediate(Arbitrary, v!(U32), Symbol::ARBITRARY_U32);
+    check_single_lset_immediate(Arbitrary, v!(U64), Symbol::ARBITRARY_U64);
+    check_single_lset_immediate(Arbitrary, v!(U128), Symbol::ARBITRARY_U128);
+    check_single_lset_immediate(Arbitrary, v!(I8), Symbol::ARBITRARY_I8);
+    check_single_lset_immediate(Arbitrary, v!(I16), Symbol::ARBITRARY_I16);
+    check_single_lset_immediate(Arbitrary, v!(I32), Symbol::ARBITRARY_I32);
+    check_single_lset_immediate(Arbitrary, v!(I64), Symbol::ARBITRARY_I64);
+    check_single_lset_immediate(Arbitrary, v!(I128), Symbol::ARBITRARY_I128);
+    check_single_lset_immediate(Arbitrary, v!(F32), Symbol::ARBITRARY_F32);
+    check_single_lset_immediate(Arbitrary, v!(F64), Symbol::ARBITRARY_F64);
+    check_single_lset_immediate(Arbitrary, v!(DEC), Symbol::ARBITRARY_DEC);
+    check_single_lset_immediate(Arbitrary, v!(STR), Symbol::ARBITRARY_STR);
+    check_single_lset_immediate(
+        Arbitrary,
+        v!(Symbol::LIST_LIST v!(U8)),
+        Symbol::ARBITRARY_LIST,
+    );
+}
+
+#[test]
+fn optional_record_field_derive_error() {
+    check_underivable(Arbitrary, v!({ ?a: v!(U8), }), DeriveError::Underivable);
+}
+
+#[test]
+fn derivable_record_ext_flex_var() {
+    check_derivable(
+        Arbitrary,
+        v!({ a: v!(STR), }* ),
+        DeriveKey::Arbitrary(FlatArbitraryKey::Record(vec!["a".into()])),
+    );
+}
+
+#[test]
+fn derivable_empty_tuple() {
+    check_derivable(
+        Arbitrary,
+        |subs: &mut Subs| synth_var(subs, Content::Structure(FlatType::EmptyTuple)),
+        DeriveKey::Arbitrary(FlatArbitraryKey::Tuple(0)),
+    );
+}
+
+#[test]
+fn derivable_tag_with_tag_ext() {
+    check_derivable(
+        Arbitrary,
+        v!([ B v!(STR) v!(U8) ][ A v!(STR) ]),
+        DeriveKey::Arbitrary(FlatArbitraryKey::TagUnion(vec![
+            ("A".into(), 1),
+            ("B".into(), 2),
+        ])),
+    );
+}
+
+#[test]
+fn tags_ordered_by_payload_count() {
+    check_derivable(
+        Arbitrary,
+        v!([ A v!(STR) v!(U8), B, C v!(U8) ]),
+        DeriveKey::Arbitrary(FlatArbitraryKey::TagUnion(vec![
+            ("B".into(), 0),
+            ("C".into(), 1),
+            ("A".into(), 2),
+        ])),
+    );
+}
+
+#[test]
+fn empty_record() {
+    derive_test(Arbitrary, v!(EMPTY_RECORD), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for {}
+        # Source -[[arbitrary_{}(0)]]-> { source : Source, value : {} }
+        # { drawn : List U64, mode : [Random U64, Replay (List U64)] } -[[arbitrary_{}(0)]]-> { source : { drawn : List U64, mode : [Random U64, Replay (List U64)] }, value : {} }
+        # Specialization lambda sets:
+        #   @<1>: [[arbitrary_{}(0)]]
+        #Derived.arbitrary_{} =
+          \#Derived.source -> { value: {}, source: #Derived.source }
+        "###
+        )
+    })
+}
+
+#[test]
+fn two_field_record() {
+    derive_test(Arbitrary, v!({ a: v!(U8), b: v!(STR), }), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for { a : U8, b : Str }
+        # Source -[[arbitrary_{a,b}(0)]]-> { source : Source, value : { a : a, b : a1 } } | a has Arbitrary, a1 has Arbitrary
+        # { drawn : List U64, mode : [Random U64, Replay (List U64)] } -[[arbitrary_{a,b}(0)]]-> { source : { drawn : List U64, mode : [Random U64, Replay (List U64)] }, value : { a : a, b : a1 } } | a has Arbitrary, a1 has Arbitrary
+        # Specialization lambda sets:
+        #   @<1>: [[arbitrary_{a,b}(0)]]
+        #Derived.arbitrary_{a,b} =
+          \#Derived.source ->
+            #Derived.generated = arbitrary #Derived.source
+            #Derived.generated2 = arbitrary #Derived.generated.source
+            {
+              value: { b: #Derived.generated2.value, a: #Derived.generated.value },
+              source: #Derived.generated2.source
+            }
+        "###
+        )
+    })
+}
+
+#[test]
+fn two_element_tuple() {
+    derive_test(Arbitrary, v!((v!(U8), v!(STR),)), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for ( U8, Str )*
+        # Source -[[arbitrary_(arity:2)(0)]]-> { source : Source, value : ( a, a1 )* } | a has Arbitrary, a1 has Arbitrary
+        # { drawn : List U64, mode : [Random U64, Replay (List U64)] } -[[arbitrary_(arity:2)(0)]]-> { source : { drawn : List U64, mode : [Random U64, Replay (List U64)] }, value : ( a, a1 )* } | a has Arbitrary, a1 has Arbitrary
+        # Specialization lambda sets:
+        #   @<1>: [[arbitrary_(arity:2)(0)]]
+        #Derived.arbitrary_(arity:2) =
+          \#Derived.source ->
+            #Derived.generated = arbitrary #Derived.source
+            #Derived.generated2 = arbitrary #Derived.generated.source
+            {
+              value: ( #Derived.generated.value, #Derived.generated2.value ),
+              source: #Derived.generated2.source
+            }
+        "###
+        )
+    })
+}
+
+#[test]
+fn tag_one_label_newtype() {
+    derive_test(Arbitrary, v!([A v!(U8) v!(STR)]), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [A U8 Str]
+        # Source -[[arbitrary_[A 2](0)]]-> { source : Source, value : [A a a1] } | a has Arbitrary, a1 has Arbitrary
+        # { drawn : List U64, mode : [Random U64, Replay (List U64)] } -[[arbitrary_[A 2](0)]]-> { source : { drawn : List U64, mode : [Random U64, Replay (List U64)] }, value : [A a a1] } | a has Arbitrary, a1 has Arbitrary
+        # Specialization lambda sets:
+        #   @<1>: [[arbitrary_[A 2](0)]]
+        #Derived.arbitrary_[A 2] =
+          \#Derived.source ->
+            #Derived.generated = arbitrary #Derived.source
+            #Derived.generated2 = arbitrary #Derived.generated.source
+            {
+              value: A #Derived.generated.value #Derived.generated2.value,
+              source: #Derived.generated2.source
+            }
+        "###
+        )
+    })
+}
+
+#[test]
+fn tag_two_labels_no_payloads() {
+    derive_test(Arbitrary, v!([A, B]), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [A, B]
+        # Source -[[arbitrary_[A 0,B 0](0)]]-> { source : Source, value : [A, B] }
+        # { drawn : List U64, mode : [Random U64, Replay (List U64)] } -[[arbitrary_[A 0,B 0](0)]]-> { source : { drawn : List U64, mode : [Random U64, Replay (List U64)] }, value : [A, B] }
+        # Specialization lambda sets:
+        #   @<1>: [[arbitrary_[A 0,B 0](0)]]
+        #Derived.arbitrary_[A 0,B 0] =
+          \#Derived.source ->
+            #Derived.generated = draw #Derived.source 1
+            when #Derived.generated.value is
+              0 -> { value: A, source: #Derived.generated.source }
+              _ -> { value: B, source: #Derived.generated.source }
+        "###
+        )
+    })
+}
+
+#[test]
+fn recursive_tag_union() {
+    derive_test(
+        Arbitrary,
+        v!([Nil, Cons v!(U8) v!(^lst) ] as lst),
+        |golden| {
+            assert_snapshot!(golden, @r###"
+        # derived for [Cons U8 $rec, Nil] as $rec
+        # Source -[[arbitrary_[Nil 0,Cons 2](0)]]-> { source : Source, value : [Cons a a1, Nil] } | a has Arbitrary, a1 has Arbitrary
+        # { drawn : List U64, mode : [Random U64, Replay (List U64)] } -[[arbitrary_[Nil 0,Cons 2](0)]]-> { source : { drawn : List U64, mode : [Random U64, Replay (List U64)] }, value : [Cons a a1, Nil] } | a has Arbitrary, a1 has Arbitrary
+        # Specialization lambda sets:
+        #   @<1>: [[arbitrary_[Nil 0,Cons 2](0)]]
+        #Derived.arbitrary_[Nil 0,Cons 2] =
+          \#Derived.source ->
+            #Derived.generated = draw #Derived.source 1
+            when #Derived.generated.value is
+              0 -> { value: Nil, source: #Derived.generated.source }
+              _ ->
+                #Derived.generated2 = arbitrary #Derived.generated.source
+                #Derived.generated3 = arbitrary #Derived.generated2.source
+                {
+                  value: Cons #Derived.generated2.value #Derived.generated3.value,
+                  source: #Derived.generated3.source
+                }
+        "###
+            )
+        },
+    )
+}
@@ -0,0 +1,238 @@
+#![cfg(test)]
+// Even with #[allow(non_snake_case)] on individual idents, rust-analyzer issues diagnostics.
+// See https://github.com/rust-lang/rust-analyzer/issues/6541.
+// For the `v!` macro we use uppercase variables when constructing tag unions.
+#![allow(non_snake_case)]
+
+use insta::assert_snapshot;
+
+use crate::{
+    test_key_eq, test_key_neq,
+    util::{check_derivable, check_immediate, derive_test},
+    v,
+};
+use roc_derive_key::{inspect::FlatInspectableKey, DeriveBuiltin::ToInspector, DeriveKey};
+use roc_module::symbol::Symbol;
+use roc_types::subs::Variable;
+
+// {{{ hash tests
+
+test_key_eq! {
+    ToInspector,
+
+    same_record:
+        v!({ a: v!(U8), }), v!({ a: v!(U8), })
+    same_record_fields_diff_types:
+        v!({ a: v!(U8), }), v!({ a: v!(STR), })
+    same_record_fields_any_order:
+        v!({ a: v!(U8), b: v!(U8), c: v!(U8), }),
+        v!({ c: v!(U8), a: v!(U8), b: v!(U8), })
+    explicit_empty_record_and_implicit_empty_record:
+        v!(EMPTY_RECORD), v!({})
+
+    same_tuple:
+        v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16),))
+    same_tuple_fields_diff_types:
+        v!((v!(U8), v!(U16),)), v!((v!(U32), v!(U64),))
+
+    same_tag_union:
+        v!([ A v!(U8) v!(STR), B v!(STR) ]), v!([ A v!(U8) v!(STR), B v!(STR) ])
+    same_tag_union_tags_diff_types:
+        v!([ A v!(U8) v!(U8), B v!(U8) ]), v!([ A v!(STR) v!(STR), B v!(STR) ])
+    same_tag_union_tags_any_order:
+        v!([ A v!(U8) v!(U8), B v!(U8), C ]), v!([ C, B v!(STR), A v!(STR) v!(STR) ])
+    explicit_empty_tag_union_and_implicit_empty_tag_union:
+        v!(EMPTY_TAG_UNION), v!([])
+
+    same_recursive_tag_union:
+        v!([ Nil, Cons v!(^lst)] as lst), v!([ Nil, Cons v!(^lst)] as lst)
+    same_tag_union_and_recursive_tag_union_fields:
+        v!([ Nil, Cons v!(STR)]), v!([ Nil, Cons v!(^lst)] as lst)
+
+    list_list_diff_types:
+        v!(Symbol::LIST_LIST v!(STR)), v!(Symbol::LIST_LIST v!(U8))
+    set_set_diff_types:
+        v!(Symbol::SET_SET v!(STR)), v!(Symbol::SET_SET v!(U8))
+    dict_dict_diff_types:
+        v!(Symbol::DICT_DICT v!(STR) v!(STR)), v!(Symbol::DICT_DICT v!(U8) v!(U8))
+}
+
+test_key_neq! {
+    ToInspector,
+
+    different_record_fields:
+        v!({ a: v!(U8), }), v!({ b: v!(U8), })
+    record_empty_vs_nonempty:
+        v!(EMPTY_RECORD), v!({ a: v!(U8), })
+
+    different_tuple_arities:
+        v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16), v!(U32),))
+
+    different_tag_union_tags:
+        v!([ A v!(U8) ]), v!([ B v!(U8) ])
+    tag_union_empty_vs_nonempty:
+        v!(EMPTY_TAG_UNION), v!([ B v!(U8) ])
+    different_recursive_tag_union_tags:
+        v!([ Nil, Cons v!(^lst) ] as lst), v!([ Nil, Next v!(^lst) ] as lst)
+
+    list_vs_set:
+        v!(Symbol::LIST_LIST v!(U8)), v!(Symbol::SET_SET v!(U8))
+}
+
+// }}} hash tests
+
+// {{{ deriver tests
+
+#[test]
+fn immediates() {
+    check_immediate(ToInspector, v!(U8), Symbol::INSPECT_U8);
+    check_immediate(ToInspector, v!(U16), Symbol::INSPECT_U16);
+    check_immediate(ToInspector, v!(U32), Symbol::INSPECT_U32);
+    check_immediate(ToInspector, v!(U64), Symbol::INSPECT_U64);
+    check_immediate(ToInspector, v!(U128), Symbol::INSPECT_U128);
+    check_immediate(ToInspector, v!(I8), Symbol::INSPECT_I8);
+    check_immediate(ToInspector, v!(I16), Symbol::INSPECT_I16);
+    check_immediate(ToInspector, v!(I32), Symbol::INSPECT_I32);
+    check_immediate(ToInspector, v!(I64), Symbol::INSPECT_I64);
+    check_immediate(ToInspector, v!(I128), Symbol::INSPECT_I128);
+    check_immediate(ToInspector, v!(DEC), Symbol::INSPECT_DEC);
+    check_immediate(ToInspector, v!(F32), Symbol::INSPECT_F32);
+    check_immediate(ToInspector, v!(F64), Symbol::INSPECT_F64);
+    check_immediate(ToInspector, v!(STR), Symbol::INSPECT_STR);
+}
+
+#[test]
+fn opaque_is_not_looked_into() {
+    check_immediate(
+        ToInspector,
+        v!(@Symbol::ATTR_ATTR => v!([ True, False ])),
+        Symbol::INSPECT_OPAQUE,
+    );
+}
+
+#[test]
+fn derivable_record_ext_flex_var() {
+    check_derivable(
+        ToInspector,
+        v!({ a: v!(STR), }* ),
+        DeriveKey::ToInspector(FlatInspectableKey::Record(vec!["a".into()])),
+    );
+}
+
+#[test]
+fn derivable_record_with_record_ext() {
+    check_derivable(
+        ToInspector,
+        v!({ b: v!(STR), }{ a: v!(STR), } ),
+        DeriveKey::ToInspector(FlatInspectableKey::Record(vec!["a".into(), "b".into()])),
+    );
+}
+
+#[test]
+fn derivable_tag_with_tag_ext() {
+    check_derivable(
+        ToInspector,
+        v!([ B v!(STR) v!(U8) ][ A v!(STR) ]),
+        DeriveKey::ToInspector(FlatInspectableKey::TagUnion(vec![
+            ("A".into(), 1),
+            ("B".into(), 2),
+        ])),
+    );
+}
+
+#[test]
+fn list() {
+    derive_test(ToInspector, v!(Symbol::LIST_LIST v!(STR)), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for List Str
+        # List val -[[toInspector_list(0)]]-> Inspector f | f has InspectFormatter, val has Inspect
+        # List val -[[toInspector_list(0)]]-> (f -[[custom(4) (List val)]]-> f) | f has InspectFormatter, val has Inspect
+        # Specialization lambda sets:
+        #   @<1>: [[toInspector_list(0)]]
+        #   @<2>: [[custom(4) (List val)]] | val has Inspect
+        #Derived.toInspector_list =
+          \#Derived.lst ->
+            custom
+              \#Derived.fmt ->
+                apply
+                  (list #Derived.lst \#Derived.elem -> toInspector #Derived.elem)
+                  #Derived.fmt
+        "###
+        )
+    })
+}
+
+#[test]
+fn two_field_record() {
+    derive_test(ToInspector, v!({ a: v!(U8), b: v!(STR), }), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for { a : U8, b : Str }
+        # { a : val, b : val1 } -[[toInspector_{a,b}(0)]]-> Inspector f | f has InspectFormatter, val has Inspect, val1 has Inspect
+        # { a : val, b : val1 } -[[toInspector_{a,b}(0)]]-> (f -[[custom(2) { a : val, b : val1 }]]-> f) | f has InspectFormatter, val has Inspect, val1 has Inspect
+        # Specialization lambda sets:
+        #   @<1>: [[toInspector_{a,b}(0)]]
+        #   @<2>: [[custom(2) { a : val, b : val1 }]] | val has Inspect, val1 has Inspect
+        #Derived.toInspector_{a,b} =
+          \#Derived.rcd ->
+            custom
+              \#Derived.fmt ->
+                apply
+                  (record
+                    [
+                      { value: toInspector #Derived.rcd.a, key: "a" },
+                      { value: toInspector #Derived.rcd.b, key: "b" },
+                    ])
+                  #Derived.fmt
+        "###
+        )
+    })
+}
+
+#[test]
+fn two_field_tuple() {
+    derive_test(ToInspector, v!((v!(U8), v!(STR),)), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for ( U8, Str )*
+        # ( val, val1 )* -[[toInspector_(arity:2)(0)]]-> Inspector f | f has InspectFormatter, val has Inspect, val1 has Inspect
+        # ( val, val1 )a -[[toInspector_(arity:2)(0)]]-> (f -[[custom(2) ( val, val1 )a]]-> f) | f has InspectFormatter, val has Inspect, val1 has Inspect
+        # Specialization lambda sets:
+        #   @<1>: [[toInspector_(arity:2)(0)]]
+        #   @<2>: [[custom(2) ( val, val1 )*]] | val has Inspect, val1 has Inspect
+        #Derived.toInspector_(arity:2) =
+          \#Derived.tup ->
+            custom
+              \#Derived.fmt ->
+                apply
+                  (tuple [toInspector #Derived.tup.0, toInspector #Derived.tup.1])
+                  #Derived.fmt
+        "###
+        )
+    })
+}
+
+#[test]
+fn tag_two_labels() {
+    derive_test(ToInspector, v!([A v!(U8) v!(STR), B]), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [A U8 Str, B]
+        # [A val val1, B] -[[toInspector_[A 2,B 0](0)]]-> Inspector f | f has InspectFormatter, val has Inspect, val1 has Inspect
+        # [A val val1, B] -[[toInspector_[A 2,B 0](0)]]-> (f -[[custom(4) [A val val1, B]]]-> f) | f has InspectFormatter, val has Inspect, val1 has Inspect
+        # Specialization lambda sets:
+        #   @<1>: [[toInspector_[A 2,B 0](0)]]
+        #   @<2>: [[custom(4) [A val val1, B]]] | val has Inspect, val1 has Inspect
+        #Derived.toInspector_[A 2,B 0] =
+          \#Derived.tag ->
+            custom
+              \#Derived.fmt ->
+                apply
+                  (when #Derived.tag is
+                    A #Derived.2 #Derived.3 ->
+                      tag "A" [toInspector #Derived.2, toInspector #Derived.3]
+                    B -> tag "B" [])
+                  #Derived.fmt
+        "###
+        )
+    })
+}
+
+// }}} deriver tests
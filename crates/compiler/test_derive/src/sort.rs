@@ -0,0 +1,221 @@
+#![cfg(test)]
+// Even with #[allow(non_snake_case)] on individual idents, rust-analyzer issues diagnostics.
+// See https://github.com/rust-lang/rust-analyzer/issues/6541.
+// For the `v!` macro we use uppercase variables when constructing tag unions.
+#![allow(non_snake_case)]
+
+use crate::{
+    test_key_eq, test_key_neq,
+    util::{check_derivable, check_single_lset_immediate, check_underivable, derive_test},
+    v,
+};
+use insta::assert_snapshot;
+use roc_module::symbol::Symbol;
+use roc_types::subs::Variable;
+
+use roc_derive_key::{sort::FlatSortKey, DeriveBuiltin::Compare, DeriveError, DeriveKey};
+
+test_key_eq! {
+    Compare,
+
+    same_record:
+        v!({ a: v!(U8), }), v!({ a: v!(U8), })
+    same_record_fields_diff_types:
+        v!({ a: v!(U8), }), v!({ a: v!(STR), })
+    same_record_fields_any_order:
+        v!({ a: v!(U8), b: v!(U8), c: v!(U8), }),
+        v!({ c: v!(U8), a: v!(U8), b: v!(U8), })
+    explicit_empty_record_and_implicit_empty_record:
+        v!(EMPTY_RECORD), v!({})
+
+    same_tuple:
+        v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16),))
+    same_tuple_fields_diff_types:
+        v!((v!(U8), v!(U16),)), v!((v!(U32), v!(U64),))
+
+    same_tag_union:
+        v!([ A v!(U8) v!(STR), B v!(STR) ]), v!([ A v!(U8) v!(STR), B v!(STR) ])
+    same_tag_union_tags_diff_types:
+        v!([ A v!(U8) v!(U8), B v!(U8) ]), v!([ A v!(STR) v!(STR), B v!(STR) ])
+    same_tag_union_tags_any_order:
+        v!([ A v!(U8) v!(U8), B v!(U8), C ]), v!([ C, B v!(STR), A v!(STR) v!(STR) ])
+    explicit_empty_tag_union_and_implicit_empty_tag_union:
+        v!(EMPTY_TAG_UNION), v!([])
+
+    same_recursive_tag_union:
+        v!([ Nil, Cons v!(^lst)] as lst), v!([ Nil, Cons v!(^lst)] as lst)
+    same_tag_union_and_recursive_tag_union_fields:
+        v!([ Nil, Cons v!(STR)]), v!([ Nil, Cons v!(^lst)] as lst)
+}
+
+test_key_neq! {
+    Compare,
+
+    different_record_fields:
+        v!({ a: v!(U8), }), v!({ b: v!(U8), })
+    record_empty_vs_nonempty:
+        v!(EMPTY_RECORD), v!({ a: v!(U8), })
+
+    different_tuple_arities:
+        v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16), v!(U32),))
+
+    different_tag_union_tags:
+        v!([ A v!(U8) ]), v!([ B v!(U8) ])
+    tag_union_empty_vs_nonempty:
+        v!(EMPTY_TAG_UNION), v!([ B v!(U8) ])
+    different_recursive_tag_union_tags:
+        v!([ Nil, Cons v!(^lst) ] as lst), v!([ Nil, Next v!(^lst) ] as lst)
+}
+
+#[test]
+fn immediates() {
+    check_single_lset_immediate(Compare, v!(U8), Symbol::NUM_COMPARE);
+    check_single_lset_immediate(Compare, v!(I64), Symbol::NUM_COMPARE);
+    check_single_lset_immediate(Compare, v!(U128), Symbol::NUM_COMPARE);
+    check_single_lset_immediate(Compare, v!(DEC), Symbol::NUM_COMPARE);
+    check_single_lset_immediate(Compare, v!(F64), Symbol::NUM_COMPARE);
+    check_single_lset_immediate(Compare, v!(STR), Symbol::STR_COMPARE);
+    check_single_lset_immediate(Compare, v!(Symbol::LIST_LIST v!(U8)), Symbol::LIST_COMPARE);
+    check_single_lset_immediate(Compare, v!(Symbol::LIST_LIST v!(STR)), Symbol::LIST_COMPARE);
+}
+
+#[test]
+fn optional_record_field_derive_error() {
+    check_underivable(Compare, v!({ ?a: v!(U8), }), DeriveError::Underivable);
+}
+
+#[test]
+fn dict_is_underivable() {
+    check_underivable(
+        Compare,
+        v!(Symbol::DICT_DICT v!(STR) v!(U8)),
+        DeriveError::Underivable,
+    );
+}
+
+#[test]
+fn derivable_record_with_record_ext() {
+    check_derivable(
+        Compare,
+        v!({ b: v!(STR), }{ a: v!(STR), } ),
+        DeriveKey::Compare(FlatSortKey::Record(vec!["a".into(), "b".into()])),
+    );
+}
+
+#[test]
+fn derivable_tag_with_tag_ext() {
+    check_derivable(
+        Compare,
+        v!([ B v!(STR) v!(U8) ][ A v!(STR) ]),
+        DeriveKey::Compare(FlatSortKey::TagUnion(vec![
+            ("A".into(), 1),
+            ("B".into(), 2),
+        ])),
+    );
+}
+
+#[test]
+fn empty_record() {
+    derive_test(Compare, v!(EMPTY_RECORD), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for {}
+        # {}, {} -[[compare_{}(0)]]-> [EQ, GT, LT]
+        # {}, {} -[[compare_{}(0)]]-> [EQ, GT, LT]
+        # Specialization lambda sets:
+        #   @<1>: [[compare_{}(0)]]
+        #Derived.compare_{} = \#Derived.lhs, #Derived.rhs -> EQ
+        "###
+        )
+    })
+}
+
+#[test]
+fn two_field_record() {
+    derive_test(Compare, v!({ a: v!(U8), b: v!(STR), }), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for { a : U8, b : Str }
+        # { a : a, b : a1 }, { a : a, b : a1 } -[[compare_{a,b}(0)]]-> [EQ, GT, LT] | a has Sort, a1 has Sort
+        # { a : a, b : a1 }, { a : a, b : a1 } -[[compare_{a,b}(0)]]-> [EQ, GT, LT] | a has Sort, a1 has Sort
+        # Specialization lambda sets:
+        #   @<1>: [[compare_{a,b}(0)]]
+        #Derived.compare_{a,b} =
+          \#Derived.lhs, #Derived.rhs ->
+            when compare #Derived.lhs.a #Derived.rhs.a is
+              EQ -> compare #Derived.lhs.b #Derived.rhs.b
+              #Derived.order -> #Derived.order
+        "###
+        )
+    })
+}
+
+#[test]
+fn two_element_tuple() {
+    derive_test(Compare, v!((v!(U8), v!(STR),)), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for ( U8, Str )*
+        # ( a, a1 )b, ( a, a1 )b -[[compare_(arity:2)(0)]]-> [EQ, GT, LT] | a has Sort, a1 has Sort
+        # ( a, a1 )b, ( a, a1 )b -[[compare_(arity:2)(0)]]-> [EQ, GT, LT] | a has Sort, a1 has Sort
+        # Specialization lambda sets:
+        #   @<1>: [[compare_(arity:2)(0)]]
+        #Derived.compare_(arity:2) =
+          \#Derived.lhs, #Derived.rhs ->
+            when compare #Derived.lhs.0 #Derived.rhs.0 is
+              EQ -> compare #Derived.lhs.1 #Derived.rhs.1
+              #Derived.order -> #Derived.order
+        "###
+        )
+    })
+}
+
+#[test]
+fn tag_one_label_newtype() {
+    derive_test(Compare, v!([A v!(U8) v!(STR)]), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [A U8 Str]
+        # [A a a1], [A a a1] -[[compare_[A 2](0)]]-> [EQ, GT, LT] | a has Sort, a1 has Sort
+        # [A a a1], [A a a1] -[[compare_[A 2](0)]]-> [EQ, GT, LT] | a has Sort, a1 has Sort
+        # Specialization lambda sets:
+        #   @<1>: [[compare_[A 2](0)]]
+        #Derived.compare_[A 2] =
+          \#Derived.lhs, #Derived.rhs ->
+            when #Derived.lhs is
+              A #Derived.3 #Derived.4 ->
+                when #Derived.rhs is
+                  A #Derived.5 #Derived.6 ->
+                    when compare #Derived.3 #Derived.5 is
+                      EQ -> compare #Derived.4 #Derived.6
+                      #Derived.order -> #Derived.order
+        "###
+        )
+    })
+}
+
+#[test]
+fn tag_three_labels() {
+    derive_test(Compare, v!([A v!(U8), B, C v!(STR)]), |golden| {
+        assert_snapshot!(golden, @r###"
+        # derived for [A U8, B, C Str]
+        # [A a, B, C a1], [A a, B, C a1] -[[compare_[A 1,B 0,C 1](0)]]-> [EQ, GT, LT] | a has Sort, a1 has Sort
+        # [A a, B, C a1], [A a, B, C a1] -[[compare_[A 1,B 0,C 1](0)]]-> [EQ, GT, LT] | a has Sort, a1 has Sort
+        # Specialization lambda sets:
+        #   @<1>: [[compare_[A 1,B 0,C 1](0)]]
+        #Derived.compare_[A 1,B 0,C 1] =
+          \#Derived.lhs, #Derived.rhs ->
+            when #Derived.lhs is
+              A #Derived.3 ->
+                when #Derived.rhs is
+                  A #Derived.4 -> compare #Derived.3 #Derived.4
+                  B | C _ -> LT
+              B ->
+                when #Derived.rhs is
+                  B -> EQ
+                  A _ -> GT
+                  C _ -> LT
+              C #Derived.5 ->
+                when #Derived.rhs is
+                  C #Derived.6 -> compare #Derived.5 #Derived.6
+                  A _ | B -> GT
+        "###
+        )
+    })
+}
@@ -1,8 +1,11 @@
 #![cfg(test)]
 
+mod arbitrary;
 mod decoding;
 mod encoding;
 mod eq;
 mod hash;
+mod inspect;
+mod sort;
 
 mod util;
@@ -1402,6 +1402,278 @@ fn decode_tuple_of_tuples() {
     )
 }
 
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_tag_union() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            Shape : [Circle U8, Rect U8 U8, Empty]
+
+            main =
+                result : Result (List Shape) _
+                result = Str.toUtf8 "[{\"Rect\":[3,4]},{\"Empty\":[]}]" |> Decode.fromBytes Json.fromUtf8
+                when result is
+                    Ok shapes if shapes == [Rect 3 4, Empty] -> "abcd"
+                    _ -> "something went wrong"
+            "#
+        ),
+        RocStr::from("abcd"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_recursive_tag_union() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            Tree : [Leaf, Node Tree U8 Tree]
+
+            sum : Tree -> U8
+            sum = \tree ->
+                when tree is
+                    Leaf -> 0
+                    Node left n right -> sum left + n + sum right
+
+            main =
+                input = Str.toUtf8 "{\"Node\":[{\"Leaf\":[]},1,{\"Node\":[{\"Leaf\":[]},2,{\"Leaf\":[]}]}]}"
+                result : Result Tree _
+                result = Decode.fromBytes input Json.fromUtf8
+                when result is
+                    Ok tree -> sum tree
+                    Err _ -> 0
+            "#
+        ),
+        3,
+        u8
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_set() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                result : Result (Set Str) _
+                result = Str.toUtf8 "[\"a\",\"b\",\"a\"]" |> Decode.fromBytes Json.fromUtf8
+                when result is
+                    Ok set -> Set.len set
+                    Err _ -> 0
+            "#
+        ),
+        2,
+        usize
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_dict() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                result : Result (Dict Str U8) _
+                result = Str.toUtf8 "[[\"a\",1],[\"b\",2]]" |> Decode.fromBytes Json.fromUtf8
+                when result is
+                    Ok dict -> Dict.get dict "b" |> Result.withDefault 0
+                    Err _ -> 0
+            "#
+        ),
+        2,
+        u8
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_record_optional_field() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            Language : { name : Str, year : Decode.OptionalField U16 }
+
+            describe : Result Language _ -> Str
+            describe = \result ->
+                when result is
+                    Ok { name, year } ->
+                        when Decode.optionalFieldToResult year is
+                            Ok _ -> Str.concat name " with a year"
+                            Err Missing -> Str.concat name " without a year"
+
+                    Err _ -> "error"
+
+            main =
+                withYear = Str.toUtf8 "{\"name\":\"a\",\"year\":2023}" |> Decode.fromBytes Json.fromUtf8
+                withoutYear = Str.toUtf8 "{\"name\":\"b\"}" |> Decode.fromBytes Json.fromUtf8
+                withoutName = Str.toUtf8 "{\"year\":2023}" |> Decode.fromBytes Json.fromUtf8
+
+                Str.joinWith [describe withYear, describe withoutYear, describe withoutName] ", "
+            "#
+        ),
+        RocStr::from("a with a year, b without a year, error"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_record_ignores_unknown_fields() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                input = Str.toUtf8 "{\"tags\":[\"a\",{\"b\":\"}\"}],\"id\":7,\"ok\":true}"
+                result : Result { id : U8 } _
+                result = Decode.fromBytes input Json.fromUtf8
+                when result is
+                    Ok { id } -> id
+                    Err _ -> 0
+            "#
+        ),
+        7,
+        u8
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_record_rejects_unknown_fields() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                input = Str.toUtf8 "{\"id\":7,\"ok\":true}"
+                result : Result { id : U8 } _
+                result = Decode.fromBytes input (Json.fromUtf8With { unknownFields: Reject })
+                when result is
+                    Ok { id } -> id
+                    Err _ -> 0
+            "#
+        ),
+        0,
+        u8
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn encode_record_snake_case_field_names() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                result = Str.fromUtf8 (Encode.toBytes { firstName: "a", userId: 1u8 } (Json.toUtf8With { fieldNameMapping: SnakeCase }))
+                when result is
+                    Ok s -> s
+                    _ -> "<bad>"
+            "#
+        ),
+        RocStr::from(r#"{"first_name":"a","user_id":1}"#),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn decode_record_kebab_case_field_names() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                input = Str.toUtf8 "{\"first-name\":\"a\",\"user-id\":1}"
+                result : Result { firstName : Str, userId : U8 } _
+                result = Decode.fromBytes input (Json.fromUtf8With { fieldNameMapping: KebabCase })
+                when result is
+                    Ok { firstName, userId } -> Str.concat firstName (Num.toStr userId)
+                    Err _ -> "<bad>"
+            "#
+        ),
+        RocStr::from("a1"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(all(
+    any(feature = "gen-llvm", feature = "gen-wasm"),
+    not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+))]
+fn encode_decode_record_custom_field_names() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" imports [Json] provides [main] to "./platform"
+
+            main =
+                fmt = Json.fromUtf8With {
+                    fieldNameMapping: Custom {
+                        toObjectName: \name -> Str.concat "$" name,
+                        fromObjectName: \name -> Str.replaceFirst name "$" "" |> Result.withDefault name,
+                    },
+                }
+                bytes = Encode.toBytes { name: "a" } fmt
+                result : Result { name : Str } _
+                result = Decode.fromBytes bytes fmt
+                when (Str.fromUtf8 bytes, result) is
+                    (Ok s, Ok { name }) -> Str.concat s name
+                    _ -> "<bad>"
+            "#
+        ),
+        RocStr::from(r#"{"$name":"a"}a"#),
+        RocStr
+    )
+}
+
 #[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
 mod hash {
     #[cfg(feature = "gen-llvm")]
@@ -2234,6 +2234,76 @@ fn issue_4749() {
     )
 }
 
+#[mono_test]
+fn decode_derived_tag_union() {
+    indoc!(
+        r###"
+        app "test" imports [Json] provides [main] to "./platform"
+
+        main =
+            input = Str.toUtf8 "{\"A\":[1,\"two\"]}"
+            result : Result [A U8 Str, B] _
+            result = Decode.fromBytes input Json.fromUtf8
+            when result is
+                Ok (A n _) -> n
+                _ -> 0
+        "###
+    )
+}
+
+#[mono_test]
+fn decode_derived_set_and_dict() {
+    indoc!(
+        r###"
+        app "test" imports [Json] provides [main] to "./platform"
+
+        main =
+            set : Result (Set U8) _
+            set = Decode.fromBytes (Str.toUtf8 "[1,2]") Json.fromUtf8
+            dict : Result (Dict Str U8) _
+            dict = Decode.fromBytes (Str.toUtf8 "[[\"a\",1]]") Json.fromUtf8
+            when (set, dict) is
+                (Ok s, Ok d) -> Set.len s + Dict.len d
+                _ -> 0
+        "###
+    )
+}
+
+#[mono_test]
+fn decode_derived_record_optional_field() {
+    indoc!(
+        r###"
+        app "test" imports [Json] provides [main] to "./platform"
+
+        main =
+            input = Str.toUtf8 "{\"name\":\"Roc\",\"extra\":[1]}"
+            result : Result { name : Str, year : Decode.OptionalField U16 } _
+            result = Decode.fromBytes input Json.fromUtf8
+            when result is
+                Ok { name, year } if Decode.optionalFieldToResult year == Err Missing -> name
+                _ -> ""
+        "###
+    )
+}
+
+#[mono_test]
+fn encode_decode_derived_record_snake_case() {
+    indoc!(
+        r###"
+        app "test" imports [Json] provides [main] to "./platform"
+
+        main =
+            fmt = Json.fromUtf8With { fieldNameMapping: SnakeCase }
+            bytes = Encode.toBytes { firstName: "Roc" } fmt
+            result : Result { firstName : Str } _
+            result = Decode.fromBytes bytes fmt
+            when result is
+                Ok { firstName } -> firstName
+                Err _ -> ""
+        "###
+    )
+}
+
 #[mono_test(mode = "test")]
 fn lambda_set_with_imported_toplevels_issue_4733() {
     indoc!(
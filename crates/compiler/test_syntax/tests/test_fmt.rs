@@ -49,8 +49,15 @@ mod test_fmt {
         }
     }
 
+    fn new_buf(arena: &Bump, opt_max_width: Option<usize>) -> Buf<'_> {
+        match opt_max_width {
+            Some(max_width) => Buf::with_max_width_in(arena, max_width),
+            None => Buf::new_in(arena),
+        }
+    }
+
     // Not intended to be used directly in tests; please use module_formats_to or module_formats_same
-    fn expect_format_module_helper(src: &str, expected: &str) {
+    fn expect_format_module_helper(src: &str, expected: &str, opt_max_width: Option<usize>) {
         let arena = Bump::new();
         let src = src.trim();
         let expected = expected.trim();
@@ -59,7 +66,7 @@ mod test_fmt {
             Ok((actual, state)) => {
                 use roc_fmt::spaces::RemoveSpaces;
 
-                let mut buf = Buf::new_in(&arena);
+                let mut buf = new_buf(&arena, opt_max_width);
 
                 fmt_module_and_defs(&arena, src, &actual, state, &mut buf);
 
@@ -91,7 +98,7 @@ mod test_fmt {
                 }
 
                 // Now verify that the resultant formatting is _stable_ - i.e. that it doesn't change again if re-formatted
-                let mut reformatted_buf = Buf::new_in(&arena);
+                let mut reformatted_buf = new_buf(&arena, opt_max_width);
 
                 fmt_module_and_defs(&arena, output, &reparsed_ast, state, &mut reformatted_buf);
 
@@ -117,17 +124,22 @@ mod test_fmt {
 
     fn module_formats_to(input: &str, expected: &str) {
         // First check that input formats to the expected version
-        expect_format_module_helper(input, expected);
+        expect_format_module_helper(input, expected, None);
 
         // Parse the expected result format it, asserting that it doesn't change
         // It's important that formatting be stable / idempotent
-        expect_format_module_helper(expected, expected);
+        expect_format_module_helper(expected, expected, None);
     }
 
     fn module_formats_same(input: &str) {
         module_formats_to(input, input);
     }
 
+    fn module_formats_to_with_max_width(input: &str, expected: &str, max_width: usize) {
+        expect_format_module_helper(input, expected, Some(max_width));
+        expect_format_module_helper(expected, expected, Some(max_width));
+    }
+
     // STRING LITERALS
 
     #[test]
@@ -5843,6 +5855,209 @@ mod test_fmt {
         );
     }
 
+    // MAX WIDTH
+
+    #[test]
+    fn max_width_breaks_list() {
+        module_formats_to_with_max_width(
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                names = ["Alice", "Bob", "Carol", "Dave", "Eve"]
+
+                short = [1, 2, 3]
+                "#
+            ),
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                names = [
+                    "Alice",
+                    "Bob",
+                    "Carol",
+                    "Dave",
+                    "Eve",
+                ]
+
+                short = [1, 2, 3]
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_only_breaks_outer_collection() {
+        module_formats_to_with_max_width(
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                people = [{ name: "Alice", age: 30 }, { name: "Bob", age: 25 }]
+                "#
+            ),
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                people = [
+                    { name: "Alice", age: 30 },
+                    { name: "Bob", age: 25 },
+                ]
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_record() {
+        module_formats_to_with_max_width(
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                point = { x: someLongFunction 1 2, y: someOtherFunction 3 4 }
+                "#
+            ),
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                point = {
+                    x: someLongFunction 1 2,
+                    y: someOtherFunction 3 4,
+                }
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_apply_args() {
+        module_formats_to_with_max_width(
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                greeting = Str.concat (Str.concat "Hello, " name) (Str.concat "!" suffix)
+                "#
+            ),
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                greeting = Str.concat
+                    (Str.concat "Hello, " name)
+                    (Str.concat "!" suffix)
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_closure_body() {
+        module_formats_to_with_max_width(
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                total = List.walk numbers 0 \state, elem -> state + elem
+                "#
+            ),
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                total = List.walk numbers 0 \state, elem ->
+                    state + elem
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_pipeline() {
+        module_formats_to_with_max_width(
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                result = numbers |> List.map double |> List.keepIf isEven |> List.sum
+                "#
+            ),
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                result =
+                    numbers
+                    |> List.map double
+                    |> List.keepIf isEven
+                    |> List.sum
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_types() {
+        module_formats_to_with_max_width(
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                Color : [Red, Green, Blue, Cyan, Magenta, Yellow, Black]
+
+                walk : List elem, state, (state, elem -> state) -> state
+
+                step : state, elem -> state
+                "#
+            ),
+            indoc!(
+                r#"
+                interface Foo exposes [] imports []
+
+                Color : [
+                    Red,
+                    Green,
+                    Blue,
+                    Cyan,
+                    Magenta,
+                    Yellow,
+                    Black,
+                ]
+
+                walk :
+                    List elem,
+                    state,
+                    (state, elem -> state)
+                    -> state
+
+                step : state, elem -> state
+                "#
+            ),
+            40,
+        );
+    }
+
+    #[test]
+    fn without_max_width_long_lines_stay() {
+        module_formats_same(indoc!(
+            r#"
+            interface Foo exposes [] imports []
+
+            names = ["Alice", "Bob", "Carol", "Dave", "Eve", "Frank", "Grace", "Heidi", "Ivan", "Judy"]
+            "#
+        ));
+    }
+
     // this is a parse error atm
     //    #[test]
     //    fn multiline_apply() {
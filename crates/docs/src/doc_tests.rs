@@ -0,0 +1,142 @@
+//! Finding the ```roc code blocks in `##` doc comments, so that `roc test --doc` can check
+//! that they still compile, and that their `expect`s pass.
+use crate::module_pages;
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag};
+use roc_load::docs::DocEntry;
+use roc_load::LoadedModule;
+
+/// A fenced code block marked as Roc code, from the docs of an exposed module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocTest {
+    /// The module whose docs contain the code block
+    pub module_name: String,
+    /// The documented value or type, or None if the block is in the module's own docs
+    pub def_name: Option<String>,
+    pub code: String,
+}
+
+impl DocTest {
+    /// e.g. `Json` for the module's own docs, or `Str.concat`
+    pub fn name(&self) -> String {
+        match &self.def_name {
+            Some(def_name) => format!("{}.{}", self.module_name, def_name),
+            None => self.module_name.clone(),
+        }
+    }
+
+    /// An interface module with the code block at its top level, so that any `expect`s
+    /// in it are top-level `expect`s. It imports the documented module if the block refers to it.
+    pub fn module_source(&self, module_name: &str) -> String {
+        let imports = if self.code.contains(&format!("{}.", self.module_name)) {
+            self.module_name.as_str()
+        } else {
+            ""
+        };
+
+        format!(
+            "interface {}\n    exposes []\n    imports [{}]\n\n{}",
+            module_name, imports, self.code
+        )
+    }
+}
+
+/// The doc tests in every module that the root package or platform exposes.
+/// Modules from the packages it depends on are skipped; those get tested with their own package.
+pub fn doc_tests(loaded_module: &LoadedModule) -> Vec<DocTest> {
+    let module_pages = module_pages(loaded_module);
+    let mut doc_tests = Vec::new();
+
+    for (module_id, module) in loaded_module.docs_by_module.iter() {
+        match module_pages.get(module_id) {
+            Some(page) if page.path == module.name => {}
+            _ => continue,
+        }
+
+        for entry in &module.entries {
+            let (def_name, docs) = match entry {
+                DocEntry::DocDef(doc_def) => match &doc_def.docs {
+                    Some(docs) => (Some(&doc_def.name), docs),
+                    None => continue,
+                },
+                DocEntry::DetachedDoc(docs) => (None, docs),
+            };
+
+            for code in roc_code_blocks(docs) {
+                doc_tests.push(DocTest {
+                    module_name: module.name.clone(),
+                    def_name: def_name.cloned(),
+                    code,
+                });
+            }
+        }
+    }
+
+    doc_tests
+}
+
+/// The fenced code blocks whose info string starts with `roc`, except for the ones
+/// which are also marked `unchecked`, e.g. ```roc,unchecked
+pub fn roc_code_blocks(markdown: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut opt_block: Option<String> = None;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if is_checked_roc(&info) => {
+                opt_block = Some(String::new());
+            }
+            Event::Text(text) => {
+                if let Some(block) = opt_block.as_mut() {
+                    block.push_str(&text);
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                blocks.extend(opt_block.take());
+            }
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+fn is_checked_roc(info: &str) -> bool {
+    let mut words = info
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty());
+
+    words.next() == Some("roc") && !words.any(|word| word == "unchecked")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{roc_code_blocks, DocTest};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn only_checked_roc_blocks() {
+        let markdown = "Some text.\n\n```roc\nx = 1\n\nexpect x == 1\n```\n\n```\nnot = roc\n```\n\n```roc,unchecked\nnope\n```\n\n```roc repl\ny = 2\n```\n";
+
+        assert_eq!(
+            roc_code_blocks(markdown),
+            vec![
+                "x = 1\n\nexpect x == 1\n".to_string(),
+                "y = 2\n".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn module_source_imports_documented_module() {
+        let doc_test = DocTest {
+            module_name: "Json".to_string(),
+            def_name: None,
+            code: "expect Json.toUtf8 == Json.toUtf8\n".to_string(),
+        };
+
+        assert_eq!(
+            doc_test.module_source("DocTest"),
+            "interface DocTest\n    exposes []\n    imports [Json]\n\nexpect Json.toUtf8 == Json.toUtf8\n"
+        );
+    }
+}
@@ -4,11 +4,11 @@ extern crate pulldown_cmark;
 extern crate roc_load;
 use bumpalo::Bump;
 use roc_can::scope::Scope;
-use roc_collections::VecSet;
-use roc_load::docs::{DocEntry, TypeAnnotation};
+use roc_collections::{MutMap, VecSet};
+use roc_load::docs::{DocDef, DocEntry, TypeAnnotation};
 use roc_load::docs::{ModuleDocumentation, RecordField};
 use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
-use roc_module::symbol::{Interns, Symbol};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_packaging::cache::{self, RocCacheDir};
 use roc_parse::ident::{parse_ident, Accessor, Ident};
 use roc_parse::state::State;
@@ -16,13 +16,28 @@ use roc_region::all::Region;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+pub mod doc_tests;
+
 const BUILD_DIR: &str = "./generated-docs";
 
+/// Where builtins which aren't part of the docs being generated get linked to
+const BUILTINS_DOCS_URL: &str = "https://www.roc-lang.org/builtins/";
+
 const LINK_SVG: &str = include_str!("./static/link.svg");
 
+/// Where a module's docs go, and what the sidebar calls it
+struct ModulePage {
+    /// Relative to the base URL, e.g. "Str" - or "json/Json" for the Json module of the package
+    /// whose shorthand is `json`
+    path: String,
+    /// e.g. "Str" or "json.Json"
+    label: String,
+}
+
 pub fn generate_docs_html(root_file: PathBuf) {
     let build_dir = Path::new(BUILD_DIR);
     let loaded_module = load_module_for_docs(root_file);
+    let module_pages = module_pages(&loaded_module);
 
     // TODO get these from the platform's source file rather than hardcoding them!
     let package_name = "Documentation".to_string();
@@ -60,11 +75,10 @@ pub fn generate_docs_html(root_file: PathBuf) {
         .replace("<!-- favicon.svg -->", "/favicon.svg")
         .replace(
             "<!-- Prefetch links -->",
-            loaded_module
-                .docs_by_module
-                .iter()
-                .map(|(_, module)| {
-                    let href = sidebar_link_url(module.name.as_str());
+            module_pages
+                .values()
+                .map(|page| {
+                    let href = sidebar_link_url(page.path.as_str());
 
                     format!(r#"<link rel="prefetch" href="{href}"/>"#)
                 })
@@ -74,7 +88,7 @@ pub fn generate_docs_html(root_file: PathBuf) {
         )
         .replace(
             "<!-- Module links -->",
-            render_sidebar(loaded_module.docs_by_module.values()).as_str(),
+            render_sidebar(loaded_module.docs_by_module.iter(), &module_pages).as_str(),
         );
 
     let all_exposed_symbols = {
@@ -87,10 +101,17 @@ pub fn generate_docs_html(root_file: PathBuf) {
         set
     };
 
+    fs::write(
+        build_dir.join("search-index.json"),
+        search_index_json(&loaded_module, &module_pages, &all_exposed_symbols),
+    )
+    .expect("TODO gracefully handle failing to write the search index");
+
     // Write each package's module docs html file
-    for module_docs in loaded_module.docs_by_module.values() {
-        let module_name = module_docs.name.as_str();
-        let module_dir = build_dir.join(module_name.replace('.', "/").as_str());
+    for (module_id, module_docs) in loaded_module.docs_by_module.iter() {
+        let page = &module_pages[module_id];
+        let module_name = page.label.as_str();
+        let module_dir = build_dir.join(page.path.replace('.', "/").as_str());
 
         fs::create_dir_all(&module_dir)
             .expect("TODO gracefully handle not being able to create the module dir");
@@ -106,8 +127,13 @@ pub fn generate_docs_html(root_file: PathBuf) {
             )
             .replace(
                 "<!-- Module Docs -->",
-                render_module_documentation(module_docs, &loaded_module, &all_exposed_symbols)
-                    .as_str(),
+                render_module_documentation(
+                    module_docs,
+                    &loaded_module,
+                    &module_pages,
+                    &all_exposed_symbols,
+                )
+                .as_str(),
             );
 
         fs::write(module_dir.join("index.html"), rendered_module)
@@ -117,8 +143,53 @@ pub fn generate_docs_html(root_file: PathBuf) {
     println!("🎉 Docs generated in {}", build_dir.display());
 }
 
-fn sidebar_link_url(module_name: &str) -> String {
-    format!("{}{}", base_url(), module_name)
+fn sidebar_link_url(module_path: &str) -> String {
+    format!("{}{}", base_url(), module_path)
+}
+
+/// Modules from the packages the root depends on get their own subdirectory, named after
+/// the shorthand the root uses for that package.
+fn module_pages(loaded_module: &LoadedModule) -> MutMap<ModuleId, ModulePage> {
+    let package_dirs: Vec<(&str, &Path)> = loaded_module
+        .package_dependencies
+        .iter()
+        .filter_map(|dependency| {
+            Some((
+                dependency.shorthand.as_str(),
+                dependency.root_module.parent()?,
+            ))
+        })
+        .collect();
+
+    loaded_module
+        .docs_by_module
+        .iter()
+        .map(|(module_id, module)| {
+            let opt_module_path = loaded_module.sources.get(module_id).map(|(path, _)| path);
+
+            // A package can be nested in another one's dir, so go with the innermost match.
+            let opt_shorthand = opt_module_path.and_then(|module_path| {
+                package_dirs
+                    .iter()
+                    .filter(|(_, dir)| module_path.starts_with(dir))
+                    .max_by_key(|(_, dir)| dir.components().count())
+                    .map(|(shorthand, _)| *shorthand)
+            });
+
+            let page = match opt_shorthand {
+                Some(shorthand) => ModulePage {
+                    path: format!("{}/{}", shorthand, module.name),
+                    label: format!("{}.{}", shorthand, module.name),
+                },
+                None => ModulePage {
+                    path: module.name.clone(),
+                    label: module.name.clone(),
+                },
+            };
+
+            (*module_id, page)
+        })
+        .collect()
 }
 
 fn page_title(package_name: &str, module_name: &str) -> String {
@@ -128,9 +199,15 @@ fn page_title(package_name: &str, module_name: &str) -> String {
 fn render_module_documentation(
     module: &ModuleDocumentation,
     root_module: &LoadedModule,
+    module_pages: &MutMap<ModuleId, ModulePage>,
     all_exposed_symbols: &VecSet<Symbol>,
 ) -> String {
     let mut buf = String::new();
+    let type_links = TypeLinks {
+        scope: &module.scope,
+        interns: &root_module.interns,
+        module_pages,
+    };
 
     push_html(&mut buf, "h2", vec![("class", "module-name")], {
         let mut link_buf = String::new();
@@ -158,18 +235,7 @@ fn render_module_documentation(
 
                     push_html(&mut content, "a", vec![("href", href.as_str())], LINK_SVG);
                     push_html(&mut content, "strong", vec![], name);
-
-                    for type_var in &doc_def.type_vars {
-                        content.push(' ');
-                        content.push_str(type_var.as_str());
-                    }
-
-                    let type_ann = &doc_def.type_annotation;
-
-                    if !matches!(type_ann, TypeAnnotation::NoTypeAnn) {
-                        content.push_str(" : ");
-                        type_annotation_to_html(0, &mut content, type_ann, false);
-                    }
+                    push_type_vars_and_annotation(&mut content, doc_def, Some(&type_links));
 
                     push_html(
                         &mut buf,
@@ -185,6 +251,7 @@ fn render_module_documentation(
                             &module.scope,
                             docs,
                             root_module,
+                            module_pages,
                         );
                     }
 
@@ -198,6 +265,7 @@ fn render_module_documentation(
                     &module.scope,
                     docs,
                     root_module,
+                    module_pages,
                 );
             }
         };
@@ -206,6 +274,72 @@ fn render_module_documentation(
     buf
 }
 
+/// e.g. ` a : List a -> Nat` after the name of `List.len`
+fn push_type_vars_and_annotation(
+    buf: &mut String,
+    doc_def: &DocDef,
+    opt_type_links: Option<&TypeLinks>,
+) {
+    for type_var in &doc_def.type_vars {
+        buf.push(' ');
+        buf.push_str(type_var.as_str());
+    }
+
+    let type_ann = &doc_def.type_annotation;
+
+    if !matches!(type_ann, TypeAnnotation::NoTypeAnn) {
+        buf.push_str(" : ");
+        type_annotation_to_html(0, buf, type_ann, false, opt_type_links);
+    }
+}
+
+/// Every exposed value and type, with its signature and a summary of its docs, so that
+/// search.js can search all of them without having to load every module's page.
+fn search_index_json(
+    loaded_module: &LoadedModule,
+    module_pages: &MutMap<ModuleId, ModulePage>,
+    all_exposed_symbols: &VecSet<Symbol>,
+) -> String {
+    let mut entries = Vec::new();
+
+    for (module_id, module) in loaded_module.docs_by_module.iter() {
+        let page = &module_pages[module_id];
+
+        for entry in &module.entries {
+            if let DocEntry::DocDef(doc_def) = entry {
+                if !all_exposed_symbols.contains(&doc_def.symbol) {
+                    continue;
+                }
+
+                let name = doc_def.name.as_str();
+                let kind = if name.starts_with(char::is_uppercase) {
+                    "type"
+                } else {
+                    "value"
+                };
+
+                // Signatures in the index are plain text on one line, without links.
+                let mut signature = name.to_string();
+                push_type_vars_and_annotation(&mut signature, doc_def, None);
+                let signature = signature.split_whitespace().collect::<Vec<_>>().join(" ");
+
+                let summary = doc_def.docs.as_deref().map(summary).unwrap_or_default();
+
+                entries.push(serde_json::json!({
+                    "module": page.label,
+                    "name": name,
+                    "kind": kind,
+                    "signature": signature,
+                    "url": format!("{}{}#{}", base_url(), page.path, name),
+                    "summary": summary,
+                }));
+            }
+        }
+    }
+
+    serde_json::to_string(&entries).unwrap()
+}
+
 fn push_html(buf: &mut String, tag_name: &str, attrs: Vec<(&str, &str)>, content: impl AsRef<str>) {
     buf.push('<');
     buf.push_str(tag_name);
@@ -292,18 +426,42 @@ fn render_name_and_version(name: &str, version: &str) -> String {
     buf
 }
 
-fn render_sidebar<'a, I: Iterator<Item = &'a ModuleDocumentation>>(modules: I) -> String {
+/// The plain text of the first paragraph, which is usually a one-sentence description.
+fn summary(markdown: &str) -> String {
+    use pulldown_cmark::{Event, Tag};
+
+    let mut text = String::new();
+    let mut in_paragraph = false;
+
+    for event in pulldown_cmark::Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Paragraph) => in_paragraph = true,
+            Event::End(Tag::Paragraph) => break,
+            Event::Text(str) | Event::Code(str) if in_paragraph => text.push_str(&str),
+            Event::SoftBreak | Event::HardBreak if in_paragraph => text.push(' '),
+            _ => {}
+        }
+    }
+
+    text
+}
+
+fn render_sidebar<'a, I: Iterator<Item = (&'a ModuleId, &'a ModuleDocumentation)>>(
+    modules: I,
+    module_pages: &MutMap<ModuleId, ModulePage>,
+) -> String {
     let mut buf = String::new();
 
-    for module in modules {
-        let href = sidebar_link_url(module.name.as_str());
+    for (module_id, module) in modules {
+        let page = &module_pages[module_id];
+        let href = sidebar_link_url(page.path.as_str());
         let mut sidebar_entry_content = String::new();
 
         push_html(
             &mut sidebar_entry_content,
             "a",
             vec![("class", "sidebar-module-link"), ("href", &href)],
-            module.name.as_str(),
+            page.label.as_str(),
         );
 
         let entries = {
@@ -385,12 +543,60 @@ fn new_line(buf: &mut String) {
     buf.push('\n');
 }
 
+/// What's needed to turn the names of types in an annotation into links to their docs
+struct TypeLinks<'a> {
+    /// The scope of the module the annotation is in, for resolving unqualified names
+    scope: &'a Scope,
+    interns: &'a Interns,
+    module_pages: &'a MutMap<ModuleId, ModulePage>,
+}
+
+impl<'a> TypeLinks<'a> {
+    /// e.g. "Str", or "Json.Json" for a type from another module
+    fn url(&self, type_name: &str) -> Option<String> {
+        let symbol = match type_name.rsplit_once('.') {
+            Some((module_name, ident)) => {
+                let module_id = self.interns.module_ids.get_id(&module_name.into())?;
+                let ident_id = self.interns.all_ident_ids.get(&module_id)?.get_id(ident)?;
+
+                Symbol::new(module_id, ident_id)
+            }
+            None => self.scope.lookup_str(type_name, Region::zero()).ok()?,
+        };
+
+        symbol_url(symbol, self.interns, self.module_pages)
+    }
+}
+
+/// Types and values from modules in these docs - including the ones from dependency
+/// packages - link to their page here. Builtins which aren't in these docs link to the
+/// builtins docs on roc-lang.org, and anything else doesn't get a link.
+fn symbol_url(
+    symbol: Symbol,
+    interns: &Interns,
+    module_pages: &MutMap<ModuleId, ModulePage>,
+) -> Option<String> {
+    let ident = symbol.as_str(interns);
+
+    match module_pages.get(&symbol.module_id()) {
+        Some(page) => Some(format!("{}{}#{}", base_url(), page.path, ident)),
+        None if symbol.is_builtin() => Some(format!(
+            "{}{}#{}",
+            BUILTINS_DOCS_URL,
+            symbol.module_string(interns),
+            ident
+        )),
+        None => None,
+    }
+}
+
 // html is written to buf
 fn type_annotation_to_html(
     indent_level: usize,
     buf: &mut String,
     type_ann: &TypeAnnotation,
     needs_parens: bool,
+    opt_type_links: Option<&TypeLinks>,
 ) {
     let is_multiline = should_be_multiline(type_ann);
     match type_ann {
@@ -425,7 +631,13 @@ fn type_annotation_to_html(
 
                     for type_value in &tag.values {
                         buf.push(' ');
-                        type_annotation_to_html(next_indent_level, buf, type_value, true);
+                        type_annotation_to_html(
+                            next_indent_level,
+                            buf,
+                            type_value,
+                            true,
+                            opt_type_links,
+                        );
                     }
 
                     if is_multiline {
@@ -444,23 +656,23 @@ fn type_annotation_to_html(
                 buf.push(']');
             }
 
-            type_annotation_to_html(indent_level, buf, extension, true);
+            type_annotation_to_html(indent_level, buf, extension, true, opt_type_links);
         }
         TypeAnnotation::BoundVariable(var_name) => {
             buf.push_str(var_name);
         }
         TypeAnnotation::Apply { name, parts } => {
             if parts.is_empty() {
-                buf.push_str(name);
+                push_type_name(buf, name, opt_type_links);
             } else {
                 if needs_parens {
                     buf.push('(');
                 }
 
-                buf.push_str(name);
+                push_type_name(buf, name, opt_type_links);
                 for part in parts {
                     buf.push(' ');
-                    type_annotation_to_html(indent_level, buf, part, true);
+                    type_annotation_to_html(indent_level, buf, part, true, opt_type_links);
                 }
 
                 if needs_parens {
@@ -508,13 +720,25 @@ fn type_annotation_to_html(
                             type_annotation, ..
                         } => {
                             buf.push_str(" : ");
-                            type_annotation_to_html(next_indent_level, buf, type_annotation, false);
+                            type_annotation_to_html(
+                                next_indent_level,
+                                buf,
+                                type_annotation,
+                                false,
+                                opt_type_links,
+                            );
                         }
                         RecordField::OptionalField {
                             type_annotation, ..
                         } => {
                             buf.push_str(" ? ");
-                            type_annotation_to_html(next_indent_level, buf, type_annotation, false);
+                            type_annotation_to_html(
+                                next_indent_level,
+                                buf,
+                                type_annotation,
+                                false,
+                                opt_type_links,
+                            );
                         }
                         RecordField::LabelOnly { .. } => {}
                     }
@@ -537,7 +761,7 @@ fn type_annotation_to_html(
                 buf.push('}');
             }
 
-            type_annotation_to_html(indent_level, buf, extension, true);
+            type_annotation_to_html(indent_level, buf, extension, true, opt_type_links);
         }
         TypeAnnotation::Function { args, output } => {
             let mut paren_is_open = false;
@@ -556,7 +780,7 @@ fn type_annotation_to_html(
 
                 let child_needs_parens =
                     matches!(arg, TypeAnnotation::Function { args: _, output: _ });
-                type_annotation_to_html(indent_level, buf, arg, child_needs_parens);
+                type_annotation_to_html(indent_level, buf, arg, child_needs_parens, opt_type_links);
 
                 if peekable_args.peek().is_some() {
                     buf.push_str(", ");
@@ -576,7 +800,7 @@ fn type_annotation_to_html(
                 next_indent_level += 1;
             }
 
-            type_annotation_to_html(next_indent_level, buf, output, false);
+            type_annotation_to_html(next_indent_level, buf, output, false, opt_type_links);
             if needs_parens && paren_is_open {
                 buf.push(')');
             }
@@ -595,6 +819,13 @@ fn type_annotation_to_html(
     }
 }
 
+fn push_type_name(buf: &mut String, name: &str, opt_type_links: Option<&TypeLinks>) {
+    match opt_type_links.and_then(|type_links| type_links.url(name)) {
+        Some(url) => push_html(buf, "a", vec![("href", url.as_str())], name),
+        None => buf.push_str(name),
+    }
+}
+
 fn should_be_multiline(type_ann: &TypeAnnotation) -> bool {
     match type_ann {
         TypeAnnotation::TagUnion { tags, extension } => {
@@ -675,10 +906,11 @@ fn doc_url<'a>(
     all_exposed_symbols: &VecSet<Symbol>,
     scope: &Scope,
     interns: &'a Interns,
+    module_pages: &MutMap<ModuleId, ModulePage>,
     mut module_name: &'a str,
     ident: &str,
 ) -> DocUrl {
-    if module_name.is_empty() {
+    let symbol = if module_name.is_empty() {
         // This is an unqualified lookup, so look for the ident
         // in scope!
         match scope.lookup_str(ident, Region::zero()) {
@@ -688,6 +920,8 @@ fn doc_url<'a>(
                 // module - for example, if this is in scope from an
                 // unqualified import.
                 module_name = symbol.module_string(interns);
+
+                symbol
             }
             Err(_) => {
                 // TODO return Err here
@@ -703,11 +937,8 @@ fn doc_url<'a>(
                 let symbol = interns.symbol(module_id, ident.into());
 
                 if symbol.is_builtin() {
-                    // We can always generate links for builtin modules.
-                    // TODO add a `--include-builtins` CLI flag for generating offline docs locally
-                    // which include builtins; if that flag is omitted, have this code path generate
-                    // a link directly to the builtin docs on roc-lang.org instead of to a localhost
-                    // URL that will 404.
+                    // We can always generate links for builtin modules. If they aren't part of
+                    // these docs, the link goes to the builtin docs on roc-lang.org.
                     module_name = symbol.module_string(interns);
                 }
                 // Note: You can do qualified lookups on your own module, e.g.
@@ -721,25 +952,20 @@ fn doc_url<'a>(
 
                 // This is a valid symbol for this dependency,
                 // so proceed using the current module's name.
-                //
-                // TODO: In the future, this is where we'll
-                // incorporate the package name into the link.
+                symbol
             }
             None => {
                 // TODO return Err here
                 panic!("Tried to generate a doc link for `{}.{}` but the `{}` module was not imported!", module_name, ident, module_name);
             }
         }
-    }
-
-    let mut url = base_url();
+    };
 
     // Example:
     //
     // module_name: "Str", ident: "join" => "/Str#join"
-    url.push_str(module_name);
-    url.push('#');
-    url.push_str(ident);
+    let url = symbol_url(symbol, interns, module_pages)
+        .unwrap_or_else(|| format!("{}{}#{}", base_url(), module_name, ident));
 
     DocUrl {
         url,
@@ -753,6 +979,7 @@ fn markdown_to_html(
     scope: &Scope,
     markdown: &str,
     loaded_module: &LoadedModule,
+    module_pages: &MutMap<ModuleId, ModulePage>,
 ) {
     use pulldown_cmark::{BrokenLink, CodeBlockKind, CowStr, Event, LinkType, Tag::*};
 
@@ -782,6 +1009,7 @@ fn markdown_to_html(
                                     all_exposed_symbols,
                                     scope,
                                     &loaded_module.interns,
+                                    module_pages,
                                     module_name,
                                     symbol_name,
                                 );
@@ -803,6 +1031,7 @@ fn markdown_to_html(
                             all_exposed_symbols,
                             scope,
                             &loaded_module.interns,
+                            module_pages,
                             "",
                             type_name,
                         );
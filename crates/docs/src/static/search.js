@@ -1,11 +1,93 @@
 (() => {
   let sidebar = document.getElementById("sidebar-nav");
   let searchBox = document.getElementById("module-search");
+  let searchResults = document.getElementById("search-results");
+  const MAX_SEARCH_RESULTS = 20;
+
+  // Every exposed value and type in these docs; written next to this script by `roc docs`.
+  let searchIndex = [];
+
+  fetch(new URL("search-index.json", document.currentScript.src))
+    .then((response) => response.json())
+    .then((index) => {
+      searchIndex = index;
+      search();
+    })
+    .catch(() => {
+      // Without the index, searching still filters the sidebar.
+    });
+
+  // The query's characters have to appear in the text in order, but not necessarily next to
+  // each other, so e.g. "lmap" finds "List.map". Runs of consecutive characters, and matches at
+  // the start of the text, score higher. Returns null if there's no match.
+  function fuzzyScore(query, text) {
+    let score = 0;
+    let run = 0;
+    let textIndex = 0;
+
+    for (const char of query) {
+      let found = text.indexOf(char, textIndex);
+
+      if (found === -1) {
+        return null;
+      }
+
+      run = found === textIndex ? run + 1 : 0;
+      score += 1 + run * 2 + (found === 0 ? 3 : 0);
+      textIndex = found + 1;
+    }
+
+    // Among equally good matches, prefer shorter names.
+    return score - text.length / 100;
+  }
+
+  function renderSearchResults(query) {
+    let scored = [];
+
+    searchIndex.forEach((entry) => {
+      let qualifiedScore = fuzzyScore(query, `${entry.module}.${entry.name}`.toLowerCase());
+      let nameScore = fuzzyScore(query, entry.name.toLowerCase());
+      let score = Math.max(qualifiedScore ?? -Infinity, nameScore ?? -Infinity);
+
+      if (score !== -Infinity) {
+        scored.push({ entry, score });
+      }
+    });
+
+    scored.sort((a, b) => b.score - a.score);
+
+    searchResults.replaceChildren(
+      ...scored.slice(0, MAX_SEARCH_RESULTS).map(({ entry }) => {
+        let item = document.createElement("li");
+        let link = document.createElement("a");
+        let name = document.createElement("span");
+        let signature = document.createElement("span");
+        let summary = document.createElement("span");
+
+        link.href = entry.url;
+        name.classList.add("search-result-name");
+        name.textContent = `${entry.module}.${entry.name}`;
+        signature.classList.add("search-result-signature");
+        signature.textContent = entry.signature;
+        summary.classList.add("search-result-summary");
+        summary.textContent = entry.summary;
+
+        link.append(name, signature, summary);
+        item.append(link);
+
+        return item;
+      })
+    );
+
+    searchResults.classList.toggle("hidden", scored.length === 0);
+  }
 
   function search() {
     let text = searchBox.value.toLowerCase(); // Search is case-insensitive.
 
     if (text === "") {
+      searchResults.classList.add("hidden");
+
       // Un-hide everything
       sidebar.querySelectorAll(".sidebar-entry a").forEach((entry) => entry.classList.remove("hidden"));
 
@@ -21,6 +103,8 @@
         entry.querySelectorAll(".sidebar-sub-entries a").forEach((subEntry) => subEntry.classList.add("hidden"));
       })
     } else {
+      renderSearchResults(text);
+
       // First, show/hide all the sub-entries within each module (top-level functions etc.)
       sidebar.querySelectorAll(".sidebar-sub-entries a").forEach((entry) => {
         if (entry.textContent.toLowerCase().includes(text)) {
@@ -0,0 +1,1093 @@
+app "c-glue"
+    packages { pf: "../platform/main.roc" }
+    imports [pf.Types.{ Types }, pf.Shape.{ Shape, RocFn, RocStructFields }, pf.File.{ File }, pf.TypeId.{ TypeId }]
+    provides [makeGlue] to pf
+
+makeGlue : List Types -> Result (List File) Str
+makeGlue = \typesByArch ->
+    archIncludes =
+        walkWithIndex typesByArch "" \content, index, types ->
+            arch = (Types.target types).architecture
+            archStr = archName arch
+            condition = archCondition arch
+            directive = if index == 0 then "#if" else "#elif"
+
+            Str.concat
+                content
+                """
+                \(directive) \(condition)
+                #include "roc_app_\(archStr).h"
+
+                """
+
+    headerContent =
+        Str.concat
+            prelude
+            """
+            \(archIncludes)#else
+            #error "`roc glue` did not generate declarations for this target architecture"
+            #endif
+
+            #endif // ROC_APP_H
+
+            """
+
+    typesByArch
+    |> List.map convertTypesToFile
+    |> List.append { name: "roc_app.h", content: headerContent }
+    |> Ok
+
+# C requires every type to be declared before it is used by value, so the
+# declarations for each architecture are written in several passes:
+#
+# 1. enums, forward declarations of structs, and the pointer "handles" of recursive tag unions
+# 2. struct, tag union and RocResult definitions, in the order they were added to Types,
+#    which always puts a type after the types it contains by value
+# 3. the payload unions of recursive tag unions, which may refer to any of the above
+# 4. inline helpers, which need every type to be complete
+# 5. prototypes and wrappers for the entry points
+convertTypesToFile : Types -> File
+convertTypesToFile = \types ->
+    archStr = archName (Types.target types).architecture
+
+    content =
+        archFileHeader archStr
+        |> \buf -> Types.walkShapes types buf \b, type, _ -> generateForwardDeclaration b types type
+        |> \buf -> Types.walkShapes types buf \b, type, id -> generateDefinition b types type id
+        |> \buf -> Types.walkShapes types buf \b, type, _ -> generateRecursivePayloadUnion b types type
+        |> \buf -> Types.walkShapes types buf \b, type, _ -> generateHelpers b types type
+        |> generateEntryPoints types
+        |> Str.concat "\n#endif // ROC_APP_\(archStr)_H\n"
+
+    {
+        name: "roc_app_\(archStr).h",
+        content,
+    }
+
+generateForwardDeclaration : Str, Types, Shape -> Str
+generateForwardDeclaration = \buf, types, type ->
+    when type is
+        Struct { name } ->
+            generateTypedef buf (escapeKW name)
+
+        TagUnionPayload { name } ->
+            generateTypedef buf (escapeKW name)
+
+        TagUnion (Enumeration { name, tags, size }) ->
+            generateEnumeration buf (escapeKW name) tags size
+
+        TagUnion (NonRecursive { name, tags, discriminantSize }) ->
+            if List.isEmpty tags then
+                buf
+            else
+                escapedName = escapeKW name
+
+                buf
+                |> generateDiscriminant escapedName (List.map tags .name) discriminantSize
+                |> generateTypedef escapedName
+
+        TagUnion (Recursive { name, tags, discriminantSize }) ->
+            if List.isEmpty tags then
+                buf
+            else
+                generateTaggedPointerHandle buf (escapeKW name) tags discriminantSize
+
+        TagUnion (NullableWrapped { name, tags, discriminantSize }) ->
+            generateTaggedPointerHandle buf (escapeKW name) tags discriminantSize
+
+        TagUnion (NullableUnwrapped { name, nullTag, nonNullTag, nonNullPayload, whichTagIsNull }) ->
+            escapedName = escapeKW name
+            tagNames =
+                when whichTagIsNull is
+                    FirstTagIsNull -> [nullTag, nonNullTag]
+                    SecondTagIsNull -> [nonNullTag, nullTag]
+
+            buf
+            |> generateDiscriminant escapedName tagNames 1
+            |> generatePointerHandle types escapedName nonNullPayload
+
+        TagUnion (NonNullableUnwrapped { name, payload }) ->
+            generatePointerHandle buf types (escapeKW name) payload
+
+        TagUnion (SingleTagStruct { name }) ->
+            generateTypedef buf (escapeKW name)
+
+        Function rocFn ->
+            if rocFn.isToplevel then
+                buf
+            else
+                generateTypedef buf (escapeKW rocFn.functionName)
+
+        RecursivePointer _
+        | Unit
+        | Unsized
+        | EmptyTagUnion
+        | Num _
+        | Bool
+        | RocResult _ _
+        | RocStr
+        | RocDict _ _
+        | RocSet _
+        | RocList _
+        | RocBox _ ->
+            buf
+
+generateDefinition : Str, Types, Shape, TypeId -> Str
+generateDefinition = \buf, types, type, id ->
+    when type is
+        Struct { name, fields } ->
+            generateStruct buf types id (escapeKW name) (structFieldList fields)
+
+        TagUnionPayload { name, fields } ->
+            generateStruct buf types id (escapeKW name) (nameTagUnionPayloadFields fields)
+
+        TagUnion (NonRecursive { name, tags, discriminantOffset }) ->
+            if List.isEmpty tags then
+                buf
+            else
+                generateNonRecursiveTagUnion buf types id (escapeKW name) tags discriminantOffset
+
+        TagUnion (SingleTagStruct { name, payload }) ->
+            fields =
+                when payload is
+                    HasNoClosure xs ->
+                        List.mapWithIndex xs \{ id: fieldId }, index ->
+                            indexStr = Num.toStr index
+
+                            { name: "f\(indexStr)", id: fieldId }
+
+                    HasClosure xs ->
+                        List.map xs \{ name: fieldName, id: fieldId } -> { name: "f\(fieldName)", id: fieldId }
+
+            generateStruct buf types id (escapeKW name) fields
+
+        RocResult ok err ->
+            generateRocResult buf types id ok err
+
+        Function rocFn ->
+            if rocFn.isToplevel then
+                buf
+            else
+                name = escapeKW rocFn.functionName
+
+                if isZeroSized types rocFn.lambdaSet then
+                    generatePlaceholderStruct buf name
+                else
+                    lambdaSet = typeName types rocFn.lambdaSet
+
+                    Str.concat buf "struct \(name) {\n\(indent)\(lambdaSet) closure_data;\n};\n\n"
+
+        TagUnion _
+        | RecursivePointer _
+        | Unit
+        | Unsized
+        | EmptyTagUnion
+        | Num _
+        | Bool
+        | RocStr
+        | RocDict _ _
+        | RocSet _
+        | RocList _
+        | RocBox _ ->
+            # These are either declared by the prelude in roc_app.h, or were
+            # completely declared by generateForwardDeclaration.
+            buf
+
+generateRecursivePayloadUnion : Str, Types, Shape -> Str
+generateRecursivePayloadUnion = \buf, types, type ->
+    when type is
+        TagUnion (Recursive { name, tags }) ->
+            if List.isEmpty tags then
+                buf
+            else
+                generatePayloadUnion buf types (escapeKW name) tags
+
+        TagUnion (NullableWrapped { name, tags }) ->
+            generatePayloadUnion buf types (escapeKW name) tags
+
+        _ ->
+            buf
+
+generateHelpers : Str, Types, Shape -> Str
+generateHelpers = \buf, types, type ->
+    when type is
+        TagUnion (NonRecursive { name, tags }) ->
+            if List.isEmpty tags then
+                buf
+            else
+                generateNonRecursiveHelpers buf types (escapeKW name) tags
+
+        TagUnion (Recursive { name, tags }) ->
+            if List.isEmpty tags then
+                buf
+            else
+                generateTaggedPointerHelpers buf types (escapeKW name) tags None
+
+        TagUnion (NullableWrapped { name, indexOfNullTag, tags }) ->
+            generateTaggedPointerHelpers buf types (escapeKW name) tags (Some indexOfNullTag)
+
+        TagUnion (NullableUnwrapped { name, nullTag, nonNullTag, nonNullPayload }) ->
+            generateNullableUnwrappedHelpers buf types (escapeKW name) nullTag nonNullTag nonNullPayload
+
+        TagUnion (NonNullableUnwrapped { name, tagName, payload }) ->
+            generateNonNullableUnwrappedHelpers buf types (escapeKW name) tagName payload
+
+        Function rocFn ->
+            if rocFn.isToplevel then
+                buf
+            else
+                generateFunction buf types rocFn
+
+        _ ->
+            buf
+
+generateTypedef : Str, Str -> Str
+generateTypedef = \buf, name ->
+    Str.concat buf "typedef struct \(name) \(name);\n\n"
+
+generateEnumeration : Str, Str, List Str, U32 -> Str
+generateEnumeration = \buf, name, tags, size ->
+    reprType = unsignedIntType size
+
+    buf
+    |> Str.concat "typedef \(reprType) \(name);\n\nenum {\n"
+    |> \b -> walkWithIndex tags b (generateEnumTag name)
+    |> Str.concat "};\n\n"
+
+generateEnumTag = \name ->
+    \accum, index, tagName ->
+        indexStr = Num.toStr index
+
+        Str.concat accum "\(indent)\(name)_\(tagName) = \(indexStr),\n"
+
+generateDiscriminant : Str, Str, List Str, U32 -> Str
+generateDiscriminant = \buf, name, tags, size ->
+    generateEnumeration buf "discriminant_\(name)" tags size
+
+generateTaggedPointerHandle : Str, Str, List { name : Str, payload : [Some TypeId, None] }, U32 -> Str
+generateTaggedPointerHandle = \buf, name, tags, discriminantSize ->
+    buf
+    |> generateDiscriminant name (List.map tags .name) discriminantSize
+    |> Str.concat
+        """
+        union union_\(name);
+
+        // The discriminant is stored in the low bits of this pointer; use
+        // \(name)_discriminant to read it rather than dereferencing it directly.
+        typedef struct \(name) {
+            union union_\(name) *ptr;
+        } \(name);
+
+
+        """
+
+generatePointerHandle : Str, Types, Str, TypeId -> Str
+generatePointerHandle = \buf, types, name, payload ->
+    payloadName = typeName types payload
+
+    # The payload struct may not have been declared yet, so refer to it
+    # through its struct tag, which C allows for pointers.
+    Str.concat
+        buf
+        """
+        typedef struct \(name) {
+            struct \(payloadName) *ptr;
+        } \(name);
+
+
+        """
+
+structFieldList : RocStructFields -> List { name : Str, id : TypeId }
+structFieldList = \structFields ->
+    when structFields is
+        HasNoClosure fields -> fields
+        HasClosure fields -> List.map fields \{ name, id } -> { name, id }
+
+nameTagUnionPayloadFields : RocStructFields -> List { name : Str, id : TypeId }
+nameTagUnionPayloadFields = \payloadFields ->
+    # Tag union payloads have numbered fields, so we prefix them
+    # with an "f" because C doesn't allow struct fields to be numbers.
+    structFieldList payloadFields
+    |> List.map \{ name, id } -> { name: "f\(name)", id }
+
+generateStruct : Str, Types, TypeId, Str, List { name : Str, id : TypeId } -> Str
+generateStruct = \buf, types, id, name, fields ->
+    # Zero-sized fields take up no space in Roc, and C has no way to express them.
+    sizedFields = List.dropIf fields \{ id: fieldId } -> isZeroSized types fieldId
+
+    if List.isEmpty sizedFields then
+        generatePlaceholderStruct buf name
+    else
+        buf
+        |> Str.concat "struct \(name) {\n"
+        |> \b -> List.walk sizedFields b (generateStructField types)
+        |> Str.concat "};\n\n"
+        |> generateLayoutChecks types id name
+
+generateStructField = \types ->
+    \accum, { name: fieldName, id } ->
+        typeStr = typeName types id
+        escapedFieldName = escapeKW fieldName
+
+        Str.concat accum "\(indent)\(typeStr) \(escapedFieldName);\n"
+
+# C has no zero-sized structs, so zero-sized Roc types get a struct which only exists to
+# give the type a name. Zero-sized values are left out wherever they would be stored or
+# passed, so Roc never reads or writes it.
+generatePlaceholderStruct : Str, Str -> Str
+generatePlaceholderStruct = \buf, name ->
+    Str.concat buf "struct \(name) {\n\(indent)uint8_t _unused;\n};\n\n"
+
+generateLayoutChecks : Str, Types, TypeId, Str -> Str
+generateLayoutChecks = \buf, types, id, name ->
+    size = Types.size types id
+
+    if size == 0 then
+        buf
+    else
+        sizeStr = Num.toStr size
+        alignStr = Num.toStr (Types.alignment types id)
+
+        Str.concat
+            buf
+            """
+            _Static_assert(sizeof(\(name)) == \(sizeStr), "\(name) does not have the size Roc expects");
+            _Static_assert(_Alignof(\(name)) == \(alignStr), "\(name) does not have the alignment Roc expects");
+
+
+            """
+
+generateNonRecursiveTagUnion : Str, Types, TypeId, Str, List { name : Str, payload : [Some TypeId, None] }, U32 -> Str
+generateNonRecursiveTagUnion = \buf, types, id, name, tags, discriminantOffset ->
+    discriminantOffsetStr = Num.toStr discriminantOffset
+
+    buf
+    |> generatePayloadUnion types name tags
+    |> Str.concat
+        """
+        struct \(name) {
+            union union_\(name) payload;
+            discriminant_\(name) discriminant;
+        };
+
+        _Static_assert(offsetof(\(name), discriminant) == \(discriminantOffsetStr), "\(name) does not have its discriminant where Roc expects");
+
+        """
+    |> generateLayoutChecks types id name
+
+generatePayloadUnion : Str, Types, Str, List { name : Str, payload : [Some TypeId, None] } -> Str
+generatePayloadUnion = \buf, types, name, tags ->
+    buf
+    |> Str.concat "union union_\(name) {\n"
+    |> \b -> List.walk tags b (generateUnionField types)
+    |> Str.concat "};\n\n"
+
+generateUnionField = \types ->
+    \accum, { name: tagName, payload } ->
+        when payloadType types payload is
+            Some typeStr -> Str.concat accum "\(indent)\(typeStr) \(tagName);\n"
+            # Tags without a payload don't need any room in the union.
+            None -> accum
+
+payloadType : Types, [Some TypeId, None] -> [Some Str, None]
+payloadType = \types, payload ->
+    when payload is
+        Some id ->
+            if isZeroSized types id then
+                None
+            else
+                Some (typeName types id)
+
+        None ->
+            None
+
+generateRocResult : Str, Types, TypeId, TypeId, TypeId -> Str
+generateRocResult = \buf, types, id, ok, err ->
+    # Different Roc types can have the same C type (all lists are RocList, for example),
+    # so the same RocResult may come up more than once.
+    name = typeName types id
+
+    payloadFields =
+        [{ fieldName: "ok", fieldId: ok }, { fieldName: "err", fieldId: err }]
+        |> List.dropIf \{ fieldId } -> isZeroSized types fieldId
+        |> List.map \{ fieldName, fieldId } ->
+            typeStr = typeName types fieldId
+
+            "\(indent)\(indent)\(typeStr) \(fieldName);\n"
+
+    payload =
+        if List.isEmpty payloadFields then
+            ""
+        else
+            fieldsStr = Str.joinWith payloadFields ""
+
+            "\(indent)union {\n\(fieldsStr)\(indent)} payload;\n"
+
+    Str.concat
+        buf
+        """
+        #ifndef \(name)_DEFINED
+        #define \(name)_DEFINED
+
+        typedef struct \(name) {
+        \(payload)    uint8_t tag;
+        } \(name);
+
+        static inline bool \(name)_is_ok(\(name) const *result) {
+            return result->tag == ROC_RESULT_OK;
+        }
+
+        #endif // \(name)_DEFINED
+
+
+        """
+
+generateNonRecursiveHelpers : Str, Types, Str, List { name : Str, payload : [Some TypeId, None] } -> Str
+generateNonRecursiveHelpers = \buf, types, name, tags ->
+    tagHelpers = \accum, { name: tagName, payload } ->
+        accessors =
+            when payloadType types payload is
+                Some typeStr ->
+                    """
+                    static inline \(typeStr) \(name)_get_\(tagName)(\(name) const *value) {
+                        return value->payload.\(tagName);
+                    }
+
+                    static inline \(name) \(name)_make_\(tagName)(\(typeStr) payload) {
+                        \(name) value;
+                        memset(&value, 0, sizeof(value));
+                        value.payload.\(tagName) = payload;
+                        value.discriminant = discriminant_\(name)_\(tagName);
+                        return value;
+                    }
+                    """
+
+                None ->
+                    """
+                    static inline \(name) \(name)_make_\(tagName)(void) {
+                        \(name) value;
+                        memset(&value, 0, sizeof(value));
+                        value.discriminant = discriminant_\(name)_\(tagName);
+                        return value;
+                    }
+                    """
+
+        """
+        \(accum)static inline bool \(name)_is_\(tagName)(\(name) const *value) {
+            return value->discriminant == discriminant_\(name)_\(tagName);
+        }
+
+        \(accessors)
+
+
+        """
+
+    buf
+    |> Str.concat
+        """
+        \(discriminantDocComment)
+        static inline discriminant_\(name) \(name)_discriminant(\(name) const *value) {
+            return value->discriminant;
+        }
+
+
+        """
+    |> \b -> List.walk tags b tagHelpers
+
+generateTaggedPointerHelpers : Str, Types, Str, List { name : Str, payload : [Some TypeId, None] }, [Some U16, None] -> Str
+generateTaggedPointerHelpers = \buf, types, name, tags, nullTagIndex ->
+    nullCheck =
+        when nullTagIndex is
+            Some index ->
+                when List.get tags (Num.toNat index) is
+                    Ok { name: nullTag } ->
+                        """
+                            if (value->ptr == NULL) {
+                                return discriminant_\(name)_\(nullTag);
+                            }
+
+
+                        """
+
+                    Err OutOfBounds ->
+                        ""
+
+            None ->
+                ""
+
+    tagHelpers = \accum, { name: tagName, payload }, index ->
+        isNullTag = Some (Num.intCast index) == nullTagIndex
+
+        constructor =
+            if isNullTag then
+                """
+                static inline \(name) \(name)_make_\(tagName)(void) {
+                    \(name) value = { NULL };
+                    return value;
+                }
+                """
+            else
+                { params, init } =
+                    when payloadType types payload is
+                        Some typeStr -> { params: "\(typeStr) payload", init: "\n\(indent)ptr->\(tagName) = payload;" }
+                        None -> { params: "void", init: "" }
+
+                """
+                static inline \(name) \(name)_make_\(tagName)(\(params)) {
+                    union union_\(name) *ptr = roc_alloc_refcounted(sizeof(union union_\(name)), _Alignof(union union_\(name)));\(init)
+                    \(name) value = { (union union_\(name) *)((uintptr_t)ptr | discriminant_\(name)_\(tagName)) };
+                    return value;
+                }
+                """
+
+        getter =
+            when payloadType types payload is
+                Some typeStr ->
+                    """
+                    static inline \(typeStr) \(name)_get_\(tagName)(\(name) const *value) {
+                        return \(name)_payload(value)->\(tagName);
+                    }
+
+
+                    """
+
+                None ->
+                    ""
+
+        """
+        \(accum)static inline bool \(name)_is_\(tagName)(\(name) const *value) {
+            return \(name)_discriminant(value) == discriminant_\(name)_\(tagName);
+        }
+
+        \(getter)\(constructor)
+
+
+        """
+
+    buf
+    |> Str.concat
+        """
+        \(discriminantDocComment)
+        static inline discriminant_\(name) \(name)_discriminant(\(name) const *value) {
+        \(nullCheck)    return (discriminant_\(name))((uintptr_t)value->ptr & ROC_TAG_ID_MASK);
+        }
+
+        static inline union union_\(name) *\(name)_payload(\(name) const *value) {
+            return (union union_\(name) *)((uintptr_t)value->ptr & ~ROC_TAG_ID_MASK);
+        }
+
+
+        """
+    |> \b -> walkWithIndex tags b \accum, index, tag -> tagHelpers accum tag index
+
+generateNullableUnwrappedHelpers : Str, Types, Str, Str, Str, TypeId -> Str
+generateNullableUnwrappedHelpers = \buf, types, name, nullTag, nonNullTag, nonNullPayload ->
+    payloadStr = typeName types nonNullPayload
+
+    """
+    \(buf)\(discriminantDocComment)
+    static inline discriminant_\(name) \(name)_discriminant(\(name) const *value) {
+        return value->ptr == NULL ? discriminant_\(name)_\(nullTag) : discriminant_\(name)_\(nonNullTag);
+    }
+
+    static inline bool \(name)_is_\(nullTag)(\(name) const *value) {
+        return value->ptr == NULL;
+    }
+
+    static inline bool \(name)_is_\(nonNullTag)(\(name) const *value) {
+        return value->ptr != NULL;
+    }
+
+    static inline \(payloadStr) \(name)_get_\(nonNullTag)(\(name) const *value) {
+        return *value->ptr;
+    }
+
+    static inline \(name) \(name)_make_\(nullTag)(void) {
+        \(name) value = { NULL };
+        return value;
+    }
+
+    static inline \(name) \(name)_make_\(nonNullTag)(\(payloadStr) payload) {
+        \(name) value = { roc_alloc_refcounted(sizeof(\(payloadStr)), _Alignof(\(payloadStr))) };
+        *value.ptr = payload;
+        return value;
+    }
+
+
+    """
+
+generateNonNullableUnwrappedHelpers : Str, Types, Str, Str, TypeId -> Str
+generateNonNullableUnwrappedHelpers = \buf, types, name, tagName, payload ->
+    payloadStr = typeName types payload
+
+    """
+    \(buf)static inline \(payloadStr) \(name)_get_\(tagName)(\(name) const *value) {
+        return *value->ptr;
+    }
+
+    static inline \(name) \(name)_make_\(tagName)(\(payloadStr) payload) {
+        \(name) value = { roc_alloc_refcounted(sizeof(\(payloadStr)), _Alignof(\(payloadStr))) };
+        *value.ptr = payload;
+        return value;
+    }
+
+
+    """
+
+generateFunction : Str, Types, RocFn -> Str
+generateFunction = \buf, types, rocFn ->
+    name = escapeKW rocFn.functionName
+    externName = rocFn.externName
+
+    # Closures take their arguments by pointer. Zero-sized arguments are left out of
+    # the wrapper, and get a NULL pointer instead, since Roc never reads through it.
+    publicArguments =
+        rocFn.args
+        |> List.mapWithIndex \argId, i ->
+            if isZeroSized types argId then
+                ""
+            else
+                type = typeName types argId
+                c = Num.toStr i
+                ", \(type) arg\(c)"
+        |> Str.joinWith ""
+
+    externDefArguments =
+        rocFn.args
+        |> List.map \argId ->
+            if isZeroSized types argId then
+                "void const *, "
+            else
+                type = typeName types argId
+                "\(type) const *, "
+        |> Str.joinWith ""
+
+    externCallArguments =
+        rocFn.args
+        |> List.mapWithIndex \argId, i ->
+            if isZeroSized types argId then
+                "NULL, "
+            else
+                c = Num.toStr i
+                "&arg\(c), "
+        |> Str.joinWith ""
+
+    if isZeroSized types rocFn.ret then
+        """
+        \(buf)extern void \(externName)(\(externDefArguments)uint8_t *closure_data, void *output);
+
+        static inline void \(name)_force(\(name) *function\(publicArguments)) {
+            \(externName)(\(externCallArguments)(uint8_t *)function, NULL);
+        }
+
+
+        """
+    else
+        ret = typeName types rocFn.ret
+
+        """
+        \(buf)extern void \(externName)(\(externDefArguments)uint8_t *closure_data, \(ret) *output);
+
+        static inline \(ret) \(name)_force(\(name) *function\(publicArguments)) {
+            \(ret) output;
+            \(externName)(\(externCallArguments)(uint8_t *)function, &output);
+            return output;
+        }
+
+
+        """
+
+generateEntryPoints : Str, Types -> Str
+generateEntryPoints = \buf, types ->
+    List.walk (Types.entryPoints types) buf \accum, T name id -> generateEntryPoint accum types name id
+
+generateEntryPoint : Str, Types, Str, TypeId -> Str
+generateEntryPoint = \buf, types, name, id ->
+    { allArgs, retId } =
+        when Types.shape types id is
+            Function rocFn -> { allArgs: rocFn.args, retId: rocFn.ret }
+            _ -> { allArgs: [], retId: id }
+
+    # Entry points take their arguments by value, and zero-sized arguments are not passed at all.
+    args = List.dropIf allArgs \argId -> isZeroSized types argId
+
+    publicSignature =
+        if List.isEmpty args then
+            "void"
+        else
+            args
+            |> List.mapWithIndex \argId, i ->
+                type = typeName types argId
+                c = Num.toStr i
+                "\(type) arg\(c)"
+            |> Str.joinWith ", "
+
+    externSignature =
+        args
+        |> List.map \argId ->
+            type = typeName types argId
+            ", \(type)"
+        |> Str.joinWith ""
+
+    externArguments =
+        args
+        |> List.mapWithIndex \_, i ->
+            c = Num.toStr i
+            ", arg\(c)"
+        |> Str.joinWith ""
+
+    if isZeroSized types retId then
+        """
+        \(buf)extern void roc__\(name)_1_exposed_generic(void *\(externSignature));
+
+        static inline void roc_\(name)(\(publicSignature)) {
+            roc__\(name)_1_exposed_generic(NULL\(externArguments));
+        }
+
+
+        """
+    else
+        ret = typeName types retId
+
+        """
+        \(buf)extern void roc__\(name)_1_exposed_generic(\(ret) *\(externSignature));
+
+        static inline \(ret) roc_\(name)(\(publicSignature)) {
+            \(ret) ret;
+            roc__\(name)_1_exposed_generic(&ret\(externArguments));
+            return ret;
+        }
+
+
+        """
+
+typeName : Types, TypeId -> Str
+typeName = \types, id ->
+    when Types.shape types id is
+        # Zero-sized values are left out wherever they would be stored or passed, so
+        # these only show up in the names of other types, like RocResult_void_RocStr.
+        Unit -> "void"
+        EmptyTagUnion -> "void"
+        Unsized -> "RocList"
+        RocStr -> "RocStr"
+        Bool -> "bool"
+        Num U8 -> "uint8_t"
+        Num U16 -> "uint16_t"
+        Num U32 -> "uint32_t"
+        Num U64 -> "uint64_t"
+        Num U128 -> "RocU128"
+        Num I8 -> "int8_t"
+        Num I16 -> "int16_t"
+        Num I32 -> "int32_t"
+        Num I64 -> "int64_t"
+        Num I128 -> "RocI128"
+        Num F32 -> "float"
+        Num F64 -> "double"
+        Num Dec -> "RocDec"
+        RocDict _ _ -> "RocDict"
+        RocSet _ -> "RocSet"
+        RocList _ -> "RocList"
+        RocBox _ -> "RocBox"
+        RocResult ok err ->
+            okName = typeName types ok
+            errName = typeName types err
+
+            "RocResult_\(okName)_\(errName)"
+
+        RecursivePointer content ->
+            typeName types content
+
+        Struct { name } -> escapeKW name
+        TagUnionPayload { name } -> escapeKW name
+        TagUnion (NonRecursive { name }) -> escapeKW name
+        TagUnion (Recursive { name }) -> escapeKW name
+        TagUnion (Enumeration { name }) -> escapeKW name
+        TagUnion (NullableWrapped { name }) -> escapeKW name
+        TagUnion (NullableUnwrapped { name }) -> escapeKW name
+        TagUnion (NonNullableUnwrapped { name }) -> escapeKW name
+        TagUnion (SingleTagStruct { name }) -> escapeKW name
+        Function { functionName } -> escapeKW functionName
+
+isZeroSized : Types, TypeId -> Bool
+isZeroSized = \types, id ->
+    Types.size types id == 0
+
+unsignedIntType : U32 -> Str
+unsignedIntType = \size ->
+    when size is
+        0 | 1 -> "uint8_t"
+        2 -> "uint16_t"
+        4 -> "uint32_t"
+        _ -> "uint64_t"
+
+walkWithIndex = \list, originalState, f ->
+    stateWithId =
+        List.walk list { id: 0nat, state: originalState } \{ id, state }, elem ->
+            nextState = f state id elem
+
+            { id: id + 1, state: nextState }
+
+    stateWithId.state
+
+archName = \arch ->
+    when arch is
+        Aarch32 ->
+            "arm"
+
+        Aarch64 ->
+            "aarch64"
+
+        Wasm32 ->
+            "wasm32"
+
+        X86x32 ->
+            "x86"
+
+        X86x64 ->
+            "x86_64"
+
+archCondition = \arch ->
+    when arch is
+        Aarch32 ->
+            "defined(__arm__) || defined(_M_ARM)"
+
+        Aarch64 ->
+            "defined(__aarch64__) || defined(_M_ARM64)"
+
+        Wasm32 ->
+            "defined(__wasm32__)"
+
+        X86x32 ->
+            "defined(__i386__) || defined(_M_IX86)"
+
+        X86x64 ->
+            "defined(__x86_64__) || defined(_M_X64)"
+
+archFileHeader = \archStr ->
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+    //
+    // These are the declarations for \(archStr). Include roc_app.h rather than this file.
+
+    #ifndef ROC_APP_\(archStr)_H
+    #define ROC_APP_\(archStr)_H
+
+
+    """
+
+prelude =
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+    #ifndef ROC_APP_H
+    #define ROC_APP_H
+
+    #include <stdbool.h>
+    #include <stddef.h>
+    #include <stdint.h>
+    #include <string.h>
+
+    // The host must provide these for Roc to allocate memory with.
+    void *roc_alloc(size_t size, unsigned int alignment);
+    void roc_dealloc(void *ptr, unsigned int alignment);
+
+    #if defined(__SIZEOF_INT128__)
+    typedef __int128_t RocI128;
+    typedef __uint128_t RocU128;
+    #else
+    typedef struct RocI128 {
+        _Alignas(16) uint8_t bytes[16];
+    } RocI128;
+    typedef struct RocU128 {
+        _Alignas(16) uint8_t bytes[16];
+    } RocU128;
+    #endif
+
+    // A fixed-point decimal, stored as an integer scaled by 10^18.
+    typedef struct RocDec {
+        RocI128 scaled;
+    } RocDec;
+
+    // Reference counts are stored in the word right before the data they count.
+    // A refcount of ROC_REFCOUNT_ONE means there is exactly one reference, and
+    // every additional reference adds one. Readonly data (such as string literals
+    // in the binary) has a refcount of ROC_REFCOUNT_READONLY and is never freed.
+    #define ROC_REFCOUNT_ONE INTPTR_MIN
+    #define ROC_REFCOUNT_READONLY ((intptr_t)0)
+
+    // Recursive tag unions store their discriminant in the low bits of their pointer.
+    #define ROC_TAG_ID_MASK ((uintptr_t)(sizeof(void *) == 8 ? 0x7 : 0x3))
+
+    #define ROC_RESULT_ERR 0
+    #define ROC_RESULT_OK 1
+
+    static inline uint32_t roc_refcount_header_size(uint32_t alignment) {
+        return alignment > sizeof(intptr_t) ? alignment : (uint32_t)sizeof(intptr_t);
+    }
+
+    // Allocates `size` bytes with a refcount of one, and returns a pointer to the data.
+    static inline void *roc_alloc_refcounted(size_t size, uint32_t alignment) {
+        uint32_t header = roc_refcount_header_size(alignment);
+        uint8_t *allocation = roc_alloc(header + size, header);
+        intptr_t *data = (intptr_t *)(allocation + header);
+
+        data[-1] = ROC_REFCOUNT_ONE;
+
+        return data;
+    }
+
+    // Frees data that was allocated by roc_alloc_refcounted (or by Roc itself).
+    static inline void roc_free_refcounted(void *data, uint32_t alignment) {
+        uint32_t header = roc_refcount_header_size(alignment);
+
+        roc_dealloc((uint8_t *)data - header, header);
+    }
+
+    static inline bool roc_refcount_is_unique(intptr_t const *refcount) {
+        return refcount != NULL && *refcount == ROC_REFCOUNT_ONE;
+    }
+
+    static inline void roc_refcount_increment(intptr_t *refcount) {
+        if (refcount != NULL && *refcount != ROC_REFCOUNT_READONLY) {
+            // Incrementing past the maximum refcount wraps to ROC_REFCOUNT_READONLY,
+            // which leaks the data instead of freeing it too early.
+            *refcount += 1;
+        }
+    }
+
+    // Returns true once there are no more references left, at which point the
+    // caller must decrement whatever the data refers to and then free it with
+    // roc_free_refcounted.
+    static inline bool roc_refcount_decrement(intptr_t *refcount) {
+        if (refcount == NULL || *refcount == ROC_REFCOUNT_READONLY) {
+            return false;
+        } else if (*refcount == ROC_REFCOUNT_ONE) {
+            return true;
+        } else {
+            *refcount -= 1;
+            return false;
+        }
+    }
+
+    typedef struct RocStr {
+        uint8_t *bytes;
+        size_t len;
+        size_t capacity;
+    } RocStr;
+
+    // Strings which fit into sizeof(RocStr) - 1 bytes are stored inline, with
+    // their length in the last byte.
+    static inline bool roc_str_is_small(RocStr const *str) {
+        return (intptr_t)str->capacity < 0;
+    }
+
+    static inline size_t roc_str_len(RocStr const *str) {
+        if (roc_str_is_small(str)) {
+            return ((uint8_t const *)str)[sizeof(RocStr) - 1] ^ 0x80;
+        } else {
+            return str->len & (SIZE_MAX >> 1);
+        }
+    }
+
+    static inline uint8_t const *roc_str_bytes(RocStr const *str) {
+        return roc_str_is_small(str) ? (uint8_t const *)str : str->bytes;
+    }
+
+    // Returns NULL for small strings, which are not reference counted.
+    static inline intptr_t *roc_str_refcount(RocStr const *str) {
+        if (roc_str_is_small(str)) {
+            return NULL;
+        } else if ((intptr_t)str->len < 0) {
+            // This is a slice of another string, which holds the refcount.
+            return (intptr_t *)(str->capacity << 1) - 1;
+        } else {
+            return (intptr_t *)str->bytes - 1;
+        }
+    }
+
+    typedef struct RocList {
+        void *elements;
+        size_t len;
+        size_t capacity;
+    } RocList;
+
+    // Dicts and sets are stored as a list of their entries.
+    typedef RocList RocDict;
+    typedef RocList RocSet;
+
+    static inline size_t roc_list_len(RocList const *list) {
+        return list->len & (SIZE_MAX >> 1);
+    }
+
+    // Returns NULL for empty lists, which are not reference counted.
+    static inline intptr_t *roc_list_refcount(RocList const *list) {
+        if (list->elements == NULL) {
+            return NULL;
+        } else if ((intptr_t)(list->len | list->capacity) < 0) {
+            // This is a slice of another list, which holds the refcount.
+            return (intptr_t *)(list->capacity << 1) - 1;
+        } else {
+            return (intptr_t *)list->elements - 1;
+        }
+    }
+
+    typedef struct RocBox {
+        void *contents;
+    } RocBox;
+
+    static inline intptr_t *roc_box_refcount(RocBox const *box) {
+        return (intptr_t *)box->contents - 1;
+    }
+
+
+    """
+
+indent = "    "
+discriminantDocComment = "// Returns which variant this tag union holds. Note that this never includes a payload!"
+
+reservedKeywords = Set.fromList [
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "char",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "true",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+]
+
+escapeKW = \input ->
+    # C has no raw identifiers, so add an underscore to names which would clash with a keyword.
+    if Set.contains reservedKeywords input then
+        "\(input)_"
+    else
+        input
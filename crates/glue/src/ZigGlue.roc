@@ -0,0 +1,1053 @@
+app "zig-glue"
+    packages { pf: "../platform/main.roc" }
+    imports [pf.Types.{ Types }, pf.Shape.{ Shape, RocFn, RocStructFields }, pf.File.{ File }, pf.TypeId.{ TypeId }]
+    provides [makeGlue] to pf
+
+makeGlue : List Types -> Result (List File) Str
+makeGlue = \typesByArch ->
+    archImports =
+        List.walk typesByArch "" \content, types ->
+            archStr = archName (Types.target types).architecture
+            archTag = zigArchTag (Types.target types).architecture
+
+            Str.concat content "\(indent).\(archTag) => @import(\"\(archStr).zig\"),\n"
+
+    appFileContent =
+        """
+        \(fileHeader)
+        const builtin = @import("builtin");
+
+        pub usingnamespace @import("roc_std.zig");
+
+        pub usingnamespace switch (builtin.cpu.arch) {
+        \(archImports)    else => @compileError("`roc glue` did not generate declarations for this target architecture"),
+        };
+
+        """
+
+    typesByArch
+    |> List.map convertTypesToFile
+    |> List.append { name: "roc_std.zig", content: rocStd }
+    |> List.append { name: "roc_app.zig", content: appFileContent }
+    |> Ok
+
+convertTypesToFile : Types -> File
+convertTypesToFile = \types ->
+    # Zig declarations are order-independent, so unlike in C, every type can be
+    # generated on its own without worrying about what it refers to.
+    content =
+        Types.walkShapes types archFileHeader \buf, type, id ->
+            when type is
+                Struct { name, fields } ->
+                    generateStruct buf types id (escapeKW name) (structFieldList fields)
+
+                TagUnionPayload { name, fields } ->
+                    generateStruct buf types id (escapeKW name) (nameTagUnionPayloadFields fields)
+
+                TagUnion (Enumeration { name, tags, size }) ->
+                    generateEnumeration buf (escapeKW name) tags size
+
+                TagUnion (NonRecursive { name, tags, discriminantSize, discriminantOffset }) ->
+                    if List.isEmpty tags then
+                        buf
+                    else
+                        generateNonRecursiveTagUnion buf types id (escapeKW name) tags discriminantSize discriminantOffset
+
+                TagUnion (Recursive { name, tags, discriminantSize }) ->
+                    if List.isEmpty tags then
+                        buf
+                    else
+                        generateRecursiveTagUnion buf types id (escapeKW name) tags discriminantSize None
+
+                TagUnion (NullableWrapped { name, indexOfNullTag, tags, discriminantSize }) ->
+                    generateRecursiveTagUnion buf types id (escapeKW name) tags discriminantSize (Some indexOfNullTag)
+
+                TagUnion (NullableUnwrapped { name, nullTag, nonNullTag, nonNullPayload, whichTagIsNull }) ->
+                    generateNullableUnwrapped buf types id (escapeKW name) nullTag nonNullTag nonNullPayload whichTagIsNull
+
+                TagUnion (NonNullableUnwrapped { name, tagName, payload }) ->
+                    generateNonNullableUnwrapped buf types id (escapeKW name) tagName payload
+
+                TagUnion (SingleTagStruct { name, payload }) ->
+                    generateSingleTagStruct buf types id (escapeKW name) payload
+
+                Function rocFn ->
+                    if rocFn.isToplevel then
+                        buf
+                    else
+                        generateFunction buf types rocFn
+
+                RecursivePointer _ ->
+                    # This is recursively pointing to a type that should already have been added,
+                    # so no extra work needs to happen.
+                    buf
+
+                Unit
+                | Unsized
+                | EmptyTagUnion
+                | Num _
+                | Bool
+                | RocResult _ _
+                | RocStr
+                | RocDict _ _
+                | RocSet _
+                | RocList _
+                | RocBox _ ->
+                    # These types are all defined in roc_std.zig.
+                    buf
+
+    archStr = archName (Types.target types).architecture
+
+    {
+        name: "\(archStr).zig",
+        content: content |> generateEntryPoints types,
+    }
+
+generateEntryPoints : Str, Types -> Str
+generateEntryPoints = \buf, types ->
+    List.walk (Types.entryPoints types) buf \accum, T name id -> generateEntryPoint accum types name id
+
+generateEntryPoint : Str, Types, Str, TypeId -> Str
+generateEntryPoint = \buf, types, name, id ->
+    { args, ret } =
+        when Types.shape types id is
+            Function rocFn -> { args: rocFn.args, ret: typeName types rocFn.ret }
+            _ -> { args: [], ret: typeName types id }
+
+    publicSignature =
+        args
+        |> List.mapWithIndex \argId, i ->
+            type = typeName types argId
+            c = Num.toStr i
+            "arg\(c): \(type)"
+        |> Str.joinWith ", "
+
+    externSignature =
+        args
+        |> List.mapWithIndex \argId, i ->
+            type = typeName types argId
+            c = Num.toStr i
+            ", arg\(c): \(type)"
+        |> Str.joinWith ""
+
+    externArguments =
+        args
+        |> List.mapWithIndex \_, i ->
+            c = Num.toStr i
+            ", arg\(c)"
+        |> Str.joinWith ""
+
+    escapedName = escapeKW name
+
+    """
+    \(buf)extern fn roc__\(name)_1_exposed_generic(ret: *\(ret)\(externSignature)) callconv(.C) void;
+
+    pub fn \(escapedName)(\(publicSignature)) \(ret) {
+        var ret: \(ret) = undefined;
+        roc__\(name)_1_exposed_generic(&ret\(externArguments));
+        return ret;
+    }
+
+
+    """
+
+generateFunction : Str, Types, RocFn -> Str
+generateFunction = \buf, types, rocFn ->
+    name = escapeKW rocFn.functionName
+    externName = rocFn.externName
+    lambdaSet = typeName types rocFn.lambdaSet
+    ret = typeName types rocFn.ret
+
+    publicArguments =
+        rocFn.args
+        |> List.mapWithIndex \argId, i ->
+            type = typeName types argId
+            c = Num.toStr i
+            ", arg\(c): \(type)"
+        |> Str.joinWith ""
+
+    externDefArguments =
+        rocFn.args
+        |> List.mapWithIndex \argId, i ->
+            type = typeName types argId
+            c = Num.toStr i
+            "arg\(c): *const \(type), "
+        |> Str.joinWith ""
+
+    externCallArguments =
+        rocFn.args
+        |> List.mapWithIndex \_, i ->
+            c = Num.toStr i
+            "&arg\(c), "
+        |> Str.joinWith ""
+
+    """
+    \(buf)pub const \(name) = extern struct {
+        closure_data: \(lambdaSet),
+
+        extern fn \(externName)(\(externDefArguments)closure_data: [*]u8, output: *\(ret)) callconv(.C) void;
+
+        /// Calls the closure. Ownership of the closure data is transferred back to Roc.
+        pub fn force(self: *\(name)\(publicArguments)) \(ret) {
+            var output: \(ret) = undefined;
+            \(externName)(\(externCallArguments)@ptrCast([*]u8, &self.closure_data), &output);
+            return output;
+        }
+    };
+
+
+    """
+
+generateStruct : Str, Types, TypeId, Str, List { name : Str, id : TypeId } -> Str
+generateStruct = \buf, types, id, name, fields ->
+    buf
+    |> Str.concat "pub const \(name) = extern struct {\n"
+    |> \b -> List.walk fields b (generateStructField types)
+    |> Str.concat "};\n\n"
+    |> generateLayoutChecks types id name
+
+generateStructField = \types ->
+    \accum, { name: fieldName, id } ->
+        typeStr = typeName types id
+        escapedFieldName = escapeKW fieldName
+
+        Str.concat accum "\(indent)\(escapedFieldName): \(typeStr),\n"
+
+structFieldList : RocStructFields -> List { name : Str, id : TypeId }
+structFieldList = \structFields ->
+    when structFields is
+        HasNoClosure fields -> fields
+        HasClosure fields -> List.map fields \{ name, id } -> { name, id }
+
+nameTagUnionPayloadFields : RocStructFields -> List { name : Str, id : TypeId }
+nameTagUnionPayloadFields = \payloadFields ->
+    # Tag union payloads have numbered fields, so we prefix them
+    # with an "f" because Zig doesn't allow struct fields to be numbers.
+    structFieldList payloadFields
+    |> List.map \{ name, id } -> { name: "f\(name)", id }
+
+generateLayoutChecks : Str, Types, TypeId, Str -> Str
+generateLayoutChecks = \buf, types, id, name ->
+    size = Types.size types id
+
+    if size == 0 then
+        buf
+    else
+        sizeStr = Num.toStr size
+        alignStr = Num.toStr (Types.alignment types id)
+
+        Str.concat
+            buf
+            """
+            comptime {
+                std.debug.assert(@sizeOf(\(name)) == \(sizeStr));
+                std.debug.assert(@alignOf(\(name)) == \(alignStr));
+            }
+
+
+            """
+
+generateEnumeration : Str, Str, List Str, U32 -> Str
+generateEnumeration = \buf, name, tags, size ->
+    buf
+    |> Str.concat "pub const \(name) = "
+    |> generateEnumBody tags size ""
+    |> Str.concat ";\n\n"
+
+# Writes out `enum(u8) { ... }` with the given tags, indented by `indents`.
+generateEnumBody : Str, List Str, U32, Str -> Str
+generateEnumBody = \buf, tags, size, indents ->
+    reprType = unsignedIntType size
+
+    buf
+    |> Str.concat "enum(\(reprType)) {\n"
+    |> \b ->
+        walkWithIndex tags b \accum, index, tagName ->
+            indexStr = Num.toStr index
+
+            Str.concat accum "\(indents)\(indent)\(tagName) = \(indexStr),\n"
+    |> Str.concat "\(indents)}"
+
+generateNonRecursiveTagUnion : Str, Types, TypeId, Str, List { name : Str, payload : [Some TypeId, None] }, U32, U32 -> Str
+generateNonRecursiveTagUnion = \buf, types, id, name, tags, discriminantSize, discriminantOffset ->
+    discriminantOffsetStr = Num.toStr discriminantOffset
+
+    tagFunctions = \accum, { name: tagName, payload } ->
+        accessors =
+            when payload is
+                Some payloadId ->
+                    payloadStr = typeName types payloadId
+
+                    """
+                        pub fn get\(tagName)(self: \(name)) \(payloadStr) {
+                            std.debug.assert(self.tag == .\(tagName));
+                            return self.payload.\(tagName);
+                        }
+
+                        pub fn make\(tagName)(payload: \(payloadStr)) \(name) {
+                            return .{ .payload = .{ .\(tagName) = payload }, .tag = .\(tagName) };
+                        }
+                    """
+
+                None ->
+                    """
+                        pub fn make\(tagName)() \(name) {
+                            return .{ .payload = undefined, .tag = .\(tagName) };
+                        }
+                    """
+
+        """
+        \(accum)
+
+            pub fn is\(tagName)(self: \(name)) bool {
+                return self.tag == .\(tagName);
+            }
+
+        \(accessors)
+        """
+
+    buf
+    |> Str.concat "pub const \(name) = extern struct {\n\(indent)payload: extern union {\n"
+    |> \b -> List.walk tags b (generateUnionField types (Str.concat indent indent))
+    |> Str.concat "\(indent)},\n\(indent)tag: Discriminant,\n\n\(indent)pub const Discriminant = "
+    |> generateEnumBody (List.map tags .name) discriminantSize indent
+    |> Str.concat
+        """
+        ;
+
+            \(discriminantDocComment)
+            pub fn discriminant(self: \(name)) Discriminant {
+                return self.tag;
+            }
+        """
+    |> \b -> List.walk tags b tagFunctions
+    |> Str.concat
+        """
+
+        };
+
+        comptime {
+            std.debug.assert(@offsetOf(\(name), "tag") == \(discriminantOffsetStr));
+        }
+
+
+        """
+    |> generateLayoutChecks types id name
+
+generateUnionField = \types, indents ->
+    \accum, { name: tagName, payload } ->
+        when payload is
+            Some id ->
+                typeStr = typeName types id
+
+                Str.concat accum "\(indents)\(tagName): \(typeStr),\n"
+
+            None ->
+                # Tags without a payload don't need any room in the union.
+                accum
+
+generateRecursiveTagUnion : Str, Types, TypeId, Str, List { name : Str, payload : [Some TypeId, None] }, U32, [Some U16, None] -> Str
+generateRecursiveTagUnion = \buf, types, id, name, tags, discriminantSize, nullTagIndex ->
+    nullCheck =
+        when nullTagIndex is
+            Some index ->
+                when List.get tags (Num.toNat index) is
+                    Ok { name: nullTag } ->
+                        """
+                                if (self.ptr == null) {
+                                    return .\(nullTag);
+                                }
+
+
+                        """
+
+                    Err OutOfBounds ->
+                        ""
+
+            None ->
+                ""
+
+    tagFunctions = \accum, { name: tagName, payload }, index ->
+        isNullTag = Some (Num.intCast index) == nullTagIndex
+
+        getter =
+            when payload is
+                Some payloadId ->
+                    payloadStr = typeName types payloadId
+
+                    """
+                        pub fn get\(tagName)(self: \(name)) \(payloadStr) {
+                            std.debug.assert(self.is\(tagName)());
+                            return self.payloadPtr().\(tagName);
+                        }
+
+
+                    """
+
+                None ->
+                    ""
+
+        constructor =
+            if isNullTag then
+                """
+                    pub fn make\(tagName)() \(name) {
+                        return .{ .ptr = null };
+                    }
+                """
+            else
+                { params, init } =
+                    when payload is
+                        Some payloadId ->
+                            payloadStr = typeName types payloadId
+
+                            { params: "payload: \(payloadStr)", init: "\n\(indent)\(indent)ptr.\(tagName) = payload;" }
+
+                        None ->
+                            { params: "", init: "" }
+
+                """
+                    pub fn make\(tagName)(\(params)) \(name) {
+                        const ptr = roc_std.allocRefcounted(Payload);\(init)
+                        return .{ .ptr = @intToPtr(*anyopaque, @ptrToInt(ptr) | @enumToInt(Discriminant.\(tagName))) };
+                    }
+                """
+
+        """
+        \(accum)
+
+            pub fn is\(tagName)(self: \(name)) bool {
+                return self.discriminant() == .\(tagName);
+            }
+
+        \(getter)\(constructor)
+        """
+
+    buf
+    |> Str.concat
+        """
+        pub const \(name) = extern struct {
+            // The discriminant is stored in the low bits of this pointer, so
+            // use discriminant() rather than dereferencing it directly.
+            ptr: ?*anyopaque,
+
+
+        """
+    |> Str.concat "\(indent)pub const Discriminant = "
+    |> generateEnumBody (List.map tags .name) discriminantSize indent
+    |> Str.concat ";\n\n\(indent)pub const Payload = extern union {\n"
+    |> \b -> List.walk tags b (generateUnionField types (Str.concat indent indent))
+    |> Str.concat
+        """
+            };
+
+            \(discriminantDocComment)
+            pub fn discriminant(self: \(name)) Discriminant {
+        \(nullCheck)        return @intToEnum(Discriminant, @truncate(u8, @ptrToInt(self.ptr) & roc_std.TAG_ID_MASK));
+            }
+
+            fn payloadPtr(self: \(name)) *Payload {
+                return @intToPtr(*Payload, @ptrToInt(self.ptr) & ~roc_std.TAG_ID_MASK);
+            }
+        """
+    |> \b -> walkWithIndex tags b \accum, index, tag -> tagFunctions accum tag index
+    |> Str.concat "\n};\n\n"
+    |> generateLayoutChecks types id name
+
+generateNullableUnwrapped : Str, Types, TypeId, Str, Str, Str, TypeId, [FirstTagIsNull, SecondTagIsNull] -> Str
+generateNullableUnwrapped = \buf, types, id, name, nullTag, nonNullTag, nonNullPayload, whichTagIsNull ->
+    payloadStr = typeName types nonNullPayload
+
+    tagNames =
+        when whichTagIsNull is
+            FirstTagIsNull -> [nullTag, nonNullTag]
+            SecondTagIsNull -> [nonNullTag, nullTag]
+
+    buf
+    |> Str.concat "pub const \(name) = extern struct {\n\(indent)ptr: ?*\(payloadStr),\n\n\(indent)pub const Discriminant = "
+    |> generateEnumBody tagNames 1 indent
+    |> Str.concat
+        """
+        ;
+
+            \(discriminantDocComment)
+            pub fn discriminant(self: \(name)) Discriminant {
+                return if (self.ptr == null) .\(nullTag) else .\(nonNullTag);
+            }
+
+            pub fn is\(nullTag)(self: \(name)) bool {
+                return self.ptr == null;
+            }
+
+            pub fn is\(nonNullTag)(self: \(name)) bool {
+                return self.ptr != null;
+            }
+
+            pub fn get\(nonNullTag)(self: \(name)) \(payloadStr) {
+                return self.ptr.?.*;
+            }
+
+            pub fn make\(nullTag)() \(name) {
+                return .{ .ptr = null };
+            }
+
+            pub fn make\(nonNullTag)(payload: \(payloadStr)) \(name) {
+                const ptr = roc_std.allocRefcounted(\(payloadStr));
+                ptr.* = payload;
+                return .{ .ptr = ptr };
+            }
+        };
+
+
+        """
+    |> generateLayoutChecks types id name
+
+generateNonNullableUnwrapped : Str, Types, TypeId, Str, Str, TypeId -> Str
+generateNonNullableUnwrapped = \buf, types, id, name, tagName, payload ->
+    payloadStr = typeName types payload
+
+    buf
+    |> Str.concat
+        """
+        pub const \(name) = extern struct {
+            ptr: *\(payloadStr),
+
+            pub fn get\(tagName)(self: \(name)) \(payloadStr) {
+                return self.ptr.*;
+            }
+
+            pub fn make\(tagName)(payload: \(payloadStr)) \(name) {
+                const ptr = roc_std.allocRefcounted(\(payloadStr));
+                ptr.* = payload;
+                return .{ .ptr = ptr };
+            }
+        };
+
+
+        """
+    |> generateLayoutChecks types id name
+
+generateSingleTagStruct : Str, Types, TypeId, Str, [HasNoClosure (List { id : TypeId }), HasClosure (List { name : Str, id : TypeId })] -> Str
+generateSingleTagStruct = \buf, types, id, name, payload ->
+    # Store single-tag unions as structs rather than unions,
+    # because they have only one alternative.
+    fields =
+        when payload is
+            HasNoClosure xs ->
+                List.mapWithIndex xs \{ id: fieldId }, index ->
+                    indexStr = Num.toStr index
+
+                    { name: "f\(indexStr)", id: fieldId }
+
+            HasClosure xs ->
+                List.map xs \{ name: fieldName, id: fieldId } -> { name: "f\(fieldName)", id: fieldId }
+
+    generateStruct buf types id name fields
+
+typeName : Types, TypeId -> Str
+typeName = \types, id ->
+    when Types.shape types id is
+        Unit -> "roc_std.RocUnit"
+        Unsized -> "roc_std.RocList(u8)"
+        EmptyTagUnion -> "roc_std.RocUnit"
+        RocStr -> "roc_std.RocStr"
+        Bool -> "bool"
+        Num U8 -> "u8"
+        Num U16 -> "u16"
+        Num U32 -> "u32"
+        Num U64 -> "u64"
+        Num U128 -> "u128"
+        Num I8 -> "i8"
+        Num I16 -> "i16"
+        Num I32 -> "i32"
+        Num I64 -> "i64"
+        Num I128 -> "i128"
+        Num F32 -> "f32"
+        Num F64 -> "f64"
+        Num Dec -> "roc_std.RocDec"
+        RocDict key value ->
+            keyName = typeName types key
+            valueName = typeName types value
+
+            "roc_std.RocDict(\(keyName), \(valueName))"
+
+        RocSet elem ->
+            elemName = typeName types elem
+
+            "roc_std.RocSet(\(elemName))"
+
+        RocList elem ->
+            elemName = typeName types elem
+
+            "roc_std.RocList(\(elemName))"
+
+        RocBox elem ->
+            elemName = typeName types elem
+
+            "roc_std.RocBox(\(elemName))"
+
+        RocResult ok err ->
+            okName = typeName types ok
+            errName = typeName types err
+
+            "roc_std.RocResult(\(okName), \(errName))"
+
+        RecursivePointer content ->
+            typeName types content
+
+        Struct { name } -> escapeKW name
+        TagUnionPayload { name } -> escapeKW name
+        TagUnion (NonRecursive { name }) -> escapeKW name
+        TagUnion (Recursive { name }) -> escapeKW name
+        TagUnion (Enumeration { name }) -> escapeKW name
+        TagUnion (NullableWrapped { name }) -> escapeKW name
+        TagUnion (NullableUnwrapped { name }) -> escapeKW name
+        TagUnion (NonNullableUnwrapped { name }) -> escapeKW name
+        TagUnion (SingleTagStruct { name }) -> escapeKW name
+        Function { functionName } -> escapeKW functionName
+
+unsignedIntType : U32 -> Str
+unsignedIntType = \size ->
+    when size is
+        0 | 1 -> "u8"
+        2 -> "u16"
+        4 -> "u32"
+        _ -> "u64"
+
+walkWithIndex = \list, originalState, f ->
+    stateWithId =
+        List.walk list { id: 0nat, state: originalState } \{ id, state }, elem ->
+            nextState = f state id elem
+
+            { id: id + 1, state: nextState }
+
+    stateWithId.state
+
+archName = \arch ->
+    when arch is
+        Aarch32 ->
+            "arm"
+
+        Aarch64 ->
+            "aarch64"
+
+        Wasm32 ->
+            "wasm32"
+
+        X86x32 ->
+            "x86"
+
+        X86x64 ->
+            "x86_64"
+
+zigArchTag = \arch ->
+    when arch is
+        Aarch32 ->
+            "arm"
+
+        Aarch64 ->
+            "aarch64"
+
+        Wasm32 ->
+            "wasm32"
+
+        X86x32 ->
+            "i386"
+
+        X86x64 ->
+            "x86_64"
+
+fileHeader =
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+    """
+
+archFileHeader =
+    """
+    \(fileHeader)
+    // Import roc_app.zig rather than this file; it picks the right one for the target.
+
+    const std = @import("std");
+    const roc_std = @import("roc_std.zig");
+
+
+    """
+
+rocStd =
+    """
+    \(fileHeader)
+    const std = @import("std");
+
+    extern fn roc_alloc(size: usize, alignment: u32) callconv(.C) ?*anyopaque;
+    extern fn roc_dealloc(c_ptr: *anyopaque, alignment: u32) callconv(.C) void;
+
+    /// Reference counts are stored in the word right before the data they count.
+    /// A refcount of REFCOUNT_ONE means there is exactly one reference, and every
+    /// additional reference adds one. Readonly data (such as string literals in
+    /// the binary) has a refcount of REFCOUNT_READONLY and is never freed.
+    pub const REFCOUNT_ONE: isize = std.math.minInt(isize);
+    pub const REFCOUNT_READONLY: isize = 0;
+
+    /// Recursive tag unions store their discriminant in the low bits of their pointer.
+    pub const TAG_ID_MASK: usize = if (@sizeOf(usize) == 8) 0b111 else 0b011;
+
+    const SEAMLESS_SLICE_BIT: usize = @bitCast(usize, @as(isize, std.math.minInt(isize)));
+
+    /// Roc values which are zero-sized, like `{}`.
+    pub const RocUnit = extern struct {};
+
+    fn headerSize(alignment: u32) u32 {
+        return if (alignment > @sizeOf(usize)) alignment else @sizeOf(usize);
+    }
+
+    /// Allocates room for a `T` with a refcount of one.
+    pub fn allocRefcounted(comptime T: type) *T {
+        const data = allocRefcountedBytes(@sizeOf(T), @alignOf(T));
+
+        return @ptrCast(*T, @alignCast(@alignOf(T), data));
+    }
+
+    fn allocRefcountedBytes(size: usize, alignment: u32) [*]u8 {
+        const header = headerSize(alignment);
+        const allocation = roc_alloc(@as(usize, header) + size, header) orelse @panic("roc_alloc returned null");
+        const data = @ptrCast([*]u8, allocation) + header;
+
+        refcountPtr(data).* = REFCOUNT_ONE;
+
+        return data;
+    }
+
+    /// Frees data that was allocated by allocRefcounted (or by Roc itself).
+    pub fn freeRefcounted(data: [*]u8, alignment: u32) void {
+        const header = headerSize(alignment);
+
+        roc_dealloc(@ptrCast(*anyopaque, data - header), header);
+    }
+
+    fn refcountPtr(data: [*]u8) *isize {
+        return @intToPtr(*isize, @ptrToInt(data) - @sizeOf(usize));
+    }
+
+    pub fn increment(refcount: *isize) void {
+        if (refcount.* != REFCOUNT_READONLY) {
+            // Incrementing past the maximum refcount wraps to REFCOUNT_READONLY,
+            // which leaks the data instead of freeing it too early.
+            refcount.* += 1;
+        }
+    }
+
+    /// Returns true once there are no more references left, at which point the
+    /// caller must decrement whatever the data refers to and then free it with
+    /// freeRefcounted.
+    pub fn decrement(refcount: *isize) bool {
+        if (refcount.* == REFCOUNT_READONLY) {
+            return false;
+        } else if (refcount.* == REFCOUNT_ONE) {
+            return true;
+        } else {
+            refcount.* -= 1;
+            return false;
+        }
+    }
+
+    pub const RocStr = extern struct {
+        bytes: ?[*]u8,
+        len: usize,
+        capacity: usize,
+
+        const SMALL_CAPACITY = @sizeOf(RocStr) - 1;
+
+        pub fn empty() RocStr {
+            return .{ .bytes = null, .len = 0, .capacity = SEAMLESS_SLICE_BIT };
+        }
+
+        /// Copies the given bytes into a new string.
+        pub fn fromSlice(slice: []const u8) RocStr {
+            if (slice.len <= SMALL_CAPACITY) {
+                var str = RocStr.empty();
+                const bytes = @ptrCast([*]u8, &str);
+
+                std.mem.copy(u8, bytes[0..slice.len], slice);
+                bytes[SMALL_CAPACITY] = @intCast(u8, slice.len) | 0b1000_0000;
+
+                return str;
+            } else {
+                const bytes = allocRefcountedBytes(slice.len, @alignOf(usize));
+
+                std.mem.copy(u8, bytes[0..slice.len], slice);
+
+                return .{ .bytes = bytes, .len = slice.len, .capacity = slice.len };
+            }
+        }
+
+        /// Strings which fit into SMALL_CAPACITY bytes are stored inline, with
+        /// their length in the last byte.
+        pub fn isSmall(self: *const RocStr) bool {
+            return @bitCast(isize, self.capacity) < 0;
+        }
+
+        pub fn length(self: *const RocStr) usize {
+            if (self.isSmall()) {
+                return @ptrCast([*]const u8, self)[SMALL_CAPACITY] ^ 0b1000_0000;
+            } else {
+                return self.len & ~SEAMLESS_SLICE_BIT;
+            }
+        }
+
+        pub fn asSlice(self: *const RocStr) []const u8 {
+            if (self.isSmall()) {
+                return @ptrCast([*]const u8, self)[0..self.length()];
+            } else {
+                return self.bytes.?[0..self.length()];
+            }
+        }
+
+        /// Returns null for small strings, which are not reference counted.
+        pub fn refcount(self: *const RocStr) ?*isize {
+            if (self.isSmall()) {
+                return null;
+            } else if (@bitCast(isize, self.len) < 0) {
+                // This is a slice of another string, which holds the refcount.
+                return refcountPtr(@intToPtr([*]u8, self.capacity << 1));
+            } else {
+                return refcountPtr(self.bytes.?);
+            }
+        }
+
+        pub fn incref(self: *const RocStr) void {
+            if (self.refcount()) |rc| {
+                increment(rc);
+            }
+        }
+
+        pub fn decref(self: *const RocStr) void {
+            if (self.refcount()) |rc| {
+                if (decrement(rc)) {
+                    freeRefcounted(@ptrCast([*]u8, rc) + @sizeOf(usize), @alignOf(usize));
+                }
+            }
+        }
+    };
+
+    pub fn RocList(comptime T: type) type {
+        return extern struct {
+            elements: ?[*]T,
+            len: usize,
+            capacity: usize,
+
+            const Self = @This();
+
+            pub fn empty() Self {
+                return .{ .elements = null, .len = 0, .capacity = 0 };
+            }
+
+            /// Copies the given elements into a new list.
+            pub fn fromSlice(slice: []const T) Self {
+                if (slice.len == 0) {
+                    return Self.empty();
+                }
+
+                const bytes = allocRefcountedBytes(slice.len * @sizeOf(T), elementsAlignment());
+                const elements = @ptrCast([*]T, @alignCast(@alignOf(T), bytes));
+
+                std.mem.copy(T, elements[0..slice.len], slice);
+
+                return .{ .elements = elements, .len = slice.len, .capacity = slice.len };
+            }
+
+            pub fn isSeamlessSlice(self: Self) bool {
+                return @bitCast(isize, self.capacity) < 0;
+            }
+
+            pub fn asSlice(self: Self) []const T {
+                if (self.elements) |elements| {
+                    return elements[0..self.len];
+                } else {
+                    return &[_]T{};
+                }
+            }
+
+            /// Returns null for empty lists, which are not reference counted.
+            pub fn refcount(self: Self) ?*isize {
+                if (self.elements == null) {
+                    return null;
+                } else if (self.isSeamlessSlice()) {
+                    // This is a slice of another list, which holds the refcount.
+                    return refcountPtr(@intToPtr([*]u8, self.capacity << 1));
+                } else {
+                    return refcountPtr(@ptrCast([*]u8, self.elements.?));
+                }
+            }
+
+            pub fn incref(self: Self) void {
+                if (self.refcount()) |rc| {
+                    increment(rc);
+                }
+            }
+
+            /// Note that this does not decrement the elements when the list is freed.
+            pub fn decref(self: Self) void {
+                if (self.refcount()) |rc| {
+                    if (decrement(rc)) {
+                        freeRefcounted(@ptrCast([*]u8, rc) + @sizeOf(usize), elementsAlignment());
+                    }
+                }
+            }
+
+            fn elementsAlignment() u32 {
+                return headerSize(@alignOf(T));
+            }
+        };
+    }
+
+    pub fn RocBox(comptime T: type) type {
+        return extern struct {
+            contents: *T,
+
+            const Self = @This();
+
+            pub fn init(value: T) Self {
+                const contents = allocRefcounted(T);
+
+                contents.* = value;
+
+                return .{ .contents = contents };
+            }
+
+            pub fn refcount(self: Self) *isize {
+                return refcountPtr(@ptrCast([*]u8, self.contents));
+            }
+        };
+    }
+
+    /// Dict entries are stored with the more-aligned of the key and value first.
+    pub fn RocDictItem(comptime K: type, comptime V: type) type {
+        if (@alignOf(K) >= @alignOf(V)) {
+            return extern struct { key: K, value: V };
+        } else {
+            return extern struct { value: V, key: K };
+        }
+    }
+
+    pub fn RocDict(comptime K: type, comptime V: type) type {
+        return extern struct {
+            items: RocList(RocDictItem(K, V)),
+        };
+    }
+
+    pub fn RocSet(comptime T: type) type {
+        return RocDict(T, RocUnit);
+    }
+
+    pub fn RocResult(comptime T: type, comptime E: type) type {
+        return extern struct {
+            payload: extern union {
+                ok: T,
+                err: E,
+            },
+            tag: Tag,
+
+            const Self = @This();
+
+            pub const Tag = enum(u8) {
+                Err = 0,
+                Ok = 1,
+            };
+
+            pub fn isOk(self: Self) bool {
+                return self.tag == .Ok;
+            }
+
+            pub fn isErr(self: Self) bool {
+                return self.tag == .Err;
+            }
+
+            pub fn makeOk(value: T) Self {
+                return .{ .payload = .{ .ok = value }, .tag = .Ok };
+            }
+
+            pub fn makeErr(value: E) Self {
+                return .{ .payload = .{ .err = value }, .tag = .Err };
+            }
+        };
+    }
+
+    /// A fixed-point decimal, stored as an integer scaled by 10^18.
+    pub const RocDec = extern struct {
+        num: i128,
+    };
+
+    """
+
+indent = "    "
+discriminantDocComment = "/// Returns which variant this tag union holds. Note that this never includes a payload!"
+
+reservedKeywords = Set.fromList [
+    "addrspace",
+    "align",
+    "allowzero",
+    "and",
+    "anyframe",
+    "anyopaque",
+    "anytype",
+    "asm",
+    "async",
+    "await",
+    "bool",
+    "break",
+    "callconv",
+    "catch",
+    "comptime",
+    "const",
+    "continue",
+    "defer",
+    "else",
+    "enum",
+    "errdefer",
+    "error",
+    "export",
+    "extern",
+    "false",
+    "fn",
+    "for",
+    "if",
+    "inline",
+    "isize",
+    "linksection",
+    "noalias",
+    "noinline",
+    "nosuspend",
+    "null",
+    "opaque",
+    "or",
+    "orelse",
+    "packed",
+    "pub",
+    "resume",
+    "return",
+    "struct",
+    "suspend",
+    "switch",
+    "test",
+    "threadlocal",
+    "true",
+    "try",
+    "type",
+    "undefined",
+    "union",
+    "unreachable",
+    "usingnamespace",
+    "usize",
+    "var",
+    "void",
+    "volatile",
+    "while",
+]
+
+escapeKW = \input ->
+    # use a quoted identifier for this, to prevent a syntax error due to using a reserved keyword.
+    # https://ziglang.org/documentation/0.9.1/#Identifiers
+    if Set.contains reservedKeywords input then
+        "@\"\(input)\""
+    else
+        input
@@ -6,7 +6,7 @@ use roc_build::{
     link::{LinkType, LinkingStrategy},
     program::{
         build_file, handle_error_module, handle_loading_problem, standard_load_config,
-        BuildFileError, BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions,
+        BuildFileError, BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions, EmitOptions,
     },
 };
 use roc_collections::MutMap;
@@ -51,12 +51,14 @@ pub fn generate(input_path: &Path, output_path: &Path, spec_path: &Path) -> io::
                 backend: CodeGenBackend::Llvm(LlvmBackendMode::BinaryGlue),
                 opt_level: OptLevel::Development,
                 emit_debug_info: false,
+                emit: EmitOptions::default(),
             };
 
             let load_config = standard_load_config(
                 &triple,
                 BuildOrdering::BuildIfChecks,
                 Threading::AllAvailable,
+                RenderTarget::ColorTerminal,
             );
 
             let arena = ManuallyDrop::new(Bump::new());
@@ -87,6 +89,8 @@ pub fn generate(input_path: &Path, output_path: &Path, spec_path: &Path) -> io::
                     problems,
                     total_time,
                     expect_metadata: _,
+                    emitted_paths: _,
+                    source_paths: _,
                 }) => {
                     // TODO: Should binary_path be update to deal with extensions?
                     use target_lexicon::OperatingSystem;
@@ -188,10 +192,16 @@ pub fn generate(input_path: &Path, output_path: &Path, spec_path: &Path) -> io::
 
                     Ok(0)
                 }
-                Err(BuildFileError::ErrorModule { module, total_time }) => {
-                    handle_error_module(module, total_time, spec_path.as_os_str(), true)
+                Err(BuildFileError::ErrorModule { module, total_time }) => handle_error_module(
+                    module,
+                    total_time,
+                    spec_path.as_os_str(),
+                    true,
+                    RenderTarget::ColorTerminal,
+                ),
+                Err(BuildFileError::LoadingProblem(problem)) => {
+                    handle_loading_problem(problem, RenderTarget::ColorTerminal)
                 }
-                Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
             }
         }
         Err(err) => match err.kind() {
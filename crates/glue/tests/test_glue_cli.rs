@@ -138,6 +138,173 @@ mod glue_cli_run {
         "#),
     }
 
+    /// Generates a test for each of the given fixtures, which runs the C glue
+    /// spec on the fixture's platform and verifies that the resulting header
+    /// compiles, including its checks that every type has the layout Roc expects.
+    macro_rules! c_headers {
+        ($($test_name:ident:$fixture_dir:expr,)+) => {
+            $(
+                #[test]
+                #[allow(non_snake_case)]
+                fn $test_name() {
+                    let dir = fixtures_dir($fixture_dir);
+
+                    check_c_header_for(&dir);
+                }
+            )*
+        }
+    }
+
+    c_headers! {
+        c_basic_record:"basic-record",
+        c_nested_record:"nested-record",
+        c_enumeration:"enumeration",
+        c_single_tag_union:"single-tag-union",
+        c_union_with_padding:"union-with-padding",
+        c_union_without_padding:"union-without-padding",
+        c_nullable_wrapped:"nullable-wrapped",
+        c_nullable_unwrapped:"nullable-unwrapped",
+        c_nonnullable_unwrapped:"nonnullable-unwrapped",
+        c_basic_recursive_union:"basic-recursive-union",
+        c_advanced_recursive_union:"advanced-recursive-union",
+        c_list_recursive_union:"list-recursive-union",
+        c_option:"option",
+        c_multiple_modules:"multiple-modules",
+        c_arguments:"arguments",
+        c_rocresult:"rocresult",
+        c_return_function:"return-function",
+    }
+
+    /// Generates a test for each of the given fixtures, which runs the Zig glue
+    /// spec on the fixture's platform and verifies that the resulting files
+    /// compile, including their checks that every type has the layout Roc expects.
+    macro_rules! zig_glue {
+        ($($test_name:ident:$fixture_dir:expr,)+) => {
+            $(
+                #[test]
+                #[allow(non_snake_case)]
+                fn $test_name() {
+                    let dir = fixtures_dir($fixture_dir);
+
+                    check_zig_glue_for(&dir);
+                }
+            )*
+        }
+    }
+
+    zig_glue! {
+        zig_basic_record:"basic-record",
+        zig_nested_record:"nested-record",
+        zig_enumeration:"enumeration",
+        zig_single_tag_union:"single-tag-union",
+        zig_union_with_padding:"union-with-padding",
+        zig_nullable_wrapped:"nullable-wrapped",
+        zig_nullable_unwrapped:"nullable-unwrapped",
+        zig_nonnullable_unwrapped:"nonnullable-unwrapped",
+        zig_basic_recursive_union:"basic-recursive-union",
+        zig_list_recursive_union:"list-recursive-union",
+        zig_arguments:"arguments",
+        zig_rocresult:"rocresult",
+        zig_return_function:"return-function",
+    }
+
+    fn check_c_header_for(platform_dir: &Path) {
+        let glue_dir = generate_glue_with_spec(platform_dir, "CGlue.roc", "c_glue");
+
+        let cc_out = std::process::Command::new("cc")
+            .args(["-std=c11", "-Wall", "-Werror", "-fsyntax-only", "-x", "c"])
+            .arg(glue_dir.join("roc_app.h"))
+            .output()
+            .expect("Unable to run cc");
+
+        assert!(
+            cc_out.status.success(),
+            "The generated C header did not compile:\n\n{}",
+            String::from_utf8_lossy(&cc_out.stderr)
+        );
+    }
+
+    fn check_zig_glue_for(platform_dir: &Path) {
+        let glue_dir = generate_glue_with_spec(platform_dir, "ZigGlue.roc", "zig_glue");
+
+        // Zig only analyzes declarations which are referenced, so reference
+        // everything declared for this architecture.
+        fs::write(
+            glue_dir.join("check.zig"),
+            format!(
+                indoc!(
+                    r#"
+                    const std = @import("std");
+                    const roc_app = @import("roc_app.zig");
+                    const arch = @import("{}.zig");
+
+                    comptime {{
+                        inline for (std.meta.declarations(arch)) |decl| {{
+                            if (decl.is_pub) {{
+                                _ = @field(arch, decl.name);
+                            }}
+                        }}
+                    }}
+                    "#
+                ),
+                std::env::consts::ARCH
+            ),
+        )
+        .unwrap();
+
+        let zig_out = std::process::Command::new("zig")
+            .args(["build-obj", "-fno-emit-bin", "check.zig"])
+            .current_dir(&glue_dir)
+            .output()
+            .expect("Unable to run zig");
+
+        assert!(
+            zig_out.status.success(),
+            "The generated Zig glue did not compile:\n\n{}",
+            String::from_utf8_lossy(&zig_out.stderr)
+        );
+    }
+
+    /// Runs the given glue spec from this crate's src/ directory on the fixture's
+    /// platform, and returns the directory it wrote its output to.
+    fn generate_glue_with_spec(
+        platform_dir: &Path,
+        spec_file_name: &str,
+        glue_dir_name: &str,
+    ) -> std::path::PathBuf {
+        let platform_module_path = platform_dir.join("platform.roc");
+        let glue_dir = platform_dir.join("src").join(glue_dir_name);
+        let glue_spec = platform_dir
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("src")
+            .join(spec_file_name);
+
+        if glue_dir.exists() {
+            fs::remove_dir_all(&glue_dir).unwrap_or_else(|_| {
+                panic!(
+                    "Unable to remove {} dir in order to regenerate it in the test",
+                    glue_dir_name
+                )
+            });
+        }
+
+        let glue_out = run_glue([
+            "glue".to_string(),
+            glue_spec.to_str().unwrap().to_string(),
+            glue_dir.to_str().unwrap().to_string(),
+            platform_module_path.to_str().unwrap().to_string(),
+        ]);
+
+        assert!(glue_out.status.success(), "bad status {:?}", glue_out);
+
+        glue_dir
+    }
+
     fn check_for_tests(all_fixtures: &mut roc_collections::VecSet<String>) {
         use roc_collections::VecSet;
 
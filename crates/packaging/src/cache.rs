@@ -5,7 +5,7 @@ use {
     std::fs,
 };
 #[cfg(not(target_family = "wasm"))]
-const MAX_DOWNLOAD_BYTES: u64 = 32 * 1_000_000_000; // GB
+pub(crate) const MAX_DOWNLOAD_BYTES: u64 = 32 * 1_000_000_000; // GB
 
 use std::path::{Path, PathBuf};
 
@@ -59,7 +59,8 @@ fn nixos_error_if_dynamic(url: &str, dest_dir: &Path) {
 /// into that dir. If the cache dir on the filesystem, then look into it to see if we already
 /// have an entry for the given URL. If we do, return its info. If we don't already have it, then:
 ///
-/// - Download and decompress the compressed tarball from the given URL
+/// - Look for the package's tarball in the package mirrors (see `package_mirrors`), and
+///   if none of them has it, download and decompress the compressed tarball from the given URL
 /// - Verify its bytes against the hash in the URL
 /// - Extract the tarball's contents into the appropriate cache directory
 ///
@@ -69,6 +70,17 @@ fn nixos_error_if_dynamic(url: &str, dest_dir: &Path) {
 pub fn install_package<'a>(
     roc_cache_dir: RocCacheDir<'_>,
     url: &'a str,
+) -> Result<(PathBuf, Option<&'a str>), Problem> {
+    install_package_with_mirrors(roc_cache_dir, url, &package_mirrors())
+}
+
+/// Like `install_package`, except it checks the given mirror directories instead of the ones
+/// configured via the ROC_PACKAGE_MIRRORS environment variable.
+#[cfg(not(target_family = "wasm"))]
+pub fn install_package_with_mirrors<'a>(
+    roc_cache_dir: RocCacheDir<'_>,
+    url: &'a str,
+    mirrors: &[PathBuf],
 ) -> Result<(PathBuf, Option<&'a str>), Problem> {
     let PackageMetadata {
         cache_subdir,
@@ -94,19 +106,26 @@ pub fn install_package<'a>(
 
                 Ok((dest_dir, root_module_filename))
             } else {
-                // Download into a tempdir; only move it to dest_dir if hash verification passes.
-                println!(
-                    "Downloading \u{001b}[36m{url}\u{001b}[0m\n    into {}\n",
-                    cache_dir.display()
-                );
+                // Unpack into a tempdir; only move it to dest_dir if hash verification passes.
                 let tempdir = tempfile::tempdir().map_err(Problem::IoErr)?;
                 let tempdir_path = tempdir.path();
-                let downloaded_hash =
-                    https::download_and_hash(url, tempdir_path, MAX_DOWNLOAD_BYTES)?;
+                let unpacked_hash = match find_in_mirrors(mirrors, cache_subdir, content_hash) {
+                    Some(tarball_path) => {
+                        // A mirror has this package, so there's no need to go to the network.
+                        https::unpack_tarball(&tarball_path, tempdir_path)?
+                    }
+                    None => {
+                        println!(
+                            "Downloading \u{001b}[36m{url}\u{001b}[0m\n    into {}\n",
+                            cache_dir.display()
+                        );
+
+                        https::download_and_hash(url, tempdir_path, MAX_DOWNLOAD_BYTES)?
+                    }
+                };
 
-                // Download the tarball into memory and verify it.
                 // The tarball name is the hash of its contents.
-                if downloaded_hash == content_hash {
+                if unpacked_hash == content_hash {
                     // Now that we've verified the hash, rename the tempdir to the real dir.
 
                     // Create the destination dir's parent dir, since it may not exist yet.
@@ -144,7 +163,7 @@ pub fn install_package<'a>(
                 } else {
                     Err(Problem::InvalidContentHash {
                         expected: content_hash.to_string(),
-                        actual: downloaded_hash,
+                        actual: unpacked_hash,
                     })
                 }
             }
@@ -160,6 +179,64 @@ pub fn install_package<'a>(
     }
 }
 
+/// The environment variable which configures package mirrors; see `package_mirrors`.
+pub const ROC_PACKAGE_MIRRORS: &str = "ROC_PACKAGE_MIRRORS";
+
+/// This looks up an environment variable, so it should ideally be called once and then cached!
+///
+/// Returns the directories listed (separated by commas) in the ROC_PACKAGE_MIRRORS environment
+/// variable. Each one can be either a plain path or a file:// URL, e.g.
+/// `ROC_PACKAGE_MIRRORS=vendor,file:///srv/roc-mirror`
+///
+/// Before downloading a package from a URL, we look in each of these (in order) for its
+/// uncompressed tarball, at the same path as in the URL. For example, the tarball for
+/// https://example.com/roc-packages/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar.br
+/// would be at vendor/example.com/roc-packages/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar,
+/// which is where `roc vendor` puts it. Tarballs from mirrors are verified against the hash
+/// in the URL just like downloaded ones.
+pub fn package_mirrors() -> Vec<PathBuf> {
+    match std::env::var(ROC_PACKAGE_MIRRORS) {
+        Ok(mirrors) => parse_package_mirrors(&mirrors),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn parse_package_mirrors(mirrors: &str) -> Vec<PathBuf> {
+    mirrors
+        .split(',')
+        .map(str::trim)
+        .filter(|mirror| !mirror.is_empty())
+        .map(|mirror| PathBuf::from(mirror.strip_prefix("file://").unwrap_or(mirror)))
+        .collect()
+}
+
+/// Returns the path to the package's uncompressed tarball in the first mirror which has it.
+pub fn find_in_mirrors(
+    mirrors: &[PathBuf],
+    cache_subdir: &str,
+    content_hash: &str,
+) -> Option<PathBuf> {
+    mirrors
+        .iter()
+        .map(|mirror| mirror_tarball_path(mirror, cache_subdir, content_hash))
+        .find(|path| path.is_file())
+}
+
+/// e.g. vendor/example.com/roc-packages/jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar
+pub fn mirror_tarball_path(mirror: &Path, cache_subdir: &str, content_hash: &str) -> PathBuf {
+    mirror
+        .join(cache_subdir)
+        .join(format!("{content_hash}.tar"))
+}
+
+#[test]
+fn mirrors_from_env_var() {
+    assert_eq!(
+        parse_package_mirrors("vendor, file:///srv/roc-mirror,,"),
+        vec![PathBuf::from("vendor"), PathBuf::from("/srv/roc-mirror")]
+    );
+}
+
 #[cfg(windows)]
 // e.g. the "Roc" in %APPDATA%\\Roc
 const ROC_CACHE_DIR_NAME: &str = "Roc";
@@ -1,4 +1,5 @@
 use std::{
+    fs::File,
     io::{self, Read},
     path::Path,
 };
@@ -123,6 +124,39 @@ pub fn download_and_hash(
     dest_dir: &Path,
     max_download_bytes: u64,
 ) -> Result<String, Problem> {
+    let (encoding, reader) = request(url, max_download_bytes)?;
+
+    decompress_into(dest_dir, encoding, reader)
+}
+
+/// Download the tarball at the given URL and write it to dest_path, decompressed (so, as a
+/// plain .tar file) but not unpacked. Returns the base64url-encoded BLAKE3 hash of what was
+/// written, which is what the URL's content hash needs to match.
+pub fn download_tarball(
+    url: &str,
+    dest_path: &Path,
+    max_download_bytes: u64,
+) -> Result<String, Problem> {
+    let (encoding, reader) = request(url, max_download_bytes)?;
+    let mut hash_reader = HashReader::new(decoder(encoding, reader));
+    let mut file = File::create(dest_path).map_err(Problem::IoErr)?;
+
+    io::copy(&mut hash_reader, &mut file).map_err(Problem::IoErr)?;
+
+    Ok(base64_url::encode(hash_reader.finalize().as_bytes()))
+}
+
+/// Unpack the uncompressed .tar file at the given path into dest_dir, and return the
+/// base64url-encoded BLAKE3 hash of the file.
+pub fn unpack_tarball(tarball_path: &Path, dest_dir: &Path) -> Result<String, Problem> {
+    let file = File::open(tarball_path).map_err(Problem::IoErr)?;
+
+    hash_and_unpack(dest_dir, file)
+}
+
+/// Send a GET request to the given URL, and return the response's Content-Encoding along with
+/// a reader for its body which will stop after max_download_bytes.
+fn request(url: &str, max_download_bytes: u64) -> Result<(Encoding, impl Read), Problem> {
     // TODO apparently it really improves performance to construct a Client once and then reuse it,
     // instead of making a new Client for every request.
     // Per https://github.com/seanmonstar/reqwest/issues/1454#issuecomment-1026076701
@@ -153,7 +187,7 @@ pub fn download_and_hash(
 
     // Use .take to prevent a malicious server from sending back bytes
     // until system resources are exhausted!
-    decompress_into(dest_dir, encoding, resp.take(max_download_bytes))
+    Ok((encoding, resp.take(max_download_bytes)))
 }
 
 /// The content encodings we support
@@ -239,17 +273,19 @@ fn decompress_into(
     encoding: Encoding,
     reader: impl Read,
 ) -> Result<String, Problem> {
+    hash_and_unpack(dest_dir, decoder(encoding, reader))
+}
+
+/// Wrap the given reader in one which decompresses its bytes using the given encoding.
+fn decoder<'r>(encoding: Encoding, reader: impl Read + 'r) -> Box<dyn Read + 'r> {
     match encoding {
-        Encoding::Brotli => hash_and_unpack(
-            dest_dir,
-            brotli::Decompressor::new(reader, BROTLI_BUFFER_BYTES),
-        ),
+        Encoding::Brotli => Box::new(brotli::Decompressor::new(reader, BROTLI_BUFFER_BYTES)),
         Encoding::Gzip => {
             // Note: GzDecoder::new immediately parses the gzip header (so, calls read())
-            hash_and_unpack(dest_dir, flate2::read::GzDecoder::new(reader))
+            Box::new(flate2::read::GzDecoder::new(reader))
         }
-        Encoding::Deflate => hash_and_unpack(dest_dir, flate2::read::DeflateDecoder::new(reader)),
-        Encoding::Uncompressed => hash_and_unpack(dest_dir, reader),
+        Encoding::Deflate => Box::new(flate2::read::DeflateDecoder::new(reader)),
+        Encoding::Uncompressed => Box::new(reader),
     }
 }
 
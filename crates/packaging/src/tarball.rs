@@ -250,7 +250,7 @@ fn add_dot_roc_files<W: Write>(
     Ok(())
 }
 
-fn read_header<'a>(
+pub(crate) fn read_header<'a>(
     arena: &'a Bump,
     buf: &'a mut Vec<u8>,
     path: &'a Path,
@@ -268,9 +268,17 @@ fn read_header<'a>(
     // (We can't use that for the parser state and still return Module<'a> unfortunately.)
     let arena_buf = bumpalo::collections::Vec::from_iter_in(buf.iter().copied(), arena);
     let parse_state = State::new(arena_buf.into_bump_slice());
-    let (module, _) = parse_header(arena, parse_state).unwrap_or_else(|_err| {
-        todo!(); // TODO report a nice error and exit 1 - or maybe just return Err, for better testability?
-    });
+    // TODO report a nice error instead of the parser's debug output
+    let (module, _) = parse_header(arena, parse_state).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Unable to parse the header of {}: {:?}",
+                path.to_string_lossy(),
+                err
+            ),
+        )
+    })?;
 
     Ok(module)
 }
@@ -0,0 +1,213 @@
+use crate::cache::{self, RocCacheDir};
+use crate::https::{self, PackageMetadata, Problem};
+use crate::tarball::read_header;
+use bumpalo::Bump;
+use roc_parse::ast::{ExtractSpaces, Header};
+use roc_parse::header::{AppHeader, PackageEntry, PackageHeader, PlatformHeader, To};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A URL package which `vendor` copied into the vendor dir.
+#[derive(Debug)]
+pub struct VendoredPackage {
+    pub url: String,
+    /// Where the package's uncompressed tarball ended up, inside the vendor dir.
+    pub tarball_path: PathBuf,
+}
+
+/// Find every package that the given module depends on via a URL - including the
+/// dependencies of those packages, and of any packages referred to by a relative path -
+/// and store its uncompressed tarball in vendor_dir, after verifying it against the
+/// hash in the URL.
+///
+/// The tarballs are laid out the way `cache::package_mirrors` expects, so pointing the
+/// ROC_PACKAGE_MIRRORS environment variable at vendor_dir lets later builds resolve all
+/// of these packages without going to the network.
+///
+/// Tarballs which are already in vendor_dir (and match their hashes) are not downloaded again.
+pub fn vendor(
+    root_module: &Path,
+    vendor_dir: &Path,
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<Vec<VendoredPackage>, Problem> {
+    let mirrors = [vendor_dir.to_path_buf()];
+    let mut vendored = Vec::new();
+    let mut visited_urls = HashSet::new();
+    let mut visited_modules = HashSet::new();
+    let mut stack = vec![root_module.to_path_buf()];
+
+    while let Some(module_path) = stack.pop() {
+        if !visited_modules.insert(module_path.clone()) {
+            continue;
+        }
+
+        for src in package_sources(&module_path)? {
+            if src.starts_with("https://") {
+                if !visited_urls.insert(src.clone()) {
+                    continue;
+                }
+
+                let tarball_path = vendor_package(&src, vendor_dir)?;
+
+                // Install the package from the tarball we just vendored, so that we can
+                // read its root module's header to find the packages it depends on in turn.
+                let (package_dir, opt_root_module) =
+                    cache::install_package_with_mirrors(roc_cache_dir, &src, &mirrors)?;
+
+                stack.push(package_dir.join(opt_root_module.unwrap_or("main.roc")));
+                vendored.push(VendoredPackage {
+                    url: src,
+                    tarball_path,
+                });
+            } else if let Some(module_dir) = module_path.parent() {
+                // Packages referred to by a path are relative to the module that refers to them.
+                stack.push(module_dir.join(src));
+            }
+        }
+    }
+
+    Ok(vendored)
+}
+
+/// Store the uncompressed tarball for the given URL in vendor_dir, and return its path.
+fn vendor_package(url: &str, vendor_dir: &Path) -> Result<PathBuf, Problem> {
+    let PackageMetadata {
+        cache_subdir,
+        content_hash,
+        ..
+    } = PackageMetadata::try_from(url).map_err(Problem::InvalidUrl)?;
+    let dest_path = cache::mirror_tarball_path(vendor_dir, cache_subdir, content_hash);
+
+    if dest_path.is_file() {
+        let bytes = fs::read(&dest_path).map_err(Problem::IoErr)?;
+
+        if base64_url::encode(blake3::hash(&bytes).as_bytes()) == content_hash {
+            return Ok(dest_path);
+        }
+    }
+
+    println!("Vendoring \u{001b}[36m{url}\u{001b}[0m");
+
+    // Download next to the destination; only move it into place if hash verification passes.
+    let parent_dir = dest_path.parent().unwrap();
+    fs::create_dir_all(parent_dir).map_err(Problem::IoErr)?;
+    let partial_path = dest_path.with_extension("tar.part");
+    let downloaded_hash = https::download_tarball(url, &partial_path, cache::MAX_DOWNLOAD_BYTES)?;
+
+    if downloaded_hash == content_hash {
+        fs::rename(&partial_path, &dest_path).map_err(Problem::IoErr)?;
+
+        Ok(dest_path)
+    } else {
+        fs::remove_file(&partial_path).map_err(Problem::IoErr)?;
+
+        Err(Problem::InvalidContentHash {
+            expected: content_hash.to_string(),
+            actual: downloaded_hash,
+        })
+    }
+}
+
+/// The URLs and paths of all the packages listed in the given module's header.
+fn package_sources(module_path: &Path) -> Result<Vec<String>, Problem> {
+    let arena = Bump::new();
+    let mut buf = Vec::new();
+    let module = read_header(&arena, &mut buf, module_path).map_err(Problem::IoErr)?;
+
+    let (entries, opt_platform) = match module.header {
+        Header::App(AppHeader {
+            packages, provides, ..
+        }) => {
+            let entries = match packages {
+                Some(packages) => packages.item.items,
+                None => &[],
+            };
+
+            // e.g. `provides [main] to "https://example.com/platform.tar.br"`
+            let opt_platform = match provides.to.value {
+                To::NewPackage(package_name) => Some(package_name),
+                To::ExistingPackage(_) => None,
+            };
+
+            (entries, opt_platform)
+        }
+        Header::Package(PackageHeader { packages, .. })
+        | Header::Platform(PlatformHeader { packages, .. }) => (packages.item.items, None),
+        Header::Interface(_) | Header::Hosted(_) => (&[][..], None),
+    };
+
+    let sources = entries
+        .iter()
+        .map(|entry| {
+            let PackageEntry { package_name, .. } = entry.value.extract_spaces().item;
+
+            package_name.value
+        })
+        .chain(opt_platform)
+        .map(|package_name| package_name.to_str().to_string())
+        .collect();
+
+    Ok(sources)
+}
+
+#[test]
+fn vendor_installs_from_existing_tarball() {
+    use crate::tarball::{self, Compression};
+
+    let package_dir = tempfile::tempdir().unwrap();
+    let vendor_dir = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let app_dir = tempfile::tempdir().unwrap();
+
+    fs::write(
+        package_dir.path().join("main.roc"),
+        "package \"json\"\n    exposes [Json]\n    packages {}\n",
+    )
+    .unwrap();
+    fs::write(
+        package_dir.path().join("Json.roc"),
+        "interface Json\n    exposes []\n    imports []\n",
+    )
+    .unwrap();
+
+    let tarball_name = tarball::build(
+        &package_dir.path().join("main.roc"),
+        Compression::Uncompressed,
+    )
+    .unwrap();
+    let content_hash = tarball_name.strip_suffix(".tar").unwrap();
+    let url = format!("https://example.com/roc-packages/{tarball_name}");
+    let vendored_path =
+        cache::mirror_tarball_path(vendor_dir.path(), "example.com/roc-packages", content_hash);
+
+    // Since the tarball is already in the vendor dir, this must not touch the network.
+    fs::create_dir_all(vendored_path.parent().unwrap()).unwrap();
+    fs::copy(package_dir.path().join(&tarball_name), &vendored_path).unwrap();
+
+    let app_path = app_dir.path().join("main.roc");
+    fs::write(
+        &app_path,
+        format!(
+            "app \"test\"\n    packages {{ json: \"{url}\" }}\n    imports []\n    provides [main] to json\n"
+        ),
+    )
+    .unwrap();
+
+    let vendored = vendor(
+        &app_path,
+        vendor_dir.path(),
+        RocCacheDir::Persistent(cache_dir.path()),
+    )
+    .unwrap();
+
+    assert_eq!(vendored.len(), 1);
+    assert_eq!(vendored[0].url, url);
+    assert_eq!(vendored[0].tarball_path, vendored_path);
+    assert!(cache_dir
+        .path()
+        .join("example.com/roc-packages")
+        .join(content_hash)
+        .join("Json.roc")
+        .is_file());
+}
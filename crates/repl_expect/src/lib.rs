@@ -97,13 +97,22 @@ mod test {
     use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError, Threading};
     use roc_packaging::cache::RocCacheDir;
     use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+    use std::num::NonZeroU64;
     use target_lexicon::Triple;
 
-    use crate::run::expect_mono_module_to_dylib;
+    use crate::run::{expect_mono_module_to_dylib, PropertyConfig};
 
     use super::*;
 
     fn run_expect_test(source: &str, expected: &str) {
+        run_expect_test_with_property_config(source, PropertyConfig::default(), expected)
+    }
+
+    fn run_expect_test_with_property_config(
+        source: &str,
+        property_config: PropertyConfig,
+        expected: &str,
+    ) {
         let arena = bumpalo::Bump::new();
         let arena = &arena;
 
@@ -156,6 +165,8 @@ mod test {
         )
         .unwrap();
 
+        property_config.set_in(&lib);
+
         let arena = &bumpalo::Bump::new();
         let interns = arena.alloc(interns);
 
@@ -170,7 +181,7 @@ mod test {
         unsafe { set_shared_buffer((shared_buffer.as_mut_ptr(), BUFFER_SIZE), &mut result) };
 
         let mut writer = Vec::with_capacity(1024);
-        let (_failed, _passed) = crate::run::run_expects_with_memory(
+        let _results = crate::run::run_expects_with_memory(
             &mut writer,
             RenderTarget::ColorTerminal,
             arena,
@@ -1212,4 +1223,88 @@ mod test {
             ),
         );
     }
+
+    #[test]
+    fn arbitrary_counterexample() {
+        run_expect_test(
+            indoc!(
+                r#"
+                interface Test exposes [] imports []
+
+                expect
+                    result : Result {} U8
+                    result =
+                        when Arbitrary.check \n -> n < 10 is
+                            Passed -> Ok {}
+                            Falsified { counterexample } -> Err counterexample
+
+                    result == Ok {}
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation failed:
+
+                 3│>  expect
+                 4│>      result : Result {} U8
+                 5│>      result =
+                 6│>          when Arbitrary.check \n -> n < 10 is
+                 7│>              Passed -> Ok {}
+                 8│>              Falsified { counterexample } -> Err counterexample
+                 9│>
+                10│>      result == Ok {}
+
+                When it failed, these variables had these values:
+
+                result : Result {} U8
+                result = Err 10
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn arbitrary_property_config() {
+        // Only 123 falsifies this. The default seed first generates it in run 644, and seed 2 in
+        // run 192, so with the default 100 runs it would pass.
+        run_expect_test_with_property_config(
+            indoc!(
+                r#"
+                interface Test exposes [] imports []
+
+                expect
+                    result : Result {} U64
+                    result =
+                        when Arbitrary.check \n -> n != 123u8 is
+                            Passed -> Ok {}
+                            Falsified { run } -> Err run
+
+                    result == Ok {}
+                "#
+            ),
+            PropertyConfig {
+                runs: NonZeroU64::new(1000),
+                seed: NonZeroU64::new(2),
+            },
+            indoc!(
+                r#"
+                This expectation failed:
+
+                 3│>  expect
+                 4│>      result : Result {} U64
+                 5│>      result =
+                 6│>          when Arbitrary.check \n -> n != 123u8 is
+                 7│>              Passed -> Ok {}
+                 8│>              Falsified { run } -> Err run
+                 9│>
+                10│>      result == Ok {}
+
+                When it failed, these variables had these values:
+
+                result : Result {} U64
+                result = Err 192
+                "#
+            ),
+        );
+    }
 }
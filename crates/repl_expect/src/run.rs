@@ -1,9 +1,11 @@
 use std::{
+    num::NonZeroU64,
     os::unix::process::parent_id,
     sync::{
         atomic::{AtomicBool, AtomicU32},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 use bumpalo::collections::Vec as BumpVec;
@@ -14,7 +16,10 @@ use roc_can::expr::ExpectLookup;
 use roc_collections::{MutSet, VecMap};
 use roc_error_macros::internal_error;
 use roc_gen_llvm::{
-    llvm::{build::LlvmBackendMode, externs::add_default_roc_externs},
+    llvm::{
+        build::{LlvmBackendMode, PROPERTY_RUNS_GLOBAL, PROPERTY_SEED_GLOBAL},
+        externs::add_default_roc_externs,
+    },
     run_roc::RocCallResult,
     run_roc_dylib,
 };
@@ -139,7 +144,7 @@ pub fn run_inline_expects<'a, W: std::io::Write>(
     let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
     let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
 
-    run_expects_with_memory(
+    let results = run_expects_with_memory(
         writer,
         render_target,
         arena,
@@ -149,11 +154,15 @@ pub fn run_inline_expects<'a, W: std::io::Write>(
         expectations,
         expects,
         &mut memory,
-    )
+    )?;
+
+    let passed = results.iter().filter(|result| result.passed).count();
+
+    Ok((results.len() - passed, passed))
 }
 
 #[allow(clippy::too_many_arguments)]
-pub fn run_toplevel_expects<'a, W: std::io::Write>(
+pub fn run_toplevel_expects<'a, 'b, W: std::io::Write>(
     writer: &mut W,
     render_target: RenderTarget,
     arena: &'a Bump,
@@ -161,8 +170,8 @@ pub fn run_toplevel_expects<'a, W: std::io::Write>(
     layout_interner: &GlobalLayoutInterner<'a>,
     lib: &libloading::Library,
     expectations: &mut VecMap<ModuleId, Expectations>,
-    expects: ExpectFunctions<'_>,
-) -> std::io::Result<(usize, usize)> {
+    expects: ExpectFunctions<'b>,
+) -> std::io::Result<Vec<ToplevelExpectResult<'b>>> {
     let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
     let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
 
@@ -180,7 +189,7 @@ pub fn run_toplevel_expects<'a, W: std::io::Write>(
 }
 
 #[allow(clippy::too_many_arguments)]
-pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
+pub(crate) fn run_expects_with_memory<'a, 'b, W: std::io::Write>(
     writer: &mut W,
     render_target: RenderTarget,
     arena: &'a Bump,
@@ -188,15 +197,16 @@ pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
     layout_interner: &GlobalLayoutInterner<'a>,
     lib: &libloading::Library,
     expectations: &mut VecMap<ModuleId, Expectations>,
-    expects: ExpectFunctions<'_>,
+    expects: ExpectFunctions<'b>,
     memory: &mut ExpectMemory,
-) -> std::io::Result<(usize, usize)> {
-    let mut failed = 0;
-    let mut passed = 0;
+) -> std::io::Result<Vec<ToplevelExpectResult<'b>>> {
+    let mut results = Vec::with_capacity(expects.fx.len() + expects.pure.len());
 
     for expect in expects.fx {
-        let result = run_expect_fx(
-            writer,
+        let start = Instant::now();
+        let mut rendered = Vec::new();
+        let passed = run_expect_fx(
+            &mut rendered,
             render_target,
             arena,
             interns,
@@ -207,17 +217,18 @@ pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
             expect,
         )?;
 
-        match result {
-            true => passed += 1,
-            false => failed += 1,
-        }
+        results.push(ToplevelExpectResult::new(
+            writer, expect, passed, start, rendered,
+        )?);
     }
 
     memory.set_shared_buffer(lib);
 
     for expect in expects.pure {
-        let result = run_expect_pure(
-            writer,
+        let start = Instant::now();
+        let mut rendered = Vec::new();
+        let passed = run_expect_pure(
+            &mut rendered,
             render_target,
             arena,
             interns,
@@ -228,13 +239,12 @@ pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
             expect,
         )?;
 
-        match result {
-            true => passed += 1,
-            false => failed += 1,
-        }
+        results.push(ToplevelExpectResult::new(
+            writer, expect, passed, start, rendered,
+        )?);
     }
 
-    Ok((failed, passed))
+    Ok(results)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -692,12 +702,68 @@ pub struct ToplevelExpect<'a> {
     pub region: Region,
 }
 
+/// The outcome of running a single top-level `expect`
+#[derive(Debug, Clone)]
+pub struct ToplevelExpectResult<'a> {
+    pub expect: ToplevelExpect<'a>,
+    pub passed: bool,
+    pub duration: Duration,
+    /// What was written to the writer about this `expect`, e.g. the values of the variables
+    /// when it failed. Rendered for the requested `RenderTarget`.
+    pub rendered: String,
+}
+
+impl<'a> ToplevelExpectResult<'a> {
+    fn new(
+        writer: &mut impl std::io::Write,
+        expect: ToplevelExpect<'a>,
+        passed: bool,
+        start: Instant,
+        rendered: Vec<u8>,
+    ) -> std::io::Result<Self> {
+        let duration = start.elapsed();
+
+        writer.write_all(&rendered)?;
+
+        Ok(Self {
+            expect,
+            passed,
+            duration,
+            rendered: String::from_utf8_lossy(&rendered).into_owned(),
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct ExpectFunctions<'a> {
     pub pure: BumpVec<'a, ToplevelExpect<'a>>,
     pub fx: BumpVec<'a, ToplevelExpect<'a>>,
 }
 
+/// The settings `roc test --property-runs` and `--property-seed` give to every `Arbitrary.check`.
+/// Those that are `None` come from `Arbitrary.defaultConfig`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PropertyConfig {
+    pub runs: Option<NonZeroU64>,
+    pub seed: Option<NonZeroU64>,
+}
+
+impl PropertyConfig {
+    /// Writes these settings into the dylib, where `Arbitrary.check` reads them. Do this before
+    /// running any expects; the ones with effects run in a forked process that gets a copy.
+    pub fn set_in(&self, lib: &libloading::Library) {
+        set_property_setting(lib, PROPERTY_RUNS_GLOBAL, self.runs);
+        set_property_setting(lib, PROPERTY_SEED_GLOBAL, self.seed);
+    }
+}
+
+fn set_property_setting(lib: &libloading::Library, global_name: &str, value: Option<NonZeroU64>) {
+    // The global only exists if some expect ends up calling `Arbitrary.check`.
+    if let Ok(global) = unsafe { lib.get::<*mut u64>(global_name.as_bytes()) } {
+        unsafe { **global = value.map_or(0, NonZeroU64::get) };
+    }
+}
+
 pub fn expect_mono_module_to_dylib<'a>(
     arena: &'a Bump,
     target: Triple,
@@ -4938,7 +4938,35 @@ fn exhaustive_problem<'a>(
             overall_region,
             branch_region,
             index,
+            covered_by,
         } => {
+            let explanation = match covered_by.as_slice() {
+                [] => alloc.reflow(
+                    "Any value of this shape will be handled by \
+                a previous pattern, so this one should be removed.",
+                ),
+                [single] => alloc.concat([
+                    alloc.reflow("Any value of this shape will be handled by the "),
+                    alloc.string(single.ordinal()),
+                    alloc.reflow(" pattern, so this one should be removed."),
+                ]),
+                [init @ .., last] => {
+                    let mut parts =
+                        vec![alloc.reflow("Any value of this shape will be handled by the ")];
+                    for (i, covering) in init.iter().enumerate() {
+                        if i > 0 {
+                            parts.push(alloc.text(", "));
+                        }
+                        parts.push(alloc.string(covering.ordinal()));
+                    }
+                    parts.push(alloc.text(" and "));
+                    parts.push(alloc.string(last.ordinal()));
+                    parts.push(alloc.reflow(" patterns together, so this one should be removed."));
+
+                    alloc.concat(parts)
+                }
+            };
+
             let doc = alloc.stack([
                 alloc.concat([
                     alloc.reflow("The "),
@@ -4949,10 +4977,7 @@ fn exhaustive_problem<'a>(
                     lines.convert_region(overall_region),
                     lines.convert_region(branch_region),
                 ),
-                alloc.reflow(
-                    "Any value of this shape will be handled by \
-                a previous pattern, so this one should be removed.",
-                ),
+                explanation,
             ]);
 
             Report {
@@ -100,10 +100,12 @@ pub fn pretty_header_with_path(title: &str, path: &Path) -> String {
     header
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RenderTarget {
     ColorTerminal,
     Generic,
+    /// One JSON object per report, for consumption by other tools (e.g. CI annotations).
+    Json,
 }
 
 /// A textual report.
@@ -118,18 +120,19 @@ impl<'b> Report<'b> {
     pub fn render(
         self,
         target: RenderTarget,
-        buf: &'b mut String,
+        buf: &mut String,
         alloc: &'b RocDocAllocator<'b>,
         palette: &'b Palette,
     ) {
         match target {
             RenderTarget::Generic => self.render_ci(buf, alloc),
             RenderTarget::ColorTerminal => self.render_color_terminal(buf, alloc, palette),
+            RenderTarget::Json => self.render_json(buf, alloc),
         }
     }
 
     /// Render to CI console output, where no colors are available.
-    pub fn render_ci(self, buf: &'b mut String, alloc: &'b RocDocAllocator<'b>) {
+    pub fn render_ci(self, buf: &mut String, alloc: &'b RocDocAllocator<'b>) {
         let err_msg = "<buffer is not a utf-8 encoded string>";
 
         self.pretty(alloc)
@@ -154,6 +157,35 @@ impl<'b> Report<'b> {
             .expect(err_msg);
     }
 
+    /// Render as a single line of JSON with the title, severity, file path, and the
+    /// message as it would be rendered by [Report::render_ci]. The region is that of
+    /// the first code snippet in the report (1-based, end-exclusive), or `null` if it
+    /// has no code snippets.
+    pub fn render_json(self, buf: &mut String, _alloc: &'b RocDocAllocator<'b>) {
+        let err_msg = "<buffer is not a utf-8 encoded string>";
+
+        let Report {
+            title,
+            filename,
+            doc,
+            severity,
+        } = self;
+
+        let mut message = String::new();
+        let mut writer = CiWrite::new(&mut message);
+
+        doc.1.render_raw(70, &mut writer).expect(err_msg);
+
+        let region = writer.source_region().map(|region| {
+            serde_json::json!({
+                "start": { "line": region.start.line + 1, "column": region.start.column + 1 },
+                "end": { "line": region.end.line + 1, "column": region.end.column + 1 },
+            })
+        });
+
+        buf.push_str(&json_line(&title, severity, &filename, region, &message));
+    }
+
     pub fn pretty(self, alloc: &'b RocDocAllocator<'b>) -> RocDocBuilder<'b> {
         if self.title.is_empty() {
             self.doc
@@ -173,6 +205,41 @@ impl<'b> Report<'b> {
     }
 }
 
+/// Renders a problem that never became a [Report], like an IO error while loading, as a
+/// single line of JSON with the same fields as [Report::render_json]. Its region is `null`.
+pub fn render_json_without_report(
+    title: &str,
+    severity: Severity,
+    filename: &Path,
+    message: &str,
+) -> String {
+    json_line(title, severity, filename, None, message)
+}
+
+fn json_line(
+    title: &str,
+    severity: Severity,
+    filename: &Path,
+    region: Option<serde_json::Value>,
+    message: &str,
+) -> String {
+    let severity = match severity {
+        Severity::Fatal => "fatal",
+        Severity::RuntimeError => "error",
+        Severity::Warning => "warning",
+    };
+
+    let json = serde_json::json!({
+        "title": title,
+        "severity": severity,
+        "path": filename.to_string_lossy(),
+        "region": region,
+        "message": message.trim_end(),
+    });
+
+    json.to_string()
+}
+
 /// This struct is a combination of several things
 /// 1. A set of StyleCodes suitable for the environment we're running in (web or terminal)
 /// 2. A set of colors we decided to use
@@ -637,7 +704,9 @@ impl<'a> RocDocAllocator<'a> {
             result = result.append(highlight_line);
         }
 
-        result.annotate(Annotation::CodeBlock)
+        result
+            .annotate(Annotation::CodeBlock)
+            .annotate(Annotation::SourceRegion(sub_region1))
     }
 
     pub fn region_with_subregion(
@@ -736,7 +805,7 @@ impl<'a> RocDocAllocator<'a> {
             result = result.append(highlight_line);
         }
 
-        result
+        result.annotate(Annotation::SourceRegion(sub_region))
     }
 
     pub fn region(&'a self, region: LineColumnRegion) -> DocBuilder<'a, Self, Annotation> {
@@ -845,6 +914,9 @@ pub enum Annotation {
     Tip,
     Header,
     ParserSuggestion,
+    /// Wraps a code snippet, recording the region of the source it points at.
+    /// Renders as nothing; it's only there so that [RenderTarget::Json] can report a location.
+    SourceRegion(LineColumnRegion),
 }
 
 /// Render with minimal formatting
@@ -852,6 +924,7 @@ pub struct CiWrite<W> {
     style_stack: Vec<Annotation>,
     in_type_block: bool,
     in_code_block: bool,
+    source_region: Option<LineColumnRegion>,
     upstream: W,
 }
 
@@ -861,9 +934,15 @@ impl<W> CiWrite<W> {
             style_stack: vec![],
             in_type_block: false,
             in_code_block: false,
+            source_region: None,
             upstream,
         }
     }
+
+    /// The region of the first code snippet rendered so far, if any.
+    pub fn source_region(&self) -> Option<LineColumnRegion> {
+        self.source_region
+    }
 }
 
 /// Render with fancy formatting
@@ -916,6 +995,9 @@ where
             CodeBlock => {
                 self.in_code_block = true;
             }
+            SourceRegion(region) => {
+                self.source_region.get_or_insert(*region);
+            }
             Emphasized => {
                 self.write_str("*")?;
             }
@@ -1053,6 +1135,7 @@ where
                 self.write_str(self.palette.parser_suggestion)?;
             }
             TypeBlock | InlineTypeBlock | Tag | RecordField | TupleElem => { /* nothing yet */ }
+            SourceRegion(_) => { /* only used for locating the problem */ }
         }
         self.style_stack.push(*annotation);
         Ok(())
@@ -1072,13 +1155,18 @@ where
 
                 TypeBlock | InlineTypeBlock | Tag | Opaque | RecordField | TupleElem => { /* nothing yet */
                 }
+                SourceRegion(_) => { /* only used for locating the problem */ }
             },
         }
         Ok(())
     }
 }
 
-pub fn to_file_problem_report_string(filename: &Path, error: io::ErrorKind) -> String {
+pub fn to_file_problem_report_string(
+    filename: &Path,
+    error: io::ErrorKind,
+    render: RenderTarget,
+) -> String {
     let src_lines: Vec<&str> = Vec::new();
 
     let mut module_ids = ModuleIds::default();
@@ -1093,7 +1181,7 @@ pub fn to_file_problem_report_string(filename: &Path, error: io::ErrorKind) -> S
     let mut buf = String::new();
     let palette = DEFAULT_PALETTE;
     let report = to_file_problem_report(&alloc, filename, error);
-    report.render_color_terminal(&mut buf, &alloc, &palette);
+    report.render(render, &mut buf, &alloc, &palette);
 
     buf
 }
@@ -22,8 +22,8 @@ mod test_reporting {
     use roc_problem::Severity;
     use roc_region::all::LineInfo;
     use roc_reporting::report::{
-        can_problem, parse_problem, type_problem, RenderTarget, Report, ANSI_STYLE_CODES,
-        DEFAULT_PALETTE,
+        can_problem, parse_problem, render_json_without_report, type_problem, RenderTarget, Report,
+        ANSI_STYLE_CODES, DEFAULT_PALETTE,
     };
     use roc_reporting::report::{RocDocAllocator, RocDocBuilder};
     use roc_solve_problem::TypeError;
@@ -656,9 +656,9 @@ mod test_reporting {
 
     Did you mean one of these?
 
-        Str
         Frac
         Num
+        Str
         Err
     "###
     );
@@ -820,6 +820,68 @@ mod test_reporting {
         );
     }
 
+    #[test]
+    fn report_region_in_json() {
+        let src = indoc!(
+            r#"
+                isDisabled = \user -> user.isAdmin
+
+                theAdmin
+                    |> isDisabled
+            "#
+        );
+
+        let arena = Bump::new();
+        let src_lines: Vec<&str> = src.split('\n').collect();
+        let lines = LineInfo::new(src);
+        let filename = filename_from_string(r"/code/proj/Main.roc");
+
+        let (_, can_problems, home, interns) = infer_expr_help(&arena, src).unwrap_or_else(|_| {
+            panic!("expected the source to parse");
+        });
+
+        let alloc = RocDocAllocator::new(&src_lines, home, &interns);
+        let problem = can_problems.into_iter().next().expect("a naming problem");
+        let report = can_problem(&alloc, &lines, filename, problem);
+
+        let mut buf = String::new();
+        report.render_json(&mut buf, &alloc);
+
+        assert!(!buf.contains('\n'));
+
+        let json: serde_json::Value = serde_json::from_str(&buf).unwrap();
+
+        assert_eq!(json["title"], "UNRECOGNIZED NAME");
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["path"], "/code/proj/Main.roc");
+        assert_eq!(json["region"]["start"]["line"], 3);
+        assert_eq!(json["region"]["end"]["line"], 3);
+        assert!(json["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Nothing is named `theAdmin` in this scope."));
+    }
+
+    #[test]
+    fn json_without_report() {
+        let buf = render_json_without_report(
+            "LOADING PROBLEM",
+            Severity::Fatal,
+            &filename_from_string(r"/code/proj/Main.roc"),
+            "Failed with error: FileProblem\n",
+        );
+
+        assert!(!buf.contains('\n'));
+
+        let json: serde_json::Value = serde_json::from_str(&buf).unwrap();
+
+        assert_eq!(json["title"], "LOADING PROBLEM");
+        assert_eq!(json["severity"], "fatal");
+        assert_eq!(json["path"], "/code/proj/Main.roc");
+        assert_eq!(json["region"], serde_json::Value::Null);
+        assert_eq!(json["message"], "Failed with error: FileProblem");
+    }
+
     test_report!(
         if_condition_not_bool,
         indoc!(
@@ -2815,7 +2877,7 @@ mod test_reporting {
 
     Other possibilities include:
 
-        { a: Just _ }
+        { a: Just _, b }
 
     I would have to crash if I saw one of those! Add branches for them!
     "###
@@ -2867,7 +2929,7 @@ mod test_reporting {
     6│>          2 -> 4
     7│           _ -> 5
 
-    Any value of this shape will be handled by a previous pattern, so this
+    Any value of this shape will be handled by the 1st pattern, so this
     one should be removed.
     "###
     );
@@ -4146,7 +4208,7 @@ mod test_reporting {
     7│          _ -> 3
                 ^
 
-    Any value of this shape will be handled by a previous pattern, so this
+    Any value of this shape will be handled by the 2nd pattern, so this
     one should be removed.
     "###
     );
@@ -8162,8 +8224,8 @@ In roc, functions are always written as a lambda, like{}
 
         Type
         Unsigned8
-        Unsigned32
         Unsigned16
+        Unsigned64
 
     ── UNRECOGNIZED NAME ───────────────────────────────────── /code/proj/Main.roc ─
 
@@ -8176,8 +8238,8 @@ In roc, functions are always written as a lambda, like{}
 
         Type
         Unsigned8
-        Unsigned32
         Unsigned16
+        Unsigned64
     "###
     );
 
@@ -9678,7 +9740,8 @@ In roc, functions are always written as a lambda, like{}
 
     Only builtin abilities can be derived.
 
-    Note: The builtin abilities are `Encoding`, `Decoding`, `Hash`, `Eq`
+    Note: The builtin abilities are `Encoding`, `Decoding`, `Hash`, `Eq`,
+    `Arbitrary`, `Inspect`, `Sort`
     "###
     );
 
@@ -12324,6 +12387,7 @@ I recommend using camelCase. It's the standard style in Roc code!
         []
         [_]
         [_, A, ..]
+        [B, B, ..]
 
     I would have to crash if I saw one of those! Add branches for them!
     "###
@@ -12393,6 +12457,7 @@ I recommend using camelCase. It's the standard style in Roc code!
 
         []
         [_]
+        [.., A, A]
         [.., _, B]
 
     I would have to crash if I saw one of those! Add branches for them!
@@ -12443,7 +12508,8 @@ I recommend using camelCase. It's the standard style in Roc code!
 
     Other possibilities include:
 
-        [_, .., _]
+        [A, .., A]
+        [B, .., B]
 
     I would have to crash if I saw one of those! Add branches for them!
     "###
@@ -12587,7 +12653,7 @@ I recommend using camelCase. It's the standard style in Roc code!
      9│>          [_] -> ""
     10│           [..] -> ""
 
-    Any value of this shape will be handled by a previous pattern, so this
+    Any value of this shape will be handled by the 2nd pattern, so this
     one should be removed.
     "###
     );
@@ -12615,8 +12681,8 @@ I recommend using camelCase. It's the standard style in Roc code!
     9│          [..] -> ""
                 ^^^^
 
-    Any value of this shape will be handled by a previous pattern, so this
-    one should be removed.
+    Any value of this shape will be handled by the 1st and 2nd patterns
+    together, so this one should be removed.
     "###
     );
 
@@ -12643,7 +12709,7 @@ I recommend using camelCase. It's the standard style in Roc code!
     9│          [.., _] -> ""
                 ^^^^^^^
 
-    Any value of this shape will be handled by a previous pattern, so this
+    Any value of this shape will be handled by the 2nd pattern, so this
     one should be removed.
     "###
     );
@@ -12670,7 +12736,7 @@ I recommend using camelCase. It's the standard style in Roc code!
     8│>          [_, .., {}] -> ""
     9│           [..] -> ""
 
-    Any value of this shape will be handled by a previous pattern, so this
+    Any value of this shape will be handled by the 1st pattern, so this
     one should be removed.
     "###
     );
@@ -12689,6 +12755,158 @@ I recommend using camelCase. It's the standard style in Roc code!
         )
     );
 
+    test_report!(
+        list_match_nested_lists_reports_concrete_patterns,
+        indoc!(
+            r#"
+            l : List (List [A, B])
+
+            when l is
+                [] -> ""
+                [[], ..] -> ""
+                [[A, ..], ..] -> ""
+            "#
+        ),
+    @r###"
+    ── UNSAFE PATTERN ──────────────────────────────────────── /code/proj/Main.roc ─
+
+    This `when` does not cover all the possibilities:
+
+    6│>      when l is
+    7│>          [] -> ""
+    8│>          [[], ..] -> ""
+    9│>          [[A, ..], ..] -> ""
+
+    Other possibilities include:
+
+        [[B, ..], ..]
+
+    I would have to crash if I saw one of those! Add branches for them!
+    "###
+    );
+
+    test_report!(
+        list_match_first_wildcard_reports_concrete_patterns,
+        indoc!(
+            r#"
+            l : List [A, B]
+
+            when l is
+                [] -> ""
+                [_] -> ""
+                [_, A, ..] -> ""
+                [A, B, ..] -> ""
+            "#
+        ),
+    @r###"
+    ── UNSAFE PATTERN ──────────────────────────────────────── /code/proj/Main.roc ─
+
+    This `when` does not cover all the possibilities:
+
+     6│>      when l is
+     7│>          [] -> ""
+     8│>          [_] -> ""
+     9│>          [_, A, ..] -> ""
+    10│>          [A, B, ..] -> ""
+
+    Other possibilities include:
+
+        [B, B, ..]
+
+    I would have to crash if I saw one of those! Add branches for them!
+    "###
+    );
+
+    test_report!(
+        nested_record_match_reports_concrete_patterns,
+        indoc!(
+            r#"
+            r : { a : { b : [X, Y], c : [P, Q] }, d : [M, N] }
+
+            when r is
+                { a: { b: X }, d: _ } -> ""
+                { a: { c: P }, d: M } -> ""
+            "#
+        ),
+    @r###"
+    ── UNSAFE PATTERN ──────────────────────────────────────── /code/proj/Main.roc ─
+
+    This `when` does not cover all the possibilities:
+
+    6│>      when r is
+    7│>          { a: { b: X }, d: _ } -> ""
+    8│>          { a: { c: P }, d: M } -> ""
+
+    Other possibilities include:
+
+        { a: { b: Y, c: Q }, d: M }
+        { a: { b: Y, c }, d: N }
+
+    I would have to crash if I saw one of those! Add branches for them!
+    "###
+    );
+
+    test_report!(
+        redundant_pattern_covered_by_several_patterns,
+        indoc!(
+            r#"
+            app "test" provides [f] to "./p"
+
+            f : [A, B, C] -> Str
+            f = \x ->
+                when x is
+                    A -> ""
+                    B -> ""
+                    C -> ""
+                    _ -> ""
+            "#
+        ),
+    @r###"
+    ── REDUNDANT PATTERN ───────────────────────────────────── /code/proj/Main.roc ─
+
+    The 4th pattern is redundant:
+
+    5│      when x is
+    6│          A -> ""
+    7│          B -> ""
+    8│          C -> ""
+    9│          _ -> ""
+                ^
+
+    Any value of this shape will be handled by the 1st, 2nd and 3rd
+    patterns together, so this one should be removed.
+    "###
+    );
+
+    test_report!(
+        redundant_pattern_covered_by_one_pattern,
+        indoc!(
+            r#"
+            x : { a : [A, B], b : [A, B] }
+
+            when x is
+                { a: A, b: _ } -> ""
+                { a: B, b: A } -> ""
+                { a: A, b: B } -> ""
+                _ -> ""
+            "#
+        ),
+    @r###"
+    ── REDUNDANT PATTERN ───────────────────────────────────── /code/proj/Main.roc ─
+
+    The 3rd pattern is redundant:
+
+     6│       when x is
+     7│           { a: A, b: _ } -> ""
+     8│           { a: B, b: A } -> ""
+     9│>          { a: A, b: B } -> ""
+    10│           _ -> ""
+
+    Any value of this shape will be handled by the 1st pattern, so this
+    one should be removed.
+    "###
+    );
+
     test_report!(
         suggest_binding_rigid_var_to_ability,
         indoc!(
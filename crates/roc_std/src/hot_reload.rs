@@ -0,0 +1,180 @@
+//! Lets a long-running host swap in new versions of the app's `roc__*` entry
+//! points, without restarting.
+//!
+//! `roc dev --hot-reload` runs the app as usual, and whenever the app's code
+//! changes, it rebuilds the app as a shared library at the path given to the
+//! host in the [LIBRARY_VAR] environment variable. The host can call
+//! [HotReload::reload_if_changed] wherever it is safe to switch (between
+//! frames, between requests, ...), and then call the entry points it gets from
+//! [HotReload::entry_point] instead of the ones linked into it.
+//!
+//! Some things to keep in mind:
+//!
+//! - The library does not contain `roc_alloc`, `roc_panic` and friends, so the
+//!   host has to export those dynamically (e.g. by linking with `-rdynamic`).
+//! - Only the app's code is swapped. If the types the platform exposes to the
+//!   app change, the host needs to be restarted.
+//! - Older versions of the app are never unloaded, so values and closures they
+//!   created stay valid after a reload.
+use core::ffi::c_void;
+use core::fmt;
+use core::ptr::NonNull;
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The environment variable `roc dev --hot-reload` uses to tell the host where
+/// the app library will be.
+pub const LIBRARY_VAR: &str = "ROC_HOT_RELOAD_LIBRARY";
+
+#[derive(Debug)]
+pub enum HotReloadError {
+    Io(io::Error),
+    /// `dlopen` could not load the library, e.g. because the host does not
+    /// export a symbol the app needs.
+    Load(String),
+}
+
+impl fmt::Display for HotReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotReloadError::Io(err) => write!(f, "could not copy the app library: {}", err),
+            HotReloadError::Load(msg) => write!(f, "could not load the app library: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HotReloadError {}
+
+impl From<io::Error> for HotReloadError {
+    fn from(err: io::Error) -> Self {
+        HotReloadError::Io(err)
+    }
+}
+
+pub struct HotReload {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    library: Option<NonNull<c_void>>,
+    generation: usize,
+}
+
+// `dlopen` handles may be used from any thread.
+unsafe impl Send for HotReload {}
+unsafe impl Sync for HotReload {}
+
+impl HotReload {
+    /// Returns `None` unless the host was started by `roc dev --hot-reload`.
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os(LIBRARY_VAR).map(Self::new)
+    }
+
+    /// Watches the app library at this path. Nothing is loaded until
+    /// [HotReload::reload_if_changed] finds it.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+            library: None,
+            generation: 0,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How many versions of the app have been loaded; 0 until the first reload.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Loads the app library if it was (re)written since the last call.
+    /// Returns whether a new version was loaded, in which case entry points
+    /// should be looked up again.
+    pub fn reload_if_changed(&mut self) -> Result<bool, HotReloadError> {
+        let modified = match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => Some(modified),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+
+        if modified == self.modified {
+            return Ok(false);
+        }
+
+        // Don't try the same library again if it fails to load.
+        self.modified = modified;
+
+        // `dlopen` returns the already loaded library when given the same path
+        // again, so each version gets loaded from its own copy.
+        let mut copy = std::env::temp_dir().join(format!(
+            "roc_hot_reload_{}_{}",
+            std::process::id(),
+            self.generation + 1
+        ));
+
+        if let Some(extension) = self.path.extension() {
+            copy.set_extension(extension);
+        }
+
+        fs::copy(&self.path, &copy)?;
+
+        let c_path = CString::new(copy.as_os_str().as_bytes())
+            .map_err(|_| HotReloadError::Load("the path contains a nul byte".to_string()))?;
+        let handle = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+
+        // Once loaded, the library stays mapped even without its file.
+        let _ = fs::remove_file(&copy);
+
+        match NonNull::new(handle) {
+            Some(handle) => {
+                self.library = Some(handle);
+                self.generation += 1;
+
+                Ok(true)
+            }
+            None => Err(HotReloadError::Load(last_dl_error())),
+        }
+    }
+
+    /// Looks up an entry point, like `"roc__mainForHost_1_exposed_generic"`,
+    /// in the latest version of the app. Returns `None` before the first
+    /// reload, or if that version has no such entry point.
+    ///
+    /// # Safety
+    ///
+    /// `F` must be a function pointer type matching the entry point's signature.
+    pub unsafe fn entry_point<F: Copy>(&self, name: &str) -> Option<F> {
+        assert_eq!(
+            core::mem::size_of::<F>(),
+            core::mem::size_of::<*mut c_void>(),
+            "entry points must be looked up as function pointers"
+        );
+
+        let handle = self.library?;
+        let name = CString::new(name).ok()?;
+        let symbol = libc::dlsym(handle.as_ptr(), name.as_ptr());
+
+        if symbol.is_null() {
+            None
+        } else {
+            Some(core::mem::transmute_copy(&symbol))
+        }
+    }
+}
+
+fn last_dl_error() -> String {
+    let msg = unsafe { libc::dlerror() };
+
+    if msg.is_null() {
+        "unknown error".to_string()
+    } else {
+        unsafe { CStr::from_ptr(msg) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
@@ -18,6 +18,9 @@ mod roc_set;
 mod roc_str;
 mod storage;
 
+#[cfg(all(unix, feature = "hot-reload"))]
+pub mod hot_reload;
+
 pub use roc_box::RocBox;
 pub use roc_dict::RocDict;
 pub use roc_list::{RocList, SendSafeRocList};
@@ -0,0 +1,93 @@
+#![cfg(unix)]
+
+use roc_std::hot_reload::HotReload;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+const ENTRY_POINT: &str = "roc__mainForHost_1_exposed";
+
+type MainForHost = extern "C" fn() -> i64;
+
+/// Builds a tiny library whose entry point returns `answer`, and moves it to `library` the way
+/// `roc dev --hot-reload` does, so `library` is never half-written.
+fn build_library(dir: &Path, library: &Path, answer: i64) {
+    let source = dir.join(format!("app_{}.rs", answer));
+    let output = dir.join(format!("app_{}.so", answer));
+
+    fs::write(
+        &source,
+        format!(
+            "#[no_mangle]\npub extern \"C\" fn {}() -> i64 {{ {} }}\n",
+            ENTRY_POINT, answer
+        ),
+    )
+    .unwrap();
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let status = Command::new(rustc)
+        .args(["--crate-type", "cdylib", "--edition", "2021", "-o"])
+        .arg(&output)
+        .arg(&source)
+        .status()
+        .unwrap();
+
+    assert!(status.success(), "could not build {}", source.display());
+
+    // A rebuild can land within the file system's timestamp resolution, and then it would look
+    // like nothing changed.
+    let previous = fs::metadata(library).and_then(|metadata| metadata.modified());
+
+    while previous.as_ref().ok() == fs::metadata(&output).unwrap().modified().ok().as_ref() {
+        std::thread::sleep(Duration::from_millis(10));
+        fs::write(&output, fs::read(&output).unwrap()).unwrap();
+    }
+
+    fs::rename(&output, library).unwrap();
+}
+
+fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("roc_std_hot_reload_{}", std::process::id()));
+
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+#[test]
+fn reloads_new_versions() {
+    let dir = temp_dir();
+    let library = dir.join("app.so");
+    let mut hot_reload = HotReload::new(&library);
+
+    // Nothing to load until the first build is done.
+    assert!(!hot_reload.reload_if_changed().unwrap());
+    assert_eq!(hot_reload.generation(), 0);
+    assert!(unsafe { hot_reload.entry_point::<MainForHost>(ENTRY_POINT) }.is_none());
+
+    build_library(&dir, &library, 1);
+
+    assert!(hot_reload.reload_if_changed().unwrap());
+    assert!(!hot_reload.reload_if_changed().unwrap());
+    assert_eq!(hot_reload.generation(), 1);
+
+    let first = unsafe { hot_reload.entry_point::<MainForHost>(ENTRY_POINT) }.unwrap();
+    assert_eq!(first(), 1);
+
+    build_library(&dir, &library, 2);
+
+    assert!(hot_reload.reload_if_changed().unwrap());
+    assert_eq!(hot_reload.generation(), 2);
+
+    let second = unsafe { hot_reload.entry_point::<MainForHost>(ENTRY_POINT) }.unwrap();
+    assert_eq!(second(), 2);
+
+    // Older versions stay loaded, so entry points looked up before the reload still work.
+    assert_eq!(first(), 1);
+
+    assert!(unsafe { hot_reload.entry_point::<MainForHost>("roc__missing") }.is_none());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -475,3 +475,40 @@ mod with_terminator {
         // verify_temp_c(&string_for_len(65), 64);
     }
 }
+
+#[cfg(all(test, unix, feature = "hot-reload"))]
+mod hot_reload {
+    use roc_std::hot_reload::{HotReload, HotReloadError};
+
+    fn library_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("roc_std_{}_{}.so", name, std::process::id()))
+    }
+
+    #[test]
+    fn nothing_loaded_until_library_exists() {
+        let mut reload = HotReload::new(library_path("missing"));
+
+        assert!(!reload.reload_if_changed().unwrap());
+        assert_eq!(reload.generation(), 0);
+
+        let entry_point: Option<extern "C" fn()> = unsafe { reload.entry_point("roc__main") };
+        assert!(entry_point.is_none());
+    }
+
+    #[test]
+    fn invalid_library_is_not_retried() {
+        let path = library_path("invalid");
+        std::fs::write(&path, b"not a shared library").unwrap();
+
+        let mut reload = HotReload::new(&path);
+
+        assert!(matches!(
+            reload.reload_if_changed(),
+            Err(HotReloadError::Load(_))
+        ));
+        assert!(!reload.reload_if_changed().unwrap());
+        assert_eq!(reload.generation(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -112,6 +112,8 @@ fn valgrind_test_linux(source: &str) {
             problems,
             total_time: _,
             expect_metadata: _,
+            emitted_paths: _,
+            source_paths: _,
         }) => {
             if problems.exit_code() != 0 {
                 panic!("there are problems")
@@ -0,0 +1,460 @@
+//! An interactive debugger for stepping through WebAssembly code, in the style of gdb.
+//!
+//! Before each instruction, the debugger checks whether it should pause. When it does, it shows
+//! where we are and reads commands until one of them resumes execution. Functions can be referred
+//! to by index (as in `wasm-objdump` and `--debug` output) or by name, if the module has a name
+//! section or exports the function.
+
+use std::fmt::{self, Write as _};
+use std::io::{self, BufRead, Write};
+use std::iter::once;
+
+use roc_wasm_module::opcodes::OpCode;
+use roc_wasm_module::parse::Parse;
+use roc_wasm_module::{ExportType, ValueType};
+
+use crate::frame::Frame;
+use crate::instance::Instance;
+use crate::ImportDispatcher;
+
+const PROMPT: &str = "(wasm-dbg) ";
+
+const HELP: &str = "\
+Commands:
+  break <function>      b   Pause when a function is called (by name or index)
+  delete <function>     d   Remove a breakpoint
+  continue              c   Run until the next breakpoint
+  step                  s   Execute one instruction, stepping into calls
+  next                  n   Execute one instruction, stepping over calls
+  finish                f   Run until the current function returns
+  backtrace             bt  Show the call stack
+  locals                l   Show the arguments and locals of the current function
+  stack                     Show the value stack of the current function
+  globals               g   Show the global variables
+  memory <addr> [len]   x   Show a range of linear memory (decimal, or hex with 0x)
+  help                  h   Show this message
+  quit                  q   Stop the program
+An empty line repeats the previous command.
+";
+
+const DEFAULT_MEMORY_DUMP_LEN: usize = 64;
+
+pub struct Debugger {
+    /// Where to read commands from. `None` means stdin, which we only lock while reading a line,
+    /// so that the app can still read from it via WASI.
+    input: Option<Box<dyn BufRead>>,
+    output: Box<dyn Write>,
+    breakpoints: Vec<Breakpoint>,
+    mode: RunMode,
+    previous_command: String,
+}
+
+struct Breakpoint {
+    fn_index: usize,
+    /// Code section address of the first instruction in the function body
+    body_start: usize,
+}
+
+#[derive(Clone, Copy)]
+enum RunMode {
+    /// Pause before the next instruction
+    Step,
+    /// Pause before the next instruction that is not in a deeper call
+    StepOver { frame_depth: usize },
+    /// Pause before the next instruction after the current function returns
+    StepOut { frame_depth: usize },
+    /// Only pause at breakpoints
+    Continue,
+}
+
+pub(crate) enum DebuggerAction {
+    Resume,
+    Quit,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Debugger::new()
+    }
+}
+
+impl fmt::Debug for Debugger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Debugger").finish_non_exhaustive()
+    }
+}
+
+impl Debugger {
+    /// A debugger that reads commands from stdin and writes to stderr.
+    /// It pauses before the first instruction, so that breakpoints can be set up.
+    pub fn new() -> Self {
+        Debugger {
+            input: None,
+            output: Box::new(io::stderr()),
+            breakpoints: Vec::new(),
+            mode: RunMode::Step,
+            previous_command: String::new(),
+        }
+    }
+
+    /// A debugger with other sources of commands and destinations for output, e.g. for testing.
+    pub fn with_io(input: Box<dyn BufRead>, output: Box<dyn Write>) -> Self {
+        Debugger {
+            input: Some(input),
+            output,
+            ..Debugger::new()
+        }
+    }
+
+    fn print(&mut self, text: &str) {
+        // If we can't write to the terminal there's nobody to tell, so just carry on.
+        let _ = self.output.write_all(text.as_bytes());
+        let _ = self.output.flush();
+    }
+
+    /// Read the next command, or `None` at the end of the input
+    fn read_command(&mut self) -> Option<String> {
+        let mut line = String::new();
+        let n_read = match &mut self.input {
+            Some(input) => input.read_line(&mut line),
+            None => io::stdin().read_line(&mut line),
+        };
+
+        match n_read {
+            Ok(0) | Err(_) => None,
+            Ok(_) if line.trim().is_empty() => Some(self.previous_command.clone()),
+            Ok(_) => {
+                self.previous_command = line.trim().to_string();
+                Some(self.previous_command.clone())
+            }
+        }
+    }
+}
+
+impl<'a, I: ImportDispatcher> Instance<'a, I> {
+    pub fn attach_debugger(&mut self, debugger: Debugger) {
+        self.debugger = Some(debugger);
+    }
+
+    /// Called before every instruction while a debugger is attached
+    pub(crate) fn debugger_check(&mut self) -> DebuggerAction {
+        let mut debugger = match self.debugger.take() {
+            Some(debugger) => debugger,
+            None => return DebuggerAction::Resume,
+        };
+
+        let frame_depth = self.previous_frames.len();
+        let is_mode_pause = match debugger.mode {
+            RunMode::Step => true,
+            RunMode::StepOver { frame_depth: d } => frame_depth <= d,
+            RunMode::StepOut { frame_depth: d } => frame_depth < d,
+            RunMode::Continue => false,
+        };
+        let is_breakpoint = debugger
+            .breakpoints
+            .iter()
+            .any(|bp| bp.body_start == self.program_counter);
+
+        let action = if is_mode_pause || is_breakpoint {
+            self.debugger_prompt(&mut debugger)
+        } else {
+            DebuggerAction::Resume
+        };
+
+        self.debugger = Some(debugger);
+        action
+    }
+
+    fn debugger_prompt(&mut self, debugger: &mut Debugger) -> DebuggerAction {
+        let mut out = String::new();
+        self.debugger_write_location(&mut out).unwrap();
+        debugger.print(&out);
+
+        loop {
+            debugger.print(PROMPT);
+            let command = match debugger.read_command() {
+                Some(command) => command,
+                None => return DebuggerAction::Quit,
+            };
+
+            let mut out = String::new();
+            let mut words = command.split_whitespace();
+            let result = match (words.next(), words.next(), words.next()) {
+                (Some("s" | "step"), ..) => {
+                    debugger.mode = RunMode::Step;
+                    return DebuggerAction::Resume;
+                }
+                (Some("n" | "next"), ..) => {
+                    let frame_depth = self.previous_frames.len();
+                    debugger.mode = RunMode::StepOver { frame_depth };
+                    return DebuggerAction::Resume;
+                }
+                (Some("f" | "finish"), ..) => {
+                    let frame_depth = self.previous_frames.len();
+                    debugger.mode = RunMode::StepOut { frame_depth };
+                    return DebuggerAction::Resume;
+                }
+                (Some("c" | "continue"), ..) => {
+                    debugger.mode = RunMode::Continue;
+                    return DebuggerAction::Resume;
+                }
+                (Some("q" | "quit"), ..) => return DebuggerAction::Quit,
+                (Some("b" | "break"), Some(function), _) => {
+                    self.debugger_add_breakpoint(debugger, function, &mut out)
+                }
+                (Some("d" | "delete"), Some(function), _) => {
+                    self.debugger_find_function(function).map(|fn_index| {
+                        debugger.breakpoints.retain(|bp| bp.fn_index != fn_index);
+                    })
+                }
+                (Some("bt" | "backtrace"), ..) => self
+                    .debugger_write_backtrace(&mut out)
+                    .map_err(|e| e.to_string()),
+                (Some("l" | "locals"), ..) => self
+                    .debugger_write_locals(&mut out)
+                    .map_err(|e| e.to_string()),
+                (Some("stack"), ..) => self
+                    .debugger_write_stack(&mut out)
+                    .map_err(|e| e.to_string()),
+                (Some("g" | "globals"), ..) => {
+                    for (i, value) in self.globals.iter().enumerate() {
+                        writeln!(out, "  {}: {:?}", i, value).unwrap();
+                    }
+                    Ok(())
+                }
+                (Some("x" | "memory"), Some(addr), len) => {
+                    self.debugger_write_memory(addr, len, &mut out)
+                }
+                (Some("h" | "help"), ..) => {
+                    out.push_str(HELP);
+                    Ok(())
+                }
+                (None, ..) => Ok(()),
+                _ => Err("I don't know that command. Type `help` for a list of commands.".into()),
+            };
+
+            if let Err(message) = result {
+                writeln!(out, "{}", message).unwrap();
+            }
+            debugger.print(&out);
+        }
+    }
+
+    fn debugger_add_breakpoint(
+        &self,
+        debugger: &mut Debugger,
+        function: &str,
+        out: &mut String,
+    ) -> Result<(), String> {
+        let fn_index = self.debugger_find_function(function)?;
+
+        let internal_fn_index = fn_index.checked_sub(self.import_count).ok_or_else(|| {
+            format!(
+                "func[{}] is imported, so there's no WebAssembly code to pause in.",
+                fn_index
+            )
+        })?;
+
+        // Skip the function's byte length and local variable declarations
+        let code_bytes = &self.module.code.bytes;
+        let mut body_start = self.module.code.function_offsets[internal_fn_index] as usize;
+        u32::parse((), code_bytes, &mut body_start).unwrap();
+        let local_group_count = u32::parse((), code_bytes, &mut body_start).unwrap();
+        for _ in 0..local_group_count {
+            <(u32, ValueType)>::parse((), code_bytes, &mut body_start).unwrap();
+        }
+
+        if !debugger
+            .breakpoints
+            .iter()
+            .any(|bp| bp.fn_index == fn_index)
+        {
+            debugger.breakpoints.push(Breakpoint {
+                fn_index,
+                body_start,
+            });
+        }
+
+        writeln!(
+            out,
+            "Breakpoint at {} ({:06x})",
+            self.debugger_function_label(fn_index),
+            body_start + self.module.code.section_offset as usize
+        )
+        .unwrap();
+
+        Ok(())
+    }
+
+    fn debugger_find_function(&self, name_or_index: &str) -> Result<usize, String> {
+        let function_count = self.import_count + self.module.code.function_count as usize;
+
+        if let Ok(fn_index) = name_or_index.parse::<usize>() {
+            return if fn_index < function_count {
+                Ok(fn_index)
+            } else {
+                Err(format!(
+                    "There is no func[{}]. This module has {} functions, including imports.",
+                    fn_index, function_count
+                ))
+            };
+        }
+
+        let from_names = self
+            .module
+            .names
+            .function_names
+            .iter()
+            .find(|(_, name)| *name == name_or_index)
+            .map(|(index, _)| *index);
+        let from_exports = || {
+            self.module
+                .export
+                .exports
+                .iter()
+                .find(|ex| ex.ty == ExportType::Func && ex.name == name_or_index)
+                .map(|ex| ex.index)
+        };
+
+        from_names
+            .or_else(from_exports)
+            .map(|index| index as usize)
+            .ok_or_else(|| format!("I couldn't find a function named `{}`.", name_or_index))
+    }
+
+    fn debugger_function_label(&self, fn_index: usize) -> String {
+        let from_names = self
+            .module
+            .names
+            .function_names
+            .iter()
+            .find(|(index, _)| *index as usize == fn_index)
+            .map(|(_, name)| *name);
+        let from_exports = || {
+            self.module
+                .export
+                .exports
+                .iter()
+                .find(|ex| ex.ty == ExportType::Func && ex.index as usize == fn_index)
+                .map(|ex| ex.name)
+        };
+
+        match from_names.or_else(from_exports) {
+            Some(name) => format!("func[{}] {}", fn_index, name),
+            None => format!("func[{}]", fn_index),
+        }
+    }
+
+    /// The current function, code address and instruction.
+    /// The address is a file offset, like in the `--debug` output and in `wasm-objdump`.
+    fn debugger_write_location(&self, out: &mut String) -> fmt::Result {
+        let op_code = OpCode::from(self.module.code.bytes[self.program_counter]);
+        writeln!(
+            out,
+            "{} at {:06x}: {:?}",
+            self.debugger_function_label(self.current_frame.fn_index),
+            self.program_counter + self.module.code.section_offset as usize,
+            op_code
+        )
+    }
+
+    fn debugger_write_backtrace(&self, out: &mut String) -> fmt::Result {
+        let frames = self.previous_frames.iter().chain(once(&self.current_frame));
+
+        // For callers, show the address of the call instruction, rather than the return address
+        let call_addrs = frames
+            .clone()
+            .skip(1)
+            .map(|f| self.debug_return_addr_to_call_addr(f.return_addr));
+        let addrs = call_addrs.chain(once(self.program_counter));
+
+        let mut lines: Vec<(usize, usize)> = frames.map(|f| f.fn_index).zip(addrs).collect();
+        lines.reverse();
+
+        for (depth, (fn_index, addr)) in lines.into_iter().enumerate() {
+            writeln!(
+                out,
+                "#{:<3} {} at {:06x}",
+                depth,
+                self.debugger_function_label(fn_index),
+                addr + self.module.code.section_offset as usize
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn debugger_write_locals(&self, out: &mut String) -> fmt::Result {
+        let Frame {
+            fn_index,
+            locals_start,
+            locals_count,
+            ..
+        } = self.current_frame;
+
+        let arg_count = self.debug_arg_count(fn_index);
+
+        for local_index in 0..locals_count {
+            let value = self.value_store.get(locals_start + local_index).unwrap();
+            let kind = if local_index < arg_count {
+                "arg"
+            } else {
+                "local"
+            };
+            writeln!(out, "  {:<5} {}: {:?}", kind, local_index, value)?;
+        }
+
+        Ok(())
+    }
+
+    fn debugger_write_stack(&self, out: &mut String) -> fmt::Result {
+        let stack_start = self.current_frame.locals_start + self.current_frame.locals_count;
+        let values: Vec<_> = self.value_store.iter().skip(stack_start).collect();
+
+        writeln!(out, "  {:?}", values)
+    }
+
+    fn debugger_write_memory(
+        &self,
+        addr_str: &str,
+        len_str: Option<&str>,
+        out: &mut String,
+    ) -> Result<(), String> {
+        let parse = |s: &str| match s.strip_prefix("0x") {
+            Some(hex) => usize::from_str_radix(hex, 16).ok(),
+            None => s.parse::<usize>().ok(),
+        };
+
+        let addr = parse(addr_str).ok_or_else(|| format!("`{}` is not an address.", addr_str))?;
+        let len = match len_str {
+            Some(s) => parse(s).ok_or_else(|| format!("`{}` is not a length.", s))?,
+            None => DEFAULT_MEMORY_DUMP_LEN,
+        };
+
+        let bytes = self
+            .memory
+            .get(addr..)
+            .and_then(|rest| rest.get(..len))
+            .ok_or_else(|| {
+                format!(
+                    "That range is out of bounds. Memory is {:#x} bytes long.",
+                    self.memory.len()
+                )
+            })?;
+
+        for (i, chunk) in bytes.chunks(16).enumerate() {
+            write!(out, "  {:08x} ", addr + 16 * i).unwrap();
+            for byte in chunk {
+                write!(out, " {:02x}", byte).unwrap();
+            }
+            let padding = 3 * (16 - chunk.len());
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() { b as char } else { '.' })
+                .collect();
+            writeln!(out, "{:padding$}  |{}|", "", ascii, padding = padding).unwrap();
+        }
+
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ use roc_wasm_module::sections::{ImportDesc, MemorySection, SignatureParamsIter};
 use roc_wasm_module::{ExportType, WasmModule};
 use roc_wasm_module::{Value, ValueType};
 
+use crate::debugger::{Debugger, DebuggerAction};
 use crate::frame::Frame;
 use crate::value_store::ValueStore;
 use crate::{Error, ImportDispatcher};
@@ -47,7 +48,7 @@ pub struct Instance<'a, I: ImportDispatcher> {
     /// The current call frame
     pub(crate) current_frame: Frame,
     /// Previous call frames
-    previous_frames: Vec<'a, Frame>,
+    pub(crate) previous_frames: Vec<'a, Frame>,
     /// The WebAssembly stack machine's stack of values
     pub(crate) value_store: ValueStore<'a>,
     /// Values of any global variables
@@ -59,13 +60,15 @@ pub struct Instance<'a, I: ImportDispatcher> {
     /// Cache for branching instructions, split into buckets for each function.
     branch_cache: Vec<'a, Vec<'a, BranchCacheEntry>>,
     /// Number of imports in the module
-    import_count: usize,
+    pub(crate) import_count: usize,
     /// Import dispatcher from user code
     pub import_dispatcher: I,
     /// Temporary storage for import arguments
     import_arguments: Vec<'a, Value>,
     /// temporary storage for output using the --debug option
     debug_string: Option<String>,
+    /// Interactive debugger, if attached
+    pub(crate) debugger: Option<Debugger>,
 }
 
 impl<'a, I: ImportDispatcher> Instance<'a, I> {
@@ -95,6 +98,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             import_dispatcher,
             import_arguments: Vec::new_in(arena),
             debug_string: Some(String::new()),
+            debugger: None,
         }
     }
 
@@ -163,6 +167,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             import_dispatcher,
             import_arguments: Vec::new_in(arena),
             debug_string,
+            debugger: None,
         })
     }
 
@@ -322,6 +327,11 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         });
 
         loop {
+            if self.debugger.is_some() {
+                if let DebuggerAction::Quit = self.debugger_check() {
+                    return Err("Stopped by the debugger".to_string());
+                }
+            }
             match self.execute_next_instruction(module) {
                 Ok(Action::Continue) => {}
                 Ok(Action::Break) => {
@@ -1785,17 +1795,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 ..
             } = frame;
 
-            let arg_count = {
-                let signature_index = if *fn_index < self.import_count {
-                    match self.module.import.imports[*fn_index].description {
-                        ImportDesc::Func { signature_index } => signature_index,
-                        _ => unreachable!(),
-                    }
-                } else {
-                    self.module.function.signatures[fn_index - self.import_count]
-                };
-                self.module.types.look_up(signature_index).0.len()
-            };
+            let arg_count = self.debug_arg_count(*fn_index);
 
             // Function and address match wasm-objdump formatting, for easy copy & find
             writeln!(buffer, "func[{}]", fn_index)?;
@@ -1831,8 +1831,20 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         Ok(())
     }
 
+    pub(crate) fn debug_arg_count(&self, fn_index: usize) -> usize {
+        let signature_index = if fn_index < self.import_count {
+            match self.module.import.imports[fn_index].description {
+                ImportDesc::Func { signature_index } => signature_index,
+                _ => unreachable!(),
+            }
+        } else {
+            self.module.function.signatures[fn_index - self.import_count]
+        };
+        self.module.types.look_up(signature_index).0.len()
+    }
+
     // Call address is more intuitive than the return address in the stack trace. Search backward for it.
-    fn debug_return_addr_to_call_addr(&self, return_addr: usize) -> usize {
+    pub(crate) fn debug_return_addr_to_call_addr(&self, return_addr: usize) -> usize {
         // return_addr is pointing at the next instruction after the CALL/CALLINDIRECT.
         // Just before that is the LEB-128 function index or type index.
         // The last LEB-128 byte is <128, but the others are >=128 so we can't mistake them for CALL/CALLINDIRECT
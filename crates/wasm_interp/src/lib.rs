@@ -1,3 +1,4 @@
+mod debugger;
 mod frame;
 mod instance;
 mod tests;
@@ -5,6 +6,7 @@ mod value_store;
 pub mod wasi;
 
 // Main external interface
+pub use debugger::Debugger;
 pub use instance::Instance;
 pub use wasi::{WasiDispatcher, WasiFile};
 
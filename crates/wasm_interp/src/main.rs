@@ -4,14 +4,19 @@ use clap::{Arg, Command};
 use std::fs;
 use std::io;
 use std::iter::once;
+use std::path::Path;
 use std::process;
 
-use roc_wasm_interp::{DefaultImportDispatcher, Instance};
+use roc_wasm_interp::{Debugger, DefaultImportDispatcher, Instance};
 use roc_wasm_module::WasmModule;
 
 pub const FLAG_FUNCTION: &str = "function";
 pub const FLAG_DEBUG: &str = "debug";
+pub const FLAG_DEBUGGER: &str = "debugger";
 pub const FLAG_HEX: &str = "hex";
+pub const FLAG_DIR: &str = "dir";
+pub const FLAG_ENV: &str = "env";
+pub const FLAG_WAT: &str = "wat";
 pub const WASM_FILE: &str = "WASM_FILE";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
 
@@ -32,12 +37,40 @@ fn main() -> io::Result<()> {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_debugger = Arg::new(FLAG_DEBUGGER)
+        .long(FLAG_DEBUGGER)
+        .help("Step through the program interactively. Type `help` at the prompt for a list of commands.")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
     let flag_hex = Arg::new(FLAG_HEX)
         .long(FLAG_HEX)
         .help("If the called function returns a value, print it in hexadecimal format.")
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_dir = Arg::new(FLAG_DIR)
+        .long(FLAG_DIR)
+        .help(
+            "Give the app access to a directory on the host, under the same name. Can be repeated.",
+        )
+        .takes_value(true)
+        .action(ArgAction::Append)
+        .required(false);
+
+    let flag_env = Arg::new(FLAG_ENV)
+        .long(FLAG_ENV)
+        .help("Set an environment variable for the app, e.g. `--env NAME=value`. Can be repeated.")
+        .takes_value(true)
+        .action(ArgAction::Append)
+        .required(false);
+
+    let flag_wat = Arg::new(FLAG_WAT)
+        .long(FLAG_WAT)
+        .help("Print the module in WebAssembly text format instead of running it.")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
     let wasm_file_to_run = Arg::new(WASM_FILE)
         .help("The .wasm file to run")
         .required(true);
@@ -51,7 +84,11 @@ fn main() -> io::Result<()> {
         .about("Run the given .wasm file")
         .arg(flag_function)
         .arg(flag_debug)
+        .arg(flag_debugger)
         .arg(flag_hex)
+        .arg(flag_dir)
+        .arg(flag_env)
+        .arg(flag_wat)
         .arg(wasm_file_to_run)
         .trailing_var_arg(true)
         .arg(args_for_app);
@@ -61,7 +98,9 @@ fn main() -> io::Result<()> {
     let matches = app.get_matches();
     let start_fn_name = matches.get_one::<String>(FLAG_FUNCTION).unwrap();
     let is_debug_mode = matches.get_flag(FLAG_DEBUG);
+    let is_debugger_mode = matches.get_flag(FLAG_DEBUGGER);
     let is_hex_format = matches.get_flag(FLAG_HEX);
+    let is_wat_mode = matches.get_flag(FLAG_WAT);
     let start_arg_strings = matches.get_many::<String>(ARGS_FOR_APP).unwrap_or_default();
     let wasm_path = matches.get_one::<String>(WASM_FILE).unwrap();
     // WASI expects the .wasm file to be argv[0]
@@ -69,6 +108,11 @@ fn main() -> io::Result<()> {
         .chain(start_arg_strings)
         .map(|s| s.as_bytes());
     let wasi_argv = Vec::from_iter_in(wasi_argv_iter, &arena);
+    let wasi_env_iter = matches
+        .get_many::<String>(FLAG_ENV)
+        .unwrap_or_default()
+        .map(|s| s.as_bytes());
+    let wasi_env = Vec::from_iter_in(wasi_env_iter, &arena);
 
     // Load the WebAssembly binary file
 
@@ -87,15 +131,36 @@ fn main() -> io::Result<()> {
         }
     };
 
+    if is_wat_mode {
+        match module.to_wat() {
+            Ok(wat) => print!("{}", wat),
+            Err(e) => {
+                eprintln!("I couldn't disassemble this WebAssembly module! There's something wrong at byte offset {:#x}.", e.offset);
+                eprintln!("{}", e.message);
+                process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
     // Create an execution instance
 
-    let dispatcher = DefaultImportDispatcher::new(&wasi_argv);
+    let mut dispatcher = DefaultImportDispatcher::new(&wasi_argv);
+    dispatcher.wasi.env = &wasi_env;
+    for dir in matches.get_many::<String>(FLAG_DIR).unwrap_or_default() {
+        dispatcher.wasi.preopen_dir(dir, Path::new(dir))?;
+    }
+
     let mut inst =
         Instance::for_module(&arena, &module, dispatcher, is_debug_mode).unwrap_or_else(|e| {
             eprintln!("{}", e);
             process::exit(2);
         });
 
+    if is_debugger_mode {
+        inst.attach_debugger(Debugger::new());
+    }
+
     // Run
 
     let result = inst.call_export_from_cli(&module, start_fn_name, &wasi_argv);
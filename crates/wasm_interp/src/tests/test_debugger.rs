@@ -0,0 +1,124 @@
+use super::create_exported_function_no_locals;
+use crate::{Debugger, DefaultImportDispatcher, Instance};
+use bumpalo::Bump;
+use roc_wasm_module::sections::MemorySection;
+use roc_wasm_module::{opcodes::OpCode, Signature, Value, ValueType, WasmModule};
+use std::cell::RefCell;
+use std::io::{self, Cursor, Write};
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Run `main`, which calls `add(2, 3)`, with the debugger reading the given commands
+fn run_with_commands(commands: &str) -> (Result<Option<Value>, String>, String) {
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+    module.memory = MemorySection::new(&arena, MemorySection::PAGE_SIZE);
+
+    create_exported_function_no_locals(
+        &mut module,
+        "main",
+        Signature {
+            param_types: bumpalo::vec![in &arena],
+            ret_type: Some(ValueType::I32),
+        },
+        |buf| {
+            buf.push(OpCode::I32CONST as u8);
+            buf.push(2);
+            buf.push(OpCode::I32CONST as u8);
+            buf.push(3);
+            buf.push(OpCode::CALL as u8);
+            buf.push(1);
+            buf.push(OpCode::END as u8);
+        },
+    );
+
+    create_exported_function_no_locals(
+        &mut module,
+        "add",
+        Signature {
+            param_types: bumpalo::vec![in &arena; ValueType::I32, ValueType::I32],
+            ret_type: Some(ValueType::I32),
+        },
+        |buf| {
+            buf.push(OpCode::GETLOCAL as u8);
+            buf.push(0);
+            buf.push(OpCode::GETLOCAL as u8);
+            buf.push(1);
+            buf.push(OpCode::I32ADD as u8);
+            buf.push(OpCode::END as u8);
+        },
+    );
+
+    let output = SharedOutput::default();
+    let input = Cursor::new(commands.to_string().into_bytes());
+
+    let mut inst =
+        Instance::for_module(&arena, &module, DefaultImportDispatcher::default(), false).unwrap();
+    inst.attach_debugger(Debugger::with_io(Box::new(input), Box::new(output.clone())));
+
+    let result = inst.call_export("main", []);
+    let output_str = String::from_utf8(output.0.borrow().clone()).unwrap();
+
+    (result, output_str)
+}
+
+#[test]
+fn test_debugger_breakpoint_and_inspection() {
+    let (result, output) = run_with_commands(
+        "break add\n\
+         continue\n\
+         backtrace\n\
+         locals\n\
+         step\n\
+         step\n\
+         stack\n\
+         continue\n",
+    );
+
+    assert_eq!(result, Ok(Some(Value::I32(5))));
+
+    assert!(output.contains("Breakpoint at func[1] add"), "{}", output);
+    assert!(output.contains("#0   func[1] add"), "{}", output);
+    assert!(output.contains("#1   func[0] main"), "{}", output);
+    assert!(output.contains("arg   0: I32(2)"), "{}", output);
+    assert!(output.contains("arg   1: I32(3)"), "{}", output);
+    assert!(output.contains("[I32(2), I32(3)]"), "{}", output);
+    assert!(output.contains("I32ADD"), "{}", output);
+}
+
+#[test]
+fn test_debugger_step_over_and_finish() {
+    // Stepping over the call should go straight from CALL to END in main,
+    // and `finish` inside `add` should stop back in main.
+    let (result, output) = run_with_commands("next\nnext\nnext\nnext\ncontinue\n");
+    assert_eq!(result, Ok(Some(Value::I32(5))));
+    assert!(!output.contains("func[1] add at"), "{}", output);
+
+    let (result, output) = run_with_commands("b 1\nc\nfinish\nbt\nc\n");
+    assert_eq!(result, Ok(Some(Value::I32(5))));
+    let after_finish = output.split("(wasm-dbg) ").nth(3).unwrap();
+    assert!(after_finish.starts_with("func[0] main at"), "{}", output);
+}
+
+#[test]
+fn test_debugger_memory_and_quit() {
+    let (result, output) = run_with_commands("x 0 20\nx 0x100000000\nquit\n");
+
+    assert!(result.is_err());
+    assert!(output.contains("  00000000  00 00"), "{}", output);
+    assert!(output.contains("  00000010  00 00 00 00 "), "{}", output);
+    assert!(output.contains("out of bounds"), "{}", output);
+}
@@ -0,0 +1,377 @@
+use crate::wasi::{Errno, WasiDispatcher, WasiFile};
+use roc_wasm_module::Value;
+use std::fs;
+
+const PTR_PATH: usize = 0x100;
+const PTR_IOVS: usize = 0x200;
+const PTR_BUF: usize = 0x300;
+const PTR_OUT: usize = 0x800;
+
+/// Call a WASI function, returning its error code
+fn call(wasi: &mut WasiDispatcher, memory: &mut [u8], function_name: &str, args: &[Value]) -> i32 {
+    match wasi.dispatch(function_name, args, memory) {
+        Some(Value::I32(code)) => code,
+        other => panic!("unexpected result from {}: {:?}", function_name, other),
+    }
+}
+
+fn read_u32(memory: &[u8], addr: usize) -> u32 {
+    u32::from_le_bytes(memory[addr..][..4].try_into().unwrap())
+}
+
+fn read_u64(memory: &[u8], addr: usize) -> u64 {
+    u64::from_le_bytes(memory[addr..][..8].try_into().unwrap())
+}
+
+const LOOKUP_FOLLOW: i32 = 1;
+const LOOKUP_NOFOLLOW: i32 = 0;
+const OFLAGS_CREAT: i32 = 1;
+
+fn path_open(
+    wasi: &mut WasiDispatcher,
+    memory: &mut [u8],
+    dir_fd: i32,
+    path: &str,
+    oflags: i32,
+) -> Result<i32, i32> {
+    path_open_with_lookup(wasi, memory, dir_fd, LOOKUP_FOLLOW, path, oflags)
+}
+
+fn path_open_with_lookup(
+    wasi: &mut WasiDispatcher,
+    memory: &mut [u8],
+    dir_fd: i32,
+    lookup_flags: i32,
+    path: &str,
+    oflags: i32,
+) -> Result<i32, i32> {
+    memory[PTR_PATH..][..path.len()].copy_from_slice(path.as_bytes());
+
+    let read_write_rights = (1 << 1) | (1 << 6);
+    let result = call(
+        wasi,
+        memory,
+        "path_open",
+        &[
+            Value::I32(dir_fd),
+            Value::I32(lookup_flags),
+            Value::I32(PTR_PATH as i32),
+            Value::I32(path.len() as i32),
+            Value::I32(oflags),
+            Value::I64(read_write_rights),
+            Value::I64(read_write_rights),
+            Value::I32(0),
+            Value::I32(PTR_OUT as i32),
+        ],
+    );
+
+    if result == Errno::Success as i32 {
+        Ok(read_u32(memory, PTR_OUT) as i32)
+    } else {
+        Err(result)
+    }
+}
+
+/// Read or write `len` bytes at PTR_BUF, using a single iovec
+fn single_iov(
+    wasi: &mut WasiDispatcher,
+    memory: &mut [u8],
+    function_name: &str,
+    fd: i32,
+    len: usize,
+) -> u32 {
+    memory[PTR_IOVS..][..4].copy_from_slice(&(PTR_BUF as u32).to_le_bytes());
+    memory[PTR_IOVS + 4..][..4].copy_from_slice(&(len as u32).to_le_bytes());
+
+    let args = [
+        Value::I32(fd),
+        Value::I32(PTR_IOVS as i32),
+        Value::I32(1),
+        Value::I32(PTR_OUT as i32),
+    ];
+    assert_eq!(
+        call(wasi, memory, function_name, &args),
+        Errno::Success as i32
+    );
+
+    read_u32(memory, PTR_OUT)
+}
+
+#[test]
+fn test_file_roundtrip_in_preopened_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(".", dir.path()).unwrap();
+
+    let creat = 1;
+    let fd = path_open(&mut wasi, &mut memory, 3, "hello.txt", creat).unwrap();
+    assert_eq!(fd, 4);
+
+    let text = b"Hello, World!";
+    memory[PTR_BUF..][..text.len()].copy_from_slice(text);
+    let n_written = single_iov(&mut wasi, &mut memory, "fd_write", fd, text.len());
+    assert_eq!(n_written as usize, text.len());
+
+    // Seek back to "World" and read it
+    let args = [
+        Value::I32(fd),
+        Value::I64(7),
+        Value::I32(0),
+        Value::I32(PTR_OUT as i32),
+    ];
+    assert_eq!(
+        call(&mut wasi, &mut memory, "fd_seek", &args),
+        Errno::Success as i32
+    );
+    assert_eq!(read_u64(&memory, PTR_OUT), 7);
+
+    memory[PTR_BUF..][..text.len()].fill(0);
+    let n_read = single_iov(&mut wasi, &mut memory, "fd_read", fd, text.len());
+    assert_eq!(&memory[PTR_BUF..][..n_read as usize], b"World!");
+
+    assert_eq!(
+        call(&mut wasi, &mut memory, "fd_close", &[Value::I32(fd)]),
+        Errno::Success as i32
+    );
+    assert_eq!(
+        call(&mut wasi, &mut memory, "fd_close", &[Value::I32(fd)]),
+        Errno::Badf as i32
+    );
+
+    assert_eq!(fs::read(dir.path().join("hello.txt")).unwrap(), text);
+}
+
+#[test]
+fn test_path_cannot_escape_sandbox() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(".", &dir.path().join("sub")).unwrap();
+
+    assert_eq!(
+        path_open(&mut wasi, &mut memory, 3, "../secret.txt", 1),
+        Err(Errno::Notcapable as i32)
+    );
+    assert_eq!(
+        path_open(&mut wasi, &mut memory, 3, "/etc/passwd", 0),
+        Err(Errno::Notcapable as i32)
+    );
+    assert_eq!(
+        path_open(&mut wasi, &mut memory, 3, "missing.txt", 0),
+        Err(Errno::Noent as i32)
+    );
+    // Going up and back down again is fine, as long as we stay inside
+    assert!(path_open(&mut wasi, &mut memory, 3, "./a/../b.txt", 1).is_ok());
+
+    assert!(!dir.path().join("secret.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_cannot_escape_sandbox() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    fs::write(dir.path().join("secret.txt"), "shh").unwrap();
+    std::os::unix::fs::symlink("../secret.txt", dir.path().join("sub/link.txt")).unwrap();
+
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(".", &dir.path().join("sub")).unwrap();
+
+    assert_eq!(
+        path_open(&mut wasi, &mut memory, 3, "link.txt", 0),
+        Err(Errno::Notcapable as i32)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unfollowed_symlink_cannot_escape_sandbox() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("a/sub")).unwrap();
+    std::os::unix::fs::symlink("../../..", dir.path().join("a/sub/up")).unwrap();
+
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(".", &dir.path().join("a/sub")).unwrap();
+
+    // Opening the link itself must not open the directory it points to
+    assert_eq!(
+        path_open_with_lookup(&mut wasi, &mut memory, 3, LOOKUP_NOFOLLOW, "up", 0),
+        Err(Errno::Loop as i32)
+    );
+    assert_eq!(
+        path_open_with_lookup(&mut wasi, &mut memory, 3, LOOKUP_FOLLOW, "up", 0),
+        Err(Errno::Notcapable as i32)
+    );
+    // Symlinks in the middle of a path are always followed
+    assert_eq!(
+        path_open_with_lookup(&mut wasi, &mut memory, 3, LOOKUP_NOFOLLOW, "up/tmp", 0),
+        Err(Errno::Notcapable as i32)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dangling_symlink_cannot_create_outside_sandbox() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    std::os::unix::fs::symlink("../created.txt", dir.path().join("sub/new.txt")).unwrap();
+    std::os::unix::fs::symlink("inside.txt", dir.path().join("sub/ok.txt")).unwrap();
+
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(".", &dir.path().join("sub")).unwrap();
+
+    for lookup_flags in [LOOKUP_FOLLOW, LOOKUP_NOFOLLOW] {
+        assert!(path_open_with_lookup(
+            &mut wasi,
+            &mut memory,
+            3,
+            lookup_flags,
+            "new.txt",
+            OFLAGS_CREAT
+        )
+        .is_err());
+    }
+    assert!(!dir.path().join("created.txt").exists());
+
+    // A dangling symlink within the sandbox can still be used to create its target
+    assert!(path_open(&mut wasi, &mut memory, 3, "ok.txt", OFLAGS_CREAT).is_ok());
+    assert!(dir.path().join("sub/inside.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_path_symlink_target_must_stay_in_sandbox() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(".", &dir.path().join("sub")).unwrap();
+
+    let mut symlink = |memory: &mut [u8], target: &str, link: &str| {
+        memory[PTR_PATH..][..target.len()].copy_from_slice(target.as_bytes());
+        memory[PTR_BUF..][..link.len()].copy_from_slice(link.as_bytes());
+
+        let args = [
+            Value::I32(PTR_PATH as i32),
+            Value::I32(target.len() as i32),
+            Value::I32(3),
+            Value::I32(PTR_BUF as i32),
+            Value::I32(link.len() as i32),
+        ];
+        call(&mut wasi, memory, "path_symlink", &args)
+    };
+
+    assert_eq!(
+        symlink(&mut memory, "../secret.txt", "link.txt"),
+        Errno::Notcapable as i32
+    );
+    assert_eq!(
+        symlink(&mut memory, "a/../../secret.txt", "link.txt"),
+        Errno::Notcapable as i32
+    );
+    assert_eq!(
+        symlink(&mut memory, "a/../b.txt", "link.txt"),
+        Errno::Success as i32
+    );
+}
+
+#[test]
+fn test_fd_readdir() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("b.txt"), "").unwrap();
+    fs::create_dir(dir.path().join("a")).unwrap();
+
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(".", dir.path()).unwrap();
+
+    let buf_len = 0x400;
+    let args = [
+        Value::I32(3),
+        Value::I32(PTR_BUF as i32),
+        Value::I32(buf_len),
+        Value::I64(0),
+        Value::I32(PTR_OUT as i32),
+    ];
+    assert_eq!(
+        call(&mut wasi, &mut memory, "fd_readdir", &args),
+        Errno::Success as i32
+    );
+
+    let buf_used = read_u32(&memory, PTR_OUT) as usize;
+    let mut names = Vec::new();
+    let mut offset = PTR_BUF;
+    while offset < PTR_BUF + buf_used {
+        let name_len = read_u32(&memory, offset + 16) as usize;
+        let filetype = memory[offset + 20];
+        let name = std::str::from_utf8(&memory[offset + 24..][..name_len]).unwrap();
+        names.push((name.to_string(), filetype));
+        offset += 24 + name_len;
+    }
+
+    let directory = 3;
+    let regular_file = 4;
+    assert_eq!(
+        names,
+        [
+            (".".to_string(), directory),
+            ("..".to_string(), directory),
+            ("a".to_string(), directory),
+            ("b.txt".to_string(), regular_file),
+        ]
+    );
+}
+
+#[test]
+fn test_environ() {
+    let env: &[&[u8]] = &[b"HOME=/home/roc", b"X=1"];
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.env = env;
+
+    let args = [Value::I32(PTR_OUT as i32), Value::I32(PTR_OUT as i32 + 4)];
+    assert_eq!(
+        call(&mut wasi, &mut memory, "environ_sizes_get", &args),
+        Errno::Success as i32
+    );
+    assert_eq!(read_u32(&memory, PTR_OUT), 2);
+    assert_eq!(read_u32(&memory, PTR_OUT + 4), 19);
+
+    let args = [Value::I32(PTR_IOVS as i32), Value::I32(PTR_BUF as i32)];
+    assert_eq!(
+        call(&mut wasi, &mut memory, "environ_get", &args),
+        Errno::Success as i32
+    );
+    assert_eq!(read_u32(&memory, PTR_IOVS), PTR_BUF as u32);
+    assert_eq!(read_u32(&memory, PTR_IOVS + 4), PTR_BUF as u32 + 15);
+    assert_eq!(&memory[PTR_BUF..][..19], b"HOME=/home/roc\0X=1\0");
+}
+
+#[test]
+fn test_in_memory_stdin_is_consumed() {
+    let mut memory = vec![0; 0x1000];
+    let mut wasi = WasiDispatcher::default();
+    wasi.files[0] = WasiFile::ReadOnly(b"abc".to_vec());
+
+    assert_eq!(single_iov(&mut wasi, &mut memory, "fd_read", 0, 2), 2);
+    assert_eq!(single_iov(&mut wasi, &mut memory, "fd_read", 0, 2), 1);
+    assert_eq!(&memory[PTR_BUF..][..1], b"c");
+    assert_eq!(single_iov(&mut wasi, &mut memory, "fd_read", 0, 2), 0);
+
+    let args = [
+        Value::I32(0),
+        Value::I64(0),
+        Value::I32(0),
+        Value::I32(PTR_OUT as i32),
+    ];
+    assert_eq!(
+        call(&mut wasi, &mut memory, "fd_seek", &args),
+        Errno::Spipe as i32
+    );
+}
@@ -1,14 +1,21 @@
 use rand::prelude::*;
 use roc_wasm_module::Value;
-use std::io::{self, Read, StderrLock, StdoutLock, Write};
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, StderrLock, StdoutLock, Write};
+use std::path::{Component, Path, PathBuf};
 use std::process::exit;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 pub const MODULE_NAME: &str = "wasi_snapshot_preview1";
 
 pub struct WasiDispatcher<'a> {
     pub args: &'a [&'a [u8]],
+    /// Environment variables for the app, in `KEY=value` form.
+    /// Empty by default, so that nothing about the host leaks into the sandbox.
+    pub env: &'a [&'a [u8]],
     pub rng: ThreadRng,
     pub files: Vec<WasiFile>,
+    start_time: Instant,
 }
 
 impl Default for WasiDispatcher<'_> {
@@ -17,19 +24,75 @@ impl Default for WasiDispatcher<'_> {
     }
 }
 
+/// The things a WASI file descriptor can refer to.
+/// The in-memory variants behave like pipes: reading consumes bytes from the front, and
+/// writing appends to the end. They are useful for faking stdio in tests.
 pub enum WasiFile {
     ReadOnly(Vec<u8>),
     WriteOnly(Vec<u8>),
     ReadWrite(Vec<u8>),
     HostSystemFile,
+    /// A file on the host, opened from inside a preopened directory
+    HostFile(File),
+    /// A directory on the host. Paths relative to it can't lead outside of `sandbox_root`.
+    HostDirectory {
+        /// The name the app knows this directory by, if it's one of the preopened directories
+        preopen_name: Option<String>,
+        /// Canonical path of this directory
+        host_path: PathBuf,
+        /// Canonical path of the preopened directory this one was opened from
+        sandbox_root: PathBuf,
+    },
+    /// A closed file descriptor, which can be reused by the next file to be opened
+    Closed,
 }
 
 enum WriteLock<'a> {
     StdOut(StdoutLock<'a>),
     Stderr(StderrLock<'a>),
     RegularFile(&'a mut Vec<u8>),
+    HostFile(&'a mut File),
 }
 
+// https://github.com/WebAssembly/WASI/blob/main/legacy/preview1/docs.md#filetype
+const FILETYPE_UNKNOWN: u8 = 0;
+const FILETYPE_CHARACTER_DEVICE: u8 = 2;
+const FILETYPE_DIRECTORY: u8 = 3;
+const FILETYPE_REGULAR_FILE: u8 = 4;
+const FILETYPE_SYMBOLIC_LINK: u8 = 7;
+
+const FDFLAGS_APPEND: u16 = 1 << 0;
+const LOOKUPFLAGS_SYMLINK_FOLLOW: i32 = 1 << 0;
+
+/// Like Linux's MAXSYMLINKS, to stop following symlinks that lead to each other
+const MAX_SYMLINKS: usize = 40;
+
+const OFLAGS_CREAT: i32 = 1 << 0;
+const OFLAGS_DIRECTORY: i32 = 1 << 1;
+const OFLAGS_EXCL: i32 = 1 << 2;
+const OFLAGS_TRUNC: i32 = 1 << 3;
+
+const RIGHTS_FD_READ: i64 = 1 << 1;
+const RIGHTS_FD_SEEK: u64 = 1 << 2;
+const RIGHTS_FD_TELL: u64 = 1 << 5;
+const RIGHTS_FD_WRITE: i64 = 1 << 6;
+const RIGHTS_FD_ALLOCATE: i64 = 1 << 8;
+const RIGHTS_FD_FILESTAT_SET_SIZE: i64 = 1 << 22;
+/// We don't restrict what the app can do with a file descriptor, beyond what makes sense for it.
+const RIGHTS_ALL: u64 = (1 << 29) - 1;
+
+const EVENTTYPE_CLOCK: u8 = 0;
+const EVENTTYPE_FD_READ: u8 = 1;
+const EVENTTYPE_FD_WRITE: u8 = 2;
+const SUBCLOCKFLAGS_ABSTIME: u16 = 1 << 0;
+
+const CLOCKID_REALTIME: i32 = 0;
+
+const FILESTAT_SIZE: usize = 64;
+const DIRENT_SIZE: usize = 24;
+const SUBSCRIPTION_SIZE: usize = 48;
+const EVENT_SIZE: usize = 32;
+
 /// Implementation of WASI syscalls
 /// References for other engines:
 /// https://github.com/wasmerio/wasmer/blob/ef8d2f651ed29b4b06fdc2070eb8189922c54d82/lib/wasi/src/syscalls/mod.rs
@@ -38,15 +101,31 @@ impl<'a> WasiDispatcher<'a> {
     pub fn new(args: &'a [&'a [u8]]) -> Self {
         WasiDispatcher {
             args,
+            env: &[],
             rng: thread_rng(),
             files: vec![
                 WasiFile::HostSystemFile,
                 WasiFile::HostSystemFile,
                 WasiFile::HostSystemFile,
             ],
+            start_time: Instant::now(),
         }
     }
 
+    /// Give the app access to a directory on the host, under the name `guest_name` (e.g. `"."`).
+    /// The app can open anything inside this directory, but nothing outside of it.
+    pub fn preopen_dir(&mut self, guest_name: &str, host_path: &Path) -> io::Result<()> {
+        let host_path = host_path.canonicalize()?;
+
+        self.files.push(WasiFile::HostDirectory {
+            preopen_name: Some(guest_name.to_string()),
+            sandbox_root: host_path.clone(),
+            host_path,
+        });
+
+        Ok(())
+    }
+
     pub fn dispatch(
         &mut self,
         function_name: &str,
@@ -58,18 +137,11 @@ impl<'a> WasiDispatcher<'a> {
         match function_name {
             "args_get" => {
                 // uint8_t ** argv,
-                let mut ptr_ptr_argv = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_ptr_argv = arguments[0].expect_i32().unwrap() as usize;
                 // uint8_t * argv_buf
-                let mut ptr_argv_buf = arguments[1].expect_i32().unwrap() as usize;
-
-                for arg in self.args {
-                    write_u32(memory, ptr_ptr_argv, ptr_argv_buf as u32);
-                    let bytes_target = &mut memory[ptr_argv_buf..][..arg.len()];
-                    bytes_target.copy_from_slice(arg);
-                    memory[ptr_argv_buf + arg.len()] = 0; // C string zero termination
-                    ptr_argv_buf += arg.len() + 1;
-                    ptr_ptr_argv += 4;
-                }
+                let ptr_argv_buf = arguments[1].expect_i32().unwrap() as usize;
+
+                write_c_strings(memory, self.args, ptr_ptr_argv, ptr_argv_buf);
 
                 success_code
             }
@@ -81,29 +153,114 @@ impl<'a> WasiDispatcher<'a> {
                 // size of string arguments buffer
                 let ptr_argv_buf_size = arguments[1].expect_i32().unwrap() as usize;
 
-                let argc = self.args.len() as u32;
-                write_u32(memory, ptr_argc, argc);
+                write_c_strings_sizes(memory, self.args, ptr_argc, ptr_argv_buf_size);
 
-                let argv_buf_size: u32 = self.args.iter().map(|a| 1 + a.len() as u32).sum();
-                write_u32(memory, ptr_argv_buf_size, argv_buf_size);
+                success_code
+            }
+            "environ_get" => {
+                // uint8_t ** environ,
+                let ptr_ptr_environ = arguments[0].expect_i32().unwrap() as usize;
+                // uint8_t * environ_buf
+                let ptr_environ_buf = arguments[1].expect_i32().unwrap() as usize;
+
+                write_c_strings(memory, self.env, ptr_ptr_environ, ptr_environ_buf);
 
                 success_code
             }
-            "environ_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "environ_sizes_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "clock_res_get" => success_code, // this dummy implementation seems to be good enough for some functions
-            "clock_time_get" => success_code,
-            "fd_advise" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_allocate" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_close" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_datasync" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_fdstat_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_fdstat_set_flags" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_fdstat_set_rights" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_set_size" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_set_times" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_pread" => todo!("WASI {}({:?})", function_name, arguments),
+            "environ_sizes_get" => {
+                // number of environment variables
+                let ptr_count = arguments[0].expect_i32().unwrap() as usize;
+                // size of environment variable buffer
+                let ptr_buf_size = arguments[1].expect_i32().unwrap() as usize;
+
+                write_c_strings_sizes(memory, self.env, ptr_count, ptr_buf_size);
+
+                success_code
+            }
+            "clock_res_get" => {
+                // The clock to query
+                let clock_id = arguments[0].expect_i32().unwrap();
+                // Out param: resolution in nanoseconds
+                let ptr_resolution = arguments[1].expect_i32().unwrap() as usize;
+
+                errno_result(self.clock_now(clock_id).map(|_| {
+                    write_u64(memory, ptr_resolution, 1);
+                }))
+            }
+            "clock_time_get" => {
+                // The clock to query
+                let clock_id = arguments[0].expect_i32().unwrap();
+                // The maximum lag the app can tolerate. We're always as precise as we can be.
+                let _precision = arguments[1].expect_i64().unwrap();
+                // Out param: time in nanoseconds
+                let ptr_time = arguments[2].expect_i32().unwrap() as usize;
+
+                errno_result(self.clock_now(clock_id).map(|time| {
+                    write_u64(memory, ptr_time, time);
+                }))
+            }
+            "fd_advise" => {
+                // The advice is only a hint, so we can ignore it, as long as the fd is a file.
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                errno_result(self.host_file(fd).map(|_| ()))
+            }
+            "fd_allocate" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let offset = arguments[1].expect_i64().unwrap() as u64;
+                let len = arguments[2].expect_i64().unwrap() as u64;
+
+                errno_result(self.host_file(fd).and_then(|file| {
+                    let required_len = offset.checked_add(len).ok_or(Errno::Fbig)?;
+                    if file.metadata()?.len() < required_len {
+                        file.set_len(required_len)?;
+                    }
+                    Ok(())
+                }))
+            }
+            "fd_close" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                match self.files.get_mut(fd) {
+                    Some(WasiFile::Closed) | None => Some(Value::I32(Errno::Badf as i32)),
+                    Some(file) => {
+                        *file = WasiFile::Closed;
+                        success_code
+                    }
+                }
+            }
+            "fd_datasync" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                errno_result(self.host_file(fd).and_then(|file| Ok(file.sync_data()?)))
+            }
+            "fd_fdstat_get" => errno_result(self.fd_fdstat_get(arguments, memory)),
+            "fd_fdstat_set_flags" => {
+                // std::fs::File can't change its flags after it's opened
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                errno_result(self.open_file(fd).and(Err(Errno::Notsup)))
+            }
+            "fd_fdstat_set_rights" => {
+                // We don't enforce rights, so there's nothing to narrow down
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                errno_result(self.open_file(fd).map(|_| ()))
+            }
+            "fd_filestat_get" => errno_result(self.fd_filestat_get(arguments, memory)),
+            "fd_filestat_set_size" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let size = arguments[1].expect_i64().unwrap() as u64;
+
+                errno_result(self.host_file(fd).and_then(|file| Ok(file.set_len(size)?)))
+            }
+            "fd_filestat_set_times" => {
+                // std::fs has no portable way to set access and modification times
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                errno_result(self.open_file(fd).and(Err(Errno::Nosys)))
+            }
+            "fd_pread" => errno_result(self.fd_pread_pwrite(arguments, memory, false)),
             "fd_prestat_get" => {
                 // The preopened file descriptor to query
                 let fd = arguments[0].expect_i32().unwrap() as usize;
@@ -111,19 +268,41 @@ impl<'a> WasiDispatcher<'a> {
                 //  preopen type: 4 bytes, where 0=dir is the only one supported, it seems
                 //  preopen name length: 4 bytes
                 let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
-                memory[ptr_buf..][..8].copy_from_slice(&0u64.to_le_bytes());
-                if fd < self.files.len() {
-                    success_code
-                } else {
-                    println!("WASI warning: file descriptor {} does not exist", fd);
-                    Some(Value::I32(Errno::Badf as i32))
+
+                // wasi-libc calls this for every fd from 3 upwards, until it gets Badf
+                match self.files.get(fd) {
+                    Some(WasiFile::HostDirectory {
+                        preopen_name: Some(name),
+                        ..
+                    }) => {
+                        write_u32(memory, ptr_buf, 0);
+                        write_u32(memory, ptr_buf + 4, name.len() as u32);
+                        success_code
+                    }
+                    _ => Some(Value::I32(Errno::Badf as i32)),
                 }
             }
             "fd_prestat_dir_name" => {
-                // We're not giving names to any of our files so just return success
-                success_code
+                // The preopened file descriptor to query
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Where to write the name
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                // Size of the buffer, as returned by fd_prestat_get
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                match self.files.get(fd) {
+                    Some(WasiFile::HostDirectory {
+                        preopen_name: Some(name),
+                        ..
+                    }) => {
+                        let len = name.len().min(path_len);
+                        memory[ptr_path..][..len].copy_from_slice(&name.as_bytes()[..len]);
+                        success_code
+                    }
+                    _ => Some(Value::I32(Errno::Badf as i32)),
+                }
             }
-            "fd_pwrite" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_pwrite" => errno_result(self.fd_pread_pwrite(arguments, memory, true)),
             "fd_read" => {
                 use WasiFile::*;
 
@@ -132,7 +311,7 @@ impl<'a> WasiDispatcher<'a> {
                 // Array of IO vectors
                 let ptr_iovs = arguments[1].expect_i32().unwrap() as usize;
                 // Length of array
-                let iovs_len = arguments[2].expect_i32().unwrap();
+                let iovs_len = arguments[2].expect_i32().unwrap() as usize;
                 // Out param: number of bytes read
                 let ptr_nread = arguments[3].expect_i32().unwrap() as usize;
 
@@ -143,46 +322,87 @@ impl<'a> WasiDispatcher<'a> {
                 // };
 
                 let mut n_read: usize = 0;
-                match self.files.get(fd) {
-                    Some(ReadOnly(content) | ReadWrite(content)) => {
-                        for _ in 0..iovs_len {
-                            let iov_base = read_u32(memory, ptr_iovs) as usize;
-                            let iov_len = read_i32(memory, ptr_iovs + 4) as usize;
-                            let remaining = content.len() - n_read;
-                            let len = remaining.min(iov_len);
-                            if len == 0 {
-                                break;
-                            }
-                            memory[iov_base..][..len].copy_from_slice(&content[n_read..][..len]);
-                            n_read += len;
+                for i in 0..iovs_len {
+                    let iov_base = read_u32(memory, ptr_iovs + 8 * i) as usize;
+                    let iov_len = read_u32(memory, ptr_iovs + 8 * i + 4) as usize;
+                    let buf = &mut memory[iov_base..][..iov_len];
+
+                    let result = match self.files.get_mut(fd) {
+                        Some(ReadOnly(content) | ReadWrite(content)) => {
+                            let len = content.len().min(iov_len);
+                            buf[..len].copy_from_slice(&content[..len]);
+                            content.drain(..len);
+                            Ok(len)
                         }
-                    }
-                    Some(HostSystemFile) if fd == 0 => {
-                        let mut stdin = io::stdin();
-                        for _ in 0..iovs_len {
-                            let iov_base = read_u32(memory, ptr_iovs) as usize;
-                            let iov_len = read_i32(memory, ptr_iovs + 4) as usize;
-                            match stdin.read(&mut memory[iov_base..][..iov_len]) {
-                                Ok(n) => {
-                                    n_read += n;
-                                }
-                                Err(_) => {
-                                    break;
-                                }
+                        Some(HostSystemFile) if fd == 0 => io::stdin().read(buf),
+                        Some(HostFile(file)) => file.read(buf),
+                        Some(HostDirectory { .. }) => return Some(Value::I32(Errno::Isdir as i32)),
+                        _ => return Some(Value::I32(Errno::Badf as i32)),
+                    };
+
+                    match result {
+                        Ok(n) => {
+                            n_read += n;
+                            if n < iov_len {
+                                // Don't block waiting for more, if we've already got something
+                                break;
                             }
                         }
+                        Err(e) => return Some(Value::I32(Errno::from(e) as i32)),
                     }
-                    _ => return Some(Value::I32(Errno::Badf as i32)),
-                };
+                }
+
+                write_u32(memory, ptr_nread, n_read as u32);
+                success_code
+            }
+            "fd_readdir" => errno_result(self.fd_readdir(arguments, memory)),
+            "fd_renumber" => {
+                let from = arguments[0].expect_i32().unwrap() as usize;
+                let to = arguments[1].expect_i32().unwrap() as usize;
 
-                memory[ptr_nread..][..4].copy_from_slice(&(n_read as u32).to_le_bytes());
+                if self.open_file(from).is_err() || self.open_file(to).is_err() {
+                    return Some(Value::I32(Errno::Badf as i32));
+                }
+
+                self.files[to] = std::mem::replace(&mut self.files[from], WasiFile::Closed);
                 success_code
             }
-            "fd_readdir" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_renumber" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_seek" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_sync" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_tell" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_seek" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let offset = arguments[1].expect_i64().unwrap();
+                let whence = arguments[2].expect_i32().unwrap();
+                // Out param: the new offset, relative to the start of the file
+                let ptr_newoffset = arguments[3].expect_i32().unwrap() as usize;
+
+                let seek_from = match whence {
+                    0 => SeekFrom::Start(offset as u64),
+                    1 => SeekFrom::Current(offset),
+                    2 => SeekFrom::End(offset),
+                    _ => return Some(Value::I32(Errno::Inval as i32)),
+                };
+
+                errno_result(self.seekable_file(fd).and_then(|file| {
+                    let new_offset = file.seek(seek_from)?;
+                    write_u64(memory, ptr_newoffset, new_offset);
+                    Ok(())
+                }))
+            }
+            "fd_sync" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                errno_result(self.host_file(fd).and_then(|file| Ok(file.sync_all()?)))
+            }
+            "fd_tell" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: the current offset, relative to the start of the file
+                let ptr_offset = arguments[1].expect_i32().unwrap() as usize;
+
+                errno_result(self.seekable_file(fd).and_then(|file| {
+                    let offset = file.stream_position()?;
+                    write_u64(memory, ptr_offset, offset);
+                    Ok(())
+                }))
+            }
             "fd_write" => {
                 use WasiFile::*;
 
@@ -191,7 +411,7 @@ impl<'a> WasiDispatcher<'a> {
                 // Array of IO vectors
                 let ptr_iovs = arguments[1].expect_i32().unwrap() as usize;
                 // Length of array
-                let iovs_len = arguments[2].expect_i32().unwrap();
+                let iovs_len = arguments[2].expect_i32().unwrap() as usize;
                 // Out param: number of bytes written
                 let ptr_nwritten = arguments[3].expect_i32().unwrap() as usize;
 
@@ -206,19 +426,21 @@ impl<'a> WasiDispatcher<'a> {
                     Some(WriteOnly(content) | ReadWrite(content)) => {
                         WriteLock::RegularFile(content)
                     }
+                    Some(HostFile(file)) => WriteLock::HostFile(file),
+                    Some(HostDirectory { .. }) => return Some(Value::I32(Errno::Isdir as i32)),
                     _ => return Some(Value::I32(Errno::Badf as i32)),
                 };
 
                 let mut n_written: i32 = 0;
                 let mut negative_length_count = 0;
-                for _ in 0..iovs_len {
+                for i in 0..iovs_len {
                     // https://man7.org/linux/man-pages/man2/readv.2.html
                     // struct iovec {
                     //     void  *iov_base;    /* Starting address */
                     //     size_t iov_len;     /* Number of bytes to transfer */
                     // };
-                    let iov_base = read_u32(memory, ptr_iovs) as usize;
-                    let iov_len = read_i32(memory, ptr_iovs + 4);
+                    let iov_base = read_u32(memory, ptr_iovs + 8 * i) as usize;
+                    let iov_len = read_i32(memory, ptr_iovs + 8 * i + 4);
                     if iov_len < 0 {
                         // I found negative-length iov's when I implemented this in JS for the web REPL (see wasi.js)
                         // I'm not sure why, but this solution worked, and it's the same WASI libc - there's only one.
@@ -228,17 +450,19 @@ impl<'a> WasiDispatcher<'a> {
                     }
                     let bytes = &memory[iov_base..][..iov_len as usize];
 
-                    match &mut write_lock {
-                        WriteLock::StdOut(stdout) => {
-                            n_written += stdout.write(bytes).unwrap() as i32;
-                        }
-                        WriteLock::Stderr(stderr) => {
-                            n_written += stderr.write(bytes).unwrap() as i32;
-                        }
+                    let result = match &mut write_lock {
+                        WriteLock::StdOut(stdout) => stdout.write(bytes),
+                        WriteLock::Stderr(stderr) => stderr.write(bytes),
                         WriteLock::RegularFile(content) => {
                             content.extend_from_slice(bytes);
-                            n_written += bytes.len() as i32;
+                            Ok(bytes.len())
                         }
+                        WriteLock::HostFile(file) => file.write(bytes),
+                    };
+
+                    match result {
+                        Ok(n) => n_written += n as i32,
+                        Err(e) => return Some(Value::I32(Errno::from(e) as i32)),
                     }
                 }
 
@@ -253,23 +477,184 @@ impl<'a> WasiDispatcher<'a> {
 
                 success_code
             }
-            "path_create_directory" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_filestat_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_filestat_set_times" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_link" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_open" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_readlink" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_remove_directory" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_rename" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_symlink" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_unlink_file" => todo!("WASI {}({:?})", function_name, arguments),
-            "poll_oneoff" => todo!("WASI {}({:?})", function_name, arguments),
+            "path_create_directory" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                errno_result(
+                    self.resolve_path(fd, memory, ptr_path, path_len, false)
+                        .and_then(|path| Ok(fs::create_dir(path)?)),
+                )
+            }
+            "path_filestat_get" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let lookup_flags = arguments[1].expect_i32().unwrap();
+                let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+                let path_len = arguments[3].expect_i32().unwrap() as usize;
+                // Out param: the filestat struct
+                let ptr_buf = arguments[4].expect_i32().unwrap() as usize;
+
+                let follow_symlinks = lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+
+                errno_result(
+                    self.resolve_path(fd, memory, ptr_path, path_len, follow_symlinks)
+                        .and_then(|path| {
+                            let metadata = if follow_symlinks {
+                                fs::metadata(path)?
+                            } else {
+                                fs::symlink_metadata(path)?
+                            };
+                            write_filestat(memory, ptr_buf, &metadata);
+                            Ok(())
+                        }),
+                )
+            }
+            "path_filestat_set_times" => {
+                // std::fs has no portable way to set access and modification times
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                errno_result(self.open_file(fd).and(Err(Errno::Nosys)))
+            }
+            "path_link" => {
+                let old_fd = arguments[0].expect_i32().unwrap() as usize;
+                let _old_lookup_flags = arguments[1].expect_i32().unwrap();
+                let ptr_old_path = arguments[2].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[3].expect_i32().unwrap() as usize;
+                let new_fd = arguments[4].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[5].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[6].expect_i32().unwrap() as usize;
+
+                errno_result((|| {
+                    let old_path =
+                        self.resolve_path(old_fd, memory, ptr_old_path, old_path_len, false)?;
+                    let new_path =
+                        self.resolve_path(new_fd, memory, ptr_new_path, new_path_len, false)?;
+                    Ok(fs::hard_link(old_path, new_path)?)
+                })())
+            }
+            "path_open" => errno_result(self.path_open(arguments, memory)),
+            "path_readlink" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+                let ptr_buf = arguments[3].expect_i32().unwrap() as usize;
+                let buf_len = arguments[4].expect_i32().unwrap() as usize;
+                // Out param: number of bytes written to the buffer
+                let ptr_bufused = arguments[5].expect_i32().unwrap() as usize;
+
+                errno_result(
+                    self.resolve_path(fd, memory, ptr_path, path_len, false)
+                        .and_then(|path| {
+                            let target = fs::read_link(path)?;
+                            let target = target.to_str().ok_or(Errno::Ilseq)?.as_bytes();
+                            // Like readlink(2), truncate the contents if the buffer is too small
+                            let len = target.len().min(buf_len);
+                            memory[ptr_buf..][..len].copy_from_slice(&target[..len]);
+                            write_u32(memory, ptr_bufused, len as u32);
+                            Ok(())
+                        }),
+                )
+            }
+            "path_remove_directory" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                errno_result(
+                    self.resolve_path(fd, memory, ptr_path, path_len, false)
+                        .and_then(|path| {
+                            if !fs::symlink_metadata(&path)?.is_dir() {
+                                return Err(Errno::Notdir);
+                            }
+                            if fs::read_dir(&path)?.next().is_some() {
+                                return Err(Errno::Notempty);
+                            }
+                            Ok(fs::remove_dir(path)?)
+                        }),
+                )
+            }
+            "path_rename" => {
+                let old_fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_old_path = arguments[1].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[2].expect_i32().unwrap() as usize;
+                let new_fd = arguments[3].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[4].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[5].expect_i32().unwrap() as usize;
+
+                errno_result((|| {
+                    let old_path =
+                        self.resolve_path(old_fd, memory, ptr_old_path, old_path_len, false)?;
+                    let new_path =
+                        self.resolve_path(new_fd, memory, ptr_new_path, new_path_len, false)?;
+                    Ok(fs::rename(old_path, new_path)?)
+                })())
+            }
+            "path_symlink" => {
+                let ptr_old_path = arguments[0].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[1].expect_i32().unwrap() as usize;
+                let fd = arguments[2].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[3].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[4].expect_i32().unwrap() as usize;
+
+                errno_result((|| {
+                    // The link target is stored as-is, and checked against the sandbox again when
+                    // something follows the link. But a target that leads out of it, judging by
+                    // the directories it names, can never be valid.
+                    let target = read_str(memory, ptr_old_path, old_path_len)?;
+                    if Path::new(target).has_root() {
+                        return Err(Errno::Notcapable);
+                    }
+                    let link = self.resolve_path(fd, memory, ptr_new_path, new_path_len, false)?;
+                    let mut target_path = link.parent().ok_or(Errno::Notcapable)?.to_path_buf();
+                    for component in Path::new(target).components() {
+                        match component {
+                            Component::Normal(name) => target_path.push(name),
+                            Component::CurDir => {}
+                            Component::ParentDir => {
+                                target_path.pop();
+                            }
+                            Component::RootDir | Component::Prefix(_) => {
+                                return Err(Errno::Notcapable)
+                            }
+                        }
+                    }
+                    if !target_path.starts_with(self.sandbox_root(fd)?) {
+                        return Err(Errno::Notcapable);
+                    }
+                    symlink(target, &link)
+                })())
+            }
+            "path_unlink_file" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                errno_result(
+                    self.resolve_path(fd, memory, ptr_path, path_len, false)
+                        .and_then(|path| {
+                            if fs::symlink_metadata(&path)?.is_dir() {
+                                return Err(Errno::Isdir);
+                            }
+                            Ok(fs::remove_file(path)?)
+                        }),
+                )
+            }
+            "poll_oneoff" => errno_result(self.poll_oneoff(arguments, memory)),
             "proc_exit" => {
                 let exit_code = arguments[0].expect_i32().unwrap();
                 exit(exit_code);
             }
-            "proc_raise" => todo!("WASI {}({:?})", function_name, arguments),
-            "sched_yield" => todo!("WASI {}({:?})", function_name, arguments),
+            "proc_raise" => {
+                // There are no signal handlers in WASI, so every signal terminates the process,
+                // with the same exit code a shell would report.
+                let signal = arguments[0].expect_i32().unwrap();
+                exit(128 + signal);
+            }
+            "sched_yield" => {
+                std::thread::yield_now();
+                success_code
+            }
             "random_get" => {
                 // A pointer to a buffer where the random bytes will be written
                 let ptr_buf = arguments[0].expect_i32().unwrap() as usize;
@@ -280,12 +665,641 @@ impl<'a> WasiDispatcher<'a> {
                 }
                 success_code
             }
-            "sock_recv" => todo!("WASI {}({:?})", function_name, arguments),
-            "sock_send" => todo!("WASI {}({:?})", function_name, arguments),
-            "sock_shutdown" => todo!("WASI {}({:?})", function_name, arguments),
+            // The sandbox has no network access, so there can't be any sockets
+            "sock_accept" | "sock_recv" | "sock_send" | "sock_shutdown" => {
+                Some(Value::I32(Errno::Notsock as i32))
+            }
             _ => panic!("Unknown WASI function {}({:?})", function_name, arguments),
         }
     }
+
+    fn clock_now(&self, clock_id: i32) -> Result<u64, Errno> {
+        match clock_id {
+            CLOCKID_REALTIME => {
+                let since_epoch = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                Ok(since_epoch.as_nanos() as u64)
+            }
+            // Monotonic, process CPU time and thread CPU time.
+            // We only have one thread, and it's always busy, so they're all roughly the same.
+            1..=3 => Ok(self.start_time.elapsed().as_nanos() as u64),
+            _ => Err(Errno::Inval),
+        }
+    }
+
+    fn open_file(&mut self, fd: usize) -> Result<&mut WasiFile, Errno> {
+        match self.files.get_mut(fd) {
+            Some(WasiFile::Closed) | None => Err(Errno::Badf),
+            Some(file) => Ok(file),
+        }
+    }
+
+    fn host_file(&mut self, fd: usize) -> Result<&mut File, Errno> {
+        match self.open_file(fd)? {
+            WasiFile::HostFile(file) => Ok(file),
+            WasiFile::HostDirectory { .. } => Err(Errno::Isdir),
+            _ => Err(Errno::Inval),
+        }
+    }
+
+    fn seekable_file(&mut self, fd: usize) -> Result<&mut File, Errno> {
+        match self.open_file(fd)? {
+            WasiFile::HostFile(file) => Ok(file),
+            WasiFile::HostDirectory { .. } => Err(Errno::Isdir),
+            // stdio and in-memory files are like pipes
+            _ => Err(Errno::Spipe),
+        }
+    }
+
+    fn sandbox_root(&self, fd: usize) -> Result<&Path, Errno> {
+        match self.files.get(fd) {
+            Some(WasiFile::HostDirectory { sandbox_root, .. }) => Ok(sandbox_root),
+            Some(WasiFile::Closed) | None => Err(Errno::Badf),
+            Some(_) => Err(Errno::Notdir),
+        }
+    }
+
+    /// Convert a path the app gave us, relative to the directory `fd`, to a path on the host.
+    /// Fails with `Notcapable` if the path leads outside of the sandbox the directory belongs to.
+    ///
+    /// Unless `follow_symlinks` is set, the last component may be a symlink, and the caller must
+    /// take care not to follow it.
+    fn resolve_path(
+        &self,
+        fd: usize,
+        memory: &[u8],
+        ptr_path: usize,
+        path_len: usize,
+        follow_symlinks: bool,
+    ) -> Result<PathBuf, Errno> {
+        let (dir_path, sandbox_root) = match self.files.get(fd) {
+            Some(WasiFile::HostDirectory {
+                host_path,
+                sandbox_root,
+                ..
+            }) => (host_path, sandbox_root),
+            Some(WasiFile::Closed) | None => return Err(Errno::Badf),
+            Some(_) => return Err(Errno::Notdir),
+        };
+
+        let mut resolved = dir_path.clone();
+        for component in Path::new(read_str(memory, ptr_path, path_len)?).components() {
+            match component {
+                Component::Normal(name) => resolved.push(name),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if resolved == *sandbox_root {
+                        return Err(Errno::Notcapable);
+                    }
+                    resolved.pop();
+                }
+                Component::RootDir | Component::Prefix(_) => return Err(Errno::Notcapable),
+            }
+        }
+
+        // Lexically, we're still in the sandbox, but a symlink along the way could lead out of it.
+        // The last component doesn't have to exist yet (e.g. when creating a file) so we only
+        // canonicalize its parent, and then follow the last component ourselves if asked to.
+        // That way a dangling symlink can't be used to create a file outside of the sandbox.
+        let mut real_path = if resolved == *sandbox_root {
+            resolved
+        } else {
+            canonicalize_parent(&resolved)?
+        };
+
+        if follow_symlinks {
+            real_path = follow_symlink(real_path)?;
+        }
+
+        if real_path.starts_with(sandbox_root) {
+            Ok(real_path)
+        } else {
+            Err(Errno::Notcapable)
+        }
+    }
+
+    fn next_free_fd(&mut self, file: WasiFile) -> usize {
+        match self
+            .files
+            .iter()
+            .position(|f| matches!(f, WasiFile::Closed))
+        {
+            Some(fd) => {
+                self.files[fd] = file;
+                fd
+            }
+            None => {
+                self.files.push(file);
+                self.files.len() - 1
+            }
+        }
+    }
+
+    fn path_open(&mut self, arguments: &[Value], memory: &mut [u8]) -> Result<(), Errno> {
+        // The directory the path is relative to
+        let fd = arguments[0].expect_i32().unwrap() as usize;
+        let lookup_flags = arguments[1].expect_i32().unwrap();
+        let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+        let path_len = arguments[3].expect_i32().unwrap() as usize;
+        let open_flags = arguments[4].expect_i32().unwrap();
+        let rights_base = arguments[5].expect_i64().unwrap();
+        let _rights_inheriting = arguments[6].expect_i64().unwrap();
+        let fd_flags = arguments[7].expect_i32().unwrap() as u16;
+        // Out param: the new file descriptor
+        let ptr_fd = arguments[8].expect_i32().unwrap() as usize;
+
+        let follow_symlinks = lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+        let path = self.resolve_path(fd, memory, ptr_path, path_len, follow_symlinks)?;
+
+        // Like O_NOFOLLOW. Otherwise opening the link would follow it to wherever it leads,
+        // without the target ever being checked against the sandbox.
+        if !follow_symlinks && matches!(fs::symlink_metadata(&path), Ok(m) if m.is_symlink()) {
+            return Err(Errno::Loop);
+        }
+
+        let new_file = if open_flags & OFLAGS_DIRECTORY != 0 || path.is_dir() {
+            if !path.is_dir() {
+                return Err(if path.exists() {
+                    Errno::Notdir
+                } else {
+                    Errno::Noent
+                });
+            }
+            if open_flags & (OFLAGS_CREAT | OFLAGS_TRUNC) != 0 {
+                return Err(Errno::Isdir);
+            }
+
+            let sandbox_root = match &self.files[fd] {
+                WasiFile::HostDirectory { sandbox_root, .. } => sandbox_root.clone(),
+                _ => unreachable!("resolve_path only succeeds for directories"),
+            };
+
+            WasiFile::HostDirectory {
+                preopen_name: None,
+                host_path: path.canonicalize()?,
+                sandbox_root,
+            }
+        } else {
+            let write_rights = RIGHTS_FD_WRITE | RIGHTS_FD_ALLOCATE | RIGHTS_FD_FILESTAT_SET_SIZE;
+            let write = rights_base & write_rights != 0;
+            let append = fd_flags & FDFLAGS_APPEND != 0;
+            // Opening with no rights at all is allowed, e.g. just to call fd_filestat_get
+            let read = rights_base & RIGHTS_FD_READ != 0 || !(write || append);
+
+            let mut options = OpenOptions::new();
+
+            // The check above can race with the app replacing the file with a symlink
+            #[cfg(unix)]
+            if !follow_symlinks {
+                std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_NOFOLLOW);
+            }
+
+            let file = options
+                .read(read)
+                .write(write)
+                .append(append)
+                .create(open_flags & OFLAGS_CREAT != 0)
+                .create_new(open_flags & OFLAGS_CREAT != 0 && open_flags & OFLAGS_EXCL != 0)
+                .truncate(open_flags & OFLAGS_TRUNC != 0)
+                .open(path)?;
+
+            WasiFile::HostFile(file)
+        };
+
+        let new_fd = self.next_free_fd(new_file);
+        write_u32(memory, ptr_fd, new_fd as u32);
+
+        Ok(())
+    }
+
+    fn fd_fdstat_get(&mut self, arguments: &[Value], memory: &mut [u8]) -> Result<(), Errno> {
+        let fd = arguments[0].expect_i32().unwrap() as usize;
+        // Out param: the fdstat struct
+        let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
+
+        let (filetype, rights) = match self.open_file(fd)? {
+            WasiFile::HostFile(file) => {
+                let filetype = filetype(&file.metadata()?);
+                (filetype, RIGHTS_ALL)
+            }
+            WasiFile::HostDirectory { .. } => (FILETYPE_DIRECTORY, RIGHTS_ALL),
+            WasiFile::HostSystemFile => (
+                FILETYPE_CHARACTER_DEVICE,
+                RIGHTS_ALL & !(RIGHTS_FD_SEEK | RIGHTS_FD_TELL),
+            ),
+            _ => (
+                FILETYPE_UNKNOWN,
+                RIGHTS_ALL & !(RIGHTS_FD_SEEK | RIGHTS_FD_TELL),
+            ),
+        };
+
+        // struct fdstat {
+        //     filetype fs_filetype;         // offset 0
+        //     fdflags fs_flags;             // offset 2
+        //     rights fs_rights_base;        // offset 8
+        //     rights fs_rights_inheriting;  // offset 16
+        // };
+        memory[ptr_buf..][..24].fill(0);
+        memory[ptr_buf] = filetype;
+        write_u64(memory, ptr_buf + 8, rights);
+        write_u64(memory, ptr_buf + 16, RIGHTS_ALL);
+
+        Ok(())
+    }
+
+    fn fd_filestat_get(&mut self, arguments: &[Value], memory: &mut [u8]) -> Result<(), Errno> {
+        let fd = arguments[0].expect_i32().unwrap() as usize;
+        // Out param: the filestat struct
+        let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
+
+        match self.open_file(fd)? {
+            WasiFile::HostFile(file) => write_filestat(memory, ptr_buf, &file.metadata()?),
+            WasiFile::HostDirectory { host_path, .. } => {
+                write_filestat(memory, ptr_buf, &fs::metadata(host_path)?)
+            }
+            WasiFile::HostSystemFile => {
+                memory[ptr_buf..][..FILESTAT_SIZE].fill(0);
+                memory[ptr_buf + 16] = FILETYPE_CHARACTER_DEVICE;
+            }
+            WasiFile::ReadOnly(content)
+            | WasiFile::WriteOnly(content)
+            | WasiFile::ReadWrite(content) => {
+                memory[ptr_buf..][..FILESTAT_SIZE].fill(0);
+                memory[ptr_buf + 16] = FILETYPE_UNKNOWN;
+                write_u64(memory, ptr_buf + 32, content.len() as u64);
+            }
+            WasiFile::Closed => unreachable!("open_file never returns closed files"),
+        }
+
+        Ok(())
+    }
+
+    fn fd_pread_pwrite(
+        &mut self,
+        arguments: &[Value],
+        memory: &mut [u8],
+        is_write: bool,
+    ) -> Result<(), Errno> {
+        let fd = arguments[0].expect_i32().unwrap() as usize;
+        // Array of IO vectors
+        let ptr_iovs = arguments[1].expect_i32().unwrap() as usize;
+        // Length of array
+        let iovs_len = arguments[2].expect_i32().unwrap() as usize;
+        // Offset in the file to read or write at
+        let offset = arguments[3].expect_i64().unwrap() as u64;
+        // Out param: number of bytes read or written
+        let ptr_nbytes = arguments[4].expect_i32().unwrap() as usize;
+
+        let file = self.seekable_file(fd)?;
+
+        // Positioned reads and writes leave the file's own offset unchanged
+        let original_offset = file.stream_position()?;
+        file.seek(SeekFrom::Start(offset))?;
+
+        let mut n_bytes = 0;
+        let mut result = Ok(());
+        for i in 0..iovs_len {
+            let iov_base = read_u32(memory, ptr_iovs + 8 * i) as usize;
+            let iov_len = read_u32(memory, ptr_iovs + 8 * i + 4) as usize;
+            let buf = &mut memory[iov_base..][..iov_len];
+
+            match if is_write {
+                file.write(buf)
+            } else {
+                file.read(buf)
+            } {
+                Ok(n) => {
+                    n_bytes += n;
+                    if n < iov_len {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    result = Err(Errno::from(e));
+                    break;
+                }
+            }
+        }
+
+        file.seek(SeekFrom::Start(original_offset))?;
+        write_u32(memory, ptr_nbytes, n_bytes as u32);
+
+        result
+    }
+
+    fn fd_readdir(&mut self, arguments: &[Value], memory: &mut [u8]) -> Result<(), Errno> {
+        let fd = arguments[0].expect_i32().unwrap() as usize;
+        // Buffer to write directory entries into
+        let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
+        let buf_len = arguments[2].expect_i32().unwrap() as usize;
+        // Where to start reading, as returned in `d_next` of the last entry we read
+        let cookie = arguments[3].expect_i64().unwrap() as usize;
+        // Out param: number of bytes written to the buffer
+        let ptr_bufused = arguments[4].expect_i32().unwrap() as usize;
+
+        let dir_path = match self.open_file(fd)? {
+            WasiFile::HostDirectory { host_path, .. } => host_path.clone(),
+            _ => return Err(Errno::Notdir),
+        };
+
+        let mut entries = vec![
+            (".".to_string(), fs::metadata(&dir_path)?),
+            ("..".to_string(), fs::metadata(&dir_path)?),
+        ];
+        let mut children = Vec::new();
+        for entry in fs::read_dir(&dir_path)? {
+            let entry = entry?;
+            let name = entry.file_name().into_string().map_err(|_| Errno::Ilseq)?;
+            children.push((name, fs::symlink_metadata(entry.path())?));
+        }
+        // Keep the order stable, so that cookies stay valid between calls
+        children.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.extend(children);
+
+        // struct dirent {
+        //     dircookie d_next;    // offset 0
+        //     inode d_ino;         // offset 8
+        //     dirnamlen d_namlen;  // offset 16
+        //     filetype d_type;     // offset 20
+        // };
+        // followed by the name, which is not zero-terminated.
+        // If the buffer fills up, the last entry is truncated, and the app will ask again.
+        let mut buf_used = 0;
+        for (index, (name, metadata)) in entries.iter().enumerate().skip(cookie) {
+            let mut dirent = [0; DIRENT_SIZE];
+            dirent[0..8].copy_from_slice(&(index as u64 + 1).to_le_bytes());
+            dirent[8..16].copy_from_slice(&inode(metadata).to_le_bytes());
+            dirent[16..20].copy_from_slice(&(name.len() as u32).to_le_bytes());
+            dirent[20] = filetype(metadata);
+
+            for bytes in [&dirent[..], name.as_bytes()] {
+                let len = bytes.len().min(buf_len - buf_used);
+                memory[ptr_buf + buf_used..][..len].copy_from_slice(&bytes[..len]);
+                buf_used += len;
+            }
+
+            if buf_used == buf_len {
+                break;
+            }
+        }
+
+        write_u32(memory, ptr_bufused, buf_used as u32);
+
+        Ok(())
+    }
+
+    fn poll_oneoff(&mut self, arguments: &[Value], memory: &mut [u8]) -> Result<(), Errno> {
+        // Array of subscriptions
+        let ptr_in = arguments[0].expect_i32().unwrap() as usize;
+        // Array of events, one for each subscription that fired
+        let ptr_out = arguments[1].expect_i32().unwrap() as usize;
+        let n_subscriptions = arguments[2].expect_i32().unwrap() as usize;
+        // Out param: number of events written
+        let ptr_nevents = arguments[3].expect_i32().unwrap() as usize;
+
+        if n_subscriptions == 0 {
+            return Err(Errno::Inval);
+        }
+
+        // struct subscription {
+        //     userdata userdata;  // offset 0
+        //     eventtype tag;      // offset 8
+        //     union {
+        //         struct { clockid id; timestamp timeout; timestamp precision; subclockflags flags; } clock;  // offset 16
+        //         struct { fd file_descriptor; } fd_readwrite;  // offset 16
+        //     } u;
+        // };
+        //
+        // We can't wait on file descriptors, so we treat them as always ready.
+        // If the app is only waiting on clocks, we sleep until the first one goes off.
+        let mut n_events = 0;
+        let mut first_clock: Option<(u64, u64)> = None;
+        for i in 0..n_subscriptions {
+            let ptr_subscription = ptr_in + SUBSCRIPTION_SIZE * i;
+            let userdata = read_u64(memory, ptr_subscription);
+
+            match memory[ptr_subscription + 8] {
+                EVENTTYPE_CLOCK => {
+                    let clock_id = read_u32(memory, ptr_subscription + 16) as i32;
+                    let timeout = read_u64(memory, ptr_subscription + 24);
+                    let flags = read_u16(memory, ptr_subscription + 40);
+
+                    let relative_timeout = if flags & SUBCLOCKFLAGS_ABSTIME != 0 {
+                        timeout.saturating_sub(self.clock_now(clock_id)?)
+                    } else {
+                        timeout
+                    };
+
+                    match first_clock {
+                        Some((earliest, _)) if earliest <= relative_timeout => {}
+                        _ => first_clock = Some((relative_timeout, userdata)),
+                    }
+                }
+                event_type @ (EVENTTYPE_FD_READ | EVENTTYPE_FD_WRITE) => {
+                    let fd = read_u32(memory, ptr_subscription + 16) as usize;
+                    let error = match self.open_file(fd) {
+                        Ok(_) => Errno::Success,
+                        Err(e) => e,
+                    };
+                    write_event(
+                        memory,
+                        ptr_out + EVENT_SIZE * n_events,
+                        userdata,
+                        error,
+                        event_type,
+                    );
+                    n_events += 1;
+                }
+                _ => return Err(Errno::Inval),
+            }
+        }
+
+        if let (0, Some((timeout, userdata))) = (n_events, first_clock) {
+            std::thread::sleep(Duration::from_nanos(timeout));
+            write_event(memory, ptr_out, userdata, Errno::Success, EVENTTYPE_CLOCK);
+            n_events = 1;
+        }
+
+        write_u32(memory, ptr_nevents, n_events as u32);
+
+        Ok(())
+    }
+}
+
+fn errno_result(result: Result<(), Errno>) -> Option<Value> {
+    let errno = match result {
+        Ok(()) => Errno::Success,
+        Err(errno) => errno,
+    };
+    Some(Value::I32(errno as i32))
+}
+
+/// Write a list of zero-terminated strings to `ptr_buf`, and an array of pointers to them at `ptr_ptrs`
+fn write_c_strings(memory: &mut [u8], strings: &[&[u8]], mut ptr_ptrs: usize, mut ptr_buf: usize) {
+    for s in strings {
+        write_u32(memory, ptr_ptrs, ptr_buf as u32);
+        let bytes_target = &mut memory[ptr_buf..][..s.len()];
+        bytes_target.copy_from_slice(s);
+        memory[ptr_buf + s.len()] = 0; // C string zero termination
+        ptr_buf += s.len() + 1;
+        ptr_ptrs += 4;
+    }
+}
+
+/// Write the number of strings, and the buffer size needed for `write_c_strings`
+fn write_c_strings_sizes(
+    memory: &mut [u8],
+    strings: &[&[u8]],
+    ptr_count: usize,
+    ptr_buf_size: usize,
+) {
+    write_u32(memory, ptr_count, strings.len() as u32);
+
+    let buf_size: u32 = strings.iter().map(|s| 1 + s.len() as u32).sum();
+    write_u32(memory, ptr_buf_size, buf_size);
+}
+
+fn write_event(memory: &mut [u8], addr: usize, userdata: u64, error: Errno, event_type: u8) {
+    // struct event {
+    //     userdata userdata;  // offset 0
+    //     errno error;        // offset 8
+    //     eventtype type;     // offset 10
+    //     struct { filesize nbytes; eventrwflags flags; } fd_readwrite;  // offset 16
+    // };
+    memory[addr..][..EVENT_SIZE].fill(0);
+    write_u64(memory, addr, userdata);
+    memory[addr + 8] = error as u8;
+    memory[addr + 10] = event_type;
+}
+
+fn write_filestat(memory: &mut [u8], addr: usize, metadata: &Metadata) {
+    // struct filestat {
+    //     device dev;         // offset 0
+    //     inode ino;          // offset 8
+    //     filetype filetype;  // offset 16
+    //     linkcount nlink;    // offset 24
+    //     filesize size;      // offset 32
+    //     timestamp atim;     // offset 40
+    //     timestamp mtim;     // offset 48
+    //     timestamp ctim;     // offset 56
+    // };
+    let nanos = |time: io::Result<SystemTime>| {
+        time.ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos() as u64)
+    };
+
+    memory[addr..][..FILESTAT_SIZE].fill(0);
+    write_u64(memory, addr, device(metadata));
+    write_u64(memory, addr + 8, inode(metadata));
+    memory[addr + 16] = filetype(metadata);
+    write_u64(memory, addr + 24, link_count(metadata));
+    write_u64(memory, addr + 32, metadata.len());
+    write_u64(memory, addr + 40, nanos(metadata.accessed()));
+    write_u64(memory, addr + 48, nanos(metadata.modified()));
+    write_u64(
+        memory,
+        addr + 56,
+        nanos(metadata.created().or(metadata.modified())),
+    );
+}
+
+fn filetype(metadata: &Metadata) -> u8 {
+    let file_type = metadata.file_type();
+    if file_type.is_dir() {
+        FILETYPE_DIRECTORY
+    } else if file_type.is_file() {
+        FILETYPE_REGULAR_FILE
+    } else if file_type.is_symlink() {
+        FILETYPE_SYMBOLIC_LINK
+    } else {
+        FILETYPE_UNKNOWN
+    }
+}
+
+#[cfg(unix)]
+fn device(metadata: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::dev(metadata)
+}
+
+#[cfg(unix)]
+fn inode(metadata: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::ino(metadata)
+}
+
+#[cfg(unix)]
+fn link_count(metadata: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::nlink(metadata)
+}
+
+#[cfg(not(unix))]
+fn device(_metadata: &Metadata) -> u64 {
+    0
+}
+
+#[cfg(not(unix))]
+fn inode(_metadata: &Metadata) -> u64 {
+    0
+}
+
+#[cfg(not(unix))]
+fn link_count(_metadata: &Metadata) -> u64 {
+    1
+}
+
+/// Canonicalizes all but the last component of `path`, which doesn't have to exist.
+fn canonicalize_parent(path: &Path) -> Result<PathBuf, Errno> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => Ok(parent.canonicalize()?.join(name)),
+        _ => Ok(path.canonicalize()?),
+    }
+}
+
+/// Follows `path` for as long as it is a symlink, given that its parent is canonical.
+/// Unlike [Path::canonicalize], this works for dangling symlinks too, returning where the
+/// file would be created.
+fn follow_symlink(mut path: PathBuf) -> Result<PathBuf, Errno> {
+    for _ in 0..MAX_SYMLINKS {
+        let target = match fs::read_link(&path) {
+            Ok(target) => target,
+            // Not a symlink, or doesn't exist
+            Err(_) => return Ok(path),
+        };
+
+        let joined = match path.parent() {
+            Some(parent) => parent.join(target),
+            None => target,
+        };
+
+        path = match joined.canonicalize() {
+            Ok(canonical) => return Ok(canonical),
+            Err(_) => canonicalize_parent(&joined)?,
+        };
+    }
+
+    Err(Errno::Loop)
+}
+
+#[cfg(unix)]
+fn symlink(target: &str, link: &Path) -> Result<(), Errno> {
+    Ok(std::os::unix::fs::symlink(target, link)?)
+}
+
+#[cfg(not(unix))]
+fn symlink(_target: &str, _link: &Path) -> Result<(), Errno> {
+    // Windows needs to know whether the target is a file or a directory, which it may not be yet
+    Err(Errno::Notsup)
+}
+
+fn read_str(memory: &[u8], addr: usize, len: usize) -> Result<&str, Errno> {
+    std::str::from_utf8(&memory[addr..][..len]).map_err(|_| Errno::Ilseq)
+}
+
+fn read_u16(memory: &[u8], addr: usize) -> u16 {
+    let mut bytes = [0; 2];
+    bytes.copy_from_slice(&memory[addr..][..2]);
+    u16::from_le_bytes(bytes)
 }
 
 fn read_u32(memory: &[u8], addr: usize) -> u32 {
@@ -300,6 +1314,12 @@ fn read_i32(memory: &[u8], addr: usize) -> i32 {
     i32::from_le_bytes(bytes)
 }
 
+fn read_u64(memory: &[u8], addr: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&memory[addr..][..8]);
+    u64::from_le_bytes(bytes)
+}
+
 fn write_u32(memory: &mut [u8], addr: usize, value: u32) {
     memory[addr..][..4].copy_from_slice(&value.to_le_bytes());
 }
@@ -308,12 +1328,34 @@ fn write_i32(memory: &mut [u8], addr: usize, value: i32) {
     memory[addr..][..4].copy_from_slice(&value.to_le_bytes());
 }
 
+fn write_u64(memory: &mut [u8], addr: usize, value: u64) {
+    memory[addr..][..8].copy_from_slice(&value.to_le_bytes());
+}
+
+impl From<io::Error> for Errno {
+    fn from(error: io::Error) -> Self {
+        use io::ErrorKind::*;
+        match error.kind() {
+            NotFound => Errno::Noent,
+            PermissionDenied => Errno::Access,
+            AlreadyExists => Errno::Exist,
+            InvalidInput => Errno::Inval,
+            InvalidData => Errno::Ilseq,
+            Interrupted => Errno::Intr,
+            WouldBlock => Errno::Again,
+            BrokenPipe => Errno::Pipe,
+            Unsupported => Errno::Notsup,
+            _ => Errno::Io,
+        }
+    }
+}
+
 /// Error codes returned by functions.
 /// Not all of these error codes are returned by the functions provided by this
 /// API; some are used in higher-level library layers, and others are provided
 /// merely for alignment with POSIX.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Errno {
     /// No error occurred. System call completed successfully.
     Success,
@@ -301,12 +301,11 @@ pub struct ComdatSym {
 /// A COMDAT group may contain one or more functions, data segments, and/or custom sections.
 /// The linker will include all of these elements with a given group name from one object file,
 /// and will exclude any element with this group name from all other object files.
-#[allow(dead_code)]
 #[derive(Debug)]
 pub struct LinkingComdat<'a> {
-    name: &'a str,
-    flags: u32,
-    syms: Vec<'a, ComdatSym>,
+    pub name: &'a str,
+    pub flags: u32,
+    pub syms: Vec<'a, ComdatSym>,
 }
 
 //------------------------------------------------
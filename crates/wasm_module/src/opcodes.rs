@@ -195,7 +195,7 @@ impl From<u8> for OpCode {
 /// Immediates appear directly in the byte stream after the opcode,
 /// rather than being popped off the value stack. These are the possible forms.
 #[derive(Debug)]
-enum OpImmediates {
+pub(crate) enum OpImmediates {
     NoImmediate,
     Byte1,
     Bytes4,
@@ -206,7 +206,7 @@ enum OpImmediates {
     BrTable,
 }
 
-fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
+pub(crate) fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
     use OpCode::*;
     use OpImmediates::*;
 
@@ -298,7 +298,12 @@ impl<'a> TypeSection<'a> {
         self.bytes.is_empty()
     }
 
-    pub fn look_up(&'a self, sig_index: u32) -> (SignatureParamsIter<'a>, Option<ValueType>) {
+    /// Number of signatures in the section
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn look_up(&self, sig_index: u32) -> (SignatureParamsIter<'_>, Option<ValueType>) {
         let mut offset = self.offsets[sig_index as usize];
         offset += 1; // separator
         let param_count = u32::parse((), &self.bytes, &mut offset).unwrap() as usize;
@@ -715,6 +720,7 @@ impl Parse<()> for TableSection {
                         message: "Only funcref tables are supported".into(),
                     })
                 } else {
+                    *cursor += 1; // the reference type we just checked
                     let limits = Limits::parse((), module_bytes, cursor)?;
                     *cursor = range.end;
                     Ok(TableSection {
@@ -964,11 +970,13 @@ impl Parse<()> for ConstExpr {
             OpCode::F32CONST => {
                 let mut b = [0; 4];
                 b.copy_from_slice(&bytes[*cursor..][..4]);
+                *cursor += 4;
                 Ok(ConstExpr::F32(f32::from_le_bytes(b)))
             }
             OpCode::F64CONST => {
                 let mut b = [0; 8];
                 b.copy_from_slice(&bytes[*cursor..][..8]);
+                *cursor += 8;
                 Ok(ConstExpr::F64(f64::from_le_bytes(b)))
             }
             _ => Err(ParseError {
@@ -1593,6 +1601,22 @@ impl<'a> DataSection<'a> {
         }
         Ok(())
     }
+
+    /// Decode the segments from the serialized section body, e.g. for disassembly
+    pub fn segments<'b>(
+        &'b self,
+        arena: &'b Bump,
+    ) -> Result<Vec<'b, (DataMode, &'b [u8])>, ParseError> {
+        let mut cursor = 0;
+        let mut segments = Vec::with_capacity_in(self.count as usize, arena);
+        for _ in 0..self.count {
+            let mode = DataMode::parse((), &self.bytes, &mut cursor)?;
+            let len = u32::parse((), &self.bytes, &mut cursor)? as usize;
+            segments.push((mode, &self.bytes[cursor..][..len]));
+            cursor += len;
+        }
+        Ok(segments)
+    }
 }
 
 impl<'a> Parse<&'a Bump> for DataSection<'a> {
@@ -1920,4 +1944,27 @@ mod tests {
         }
         test_assert_types_preload(arena, &section);
     }
+
+    #[test]
+    fn test_const_expr_parse_floats() {
+        // Float constants have fixed-size immediates, which must be skipped before the END,
+        // or the expression after them gets misread
+        let exprs = [
+            ConstExpr::F32(1.5),
+            ConstExpr::F64(-0.25),
+            ConstExpr::I32(7),
+            ConstExpr::I64(-8),
+        ];
+        let mut bytes = std::vec::Vec::new();
+        for expr in exprs.iter() {
+            expr.serialize(&mut bytes);
+        }
+
+        let mut cursor = 0;
+        for expr in exprs.iter() {
+            let parsed = ConstExpr::parse((), &bytes, &mut cursor).unwrap();
+            assert_eq!(parsed.as_value(), expr.as_value());
+        }
+        assert_eq!(cursor, bytes.len());
+    }
 }
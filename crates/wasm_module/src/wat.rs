@@ -0,0 +1,1422 @@
+//! Disassembler from a `WasmModule` to the WebAssembly text format (WAT)
+//! https://webassembly.github.io/spec/core/text/index.html
+//!
+//! The output follows the conventions of `wasm2wat`, so that it's easy to compare the two.
+//! The "linking" and "reloc.*" custom sections have no text format, so we print them as comments,
+//! and annotate each instruction in the code section with the relocations that apply to it.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use bumpalo::Bump;
+
+use crate::linking::{DataSymbol, RelocationEntry, RelocationSection, SymInfo, WasmObjectSymbol};
+use crate::opcodes::{immediates_for, OpCode, OpImmediates};
+use crate::parse::{Parse, ParseError};
+use crate::sections::{ConstExpr, DataMode, ExportType, ImportDesc, Limits, RefType, TableType};
+use crate::{GlobalType, ValueType, WasmModule, DUMMY_FUNCTION};
+
+const INDENT: &str = "  ";
+
+impl<'a> WasmModule<'a> {
+    /// Disassemble the module into the WebAssembly text format.
+    /// The output shows the module as it would be serialized, including any dummy functions
+    /// inserted by dead code elimination.
+    pub fn to_wat(&self) -> Result<String, ParseError> {
+        let mut printer = WatPrinter::new(self);
+        printer.print_module()?;
+        Ok(printer.buf)
+    }
+}
+
+struct WatPrinter<'m, 'a> {
+    module: &'m WasmModule<'a>,
+    fn_names: HashMap<u32, &'a str>,
+    /// Relocations for the code section, sorted by offset
+    code_relocs: Vec<&'m RelocationEntry>,
+    buf: String,
+}
+
+impl<'m, 'a> WatPrinter<'m, 'a> {
+    fn new(module: &'m WasmModule<'a>) -> Self {
+        let fn_names = module.names.function_names.iter().copied().collect();
+
+        let mut code_relocs: Vec<&RelocationEntry> = module.reloc_code.entries.iter().collect();
+        code_relocs.sort_by_key(|entry| reloc_offset(entry));
+
+        WatPrinter {
+            module,
+            fn_names,
+            code_relocs,
+            buf: String::with_capacity(module.size() * 4),
+        }
+    }
+
+    fn print_module(&mut self) -> Result<(), ParseError> {
+        self.buf.push_str("(module\n");
+        self.print_types();
+        let import_counts = self.print_imports();
+        self.print_functions(import_counts.functions)?;
+        // Like `serialize`, leave out the table if nothing goes in it. Parsing a module without
+        // one gives us an empty placeholder, for the backend to add functions to.
+        let has_table = !self.module.element.is_empty();
+        if has_table {
+            self.print_table(import_counts.tables);
+        }
+        self.print_memories(import_counts.memories)?;
+        self.print_globals(import_counts.globals)?;
+        self.print_exports();
+        if has_table {
+            self.print_elements();
+        }
+        self.print_data()?;
+        self.print_linking();
+        let module = self.module;
+        self.print_relocs(&module.reloc_code);
+        self.print_relocs(&module.reloc_data);
+        self.buf.push_str(")\n");
+        Ok(())
+    }
+
+    fn print_types(&mut self) {
+        for sig_index in 0..self.module.types.len() {
+            let (params, ret_type) = self.module.types.look_up(sig_index as u32);
+            self.buf.push_str(INDENT);
+            write!(self.buf, "(type (;{};) (func", sig_index).unwrap();
+            write_params_and_result(&mut self.buf, params, ret_type);
+            self.buf.push_str("))\n");
+        }
+    }
+
+    fn print_imports(&mut self) -> ImportCounts {
+        let mut counts = ImportCounts::default();
+
+        for import in self.module.import.imports.iter() {
+            self.buf.push_str(INDENT);
+            self.buf.push_str("(import ");
+            write_string(&mut self.buf, import.module.as_bytes());
+            self.buf.push(' ');
+            write_string(&mut self.buf, import.name.as_bytes());
+            match &import.description {
+                ImportDesc::Func { signature_index } => {
+                    self.buf.push_str(" (func");
+                    self.write_fn_declaration_name(counts.functions);
+                    write!(self.buf, " (type {})", signature_index).unwrap();
+                    counts.functions += 1;
+                }
+                ImportDesc::Table { ty } => {
+                    write!(self.buf, " (table (;{};) ", counts.tables).unwrap();
+                    write_table_type(&mut self.buf, ty);
+                    counts.tables += 1;
+                }
+                ImportDesc::Mem { limits } => {
+                    write!(self.buf, " (memory (;{};) ", counts.memories).unwrap();
+                    write_limits(&mut self.buf, limits);
+                    counts.memories += 1;
+                }
+                ImportDesc::Global { ty } => {
+                    write!(self.buf, " (global (;{};) ", counts.globals).unwrap();
+                    write_global_type(&mut self.buf, ty);
+                    counts.globals += 1;
+                }
+            }
+            self.buf.push_str("))\n");
+        }
+
+        counts
+    }
+
+    fn print_functions(&mut self, import_fn_count: u32) -> Result<(), ParseError> {
+        let code = &self.module.code;
+        let dummy_count = code.dead_import_dummy_count as usize;
+
+        for (i, sig_index) in self.module.function.signatures.iter().enumerate() {
+            let fn_index = import_fn_count + i as u32;
+
+            self.buf.push_str(INDENT);
+            self.buf.push_str("(func");
+            self.write_fn_declaration_name(fn_index);
+            write!(self.buf, " (type {})", sig_index).unwrap();
+            let (params, ret_type) = self.module.types.look_up(*sig_index);
+            write_params_and_result(&mut self.buf, params, ret_type);
+            self.buf.push('\n');
+
+            if i < dummy_count {
+                // Dummy functions get their length prefix during serialization
+                self.print_function_body(&DUMMY_FUNCTION, 0, DUMMY_FUNCTION.len(), false)?;
+            } else {
+                let mut cursor = match code.function_offsets.get(i - dummy_count) {
+                    Some(offset) => *offset as usize,
+                    None => {
+                        return Err(ParseError {
+                            offset: code.bytes.len(),
+                            message: format!("No code found for function {}", fn_index),
+                        })
+                    }
+                };
+                let size = u32::parse((), &code.bytes, &mut cursor)? as usize;
+                self.print_function_body(&code.bytes, cursor, cursor + size, true)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_function_body(
+        &mut self,
+        bytes: &[u8],
+        mut cursor: usize,
+        end: usize,
+        is_code_section: bool,
+    ) -> Result<(), ParseError> {
+        let local_group_count = u32::parse((), bytes, &mut cursor)?;
+        if local_group_count > 0 {
+            self.buf.push_str(INDENT);
+            self.buf.push_str(INDENT);
+            self.buf.push_str("(local");
+            for _ in 0..local_group_count {
+                let (count, ty) = <(u32, ValueType)>::parse((), bytes, &mut cursor)?;
+                for _ in 0..count {
+                    self.buf.push(' ');
+                    self.buf.push_str(value_type_name(ty));
+                }
+            }
+            self.buf.push_str(")\n");
+        }
+
+        let mut depth: u32 = 0;
+        while cursor < end {
+            let op = OpCode::from(bytes[cursor]);
+            let indent_depth = match op {
+                // The last END closes the function itself. wasm2wat leaves it out, and so do we.
+                OpCode::END if depth == 0 => break,
+                OpCode::END => {
+                    depth -= 1;
+                    depth
+                }
+                OpCode::ELSE => depth.saturating_sub(1),
+                _ => depth,
+            };
+            for _ in 0..indent_depth + 2 {
+                self.buf.push_str(INDENT);
+            }
+
+            let start = cursor;
+            self.write_instruction(bytes, &mut cursor, depth)?;
+
+            // Relocation offsets are relative to the code section body, just like our byte offsets
+            if is_code_section {
+                self.write_code_relocs(start, cursor);
+            }
+            self.buf.push('\n');
+
+            if matches!(op, OpCode::BLOCK | OpCode::LOOP | OpCode::IF) {
+                depth += 1;
+            }
+        }
+
+        self.buf.push_str(INDENT);
+        self.buf.push_str(")\n");
+        Ok(())
+    }
+
+    /// Write one instruction in text format, advancing the cursor past its immediates.
+    /// `depth` is the number of enclosing blocks, used to show the targets of branches.
+    fn write_instruction(
+        &mut self,
+        bytes: &[u8],
+        cursor: &mut usize,
+        depth: u32,
+    ) -> Result<(), ParseError> {
+        use OpCode::*;
+
+        let start = *cursor;
+        let op = OpCode::from(bytes[start]);
+        let immediates = immediates_for(op).map_err(|message| ParseError {
+            message,
+            offset: start,
+        })?;
+        *cursor += 1;
+
+        self.buf.push_str(mnemonic(op));
+
+        match immediates {
+            OpImmediates::NoImmediate => {}
+            OpImmediates::Byte1 => {
+                let byte = bytes[*cursor];
+                *cursor += 1;
+                if matches!(op, BLOCK | LOOP | IF) {
+                    if byte != ValueType::VOID {
+                        write!(
+                            self.buf,
+                            " (result {})",
+                            value_type_name(ValueType::from(byte))
+                        )
+                        .unwrap();
+                    }
+                    write!(self.buf, "  ;; label = @{}", depth + 1).unwrap();
+                }
+                // memory.size and memory.grow have a reserved memory index, which is always 0
+            }
+            OpImmediates::Bytes4 => {
+                let mut b = [0; 4];
+                b.copy_from_slice(&bytes[*cursor..][..4]);
+                *cursor += 4;
+                self.buf.push(' ');
+                write_float(&mut self.buf, f32::from_le_bytes(b) as f64);
+            }
+            OpImmediates::Bytes8 => {
+                let mut b = [0; 8];
+                b.copy_from_slice(&bytes[*cursor..][..8]);
+                *cursor += 8;
+                self.buf.push(' ');
+                write_float(&mut self.buf, f64::from_le_bytes(b));
+            }
+            OpImmediates::Leb32x1 => match op {
+                I32CONST => {
+                    let value = i32::parse((), bytes, cursor)?;
+                    write!(self.buf, " {}", value).unwrap();
+                }
+                CALL => {
+                    let fn_index = u32::parse((), bytes, cursor)?;
+                    self.buf.push(' ');
+                    self.write_fn_ref(fn_index);
+                }
+                BR | BRIF => {
+                    let label = u32::parse((), bytes, cursor)?;
+                    write_label(&mut self.buf, label, depth);
+                }
+                _ => {
+                    let index = u32::parse((), bytes, cursor)?;
+                    write!(self.buf, " {}", index).unwrap();
+                }
+            },
+            OpImmediates::Leb64x1 => {
+                let value = i64::parse((), bytes, cursor)?;
+                write!(self.buf, " {}", value).unwrap();
+            }
+            OpImmediates::Leb32x2 => {
+                if op == CALLINDIRECT {
+                    let sig_index = u32::parse((), bytes, cursor)?;
+                    let table_index = u32::parse((), bytes, cursor)?;
+                    if table_index != 0 {
+                        write!(self.buf, " {}", table_index).unwrap();
+                    }
+                    write!(self.buf, " (type {})", sig_index).unwrap();
+                } else {
+                    let align_pow2 = u32::parse((), bytes, cursor)?;
+                    let offset = u32::parse((), bytes, cursor)?;
+                    if offset != 0 {
+                        write!(self.buf, " offset={}", offset).unwrap();
+                    }
+                    let align = 1u64 << align_pow2.min(63);
+                    if align != natural_alignment(op) {
+                        write!(self.buf, " align={}", align).unwrap();
+                    }
+                }
+            }
+            OpImmediates::BrTable => {
+                let label_count = 1 + u32::parse((), bytes, cursor)?;
+                for _ in 0..label_count {
+                    let label = u32::parse((), bytes, cursor)?;
+                    write_label(&mut self.buf, label, depth);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_code_relocs(&mut self, start: usize, end: usize) {
+        let first = self
+            .code_relocs
+            .partition_point(|entry| (reloc_offset(entry) as usize) < start);
+
+        let last = first
+            + self.code_relocs[first..]
+                .partition_point(|entry| (reloc_offset(entry) as usize) < end);
+
+        for i in first..last {
+            self.buf.push_str(if i == first { "  ;; " } else { ", " });
+            let entry: &RelocationEntry = self.code_relocs[i];
+            self.write_reloc_entry(entry);
+        }
+    }
+
+    fn print_table(&mut self, import_table_count: u32) {
+        // Our TableSection always serializes exactly one table
+        self.buf.push_str(INDENT);
+        write!(self.buf, "(table (;{};) ", import_table_count).unwrap();
+        write_table_type(&mut self.buf, &self.module.table.function_table);
+        self.buf.push_str(")\n");
+    }
+
+    fn print_memories(&mut self, import_memory_count: u32) -> Result<(), ParseError> {
+        let memory = &self.module.memory;
+        let mut cursor = 0;
+        for i in 0..memory.count {
+            let limits = Limits::parse((), &memory.bytes, &mut cursor)?;
+            self.buf.push_str(INDENT);
+            write!(self.buf, "(memory (;{};) ", import_memory_count + i).unwrap();
+            write_limits(&mut self.buf, &limits);
+            self.buf.push_str(")\n");
+        }
+        Ok(())
+    }
+
+    fn print_globals(&mut self, import_global_count: u32) -> Result<(), ParseError> {
+        let global = &self.module.global;
+        let mut cursor = 0;
+        for i in 0..global.count {
+            let ty = GlobalType::parse((), &global.bytes, &mut cursor)?;
+            let init = ConstExpr::parse((), &global.bytes, &mut cursor)?;
+            self.buf.push_str(INDENT);
+            write!(self.buf, "(global (;{};) ", import_global_count + i).unwrap();
+            write_global_type(&mut self.buf, &ty);
+            self.buf.push(' ');
+            write_const_expr(&mut self.buf, &init);
+            self.buf.push_str(")\n");
+        }
+        Ok(())
+    }
+
+    fn print_exports(&mut self) {
+        for export in self.module.export.exports.iter() {
+            self.buf.push_str(INDENT);
+            self.buf.push_str("(export ");
+            write_string(&mut self.buf, export.name.as_bytes());
+            match export.ty {
+                ExportType::Func => {
+                    self.buf.push_str(" (func ");
+                    self.write_fn_ref(export.index);
+                }
+                ExportType::Table => write!(self.buf, " (table {}", export.index).unwrap(),
+                ExportType::Mem => write!(self.buf, " (memory {}", export.index).unwrap(),
+                ExportType::Global => write!(self.buf, " (global {}", export.index).unwrap(),
+            }
+            self.buf.push_str("))\n");
+        }
+    }
+
+    fn print_elements(&mut self) {
+        for (i, segment) in self.module.element.segments.iter().enumerate() {
+            self.buf.push_str(INDENT);
+            write!(self.buf, "(elem (;{};) ", i).unwrap();
+            write_const_expr(&mut self.buf, &segment.offset);
+            self.buf.push_str(" func");
+            for fn_index in segment.fn_indices.iter() {
+                self.buf.push(' ');
+                self.write_fn_ref(*fn_index);
+            }
+            self.buf.push_str(")\n");
+        }
+    }
+
+    fn print_data(&mut self) -> Result<(), ParseError> {
+        let arena = Bump::new();
+        let segments = self.module.data.segments(&arena)?;
+        for (i, (mode, init)) in segments.iter().enumerate() {
+            self.buf.push_str(INDENT);
+            write!(self.buf, "(data (;{};) ", i).unwrap();
+            if let DataMode::Active { offset } = mode {
+                write_const_expr(&mut self.buf, offset);
+                self.buf.push(' ');
+            }
+            write_string(&mut self.buf, init);
+            self.buf.push_str(")\n");
+        }
+        Ok(())
+    }
+
+    fn print_linking(&mut self) {
+        let linking = &self.module.linking;
+        if linking.symbol_table.is_empty()
+            && linking.segment_info.is_empty()
+            && linking.init_funcs.is_empty()
+            && linking.comdat_info.is_empty()
+        {
+            return;
+        }
+
+        self.buf.push_str(INDENT);
+        self.buf.push_str(";; custom section \"linking\"\n");
+
+        for (i, sym) in linking.symbol_table.iter().enumerate() {
+            self.buf.push_str(INDENT);
+            write!(self.buf, ";;   symbol {}: ", i).unwrap();
+            match sym {
+                SymInfo::Function(s) => self.write_object_symbol("func", s),
+                SymInfo::Global(s) => self.write_object_symbol("global", s),
+                SymInfo::Event(s) => self.write_object_symbol("event", s),
+                SymInfo::Table(s) => self.write_object_symbol("table", s),
+                SymInfo::Data(DataSymbol::Defined {
+                    flags,
+                    name,
+                    segment_index,
+                    segment_offset,
+                    size,
+                }) => {
+                    write!(
+                        self.buf,
+                        "data {} segment={} offset={} size={} flags={:#x}",
+                        name, segment_index, segment_offset, size, flags
+                    )
+                    .unwrap();
+                }
+                SymInfo::Data(DataSymbol::Imported { flags, name }) => {
+                    write!(self.buf, "data {} (imported) flags={:#x}", name, flags).unwrap();
+                }
+                SymInfo::Section(_) => self.buf.push_str("section"),
+            }
+            self.buf.push('\n');
+        }
+
+        for (i, segment) in linking.segment_info.iter().enumerate() {
+            self.buf.push_str(INDENT);
+            writeln!(
+                self.buf,
+                ";;   segment {}: {} align={} flags={:#x}",
+                i,
+                segment.name,
+                1u64 << segment.align_bytes_pow2.min(63),
+                segment.flags
+            )
+            .unwrap();
+        }
+
+        for init_func in linking.init_funcs.iter() {
+            self.buf.push_str(INDENT);
+            writeln!(
+                self.buf,
+                ";;   init func: symbol={} priority={}",
+                init_func.symbol_index, init_func.priority
+            )
+            .unwrap();
+        }
+
+        for comdat in linking.comdat_info.iter() {
+            self.buf.push_str(INDENT);
+            write!(
+                self.buf,
+                ";;   comdat {} flags={:#x}:",
+                comdat.name, comdat.flags
+            )
+            .unwrap();
+            for sym in comdat.syms.iter() {
+                write!(self.buf, " {:?}[{}]", sym.kind, sym.index).unwrap();
+            }
+            self.buf.push('\n');
+        }
+    }
+
+    fn print_relocs(&mut self, section: &RelocationSection) {
+        if section.entries.is_empty() {
+            return;
+        }
+
+        self.buf.push_str(INDENT);
+        writeln!(
+            self.buf,
+            ";; custom section \"{}\" (target section index {})",
+            section.name, section.target_section_index
+        )
+        .unwrap();
+
+        for entry in section.entries.iter() {
+            self.buf.push_str(INDENT);
+            write!(self.buf, ";;   {:06x} ", reloc_offset(entry)).unwrap();
+            self.write_reloc_entry(entry);
+            self.buf.push('\n');
+        }
+    }
+
+    fn write_object_symbol(&mut self, kind: &str, sym: &WasmObjectSymbol) {
+        match sym {
+            WasmObjectSymbol::ExplicitlyNamed { flags, index, name } => {
+                write!(
+                    self.buf,
+                    "{} {} index={} flags={:#x}",
+                    kind, name, index, flags
+                )
+                .unwrap();
+            }
+            WasmObjectSymbol::ImplicitlyNamed { flags, index } => {
+                write!(self.buf, "{} index={} flags={:#x}", kind, index, flags).unwrap();
+            }
+        }
+    }
+
+    fn write_reloc_entry(&mut self, entry: &RelocationEntry) {
+        let symbol_index = match entry {
+            RelocationEntry::Index {
+                type_id,
+                symbol_index,
+                ..
+            } => {
+                write!(self.buf, "{:?}", type_id).unwrap();
+                *symbol_index
+            }
+            RelocationEntry::Offset {
+                type_id,
+                symbol_index,
+                addend,
+                ..
+            } => {
+                write!(self.buf, "{:?}", type_id).unwrap();
+                if *addend != 0 {
+                    write!(self.buf, " addend={}", addend).unwrap();
+                }
+                *symbol_index
+            }
+        };
+
+        write!(self.buf, " symbol={}", symbol_index).unwrap();
+        let name = self
+            .module
+            .linking
+            .symbol_table
+            .get(symbol_index as usize)
+            .and_then(|sym| match sym {
+                SymInfo::Function(WasmObjectSymbol::ImplicitlyNamed { index, .. }) => {
+                    self.fn_names.get(index).copied()
+                }
+                _ => sym.name(),
+            });
+        if let Some(name) = name {
+            write!(self.buf, " ({})", name).unwrap();
+        }
+    }
+
+    /// The name of a function in its declaration, followed by its index as a comment
+    fn write_fn_declaration_name(&mut self, fn_index: u32) {
+        if let Some(name) = self.fn_names.get(&fn_index) {
+            self.buf.push_str(" $");
+            write_identifier(&mut self.buf, name);
+        }
+        write!(self.buf, " (;{};)", fn_index).unwrap();
+    }
+
+    /// A reference to a function, by name if we have one
+    fn write_fn_ref(&mut self, fn_index: u32) {
+        match self.fn_names.get(&fn_index) {
+            Some(name) => {
+                self.buf.push('$');
+                write_identifier(&mut self.buf, name);
+            }
+            None => write!(self.buf, "{}", fn_index).unwrap(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ImportCounts {
+    functions: u32,
+    tables: u32,
+    memories: u32,
+    globals: u32,
+}
+
+fn reloc_offset(entry: &RelocationEntry) -> u32 {
+    match entry {
+        RelocationEntry::Index { offset, .. } | RelocationEntry::Offset { offset, .. } => *offset,
+    }
+}
+
+fn value_type_name(ty: ValueType) -> &'static str {
+    match ty {
+        ValueType::I32 => "i32",
+        ValueType::I64 => "i64",
+        ValueType::F32 => "f32",
+        ValueType::F64 => "f64",
+    }
+}
+
+fn write_params_and_result(
+    buf: &mut String,
+    params: impl ExactSizeIterator<Item = ValueType>,
+    ret_type: Option<ValueType>,
+) {
+    if params.len() > 0 {
+        buf.push_str(" (param");
+        for ty in params {
+            buf.push(' ');
+            buf.push_str(value_type_name(ty));
+        }
+        buf.push(')');
+    }
+    if let Some(ty) = ret_type {
+        write!(buf, " (result {})", value_type_name(ty)).unwrap();
+    }
+}
+
+fn write_limits(buf: &mut String, limits: &Limits) {
+    match limits {
+        Limits::Min(min) => write!(buf, "{}", min).unwrap(),
+        Limits::MinMax(min, max) => write!(buf, "{} {}", min, max).unwrap(),
+    }
+}
+
+fn write_table_type(buf: &mut String, ty: &TableType) {
+    write_limits(buf, &ty.limits);
+    buf.push_str(match ty.ref_type {
+        RefType::Func => " funcref",
+        RefType::Extern => " externref",
+    });
+}
+
+fn write_global_type(buf: &mut String, ty: &GlobalType) {
+    if ty.is_mutable {
+        write!(buf, "(mut {})", value_type_name(ty.value_type)).unwrap();
+    } else {
+        buf.push_str(value_type_name(ty.value_type));
+    }
+}
+
+fn write_const_expr(buf: &mut String, expr: &ConstExpr) {
+    match expr {
+        ConstExpr::I32(x) => write!(buf, "(i32.const {})", x).unwrap(),
+        ConstExpr::I64(x) => write!(buf, "(i64.const {})", x).unwrap(),
+        ConstExpr::F32(x) => {
+            buf.push_str("(f32.const ");
+            write_float(buf, *x as f64);
+            buf.push(')');
+        }
+        ConstExpr::F64(x) => {
+            buf.push_str("(f64.const ");
+            write_float(buf, *x);
+            buf.push(')');
+        }
+    }
+}
+
+fn write_float(buf: &mut String, x: f64) {
+    let sign = if x.is_sign_negative() { "-" } else { "" };
+    if x.is_nan() {
+        write!(buf, "{}nan", sign).unwrap();
+    } else if x.is_infinite() {
+        write!(buf, "{}inf", sign).unwrap();
+    } else {
+        write!(buf, "{:?}", x).unwrap();
+    }
+}
+
+/// Branch targets are relative, so also show which block they refer to, like wasm2wat
+fn write_label(buf: &mut String, label: u32, depth: u32) {
+    write!(buf, " {}", label).unwrap();
+    if let Some(target) = depth.checked_sub(label) {
+        write!(buf, " (;@{};)", target).unwrap();
+    }
+}
+
+/// A string literal, escaping anything that isn't printable ASCII
+fn write_string(buf: &mut String, bytes: &[u8]) {
+    buf.push('"');
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' => {
+                buf.push('\\');
+                buf.push(byte as char);
+            }
+            0x20..=0x7e => buf.push(byte as char),
+            _ => write!(buf, "\\{:02x}", byte).unwrap(),
+        }
+    }
+    buf.push('"');
+}
+
+/// Function names can contain characters that aren't allowed in WAT identifiers, like spaces.
+fn write_identifier(buf: &mut String, name: &str) {
+    for c in name.chars() {
+        let is_id_char = c.is_ascii_graphic()
+            && !matches!(c, '"' | ',' | ';' | '(' | ')' | '[' | ']' | '{' | '}');
+        buf.push(if is_id_char { c } else { '_' });
+    }
+}
+
+/// The alignment that a memory instruction uses when none is specified in the text format
+fn natural_alignment(op: OpCode) -> u64 {
+    use OpCode::*;
+    match op {
+        I32LOAD8S | I32LOAD8U | I64LOAD8S | I64LOAD8U | I32STORE8 | I64STORE8 => 1,
+        I32LOAD16S | I32LOAD16U | I64LOAD16S | I64LOAD16U | I32STORE16 | I64STORE16 => 2,
+        I32LOAD | F32LOAD | I64LOAD32S | I64LOAD32U | I32STORE | F32STORE | I64STORE32 => 4,
+        _ => 8,
+    }
+}
+
+fn mnemonic(op: OpCode) -> &'static str {
+    use OpCode::*;
+    match op {
+        UNREACHABLE => "unreachable",
+        NOP => "nop",
+        BLOCK => "block",
+        LOOP => "loop",
+        IF => "if",
+        ELSE => "else",
+        END => "end",
+        BR => "br",
+        BRIF => "br_if",
+        BRTABLE => "br_table",
+        RETURN => "return",
+        CALL => "call",
+        CALLINDIRECT => "call_indirect",
+        DROP => "drop",
+        SELECT => "select",
+        GETLOCAL => "local.get",
+        SETLOCAL => "local.set",
+        TEELOCAL => "local.tee",
+        GETGLOBAL => "global.get",
+        SETGLOBAL => "global.set",
+        I32LOAD => "i32.load",
+        I64LOAD => "i64.load",
+        F32LOAD => "f32.load",
+        F64LOAD => "f64.load",
+        I32LOAD8S => "i32.load8_s",
+        I32LOAD8U => "i32.load8_u",
+        I32LOAD16S => "i32.load16_s",
+        I32LOAD16U => "i32.load16_u",
+        I64LOAD8S => "i64.load8_s",
+        I64LOAD8U => "i64.load8_u",
+        I64LOAD16S => "i64.load16_s",
+        I64LOAD16U => "i64.load16_u",
+        I64LOAD32S => "i64.load32_s",
+        I64LOAD32U => "i64.load32_u",
+        I32STORE => "i32.store",
+        I64STORE => "i64.store",
+        F32STORE => "f32.store",
+        F64STORE => "f64.store",
+        I32STORE8 => "i32.store8",
+        I32STORE16 => "i32.store16",
+        I64STORE8 => "i64.store8",
+        I64STORE16 => "i64.store16",
+        I64STORE32 => "i64.store32",
+        CURRENTMEMORY => "memory.size",
+        GROWMEMORY => "memory.grow",
+        I32CONST => "i32.const",
+        I64CONST => "i64.const",
+        F32CONST => "f32.const",
+        F64CONST => "f64.const",
+        I32EQZ => "i32.eqz",
+        I32EQ => "i32.eq",
+        I32NE => "i32.ne",
+        I32LTS => "i32.lt_s",
+        I32LTU => "i32.lt_u",
+        I32GTS => "i32.gt_s",
+        I32GTU => "i32.gt_u",
+        I32LES => "i32.le_s",
+        I32LEU => "i32.le_u",
+        I32GES => "i32.ge_s",
+        I32GEU => "i32.ge_u",
+        I64EQZ => "i64.eqz",
+        I64EQ => "i64.eq",
+        I64NE => "i64.ne",
+        I64LTS => "i64.lt_s",
+        I64LTU => "i64.lt_u",
+        I64GTS => "i64.gt_s",
+        I64GTU => "i64.gt_u",
+        I64LES => "i64.le_s",
+        I64LEU => "i64.le_u",
+        I64GES => "i64.ge_s",
+        I64GEU => "i64.ge_u",
+        F32EQ => "f32.eq",
+        F32NE => "f32.ne",
+        F32LT => "f32.lt",
+        F32GT => "f32.gt",
+        F32LE => "f32.le",
+        F32GE => "f32.ge",
+        F64EQ => "f64.eq",
+        F64NE => "f64.ne",
+        F64LT => "f64.lt",
+        F64GT => "f64.gt",
+        F64LE => "f64.le",
+        F64GE => "f64.ge",
+        I32CLZ => "i32.clz",
+        I32CTZ => "i32.ctz",
+        I32POPCNT => "i32.popcnt",
+        I32ADD => "i32.add",
+        I32SUB => "i32.sub",
+        I32MUL => "i32.mul",
+        I32DIVS => "i32.div_s",
+        I32DIVU => "i32.div_u",
+        I32REMS => "i32.rem_s",
+        I32REMU => "i32.rem_u",
+        I32AND => "i32.and",
+        I32OR => "i32.or",
+        I32XOR => "i32.xor",
+        I32SHL => "i32.shl",
+        I32SHRS => "i32.shr_s",
+        I32SHRU => "i32.shr_u",
+        I32ROTL => "i32.rotl",
+        I32ROTR => "i32.rotr",
+        I64CLZ => "i64.clz",
+        I64CTZ => "i64.ctz",
+        I64POPCNT => "i64.popcnt",
+        I64ADD => "i64.add",
+        I64SUB => "i64.sub",
+        I64MUL => "i64.mul",
+        I64DIVS => "i64.div_s",
+        I64DIVU => "i64.div_u",
+        I64REMS => "i64.rem_s",
+        I64REMU => "i64.rem_u",
+        I64AND => "i64.and",
+        I64OR => "i64.or",
+        I64XOR => "i64.xor",
+        I64SHL => "i64.shl",
+        I64SHRS => "i64.shr_s",
+        I64SHRU => "i64.shr_u",
+        I64ROTL => "i64.rotl",
+        I64ROTR => "i64.rotr",
+        F32ABS => "f32.abs",
+        F32NEG => "f32.neg",
+        F32CEIL => "f32.ceil",
+        F32FLOOR => "f32.floor",
+        F32TRUNC => "f32.trunc",
+        F32NEAREST => "f32.nearest",
+        F32SQRT => "f32.sqrt",
+        F32ADD => "f32.add",
+        F32SUB => "f32.sub",
+        F32MUL => "f32.mul",
+        F32DIV => "f32.div",
+        F32MIN => "f32.min",
+        F32MAX => "f32.max",
+        F32COPYSIGN => "f32.copysign",
+        F64ABS => "f64.abs",
+        F64NEG => "f64.neg",
+        F64CEIL => "f64.ceil",
+        F64FLOOR => "f64.floor",
+        F64TRUNC => "f64.trunc",
+        F64NEAREST => "f64.nearest",
+        F64SQRT => "f64.sqrt",
+        F64ADD => "f64.add",
+        F64SUB => "f64.sub",
+        F64MUL => "f64.mul",
+        F64DIV => "f64.div",
+        F64MIN => "f64.min",
+        F64MAX => "f64.max",
+        F64COPYSIGN => "f64.copysign",
+        I32WRAPI64 => "i32.wrap_i64",
+        I32TRUNCSF32 => "i32.trunc_f32_s",
+        I32TRUNCUF32 => "i32.trunc_f32_u",
+        I32TRUNCSF64 => "i32.trunc_f64_s",
+        I32TRUNCUF64 => "i32.trunc_f64_u",
+        I64EXTENDSI32 => "i64.extend_i32_s",
+        I64EXTENDUI32 => "i64.extend_i32_u",
+        I64TRUNCSF32 => "i64.trunc_f32_s",
+        I64TRUNCUF32 => "i64.trunc_f32_u",
+        I64TRUNCSF64 => "i64.trunc_f64_s",
+        I64TRUNCUF64 => "i64.trunc_f64_u",
+        F32CONVERTSI32 => "f32.convert_i32_s",
+        F32CONVERTUI32 => "f32.convert_i32_u",
+        F32CONVERTSI64 => "f32.convert_i64_s",
+        F32CONVERTUI64 => "f32.convert_i64_u",
+        F32DEMOTEF64 => "f32.demote_f64",
+        F64CONVERTSI32 => "f64.convert_i32_s",
+        F64CONVERTUI32 => "f64.convert_i32_u",
+        F64CONVERTSI64 => "f64.convert_i64_s",
+        F64CONVERTUI64 => "f64.convert_i64_u",
+        F64PROMOTEF32 => "f64.promote_f32",
+        I32REINTERPRETF32 => "i32.reinterpret_f32",
+        I64REINTERPRETF64 => "i64.reinterpret_f64",
+        F32REINTERPRETI32 => "f32.reinterpret_i32",
+        F64REINTERPRETI64 => "f64.reinterpret_i64",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linking::{IndexRelocType, OffsetRelocType, WASM_SYM_UNDEFINED};
+    use crate::sections::{
+        update_section_size, write_custom_section_header, DataSegment, ElementSegment, Import,
+    };
+    use crate::{Export, Global, SerialBuffer, Serialize, Signature};
+
+    /// A module with one function of type `() -> ()`, which runs `instructions`
+    fn module_with_body<'a>(arena: &'a Bump, instructions: &[u8]) -> WasmModule<'a> {
+        let mut module = WasmModule::new(arena);
+        module.add_function_signature(Signature {
+            param_types: bumpalo::vec![in arena],
+            ret_type: None,
+        });
+        module.code.function_offsets.push(0);
+        module.code.function_count = 1;
+
+        let buf = &mut module.code.bytes;
+        buf.encode_u32(instructions.len() as u32 + 2);
+        buf.append_u8(0); // no locals
+        buf.append_slice(instructions);
+        buf.append_u8(OpCode::END as u8);
+
+        module
+    }
+
+    /// Serialize the module like the Wasm backend does, then parse it back and disassemble it.
+    /// `custom_sections` are inserted where the parser expects "linking" and "reloc.*",
+    /// just before the name section.
+    fn round_trip(module: &WasmModule, custom_sections: &[u8]) -> String {
+        let mut bytes = Vec::with_capacity(module.size() + custom_sections.len());
+        module.serialize(&mut bytes);
+
+        let mut names = Vec::new();
+        module.names.serialize(&mut names);
+        let names_start = bytes.len() - names.len();
+        bytes.splice(names_start..names_start, custom_sections.iter().copied());
+
+        let arena = Bump::new();
+        let parsed = WasmModule::preload(&arena, &bytes, false).unwrap();
+        parsed.to_wat().unwrap()
+    }
+
+    /// A subsection of the "linking" section, with `count` already encoded `items`
+    fn write_subsection(buf: &mut Vec<u8>, id: u8, count: u32, items: &[u8]) {
+        let mut body = Vec::new();
+        body.encode_u32(count);
+        body.append_slice(items);
+        buf.append_u8(id);
+        buf.encode_u32(body.len() as u32);
+        buf.append_slice(&body);
+    }
+
+    #[test]
+    fn test_to_wat() {
+        let arena = &Bump::new();
+        let mut module = WasmModule::new(arena);
+
+        module.types.insert(Signature {
+            param_types: bumpalo::vec![in arena; ValueType::I32],
+            ret_type: None,
+        });
+        module.types.insert(Signature {
+            param_types: bumpalo::vec![in arena],
+            ret_type: Some(ValueType::I32),
+        });
+
+        module.import.imports.push(Import {
+            module: "env",
+            name: "log",
+            description: ImportDesc::Func { signature_index: 0 },
+        });
+        module.names.append_function(0, "log");
+
+        // Count down from 3, logging each number
+        module.function.add_sig(1);
+        module.names.append_function(1, "count down");
+        module.code.function_offsets.push(0);
+        module.code.function_count = 1;
+        {
+            let buf = &mut module.code.bytes;
+            let fn_len_index = buf.encode_padded_u32(0);
+            buf.append_slice(&[1, 1, ValueType::I32 as u8]);
+            buf.append_u8(OpCode::I32CONST as u8);
+            buf.encode_i32(3);
+            buf.append_u8(OpCode::SETLOCAL as u8);
+            buf.encode_u32(0);
+            buf.append_slice(&[OpCode::LOOP as u8, ValueType::VOID]);
+            buf.append_u8(OpCode::GETLOCAL as u8);
+            buf.encode_u32(0);
+            buf.append_u8(OpCode::CALL as u8);
+            buf.encode_u32(0);
+            buf.append_u8(OpCode::GETLOCAL as u8);
+            buf.encode_u32(0);
+            buf.append_u8(OpCode::I32CONST as u8);
+            buf.encode_i32(-1);
+            buf.append_u8(OpCode::I32ADD as u8);
+            buf.append_u8(OpCode::TEELOCAL as u8);
+            buf.encode_u32(0);
+            buf.append_u8(OpCode::BRIF as u8);
+            buf.encode_u32(0);
+            buf.append_u8(OpCode::END as u8);
+            buf.append_u8(OpCode::I32CONST as u8);
+            buf.encode_i32(1024);
+            buf.append_u8(OpCode::I32LOAD8U as u8);
+            buf.encode_u32(0);
+            buf.encode_u32(4);
+            buf.append_u8(OpCode::END as u8);
+            let fn_len = buf.len() - fn_len_index - 5;
+            buf.overwrite_padded_u32(fn_len_index, fn_len as u32);
+        }
+
+        module.memory = crate::sections::MemorySection::new(arena, 1);
+        module.global.append(Global {
+            ty: GlobalType {
+                value_type: ValueType::F64,
+                is_mutable: true,
+            },
+            init: ConstExpr::F64(1.5),
+        });
+        module.export.append(Export {
+            name: "main",
+            ty: ExportType::Func,
+            index: 1,
+        });
+        module.element.segments.push(ElementSegment {
+            offset: ConstExpr::I32(1),
+            fn_indices: bumpalo::vec![in arena; 1],
+        });
+        module.data.append_segment(DataSegment {
+            mode: DataMode::active_at(1024),
+            init: bumpalo::vec![in arena; b'h', b'i', b'"', 0],
+        });
+
+        let expected = "\
+(module
+  (type (;0;) (func (param i32)))
+  (type (;1;) (func (result i32)))
+  (import \"env\" \"log\" (func $log (;0;) (type 0)))
+  (func $count_down (;1;) (type 1) (result i32)
+    (local i32)
+    i32.const 3
+    local.set 0
+    loop  ;; label = @1
+      local.get 0
+      call $log
+      local.get 0
+      i32.const -1
+      i32.add
+      local.tee 0
+      br_if 0 (;@1;)
+    end
+    i32.const 1024
+    i32.load8_u offset=4
+  )
+  (table (;0;) 0 funcref)
+  (memory (;0;) 1)
+  (global (;0;) (mut f64) (f64.const 1.5))
+  (export \"main\" (func $count_down))
+  (elem (;0;) (i32.const 1) func $count_down)
+  (data (;0;) (i32.const 1024) \"hi\\\"\\00\")
+)
+";
+        assert_eq!(module.to_wat().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_to_wat_relocations() {
+        use crate::linking::{IndexRelocType, WASM_SYM_UNDEFINED};
+
+        let arena = &Bump::new();
+        let mut module = WasmModule::new(arena);
+        module.types.insert(Signature {
+            param_types: bumpalo::vec![in arena],
+            ret_type: None,
+        });
+        module.import.imports.push(Import {
+            module: "env",
+            name: "host_fn",
+            description: ImportDesc::Func { signature_index: 0 },
+        });
+        module.function.add_sig(0);
+        module.code.function_offsets.push(0);
+        module.code.function_count = 1;
+
+        let call_offset = {
+            let buf = &mut module.code.bytes;
+            buf.append_slice(&[8, 0, OpCode::CALL as u8]);
+            let call_offset = buf.encode_padded_u32(0);
+            buf.append_u8(OpCode::END as u8);
+            call_offset
+        };
+
+        module
+            .linking
+            .symbol_table
+            .push(SymInfo::Function(WasmObjectSymbol::ImplicitlyNamed {
+                flags: WASM_SYM_UNDEFINED,
+                index: 0,
+            }));
+        module.reloc_code.entries.push(RelocationEntry::Index {
+            type_id: IndexRelocType::FunctionIndexLeb,
+            offset: call_offset as u32,
+            symbol_index: 0,
+        });
+        module.names.append_function(0, "host_fn");
+
+        let wat = module.to_wat().unwrap();
+        assert!(
+            wat.contains("    call $host_fn  ;; FunctionIndexLeb symbol=0 (host_fn)\n"),
+            "{}",
+            wat
+        );
+        assert!(
+            wat.contains("  ;;   symbol 0: func index=0 flags=0x10\n"),
+            "{}",
+            wat
+        );
+        assert!(
+            wat.contains("  ;; custom section \"reloc.CODE\" (target section index 0)\n"),
+            "{}",
+            wat
+        );
+        assert!(
+            wat.contains("  ;;   000003 FunctionIndexLeb symbol=0 (host_fn)\n"),
+            "{}",
+            wat
+        );
+    }
+
+    #[test]
+    fn test_round_trip_types_and_imports() {
+        let arena = &Bump::new();
+        let mut module = module_with_body(arena, &[]);
+
+        let log_sig = module.types.insert(Signature {
+            param_types: bumpalo::vec![in arena; ValueType::I32, ValueType::I64],
+            ret_type: Some(ValueType::F32),
+        });
+        module.import.imports.push(Import {
+            module: "env",
+            name: "log",
+            description: ImportDesc::Func {
+                signature_index: log_sig,
+            },
+        });
+        module.import.imports.push(Import {
+            module: "env",
+            name: "memory",
+            description: ImportDesc::Mem {
+                limits: Limits::MinMax(1, 2),
+            },
+        });
+        module.import.imports.push(Import {
+            module: "env",
+            name: "__stack_pointer",
+            description: ImportDesc::Global {
+                ty: GlobalType {
+                    value_type: ValueType::I32,
+                    is_mutable: true,
+                },
+            },
+        });
+        module.import.imports.push(Import {
+            module: "env",
+            name: "__indirect_function_table",
+            description: ImportDesc::Table {
+                ty: TableType {
+                    ref_type: RefType::Func,
+                    limits: Limits::Min(0),
+                },
+            },
+        });
+
+        let expected = "\
+(module
+  (type (;0;) (func))
+  (type (;1;) (func (param i32 i64) (result f32)))
+  (import \"env\" \"log\" (func (;0;) (type 1)))
+  (import \"env\" \"memory\" (memory (;0;) 1 2))
+  (import \"env\" \"__stack_pointer\" (global (;0;) (mut i32)))
+  (import \"env\" \"__indirect_function_table\" (table (;0;) 0 funcref))
+  (func (;1;) (type 0)
+  )
+)
+";
+        assert_eq!(round_trip(&module, &[]), expected);
+    }
+
+    #[test]
+    fn test_round_trip_globals() {
+        let arena = &Bump::new();
+        let mut module = module_with_body(arena, &[]);
+
+        // Floats come first, so that a misplaced cursor after them would garble what follows
+        let globals = [
+            (ValueType::F32, false, ConstExpr::F32(0.5)),
+            (ValueType::F64, true, ConstExpr::F64(-2.25)),
+            (ValueType::F32, false, ConstExpr::F32(f32::INFINITY)),
+            (ValueType::I32, false, ConstExpr::I32(-1)),
+            (ValueType::I64, true, ConstExpr::I64(1 << 40)),
+        ];
+        for (value_type, is_mutable, init) in globals {
+            module.global.append(Global {
+                ty: GlobalType {
+                    value_type,
+                    is_mutable,
+                },
+                init,
+            });
+        }
+
+        let expected = "\
+(module
+  (type (;0;) (func))
+  (func (;0;) (type 0)
+  )
+  (global (;0;) f32 (f32.const 0.5))
+  (global (;1;) (mut f64) (f64.const -2.25))
+  (global (;2;) f32 (f32.const inf))
+  (global (;3;) i32 (i32.const -1))
+  (global (;4;) (mut i64) (i64.const 1099511627776))
+)
+";
+        assert_eq!(round_trip(&module, &[]), expected);
+    }
+
+    #[test]
+    fn test_round_trip_data() {
+        let arena = &Bump::new();
+        let mut module = module_with_body(arena, &[]);
+        module.memory = crate::sections::MemorySection::new(arena, 1);
+
+        module.data.append_segment(DataSegment {
+            mode: DataMode::active_at(1024),
+            init: bumpalo::vec![in arena; b'h', b'i', b'"', b'\\', 0],
+        });
+        module.data.append_segment(DataSegment {
+            mode: DataMode::Passive,
+            init: bumpalo::vec![in arena; 0x01, 0xff],
+        });
+
+        let expected = "\
+(module
+  (type (;0;) (func))
+  (func (;0;) (type 0)
+  )
+  (memory (;0;) 1)
+  (data (;0;) (i32.const 1024) \"hi\\\"\\\\\\00\")
+  (data (;1;) \"\\01\\ff\")
+)
+";
+        assert_eq!(round_trip(&module, &[]), expected);
+    }
+
+    #[test]
+    fn test_round_trip_elements() {
+        let arena = &Bump::new();
+        let mut module = module_with_body(arena, &[]);
+        module.add_function_signature(Signature {
+            param_types: bumpalo::vec![in arena],
+            ret_type: None,
+        });
+        module
+            .code
+            .function_offsets
+            .push(module.code.bytes.len() as u32);
+        module.code.function_count = 2;
+        module.code.bytes.append_slice(&[2, 0, OpCode::END as u8]);
+
+        module.element.segments.push(ElementSegment {
+            offset: ConstExpr::I32(1),
+            fn_indices: bumpalo::vec![in arena; 1, 0],
+        });
+        module.names.append_function(1, "second");
+        // The Wasm backend sizes the table to fit the highest element index
+        module.table.function_table.limits = Limits::MinMax(3, 3);
+
+        let expected = "\
+(module
+  (type (;0;) (func))
+  (func (;0;) (type 0)
+  )
+  (func $second (;1;) (type 0)
+  )
+  (table (;0;) 3 3 funcref)
+  (elem (;0;) (i32.const 1) func $second 0)
+)
+";
+        assert_eq!(round_trip(&module, &[]), expected);
+    }
+
+    #[test]
+    fn test_round_trip_linking_and_relocations() {
+        let arena = &Bump::new();
+
+        // call $host_fn; i32.const <address of str>; drop
+        let mut instructions = Vec::new();
+        instructions.append_u8(OpCode::CALL as u8);
+        let call_offset = instructions.encode_padded_u32(0);
+        instructions.append_u8(OpCode::I32CONST as u8);
+        // Zero is encoded the same way in signed and unsigned LEB-128
+        let const_offset = instructions.encode_padded_u32(0);
+        instructions.append_u8(OpCode::DROP as u8);
+
+        let mut module = module_with_body(arena, &instructions);
+        module.import.imports.push(Import {
+            module: "env",
+            name: "host_fn",
+            description: ImportDesc::Func { signature_index: 0 },
+        });
+        module.names.append_function(0, "host_fn");
+        module.names.append_function(1, "main");
+
+        let mut custom_sections = Vec::new();
+
+        let header = write_custom_section_header(&mut custom_sections, "linking");
+        custom_sections.append_u8(2); // version
+        let mut symbols = Vec::new();
+        symbols.append_slice(&[0, WASM_SYM_UNDEFINED as u8, 0]); // func index=0
+        symbols.append_slice(&[1, 0]); // data, no flags
+        "str".serialize(&mut symbols);
+        symbols.append_slice(&[0, 0, 4]); // segment, offset, size
+        write_subsection(&mut custom_sections, 8, 2, &symbols);
+        let mut segments = Vec::new();
+        ".rodata.str".serialize(&mut segments);
+        segments.append_slice(&[2, 0]); // align=4, flags
+        write_subsection(&mut custom_sections, 5, 1, &segments);
+        update_section_size(&mut custom_sections, header);
+
+        // Offsets in the code section body also count its function count and the function's
+        // size and local declarations, one byte each
+        let body_offset = 3;
+        let header = write_custom_section_header(&mut custom_sections, "reloc.CODE");
+        custom_sections.encode_u32(3); // target section index
+        custom_sections.encode_u32(2);
+        custom_sections.append_u8(IndexRelocType::FunctionIndexLeb as u8);
+        custom_sections.encode_u32((body_offset + call_offset) as u32);
+        custom_sections.encode_u32(0);
+        custom_sections.append_u8(OffsetRelocType::MemoryAddrSleb as u8);
+        custom_sections.encode_u32((body_offset + const_offset) as u32);
+        custom_sections.encode_u32(1);
+        custom_sections.encode_i32(8);
+        update_section_size(&mut custom_sections, header);
+
+        let expected = "\
+(module
+  (type (;0;) (func))
+  (import \"env\" \"host_fn\" (func $host_fn (;0;) (type 0)))
+  (func $main (;1;) (type 0)
+    call $host_fn  ;; FunctionIndexLeb symbol=0 (host_fn)
+    i32.const 0  ;; MemoryAddrSleb addend=8 symbol=1 (str)
+    drop
+  )
+  ;; custom section \"linking\"
+  ;;   symbol 0: func index=0 flags=0x10
+  ;;   symbol 1: data str segment=0 offset=0 size=4 flags=0x0
+  ;;   segment 0: .rodata.str align=4 flags=0x0
+  ;; custom section \"reloc.CODE\" (target section index 3)
+  ;;   000004 FunctionIndexLeb symbol=0 (host_fn)
+  ;;   00000a MemoryAddrSleb addend=8 symbol=1 (str)
+)
+";
+        assert_eq!(round_trip(&module, &custom_sections), expected);
+    }
+
+    #[test]
+    fn test_round_trip_names() {
+        let arena = &Bump::new();
+        let mut module = module_with_body(arena, &[OpCode::CALL as u8, 0]);
+        module.import.imports.push(Import {
+            module: "env",
+            name: "roc_panic",
+            description: ImportDesc::Func { signature_index: 0 },
+        });
+        module.names.append_function(0, "roc_panic");
+        module.names.append_function(1, "#UserApp_main (closure)");
+        module.export.append(Export {
+            name: "main",
+            ty: ExportType::Func,
+            index: 1,
+        });
+
+        let expected = "\
+(module
+  (type (;0;) (func))
+  (import \"env\" \"roc_panic\" (func $roc_panic (;0;) (type 0)))
+  (func $#UserApp_main__closure_ (;1;) (type 0)
+    call $roc_panic
+  )
+  (export \"main\" (func $#UserApp_main__closure_))
+)
+";
+        assert_eq!(round_trip(&module, &[]), expected);
+    }
+}
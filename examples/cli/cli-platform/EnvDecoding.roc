@@ -20,6 +20,7 @@ EnvFormat := {} has [
              list: envList,
              record: envRecord,
              tuple: envTuple,
+             tag: envTag,
          },
      ]
 
@@ -101,3 +102,10 @@ envRecord = \_initialState, _stepField, _finalizer -> Decode.custom \bytes, @Env
 envTuple : _, (_, _ -> [Next (Decoder _ _), TooLong]), (_ -> _) -> Decoder _ _
 envTuple = \_initialState, _stepElem, _finalizer -> Decode.custom \bytes, @EnvFormat {} ->
         { result: Err TooShort, rest: bytes }
+
+# TODO: we must currently annotate the arrows here so that the lambda sets are
+# exercised, and the solver can find an ambient lambda set for the
+# specialization.
+envTag : (_ -> [Payload (Decoder _ _), UnknownTag]) -> Decoder _ _
+envTag = \_stepTag -> Decode.custom \bytes, @EnvFormat {} ->
+        { result: Err TooShort, rest: bytes }